@@ -0,0 +1,347 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{Datelike, Local, Timelike};
+use std::fmt;
+use std::fs;
+use std::io::{self, BufReader, BufWriter, Read, Seek};
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, DateTime, ZipArchive, ZipWriter};
+use crate::integridade;
+use crate::paths::SubDir;
+use crate::types::ResultadoBackup;
+
+/// Acima de 4 GiB a entrada precisa das extensões ZIP64
+const LIMITE_ZIP32: u64 = u32::MAX as u64;
+
+/// Quantos arquivos em conflito aparecem na mensagem da restauração
+const MAX_CONFLITOS_LISTADOS: usize = 5;
+
+/// Backup recusado antes de qualquer escrita: ZIP fora da estrutura esperada ou dados que seriam sobrescritos
+///
+/// Reconhecido na cadeia do anyhow por `eh_backup_recusado`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupRecusado {
+    pub motivo: String,
+}
+
+impl fmt::Display for BackupRecusado {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.motivo)
+    }
+}
+
+impl std::error::Error for BackupRecusado {}
+
+/// O erro (ou alguma das causas) é uma recusa de backup/restauração, e não falha de disco
+pub fn eh_backup_recusado(erro: &anyhow::Error) -> bool {
+    erro.chain().any(|causa| causa.is::<BackupRecusado>())
+}
+
+fn recusar(motivo: String) -> anyhow::Error {
+    anyhow!(BackupRecusado { motivo })
+}
+
+/// Nome do ZIP quando o destino do backup é uma pasta: "licitacao360_backup_20241231_235959.zip"
+pub fn nome_arquivo_backup() -> String {
+    format!("licitacao360_backup_{}.zip", Local::now().format("%Y%m%d_%H%M%S"))
+}
+
+/// Arquivos das subpastas da Database, com o caminho usado no ZIP ("PDFs/edital.pdf")
+fn arquivos_da_database(raiz: &Path) -> Vec<(PathBuf, String)> {
+    let mut arquivos = Vec::new();
+    for subdir in SubDir::ALL {
+        let pasta = raiz.join(subdir.folder_name());
+        for entry in WalkDir::new(&pasta).sort_by_file_name().into_iter().filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let Ok(relativo) = entry.path().strip_prefix(raiz) else {
+                continue;
+            };
+            let nome = relativo.components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            arquivos.push((entry.into_path(), nome));
+        }
+    }
+    arquivos
+}
+
+/// Data de modificação do arquivo no formato do ZIP (horário local, de 1980 a 2107)
+fn data_modificacao(path: &Path) -> Option<DateTime> {
+    let modificado: chrono::DateTime<Local> = fs::metadata(path).and_then(|m| m.modified()).ok()?.into();
+    DateTime::from_date_and_time(
+        u16::try_from(modificado.year()).ok()?,
+        modificado.month() as u8,
+        modificado.day() as u8,
+        modificado.hour() as u8,
+        modificado.minute() as u8,
+        modificado.second() as u8,
+    ).ok()
+}
+
+/// Gera um ZIP com as pastas PDFs, Resultados, SICAF e Config de `raiz`
+///
+/// Cada arquivo é copiado em streaming, sem ser carregado na memória, para um temporário ao
+/// lado de `destino`, que só é renomeado no fim. `progresso` recebe (adicionados, total,
+/// caminho no ZIP) a cada arquivo.
+pub fn criar_backup(raiz: &Path, destino: &Path, mut progresso: impl FnMut(usize, usize, &str)) -> Result<ResultadoBackup> {
+    let arquivos = arquivos_da_database(raiz);
+    if arquivos.is_empty() {
+        return Err(recusar(format!("Nenhum arquivo encontrado em {} para o backup", raiz.display())));
+    }
+
+    if let Some(pasta) = destino.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(pasta)
+            .context(format!("Erro ao criar pasta de destino: {}", pasta.display()))?;
+    }
+    let nome = destino.file_name().unwrap_or_default().to_string_lossy();
+    let temporario = destino.with_file_name(format!(".{}.tmp", nome));
+
+    let resultado = gravar_zip(&arquivos, &temporario, &mut progresso)
+        .and_then(|()| integridade::substituir(&temporario, destino));
+    if resultado.is_err() {
+        let _ = fs::remove_file(&temporario);
+    }
+    resultado?;
+
+    Ok(ResultadoBackup {
+        caminho: destino.to_string_lossy().to_string(),
+        arquivos: arquivos.len(),
+        tamanho_bytes: fs::metadata(destino).map(|m| m.len()).unwrap_or(0),
+    })
+}
+
+fn gravar_zip(arquivos: &[(PathBuf, String)], temporario: &Path, progresso: &mut impl FnMut(usize, usize, &str)) -> Result<()> {
+    let arquivo = fs::File::create(temporario)
+        .context(format!("Erro ao criar arquivo temporário: {}", temporario.display()))?;
+    let mut zip = ZipWriter::new(BufWriter::new(arquivo));
+
+    for (indice, (path, nome)) in arquivos.iter().enumerate() {
+        let mut origem = fs::File::open(path)
+            .context(format!("Erro ao abrir {}", path.display()))?;
+        let tamanho = origem.metadata().map(|m| m.len()).unwrap_or(0);
+        // PDFs e ZIPs já vêm comprimidos: recomprimir só gasta tempo
+        let ja_comprimido = path.extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("pdf") || e.eq_ignore_ascii_case("zip"));
+        let opcoes = SimpleFileOptions::default()
+            .compression_method(if ja_comprimido { CompressionMethod::Stored } else { CompressionMethod::Deflated })
+            .large_file(tamanho >= LIMITE_ZIP32)
+            .last_modified_time(data_modificacao(path).unwrap_or_default());
+
+        zip.start_file(nome.as_str(), opcoes)
+            .context(format!("Erro ao adicionar {} ao ZIP", nome))?;
+        io::copy(&mut origem, &mut zip)
+            .context(format!("Erro ao compactar {}", path.display()))?;
+        progresso(indice + 1, arquivos.len(), nome);
+    }
+
+    let escritor = zip.finish().context("Erro ao finalizar o ZIP")?;
+    let arquivo = escritor.into_inner().map_err(|e| e.into_error())
+        .context(format!("Erro ao gravar {}", temporario.display()))?;
+    arquivo.sync_all()
+        .context(format!("Erro ao sincronizar {}", temporario.display()))
+}
+
+/// Entrada validada do ZIP: caminho relativo à Database e se é pasta
+struct EntradaBackup {
+    relativo: PathBuf,
+    pasta: bool,
+}
+
+/// Entradas do ZIP, na ordem do arquivo, validando a estrutura do backup
+///
+/// Toda entrada precisa ficar dentro de uma das subpastas da Database, sem "../" nem caminho absoluto.
+fn validar_estrutura<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Result<Vec<EntradaBackup>> {
+    let pastas: Vec<&str> = SubDir::ALL.iter().map(|s| s.folder_name()).collect();
+    let mut entradas = Vec::with_capacity(zip.len());
+
+    for indice in 0..zip.len() {
+        let entrada = zip.by_index_raw(indice).context("Erro ao ler o índice do ZIP")?;
+        let relativo = entrada.enclosed_name()
+            .filter(|relativo| relativo.components().all(|c| matches!(c, Component::Normal(_))));
+        let Some(relativo) = relativo else {
+            return Err(recusar(format!("Entrada com caminho inseguro no ZIP: {}", entrada.name())));
+        };
+        let primeira = relativo.components().next();
+        let dentro_da_database = matches!(primeira, Some(Component::Normal(pasta)) if pastas.iter().any(|p| pasta == *p));
+        if !dentro_da_database {
+            return Err(recusar(format!(
+                "O ZIP não é um backup da Database: {} está fora das pastas {}",
+                entrada.name(),
+                pastas.join(", "),
+            )));
+        }
+        entradas.push(EntradaBackup { relativo, pasta: entrada.is_dir() });
+    }
+
+    if entradas.iter().all(|entrada| entrada.pasta) {
+        return Err(recusar("O ZIP de backup não contém arquivos".to_string()));
+    }
+    Ok(entradas)
+}
+
+/// Restaura um ZIP gerado por `criar_backup` em `raiz`
+///
+/// A estrutura é validada antes de qualquer escrita. Sem `sobrescrever`, a restauração é recusada
+/// se algum arquivo do ZIP já existir; arquivos da Database ausentes do ZIP nunca são apagados.
+/// Cada arquivo é extraído em streaming para um temporário e renomeado sobre o destino.
+pub fn restaurar_backup(
+    zip_path: &Path,
+    raiz: &Path,
+    sobrescrever: bool,
+    mut progresso: impl FnMut(usize, usize, &str),
+) -> Result<ResultadoBackup> {
+    let arquivo = fs::File::open(zip_path)
+        .context(format!("Erro ao abrir {}", zip_path.display()))?;
+    let mut zip = ZipArchive::new(BufReader::new(arquivo))
+        .map_err(|e| recusar(format!("{} não é um ZIP válido: {}", zip_path.display(), e)))?;
+    let entradas = validar_estrutura(&mut zip)?;
+
+    if !sobrescrever {
+        let conflitos: Vec<String> = entradas.iter()
+            .filter(|entrada| !entrada.pasta && raiz.join(&entrada.relativo).exists())
+            .map(|entrada| entrada.relativo.to_string_lossy().to_string())
+            .collect();
+        if !conflitos.is_empty() {
+            return Err(recusar(format!(
+                "{} arquivo(s) do backup já existem na Database ({}); use a opção de sobrescrever",
+                conflitos.len(),
+                conflitos.iter().take(MAX_CONFLITOS_LISTADOS).cloned().collect::<Vec<_>>().join(", "),
+            )));
+        }
+    }
+
+    let total = entradas.iter().filter(|entrada| !entrada.pasta).count();
+    let mut extraidos = 0;
+    let mut tamanho_bytes = 0;
+    for (indice, EntradaBackup { relativo, pasta }) in entradas.iter().enumerate() {
+        let destino = raiz.join(relativo);
+        if *pasta {
+            fs::create_dir_all(&destino)
+                .context(format!("Erro ao criar pasta {}", destino.display()))?;
+            continue;
+        }
+
+        let mut entrada = zip.by_index(indice).context("Erro ao ler entrada do ZIP")?;
+        tamanho_bytes += extrair_entrada(&mut entrada, &destino)?;
+        extraidos += 1;
+        progresso(extraidos, total, entrada.name());
+    }
+
+    Ok(ResultadoBackup {
+        caminho: raiz.to_string_lossy().to_string(),
+        arquivos: extraidos,
+        tamanho_bytes,
+    })
+}
+
+fn extrair_entrada(entrada: &mut impl Read, destino: &Path) -> Result<u64> {
+    if let Some(pasta) = destino.parent() {
+        fs::create_dir_all(pasta)
+            .context(format!("Erro ao criar pasta {}", pasta.display()))?;
+    }
+    let nome = destino.file_name().unwrap_or_default().to_string_lossy();
+    let temporario = destino.with_file_name(format!(".{}.tmp", nome));
+
+    let resultado = (|| -> Result<u64> {
+        let mut arquivo = fs::File::create(&temporario)
+            .context(format!("Erro ao criar arquivo temporário: {}", temporario.display()))?;
+        let copiados = io::copy(entrada, &mut arquivo)
+            .context(format!("Erro ao extrair {}", destino.display()))?;
+        arquivo.sync_all()
+            .context(format!("Erro ao sincronizar {}", temporario.display()))?;
+        integridade::substituir(&temporario, destino)?;
+        Ok(copiados)
+    })();
+    if resultado.is_err() {
+        let _ = fs::remove_file(&temporario);
+    }
+    resultado
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn pasta_teste(nome: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("licitacao360_backup_{}_{}", nome, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    #[test]
+    fn test_backup_e_restauracao_preservam_a_database() {
+        let root = pasta_teste("ida_e_volta");
+        let origem = root.join("Database");
+        fs::create_dir_all(origem.join("PDFs/Processados")).unwrap();
+        fs::create_dir_all(origem.join("Resultados")).unwrap();
+        fs::create_dir_all(origem.join("Outros")).unwrap();
+        fs::write(origem.join("PDFs/Processados/ata.pdf"), b"%PDF-1.4 ata").unwrap();
+        fs::write(origem.join("Resultados/consolidado.json"), "{\"propostas\": []}").unwrap();
+        fs::write(origem.join("Outros/fora.txt"), "não entra no backup").unwrap();
+
+        let mut eventos = Vec::new();
+        let zip = root.join("backup/licitacao360.zip");
+        let backup = criar_backup(&origem, &zip, |feitos, total, nome| eventos.push((feitos, total, nome.to_string()))).unwrap();
+        assert_eq!(backup.arquivos, 2);
+        assert_eq!(backup.tamanho_bytes, fs::metadata(&zip).unwrap().len());
+        assert_eq!(eventos, [(1, 2, "PDFs/Processados/ata.pdf".to_string()), (2, 2, "Resultados/consolidado.json".to_string())]);
+        assert!(!root.join("backup/.licitacao360.zip.tmp").exists());
+
+        let destino = root.join("Restaurada");
+        let restaurado = restaurar_backup(&zip, &destino, false, |_, _, _| {}).unwrap();
+        assert_eq!(restaurado.arquivos, 2);
+        assert_eq!(fs::read(destino.join("PDFs/Processados/ata.pdf")).unwrap(), b"%PDF-1.4 ata");
+        assert_eq!(fs::read_to_string(destino.join("Resultados/consolidado.json")).unwrap(), "{\"propostas\": []}");
+        assert!(!destino.join("Outros").exists());
+
+        // Sem sobrescrever, dados existentes não são tocados
+        fs::write(destino.join("Resultados/consolidado.json"), "alterado").unwrap();
+        let erro = restaurar_backup(&zip, &destino, false, |_, _, _| {}).unwrap_err();
+        assert!(eh_backup_recusado(&erro), "{:#}", erro);
+        assert_eq!(fs::read_to_string(destino.join("Resultados/consolidado.json")).unwrap(), "alterado");
+
+        restaurar_backup(&zip, &destino, true, |_, _, _| {}).unwrap();
+        assert_eq!(fs::read_to_string(destino.join("Resultados/consolidado.json")).unwrap(), "{\"propostas\": []}");
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_restauracao_recusa_zip_fora_da_estrutura() {
+        let root = pasta_teste("estrutura");
+        let gravar = |nome: &str, entradas: &[&str]| {
+            let path = root.join(nome);
+            let mut zip = ZipWriter::new(fs::File::create(&path).unwrap());
+            for entrada in entradas {
+                zip.start_file(*entrada, SimpleFileOptions::default()).unwrap();
+                zip.write_all(b"x").unwrap();
+            }
+            zip.finish().unwrap();
+            path
+        };
+
+        let destino = root.join("Database");
+        for (nome, entradas) in [
+            ("fora.zip", &["PDFs/ata.pdf", "Documentos/x.txt"][..]),
+            ("traversal.zip", &["PDFs/../../fora.txt"][..]),
+            ("volta.zip", &["PDFs/../Documentos/x.txt"][..]),
+            ("vazio.zip", &[][..]),
+        ] {
+            let erro = restaurar_backup(&gravar(nome, entradas), &destino, true, |_, _, _| {}).unwrap_err();
+            assert!(eh_backup_recusado(&erro), "{}: {:#}", nome, erro);
+        }
+        // A validação acontece antes de extrair qualquer arquivo
+        assert!(!destino.exists());
+
+        fs::write(root.join("texto.zip"), "não é zip").unwrap();
+        assert!(eh_backup_recusado(&restaurar_backup(&root.join("texto.zip"), &destino, true, |_, _, _| {}).unwrap_err()));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,418 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::info;
+use crate::integridade;
+use crate::types::{EntradaCatalogoPreco, PropostaConsolidada, SugestaoPreco};
+
+/// Nome do arquivo do catálogo dentro da pasta de resultados
+pub const ARQUIVO_CATALOGO: &str = "catalogo_precos.json";
+
+/// Palavras ignoradas na chave normalizada da descrição
+const STOPWORDS: &[&str] = &[
+    "a", "o", "as", "os", "e", "ou", "de", "da", "do", "das", "dos", "em", "na", "no", "nas", "nos",
+    "para", "por", "com", "sem", "um", "uma", "tipo", "conforme", "item", "und", "unidade",
+];
+
+/// Observação de preço unitário extraída de um arquivo de resultado
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ObservacaoPreco {
+    pub chave: String,
+    pub descricao: String,
+    pub valor_unitario: f64,
+    pub licitacao: String,
+    pub data: String,
+}
+
+/// Manifesto de um arquivo de resultado já incorporado ao catálogo
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FonteCatalogo {
+    pub modified_timestamp: u64,
+    pub file_size: u64,
+    pub observacoes: Vec<ObservacaoPreco>,
+}
+
+/// Catálogo persistido em catalogo_precos.json
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CatalogoPrecos {
+    pub data_geracao: String,
+    pub total_entradas: usize,
+    pub entradas: Vec<EntradaCatalogoPreco>,
+    pub fontes: BTreeMap<String, FonteCatalogo>,
+}
+
+/// Resultado de uma construção incremental do catálogo
+#[derive(Debug, Default)]
+pub struct EstatisticasConstrucao {
+    pub arquivos_reprocessados: usize,
+    pub arquivos_inalterados: usize,
+    pub arquivos_removidos: usize,
+}
+
+/// Remove acentos dos caracteres mais comuns em português
+fn remover_acentos(c: char) -> char {
+    match c {
+        'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'í' | 'ì' | 'î' | 'ï' => 'i',
+        'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+        'ú' | 'ù' | 'û' | 'ü' => 'u',
+        'ç' => 'c',
+        _ => c,
+    }
+}
+
+/// Quebra uma descrição em tokens normalizados, sem stopwords e sem repetição
+pub fn tokenizar_descricao(descricao: &str) -> BTreeSet<String> {
+    descricao
+        .to_lowercase()
+        .chars()
+        .map(remover_acentos)
+        .collect::<String>()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty() && !STOPWORDS.contains(t))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Chave normalizada da descrição: tokens ordenados separados por espaço
+pub fn normalizar_chave(descricao: &str) -> String {
+    tokenizar_descricao(descricao)
+        .into_iter()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Percentil com interpolação linear sobre valores já ordenados
+fn percentil(valores_ordenados: &[f64], p: f64) -> f64 {
+    if valores_ordenados.is_empty() {
+        return 0.0;
+    }
+
+    let posicao = p * (valores_ordenados.len() - 1) as f64;
+    let inferior = posicao.floor() as usize;
+    let superior = posicao.ceil() as usize;
+    let fracao = posicao - inferior as f64;
+
+    valores_ordenados[inferior] + (valores_ordenados[superior] - valores_ordenados[inferior]) * fracao
+}
+
+/// Extrai as observações de preço de um JSON de licitação
+pub fn extrair_observacoes(json: &serde_json::Value) -> Vec<ObservacaoPreco> {
+    let data = json.get("data_geracao").and_then(|v| v.as_str()).unwrap_or("N/A").to_string();
+
+    let propostas: Vec<PropostaConsolidada> = match json.get("propostas").and_then(|p| p.as_array()) {
+        Some(propostas) => propostas.iter()
+            .filter_map(|p| serde_json::from_value(p.clone()).ok())
+            .collect(),
+        None => return Vec::new(),
+    };
+
+    propostas.iter().filter_map(|proposta| {
+        let valor_unitario = proposta.valor_adjudicado?.0;
+        let chave = normalizar_chave(&proposta.descricao);
+
+        if valor_unitario <= 0.0 || chave.is_empty() || proposta.descricao == "N/A" {
+            return None;
+        }
+
+        Some(ObservacaoPreco {
+            chave,
+            descricao: proposta.descricao.clone(),
+            valor_unitario,
+            licitacao: format!("{}-{}", proposta.uasg, proposta.pregao),
+            data: data.clone(),
+        })
+    }).collect()
+}
+
+/// Recalcula as entradas agregadas a partir das observações de todas as fontes
+pub fn agregar_entradas(fontes: &BTreeMap<String, FonteCatalogo>) -> Vec<EntradaCatalogoPreco> {
+    let mut grupos: HashMap<&str, Vec<&ObservacaoPreco>> = HashMap::new();
+
+    for fonte in fontes.values() {
+        for observacao in &fonte.observacoes {
+            grupos.entry(observacao.chave.as_str()).or_default().push(observacao);
+        }
+    }
+
+    let mut entradas: Vec<EntradaCatalogoPreco> = grupos.into_iter().map(|(chave, observacoes)| {
+        let mut valores: Vec<f64> = observacoes.iter().map(|o| o.valor_unitario).collect();
+        valores.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        let licitacoes: BTreeSet<String> = observacoes.iter().map(|o| o.licitacao.clone()).collect();
+        let mais_recente = observacoes.iter()
+            .max_by(|a, b| a.data.cmp(&b.data))
+            .expect("grupo sempre tem ao menos uma observação");
+
+        EntradaCatalogoPreco {
+            chave: chave.to_string(),
+            descricao_exemplo: mais_recente.descricao.clone(),
+            total_ocorrencias: valores.len(),
+            mediana: percentil(&valores, 0.5),
+            p25: percentil(&valores, 0.25),
+            p75: percentil(&valores, 0.75),
+            ultima_ocorrencia: mais_recente.data.clone(),
+            licitacoes: licitacoes.into_iter().collect(),
+        }
+    }).collect();
+
+    entradas.sort_by(|a, b| a.chave.cmp(&b.chave));
+    entradas
+}
+
+/// Constrói o catálogo reaproveitando as fontes inalteradas do catálogo anterior
+pub fn construir_catalogo(
+    arquivos: &[PathBuf],
+    anterior: Option<CatalogoPrecos>,
+    verbose: bool,
+) -> (CatalogoPrecos, EstatisticasConstrucao) {
+    let mut fontes_anteriores = anterior.map(|c| c.fontes).unwrap_or_default();
+    let mut fontes: BTreeMap<String, FonteCatalogo> = BTreeMap::new();
+    let mut estatisticas = EstatisticasConstrucao::default();
+
+    for arquivo in arquivos {
+        let chave_arquivo = arquivo.to_string_lossy().to_string();
+        let metadata = match fs::metadata(arquivo) {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+
+        let modified_timestamp = metadata.modified()
+            .ok()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        let file_size = metadata.len();
+
+        // Fonte inalterada: reaproveitar observações já extraídas
+        if let Some(fonte) = fontes_anteriores.remove(&chave_arquivo) {
+            if fonte.modified_timestamp == modified_timestamp && fonte.file_size == file_size {
+                fontes.insert(chave_arquivo, fonte);
+                estatisticas.arquivos_inalterados += 1;
+                continue;
+            }
+        }
+
+        let observacoes = fs::read_to_string(arquivo)
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .map(|json| extrair_observacoes(&json))
+            .unwrap_or_default();
+
+        if verbose {
+            info!(arquivo = %arquivo.display(), observacoes = observacoes.len(), "Catálogo: observações de preço lidas");
+        }
+
+        fontes.insert(chave_arquivo, FonteCatalogo {
+            modified_timestamp,
+            file_size,
+            observacoes,
+        });
+        estatisticas.arquivos_reprocessados += 1;
+    }
+
+    // O que sobrou do catálogo anterior não existe mais
+    estatisticas.arquivos_removidos = fontes_anteriores.len();
+
+    let entradas = agregar_entradas(&fontes);
+    let catalogo = CatalogoPrecos {
+        data_geracao: Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        total_entradas: entradas.len(),
+        entradas,
+        fontes,
+    };
+
+    (catalogo, estatisticas)
+}
+
+/// Sugere preços de referência para uma descrição, ordenados por sobreposição de tokens
+pub fn sugerir_precos(catalogo: &CatalogoPrecos, descricao: &str, limite: usize) -> Vec<SugestaoPreco> {
+    let tokens_busca = tokenizar_descricao(descricao);
+    if tokens_busca.is_empty() {
+        return Vec::new();
+    }
+
+    let mut sugestoes: Vec<SugestaoPreco> = catalogo.entradas.iter().filter_map(|entrada| {
+        let tokens_entrada: BTreeSet<String> = entrada.chave.split(' ').map(str::to_string).collect();
+        let intersecao = tokens_busca.intersection(&tokens_entrada).count();
+
+        if intersecao == 0 {
+            return None;
+        }
+
+        // Similaridade de Jaccard entre os conjuntos de tokens
+        let uniao = tokens_busca.union(&tokens_entrada).count();
+        Some(SugestaoPreco {
+            pontuacao: intersecao as f64 / uniao as f64,
+            entrada: entrada.clone(),
+        })
+    }).collect();
+
+    sugestoes.sort_by(|a, b| {
+        b.pontuacao.partial_cmp(&a.pontuacao)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.entrada.total_ocorrencias.cmp(&a.entrada.total_ocorrencias))
+            .then(a.entrada.chave.cmp(&b.entrada.chave))
+    });
+    sugestoes.truncate(limite);
+    sugestoes
+}
+
+/// Carrega o catálogo salvo, se existir
+pub fn carregar_catalogo(output_dir: &Path) -> Result<Option<CatalogoPrecos>> {
+    let path = output_dir.join(ARQUIVO_CATALOGO);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&path)
+        .context("Erro ao ler catálogo de preços")?;
+    let catalogo = serde_json::from_str(&content)
+        .context("Erro ao parsear catálogo de preços")?;
+
+    Ok(Some(catalogo))
+}
+
+/// Salva o catálogo em catalogo_precos.json
+pub fn salvar_catalogo(catalogo: &CatalogoPrecos, output_dir: &Path) -> Result<PathBuf> {
+    let path = output_dir.join(ARQUIVO_CATALOGO);
+    let content = serde_json::to_string_pretty(catalogo)
+        .context("Erro ao serializar catálogo de preços")?;
+
+    integridade::gravar_artefato(output_dir, &path, content.as_bytes(), None)
+        .context("Erro ao salvar catálogo de preços")?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observacao(descricao: &str, valor: f64, licitacao: &str, data: &str) -> ObservacaoPreco {
+        ObservacaoPreco {
+            chave: normalizar_chave(descricao),
+            descricao: descricao.to_string(),
+            valor_unitario: valor,
+            licitacao: licitacao.to_string(),
+            data: data.to_string(),
+        }
+    }
+
+    fn catalogo(observacoes: Vec<ObservacaoPreco>) -> CatalogoPrecos {
+        let mut fontes = BTreeMap::new();
+        fontes.insert("licitacao_teste.json".to_string(), FonteCatalogo {
+            modified_timestamp: 0,
+            file_size: 0,
+            observacoes,
+        });
+        let entradas = agregar_entradas(&fontes);
+        CatalogoPrecos {
+            data_geracao: String::new(),
+            total_entradas: entradas.len(),
+            entradas,
+            fontes,
+        }
+    }
+
+    #[test]
+    fn test_normalizar_chave_colisoes() {
+        // Ordem, caixa, acentos e stopwords não mudam a chave
+        assert_eq!(
+            normalizar_chave("Caneta esferográfica AZUL"),
+            normalizar_chave("caneta, azul, de tipo esferografica")
+        );
+        assert_eq!(normalizar_chave("Papel A4 - 500 folhas"), "500 a4 folhas papel");
+
+        // Tokens relevantes diferentes geram chaves diferentes
+        assert_ne!(normalizar_chave("Papel A4"), normalizar_chave("Papel A3"));
+        assert_ne!(normalizar_chave("Caneta azul"), normalizar_chave("Caneta azul ponta fina"));
+
+        // Descrição só com stopwords não gera chave
+        assert_eq!(normalizar_chave("de da do"), "");
+    }
+
+    #[test]
+    fn test_agregar_estatisticas() {
+        let catalogo = catalogo(vec![
+            observacao("Caneta azul", 1.0, "787000-1/2024", "2024-01-10 10:00:00 UTC"),
+            observacao("caneta AZUL", 2.0, "787000-2/2024", "2024-03-10 10:00:00 UTC"),
+            observacao("Azul caneta", 3.0, "787000-1/2024", "2024-02-10 10:00:00 UTC"),
+            observacao("Caneta azul", 4.0, "160000-5/2023", "2023-12-01 10:00:00 UTC"),
+        ]);
+
+        assert_eq!(catalogo.entradas.len(), 1);
+        let entrada = &catalogo.entradas[0];
+        assert_eq!(entrada.total_ocorrencias, 4);
+        assert!((entrada.mediana - 2.5).abs() < 1e-9);
+        assert!((entrada.p25 - 1.75).abs() < 1e-9);
+        assert!((entrada.p75 - 3.25).abs() < 1e-9);
+        assert_eq!(entrada.ultima_ocorrencia, "2024-03-10 10:00:00 UTC");
+        assert_eq!(entrada.descricao_exemplo, "caneta AZUL");
+        assert_eq!(entrada.licitacoes, vec!["160000-5/2023", "787000-1/2024", "787000-2/2024"]);
+    }
+
+    #[test]
+    fn test_sugerir_precos_ordenacao() {
+        let catalogo = catalogo(vec![
+            observacao("Caneta esferográfica azul", 1.5, "1-1/2024", "2024-01-01"),
+            observacao("Caneta esferográfica azul", 1.7, "1-2/2024", "2024-01-02"),
+            observacao("Caneta marca-texto amarela", 3.0, "1-1/2024", "2024-01-01"),
+            observacao("Papel sulfite A4", 25.0, "1-1/2024", "2024-01-01"),
+        ]);
+
+        let sugestoes = sugerir_precos(&catalogo, "caneta azul esferografica", 10);
+        assert_eq!(sugestoes.len(), 2);
+        assert_eq!(sugestoes[0].entrada.chave, "azul caneta esferografica");
+        assert!((sugestoes[0].pontuacao - 1.0).abs() < 1e-9);
+        assert_eq!(sugestoes[1].entrada.chave, "amarela caneta marca texto");
+        assert!(sugestoes[0].pontuacao > sugestoes[1].pontuacao);
+
+        // Limite e busca sem tokens relevantes
+        assert_eq!(sugerir_precos(&catalogo, "caneta", 1).len(), 1);
+        assert!(sugerir_precos(&catalogo, "grampeador", 10).is_empty());
+        assert!(sugerir_precos(&catalogo, "de para com", 10).is_empty());
+    }
+
+    #[test]
+    fn test_construir_catalogo_incremental() {
+        let root = std::env::temp_dir().join(format!("licitacao360_catalogo_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+
+        let proposta = serde_json::json!({
+            "uasg": "787000", "pregao": "90008/2024", "processo": "1", "item": "1", "grupo": null,
+            "quantidade": "10", "descricao": "Caneta azul", "valor_estimado": "2,00",
+            "valor_adjudicado": "1,50", "fornecedor": "EMPRESA", "cnpj": "00.000.000/0001-00",
+            "marca_fabricante": "N/A", "modelo_versao": "N/A", "responsavel": "N/A",
+            "melhor_lance": "1,50", "tipo_formato": "individual"
+        });
+        let arquivo = root.join("licitacao_teste.json");
+        fs::write(&arquivo, serde_json::json!({
+            "data_geracao": "2024-05-01 10:00:00 UTC",
+            "propostas": [proposta]
+        }).to_string()).unwrap();
+
+        let arquivos = vec![arquivo.clone()];
+        let (primeiro, estatisticas) = construir_catalogo(&arquivos, None, false);
+        assert_eq!(estatisticas.arquivos_reprocessados, 1);
+        assert_eq!(primeiro.total_entradas, 1);
+
+        // Segunda construção reaproveita o arquivo inalterado
+        let (segundo, estatisticas) = construir_catalogo(&arquivos, Some(primeiro), false);
+        assert_eq!(estatisticas.arquivos_reprocessados, 0);
+        assert_eq!(estatisticas.arquivos_inalterados, 1);
+        assert_eq!(segundo.total_entradas, 1);
+
+        // Arquivo removido some do catálogo
+        let (terceiro, estatisticas) = construir_catalogo(&[], Some(segundo), false);
+        assert_eq!(estatisticas.arquivos_removidos, 1);
+        assert_eq!(terceiro.total_entradas, 0);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use chrono::Utc;
+use tauri::{AppHandle, Emitter};
+use crate::types::{ProgressEvent, ResultadoBackup, TauriError};
+use crate::{backup, paths};
+
+/// Evento com o progresso de `backup_database` e `restore_database` (payload `ProgressEvent`)
+pub const EVENTO_BACKUP: &str = "backup://progress";
+
+/// Emite o progresso de um backup ou restauração: arquivos processados / total
+fn emissor_progresso(app: AppHandle, session_id: String) -> impl FnMut(usize, usize, &str) {
+    let inicio = Instant::now();
+    move |processed, total, arquivo| {
+        let percentage = if total > 0 { (processed as f64 / total as f64) * 100.0 } else { 0.0 };
+        let _ = app.emit(EVENTO_BACKUP, ProgressEvent {
+            session_id: session_id.clone(),
+            processed,
+            total,
+            current_file: Some(arquivo.to_string()),
+            percentage,
+            elapsed_ms: inicio.elapsed().as_millis() as u64,
+        });
+    }
+}
+
+fn erro_backup(erro: anyhow::Error, caminho: &Path) -> TauriError {
+    let details = caminho.to_string_lossy().to_string();
+    if backup::eh_backup_recusado(&erro) {
+        TauriError::validation(format!("{:#}", erro), details)
+    } else {
+        TauriError::filesystem(format!("{:#}", erro), details)
+    }
+}
+
+/// Gera um ZIP com as pastas PDFs, Resultados, SICAF e Config da Database
+///
+/// `destination_path` pode ser o arquivo .zip ou uma pasta, onde o ZIP recebe um nome com data e hora.
+/// O progresso é emitido em `backup://progress`.
+#[tauri::command]
+pub async fn backup_database(destination_path: String, app: AppHandle) -> Result<ResultadoBackup, TauriError> {
+    let destino = PathBuf::from(destination_path.trim());
+    let destino = if destino.is_dir() {
+        destino.join(backup::nome_arquivo_backup())
+    } else if destino.extension().is_some_and(|e| e.eq_ignore_ascii_case("zip")) {
+        destino
+    } else {
+        return Err(TauriError::validation(
+            "O destino do backup deve ser uma pasta ou um arquivo .zip",
+            destination_path,
+        ));
+    };
+
+    let raiz = paths::resolve_database_root()?;
+    let progresso = emissor_progresso(app, format!("backup_{}", Utc::now().timestamp_millis()));
+    let caminho = destino.clone();
+    tokio::task::spawn_blocking(move || backup::criar_backup(&raiz, &destino, progresso))
+        .await
+        .map_err(|e| TauriError::system(format!("Erro ao executar o backup: {}", e), None))?
+        .map_err(|e| erro_backup(e, &caminho))
+}
+
+/// Restaura na Database um ZIP gerado por `backup_database`
+///
+/// A estrutura do ZIP é validada antes de extrair. Sem `overwrite`, a restauração é recusada
+/// se algum arquivo do backup já existir. O progresso é emitido em `backup://progress`.
+#[tauri::command]
+pub async fn restore_database(zip_path: String, overwrite: bool, app: AppHandle) -> Result<ResultadoBackup, TauriError> {
+    let origem = PathBuf::from(&zip_path);
+    if !origem.is_file() {
+        return Err(TauriError::not_found(format!("Arquivo de backup não encontrado: {}", zip_path), zip_path));
+    }
+
+    let raiz = paths::resolve_database_root()?;
+    let progresso = emissor_progresso(app, format!("restore_{}", Utc::now().timestamp_millis()));
+    tokio::task::spawn_blocking(move || backup::restaurar_backup(&origem, &raiz, overwrite, progresso))
+        .await
+        .map_err(|e| TauriError::system(format!("Erro ao executar a restauração: {}", e), None))?
+        .map_err(|e| erro_backup(e, Path::new(&zip_path)))
+}
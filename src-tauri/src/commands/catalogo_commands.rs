@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+use tauri::State;
+use crate::types::{TauriError, SugestaoPreco};
+use crate::{catalogo_precos, config, resultados};
+use crate::commands::directory_commands::get_output_directory;
+
+/// Constrói (incrementalmente) o catálogo de preços unitários a partir dos resultados
+#[tauri::command]
+pub async fn build_price_catalog(verbose: Option<bool>, config_state: State<'_, config::ConfigState>) -> Result<serde_json::Value, TauriError> {
+    let output_dir = get_output_directory().await?;
+    let output_path = PathBuf::from(&output_dir);
+
+    // Um catálogo corrompido é simplesmente reconstruído do zero
+    let anterior = catalogo_precos::carregar_catalogo(&output_path).ok().flatten();
+    let arquivos = resultados::listar_arquivos_licitacao(
+        &resultados::diretorios_resultados(&output_path, &config::load_routing_rules(&config_state))
+    );
+
+    let (catalogo, estatisticas) = catalogo_precos::construir_catalogo(&arquivos, anterior, verbose.unwrap_or(false));
+
+    let catalogo_path = catalogo_precos::salvar_catalogo(&catalogo, &output_path)
+        .map_err(|e| TauriError::processing(format!("Erro ao salvar catálogo de preços: {}", e), output_dir.clone()))?;
+
+    Ok(serde_json::json!({
+        "catalogo_path": catalogo_path.to_string_lossy(),
+        "data_geracao": catalogo.data_geracao,
+        "total_entradas": catalogo.total_entradas,
+        "arquivos_reprocessados": estatisticas.arquivos_reprocessados,
+        "arquivos_inalterados": estatisticas.arquivos_inalterados,
+        "arquivos_removidos": estatisticas.arquivos_removidos
+    }))
+}
+
+/// Sugere preços de referência para uma descrição a partir do catálogo
+#[tauri::command]
+pub async fn suggest_price(descricao: String, limite: Option<usize>) -> Result<Vec<SugestaoPreco>, TauriError> {
+    let output_dir = get_output_directory().await?;
+    let output_path = PathBuf::from(&output_dir);
+
+    let catalogo = catalogo_precos::carregar_catalogo(&output_path)
+        .map_err(|e| TauriError::parse(
+            format!("Erro ao carregar catálogo de preços: {}", e),
+            output_path.join(catalogo_precos::ARQUIVO_CATALOGO).to_string_lossy().to_string(),
+        ))?
+        .ok_or_else(|| TauriError::not_found("Catálogo de preços ainda não foi gerado", output_dir.clone()))?;
+
+    Ok(catalogo_precos::sugerir_precos(&catalogo, &descricao, limite.unwrap_or(10)))
+}
@@ -1,133 +1,218 @@
 use std::path::PathBuf;
 use crate::types::*;
 use crate::config;
+use crate::i18n::Idioma;
+use crate::integridade;
+use crate::routing;
+use crate::logs;
+use crate::rastreamento;
 use chrono::Utc;
+use tauri::State;
 
 /// Carrega a configuração da aplicação
 #[tauri::command]
-pub async fn load_app_config() -> Result<AppConfig, TauriError> {
-    config::load_config()
+pub async fn load_app_config(config_state: State<'_, config::ConfigState>) -> Result<AppConfig, TauriError> {
+    Ok(config::ler_config(&config_state))
 }
 
 /// Salva a configuração da aplicação
 #[tauri::command]
-pub async fn save_app_config(config: AppConfig) -> Result<ConfigResult, TauriError> {
-    match config::save_config(&config) {
-        Ok(_) => Ok(ConfigResult {
-            success: true,
-            message: "Configuração salva com sucesso".to_string(),
-            config: Some(config),
-        }),
-        Err(e) => Err(e),
+pub async fn save_app_config(config: AppConfig, config_state: State<'_, config::ConfigState>) -> Result<ConfigResult, TauriError> {
+    // Validar regras de roteamento antes de persistir
+    if let Err(e) = routing::validar_regras(&config.routing_rules) {
+        return Err(TauriError::validation(format!("Regras de roteamento inválidas: {}", e), None));
     }
+    
+    let config = config::atualizar_config(&config_state, |atual| {
+        *atual = config;
+        Ok(())
+    })?;
+    
+    Ok(ConfigResult {
+        success: true,
+        message: "Configuração salva com sucesso".to_string(),
+        config: Some(config),
+    })
 }
 
 /// Atualiza os diretórios de entrada e saída
 #[tauri::command]
 pub async fn update_config_directories(
     input_dir: Option<String>,
-    output_dir: Option<String>
+    output_dir: Option<String>,
+    config_state: State<'_, config::ConfigState>
 ) -> Result<ConfigResult, TauriError> {
-    let mut config = config::load_config()?;
+    let config = config::atualizar_config(&config_state, |config| {
+        if let Some(dir) = input_dir {
+            config.last_input_directory = Some(dir);
+        }
+        if let Some(dir) = output_dir {
+            config.last_output_directory = Some(dir);
+        }
+        Ok(())
+    })?;
     
-    if let Some(dir) = input_dir {
-        config.last_input_directory = Some(dir);
-    }
+    Ok(ConfigResult {
+        success: true,
+        message: "Diretórios atualizados com sucesso".to_string(),
+        config: Some(config),
+    })
+}
+
+/// Relê o arquivo de configuração, descartando o estado em memória (após edição manual)
+#[tauri::command]
+pub async fn reload_config_from_disk(config_state: State<'_, config::ConfigState>) -> Result<ConfigResult, TauriError> {
+    let config = config::recarregar_config(&config_state)?;
     
-    if let Some(dir) = output_dir {
-        config.last_output_directory = Some(dir);
-    }
+    Ok(ConfigResult {
+        success: true,
+        message: "Configuração recarregada do disco".to_string(),
+        config: Some(config),
+    })
+}
+
+/// Informa qual regra de roteamento seria aplicada a uma licitação
+#[tauri::command]
+pub async fn test_routing(uasg: String, pregao: String, config_state: State<'_, config::ConfigState>) -> Result<serde_json::Value, TauriError> {
+    use crate::commands::directory_commands::get_output_directory;
     
-    config.updated_at = Utc::now().to_rfc3339();
+    let config = config::ler_config(&config_state);
+    let output_dir = get_output_directory().await?;
     
-    match config::save_config(&config) {
-        Ok(_) => Ok(ConfigResult {
-            success: true,
-            message: "Diretórios atualizados com sucesso".to_string(),
-            config: Some(config),
+    let resultado = match routing::encontrar_regra(&config.routing_rules, &uasg, &pregao) {
+        Some((index, regra)) => serde_json::json!({
+            "uasg": uasg,
+            "pregao": pregao,
+            "regra_aplicada": true,
+            "regra_index": index,
+            "regra": regra,
+            "diretorio_destino": regra.target_directory
         }),
-        Err(e) => Err(e),
-    }
+        None => serde_json::json!({
+            "uasg": uasg,
+            "pregao": pregao,
+            "regra_aplicada": false,
+            "regra_index": null,
+            "regra": null,
+            "diretorio_destino": output_dir
+        }),
+    };
+    
+    Ok(resultado)
 }
 
-/// Adiciona um log ao histórico de processamento
+/// Adiciona um log ao histórico de processamento (arquivo JSON Lines do dia)
 #[tauri::command]
 pub async fn add_config_log(
     message: String,
     log_type: String,
     session_id: Option<String>
 ) -> Result<ConfigResult, TauriError> {
-    let mut config = config::load_config()?;
+    let log_entry = logs::novo_log(message, log_type, session_id);
+    config::append_processing_log(log_entry)?;
     
-    let log_entry = ProcessingLog {
-        timestamp: Utc::now().to_rfc3339(),
-        message,
-        log_type,
-        session_id,
+    Ok(ConfigResult {
+        success: true,
+        message: "Log adicionado com sucesso".to_string(),
+        config: None,
+    })
+}
+
+/// Limites de exibição derivados do `max_logs` da configuração
+fn limites_logs(config_state: &config::ConfigState) -> logs::LimitesLogs {
+    logs::LimitesLogs::para_max_logs(config::ler_config(config_state).max_logs)
+}
+
+/// Obtém os logs de processamento, com os resumos de progresso compactados à parte
+#[tauri::command]
+pub async fn get_processing_logs(
+    session_id: Option<String>,
+    config_state: State<'_, config::ConfigState>
+) -> Result<ProcessingLogsView, TauriError> {
+    let pasta = config::get_logs_dir()?;
+    let entradas = logs::ler_logs(&pasta, None)
+        .map_err(|e| TauriError::filesystem(format!("Erro ao ler logs: {:#}", e), pasta.to_string_lossy().to_string()))?;
+    
+    let logs_filtrados: Vec<ProcessingLog> = match session_id {
+        Some(session_id) => entradas.into_iter()
+            .filter(|log| log.session_id.as_deref() == Some(session_id.as_str()))
+            .collect(),
+        None => entradas,
     };
     
-    config.processing_logs.push(log_entry);
+    Ok(logs::visao_logs(logs_filtrados, &limites_logs(&config_state)))
+}
+
+/// Obtém os logs de um dia (AAAA-MM-DD; todos os dias se omitido), opcionalmente só de um nível
+///
+/// `limit` mantém apenas as entradas mais recentes.
+#[tauri::command]
+pub async fn get_logs(
+    date: Option<String>,
+    limit: Option<usize>,
+    level: Option<String>,
+    config_state: State<'_, config::ConfigState>
+) -> Result<ProcessingLogsView, TauriError> {
+    let data = match date.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+        Some(data) => Some(chrono::NaiveDate::parse_from_str(data, "%Y-%m-%d")
+            .map_err(|_| TauriError::validation(format!("Data inválida: {} (use AAAA-MM-DD)", data), data.to_string()))?),
+        None => None,
+    };
     
-    // Manter apenas os últimos logs
-    if config.processing_logs.len() > config.max_logs {
-        let total_logs = config.processing_logs.len();
-        config.processing_logs = config.processing_logs
-            .into_iter()
-            .skip(total_logs - config.max_logs)
-            .collect();
+    let pasta = config::get_logs_dir()?;
+    let mut entradas = logs::ler_logs(&pasta, data)
+        .map_err(|e| TauriError::filesystem(format!("Erro ao ler logs: {:#}", e), pasta.to_string_lossy().to_string()))?;
+    if let Some(level) = level.as_deref().filter(|l| !l.is_empty()) {
+        entradas.retain(|log| log.log_type == level);
     }
-    
-    config.updated_at = Utc::now().to_rfc3339();
-    
-    match config::save_config(&config) {
-        Ok(_) => Ok(ConfigResult {
-            success: true,
-            message: "Log adicionado com sucesso".to_string(),
-            config: Some(config),
-        }),
-        Err(e) => Err(e),
+    if let Some(limit) = limit {
+        entradas.drain(..entradas.len().saturating_sub(limit));
     }
+    
+    Ok(logs::visao_logs(entradas, &limites_logs(&config_state)))
 }
 
 /// Limpa o histórico de logs
 #[tauri::command]
 pub async fn clear_config_logs() -> Result<ConfigResult, TauriError> {
-    let mut config = config::load_config()?;
-    
-    config.processing_logs.clear();
-    config.updated_at = Utc::now().to_rfc3339();
+    let pasta = config::get_logs_dir()?;
+    let removidos = logs::limpar_logs(&pasta)
+        .map_err(|e| TauriError::filesystem(format!("Erro ao limpar logs: {:#}", e), pasta.to_string_lossy().to_string()))?;
     
-    match config::save_config(&config) {
-        Ok(_) => Ok(ConfigResult {
-            success: true,
-            message: "Histórico de logs limpo com sucesso".to_string(),
-            config: Some(config),
-        }),
-        Err(e) => Err(e),
-    }
+    Ok(ConfigResult {
+        success: true,
+        message: format!("Histórico de logs limpo com sucesso ({} arquivos removidos)", removidos),
+        config: None,
+    })
+}
+
+/// Últimas `n` linhas do trace do pipeline (Database/Config/logs/trace.log)
+#[tauri::command]
+pub async fn get_recent_trace_lines(n: usize) -> Result<Vec<String>, TauriError> {
+    let pasta = config::get_logs_dir()?;
+    rastreamento::linhas_recentes(&pasta, n)
+        .map_err(|e| TauriError::filesystem(format!("Erro ao ler o trace: {:#}", e), pasta.to_string_lossy().to_string()))
 }
 
 /// Atualiza configuração verbose
 #[tauri::command]
-pub async fn update_config_verbose(verbose: bool) -> Result<ConfigResult, TauriError> {
-    let mut config = config::load_config()?;
+pub async fn update_config_verbose(verbose: bool, config_state: State<'_, config::ConfigState>) -> Result<ConfigResult, TauriError> {
+    let config = config::atualizar_config(&config_state, |config| {
+        config.verbose = verbose;
+        Ok(())
+    })?;
+    rastreamento::definir_verbose(verbose);
     
-    config.verbose = verbose;
-    config.updated_at = Utc::now().to_rfc3339();
-    
-    match config::save_config(&config) {
-        Ok(_) => Ok(ConfigResult {
-            success: true,
-            message: format!("Configuração verbose atualizada para: {}", verbose),
-            config: Some(config),
-        }),
-        Err(e) => Err(e),
-    }
+    Ok(ConfigResult {
+        success: true,
+        message: format!("Configuração verbose atualizada para: {}", verbose),
+        config: Some(config),
+    })
 }
 
 /// Debug e reparo do arquivo de configuração
 #[tauri::command]
-pub async fn debug_and_repair_config() -> Result<ConfigResult, TauriError> {
+pub async fn debug_and_repair_config(config_state: State<'_, config::ConfigState>) -> Result<ConfigResult, TauriError> {
     use std::path::PathBuf;
     
     let mut debug_info = String::new();
@@ -182,11 +267,10 @@ pub async fn debug_and_repair_config() -> Result<ConfigResult, TauriError> {
         // Criar diretório se não existir
         if let Err(e) = std::fs::create_dir_all(&config_dir) {
             debug_info.push_str(&format!("❌ Erro ao criar diretório: {}\n", e));
-            return Err(TauriError {
-                error_type: "FileSystemError".to_string(),
-                message: format!("Erro ao criar diretório de configuração: {}", e),
-                details: Some(config_dir.to_string_lossy().to_string()),
-            });
+            return Err(TauriError::filesystem(
+                format!("Erro ao criar diretório de configuração: {}", e),
+                config_dir.to_string_lossy().to_string(),
+            ));
         }
         
         create_new_config_with_backup(&config_path, &mut debug_info);
@@ -194,16 +278,17 @@ pub async fn debug_and_repair_config() -> Result<ConfigResult, TauriError> {
     
     debug_info.push_str("\n=== REPARO CONCLUÍDO ===\n");
     
+    // O arquivo pode ter sido recriado: o estado em memória passa a refletir o disco
     Ok(ConfigResult {
         success: true,
         message: debug_info,
-        config: config::load_config().ok(),
+        config: config::recarregar_config(&config_state).ok(),
     })
 }
 
 /// Inicializa a aplicação criando diretórios padrão e configuração
 #[tauri::command]
-pub async fn initialize_application() -> Result<ConfigResult, TauriError> {
+pub async fn initialize_application(config_state: State<'_, config::ConfigState>) -> Result<ConfigResult, TauriError> {
     use crate::commands::directory_commands::{get_config_directory, get_pdf_directory, get_output_directory};
     
     // Garantir que os diretórios existem
@@ -212,13 +297,14 @@ pub async fn initialize_application() -> Result<ConfigResult, TauriError> {
     let _output_dir = get_output_directory().await?;
     
     // Carregar ou criar configuração
-    let config = match config::load_config() {
+    let config = match config::recarregar_config(&config_state) {
         Ok(config) => config,
         Err(_) => {
             // Criar configuração padrão se não existir
-            let default_config = config::create_default_config();
-            config::save_config(&default_config)?;
-            default_config
+            config::atualizar_config(&config_state, |config| {
+                *config = config::create_default_config();
+                Ok(())
+            })?
         }
     };
     
@@ -285,11 +371,7 @@ pub async fn ensure_directory_exists(path: String) -> Result<bool, TauriError> {
     
     match std::fs::create_dir_all(&path_buf) {
         Ok(_) => Ok(true),
-        Err(e) => Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao criar diretório: {}", e),
-            details: Some(path),
-        })
+        Err(e) => Err(TauriError::filesystem(format!("Erro ao criar diretório: {}", e), path))
     }
 }
 
@@ -298,24 +380,20 @@ pub async fn ensure_directory_exists(path: String) -> Result<bool, TauriError> {
 pub async fn get_user_home_directory() -> Result<String, TauriError> {
     match dirs::home_dir() {
         Some(path) => Ok(path.to_string_lossy().to_string()),
-        None => Err(TauriError {
-            error_type: "SystemError".to_string(),
-            message: "Não foi possível obter o diretório home do usuário".to_string(),
-            details: None,
-        })
+        None => Err(TauriError::system("Não foi possível obter o diretório home do usuário", None))
     }
 }
 
 /// Atualiza o diretório PDF na configuração
 #[tauri::command]
-pub async fn update_pdf_directory(path: String) -> Result<ConfigResult, TauriError> {
-    update_config_directories(Some(path), None).await
+pub async fn update_pdf_directory(path: String, config_state: State<'_, config::ConfigState>) -> Result<ConfigResult, TauriError> {
+    update_config_directories(Some(path), None, config_state).await
 }
 
 /// Atualiza o diretório de saída na configuração
 #[tauri::command]
-pub async fn update_output_directory(path: String) -> Result<ConfigResult, TauriError> {
-    update_config_directories(None, Some(path)).await
+pub async fn update_output_directory(path: String, config_state: State<'_, config::ConfigState>) -> Result<ConfigResult, TauriError> {
+    update_config_directories(None, Some(path), config_state).await
 }
 
 fn create_new_config_with_backup(config_path: &PathBuf, debug_info: &mut String) {
@@ -338,21 +416,33 @@ fn create_new_config_with_backup(config_path: &PathBuf, debug_info: &mut String)
         max_logs: 1000,
         created_at: Utc::now().to_rfc3339(),
         updated_at: Utc::now().to_rfc3339(),
+        routing_rules: Vec::new(),
+        max_parallel_jobs: 0,
+        http_api_bind_address: None,
+        ocr_enabled: false,
+        session_retention_minutes: RETENCAO_SESSOES_PADRAO,
+        archive_processed: false,
+        database_root: None,
+        pdf_extraction_timeout_secs: TIMEOUT_EXTRACAO_PADRAO_SECS,
+        max_import_size_mb: TAMANHO_IMPORTACAO_PADRAO_MB,
+        sicaf_name_match_threshold: LIMIAR_RAZAO_SOCIAL_PADRAO,
+        retention_policy: None,
+        language: Idioma::PtBr,
+        scan_max_depth: PROFUNDIDADE_VARREDURA_PADRAO,
+        scan_exclude_patterns: padroes_exclusao_padrao(),
+        clipboard_max_rows: LIMITE_LINHAS_TSV_PADRAO,
+        processed_bytes_total: 0,
+        processing_seconds_total: 0.0,
+        allowed_external_dirs: Vec::new(),
+        cnpj_cache_days: VALIDADE_CACHE_CNPJ_PADRAO_DIAS,
     };
     
-    match serde_json::to_string_pretty(&new_config) {
-        Ok(json_content) => {
-            match std::fs::write(config_path, json_content) {
-                Ok(_) => {
-                    debug_info.push_str("✅ Nova configuração criada com sucesso\n");
-                }
-                Err(e) => {
-                    debug_info.push_str(&format!("❌ Erro ao escrever nova configuração: {}\n", e));
-                }
-            }
+    match integridade::escrever_json_atomico(config_path, &new_config) {
+        Ok(()) => {
+            debug_info.push_str("✅ Nova configuração criada com sucesso\n");
         }
         Err(e) => {
-            debug_info.push_str(&format!("❌ Erro ao serializar nova configuração: {}\n", e));
+            debug_info.push_str(&format!("❌ Erro ao escrever nova configuração: {:#}\n", e));
         }
     }
 }
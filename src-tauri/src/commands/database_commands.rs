@@ -0,0 +1,41 @@
+use std::path::Path;
+use tauri::State;
+use crate::types::{FiltroPropostasSql, ReconstrucaoBanco, ResultadoConsultaPropostas, TauriError};
+use crate::{config, resultados, storage};
+use super::directory_commands::get_output_directory;
+
+/// Consulta propostas no banco SQLite com filtros por fornecedor, CNPJ, UASG, pregão, descrição e valor
+#[tauri::command]
+pub async fn query_propostas_sql_like(filters: FiltroPropostasSql) -> Result<ResultadoConsultaPropostas, TauriError> {
+    if let (Some(minimo), Some(maximo)) = (filters.valor_min, filters.valor_max) {
+        if minimo > maximo {
+            return Err(TauriError::validation(
+                format!("Valor mínimo ({}) maior que o máximo ({})", minimo, maximo),
+                None,
+            ));
+        }
+    }
+    
+    let output_dir = get_output_directory().await?;
+    let conn = storage::abrir(Path::new(&output_dir))
+        .map_err(|e| TauriError::filesystem(format!("Erro ao abrir o banco de resultados: {:#}", e), output_dir.clone()))?;
+    
+    storage::consultar_propostas(&conn, &filters)
+        .map_err(|e| TauriError::processing(format!("Erro ao consultar propostas: {:#}", e), None))
+}
+
+/// Recria o conteúdo do banco SQLite a partir dos JSONs de licitação e do SICAF
+#[tauri::command]
+pub async fn rebuild_database_from_json(config_state: State<'_, config::ConfigState>) -> Result<ReconstrucaoBanco, TauriError> {
+    let output_dir = get_output_directory().await?;
+    let raiz = Path::new(&output_dir);
+    let arquivos = resultados::listar_arquivos_licitacao(
+        &resultados::diretorios_resultados(raiz, &config::load_routing_rules(&config_state))
+    );
+    
+    let mut conn = storage::abrir(raiz)
+        .map_err(|e| TauriError::filesystem(format!("Erro ao abrir o banco de resultados: {:#}", e), output_dir.clone()))?;
+    
+    storage::reconstruir(&mut conn, &arquivos, Some(&raiz.join("sicaf_dados.json")))
+        .map_err(|e| TauriError::processing(format!("Erro ao reconstruir o banco de resultados: {:#}", e), output_dir))
+}
@@ -1,16 +1,18 @@
-use std::path::PathBuf;
-use crate::types::TauriError;
+use std::path::{Path, PathBuf};
+use chrono::Utc;
+use tauri::State;
+use crate::config;
+use crate::integridade;
+use crate::uso_disco;
+use crate::types::{DatabaseUsage, TauriError};
+use crate::paths::{self, SubDir};
 
 /// Obtém o diretório de trabalho atual
 #[tauri::command]
 pub async fn get_current_directory() -> Result<String, TauriError> {
     match std::env::current_dir() {
         Ok(path) => Ok(path.to_string_lossy().to_string()),
-        Err(e) => Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao obter diretório atual: {}", e),
-            details: None,
-        })
+        Err(e) => Err(TauriError::filesystem(format!("Erro ao obter diretório atual: {}", e), None))
     }
 }
 
@@ -29,44 +31,11 @@ pub async fn create_default_directories() -> Result<String, TauriError> {
 /// Inicializa toda a estrutura de pastas Database
 #[tauri::command]
 pub async fn initialize_database_structure() -> Result<String, TauriError> {
-    let current_exe = std::env::current_exe()
-        .map_err(|e| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao obter diretório do executável: {}", e),
-            details: None,
-        })?;
+    let database_dir = paths::database_dir()?;
     
-    let exe_dir = current_exe.parent()
-        .ok_or_else(|| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: "Não foi possível obter o diretório pai do executável".to_string(),
-            details: None,
-        })?;
-    
-    let database_dir = exe_dir.join("Database");
-    let subdirs = ["PDFs", "Resultados", "SICAF", "Config"];
-    
-    // Criar pasta Database principal
-    if !database_dir.exists() {
-        std::fs::create_dir_all(&database_dir)
-            .map_err(|e| TauriError {
-                error_type: "FileSystemError".to_string(),
-                message: format!("Erro ao criar pasta Database: {}", e),
-                details: Some(database_dir.to_string_lossy().to_string()),
-            })?;
-    }
-    
-    // Criar subpastas
-    for subdir in &subdirs {
-        let dir_path = database_dir.join(subdir);
-        if !dir_path.exists() {
-            std::fs::create_dir_all(&dir_path)
-                .map_err(|e| TauriError {
-                    error_type: "FileSystemError".to_string(),
-                    message: format!("Erro ao criar pasta {}: {}", subdir, e),
-                    details: Some(dir_path.to_string_lossy().to_string()),
-                })?;
-        }
+    // Criar subpastas (a pasta Database principal é criada junto)
+    for subdir in SubDir::ALL {
+        paths::database_subdir(subdir)?;
     }
     
     // Criar arquivo README na pasta Database
@@ -87,142 +56,118 @@ Esta estrutura é mantida durante atualizações do programa.
 "#;
         
         std::fs::write(&readme_path, readme_content)
-            .map_err(|e| TauriError {
-                error_type: "FileSystemError".to_string(),
-                message: format!("Erro ao criar README: {}", e),
-                details: Some(readme_path.to_string_lossy().to_string()),
-            })?;
+            .map_err(|e| TauriError::filesystem(
+                format!("Erro ao criar README: {}", e),
+                readme_path.to_string_lossy().to_string(),
+            ))?;
     }
     
     Ok(format!("Estrutura Database inicializada com sucesso em: {}", database_dir.to_string_lossy()))
 }
 
-/// Obtém o diretório da pasta de configuração
+/// Troca a pasta de dados (Database), copiando a estrutura atual para o novo local
+///
+/// Os arquivos da pasta antiga são mantidos; arquivos que já existem no destino não são
+/// sobrescritos. A escolha fica registrada na configuração da pasta padrão.
 #[tauri::command]
-pub async fn get_config_directory() -> Result<String, TauriError> {
-    let current_exe = std::env::current_exe()
-        .map_err(|e| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao obter diretório do executável: {}", e),
-            details: None,
-        })?;
-    
-    let exe_dir = current_exe.parent()
-        .ok_or_else(|| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: "Não foi possível obter o diretório pai do executável".to_string(),
-            details: None,
-        })?;
+pub async fn set_database_root(path: String, config_state: State<'_, config::ConfigState>) -> Result<String, TauriError> {
+    let novo = PathBuf::from(path.trim());
+    if !novo.is_absolute() {
+        return Err(TauriError::validation("Informe um caminho absoluto para a pasta de dados", path));
+    }
     
-    let config_dir = exe_dir.join("Database").join("Config");
+    std::fs::create_dir_all(&novo)
+        .map_err(|e| TauriError::filesystem(format!("Erro ao criar pasta de dados: {}", e), path.clone()))?;
+    let novo = novo.canonicalize().unwrap_or(novo);
+    let atual = paths::database_dir()?;
+    let atual = atual.canonicalize().unwrap_or(atual);
     
-    // Criar a pasta se não existir
-    if !config_dir.exists() {
-        std::fs::create_dir_all(&config_dir)
-            .map_err(|e| TauriError {
-                error_type: "FileSystemError".to_string(),
-                message: format!("Erro ao criar pasta Database/Config: {}", e),
-                details: Some(config_dir.to_string_lossy().to_string()),
-            })?;
+    let mut copiados = 0;
+    if novo != atual {
+        if novo.starts_with(&atual) || atual.starts_with(&novo) {
+            return Err(TauriError::validation(
+                "A nova pasta de dados não pode estar dentro da atual (nem conter a atual)",
+                path,
+            ));
+        }
+        if atual.exists() {
+            copiados = paths::copiar_estrutura(&atual, &novo)?;
+        }
     }
     
+    // Diretórios lembrados dentro da pasta antiga passam a apontar para a nova
+    let rebase = |dir: &str| match Path::new(dir).strip_prefix(&atual) {
+        Ok(relativo) => novo.join(relativo).to_string_lossy().to_string(),
+        Err(_) => dir.to_string(),
+    };
+    let padrao = paths::default_database_root_with(&paths::CurrentExe)?;
+    config::atualizar_config(&config_state, |app_config| {
+        app_config.last_input_directory = app_config.last_input_directory.as_deref().map(rebase);
+        app_config.last_output_directory = app_config.last_output_directory.as_deref().map(rebase);
+        for regra in &mut app_config.routing_rules {
+            regra.target_directory = rebase(&regra.target_directory);
+        }
+        app_config.database_root = Some(novo.to_string_lossy().to_string());
+        app_config.updated_at = Utc::now().to_rfc3339();
+        
+        // A configuração da pasta padrão funciona como ponteiro para a pasta escolhida
+        config::save_config_in(&padrao.join(SubDir::Config.folder_name()), app_config)
+    })?;
+    
+    Ok(format!("Pasta de dados alterada para {} ({} arquivos copiados)", novo.display(), copiados))
+}
+
+/// Obtém o diretório da pasta de configuração
+#[tauri::command]
+pub async fn get_config_directory() -> Result<String, TauriError> {
+    let config_dir = paths::database_subdir(SubDir::Config)?;
     Ok(config_dir.to_string_lossy().to_string())
 }
 
 /// Obtém o diretório da pasta PDF (Database/PDFs)
 #[tauri::command]
 pub async fn get_pdf_directory() -> Result<String, TauriError> {
-    let current_exe = std::env::current_exe()
-        .map_err(|e| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao obter diretório do executável: {}", e),
-            details: None,
-        })?;
-    
-    let exe_dir = current_exe.parent()
-        .ok_or_else(|| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: "Não foi possível obter o diretório pai do executável".to_string(),
-            details: None,
-        })?;
-    
-    let pdf_dir = exe_dir.join("Database").join("PDFs");
-    
-    // Criar a pasta se não existir
-    if !pdf_dir.exists() {
-        std::fs::create_dir_all(&pdf_dir)
-            .map_err(|e| TauriError {
-                error_type: "FileSystemError".to_string(),
-                message: format!("Erro ao criar pasta Database/PDFs: {}", e),
-                details: Some(pdf_dir.to_string_lossy().to_string()),
-            })?;
-    }
-    
+    let pdf_dir = paths::database_subdir(SubDir::Pdfs)?;
     Ok(pdf_dir.to_string_lossy().to_string())
 }
 
 /// Obtém o diretório da pasta de saída (Database/Resultados)
 #[tauri::command]
 pub async fn get_output_directory() -> Result<String, TauriError> {
-    let current_exe = std::env::current_exe()
-        .map_err(|e| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao obter diretório do executável: {}", e),
-            details: None,
-        })?;
-    
-    let exe_dir = current_exe.parent()
-        .ok_or_else(|| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: "Não foi possível obter o diretório pai do executável".to_string(),
-            details: None,
-        })?;
-    
-    let output_dir = exe_dir.join("Database").join("Resultados");
-    
-    // Criar a pasta se não existir
-    if !output_dir.exists() {
-        std::fs::create_dir_all(&output_dir)
-            .map_err(|e| TauriError {
-                error_type: "FileSystemError".to_string(),
-                message: format!("Erro ao criar pasta Database/Resultados: {}", e),
-                details: Some(output_dir.to_string_lossy().to_string()),
-            })?;
-    }
-    
+    let output_dir = paths::database_subdir(SubDir::Resultados)?;
     Ok(output_dir.to_string_lossy().to_string())
 }
 
 /// Obtém o diretório da pasta SICAF (Database/SICAF)
 #[tauri::command]
 pub async fn get_sicaf_directory() -> Result<String, TauriError> {
-    let current_exe = std::env::current_exe()
-        .map_err(|e| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao obter diretório do executável: {}", e),
-            details: None,
-        })?;
-    
-    let exe_dir = current_exe.parent()
-        .ok_or_else(|| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: "Não foi possível obter o diretório pai do executável".to_string(),
-            details: None,
-        })?;
-    
-    let sicaf_dir = exe_dir.join("Database").join("SICAF");
+    let sicaf_dir = paths::database_subdir(SubDir::Sicaf)?;
+    Ok(sicaf_dir.to_string_lossy().to_string())
+}
+
+/// Tamanho, arquivos por extensão e arquivos mais antigo e mais recente de cada subpasta da Database
+///
+/// Lê só os metadados, sem abrir os arquivos; entradas sem permissão contam como inacessíveis.
+#[tauri::command]
+pub async fn get_database_usage() -> Result<DatabaseUsage, TauriError> {
+    let raiz = paths::database_dir()?;
+    let raiz_texto = raiz.to_string_lossy().to_string();
     
-    // Criar a pasta se não existir
-    if !sicaf_dir.exists() {
-        std::fs::create_dir_all(&sicaf_dir)
-            .map_err(|e| TauriError {
-                error_type: "FileSystemError".to_string(),
-                message: format!("Erro ao criar pasta Database/SICAF: {}", e),
-                details: Some(sicaf_dir.to_string_lossy().to_string()),
-            })?;
-    }
+    let pastas = tokio::task::spawn_blocking(move || {
+        SubDir::ALL.iter()
+            .map(|subdir| uso_disco::medir_pasta(subdir.folder_name(), &raiz.join(subdir.folder_name())))
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| TauriError::system(format!("Erro ao medir a pasta de dados: {}", e), raiz_texto.clone()))?;
     
-    Ok(sicaf_dir.to_string_lossy().to_string())
+    Ok(DatabaseUsage {
+        raiz: raiz_texto,
+        tamanho_total_bytes: pastas.iter().map(|p| p.tamanho_bytes).sum(),
+        total_arquivos: pastas.iter().map(|p| p.total_arquivos).sum(),
+        inacessiveis: pastas.iter().map(|p| p.inacessiveis).sum(),
+        pastas,
+    })
 }
 
 /// Verifica e cria o diretório de saída, retornando informações sobre ele
@@ -237,7 +182,7 @@ pub async fn verify_output_directory() -> Result<String, TauriError> {
         .into_iter()
         .filter_map(|e| e.ok())
         .filter(|e| e.file_type().is_file())
-        .filter(|e| e.path().extension().map_or(false, |ext| ext == "json"))
+        .filter(|e| paths::is_json_file(e.path()))
         .count();
     
     // Criar um arquivo de exemplo se não houver arquivos JSON
@@ -262,12 +207,11 @@ pub async fn verify_output_directory() -> Result<String, TauriError> {
             }
         });
         
-        std::fs::write(&exemplo_path, serde_json::to_string_pretty(&exemplo_content).unwrap())
-            .map_err(|e| TauriError {
-                error_type: "FileSystemError".to_string(),
-                message: format!("Erro ao criar arquivo de exemplo: {}", e),
-                details: Some(exemplo_path.to_string_lossy().to_string()),
-            })?;
+        integridade::escrever_json_atomico(&exemplo_path, &exemplo_content)
+            .map_err(|e| TauriError::filesystem(
+                format!("Erro ao criar arquivo de exemplo: {:#}", e),
+                exemplo_path.to_string_lossy().to_string(),
+            ))?;
     }
     
     Ok(format!("Pasta de resultados verificada: {} ({} arquivos JSON encontrados)", 
@@ -281,11 +225,7 @@ pub async fn open_folder(path: String) -> Result<bool, TauriError> {
     
     // Verificar se o caminho existe
     if !path_buf.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Caminho não encontrado: {}", path),
-            details: Some(path.clone()),
-        });
+        return Err(TauriError::filesystem(format!("Caminho não encontrado: {}", path), path.clone()));
     }
     
     // Abrir pasta no sistema operacional
@@ -294,11 +234,7 @@ pub async fn open_folder(path: String) -> Result<bool, TauriError> {
         std::process::Command::new("explorer")
             .arg(&path)
             .spawn()
-            .map_err(|e| TauriError {
-                error_type: "SystemError".to_string(),
-                message: format!("Erro ao abrir pasta: {}", e),
-                details: Some(path.clone()),
-            })?;
+            .map_err(|e| TauriError::system(format!("Erro ao abrir pasta: {}", e), path.clone()))?;
     }
     
     #[cfg(target_os = "macos")]
@@ -306,11 +242,7 @@ pub async fn open_folder(path: String) -> Result<bool, TauriError> {
         std::process::Command::new("open")
             .arg(&path)
             .spawn()
-            .map_err(|e| TauriError {
-                error_type: "SystemError".to_string(),
-                message: format!("Erro ao abrir pasta: {}", e),
-                details: Some(path.clone()),
-            })?;
+            .map_err(|e| TauriError::system(format!("Erro ao abrir pasta: {}", e), path.clone()))?;
     }
     
     #[cfg(target_os = "linux")]
@@ -318,11 +250,7 @@ pub async fn open_folder(path: String) -> Result<bool, TauriError> {
         std::process::Command::new("xdg-open")
             .arg(&path)
             .spawn()
-            .map_err(|e| TauriError {
-                error_type: "SystemError".to_string(),
-                message: format!("Erro ao abrir pasta: {}", e),
-                details: Some(path.clone()),
-            })?;
+            .map_err(|e| TauriError::system(format!("Erro ao abrir pasta: {}", e), path.clone()))?;
     }
     
     Ok(true)
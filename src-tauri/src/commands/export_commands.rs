@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+use tauri::State;
+use crate::types::{LicitacaoConsolidada, PropostaConsolidada, TauriError};
+use crate::{config, exportacao, integridade, pdf_processor, report, resultados};
+
+/// Lê as propostas de um JSON de licitação gerado por `salvar_json_consolidado`
+pub(crate) fn ler_propostas_json(json_file_path: &str) -> Result<Vec<PropostaConsolidada>, TauriError> {
+    let content = std::fs::read_to_string(json_file_path)
+        .map_err(|e| TauriError::filesystem(format!("Erro ao ler arquivo: {}", e), json_file_path.to_string()))?;
+
+    let json: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| TauriError::parse(format!("Erro ao analisar JSON: {}", e), json_file_path.to_string()))?;
+
+    let propostas = json.get("propostas")
+        .filter(|p| p.is_array())
+        .ok_or_else(|| TauriError::validation("O JSON não contém o array 'propostas'", json_file_path.to_string()))?;
+
+    serde_json::from_value::<Vec<PropostaConsolidada>>(propostas.clone())
+        .map_err(|e| TauriError::parse(format!("Erro ao ler propostas: {}", e), json_file_path.to_string()))
+}
+
+/// Propostas de um JSON de licitação ou de todas as licitações do consolidado.json
+fn ler_propostas_ou_consolidado(json_file_path: &str) -> Result<Vec<PropostaConsolidada>, TauriError> {
+    let path = Path::new(json_file_path);
+    if path.file_name().is_none_or(|nome| nome != pdf_processor::ARQUIVO_CONSOLIDADO) {
+        return ler_propostas_json(json_file_path);
+    }
+
+    let consolidado = pdf_processor::carregar_consolidado(path)
+        .map_err(|e| TauriError::parse(format!("{:#}", e), json_file_path.to_string()))?;
+    let mut licitacoes: Vec<(String, LicitacaoConsolidada)> = consolidado.licitacoes.into_iter().collect();
+    licitacoes.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(licitacoes.into_iter().flat_map(|(_, licitacao)| licitacao.propostas).collect())
+}
+
+/// Gera o texto TSV (com cabeçalho) das propostas, para o frontend copiar para a área de transferência
+///
+/// Aceita um JSON de licitação ou o consolidado.json; `item_filter` restringe aos itens informados.
+/// O número de linhas é limitado por `clipboard_max_rows` da configuração.
+#[tauri::command]
+pub async fn get_propostas_tsv(
+    json_file_path: String,
+    item_filter: Option<Vec<String>>,
+    config_state: State<'_, config::ConfigState>
+) -> Result<String, TauriError> {
+    let propostas = ler_propostas_ou_consolidado(&json_file_path)?;
+    let limite = config::ler_config(&config_state).clipboard_max_rows;
+
+    let selecionadas = propostas.iter().filter(|proposta| {
+        item_filter.as_ref().is_none_or(|itens| itens.iter().any(|item| exportacao::mesmo_item(&proposta.item, item)))
+    });
+    Ok(exportacao::gerar_tsv_propostas(selecionadas, limite))
+}
+
+/// Exporta as propostas de um JSON de licitação para CSV (separador `;`, UTF-8 com BOM)
+#[tauri::command]
+pub async fn export_propostas_csv(json_file_path: String, output_path: String) -> Result<String, TauriError> {
+    let propostas = ler_propostas_json(&json_file_path)?;
+    let output = PathBuf::from(&output_path);
+
+    if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| TauriError::filesystem(
+                format!("Erro ao criar diretório de saída: {}", e),
+                parent.to_string_lossy().to_string(),
+            ))?;
+    }
+
+    std::fs::write(&output, exportacao::gerar_csv_propostas(&propostas))
+        .map_err(|e| TauriError::filesystem(format!("Erro ao salvar CSV: {}", e), output_path.clone()))?;
+
+    Ok(output.to_string_lossy().to_string())
+}
+
+/// Gera o relatório resumido de um JSON de licitação em PDF e retorna o caminho do arquivo
+#[tauri::command]
+pub async fn generate_licitacao_pdf_report(json_file_path: String, output_path: String) -> Result<String, TauriError> {
+    let json_path = std::path::Path::new(&json_file_path);
+    if !json_path.is_file() {
+        return Err(TauriError::not_found(format!("Arquivo não encontrado: {}", json_file_path), json_file_path));
+    }
+    let licitacao = resultados::carregar_licitacao(json_path)
+        .map_err(|e| TauriError::validation(
+            format!("O arquivo não é um JSON de licitação: {:#}", e),
+            json_file_path.clone(),
+        ))?;
+
+    let bytes = report::gerar_pdf(&licitacao)
+        .map_err(|e| TauriError::processing(format!("{:#}", e), json_file_path.clone()))?;
+
+    let output = PathBuf::from(&output_path);
+    if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| TauriError::filesystem(
+                format!("Erro ao criar diretório de saída: {}", e),
+                parent.to_string_lossy().to_string(),
+            ))?;
+    }
+    integridade::escrever_atomico(&output, &bytes)
+        .map_err(|e| TauriError::filesystem(format!("Erro ao salvar PDF: {}", e), output_path.clone()))?;
+
+    Ok(output.to_string_lossy().to_string())
+}
+
+/// Exporta todas as licitações da pasta de resultados para uma planilha XLSX
+///
+/// JSONs ilegíveis ficam de fora da planilha e são listados em `arquivos_com_erro`.
+#[tauri::command]
+pub async fn export_consolidado_xlsx(output_dir: String, config_state: State<'_, config::ConfigState>) -> Result<serde_json::Value, TauriError> {
+    let output_path = PathBuf::from(&output_dir);
+
+    if !output_path.is_dir() {
+        return Err(TauriError::filesystem(format!("Diretório não encontrado: {}", output_dir), output_dir));
+    }
+
+    let mut licitacoes: Vec<LicitacaoConsolidada> = Vec::new();
+    let mut arquivos_com_erro: Vec<String> = Vec::new();
+    let diretorios = resultados::diretorios_resultados(&output_path, &config::load_routing_rules(&config_state));
+    for arquivo in resultados::listar_arquivos_licitacao(&diretorios) {
+        match resultados::carregar_licitacao(&arquivo) {
+            Ok(licitacao) => licitacoes.push(licitacao),
+            Err(e) => arquivos_com_erro.push(format!("{}: {:#}", arquivo.display(), e)),
+        }
+    }
+
+    let bytes = exportacao::gerar_xlsx_consolidado(&licitacoes)
+        .map_err(|e| TauriError::processing(format!("Erro ao gerar planilha XLSX: {}", e), None))?;
+
+    let xlsx_path = output_path.join("consolidado.xlsx");
+    integridade::gravar_artefato(&output_path, &xlsx_path, &bytes, None)
+        .map_err(|e| TauriError::filesystem(
+            format!("Erro ao salvar planilha XLSX: {}", e),
+            xlsx_path.to_string_lossy().to_string(),
+        ))?;
+
+    Ok(serde_json::json!({
+        "xlsx_path": xlsx_path.to_string_lossy(),
+        "total_licitacoes": licitacoes.len(),
+        "arquivos_com_erro": arquivos_com_erro
+    }))
+}
@@ -1,166 +1,358 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use rayon::prelude::*;
+use tauri::State;
 use walkdir::WalkDir;
-use crate::types::TauriError;
+use crate::types::{AppConfig, GrupoPdfsDuplicados, ImportacaoPdfs, PdfImportado, PdfNaoImportado, TauriError};
+use crate::{config, indice_pdfs, integridade, metadados_pdf, paths, resultados, revelar};
+use super::directory_commands::get_pdf_directory;
 
-/// Obtém informações de um arquivo PDF específico
+/// Informações básicas de um PDF e, se pedido, os metadados lidos do próprio arquivo
+fn info_pdf(path: &Path, metadata: &std::fs::Metadata, include_metadata: bool) -> serde_json::Value {
+    let modified_timestamp = metadata.modified()
+        .ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    
+    let mut file_info = serde_json::json!({
+        "file_name": path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "unknown".to_string()),
+        "file_path": path.to_string_lossy(),
+        "file_size": metadata.len(),
+        "modified_timestamp": modified_timestamp
+    });
+    
+    if include_metadata {
+        let extras = match metadados_pdf::ler_metadados_pdf(path) {
+            Ok(metadados) => serde_json::json!({
+                "page_count": metadados.paginas,
+                "title": metadados.titulo,
+                "author": metadados.autor,
+                "creation_date": metadados.data_criacao,
+                "text_extractable": metadados.texto_extraivel,
+                "metadata_error": null
+            }),
+            // PDF corrompido: mantém os campos básicos e informa o motivo
+            Err(e) => serde_json::json!({ "metadata_error": format!("{:#}", e) }),
+        };
+        if let (Some(info), serde_json::Value::Object(extras)) = (file_info.as_object_mut(), extras) {
+            info.extend(extras);
+            info.insert("sha256".to_string(), integridade::sha256_arquivo(path).ok().into());
+        }
+    }
+    
+    file_info
+}
+
+/// Obtém informações de um arquivo PDF específico, com páginas, título, autor e data de criação
 #[tauri::command]
-pub async fn get_pdf_file_info(file_path: String) -> Result<serde_json::Value, TauriError> {
-    let path = PathBuf::from(&file_path);
+pub async fn get_pdf_file_info(file_path: String, include_metadata: Option<bool>, config_state: State<'_, config::ConfigState>) -> Result<serde_json::Value, TauriError> {
+    let path = paths::ensure_within_allowed(Path::new(&file_path), &config::ler_config(&config_state))?;
+    
+    let metadata = std::fs::metadata(&path).map_err(|e| TauriError::filesystem(
+        format!("Erro ao ler metadados do arquivo: {}", e),
+        file_path.clone(),
+    ))?;
+    
+    Ok(info_pdf(&path, &metadata, include_metadata.unwrap_or(true)))
+}
+
+/// PDFs do diretório e subpastas, fora da pasta de processados
+fn pdfs_do_diretorio(path: &Path) -> Vec<(PathBuf, std::fs::Metadata)> {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !paths::eh_pasta_processados(e.file_name()))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| paths::is_pdf_file(e.path()))
+        .filter_map(|e| e.metadata().ok().map(|metadata| (e.into_path(), metadata)))
+        .collect()
+}
+
+/// Obtém informações de todos os arquivos PDF em um diretório (metadados opcionais, lidos em paralelo)
+#[tauri::command]
+pub async fn get_pdf_files_info(directory: String, include_metadata: Option<bool>) -> Result<Vec<serde_json::Value>, TauriError> {
+    let path = PathBuf::from(&directory);
     
     if !path.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Arquivo não encontrado: {}", file_path),
-            details: Some(file_path.clone()),
-        });
+        return Err(TauriError::filesystem(format!("Diretório não encontrado: {}", directory), directory));
     }
     
-    let metadata = std::fs::metadata(&path).map_err(|e| TauriError {
-        error_type: "FileSystemError".to_string(),
-        message: format!("Erro ao ler metadados do arquivo: {}", e),
-        details: Some(file_path.clone()),
-    })?;
+    // Mais recentes primeiro
+    let mut arquivos = pdfs_do_diretorio(&path);
+    arquivos.sort_by_key(|(_, metadata)| std::cmp::Reverse(metadata.modified().ok()));
     
-    let file_name = path.file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("unknown")
-        .to_string();
-    
-    let file_size = metadata.len();
-    let modified = metadata.modified()
-        .map_err(|e| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao ler data de modificação: {}", e),
-            details: Some(file_path.clone()),
-        })?;
-    
-    let modified_timestamp = modified.duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs();
-    
-    let file_info = serde_json::json!({
-        "file_name": file_name,
-        "file_path": file_path,
-        "file_size": file_size,
-        "modified_timestamp": modified_timestamp
-    });
+    let include_metadata = include_metadata.unwrap_or(false);
+    if !include_metadata {
+        return Ok(arquivos.iter().map(|(path, metadata)| info_pdf(path, metadata, false)).collect());
+    }
     
-    Ok(file_info)
+    // Abrir cada PDF é caro: fora da thread do runtime e em paralelo
+    tokio::task::spawn_blocking(move || {
+        arquivos.par_iter().map(|(path, metadata)| info_pdf(path, metadata, true)).collect()
+    })
+    .await
+    .map_err(|e| TauriError::system(format!("Erro ao ler metadados dos PDFs: {}", e), directory))
 }
 
-/// Obtém informações de todos os arquivos PDF em um diretório
+/// Agrupa os PDFs do diretório com conteúdo idêntico (mesmo SHA-256), mesmo que renomeados
 #[tauri::command]
-pub async fn get_pdf_files_info(directory: String) -> Result<Vec<serde_json::Value>, TauriError> {
+pub async fn find_duplicate_pdfs(directory: String) -> Result<Vec<GrupoPdfsDuplicados>, TauriError> {
     let path = PathBuf::from(&directory);
     
-    if !path.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Diretório não encontrado: {}", directory),
-            details: Some(directory),
-        });
+    if !path.is_dir() {
+        return Err(TauriError::filesystem(format!("Diretório não encontrado: {}", directory), directory));
     }
     
-    let mut pdf_files_info = Vec::new();
+    let arquivos: Vec<PathBuf> = pdfs_do_diretorio(&path).into_iter().map(|(path, _)| path).collect();
     
-    for entry in WalkDir::new(&path)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-        .filter(|e| e.path().extension().map_or(false, |ext| ext == "pdf"))
-    {
-        let file_path = entry.path().to_string_lossy().to_string();
+    tokio::task::spawn_blocking(move || indice_pdfs::agrupar_duplicados(&arquivos))
+        .await
+        .map_err(|e| TauriError::system(format!("Erro ao comparar os PDFs: {}", e), directory))
+}
+
+/// Valida um PDF a importar, devolvendo o tamanho ou o motivo da recusa
+fn validar_pdf_importado(origem: &Path, limite_bytes: Option<u64>) -> Result<u64, String> {
+    let metadata = std::fs::metadata(origem).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => "Arquivo não encontrado".to_string(),
+        _ => format!("Erro ao ler metadados do arquivo: {}", e),
+    })?;
+    if !metadata.is_file() {
+        return Err("O caminho não é um arquivo".to_string());
+    }
+    if !paths::is_pdf_file(origem) {
+        return Err("O arquivo deve ter extensão .pdf".to_string());
+    }
+    if metadata.len() == 0 {
+        return Err("Arquivo vazio".to_string());
+    }
+    if let Some(limite) = limite_bytes.filter(|limite| metadata.len() > *limite) {
+        return Err(format!(
+            "Arquivo com {:.1} MB excede o limite de {} MB",
+            metadata.len() as f64 / (1024.0 * 1024.0),
+            limite / (1024 * 1024)
+        ));
+    }
+    Ok(metadata.len())
+}
+
+/// Copia PDFs escolhidos pelo usuário para a pasta de PDFs, sem sobrescrever arquivos existentes
+#[tauri::command]
+pub async fn import_pdf_files(paths: Vec<String>, config_state: State<'_, config::ConfigState>) -> Result<ImportacaoPdfs, TauriError> {
+    let pdf_dir = PathBuf::from(get_pdf_directory().await?);
+    let pasta_destino = pdf_dir.canonicalize().map_err(|e| TauriError::filesystem(
+        format!("Erro ao acessar a pasta de PDFs: {}", e),
+        pdf_dir.to_string_lossy().to_string(),
+    ))?;
+    let limite_mb = config::ler_config(&config_state).max_import_size_mb;
+    let limite_bytes = (limite_mb > 0).then(|| limite_mb.saturating_mul(1024 * 1024));
+    
+    let mut relatorio = ImportacaoPdfs::default();
+    for arquivo in paths {
+        let origem = PathBuf::from(&arquivo);
+        let recusa = |motivo: String| PdfNaoImportado { arquivo: arquivo.clone(), motivo };
+        
+        if let Err(motivo) = validar_pdf_importado(&origem, limite_bytes) {
+            relatorio.erros.push(recusa(motivo));
+            continue;
+        }
         
-        if let Ok(metadata) = entry.metadata() {
-            let file_name = entry.file_name().to_string_lossy().to_string();
-            let file_size = metadata.len();
-            
-            let modified_timestamp = metadata.modified()
-                .ok()
-                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
-                .map(|duration| duration.as_secs())
-                .unwrap_or(0);
-            
-            let file_info = serde_json::json!({
-                "file_name": file_name,
-                "file_path": file_path,
-                "file_size": file_size,
-                "modified_timestamp": modified_timestamp
-            });
-            
-            pdf_files_info.push(file_info);
+        // Já está na pasta de PDFs (ou em Processados): copiar seria duplicar o arquivo
+        if origem.canonicalize().is_ok_and(|origem| origem.starts_with(&pasta_destino)) {
+            relatorio.ignorados.push(recusa("O arquivo já está na pasta de PDFs".to_string()));
+            continue;
+        }
+        
+        let destino = paths::caminho_para_copia(&pasta_destino, origem.file_name().unwrap_or_default());
+        match std::fs::copy(&origem, &destino) {
+            Ok(_) => relatorio.importados.push(PdfImportado {
+                origem: arquivo.clone(),
+                destino: destino.to_string_lossy().to_string(),
+            }),
+            Err(e) => relatorio.erros.push(recusa(format!("Erro ao copiar arquivo: {}", e))),
         }
     }
     
-    // Ordenar por data de modificação (mais recente primeiro)
-    pdf_files_info.sort_by(|a, b| {
-        let a_timestamp = a["modified_timestamp"].as_u64().unwrap_or(0);
-        let b_timestamp = b["modified_timestamp"].as_u64().unwrap_or(0);
-        b_timestamp.cmp(&a_timestamp)
-    });
-    
-    Ok(pdf_files_info)
+    Ok(relatorio)
+}
+
+/// Extensões abertas por `open_file` quando o chamador não informa outras
+pub const EXTENSOES_ABERTURA_PADRAO: [&str; 6] = ["pdf", "json", "md", "html", "xlsx", "csv"];
+
+/// Abre o arquivo no aplicativo padrão do sistema operacional
+///
+/// Espera o launcher terminar para distinguir "sem aplicativo associado" de sucesso. O caminho vai
+/// como argumento direto, sem passar por um shell: no Windows o `cmd /C start` reinterpretaria
+/// `&`, `|`, `^` e `%` do nome do arquivo como comandos.
+async fn abrir_no_sistema(path: PathBuf) -> Result<(), TauriError> {
+    let caminho = path.to_string_lossy().to_string();
+    let extensao = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+
+    let saida = tokio::task::spawn_blocking(move || {
+        #[cfg(target_os = "windows")]
+        let mut comando = {
+            let mut comando = std::process::Command::new("explorer.exe");
+            comando.arg(&path);
+            comando
+        };
+        #[cfg(target_os = "macos")]
+        let mut comando = {
+            let mut comando = std::process::Command::new("open");
+            comando.arg(&path);
+            comando
+        };
+        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+        let mut comando = {
+            let mut comando = std::process::Command::new("xdg-open");
+            comando.arg(&path);
+            comando
+        };
+        comando.output()
+    })
+    .await
+    .map_err(|e| TauriError::system(format!("Erro ao abrir arquivo: {}", e), caminho.clone()))?
+    .map_err(|e| TauriError::system(format!("Erro ao abrir arquivo: {}", e), caminho.clone()))?;
+
+    // O explorer.exe sai com código 1 mesmo quando abre o arquivo; sem associação ele mostra o próprio diálogo
+    if !saida.status.success() && !cfg!(target_os = "windows") {
+        let detalhe = String::from_utf8_lossy(&saida.stderr).trim().to_string();
+        return Err(TauriError::system(
+            format!(
+                "Nenhum aplicativo associado a arquivos .{} neste sistema{}",
+                extensao,
+                if detalhe.is_empty() { String::new() } else { format!(" ({})", detalhe) }
+            ),
+            caminho,
+        ));
+    }
+    Ok(())
+}
+
+/// Valida e abre um arquivo gerado pelo app no aplicativo padrão
+///
+/// O arquivo precisa existir dentro da pasta Database (ou de um destino de roteamento)
+/// e ter uma das extensões permitidas, para que o comando não execute arquivos arbitrários.
+async fn abrir_arquivo_validado(file_path: String, extensoes: Vec<String>, config: &AppConfig) -> Result<bool, TauriError> {
+    let path = PathBuf::from(&file_path);
+    if !path.is_file() {
+        return Err(TauriError::filesystem(format!("Arquivo não encontrado: {}", file_path), file_path.clone()));
+    }
+
+    if !paths::extensao_permitida(&path, &extensoes) {
+        return Err(TauriError::validation(
+            format!("Extensão não permitida (use {})", extensoes.join(", ")),
+            file_path.clone(),
+        ));
+    }
+
+    let canonico = paths::ensure_within_allowed(&path, config)?;
+    abrir_no_sistema(canonico).await?;
+    Ok(true)
+}
+
+/// Abre no aplicativo padrão um arquivo gerado pelo app (.pdf, .json, .md, .html, .xlsx, .csv)
+///
+/// `allowed_extensions` restringe ainda mais as extensões aceitas (ex.: `["xlsx"]`); extensões
+/// fora da lista padrão são ignoradas.
+#[tauri::command]
+pub async fn open_file(file_path: String, allowed_extensions: Option<Vec<String>>, config_state: State<'_, config::ConfigState>) -> Result<bool, TauriError> {
+    let extensoes: Vec<String> = match allowed_extensions.filter(|extensoes| !extensoes.is_empty()) {
+        None => EXTENSOES_ABERTURA_PADRAO.iter().map(|ext| ext.to_string()).collect(),
+        Some(pedidas) => pedidas.into_iter()
+            .filter(|ext| {
+                let ext = ext.trim().trim_start_matches('.');
+                EXTENSOES_ABERTURA_PADRAO.iter().any(|padrao| padrao.eq_ignore_ascii_case(ext))
+            })
+            .collect(),
+    };
+    if extensoes.is_empty() {
+        return Err(TauriError::validation(
+            format!("Extensão não permitida (use {})", EXTENSOES_ABERTURA_PADRAO.join(", ")),
+            file_path,
+        ));
+    }
+    abrir_arquivo_validado(file_path, extensoes, &config::ler_config(&config_state)).await
 }
 
 /// Abre um arquivo PDF no visualizador padrão do sistema
 #[tauri::command]
-pub async fn open_pdf_file(file_path: String) -> Result<bool, TauriError> {
-    let path_buf = PathBuf::from(&file_path);
-    
-    // Verificar se o arquivo existe
-    if !path_buf.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Arquivo não encontrado: {}", file_path),
-            details: Some(file_path.clone()),
-        });
-    }
-    
-    // Verificar se é um arquivo PDF
-    if path_buf.extension().map_or(true, |ext| ext != "pdf") {
-        return Err(TauriError {
-            error_type: "ValidationError".to_string(),
-            message: "O arquivo deve ter extensão .pdf".to_string(),
-            details: Some(file_path.clone()),
-        });
-    }
-    
-    // Abrir arquivo no sistema operacional
-    #[cfg(target_os = "windows")]
-    {
-        std::process::Command::new("cmd")
-            .args(["/C", "start", "", &file_path])
-            .spawn()
-            .map_err(|e| TauriError {
-                error_type: "SystemError".to_string(),
-                message: format!("Erro ao abrir arquivo PDF: {}", e),
-                details: Some(file_path.clone()),
-            })?;
-    }
-    
-    #[cfg(target_os = "macos")]
-    {
-        std::process::Command::new("open")
-            .arg(&file_path)
-            .spawn()
-            .map_err(|e| TauriError {
-                error_type: "SystemError".to_string(),
-                message: format!("Erro ao abrir arquivo PDF: {}", e),
-                details: Some(file_path.clone()),
-            })?;
-    }
-    
-    #[cfg(target_os = "linux")]
-    {
-        std::process::Command::new("xdg-open")
-            .arg(&file_path)
-            .spawn()
-            .map_err(|e| TauriError {
-                error_type: "SystemError".to_string(),
-                message: format!("Erro ao abrir arquivo PDF: {}", e),
-                details: Some(file_path.clone()),
-            })?;
+pub async fn open_pdf_file(file_path: String, config_state: State<'_, config::ConfigState>) -> Result<bool, TauriError> {
+    abrir_arquivo_validado(file_path, vec!["pdf".to_string()], &config::ler_config(&config_state)).await
+}
+
+/// Dispara um comando do sistema; com `aguardar`, espera o término e informa se teve sucesso
+fn executar_comando(comando: &revelar::ComandoSistema, aguardar: bool) -> std::io::Result<bool> {
+    let mut processo = std::process::Command::new(&comando.programa);
+    if comando.argumentos_brutos {
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            for arg in &comando.args {
+                processo.raw_arg(arg);
+            }
+        }
+        #[cfg(not(target_os = "windows"))]
+        processo.args(&comando.args);
+    } else {
+        processo.args(&comando.args);
+    }
+
+    if aguardar {
+        Ok(processo.stdout(std::process::Stdio::null()).stderr(std::process::Stdio::null()).status()?.success())
+    } else {
+        processo.spawn().map(|_| true)
+    }
+}
+
+/// Abre o gerenciador de arquivos com o arquivo já selecionado
+///
+/// No Linux, sem gerenciador compatível com `org.freedesktop.FileManager1`, abre apenas a pasta.
+#[tauri::command]
+pub async fn reveal_file_in_folder(file_path: String) -> Result<bool, TauriError> {
+    let path = PathBuf::from(&file_path);
+    if !path.is_file() {
+        return Err(TauriError::filesystem(format!("Arquivo não encontrado: {}", file_path), file_path.clone()));
+    }
+    // O explorer.exe não entende o prefixo `\\?\` que o canonicalize põe no Windows
+    let path = path.canonicalize().map(|canonico| paths::sem_prefixo_verbatim(&canonico)).unwrap_or(path);
+    let comandos = revelar::comandos_revelar(revelar::Plataforma::atual(), &path);
+
+    tokio::task::spawn_blocking(move || {
+        let mut ultimo_erro = None;
+        for (indice, comando) in comandos.iter().enumerate() {
+            // As alternativas anteriores à última precisam confirmar que funcionaram
+            let ultima = indice + 1 == comandos.len();
+            match executar_comando(comando, !ultima) {
+                Ok(true) => return Ok(true),
+                Ok(false) => {}
+                Err(e) => ultimo_erro = Some(format!("{}: {}", comando.programa, e)),
+            }
+        }
+        Err(TauriError::system(
+            format!("Erro ao abrir a pasta do arquivo: {}", ultimo_erro.unwrap_or_else(|| "nenhum gerenciador de arquivos respondeu".to_string())),
+            file_path,
+        ))
+    })
+    .await
+    .map_err(|e| TauriError::system(format!("Erro ao abrir a pasta do arquivo: {}", e), None))?
+}
+
+/// Abre no navegador padrão o relatório HTML gerado para um PDF (pelo nome do PDF sem extensão)
+#[tauri::command]
+pub async fn open_report_html(pdf_stem: String, config_state: State<'_, config::ConfigState>) -> Result<String, TauriError> {
+    // Apenas o nome: separadores permitiriam abrir arquivos fora das pastas de resultado
+    if pdf_stem.trim().is_empty() || pdf_stem.contains(['/', '\\']) || pdf_stem == ".." {
+        return Err(TauriError::validation(format!("Nome de relatório inválido: {}", pdf_stem), pdf_stem));
     }
     
-    Ok(true)
+    let output_dir = super::directory_commands::get_output_directory().await?;
+    let nome = format!("{}.html", pdf_stem);
+    let html_path = resultados::diretorios_resultados(Path::new(&output_dir), &config::load_routing_rules(&config_state))
+        .into_iter()
+        .map(|dir| dir.join(&nome))
+        .find(|path| path.is_file())
+        .ok_or_else(|| TauriError::not_found(format!("Relatório HTML não encontrado: {}", nome), pdf_stem.clone()))?;
+    let html = html_path.to_string_lossy().to_string();
+    
+    abrir_no_sistema(html_path).await?;
+    Ok(html)
 }
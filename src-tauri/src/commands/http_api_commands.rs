@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+use tauri::State;
+use crate::types::TauriError;
+use crate::config;
+use crate::http_api::{self, EstadoApi, HttpApiState};
+use crate::commands::directory_commands::get_output_directory;
+
+/// Inicia a API HTTP somente leitura para dashboards internos
+#[tauri::command]
+pub async fn start_http_api(
+    port: u16,
+    token: String,
+    http_state: State<'_, HttpApiState>,
+    config_state: State<'_, config::ConfigState>
+) -> Result<serde_json::Value, TauriError> {
+    if token.trim().len() < http_api::TAMANHO_MINIMO_TOKEN {
+        return Err(TauriError::validation(
+            format!("O token deve ter pelo menos {} caracteres", http_api::TAMANHO_MINIMO_TOKEN),
+            None,
+        ));
+    }
+
+    if http_state.lock().unwrap_or_else(|e| e.into_inner()).is_some() {
+        return Err(TauriError::validation("A API HTTP já está em execução", None));
+    }
+
+    let app_config = config::ler_config(&config_state);
+    let host = app_config.http_api_bind_address
+        .clone()
+        .filter(|h| !h.trim().is_empty())
+        .unwrap_or_else(|| http_api::HOST_PADRAO.to_string());
+
+    let estado = EstadoApi {
+        token: token.trim().to_string(),
+        output_dir: PathBuf::from(get_output_directory().await?),
+        regras: app_config.routing_rules,
+    };
+
+    let servidor = http_api::iniciar_servidor(&host, port, estado).await
+        .map_err(|e| TauriError::network(format!("Erro ao iniciar API HTTP: {}", e), format!("{}:{}", host, port)))?;
+
+    let endereco = servidor.endereco.to_string();
+
+    // Outra chamada pode ter iniciado um servidor enquanto este abria a porta
+    let anterior = {
+        let mut state = http_state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.is_some() {
+            Some(servidor)
+        } else {
+            *state = Some(servidor);
+            None
+        }
+    };
+    if let Some(servidor) = anterior {
+        servidor.parar().await;
+        return Err(TauriError::validation("A API HTTP já está em execução", None));
+    }
+
+    Ok(serde_json::json!({
+        "endereco": endereco,
+        "running": true
+    }))
+}
+
+/// Encerra a API HTTP, retornando `false` se ela não estava em execução
+#[tauri::command]
+pub async fn stop_http_api(http_state: State<'_, HttpApiState>) -> Result<bool, TauriError> {
+    let servidor = http_state.lock().unwrap_or_else(|e| e.into_inner()).take();
+
+    match servidor {
+        Some(servidor) => {
+            servidor.parar().await;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
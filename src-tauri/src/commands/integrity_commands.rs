@@ -0,0 +1,93 @@
+use std::path::{Path, PathBuf};
+use tauri::State;
+use crate::types::{RelatorioIntegridade, TauriError};
+use crate::{config, integridade, pdf_processor, resultados};
+use crate::commands::directory_commands::get_output_directory;
+
+/// Verifica os arquivos de resultado contra os checksums registrados
+#[tauri::command]
+pub async fn verify_result_integrity() -> Result<RelatorioIntegridade, TauriError> {
+    let output_dir = get_output_directory().await?;
+
+    integridade::verificar_integridade(Path::new(&output_dir))
+        .map_err(|e| TauriError::processing(
+            format!("Erro ao verificar integridade dos resultados: {}", e),
+            output_dir.clone(),
+        ))
+}
+
+/// Resolve um arquivo divergente: `acao` "regenerar" (a partir do PDF de origem) ou "lixeira"
+#[tauri::command]
+pub async fn resolve_integrity_issue(arquivo: String, acao: String, config_state: State<'_, config::ConfigState>) -> Result<serde_json::Value, TauriError> {
+    let output_dir = get_output_directory().await?;
+    let raiz = PathBuf::from(&output_dir);
+
+    let manifesto = integridade::carregar_manifesto(&raiz)
+        .map_err(|e| TauriError::parse(
+            format!("Erro ao carregar manifesto de checksums: {}", e),
+            raiz.join(integridade::ARQUIVO_CHECKSUMS).to_string_lossy().to_string(),
+        ))?;
+
+    let entrada = manifesto.arquivos.get(&arquivo)
+        .ok_or_else(|| TauriError::not_found(
+            format!("Arquivo não registrado no manifesto de checksums: {}", arquivo),
+            arquivo.clone(),
+        ))?;
+    let path = integridade::caminho_do_arquivo(&raiz, &arquivo);
+
+    match acao.as_str() {
+        "regenerar" => {
+            if !entrada.pode_regenerar() {
+                return Err(TauriError::validation(
+                    "PDF de origem indisponível; o arquivo não pode ser regenerado",
+                    arquivo.clone(),
+                ));
+            }
+            let origens: Vec<PathBuf> = entrada.pdfs_de_origem().into_iter().map(PathBuf::from).collect();
+
+            let app_config = config::ler_config(&config_state);
+            let opcoes = pdf_processor::OpcoesProcessamento::from(&app_config);
+            let regras = app_config.routing_rules;
+
+            // Reprocessar refaz o md/html; as propostas extraídas refazem um JSON de licitação
+            let mut reextraidas = Vec::new();
+            for origem in origens {
+                let resultado = pdf_processor::processar_pdf_com_consolidacao(&origem, &raiz, &regras, opcoes)
+                    .map_err(|e| TauriError::processing(
+                        format!("Erro ao regenerar arquivo: {:#}", e),
+                        origem.to_string_lossy().to_string(),
+                    ))?;
+                reextraidas.push((origem, resultado.propostas));
+            }
+
+            let regravados = if entrada.origens_pdf.is_empty() {
+                Vec::new()
+            } else {
+                resultados::regenerar_licitacao(&raiz, &path, &regras, &reextraidas)
+                    .map_err(|e| TauriError::processing(format!("Erro ao regenerar arquivo: {:#}", e), arquivo.clone()))?
+            };
+
+            Ok(serde_json::json!({
+                "arquivo": arquivo,
+                "acao": acao,
+                "origem_pdf": reextraidas.first().map(|(pdf, _)| pdf.to_string_lossy()),
+                "origens_pdf": reextraidas.iter().map(|(pdf, _)| pdf.to_string_lossy()).collect::<Vec<_>>(),
+                "arquivos_regravados": regravados
+            }))
+        }
+        "lixeira" => {
+            let destino = integridade::mover_para_lixeira(&raiz, &path)
+                .map_err(|e| TauriError::filesystem(
+                    format!("Erro ao mover arquivo para a lixeira: {}", e),
+                    path.to_string_lossy().to_string(),
+                ))?;
+
+            Ok(serde_json::json!({
+                "arquivo": arquivo,
+                "acao": acao,
+                "destino": destino.to_string_lossy()
+            }))
+        }
+        _ => Err(TauriError::validation(format!("Ação inválida: {} (use \"regenerar\" ou \"lixeira\")", acao), None)),
+    }
+}
@@ -1,30 +1,50 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use tauri::State;
+use tracing::warn;
 use walkdir::WalkDir;
-use crate::types::TauriError;
+use chrono::Local;
+use crate::types::{
+    ComparacaoLicitacoes, ConsolidadoJson, FiltroArquivosLicitacao, FornecedorHistory, LicitacaoFileInfo, LicitacoesSummary, LimpezaResultados, MergeResult, MigracaoResultados, NormalizacaoUasg, PropostasPaginadas, RelatorioValidacao, ResultadoBuscaPropostas, ResumoGeral, RetentionPolicy, RoutingRule, TauriError,
+    ValidacaoLicitacao, VersaoAplicativo, MODALIDADE_CONCORRENCIA, MODALIDADE_DISPENSA, MODALIDADE_PREGAO,
+};
+use crate::{config, integridade, migracoes, paths, pdf_processor, resultados, retencao, routing, sicaf_processor, storage, validacao};
 
 /// Lista arquivos JSON em um diretório
 #[tauri::command]
-pub async fn list_json_files(directory: String) -> Result<Vec<String>, TauriError> {
+pub async fn list_json_files(directory: String, config_state: State<'_, config::ConfigState>) -> Result<Vec<String>, TauriError> {
     let path = PathBuf::from(&directory);
     
     if !path.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Diretório não encontrado: {}", directory),
-            details: Some(directory),
-        });
+        return Err(TauriError::filesystem(format!("Diretório não encontrado: {}", directory), directory));
     }
     
     let mut json_files = Vec::new();
+    let mut diretorios = vec![path.clone()];
     
-    for entry in WalkDir::new(&path)
-        .max_depth(2) // Limitar profundidade para evitar muitos arquivos
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-        .filter(|e| e.path().extension().map_or(false, |ext| ext == "json"))
-    {
-        json_files.push(entry.path().to_string_lossy().to_string());
+    // Na pasta de resultados padrão, incluir também os destinos das regras de roteamento
+    let output_dir = super::directory_commands::get_output_directory().await?;
+    if mesmo_diretorio(&path, &PathBuf::from(&output_dir)) {
+        for destino in routing::diretorios_roteados(&config::load_routing_rules(&config_state)) {
+            if destino.exists() && !diretorios.iter().any(|d| mesmo_diretorio(d, &destino)) {
+                diretorios.push(destino);
+            }
+        }
+    }
+    
+    for diretorio in &diretorios {
+        for entry in WalkDir::new(diretorio)
+            .max_depth(2) // Limitar profundidade para evitar muitos arquivos
+            .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !integridade::eh_pasta_ignorada(e.file_name()))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .filter(|e| paths::is_json_file(e.path()))
+        {
+            let file_path = entry.path().to_string_lossy().to_string();
+            if !json_files.contains(&file_path) {
+                json_files.push(file_path);
+            }
+        }
     }
     
     // Ordenar por data de modificação (mais recente primeiro)
@@ -44,65 +64,128 @@ pub async fn list_json_files(directory: String) -> Result<Vec<String>, TauriErro
     Ok(json_files)
 }
 
-/// Lê e retorna o conteúdo de um arquivo JSON
+/// Lista os JSONs de licitação da pasta de resultados (e destinos roteados) com filtros
+///
+/// `sort_by` aceita "mtime" (padrão), "valor_total" ou "total_propostas", sempre do maior para o menor.
 #[tauri::command]
-pub async fn read_json_file(file_path: String) -> Result<serde_json::Value, TauriError> {
-    let path = PathBuf::from(&file_path);
-    
-    if !path.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Arquivo não encontrado: {}", file_path),
-            details: Some(file_path),
-        });
+pub async fn list_licitacao_files(
+    filter: Option<FiltroArquivosLicitacao>,
+    sort_by: Option<String>,
+    config_state: State<'_, config::ConfigState>
+) -> Result<Vec<LicitacaoFileInfo>, TauriError> {
+    let filtro = filter.unwrap_or_default();
+    for data in [&filtro.date_from, &filtro.date_to, &filtro.homologation_from, &filtro.homologation_to].into_iter().flatten() {
+        if chrono::NaiveDate::parse_from_str(data.trim(), "%Y-%m-%d").is_err() {
+            return Err(TauriError::validation(format!("Data inválida: {} (use AAAA-MM-DD)", data), data.clone()));
+        }
     }
+    if let Some(modalidade) = filtro.modalidade.as_deref().filter(|m| !m.trim().is_empty()) {
+        let modalidades = [MODALIDADE_PREGAO, MODALIDADE_DISPENSA, MODALIDADE_CONCORRENCIA];
+        if !modalidades.contains(&resultados::normalizar_busca(modalidade.trim()).as_str()) {
+            return Err(TauriError::validation(
+                format!("Modalidade inválida: {} (use {})", modalidade, modalidades.join(", ")),
+                modalidade.to_string(),
+            ));
+        }
+    }
+
+    let ordenacao = match sort_by.as_deref().filter(|campo| !campo.is_empty()) {
+        Some(campo) => resultados::OrdenacaoArquivos::de_nome(campo)
+            .ok_or_else(|| TauriError::validation(
+                format!("Campo de ordenação inválido: {} (use {})", campo, resultados::OrdenacaoArquivos::NOMES.join(", ")),
+                campo.to_string(),
+            ))?,
+        None => resultados::OrdenacaoArquivos::default(),
+    };
+
+    let output_dir = super::directory_commands::get_output_directory().await?;
+    let diretorios = resultados::diretorios_resultados(Path::new(&output_dir), &config::load_routing_rules(&config_state));
+    Ok(resultados::listar_licitacoes_filtradas(&diretorios, &filtro, ordenacao))
+}
+
+/// Lê e retorna o conteúdo de um arquivo JSON
+///
+/// JSONs de licitação gravados com um esquema antigo são devolvidos já migrados (o arquivo não é alterado).
+#[tauri::command]
+pub async fn read_json_file(file_path: String, config_state: State<'_, config::ConfigState>) -> Result<serde_json::Value, TauriError> {
+    let path = paths::ensure_within_allowed(Path::new(&file_path), &config::ler_config(&config_state))?;
     
-    if path.extension().map_or(true, |ext| ext != "json") {
-        return Err(TauriError {
-            error_type: "ValidationError".to_string(),
-            message: "O arquivo deve ter extensão .json".to_string(),
-            details: Some(file_path),
-        });
+    if !paths::is_json_file(&path) {
+        return Err(TauriError::validation("O arquivo deve ter extensão .json", file_path));
     }
     
     match std::fs::read_to_string(&path) {
         Ok(content) => {
             match serde_json::from_str::<serde_json::Value>(&content) {
+                Ok(json) if json.get("propostas").is_some() && migracoes::precisa_migrar(&json) => {
+                    migracoes::migrar_licitacao_json(json)
+                        .map_err(|e| TauriError::parse(format!("Erro ao migrar JSON: {:#}", e), file_path))
+                }
                 Ok(json) => Ok(json),
-                Err(e) => Err(TauriError {
-                    error_type: "ParseError".to_string(),
-                    message: format!("Erro ao analisar JSON: {}", e),
-                    details: Some(file_path),
-                })
+                Err(e) => Err(TauriError::parse(format!("Erro ao analisar JSON: {}", e), file_path))
             }
         }
-        Err(e) => Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao ler arquivo: {}", e),
-            details: Some(file_path),
-        })
+        Err(e) => Err(TauriError::filesystem(format!("Erro ao ler arquivo: {}", e), file_path))
     }
 }
 
-/// Obtém informações detalhadas de um arquivo JSON
+/// Lê uma página das propostas de um JSON de resultado, opcionalmente ordenada
+///
+/// `sort_by` aceita "item", "valor_adjudicado" ou "fornecedor"; `order` aceita "asc" (padrão) ou "desc".
 #[tauri::command]
-pub async fn get_json_file_info(file_path: String) -> Result<serde_json::Value, TauriError> {
-    let path = PathBuf::from(&file_path);
+pub async fn read_json_propostas_paginated(
+    file_path: String,
+    offset: usize,
+    limit: usize,
+    sort_by: Option<String>,
+    order: Option<String>,
+    config_state: State<'_, config::ConfigState>
+) -> Result<PropostasPaginadas, TauriError> {
+    let path = paths::ensure_within_allowed(Path::new(&file_path), &config::ler_config(&config_state))?;
     
-    if !path.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Arquivo não encontrado: {}", file_path),
-            details: Some(file_path.clone()),
-        });
+    if !paths::is_json_file(&path) {
+        return Err(TauriError::validation("O arquivo deve ter extensão .json", file_path));
     }
     
+    if limit == 0 {
+        return Err(TauriError::validation("O limite da página deve ser maior que zero", None));
+    }
+    
+    let decrescente = match order.as_deref().unwrap_or("asc") {
+        "asc" => false,
+        "desc" => true,
+        outra => return Err(TauriError::validation(format!("Ordem inválida: {} (use asc ou desc)", outra), outra.to_string())),
+    };
+    
+    let ordenacao = match sort_by.as_deref().filter(|campo| !campo.is_empty()) {
+        Some(campo) => {
+            let campo_ordenacao = resultados::CampoOrdenacao::de_nome(campo)
+                .ok_or_else(|| TauriError::validation(
+                    format!("Campo de ordenação inválido: {} (use {})", campo, resultados::CampoOrdenacao::NOMES.join(", ")),
+                    campo.to_string(),
+                ))?;
+            Some((campo_ordenacao, decrescente))
+        }
+        None => None,
+    };
+    
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| TauriError::filesystem(format!("Erro ao ler arquivo: {}", e), file_path.clone()))?;
+    
+    resultados::paginar_propostas(&content, offset, limit, ordenacao)
+        .map_err(|e| TauriError::parse(format!("Erro ao analisar JSON: {:#}", e), file_path))
+}
+
+/// Obtém informações detalhadas de um arquivo JSON
+#[tauri::command]
+pub async fn get_json_file_info(file_path: String, config_state: State<'_, config::ConfigState>) -> Result<serde_json::Value, TauriError> {
+    let path = paths::ensure_within_allowed(Path::new(&file_path), &config::ler_config(&config_state))?;
+    
     // Obter metadados do arquivo
-    let metadata = std::fs::metadata(&path).map_err(|e| TauriError {
-        error_type: "FileSystemError".to_string(),
-        message: format!("Erro ao ler metadados do arquivo: {}", e),
-        details: Some(file_path.clone()),
-    })?;
+    let metadata = std::fs::metadata(&path).map_err(|e| TauriError::filesystem(
+        format!("Erro ao ler metadados do arquivo: {}", e),
+        file_path.clone(),
+    ))?;
     
     let file_name = path.file_name()
         .and_then(|n| n.to_str())
@@ -111,11 +194,7 @@ pub async fn get_json_file_info(file_path: String) -> Result<serde_json::Value,
     
     let file_size = metadata.len();
     let modified = metadata.modified()
-        .map_err(|e| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao ler data de modificação: {}", e),
-            details: Some(file_path.clone()),
-        })?;
+        .map_err(|e| TauriError::filesystem(format!("Erro ao ler data de modificação: {}", e), file_path.clone()))?;
     
     let modified_timestamp = modified.duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
@@ -151,13 +230,16 @@ pub async fn get_json_file_info(file_path: String) -> Result<serde_json::Value,
                         file_info["uasg"] = serde_json::Value::String(uasg.to_string());
                     }
                     
-                    if let Some(total_propostas) = json.get("total_propostas").and_then(|v| v.as_u64()) {
+                    // Arquivos antigos gravavam os totais como texto, ou nem os gravavam
+                    let totais = resultados::totais_do_arquivo(&json);
+                    if let Some(total_propostas) = totais.total_propostas {
                         file_info["total_propostas"] = serde_json::Value::Number(serde_json::Number::from(total_propostas));
                     }
                     
-                    if let Some(valor_total) = json.get("valor_total").and_then(|v| v.as_f64()) {
+                    if let Some(valor_total) = totais.valor_total {
                         file_info["valor_total"] = serde_json::Value::Number(serde_json::Number::from_f64(valor_total).unwrap_or(serde_json::Number::from(0)));
                     }
+                    file_info["inferred"] = serde_json::Value::Bool(totais.inferido);
                     
                     // Contar propostas se for um array
                     if let Some(propostas) = json.get("propostas").and_then(|v| v.as_array()) {
@@ -176,3 +258,374 @@ pub async fn get_json_file_info(file_path: String) -> Result<serde_json::Value,
     
     Ok(file_info)
 }
+
+/// Busca propostas por fornecedor, CNPJ ou descrição em todos os JSONs de licitação
+#[tauri::command]
+pub async fn search_propostas(query: String, fields: Vec<String>, limit: usize, config_state: State<'_, config::ConfigState>) -> Result<ResultadoBuscaPropostas, TauriError> {
+    if query.trim().is_empty() {
+        return Err(TauriError::validation("Informe um termo de busca", None));
+    }
+    
+    if let Some(campo) = fields.iter().find(|campo| !resultados::CAMPOS_BUSCA.contains(&campo.as_str())) {
+        return Err(TauriError::validation(
+            format!("Campo de busca inválido: {} (use {})", campo, resultados::CAMPOS_BUSCA.join(", ")),
+            campo.clone(),
+        ));
+    }
+    
+    let output_dir = super::directory_commands::get_output_directory().await?;
+    let diretorios = resultados::diretorios_resultados(Path::new(&output_dir), &config::load_routing_rules(&config_state));
+    let arquivos = resultados::listar_arquivos_licitacao(&diretorios);
+    let limite = if limit == 0 { resultados::LIMITE_BUSCA_PADRAO } else { limit };
+    
+    Ok(resultados::pesquisar_propostas(&arquivos, &query, &fields, limite))
+}
+
+/// Ficha do fornecedor: licitações, itens e valores adjudicados a um CNPJ, com os dados SICAF
+#[tauri::command]
+pub async fn get_fornecedor_history(cnpj: String, config_state: State<'_, config::ConfigState>) -> Result<FornecedorHistory, TauriError> {
+    if !cnpj.chars().any(|c| c.is_ascii_digit()) {
+        return Err(TauriError::validation(format!("CNPJ inválido: {}", cnpj), cnpj));
+    }
+    
+    let output_dir = super::directory_commands::get_output_directory().await?;
+    let diretorios = resultados::diretorios_resultados(Path::new(&output_dir), &config::load_routing_rules(&config_state));
+    let arquivos = resultados::listar_arquivos_licitacao(&diretorios);
+    let mut historico = resultados::historico_fornecedor(&arquivos, &cnpj);
+    
+    let sicaf_data = super::sicaf_commands::load_sicaf_data().await?;
+    historico.dados_sicaf = sicaf_processor::obter_dados_cnpj(&cnpj, &sicaf_data).cloned();
+    
+    Ok(historico)
+}
+
+/// Estatísticas agregadas dos JSONs de licitação para o dashboard
+#[tauri::command]
+pub async fn get_licitacoes_summary(config_state: State<'_, config::ConfigState>) -> Result<LicitacoesSummary, TauriError> {
+    let output_dir = super::directory_commands::get_output_directory().await?;
+    let diretorios = resultados::diretorios_resultados(Path::new(&output_dir), &config::load_routing_rules(&config_state));
+    let arquivos = resultados::listar_arquivos_licitacao(&diretorios);
+    
+    Ok(resultados::resumir_licitacoes(&arquivos))
+}
+
+/// Carrega o consolidado.json da pasta informada (ou da pasta de resultados)
+async fn carregar_consolidado(output_dir: Option<String>) -> Result<ConsolidadoJson, TauriError> {
+    let output_dir = match output_dir {
+        Some(dir) => dir,
+        None => super::directory_commands::get_output_directory().await?,
+    };
+    let path = Path::new(&output_dir).join(pdf_processor::ARQUIVO_CONSOLIDADO);
+    if !path.exists() {
+        return Err(TauriError::not_found(
+            format!("{} não encontrado em {}", pdf_processor::ARQUIVO_CONSOLIDADO, output_dir),
+            path.to_string_lossy().to_string(),
+        ));
+    }
+    
+    pdf_processor::carregar_consolidado(&path)
+        .map_err(|e| TauriError::parse(format!("{:#}", e), path.to_string_lossy().to_string()))
+}
+
+/// Devolve o consolidado.json da última gravação, com todas as licitações pela chave
+#[tauri::command]
+pub async fn get_consolidated_json(output_dir: Option<String>) -> Result<ConsolidadoJson, TauriError> {
+    carregar_consolidado(output_dir).await
+}
+
+/// Estatísticas do dashboard calculadas a partir do consolidado.json, sem abrir cada JSON de licitação
+#[tauri::command]
+pub async fn get_consolidated_summary(output_dir: Option<String>) -> Result<LicitacoesSummary, TauriError> {
+    let consolidado = carregar_consolidado(output_dir).await?;
+    Ok(resultados::resumir_consolidado(&consolidado))
+}
+
+/// Junta os JSONs de licitação de várias execuções, sem propostas repetidas
+///
+/// As propostas da mesma UASG + Pregão + Processo são reunidas e os arquivos por
+/// licitação e o resumo geral são regravados com os totais recalculados. JSONs lidos
+/// que não foram regravados (nome antigo ou destino diferente) vão para a lixeira, para
+/// não serem contados duas vezes.
+#[tauri::command]
+pub async fn merge_licitacao_jsons(output_dir: String, config_state: State<'_, config::ConfigState>) -> Result<MergeResult, TauriError> {
+    let raiz = PathBuf::from(&output_dir);
+    if !raiz.is_dir() {
+        return Err(TauriError::filesystem(format!("Diretório não encontrado: {}", output_dir), output_dir));
+    }
+    
+    mesclar_resultados(&raiz, output_dir, &config::load_routing_rules(&config_state))
+}
+
+/// Regrava os JSONs de licitação com UASG sem o zero à esquerda ("70011") na forma de 6 dígitos
+///
+/// Os arquivos antigos são fundidos com os da mesma licitação já normalizada, pelo mesmo
+/// caminho de `merge_licitacao_jsons`. Sem arquivos a corrigir, nada é regravado.
+#[tauri::command]
+pub async fn normalize_uasg_results(output_dir: String, config_state: State<'_, config::ConfigState>) -> Result<NormalizacaoUasg, TauriError> {
+    let raiz = PathBuf::from(&output_dir);
+    if !raiz.is_dir() {
+        return Err(TauriError::filesystem(format!("Diretório não encontrado: {}", output_dir), output_dir));
+    }
+    
+    let regras = config::load_routing_rules(&config_state);
+    let arquivos = resultados::listar_arquivos_licitacao(&resultados::diretorios_resultados(&raiz, &regras));
+    let nao_normalizados: Vec<String> = resultados::arquivos_com_uasg_nao_normalizada(&arquivos)
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    
+    let mesclagem = if nao_normalizados.is_empty() {
+        None
+    } else {
+        Some(mesclar_resultados(&raiz, output_dir, &regras)?)
+    };
+    
+    Ok(NormalizacaoUasg { arquivos_normalizados: nao_normalizados, mesclagem })
+}
+
+/// Reconsolida todos os JSONs de licitação da pasta, movendo para a lixeira os substituídos
+fn mesclar_resultados(raiz: &Path, output_dir: String, regras: &[RoutingRule]) -> Result<MergeResult, TauriError> {
+    let arquivos = resultados::listar_arquivos_licitacao(&resultados::diretorios_resultados(raiz, regras));
+    let mesclagem = resultados::mesclar_licitacoes(&arquivos);
+    let consolidacao = pdf_processor::consolidar(&mesclagem.propostas, raiz, regras);
+    
+    let arquivos_gerados = pdf_processor::persistir(&consolidacao, raiz, false, &[])
+        .map_err(|e| TauriError::filesystem(
+            format!("Erro ao salvar licitações mescladas: {:#}", e),
+            output_dir.clone(),
+        ))?;
+    if let Err(e) = storage::registrar_consolidacao(raiz, &consolidacao) {
+        warn!(erro = %format!("{:#}", e), "Erro ao atualizar o banco SQLite");
+    }
+    
+    // Os gerados na pasta padrão vêm só com o nome; os roteados, com o caminho completo
+    let gerados: Vec<PathBuf> = arquivos_gerados.iter()
+        .filter_map(|arquivo| raiz.join(arquivo).canonicalize().ok())
+        .collect();
+    let raiz_canonica = raiz.canonicalize().unwrap_or_else(|_| raiz.to_path_buf());
+    let mut arquivos_substituidos = Vec::new();
+    for lido in &mesclagem.arquivos_lidos {
+        let Some(canonico) = integridade::caminho_dentro_da_raiz(raiz, lido) else {
+            continue;
+        };
+        if gerados.contains(&canonico) {
+            continue;
+        }
+        match integridade::mover_para_lixeira(&raiz_canonica, &canonico) {
+            Ok(_) => arquivos_substituidos.push(lido.to_string_lossy().to_string()),
+            Err(e) => warn!(arquivo = %lido.display(), erro = %format!("{:#}", e), "Erro ao mover para a lixeira"),
+        }
+    }
+    
+    Ok(MergeResult {
+        arquivos_lidos: mesclagem.arquivos_lidos.len(),
+        total_licitacoes: consolidacao.licitacoes.len(),
+        total_propostas: consolidacao.total_propostas,
+        valor_total_geral: consolidacao.valor_total_geral,
+        duplicatas_removidas: mesclagem.duplicatas_removidas,
+        conflitos: mesclagem.conflitos,
+        arquivos_gerados,
+        arquivos_substituidos,
+        arquivos_com_erro: mesclagem.arquivos_com_erro,
+    })
+}
+
+/// Move um JSON de resultado para Resultados/Lixeira (com timestamp no nome)
+#[tauri::command]
+pub async fn delete_json_file(file_path: String, config_state: State<'_, config::ConfigState>) -> Result<String, TauriError> {
+    let output_dir = PathBuf::from(super::directory_commands::get_output_directory().await?);
+    let path = paths::ensure_within_allowed(Path::new(&file_path), &config::ler_config(&config_state))?;
+    
+    if !paths::is_json_file(&path) {
+        return Err(TauriError::validation("O arquivo deve ter extensão .json", file_path));
+    }
+    
+    // Canonicalizar antes de comparar impede escapar da pasta com `..` ou links
+    let canonico = integridade::caminho_dentro_da_raiz(&output_dir, &path)
+        .filter(|p| p.is_file())
+        .ok_or_else(|| TauriError::validation(
+            "Só é possível remover arquivos da pasta de resultados",
+            file_path.clone(),
+        ))?;
+    let raiz = output_dir.canonicalize().unwrap_or(output_dir);
+    
+    if canonico.starts_with(raiz.join(integridade::PASTA_LIXEIRA)) {
+        return Err(TauriError::validation("O arquivo já está na lixeira", file_path));
+    }
+    
+    let destino = integridade::mover_para_lixeira(&raiz, &canonico)
+        .map_err(|e| TauriError::filesystem(format!("Erro ao mover arquivo para a lixeira: {:#}", e), file_path))?;
+    
+    Ok(destino.to_string_lossy().to_string())
+}
+
+/// Renomeia um JSON de licitação da pasta de resultados e atualiza sua entrada no resumo_geral.json
+///
+/// O novo nome é sanitizado, precisa manter o prefixo `licitacao_` e a extensão .json, e o
+/// arquivo continua na mesma pasta. Retorna o novo caminho.
+#[tauri::command]
+pub async fn rename_result_file(old_path: String, new_name: String, config_state: State<'_, config::ConfigState>) -> Result<String, TauriError> {
+    let output_dir = PathBuf::from(super::directory_commands::get_output_directory().await?);
+    let path = paths::ensure_within_allowed(Path::new(&old_path), &config::ler_config(&config_state))?;
+    
+    let canonico = integridade::caminho_dentro_da_raiz(&output_dir, &path)
+        .filter(|p| p.is_file() && paths::is_json_file(p))
+        .ok_or_else(|| TauriError::validation(
+            "Só é possível renomear arquivos JSON da pasta de resultados",
+            old_path.clone(),
+        ))?;
+    let raiz = output_dir.canonicalize().unwrap_or(output_dir);
+    
+    if canonico.starts_with(raiz.join(integridade::PASTA_LIXEIRA)) {
+        return Err(TauriError::validation("Arquivos da lixeira não podem ser renomeados", old_path));
+    }
+    
+    let nome = paths::sanitize_filename(new_name.trim());
+    if !nome.starts_with(resultados::PREFIXO_LICITACAO) || !paths::is_json_file(Path::new(&nome)) {
+        return Err(TauriError::validation(
+            format!("O novo nome deve começar com {} e ter extensão .json", resultados::PREFIXO_LICITACAO),
+            new_name,
+        ));
+    }
+    
+    let destino = canonico.with_file_name(&nome);
+    if destino == canonico {
+        return Ok(destino.to_string_lossy().to_string());
+    }
+    if destino.exists() {
+        return Err(TauriError::validation(format!("Já existe um arquivo chamado {}", nome), destino.to_string_lossy().to_string()));
+    }
+    
+    integridade::renomear_artefato(&raiz, &canonico, &destino)
+        .map_err(|e| TauriError::filesystem(format!("{:#}", e), old_path.clone()))?;
+    
+    let diretorios = resultados::diretorios_resultados(&raiz, &config::load_routing_rules(&config_state));
+    resultados::renomear_no_resumo_geral(&raiz, &diretorios, &canonico, &destino)
+        .map_err(|e| TauriError::filesystem(
+            format!("Arquivo renomeado, mas o resumo geral não foi atualizado: {:#}", e),
+            destino.to_string_lossy().to_string(),
+        ))?;
+    
+    Ok(destino.to_string_lossy().to_string())
+}
+
+/// Regrava o resumo_geral.json a partir dos JSONs de licitação presentes (útil após mexer nos arquivos por fora)
+#[tauri::command]
+pub async fn rebuild_resumo_geral(config_state: State<'_, config::ConfigState>) -> Result<ResumoGeral, TauriError> {
+    let output_dir = super::directory_commands::get_output_directory().await?;
+    let raiz = PathBuf::from(&output_dir);
+    let arquivos = resultados::listar_arquivos_licitacao(
+        &resultados::diretorios_resultados(&raiz, &config::load_routing_rules(&config_state)),
+    );
+    
+    resultados::reconstruir_resumo_geral(&raiz, &arquivos)
+        .map_err(|e| TauriError::filesystem(format!("Erro ao reconstruir o resumo geral: {:#}", e), output_dir))
+}
+
+/// Compara duas versões de um JSON de licitação (ex.: antes e depois de reprocessar a ata)
+///
+/// As propostas são alinhadas por grupo, item e CNPJ; diferenças só de formatação de valor são ignoradas.
+#[tauri::command]
+pub async fn compare_licitacao_jsons(path_a: String, path_b: String, config_state: State<'_, config::ConfigState>) -> Result<ComparacaoLicitacoes, TauriError> {
+    let mut licitacoes = Vec::with_capacity(2);
+    for file_path in [&path_a, &path_b] {
+        let path = paths::ensure_within_allowed(Path::new(file_path), &config::ler_config(&config_state))?;
+        let licitacao = resultados::carregar_licitacao(&path)
+            .map_err(|e| TauriError::parse(format!("Erro ao ler {}: {:#}", file_path, e), file_path.clone()))?;
+        licitacoes.push(licitacao);
+    }
+
+    let mut comparacao = resultados::comparar_licitacoes(&licitacoes[0], &licitacoes[1]);
+    comparacao.arquivo_a = path_a;
+    comparacao.arquivo_b = path_b;
+    Ok(comparacao)
+}
+
+/// Valida um JSON de licitação: leitura estrita (campos desconhecidos e tipos errados) e invariantes
+///
+/// Os problemas vêm com o caminho do campo e a severidade; o arquivo não é alterado.
+#[tauri::command]
+pub async fn validate_licitacao_json(file_path: String, config_state: State<'_, config::ConfigState>) -> Result<ValidacaoLicitacao, TauriError> {
+    let path = paths::ensure_within_allowed(Path::new(&file_path), &config::ler_config(&config_state))?;
+    
+    if !paths::is_json_file(&path) {
+        return Err(TauriError::validation("O arquivo deve ter extensão .json", file_path));
+    }
+    
+    Ok(validacao::validar_arquivo(&path))
+}
+
+/// Valida todos os JSONs de licitação e grava o relatorio_validacao.json na pasta de resultados
+#[tauri::command]
+pub async fn validate_all_results(config_state: State<'_, config::ConfigState>) -> Result<RelatorioValidacao, TauriError> {
+    let output_dir = super::directory_commands::get_output_directory().await?;
+    let raiz = PathBuf::from(&output_dir);
+    let arquivos = resultados::listar_arquivos_licitacao(
+        &resultados::diretorios_resultados(&raiz, &config::load_routing_rules(&config_state)),
+    );
+    
+    tokio::task::spawn_blocking(move || validacao::validar_e_gravar(&raiz, &arquivos))
+        .await
+        .map_err(|e| TauriError::system(format!("Erro ao executar a validação: {}", e), None))?
+        .map_err(|e| TauriError::filesystem(format!("Erro ao gravar relatório de validação: {:#}", e), output_dir))
+}
+
+/// Move para Resultados/Arquivo/AAAA-MM os JSONs de licitação e relatórios .md que a política manda arquivar
+///
+/// Nada é apagado. Com `dry_run`, só retorna a lista do que seria movido.
+#[tauri::command]
+pub async fn cleanup_old_results(policy: RetentionPolicy) -> Result<LimpezaResultados, TauriError> {
+    let output_dir = super::directory_commands::get_output_directory().await?;
+    
+    if policy.max_age_days.is_none() && policy.keep_last_n.is_none() {
+        return Err(TauriError::validation("Informe max_age_days ou keep_last_n na política de retenção", None));
+    }
+    
+    retencao::aplicar_politica(Path::new(&output_dir), config::get_config_dir().ok().as_deref(), &policy, Local::now())
+        .map_err(|e| TauriError::filesystem(format!("Erro ao arquivar resultados antigos: {:#}", e), output_dir))
+}
+
+/// Apaga definitivamente os arquivos de Resultados/Lixeira
+#[tauri::command]
+pub async fn empty_results_trash() -> Result<usize, TauriError> {
+    let output_dir = super::directory_commands::get_output_directory().await?;
+    
+    integridade::esvaziar_lixeira(Path::new(&output_dir))
+        .map_err(|e| TauriError::filesystem(
+            format!("Erro ao esvaziar a lixeira: {:#}", e),
+            Path::new(&output_dir).join(integridade::PASTA_LIXEIRA).to_string_lossy().to_string(),
+        ))
+}
+
+/// Versão do aplicativo, versão atual do esquema dos JSONs e quantos arquivos ainda estão em esquemas antigos
+#[tauri::command]
+pub async fn get_app_version_and_migrations(config_state: State<'_, config::ConfigState>) -> Result<VersaoAplicativo, TauriError> {
+    let output_dir = super::directory_commands::get_output_directory().await?;
+    let diretorios = resultados::diretorios_resultados(Path::new(&output_dir), &config::load_routing_rules(&config_state));
+
+    Ok(VersaoAplicativo {
+        versao_app: env!("CARGO_PKG_VERSION").to_string(),
+        versao_esquema: migracoes::VERSAO_ESQUEMA,
+        arquivos_desatualizados: migracoes::contar_desatualizados(&resultados::listar_arquivos_licitacao(&diretorios)),
+    })
+}
+
+/// Atualiza no lugar os JSONs de licitação gravados com esquemas antigos, guardando uma cópia dos originais
+#[tauri::command]
+pub async fn migrate_all_results(config_state: State<'_, config::ConfigState>) -> Result<MigracaoResultados, TauriError> {
+    let output_dir = super::directory_commands::get_output_directory().await?;
+    let raiz = PathBuf::from(&output_dir);
+    let arquivos = resultados::listar_arquivos_licitacao(
+        &resultados::diretorios_resultados(&raiz, &config::load_routing_rules(&config_state)),
+    );
+
+    Ok(migracoes::migrar_resultados(&raiz, &arquivos))
+}
+
+/// Compara dois diretórios pelo caminho canônico (ou literal, se não existirem)
+fn mesmo_diretorio(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
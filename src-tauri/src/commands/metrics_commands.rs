@@ -0,0 +1,8 @@
+use crate::metrics;
+use crate::types::TauriError;
+
+/// Retorna as métricas no formato de exposição do Prometheus (o mesmo conteúdo de metrics.prom)
+#[tauri::command]
+pub async fn get_metrics_text() -> Result<String, TauriError> {
+    Ok(metrics::texto_metricas())
+}
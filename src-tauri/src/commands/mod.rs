@@ -5,6 +5,14 @@ pub mod directory_commands;
 pub mod json_commands;
 pub mod sicaf_commands;
 pub mod file_operations;
+pub mod catalogo_commands;
+pub mod export_commands;
+pub mod integrity_commands;
+pub mod http_api_commands;
+pub mod metrics_commands;
+pub mod watcher_commands;
+pub mod database_commands;
+pub mod backup_commands;
 
 // Re-exportar todos os comandos para uso fácil
 pub use pdf_commands::*;
@@ -13,3 +21,11 @@ pub use directory_commands::*;
 pub use json_commands::*;
 pub use sicaf_commands::*;
 pub use file_operations::*;
+pub use catalogo_commands::*;
+pub use export_commands::*;
+pub use integrity_commands::*;
+pub use http_api_commands::*;
+pub use metrics_commands::*;
+pub use watcher_commands::*;
+pub use database_commands::*;
+pub use backup_commands::*;
@@ -1,22 +1,195 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use std::collections::HashMap;
-use tauri::State;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::warn;
 use crate::types::*;
 use crate::pdf_processor;
-use walkdir::WalkDir;
-use chrono::Utc;
+use crate::extraction_debug;
+use crate::config;
+use crate::logs;
+use crate::metadados_pdf;
+use crate::paths;
+use crate::sessoes;
+use crate::indice_pdfs::{self, EntradaIndicePdf};
+use crate::integridade;
+use crate::resultados;
+use crate::retencao;
+use crate::rastreamento;
+use crate::i18n::{self, MsgKey};
+use crate::metrics::{self, Execucao, ModoExecucao, ResultadoExecucao};
+use chrono::{Local, Utc};
 
 // Estado global para rastrear o progresso do processamento
 pub type ProcessingState = Arc<Mutex<HashMap<String, ProcessingStatus>>>;
 
+/// Evento emitido a cada arquivo iniciado ou concluído
+pub const EVENTO_PROGRESSO: &str = "processing://progress";
+/// Evento emitido ao final do processamento de um diretório
+pub const EVENTO_CONCLUIDO: &str = "processing://done";
+
+/// Tempo que os processamentos têm para parar sozinhos quando o aplicativo é fechado
+pub const TEMPO_GRACA_ENCERRAMENTO: Duration = Duration::from_secs(3);
+
+/// Erro registrado nas sessões interrompidas por `clear_all_processing_state`
+const ERRO_ESTADO_LIMPO: &str = "estado de processamento limpo";
+
+/// Erro registrado no status para os PDFs que exigem senha
+const ERRO_PDF_PROTEGIDO: &str = "PDF protegido por senha";
+
+/// Registra a execução nas métricas; erros ao gravar o arquivo não afetam o processamento
+fn registrar_metricas(execucao: Execucao) {
+    if let Err(e) = metrics::registrar_execucao(&execucao) {
+        warn!(erro = %e, "Erro ao registrar métricas");
+    }
+}
+
+/// Acumula bytes e tempo de uma execução completa na configuração, para a estimativa do dry-run
+fn registrar_vazao(app: &AppHandle, bytes: u64, duracao: Duration) {
+    if bytes == 0 || duracao.is_zero() {
+        return;
+    }
+    let config_state = app.state::<config::ConfigState>();
+    if let Err(e) = config::atualizar_config(&config_state, |config| {
+        config.processed_bytes_total += bytes;
+        config.processing_seconds_total += duracao.as_secs_f64();
+        Ok(())
+    }) {
+        warn!(erro = %e, "Erro ao registrar vazão do processamento");
+    }
+}
+
+/// Critério de varredura: os parâmetros do comando ou, na falta deles, os padrões da configuração
+fn opcoes_varredura(max_depth: Option<usize>, exclude_patterns: Option<Vec<String>>, app_config: &AppConfig) -> OpcoesVarredura {
+    OpcoesVarredura {
+        max_depth: max_depth.unwrap_or(app_config.scan_max_depth),
+        exclude_patterns: exclude_patterns.unwrap_or_else(|| app_config.scan_exclude_patterns.clone()),
+    }
+}
+
+fn filtro_varredura(input_path: &Path, opcoes: &OpcoesVarredura, output_dir: Option<&Path>) -> Result<pdf_processor::FiltroVarredura, TauriError> {
+    pdf_processor::FiltroVarredura::novo(input_path, opcoes, output_dir)
+        .map_err(|e| TauriError::traduzido(ErrorKind::ValidationError, MsgKey::InvalidExcludePattern, &[("erro", &format!("{:#}", e))], None))
+}
+
+/// Registra os PDFs no índice de processados e como origem dos seus JSONs no manifesto de checksums
+///
+/// Falhas não afetam o processamento.
+fn registrar_no_indice(config_dir: Option<&Path>, output_dir: &Path, entradas: Vec<(String, EntradaIndicePdf)>) {
+    let origens: Vec<(String, Vec<String>)> = entradas.iter()
+        .map(|(_, entrada)| (entrada.arquivo.clone(), entrada.jsons.clone()))
+        .collect();
+    if let Err(e) = integridade::registrar_origens(output_dir, &origens) {
+        warn!(erro = %format!("{:#}", e), "Erro ao registrar a origem dos JSONs no manifesto");
+    }
+    
+    let Some(config_dir) = config_dir else {
+        return;
+    };
+    if let Err(e) = indice_pdfs::registrar_processados(config_dir, entradas) {
+        warn!(erro = %format!("{:#}", e), "Erro ao gravar índice de PDFs processados");
+    }
+}
+
+/// Grava os JSONs das licitações de um PDF processado e registra o PDF no índice de processados
+///
+/// As propostas entram nos JSONs já existentes como num reprocessamento, sem apagar as de outros
+/// PDFs do mesmo pregão, e o resumo geral é reconstruído. Devolve os JSONs gravados; o índice
+/// só é atualizado depois que eles estão em disco.
+pub(crate) fn persistir_pdf_processado(
+    input_path: &Path,
+    propostas: &[PropostaConsolidada],
+    output_path: &Path,
+    regras: &[RoutingRule],
+) -> anyhow::Result<Vec<String>> {
+    let json_file_paths: Vec<String> = if propostas.is_empty() {
+        Vec::new()
+    } else {
+        let arquivo_origem = input_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        resultados::substituir_propostas(output_path, regras, &arquivo_origem, propostas)?.arquivos_alterados
+    };
+    
+    if let Ok(hash) = integridade::sha256_arquivo(input_path) {
+        registrar_no_indice(config::get_config_dir().ok().as_deref(), output_path, vec![(hash, EntradaIndicePdf {
+            arquivo: input_path.to_string_lossy().to_string(),
+            processado_em: Utc::now().to_rfc3339(),
+            jsons: json_file_paths.clone(),
+        })]);
+    }
+    Ok(json_file_paths)
+}
+
+/// Aplica a política de retenção configurada, retornando quantos resultados foram arquivados
+///
+/// Falhas ficam no histórico de processamento, sem afetar o resultado do processamento.
+fn aplicar_retencao(output_dir: &Path, politica: &RetentionPolicy, session_id: &str) -> usize {
+    // Uma simulação não faz sentido na aplicação automática
+    let politica = RetentionPolicy { dry_run: false, ..politica.clone() };
+    let (movidos, erros) = match retencao::aplicar_politica(output_dir, config::get_config_dir().ok().as_deref(), &politica, Local::now()) {
+        Ok(limpeza) => (limpeza.arquivos.len(), limpeza.erros),
+        Err(e) => (0, vec![format!("{:#}", e)]),
+    };
+    
+    for erro in erros {
+        let _ = config::append_processing_log(logs::novo_log(
+            format!("Erro ao aplicar a política de retenção: {}", erro),
+            "warning".to_string(),
+            Some(session_id.to_string()),
+        ));
+    }
+    movidos
+}
+
+/// Move os PDFs concluídos para a subpasta Processados, retornando quantos foram movidos
+///
+/// Arquivos bloqueados (abertos em outro programa) são registrados no status e no log,
+/// sem interromper os demais.
+fn arquivar_processados(
+    arquivos: &[String],
+    input_dir: &Path,
+    output_dir: &Path,
+    session_id: &str,
+    processing_state: &ProcessingState
+) -> usize {
+    let mut arquivados = 0;
+    
+    for arquivo in arquivos {
+        match pdf_processor::arquivar_pdf(Path::new(arquivo), input_dir) {
+            Ok(destino) => {
+                arquivados += 1;
+                if let Err(e) = integridade::mover_origem(output_dir, Path::new(arquivo), &destino) {
+                    warn!(arquivo = %arquivo, erro = %format!("{:#}", e), "Erro ao atualizar a origem dos resultados");
+                }
+            }
+            Err(e) => {
+                let erro = format!("{:#}", e);
+                let _ = sessoes::with_state(processing_state, |state| {
+                    if let Some(status) = state.get_mut(session_id) {
+                        status.errors.push(format!("{}: {}", arquivo, erro));
+                    }
+                });
+                let _ = config::append_processing_log(logs::novo_log(
+                    erro,
+                    "error".to_string(),
+                    Some(session_id.to_string()),
+                ));
+            }
+        }
+    }
+    
+    arquivados
+}
+
 /// Processa um único arquivo PDF
 #[tauri::command]
 pub async fn process_pdf_file(
     file_path: String,
     output_dir: String,
     verbose: bool,
-    processing_state: State<'_, ProcessingState>
+    processing_state: State<'_, ProcessingState>,
+    config_state: State<'_, config::ConfigState>
 ) -> Result<ProcessingResult, TauriError> {
     let session_id = format!("pdf_file_{}", Utc::now().timestamp_millis());
     let input_path = PathBuf::from(&file_path);
@@ -24,222 +197,950 @@ pub async fn process_pdf_file(
     
     // Verificar se o arquivo existe
     if !input_path.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Arquivo não encontrado: {}", file_path),
-            details: Some(file_path.clone()),
-        });
+        return Err(TauriError::traduzido(ErrorKind::FileSystemError, MsgKey::FileNotFound, &[("arquivo", &file_path)], file_path.clone()));
     }
     
-    // Verificar se é um arquivo PDF
-    if input_path.extension().map_or(true, |ext| ext != "pdf") {
-        return Err(TauriError {
-            error_type: "ValidationError".to_string(),
-            message: "O arquivo deve ter extensão .pdf".to_string(),
-            details: Some(file_path.clone()),
-        });
-    }
+    // Verificar se é uma ata em formato aceito
+    let Some(tipo) = paths::TipoArquivoAta::do_caminho(&input_path) else {
+        return Err(TauriError::traduzido(ErrorKind::ValidationError, MsgKey::InvalidAtaExtension, &[], file_path.clone()));
+    };
     
     // Criar diretório de saída se não existir
     if let Err(e) = std::fs::create_dir_all(&output_path) {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao criar diretório de saída: {}", e),
-            details: Some(output_dir.clone()),
-        });
+        return Err(TauriError::traduzido(ErrorKind::FileSystemError, MsgKey::OutputDirectoryCreateFailed, &[("erro", &e)], output_dir.clone()));
     }
     
+    let app_config = config::ler_config(&config_state);
+    let opcoes = pdf_processor::OpcoesProcessamento::from(&app_config);
+    
     // Inicializar estado de processamento
-    {
-        let mut state = processing_state.lock().unwrap();
-        state.insert(session_id.clone(), ProcessingStatus {
+    sessoes::with_state(&processing_state, |state| {
+        sessoes::registrar(state, session_id.clone(), ProcessingStatus {
             is_processing: true,
             current_file: Some(file_path.clone()),
             processed_files: 0,
             total_files: 1,
             errors: Vec::new(),
             progress_percentage: 0.0,
-        });
-    }
+            cancelled: false,
+            cancel_flag: Arc::new(AtomicBool::new(false)),
+            propostas: Arc::default(),
+            started_at: Utc::now().to_rfc3339(),
+            finished_at: None,
+            resultado: None,
+        }, app_config.session_retention_minutes);
+    })?;
     
-    match pdf_processor::processar_pdf_com_consolidacao(&input_path, &output_path, verbose) {
-        Ok(propostas) => {
+    let regras = app_config.routing_rules;
+    let inicio = Instant::now();
+    let sessao = rastreamento::span_sessao(&session_id, verbose);
+    
+    match sessao.in_scope(|| pdf_processor::processar_pdf_com_consolidacao(&input_path, &output_path, &regras, opcoes)) {
+        Ok(resultado) => {
+            let propostas = resultado.propostas;
+            
+            let json_file_paths = match sessao.in_scope(|| persistir_pdf_processado(&input_path, &propostas, &output_path, &regras)) {
+                Ok(json_file_paths) => json_file_paths,
+                Err(e) => {
+                    sessoes::with_state(&processing_state, |state| {
+                        if let Some(status) = state.get_mut(&session_id) {
+                            sessoes::finalizar(status);
+                            status.errors.push(format!("Erro ao salvar JSON: {:#}", e));
+                        }
+                    })?;
+                    
+                    let mut execucao = Execucao::nova(ModoExecucao::Arquivo, ResultadoExecucao::Falha, inicio.elapsed());
+                    execucao.falhas.insert(metrics::FALHA_PERSISTENCIA, 1);
+                    registrar_metricas(execucao);
+                    
+                    return Err(TauriError::traduzido(
+                        ErrorKind::FileSystemError,
+                        MsgKey::LicitacaoJsonSaveFailed,
+                        &[("erro", &format!("{:#}", e))],
+                        output_dir,
+                    ));
+                }
+            };
+            
+            let mut execucao = Execucao::nova(ModoExecucao::Arquivo, ResultadoExecucao::Sucesso, inicio.elapsed());
+            execucao.arquivos_processados = 1;
+            execucao.propostas = propostas.len();
+            registrar_metricas(execucao);
+            
             // Atualizar progresso final
-            {
-                let mut state = processing_state.lock().unwrap();
+            sessoes::with_state(&processing_state, |state| {
                 if let Some(status) = state.get_mut(&session_id) {
                     status.processed_files = 1;
                     status.progress_percentage = 100.0;
-                    status.is_processing = false;
+                    sessoes::finalizar(status);
+                    status.propostas = Arc::new(propostas.clone());
+                    // PDFs escaneados não devem terminar com zero propostas sem explicação
+                    for aviso in &resultado.avisos {
+                        status.errors.push(format!("{}: {}", file_path, aviso));
+                    }
                 }
-            }
-            
-            // Gerar nome do arquivo de saída baseado no arquivo de entrada
-            let file_stem = input_path.file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("output");
-            let json_file_path = output_path.join(format!("{}.json", file_stem));
+            })?;
             
             Ok(ProcessingResult {
                 success: true,
-                message: format!("Arquivo processado com sucesso: {} propostas encontradas", propostas.len()),
+                message: i18n::t(MsgKey::FileProcessed, &[("propostas", &propostas.len())]),
                 propostas,
                 total_processed: 1,
-                json_file_path: Some(json_file_path.to_string_lossy().to_string()),
+                json_file_path: json_file_paths.first().cloned(),
+                json_file_paths,
                 session_id: Some(session_id),
+                skipped_files: Vec::new(),
+                arquivos_por_tipo: BTreeMap::from([(tipo.rotulo().to_string(), 1)]),
+                dry_run: None,
+                metrics: vec![resultado.metricas],
             })
         }
         Err(e) => {
             // Atualizar estado com erro
-            {
-                let mut state = processing_state.lock().unwrap();
+            sessoes::with_state(&processing_state, |state| {
                 if let Some(status) = state.get_mut(&session_id) {
-                    status.is_processing = false;
+                    sessoes::finalizar(status);
                     status.errors.push(format!("Erro ao processar arquivo: {}", e));
                 }
-            }
+            })?;
             
-            Err(TauriError {
-                error_type: "ProcessingError".to_string(),
-                message: format!("Erro ao processar arquivo: {}", e),
-                details: Some(file_path),
-            })
+            let mut execucao = Execucao::nova(ModoExecucao::Arquivo, ResultadoExecucao::Falha, inicio.elapsed());
+            execucao.falhas.insert(metrics::FALHA_PROCESSAMENTO, 1);
+            registrar_metricas(execucao);
+            
+            if metadados_pdf::eh_pdf_protegido(&e) {
+                return Err(TauriError::pdf_encrypted(ERRO_PDF_PROTEGIDO, file_path));
+            }
+            Err(TauriError::traduzido(ErrorKind::ProcessingError, MsgKey::FileProcessingFailed, &[("erro", &e)], file_path))
         }
     }
 }
 
+/// Reprocessa uma ata e troca as propostas dela nos resultados já gravados
+///
+/// Só os JSONs das licitações (UASG + pregão + processo) encontradas no arquivo são regravados;
+/// propostas de outros arquivos do mesmo pregão são mantidas. Sem `output_dir`, usa a pasta Resultados.
+#[tauri::command]
+pub async fn reprocess_pdf(file_path: String, output_dir: Option<String>, config_state: State<'_, config::ConfigState>) -> Result<ReprocessamentoPdf, TauriError> {
+    let input_path = PathBuf::from(&file_path);
+    if !input_path.exists() {
+        return Err(TauriError::traduzido(ErrorKind::FileSystemError, MsgKey::FileNotFound, &[("arquivo", &file_path)], file_path.clone()));
+    }
+    if paths::TipoArquivoAta::do_caminho(&input_path).is_none() {
+        return Err(TauriError::traduzido(ErrorKind::ValidationError, MsgKey::InvalidAtaExtension, &[], file_path.clone()));
+    }
+    
+    let output_dir = match output_dir {
+        Some(output_dir) => output_dir,
+        None => super::directory_commands::get_output_directory().await?,
+    };
+    let output_path = PathBuf::from(&output_dir);
+    let app_config = config::ler_config(&config_state);
+    let opcoes = pdf_processor::OpcoesProcessamento::from(&app_config);
+    let regras = app_config.routing_rules;
+    
+    let resultado = pdf_processor::processar_pdf_com_consolidacao(&input_path, &output_path, &regras, opcoes).map_err(|e| {
+        if metadados_pdf::eh_pdf_protegido(&e) {
+            return TauriError::pdf_encrypted(ERRO_PDF_PROTEGIDO, file_path.clone());
+        }
+        TauriError::traduzido(ErrorKind::ProcessingError, MsgKey::FileProcessingFailed, &[("erro", &e)], file_path.clone())
+    })?;
+    
+    // Sem propostas (ex.: OCR falhou de novo) os resultados antigos ficam como estão
+    let arquivo_origem = input_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let mut reprocessamento = if resultado.propostas.is_empty() {
+        ReprocessamentoPdf { arquivo: arquivo_origem, ..Default::default() }
+    } else {
+        resultados::substituir_propostas(&output_path, &regras, &arquivo_origem, &resultado.propostas).map_err(|e| TauriError::traduzido(
+            ErrorKind::FileSystemError,
+            MsgKey::LicitacaoJsonSaveFailed,
+            &[("erro", &format!("{:#}", e))],
+            output_dir.clone(),
+        ))?
+    };
+    reprocessamento.avisos = resultado.avisos;
+    
+    if !reprocessamento.arquivos_alterados.is_empty() {
+        if let Ok(hash) = integridade::sha256_arquivo(&input_path) {
+            registrar_no_indice(config::get_config_dir().ok().as_deref(), &output_path, vec![(hash, EntradaIndicePdf {
+                arquivo: file_path.clone(),
+                processado_em: Utc::now().to_rfc3339(),
+                jsons: reprocessamento.arquivos_alterados.clone(),
+            })]);
+        }
+    }
+    
+    Ok(reprocessamento)
+}
+
+/// Mostra o texto bruto extraído de uma ata, para a tela de diagnóstico de extrações ruins
+///
+/// Devolve os primeiros `max_chars` caracteres (de uma página, com `page` a partir de 1), nunca mais
+/// que `LIMITE_PREVIA_BYTES`, e quantas vezes "Adjudicado", "CNPJ" e "UASG" aparecem.
+#[tauri::command]
+pub async fn get_pdf_text_preview(file_path: String, max_chars: usize, page: Option<usize>, config_state: State<'_, config::ConfigState>) -> Result<PreviaTextoPdf, TauriError> {
+    let app_config = config::ler_config(&config_state);
+    let path = paths::ensure_within_allowed(Path::new(&file_path), &app_config)?;
+    if paths::TipoArquivoAta::do_caminho(&path).is_none() {
+        return Err(TauriError::traduzido(ErrorKind::ValidationError, MsgKey::InvalidAtaExtension, &[], file_path));
+    }
+    if page == Some(0) {
+        return Err(TauriError::validation("As páginas começam em 1", file_path));
+    }
+    let timeout = pdf_processor::OpcoesProcessamento::from(&app_config).timeout_extracao;
+    
+    let caminho = path.clone();
+    tauri::async_runtime::spawn_blocking(move || pdf_processor::previa_texto(&caminho, max_chars, page, timeout))
+        .await
+        .map_err(|e| TauriError::system(format!("Erro ao executar a extração: {}", e), file_path.clone()))?
+        .map_err(|e| {
+            let e = metadados_pdf::classificar_erro_extracao(&path, e);
+            if metadados_pdf::eh_pdf_protegido(&e) {
+                return TauriError::pdf_encrypted(ERRO_PDF_PROTEGIDO, file_path.clone());
+            }
+            TauriError::processing(format!("{:#}", e), file_path.clone())
+        })
+}
+
+/// Roda todas as extrações (ata e SICAF) sobre um texto colado, sem gravar nada
+///
+/// Devolve o valor de cada extração e o que cada padrão capturou, com as posições no texto
+/// normalizado; serve para o suporte diagnosticar atas de órgãos novos.
+#[tauri::command]
+pub async fn try_extraction_patterns(text: String) -> Result<RelatorioPadroes, TauriError> {
+    if text.len() > extraction_debug::LIMITE_TEXTO_BYTES {
+        return Err(TauriError::validation(
+            format!("Texto grande demais para o teste de padrões (máximo {} KB)", extraction_debug::LIMITE_TEXTO_BYTES / 1024),
+            format!("{} bytes", text.len()),
+        ));
+    }
+    
+    tauri::async_runtime::spawn_blocking(move || extraction_debug::run_all(&text))
+        .await
+        .map_err(|e| TauriError::system(format!("Erro ao executar os padrões: {}", e), None))
+}
+
+/// Atas de um diretório prontas para processar, ou o relatório do dry-run
+enum PreparacaoDiretorio {
+    Simulacao(DryRunReport),
+    Arquivos { pendentes: Vec<PathBuf>, pulados: Vec<String> },
+}
+
+/// Lista as atas (PDF, HTML e TXT) do diretório e separa as já processadas; com `simular`,
+/// devolve só o relatório do dry-run. A lista de pendentes define o total do progresso.
+fn preparar_diretorio(
+    input_dir: &str,
+    filtro: &pdf_processor::FiltroVarredura,
+    pular_processados: bool,
+    simular: bool,
+    bytes_por_segundo: Option<f64>,
+) -> Result<PreparacaoDiretorio, TauriError> {
+    let pendentes = pdf_processor::listar_atas(Path::new(input_dir), filtro);
+    if pendentes.is_empty() {
+        return Err(TauriError::traduzido(ErrorKind::ValidationError, MsgKey::NoAtaFilesFound, &[], input_dir.to_string()));
+    }
+    
+    let carregar_indice = || -> Result<_, TauriError> {
+        let config_dir = config::get_config_dir()?;
+        indice_pdfs::carregar_indice(&config_dir)
+            .map_err(|e| TauriError::traduzido(ErrorKind::ParseError, MsgKey::ProcessedIndexLoadFailed, &[("erro", &format!("{:#}", e))], None))
+    };
+    
+    if simular {
+        let indice = carregar_indice()?;
+        return Ok(PreparacaoDiretorio::Simulacao(indice_pdfs::simular_processamento(
+            input_dir,
+            &pendentes,
+            &indice,
+            pular_processados,
+            bytes_por_segundo,
+        )));
+    }
+    
+    if !pular_processados {
+        return Ok(PreparacaoDiretorio::Arquivos { pendentes, pulados: Vec::new() });
+    }
+    let (pendentes, pulados) = indice_pdfs::separar_ja_processados(pendentes, &carregar_indice()?);
+    Ok(PreparacaoDiretorio::Arquivos {
+        pendentes,
+        pulados: pulados.iter().map(|p| p.to_string_lossy().to_string()).collect(),
+    })
+}
+
 /// Processa múltiplos arquivos PDF em um diretório
+///
+/// Com `skip_already_processed`, PDFs cujo conteúdo já consta no índice de processados
+/// (mesmo renomeados) são pulados. Com `dry_run`, só devolve um `DryRunReport` com os arquivos
+/// encontrados e o tempo estimado, sem extrair texto nem gravar em Resultados.
+///
+/// `max_depth` e `exclude_patterns` limitam a varredura (padrões da configuração quando omitidos);
+/// a pasta de saída é sempre ignorada quando estiver dentro da pasta de entrada.
+///
+/// Com `fire_and_forget`, retorna assim que a sessão começa, só com o `session_id`; o resultado
+/// final chega pelo evento `processing://done` e fica disponível em `get_processing_result`.
+/// O dry-run sempre responde na hora.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)] // parâmetros nomeados do invoke do frontend
 pub async fn process_pdf_directory(
     input_dir: String,
     output_dir: String,
     verbose: bool,
     session_id: Option<String>,
-    processing_state: State<'_, ProcessingState>
+    skip_already_processed: Option<bool>,
+    dry_run: Option<bool>,
+    max_depth: Option<usize>,
+    exclude_patterns: Option<Vec<String>>,
+    fire_and_forget: Option<bool>,
+    app: AppHandle,
+    processing_state: State<'_, ProcessingState>,
+    config_state: State<'_, config::ConfigState>
 ) -> Result<ProcessingResult, TauriError> {
     let session_id = session_id.unwrap_or_else(|| format!("pdf_directory_{}", Utc::now().timestamp_millis()));
     
     let input_path = PathBuf::from(&input_dir);
-    let output_path = PathBuf::from(&output_dir);
     
     // Verificar se o diretório de entrada existe
     if !input_path.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Diretório de entrada não encontrado: {}", input_dir),
-            details: Some(input_dir.clone()),
-        });
+        return Err(TauriError::traduzido(
+            ErrorKind::FileSystemError,
+            MsgKey::InputDirectoryNotFound,
+            &[("diretorio", &input_dir)],
+            input_dir.clone(),
+        ));
     }
     
-    // Contar arquivos PDF no diretório
-    let total_files = WalkDir::new(&input_path)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-        .filter(|e| e.path().extension().map_or(false, |ext| ext == "pdf"))
-        .count();
+    let app_config = config::ler_config(&config_state);
+    let varredura = opcoes_varredura(max_depth, exclude_patterns, &app_config);
+    let filtro = filtro_varredura(&input_path, &varredura, Some(Path::new(&output_dir)))?;
     
-    if total_files == 0 {
-        return Err(TauriError {
-            error_type: "ValidationError".to_string(),
-            message: "Nenhum arquivo PDF encontrado no diretório especificado".to_string(),
-            details: Some(input_dir.clone()),
-        });
+    // Varredura, hash dos já processados e simulação leem o diretório inteiro: rodam numa
+    // thread de bloqueio para não travar os demais comandos
+    let (diretorio, pular_processados) = (input_dir.clone(), skip_already_processed.unwrap_or(false));
+    let (simular, bytes_por_segundo) = (dry_run.unwrap_or(false), app_config.bytes_por_segundo_medio());
+    let preparacao = tauri::async_runtime::spawn_blocking(move || preparar_diretorio(&diretorio, &filtro, pular_processados, simular, bytes_por_segundo))
+        .await
+        .map_err(|e| TauriError::system(format!("Erro ao listar as atas: {}", e), input_dir.clone()))??;
+    
+    let (pendentes, pulados) = match preparacao {
+        PreparacaoDiretorio::Arquivos { pendentes, pulados } => (pendentes, pulados),
+        // Dry-run: só lista o que seria processado, sem extrair texto nem gravar em Resultados
+        PreparacaoDiretorio::Simulacao(relatorio) => return Ok(ProcessingResult {
+            success: true,
+            message: i18n::t(MsgKey::DryRunSummary, &[("total", &relatorio.total_arquivos), ("processados", &relatorio.ja_processados)]),
+            propostas: Vec::new(),
+            total_processed: 0,
+            json_file_path: None,
+            json_file_paths: Vec::new(),
+            session_id: None,
+            skipped_files: Vec::new(),
+            arquivos_por_tipo: BTreeMap::new(),
+            dry_run: Some(relatorio),
+            metrics: Vec::new(),
+        }),
+    };
+    
+    if pendentes.is_empty() {
+        let resultado = ProcessingResult {
+            success: true,
+            message: i18n::t(MsgKey::AllFilesAlreadyProcessed, &[("total", &pulados.len())]),
+            propostas: Vec::new(),
+            total_processed: 0,
+            json_file_path: None,
+            json_file_paths: Vec::new(),
+            session_id: Some(session_id.clone()),
+            skipped_files: pulados,
+            arquivos_por_tipo: BTreeMap::new(),
+            dry_run: None,
+            metrics: Vec::new(),
+        };
+        // Quem não aguarda o retorno espera o evento de conclusão ou consulta `get_processing_result`
+        if fire_and_forget.unwrap_or(false) {
+            let agora = Utc::now().to_rfc3339();
+            sessoes::with_state(&processing_state, |state| {
+                sessoes::registrar(state, session_id, ProcessingStatus {
+                    is_processing: false,
+                    current_file: None,
+                    processed_files: 0,
+                    total_files: 0,
+                    errors: Vec::new(),
+                    progress_percentage: 100.0,
+                    cancelled: false,
+                    cancel_flag: Arc::new(AtomicBool::new(false)),
+                    propostas: Arc::default(),
+                    started_at: agora.clone(),
+                    finished_at: Some(agora),
+                    resultado: Some(Arc::new(Ok(resultado.clone()))),
+                }, app_config.session_retention_minutes);
+            })?;
+            let _ = app.emit(EVENTO_CONCLUIDO, resultado.clone());
+        }
+        return Ok(resultado);
     }
     
-    // Inicializar estado de processamento
-    {
-        let mut state = processing_state.lock().unwrap();
-        state.insert(session_id.clone(), ProcessingStatus {
+    executar_em_segundo_plano(SessaoDiretorio {
+        session_id,
+        input_dir,
+        output_dir,
+        verbose,
+        varredura,
+        pendentes,
+        pulados,
+        concluidos: Vec::new(),
+        propostas_anteriores: Vec::new(),
+        app_config,
+    }, app, processing_state.inner().clone(), !fire_and_forget.unwrap_or(false)).await
+}
+
+/// Retoma uma sessão de diretório interrompida (app fechado ou cancelamento)
+///
+/// Os PDFs já concluídos, com a mesma data de modificação, são pulados e suas
+/// propostas são consolidadas junto com as novas.
+#[tauri::command]
+pub async fn resume_processing(
+    session_id: String,
+    app: AppHandle,
+    processing_state: State<'_, ProcessingState>,
+    config_state: State<'_, config::ConfigState>
+) -> Result<ProcessingResult, TauriError> {
+    if sessoes::with_state(&processing_state, |state| state.get(&session_id).is_some_and(|s| s.is_processing))? {
+        return Err(TauriError::traduzido(ErrorKind::ValidationError, MsgKey::SessionInProgress, &[("sessao", &session_id)], session_id.clone()));
+    }
+    
+    let config_dir = config::get_config_dir()?;
+    let mut persistidas = sessoes::carregar_persistidas(&config_dir)
+        .map_err(|e| TauriError::traduzido(ErrorKind::ParseError, MsgKey::SessionsLoadFailed, &[("erro", &format!("{:#}", e))], None))?;
+    
+    let Some(sessao) = persistidas.remove(&session_id) else {
+        return Err(TauriError::traduzido(ErrorKind::NotFound, MsgKey::SessionNotFound, &[("sessao", &session_id)], session_id.clone()));
+    };
+    
+    if sessao.finished_at.is_some() && !sessao.cancelled {
+        return Err(TauriError::traduzido(ErrorKind::ValidationError, MsgKey::SessionAlreadyFinished, &[("sessao", &session_id)], session_id.clone()));
+    }
+    
+    let input_path = PathBuf::from(&sessao.input_dir);
+    if !input_path.exists() {
+        return Err(TauriError::traduzido(
+            ErrorKind::FileSystemError,
+            MsgKey::InputDirectoryNotFound,
+            &[("diretorio", &sessao.input_dir)],
+            sessao.input_dir.clone(),
+        ));
+    }
+    
+    let (concluidos, propostas_anteriores) = sessoes::carregar_parcial(&config_dir, &session_id)
+        .map_err(|e| TauriError::traduzido(ErrorKind::ParseError, MsgKey::SessionProposalsLoadFailed, &[("erro", &format!("{:#}", e))], session_id.clone()))?;
+    // Mesmo critério de varredura da execução original
+    let filtro = filtro_varredura(&input_path, &sessao.varredura, Some(Path::new(&sessao.output_dir)))?;
+    let pendentes = sessoes::arquivos_pendentes(pdf_processor::listar_atas(&input_path, &filtro), &concluidos);
+    
+    executar_em_segundo_plano(SessaoDiretorio {
+        session_id,
+        input_dir: sessao.input_dir,
+        output_dir: sessao.output_dir,
+        verbose: sessao.verbose,
+        varredura: sessao.varredura,
+        pendentes,
+        pulados: Vec::new(),
+        concluidos,
+        propostas_anteriores,
+        app_config: config::ler_config(&config_state),
+    }, app, processing_state.inner().clone(), true).await
+}
+
+/// Sessão de diretório a executar, nova ou retomada
+struct SessaoDiretorio {
+    session_id: String,
+    input_dir: String,
+    output_dir: String,
+    verbose: bool,
+    varredura: OpcoesVarredura,
+    /// PDFs ainda não processados
+    pendentes: Vec<PathBuf>,
+    /// PDFs pulados por já constarem no índice de processados
+    pulados: Vec<String>,
+    /// Arquivos concluídos numa execução anterior da mesma sessão
+    concluidos: Vec<CompletedFile>,
+    /// Propostas extraídas desses arquivos
+    propostas_anteriores: Vec<PropostaConsolidada>,
+    /// Configuração vigente quando a sessão foi iniciada
+    app_config: AppConfig,
+}
+
+/// Soma um arquivo concluído à contagem por formato
+fn contar_tipo(arquivos_por_tipo: &mut BTreeMap<String, usize>, arquivo: &str) {
+    if let Some(tipo) = paths::TipoArquivoAta::do_caminho(Path::new(arquivo)) {
+        *arquivos_por_tipo.entry(tipo.rotulo().to_string()).or_default() += 1;
+    }
+}
+
+/// Falhas ao espelhar a sessão em disco não interrompem o processamento
+fn espelhar_sessao(resultado: anyhow::Result<()>) {
+    if let Err(e) = resultado {
+        warn!(erro = %format!("{:#}", e), "Erro ao gravar sessão de processamento");
+    }
+}
+
+/// Registra a sessão no estado de processamento; a partir daqui ela aparece em `get_processing_status`
+///
+/// Devolve o sinal de cancelamento e o início da sessão.
+fn registrar_sessao_diretorio(sessao: &SessaoDiretorio, processing_state: &ProcessingState) -> Result<(Arc<AtomicBool>, String), TauriError> {
+    let started_at = Utc::now().to_rfc3339();
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    
+    sessoes::with_state(processing_state, |state| {
+        sessoes::registrar(state, sessao.session_id.clone(), ProcessingStatus {
             is_processing: true,
             current_file: None,
-            processed_files: 0,
-            total_files,
+            processed_files: sessao.concluidos.len(),
+            total_files: sessao.concluidos.len() + sessao.pendentes.len(),
             errors: Vec::new(),
             progress_percentage: 0.0,
+            cancelled: false,
+            cancel_flag: cancel_flag.clone(),
+            propostas: Arc::default(),
+            started_at: started_at.clone(),
+            finished_at: None,
+            resultado: None,
+        }, sessao.app_config.session_retention_minutes);
+    })?;
+    
+    Ok((cancel_flag, started_at))
+}
+
+/// Executa a sessão numa thread de bloqueio, fora do runtime assíncrono do Tauri
+///
+/// Extração, regex e gravação bloqueiam; rodando dentro da task, deixavam comandos como
+/// `get_processing_status` sem resposta até o fim do lote. O resultado também fica no status
+/// da sessão para `get_processing_result` e é emitido em `processing://done` em qualquer desfecho,
+/// inclusive falha ou panic. Sem `aguardar`, devolve na hora só o `session_id`.
+async fn executar_em_segundo_plano(
+    sessao: SessaoDiretorio,
+    app: AppHandle,
+    processing_state: ProcessingState,
+    aguardar: bool,
+) -> Result<ProcessingResult, TauriError> {
+    let session_id = sessao.session_id.clone();
+    let total_files = sessao.concluidos.len() + sessao.pendentes.len();
+    let pulados = sessao.pulados.clone();
+    let (cancel_flag, started_at) = registrar_sessao_diretorio(&sessao, &processing_state)?;
+    
+    let id = session_id.clone();
+    let pulados_evento = pulados.clone();
+    let tarefa = tauri::async_runtime::spawn(async move {
+        let (estado, app_execucao) = (Arc::clone(&processing_state), app.clone());
+        let execucao = tauri::async_runtime::spawn_blocking(move || {
+            executar_sessao_diretorio(sessao, cancel_flag, started_at, &app_execucao, &estado)
+        });
+        let resultado = execucao.await.unwrap_or_else(|e| {
+            Err(TauriError::system(format!("A tarefa de processamento terminou inesperadamente: {}", e), id.clone()))
         });
+        
+        // Um estado inacessível só impede a consulta posterior; o evento sai mesmo assim
+        let evento = sessoes::with_state(&processing_state, |state| sessoes::concluir(state, &id, resultado.clone(), pulados_evento.clone()))
+            .unwrap_or_else(|_| sessoes::resultado_do_evento(&id, resultado.clone(), 0, pulados_evento));
+        let _ = app.emit(EVENTO_CONCLUIDO, evento);
+        resultado
+    });
+    
+    if aguardar {
+        return tarefa.await.unwrap_or_else(|e| {
+            Err(TauriError::system(format!("A tarefa de processamento terminou inesperadamente: {}", e), session_id))
+        });
+    }
+    
+    Ok(ProcessingResult {
+        success: true,
+        message: i18n::t(MsgKey::ProcessingStarted, &[("total", &total_files), ("sessao", &session_id)]),
+        propostas: Vec::new(),
+        total_processed: 0,
+        json_file_path: None,
+        json_file_paths: Vec::new(),
+        session_id: Some(session_id),
+        skipped_files: pulados,
+        arquivos_por_tipo: BTreeMap::new(),
+        dry_run: None,
+        metrics: Vec::new(),
+    })
+}
+
+/// Processa os PDFs pendentes de uma sessão já registrada, espelhando o progresso em processing_sessions.json
+fn executar_sessao_diretorio(
+    sessao: SessaoDiretorio,
+    cancel_flag: Arc<AtomicBool>,
+    started_at: String,
+    app: &AppHandle,
+    processing_state: &ProcessingState
+) -> Result<ProcessingResult, TauriError> {
+    let SessaoDiretorio { session_id, input_dir, output_dir, verbose, varredura, pendentes, pulados, concluidos, propostas_anteriores, app_config } = sessao;
+    let _sessao = rastreamento::span_sessao(&session_id, verbose).entered();
+    let input_path = PathBuf::from(&input_dir);
+    let output_path = PathBuf::from(&output_dir);
+    let concluidos_antes = concluidos.len();
+    let total_files = concluidos_antes + pendentes.len();
+    
+    let config_dir = config::get_config_dir().ok();
+    
+    // Contagem por formato, incluindo os concluídos antes de uma retomada
+    let mut arquivos_por_tipo: BTreeMap<String, usize> = BTreeMap::new();
+    for concluido in &concluidos {
+        contar_tipo(&mut arquivos_por_tipo, &concluido.path);
+    }
+    
+    // Arquivos já concluídos que ainda estão na pasta também serão arquivados
+    let mut arquivos_concluidos: Vec<String> = concluidos.iter()
+        .filter(|c| Path::new(&c.path).exists())
+        .map(|c| c.path.clone())
+        .collect();
+    
+    if let Some(config_dir) = &config_dir {
+        espelhar_sessao(sessoes::persistir_sessao(config_dir, PersistedSession {
+            session_id: session_id.clone(),
+            input_dir: input_dir.clone(),
+            output_dir: output_dir.clone(),
+            verbose,
+            total_files,
+            started_at,
+            finished_at: None,
+            cancelled: false,
+            completed_files: concluidos,
+            errors: Vec::new(),
+            varredura,
+        }, app_config.session_retention_minutes));
     }
     
     // Processar todos os arquivos
-    let processing_state_clone = processing_state.clone();
     let session_id_clone = session_id.clone();
+    let regras = app_config.routing_rules.clone();
+    let opcoes = pdf_processor::OpcoesProcessamento::from(&app_config);
+    let arquivar = app_config.archive_processed;
+    // Medido antes do processamento, já que o arquivamento move os PDFs
+    let bytes_pendentes: u64 = pendentes.iter().filter_map(|p| std::fs::metadata(p).ok()).map(|m| m.len()).sum();
+    let inicio = Instant::now();
+    let mut falhas_extracao = 0u64;
+    let mut protegidos = 0usize;
+    // (hash, PDF, propostas) dos concluídos nesta execução, para o índice de processados
+    let mut concluidos_indice: Vec<(String, String, Vec<PropostaConsolidada>)> = Vec::new();
+    let mut metricas: Vec<FileProcessingMetrics> = Vec::new();
     
-    match pdf_processor::processar_diretorio_pdfs_com_progresso(
-        &input_path, 
+    // O próprio pipeline registra o histórico; falhas de log não interrompem o processamento
+    let inicio_mensagem = if concluidos_antes > 0 {
+        format!("Retomando processamento de {} arquivos em {} ({} já concluídos)", pendentes.len(), input_dir, concluidos_antes)
+    } else {
+        format!("Iniciando processamento de {} arquivos em {}", total_files, input_dir)
+    };
+    let _ = config::append_processing_log(logs::novo_log(
+        inicio_mensagem,
+        "info".to_string(),
+        Some(session_id.clone()),
+    ));
+    
+    match pdf_processor::processar_pdfs_com_progresso(
+        &pendentes, 
         &output_path, 
-        verbose,
-        |processed, total, current_file| {
-            // Atualizar progresso em tempo real
-            let mut state = processing_state_clone.lock().unwrap();
-            if let Some(status) = state.get_mut(&session_id_clone) {
-                status.processed_files = processed;
-                status.total_files = total;
-                status.current_file = current_file;
-                status.progress_percentage = if total > 0 { (processed as f64 / total as f64) * 100.0 } else { 0.0 };
+        &regras,
+        opcoes,
+        &cancel_flag,
+        |processed, _, evento| {
+            // A contagem inclui os arquivos concluídos antes de uma retomada
+            let processed = concluidos_antes + processed;
+            let total = total_files;
+            let percentage = if total > 0 { (processed as f64 / total as f64) * 100.0 } else { 0.0 };
+            let arquivo_evento = match &evento {
+                pdf_processor::EventoProgresso::Iniciado(arquivo)
+                | pdf_processor::EventoProgresso::Aviso(arquivo, _)
+                | pdf_processor::EventoProgresso::Concluido(arquivo, ..)
+                | pdf_processor::EventoProgresso::Falhou(arquivo, _)
+                | pdf_processor::EventoProgresso::Protegido(arquivo) => arquivo.clone(),
+            };
+            
+            // Notificar o frontend; o estado abaixo continua disponível para polling
+            let _ = app.emit(EVENTO_PROGRESSO, ProgressEvent {
+                session_id: session_id_clone.clone(),
+                processed,
+                total,
+                current_file: Some(arquivo_evento),
+                percentage,
+                elapsed_ms: inicio.elapsed().as_millis() as u64,
+            });
+            
+            // Atualizar progresso em tempo real; um estado inacessível não interrompe o lote
+            let _ = sessoes::with_state(processing_state, |state| {
+                if let Some(status) = state.get_mut(&session_id_clone) {
+                    status.processed_files = processed;
+                    status.total_files = total;
+                    status.progress_percentage = percentage;
+                    match &evento {
+                        pdf_processor::EventoProgresso::Iniciado(arquivo) => status.current_file = Some(arquivo.clone()),
+                        pdf_processor::EventoProgresso::Concluido(..) => {}
+                        pdf_processor::EventoProgresso::Aviso(arquivo, erro)
+                        | pdf_processor::EventoProgresso::Falhou(arquivo, erro) => {
+                            status.errors.push(format!("{}: {}", arquivo, erro));
+                        }
+                        pdf_processor::EventoProgresso::Protegido(arquivo) => {
+                            status.errors.push(format!("{}: {}", arquivo, ERRO_PDF_PROTEGIDO));
+                        }
+                    }
+                }
+            });
+            
+            // Registrar uma entrada por arquivo concluído (erros não interrompem os demais)
+            match evento {
+                pdf_processor::EventoProgresso::Iniciado(_) => {}
+                pdf_processor::EventoProgresso::Aviso(arquivo, aviso) => {
+                    let _ = config::append_processing_log(logs::novo_log(
+                        format!("Aviso em {}: {}", arquivo, aviso),
+                        "warning".to_string(),
+                        Some(session_id_clone.clone()),
+                    ));
+                }
+                pdf_processor::EventoProgresso::Concluido(arquivo, propostas, metricas_arquivo) => {
+                    metricas.push(metricas_arquivo);
+                    let _ = config::append_processing_log(
+                        logs::novo_log_progresso(&session_id_clone, processed, total, &arquivo)
+                    );
+                    if let Some(config_dir) = &config_dir {
+                        espelhar_sessao(sessoes::registrar_concluido(config_dir, &session_id_clone, Path::new(&arquivo), &propostas));
+                    }
+                    // O hash é calculado antes do arquivamento mover o PDF
+                    if let Ok(hash) = integridade::sha256_arquivo(Path::new(&arquivo)) {
+                        concluidos_indice.push((hash, arquivo.clone(), propostas));
+                    }
+                    contar_tipo(&mut arquivos_por_tipo, &arquivo);
+                    arquivos_concluidos.push(arquivo);
+                }
+                pdf_processor::EventoProgresso::Protegido(arquivo) => {
+                    protegidos += 1;
+                    let mensagem = format!("{}: {}", arquivo, ERRO_PDF_PROTEGIDO);
+                    if let Some(config_dir) = &config_dir {
+                        espelhar_sessao(sessoes::registrar_erro(config_dir, &session_id_clone, mensagem.clone()));
+                    }
+                    let _ = config::append_processing_log(logs::novo_log(
+                        mensagem,
+                        "error".to_string(),
+                        Some(session_id_clone.clone()),
+                    ));
+                }
+                pdf_processor::EventoProgresso::Falhou(arquivo, erro) => {
+                    falhas_extracao += 1;
+                    let mensagem = format!("Erro ao processar {}: {}", arquivo, erro);
+                    if let Some(config_dir) = &config_dir {
+                        espelhar_sessao(sessoes::registrar_erro(config_dir, &session_id_clone, mensagem.clone()));
+                    }
+                    let _ = config::append_processing_log(logs::novo_log(
+                        mensagem,
+                        "error".to_string(),
+                        Some(session_id_clone.clone()),
+                    ));
+                }
             }
         }
     ) {
-        Ok(propostas) => {
+        Ok(novas) => {
+            let cancelado = cancel_flag.load(Ordering::SeqCst);
+            
+            // Propostas da execução anterior entram na mesma consolidação
+            let mut propostas = propostas_anteriores;
+            propostas.extend(novas);
+            
             // Atualizar progresso final
-            {
-                let mut state = processing_state.lock().unwrap();
-                if let Some(status) = state.get_mut(&session_id) {
-                    status.processed_files = total_files;
-                    status.progress_percentage = 100.0;
-                    status.is_processing = false;
+            let processed_files = sessoes::with_state(processing_state, |state| {
+                match state.get_mut(&session_id) {
+                    Some(status) => {
+                        if cancelado {
+                            status.cancelled = true;
+                            status.current_file = None;
+                        } else {
+                            status.processed_files = total_files;
+                            status.progress_percentage = 100.0;
+                        }
+                        sessoes::finalizar(status);
+                        status.propostas = Arc::new(propostas.clone());
+                        status.processed_files
+                    }
+                    None => total_files,
                 }
+            })?;
+            
+            // Salvar JSON consolidado (parcial, se cancelado), pelo mesmo caminho usado na prévia
+            let consolidacao = pdf_processor::consolidar(&propostas, &output_path, &regras);
+            let duplicadas = consolidacao.propostas_duplicadas;
+            let resultado_execucao = if cancelado { ResultadoExecucao::Cancelado } else { ResultadoExecucao::Sucesso };
+            let mut execucao = Execucao::nova(ModoExecucao::Diretorio, resultado_execucao, inicio.elapsed());
+            execucao.arquivos_processados = processed_files.saturating_sub(concluidos_antes);
+            execucao.propostas = consolidacao.total_propostas;
+            if falhas_extracao > 0 {
+                execucao.falhas.insert(metrics::FALHA_EXTRACAO, falhas_extracao);
             }
             
-            // Salvar JSON consolidado
-            if let Err(e) = pdf_processor::salvar_json_consolidado(&propostas, &output_path, "consolidado.json", verbose) {
-                return Err(TauriError {
-                    error_type: "ProcessingError".to_string(),
-                    message: format!("Erro ao salvar JSON consolidado: {}", e),
-                    details: Some(output_dir),
-                });
+            // Se a gravação falhar, a sessão continua pendente em disco para ser retomada
+            if let Err(e) = pdf_processor::persistir(&consolidacao, &output_path, cancelado, &metricas) {
+                execucao.resultado = ResultadoExecucao::Falha;
+                execucao.duracao = inicio.elapsed();
+                execucao.falhas.insert(metrics::FALHA_PERSISTENCIA, 1);
+                registrar_metricas(execucao);
+                
+                return Err(TauriError::traduzido(ErrorKind::ProcessingError, MsgKey::ConsolidatedJsonSaveFailed, &[("erro", &e)], output_dir));
+            }
+            
+            execucao.duracao = inicio.elapsed();
+            registrar_metricas(execucao);
+            if !cancelado {
+                registrar_vazao(app, bytes_pendentes, inicio.elapsed());
             }
             
+            if let Some(config_dir) = &config_dir {
+                espelhar_sessao(sessoes::finalizar_persistida(config_dir, &session_id, cancelado));
+            }
+            let processado_em = Utc::now().to_rfc3339();
+            registrar_no_indice(config_dir.as_deref(), &output_path, concluidos_indice.into_iter()
+                .map(|(hash, arquivo, propostas_pdf)| (hash, EntradaIndicePdf {
+                    arquivo,
+                    processado_em: processado_em.clone(),
+                    jsons: consolidacao.caminhos_das_propostas(&propostas_pdf)
+                        .iter()
+                        .map(|caminho| caminho.to_string_lossy().to_string())
+                        .collect(),
+                }))
+                .collect());
+            
+            // Só depois de gravar o JSON os PDFs concluídos saem da pasta; falhas ficam onde estão
+            let arquivados = if arquivar {
+                arquivar_processados(&arquivos_concluidos, &input_path, &output_path, &session_id, processing_state)
+            } else {
+                0
+            };
+            
+            // Com uma política configurada, os resultados antigos vão para Resultados/Arquivo
+            let politica_retencao = app_config.retention_policy.as_ref();
+            let retidos = politica_retencao.map(|politica| aplicar_retencao(&output_path, politica, &session_id));
+            
+            // Devolver ao frontend as mesmas propostas que foram consolidadas
+            let (propostas, _) = pdf_processor::deduplicar_propostas(&propostas);
+            
             let json_file_path = output_path.join("resumo_geral.json");
             
-            Ok(ProcessingResult {
+            let mut message = if cancelado {
+                i18n::t(MsgKey::ProcessingCancelled, &[("processados", &processed_files), ("total", &total_files)])
+            } else {
+                i18n::t(MsgKey::ProcessingFinished, &[("total", &total_files)])
+            };
+            if concluidos_antes > 0 {
+                message.push_str(&i18n::t(MsgKey::ResumedFromPreviousRun, &[("quantidade", &concluidos_antes)]));
+            }
+            // A divisão por formato só interessa quando há atas além de PDFs
+            if arquivos_por_tipo.keys().any(|tipo| tipo != paths::TipoArquivoAta::Pdf.rotulo()) {
+                let por_tipo: Vec<String> = arquivos_por_tipo.iter()
+                    .map(|(tipo, quantidade)| format!("{} {}", quantidade, tipo.to_uppercase()))
+                    .collect();
+                message.push_str(&i18n::t(MsgKey::FilesByType, &[("tipos", &por_tipo.join(", "))]));
+            }
+            if protegidos > 0 {
+                message.push_str(&i18n::t(MsgKey::PasswordProtectedFiles, &[("quantidade", &protegidos)]));
+            }
+            if !pulados.is_empty() {
+                message.push_str(&i18n::t(MsgKey::SkippedFiles, &[("quantidade", &pulados.len())]));
+            }
+            if duplicadas > 0 {
+                message.push_str(&i18n::t(MsgKey::DuplicateProposalsIgnored, &[("quantidade", &duplicadas)]));
+            }
+            if arquivar {
+                message.push_str(&i18n::t(MsgKey::FilesArchived, &[("quantidade", &arquivados), ("pasta", &paths::PASTA_PROCESSADOS)]));
+            }
+            if let Some(retidos @ 1..) = retidos {
+                message.push_str(&i18n::t(MsgKey::OldResultsArchived, &[("quantidade", &retidos), ("pasta", &retencao::PASTA_ARQUIVO)]));
+            }
+            
+            let _ = config::append_processing_log(logs::novo_log(
+                message.clone(),
+                "success".to_string(),
+                Some(session_id.clone()),
+            ));
+            
+            let result = ProcessingResult {
                 success: true,
-                message: format!("Processamento concluído: {} arquivos processados", total_files),
+                message,
                 propostas,
-                total_processed: total_files,
+                total_processed: processed_files,
                 json_file_path: Some(json_file_path.to_string_lossy().to_string()),
+                json_file_paths: consolidacao.caminhos_licitacoes()
+                    .iter()
+                    .map(|caminho| caminho.to_string_lossy().to_string())
+                    .collect(),
                 session_id: Some(session_id),
-            })
+                skipped_files: pulados,
+                arquivos_por_tipo,
+                dry_run: None,
+                metrics: metricas,
+            };
+            
+            Ok(result)
         }
         Err(e) => {
             // Atualizar estado com erro
-            {
-                let mut state = processing_state.lock().unwrap();
+            sessoes::with_state(processing_state, |state| {
                 if let Some(status) = state.get_mut(&session_id) {
-                    status.is_processing = false;
+                    sessoes::finalizar(status);
                     status.errors.push(format!("Erro ao processar diretório: {}", e));
                 }
+            })?;
+            
+            let mut execucao = Execucao::nova(ModoExecucao::Diretorio, ResultadoExecucao::Falha, inicio.elapsed());
+            execucao.falhas.insert(metrics::FALHA_PROCESSAMENTO, 1);
+            if falhas_extracao > 0 {
+                execucao.falhas.insert(metrics::FALHA_EXTRACAO, falhas_extracao);
             }
+            registrar_metricas(execucao);
             
-            Err(TauriError {
-                error_type: "ProcessingError".to_string(),
-                message: format!("Erro ao processar diretório: {}", e),
-                details: Some(input_dir),
-            })
+            let _ = config::append_processing_log(logs::novo_log(
+                format!("Erro ao processar diretório: {}", e),
+                "error".to_string(),
+                Some(session_id.clone()),
+            ));
+            
+            Err(TauriError::traduzido(ErrorKind::ProcessingError, MsgKey::DirectoryProcessingFailed, &[("erro", &e)], input_dir))
         }
     }
 }
 
 /// Processa múltiplos arquivos PDF na pasta PDF fixa
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn process_pdf_fixed_directory(
     verbose: bool,
     session_id: Option<String>,
-    processing_state: State<'_, ProcessingState>
+    skip_already_processed: Option<bool>,
+    dry_run: Option<bool>,
+    fire_and_forget: Option<bool>,
+    app: AppHandle,
+    processing_state: State<'_, ProcessingState>,
+    config_state: State<'_, config::ConfigState>
 ) -> Result<ProcessingResult, TauriError> {
     let input_dir = super::directory_commands::get_pdf_directory().await?;
     let output_dir = super::directory_commands::get_output_directory().await?;
     
-    process_pdf_directory(input_dir, output_dir, verbose, session_id, processing_state).await
+    process_pdf_directory(input_dir, output_dir, verbose, session_id, skip_already_processed, dry_run, None, None, fire_and_forget, app, processing_state, config_state).await
+}
+
+/// Solicita o cancelamento de um processamento em andamento
+#[tauri::command]
+pub async fn cancel_processing(
+    session_id: String,
+    processing_state: State<'_, ProcessingState>
+) -> Result<bool, TauriError> {
+    sessoes::with_state(&processing_state, |state| match state.get_mut(&session_id) {
+        Some(status) => {
+            if !status.is_processing {
+                return Ok(false);
+            }
+            
+            // O loop verifica o sinal entre arquivos e para limpo
+            status.cancel_flag.store(true, Ordering::SeqCst);
+            status.cancelled = true;
+            Ok(true)
+        }
+        None => Err(TauriError::traduzido(ErrorKind::NotFound, MsgKey::SessionNotFound, &[("sessao", &session_id)], session_id.clone()))
+    })?
 }
 
 /// Obtém o status atual do processamento
@@ -248,16 +1149,34 @@ pub async fn get_processing_status(
     session_id: String,
     processing_state: State<'_, ProcessingState>
 ) -> Result<ProcessingStatus, TauriError> {
-    let state = processing_state.lock().unwrap();
-    
-    match state.get(&session_id) {
-        Some(status) => Ok(status.clone()),
-        None => Err(TauriError {
-            error_type: "NotFound".to_string(),
-            message: format!("Sessão de processamento não encontrada: {}", session_id),
-            details: Some(session_id),
-        })
-    }
+    sessoes::with_state(&processing_state, |state| state.get(&session_id).cloned())?
+        .ok_or_else(|| TauriError::traduzido(ErrorKind::NotFound, MsgKey::SessionNotFound, &[("sessao", &session_id)], session_id.clone()))
+}
+
+/// Resultado final de uma sessão de diretório, o mesmo entregue no evento `processing://done`
+///
+/// Serve às sessões iniciadas com `fire_and_forget`; enquanto a sessão roda, devolve erro de
+/// sessão em andamento. O resultado é descartado junto com a sessão, ao fim da retenção.
+#[tauri::command]
+pub async fn get_processing_result(
+    session_id: String,
+    processing_state: State<'_, ProcessingState>
+) -> Result<ProcessingResult, TauriError> {
+    sessoes::with_state(&processing_state, |state| sessoes::consultar_resultado(state, &session_id))?
+}
+
+/// Lista todas as sessões de processamento com seus status e horários, inclusive as de execuções anteriores
+#[tauri::command]
+pub async fn list_processing_sessions(
+    processing_state: State<'_, ProcessingState>
+) -> Result<Vec<ProcessingSession>, TauriError> {
+    // Sessões interrompidas por um reinício só existem no espelho em disco
+    let persistidas = config::get_config_dir()
+        .ok()
+        .and_then(|dir| sessoes::carregar_persistidas(&dir).ok())
+        .unwrap_or_default();
+    
+    sessoes::with_state(&processing_state, |state| sessoes::listar(state, &persistidas))
 }
 
 /// Limpa o estado de processamento (útil para limpeza)
@@ -266,51 +1185,123 @@ pub async fn clear_processing_state(
     session_id: String,
     processing_state: State<'_, ProcessingState>
 ) -> Result<(), TauriError> {
-    let mut state = processing_state.lock().unwrap();
-    state.remove(&session_id);
-    Ok(())
+    sessoes::with_state(&processing_state, |state| {
+        state.remove(&session_id);
+    })
 }
 
-/// Lista arquivos PDF em um diretório
+/// Limpa o estado de todas as sessões, cancelando as que ainda estão em andamento
+///
+/// Retorna quantas sessões foram removidas da memória.
 #[tauri::command]
-pub async fn list_pdf_files(directory: String) -> Result<Vec<String>, TauriError> {
-    let path = PathBuf::from(&directory);
+pub async fn clear_all_processing_state(
+    processing_state: State<'_, ProcessingState>
+) -> Result<usize, TauriError> {
+    let (removidas, interrompidas) = sessoes::with_state(&processing_state, |state| {
+        let interrompidas = sessoes::interromper_ativas(state, ERRO_ESTADO_LIMPO);
+        let removidas = state.len();
+        state.clear();
+        (removidas, interrompidas)
+    })?;
     
-    if !path.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Diretório não encontrado: {}", directory),
-            details: Some(directory),
-        });
+    if let Ok(config_dir) = config::get_config_dir() {
+        espelhar_sessao(sessoes::interromper_persistidas(&config_dir, &interrompidas, ERRO_ESTADO_LIMPO));
+    }
+    Ok(removidas)
+}
+
+/// Cancela os processamentos em andamento ao fechar o aplicativo
+///
+/// Aguarda até `graca` que os loops parem sozinhos; as sessões que continuarem ativas
+/// são finalizadas com o erro "aplicação encerrada", tanto em memória quanto no espelho
+/// em disco, para que o frontend não as encontre presas em processamento ao reabrir.
+pub fn encerrar_processamentos(processing_state: &ProcessingState, graca: Duration) -> Vec<String> {
+    let ativas = sessoes::with_state(processing_state, sessoes::cancelar_ativas).unwrap_or_default();
+    if ativas.is_empty() {
+        return ativas;
     }
     
-    let mut pdf_files = Vec::new();
-    
-    for entry in WalkDir::new(&path)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-        .filter(|e| e.path().extension().map_or(false, |ext| ext == "pdf"))
-    {
-        pdf_files.push(entry.path().to_string_lossy().to_string());
+    let limite = Instant::now() + graca;
+    while Instant::now() < limite {
+        let em_andamento = sessoes::with_state(processing_state, |state| {
+            ativas.iter().any(|id| state.get(id).is_some_and(|s| s.is_processing))
+        });
+        if !em_andamento.unwrap_or(false) {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
     }
     
-    Ok(pdf_files)
+    let _ = sessoes::with_state(processing_state, |state| sessoes::interromper_ativas(state, sessoes::ERRO_APLICACAO_ENCERRADA));
+    if let Ok(config_dir) = config::get_config_dir() {
+        espelhar_sessao(sessoes::interromper_persistidas(&config_dir, &ativas, sessoes::ERRO_APLICACAO_ENCERRADA));
+    }
+    ativas
 }
 
-/// Valida se um arquivo PDF é válido
+/// Lista arquivos PDF em um diretório e, com `include_text_formats`, também as atas em HTML e TXT
+///
+/// Usa o mesmo critério de varredura de `process_pdf_directory`.
 #[tauri::command]
-pub async fn validate_pdf_file(file_path: String) -> Result<bool, TauriError> {
-    let path = PathBuf::from(&file_path);
+pub async fn list_pdf_files(
+    directory: String,
+    include_text_formats: Option<bool>,
+    max_depth: Option<usize>,
+    exclude_patterns: Option<Vec<String>>,
+    config_state: State<'_, config::ConfigState>
+) -> Result<Vec<String>, TauriError> {
+    let path = PathBuf::from(&directory);
     
     if !path.exists() {
-        return Ok(false);
+        return Err(TauriError::traduzido(ErrorKind::FileSystemError, MsgKey::DirectoryNotFound, &[("diretorio", &directory)], directory.clone()));
     }
     
-    // Verificar se é um arquivo PDF
-    if path.extension().map_or(false, |ext| ext == "pdf") {
-        Ok(true)
+    let varredura = opcoes_varredura(max_depth, exclude_patterns, &config::ler_config(&config_state));
+    let resultados = paths::database_subdir(paths::SubDir::Resultados).ok();
+    let filtro = filtro_varredura(&path, &varredura, resultados.as_deref())?;
+    let arquivos = if include_text_formats.unwrap_or(false) {
+        pdf_processor::listar_atas(&path, &filtro)
     } else {
-        Ok(false)
-    }
+        pdf_processor::listar_pdfs(&path, &filtro)
+    };
+    
+    Ok(arquivos.iter().map(|arquivo| arquivo.to_string_lossy().to_string()).collect())
+}
+
+/// Verifica se o arquivo é um PDF que pode ser processado (legível e sem senha)
+#[tauri::command]
+pub async fn validate_pdf_file(file_path: String) -> Result<PdfValidation, TauriError> {
+    let path = PathBuf::from(&file_path);
+    
+    // Carregar o documento pode demorar em PDFs grandes
+    tokio::task::spawn_blocking(move || metadados_pdf::validar_pdf(&path))
+        .await
+        .map_err(|e| TauriError::traduzido(ErrorKind::SystemError, MsgKey::PdfValidationFailed, &[("erro", &e)], file_path))
+}
+
+/// Mostra como as propostas serão agrupadas em licitações, sem gravar arquivos
+///
+/// Usa as propostas informadas ou, se ausentes, as extraídas na sessão indicada.
+#[tauri::command]
+pub async fn preview_consolidation(
+    session_id: Option<String>,
+    propostas: Option<Vec<PropostaConsolidada>>,
+    processing_state: State<'_, ProcessingState>,
+    config_state: State<'_, config::ConfigState>
+) -> Result<PreviaConsolidacao, TauriError> {
+    let propostas: Arc<Vec<PropostaConsolidada>> = match (propostas, session_id) {
+        (Some(propostas), _) => Arc::new(propostas),
+        (None, Some(session_id)) => {
+            match sessoes::with_state(&processing_state, |state| state.get(&session_id).map(|status| status.propostas.clone()))? {
+                Some(propostas) => propostas,
+                None => return Err(TauriError::traduzido(ErrorKind::NotFound, MsgKey::SessionNotFound, &[("sessao", &session_id)], session_id.clone())),
+            }
+        }
+        (None, None) => return Err(TauriError::traduzido(ErrorKind::ValidationError, MsgKey::SessionOrProposalsRequired, &[], None)),
+    };
+    
+    let output_dir = super::directory_commands::get_output_directory().await?;
+    let consolidacao = pdf_processor::consolidar(&propostas, &PathBuf::from(&output_dir), &config::load_routing_rules(&config_state));
+    
+    Ok(consolidacao.previa())
 }
@@ -1,39 +1,195 @@
-use std::path::PathBuf;
-use crate::types::{TauriError, ProcessingSicafResult, SicafData, PropostaConsolidada};
-use crate::sicaf_processor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use chrono::Utc;
+use tauri::{AppHandle, Emitter, State};
+use crate::types::{
+    DadosCnpjOnline, EstatisticasSicaf, ErrorKind, TauriError, ProcessingSicafResult, ProcessingStatus, ProgressEvent, SicafData, PropostaConsolidada, VerificacaoSicaf,
+};
+use crate::sicaf_processor::{self, EventoProgressoSicaf};
+use crate::{config, consulta_cnpj, metadados_pdf, paths, rastreamento, sessoes, validators};
+use crate::i18n::MsgKey;
+use crate::commands::pdf_commands::{ProcessingState, EVENTO_PROGRESSO};
 use crate::commands::directory_commands::{get_sicaf_directory, get_output_directory};
 use crate::commands::json_commands::read_json_file;
 
 /// Processa arquivos PDF SICAF na pasta SICAF fixa
+///
+/// O progresso fica disponível em `get_processing_status` e é emitido em `processing://progress`,
+/// como no processamento de licitações; a sessão pode ser cancelada com `cancel_processing`.
+/// Os registros são mesclados por CNPJ ao sicaf_dados.json; com `replace`, o arquivo é substituído.
 #[tauri::command]
-pub async fn process_sicaf_pdfs(verbose: bool) -> Result<ProcessingSicafResult, TauriError> {
+pub async fn process_sicaf_pdfs(
+    verbose: bool,
+    session_id: Option<String>,
+    replace: Option<bool>,
+    app: AppHandle,
+    processing_state: State<'_, ProcessingState>,
+    config_state: State<'_, config::ConfigState>
+) -> Result<ProcessingSicafResult, TauriError> {
+    let session_id = session_id.unwrap_or_else(|| format!("sicaf_{}", Utc::now().timestamp_millis()));
     let sicaf_dir = get_sicaf_directory().await?;
-    let sicaf_path = PathBuf::from(&sicaf_dir);
-    
-    match sicaf_processor::processar_sicaf_pdfs(&sicaf_path, verbose) {
-        Ok(result) => {
-            // Salvar dados em JSON se houver dados processados
-            if !result.sicaf_data.is_empty() {
-                let output_dir = get_output_directory().await?;
-                let output_path = PathBuf::from(&output_dir);
+    let output_dir = get_output_directory().await?;
+    
+    // Inicializar estado de processamento
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    let app_config = config::ler_config(&config_state);
+    sessoes::with_state(&processing_state, |state| {
+        sessoes::registrar(state, session_id.clone(), ProcessingStatus {
+            is_processing: true,
+            current_file: None,
+            processed_files: 0,
+            total_files: 0,
+            errors: Vec::new(),
+            progress_percentage: 0.0,
+            cancelled: false,
+            cancel_flag: cancel_flag.clone(),
+            propostas: Arc::default(),
+            started_at: Utc::now().to_rfc3339(),
+            finished_at: None,
+            resultado: None,
+        }, app_config.session_retention_minutes);
+    })?;
+    
+    // Extração e gravação fora do runtime assíncrono: lotes grandes levam minutos
+    let state = processing_state.inner().clone();
+    let id = session_id.clone();
+    let diretorio = sicaf_dir.clone();
+    let inicio = Instant::now();
+    let sessao = rastreamento::span_sessao(&session_id, verbose);
+    let resultado = tauri::async_runtime::spawn_blocking(move || -> anyhow::Result<ProcessingSicafResult> {
+        let _sessao = sessao.entered();
+        let result = sicaf_processor::processar_sicaf_pdfs_com_progresso(
+            Path::new(&diretorio),
+            &cancel_flag,
+            |processed, total, evento| {
+                let percentage = if total > 0 { (processed as f64 / total as f64) * 100.0 } else { 0.0 };
+                let arquivo = match &evento {
+                    EventoProgressoSicaf::Iniciado(arquivo)
+                    | EventoProgressoSicaf::Concluido(arquivo)
+                    | EventoProgressoSicaf::SemDados(arquivo)
+                    | EventoProgressoSicaf::Falhou(arquivo, _)
+                    | EventoProgressoSicaf::Protegido(arquivo) => arquivo.clone(),
+                };
+                
+                let _ = app.emit(EVENTO_PROGRESSO, ProgressEvent {
+                    session_id: id.clone(),
+                    processed,
+                    total,
+                    current_file: Some(arquivo),
+                    percentage,
+                    elapsed_ms: inicio.elapsed().as_millis() as u64,
+                });
                 
-                if let Err(e) = sicaf_processor::salvar_sicaf_json(&result.sicaf_data, &output_path, verbose) {
-                    return Err(TauriError {
-                        error_type: "ProcessingError".to_string(),
-                        message: format!("Erro ao salvar dados SICAF: {}", e),
-                        details: Some(sicaf_dir),
-                    });
-                }
+                let _ = sessoes::with_state(&state, |ativas| {
+                    let Some(status) = ativas.get_mut(&id) else { return };
+                    status.processed_files = processed;
+                    status.total_files = total;
+                    status.progress_percentage = percentage;
+                    match evento {
+                        EventoProgressoSicaf::Iniciado(arquivo) => status.current_file = Some(arquivo),
+                        EventoProgressoSicaf::Concluido(_) => {}
+                        EventoProgressoSicaf::SemDados(arquivo) => {
+                            status.errors.push(format!("{}: dados SICAF não encontrados", arquivo));
+                        }
+                        EventoProgressoSicaf::Falhou(arquivo, erro) => status.errors.push(format!("{}: {}", arquivo, erro)),
+                        EventoProgressoSicaf::Protegido(arquivo) => {
+                            status.errors.push(format!("{}: PDF protegido por senha", arquivo));
+                        }
+                    }
+                });
+            },
+        )?;
+        
+        // Salvar dados em JSON se houver dados processados
+        let mut result = result;
+        if result.sicaf_data.is_empty() {
+            return Ok(result);
+        }
+        if replace.unwrap_or(false) {
+            // Substituir o cadastro pelos PDFs desta rodada
+            sicaf_processor::salvar_sicaf_json(&result.sicaf_data, Path::new(&output_dir))
+                .map_err(|e| e.context("Erro ao salvar dados SICAF"))?;
+            result.adicionados = result.sicaf_data.len();
+        } else {
+            let mesclagem = sicaf_processor::salvar_sicaf_incremental(&result.sicaf_data, Path::new(&output_dir))
+                .map_err(|e| e.context("Erro ao salvar dados SICAF"))?;
+            result.adicionados = mesclagem.adicionados;
+            result.atualizados = mesclagem.atualizados;
+            result.mantidos = mesclagem.mantidos;
+        }
+        
+        Ok(result)
+    })
+    .await
+    .map_err(|e| TauriError::traduzido(ErrorKind::SystemError, MsgKey::SicafExecutionFailed, &[("erro", &e)], sicaf_dir.clone()))?;
+    
+    // Atualizar progresso final
+    sessoes::with_state(&processing_state, |state| {
+        if let Some(status) = state.get_mut(&session_id) {
+            if status.cancel_flag.load(Ordering::SeqCst) {
+                status.cancelled = true;
+                status.current_file = None;
+            } else if resultado.is_ok() {
+                status.processed_files = status.total_files;
+                status.progress_percentage = 100.0;
+            }
+            if let Err(e) = &resultado {
+                status.errors.push(format!("{:#}", e));
             }
-            
-            Ok(result)
+            sessoes::finalizar(status);
         }
-        Err(e) => Err(TauriError {
-            error_type: "ProcessingError".to_string(),
-            message: format!("Erro ao processar PDFs SICAF: {}", e),
-            details: Some(sicaf_dir),
-        })
+    })?;
+    
+    let mut result = resultado
+        .map_err(|e| TauriError::traduzido(ErrorKind::ProcessingError, MsgKey::SicafProcessingFailed, &[("erro", &format!("{:#}", e))], sicaf_dir))?;
+    result.session_id = Some(session_id);
+    Ok(result)
+}
+
+/// Processa um PDF SICAF escolhido pelo usuário e mescla o registro ao sicaf_dados.json
+///
+/// Quando o layout não é reconhecido, o erro traz nos detalhes os primeiros caracteres depois
+/// de "CNPJ:". Com `archive`, o PDF é copiado para a pasta SICAF.
+#[tauri::command]
+pub async fn process_single_sicaf_pdf(file_path: String, verbose: bool, archive: Option<bool>) -> Result<SicafData, TauriError> {
+    let pdf_path = PathBuf::from(&file_path);
+    if !pdf_path.is_file() {
+        return Err(TauriError::traduzido(ErrorKind::FileSystemError, MsgKey::FileNotFound, &[("arquivo", &file_path)], file_path.clone()));
     }
+    if !paths::is_pdf_file(&pdf_path) {
+        return Err(TauriError::validation(format!("O arquivo SICAF deve ser um PDF: {}", file_path), file_path.clone()));
+    }
+    let output_dir = get_output_directory().await?;
+    let sicaf_dir = get_sicaf_directory().await?;
+    
+    let caminho = pdf_path.clone();
+    let sessao = rastreamento::span_sessao(&file_path, verbose);
+    let leitura = tauri::async_runtime::spawn_blocking(move || sessao.in_scope(|| sicaf_processor::ler_pdf_sicaf(&caminho)))
+        .await
+        .map_err(|e| TauriError::traduzido(ErrorKind::SystemError, MsgKey::SicafExecutionFailed, &[("erro", &e)], file_path.clone()))?;
+    
+    let sicaf_data = match leitura {
+        Ok(sicaf_processor::LeituraSicaf::Extraido(sicaf_data)) => *sicaf_data,
+        Ok(sicaf_processor::LeituraSicaf::NaoReconhecido { trecho }) => {
+            return Err(TauriError::traduzido(ErrorKind::ProcessingError, MsgKey::SicafLayoutNotRecognized, &[("arquivo", &file_path)], trecho));
+        }
+        Err(e) if metadados_pdf::eh_pdf_protegido(&e) => return Err(TauriError::pdf_encrypted("PDF protegido por senha", file_path)),
+        Err(e) => {
+            return Err(TauriError::traduzido(ErrorKind::ProcessingError, MsgKey::SicafProcessingFailed, &[("erro", &format!("{:#}", e))], file_path));
+        }
+    };
+    
+    sicaf_processor::salvar_sicaf_incremental(std::slice::from_ref(&sicaf_data), Path::new(&output_dir))
+        .map_err(|e| TauriError::traduzido(ErrorKind::FileSystemError, MsgKey::SicafSaveFailed, &[("erro", &format!("{:#}", e))], output_dir.clone()))?;
+    
+    if archive.unwrap_or(false) {
+        sicaf_processor::copiar_para_pasta_sicaf(&pdf_path, Path::new(&sicaf_dir))
+            .map_err(|e| TauriError::filesystem(format!("{:#}", e), sicaf_dir.clone()))?;
+    }
+    
+    Ok(sicaf_data)
 }
 
 /// Carrega dados SICAF do arquivo JSON
@@ -48,21 +204,69 @@ pub async fn load_sicaf_data() -> Result<Vec<SicafData>, TauriError> {
     
     match sicaf_processor::carregar_sicaf_json(&sicaf_json_path) {
         Ok(data) => Ok(data),
-        Err(e) => Err(TauriError {
-            error_type: "ProcessingError".to_string(),
-            message: format!("Erro ao carregar dados SICAF: {}", e),
-            details: Some(sicaf_json_path.to_string_lossy().to_string()),
-        })
+        Err(e) => Err(TauriError::traduzido(
+            ErrorKind::ProcessingError,
+            MsgKey::SicafLoadFailed,
+            &[("erro", &e)],
+            sicaf_json_path.to_string_lossy().to_string(),
+        ))
     }
 }
 
+/// Lista os registros SICAF, filtrando por razão social, nome fantasia ou CNPJ
+#[tauri::command]
+pub async fn list_sicaf_entries(filter: Option<String>) -> Result<Vec<SicafData>, TauriError> {
+    let sicaf_data = load_sicaf_data().await?;
+    match filter {
+        Some(filtro) => Ok(sicaf_processor::filtrar_sicaf(&sicaf_data, &filtro).into_iter().cloned().collect()),
+        None => Ok(sicaf_data),
+    }
+}
+
+/// Remove um fornecedor do sicaf_dados.json e devolve o registro removido
+#[tauri::command]
+pub async fn delete_sicaf_entry(cnpj: String) -> Result<SicafData, TauriError> {
+    let output_dir = get_output_directory().await?;
+    let mut sicaf_data = load_sicaf_data().await?;
+
+    let removido = sicaf_processor::remover_cnpj_sicaf(&mut sicaf_data, &cnpj)
+        .ok_or_else(|| TauriError::traduzido(ErrorKind::NotFound, MsgKey::SicafCnpjNotFound, &[("cnpj", &cnpj)], cnpj.clone()))?;
+
+    sicaf_processor::salvar_sicaf_json(&sicaf_data, Path::new(&output_dir))
+        .map_err(|e| TauriError::traduzido(ErrorKind::FileSystemError, MsgKey::SicafSaveFailed, &[("erro", &format!("{:#}", e))], output_dir))?;
+    Ok(removido)
+}
+
+/// Total de registros SICAF, cadastros vencidos e UFs representadas
+#[tauri::command]
+pub async fn get_sicaf_stats() -> Result<EstatisticasSicaf, TauriError> {
+    let sicaf_data = load_sicaf_data().await?;
+    Ok(sicaf_processor::estatisticas_sicaf(&sicaf_data, chrono::Local::now().date_naive()))
+}
+
 /// Verifica se um CNPJ existe nos dados SICAF
 #[tauri::command]
 pub async fn verify_cnpj_sicaf(cnpj: String) -> Result<bool, TauriError> {
+    if !validators::validar_cnpj(&cnpj) {
+        return Err(TauriError::traduzido(ErrorKind::ValidationError, MsgKey::InvalidCnpj, &[("cnpj", &cnpj)], cnpj.clone()));
+    }
+
     let sicaf_data = load_sicaf_data().await?;
     Ok(sicaf_processor::verificar_cnpj_sicaf(&cnpj, &sicaf_data))
 }
 
+/// Verifica um CNPJ nos dados SICAF, informando se o cadastro está vencido e quantos dias faltam
+#[tauri::command]
+pub async fn verify_cnpj_sicaf_detailed(cnpj: String) -> Result<VerificacaoSicaf, TauriError> {
+    if !validators::validar_cnpj(&cnpj) {
+        return Err(TauriError::traduzido(ErrorKind::ValidationError, MsgKey::InvalidCnpj, &[("cnpj", &cnpj)], cnpj.clone()));
+    }
+
+    let sicaf_data = load_sicaf_data().await?;
+    let hoje = chrono::Local::now().date_naive();
+    Ok(sicaf_processor::verificar_cnpj_sicaf_detalhado(&cnpj, &sicaf_data, hoje))
+}
+
 /// Obtém dados SICAF para um CNPJ específico
 #[tauri::command]
 pub async fn get_cnpj_sicaf_data(cnpj: String) -> Result<Option<SicafData>, TauriError> {
@@ -73,40 +277,90 @@ pub async fn get_cnpj_sicaf_data(cnpj: String) -> Result<Option<SicafData>, Taur
     }
 }
 
+/// Razão social, situação cadastral e município de um CNPJ na BrasilAPI, para fornecedores fora do SICAF
+///
+/// A consulta fica em cache em Database/Config/cnpj_cache.json pelos dias de `cnpj_cache_days`.
+#[tauri::command]
+pub async fn lookup_cnpj_online(cnpj: String, config_state: State<'_, config::ConfigState>) -> Result<DadosCnpjOnline, TauriError> {
+    if !validators::validar_cnpj(&cnpj) {
+        return Err(TauriError::traduzido(ErrorKind::ValidationError, MsgKey::InvalidCnpj, &[("cnpj", &cnpj)], cnpj.clone()));
+    }
+
+    let cache_path = config::get_config_dir()?.join(consulta_cnpj::ARQUIVO_CACHE_CNPJ);
+    let validade_dias = config::ler_config(&config_state).cnpj_cache_days;
+    consulta_cnpj::consultar(&cnpj, &cache_path, validade_dias).await
+        .map_err(|e| TauriError::network(format!("Erro ao consultar o CNPJ na BrasilAPI: {:#}", e), cnpj))
+}
+
 /// Gera relatório de comparação entre licitação e SICAF
+///
+/// `name_match_threshold` substitui o limiar da configuração para o passe por razão social.
+/// `format` é "json" (padrão), "csv" ou "ambos"; com `status`, só as propostas nesses status
+/// (ex.: "SICAF Não Encontrado") entram na listagem. Com `enrich_online`, os CNPJs não encontrados
+/// são consultados na BrasilAPI; após a primeira falha de rede, os demais vêm só do cache ou saem marcados
+/// como offline. Retorna os caminhos dos arquivos gerados.
 #[tauri::command]
-pub async fn generate_sicaf_comparison_report(json_file_path: String) -> Result<String, TauriError> {
+pub async fn generate_sicaf_comparison_report(
+    json_file_path: String,
+    name_match_threshold: Option<f64>,
+    format: Option<String>,
+    status: Option<Vec<String>>,
+    enrich_online: Option<bool>,
+    config_state: State<'_, config::ConfigState>
+) -> Result<Vec<String>, TauriError> {
+    let config_atual = config::ler_config(&config_state);
+    let limiar = name_match_threshold.unwrap_or(config_atual.sicaf_name_match_threshold);
+    if !(0.0..=1.0).contains(&limiar) {
+        return Err(TauriError::traduzido(ErrorKind::ValidationError, MsgKey::InvalidSimilarityThreshold, &[("limiar", &limiar)], None));
+    }
+    
+    let formato = match format.as_deref().filter(|f| !f.is_empty()) {
+        Some(nome) => sicaf_processor::FormatoRelatorio::de_nome(&nome.to_lowercase())
+            .ok_or_else(|| TauriError::validation(
+                format!("Formato de relatório inválido: {} (use {})", nome, sicaf_processor::FormatoRelatorio::NOMES.join(", ")),
+                nome.to_string(),
+            ))?,
+        None => sicaf_processor::FormatoRelatorio::default(),
+    };
+    
+    let filtro_status = match status.filter(|s| !s.is_empty()) {
+        Some(status) => Some(status.iter()
+            .map(|s| sicaf_processor::status_sicaf_canonico(s).ok_or_else(|| TauriError::validation(
+                format!("Status SICAF inválido: {} (use {})", s, sicaf_processor::STATUS_SICAF.join(", ")),
+                s.clone(),
+            )))
+            .collect::<Result<Vec<&str>, TauriError>>()?),
+        None => None,
+    };
+    
     // Carregar dados da licitação
-    let licitacao_data = read_json_file(json_file_path.clone()).await?;
+    let licitacao_data = read_json_file(json_file_path.clone(), config_state.clone()).await?;
     
     let propostas: Vec<PropostaConsolidada> = if let Some(propostas_array) = licitacao_data.get("propostas").and_then(|p| p.as_array()) {
         propostas_array.iter().filter_map(|p| {
             serde_json::from_value(p.clone()).ok()
         }).collect()
     } else {
-        return Err(TauriError {
-            error_type: "ValidationError".to_string(),
-            message: "Arquivo JSON não contém propostas válidas".to_string(),
-            details: Some(json_file_path),
-        });
+        return Err(TauriError::traduzido(ErrorKind::ValidationError, MsgKey::JsonWithoutProposals, &[], json_file_path));
     };
     
     // Carregar dados SICAF
     let sicaf_data = load_sicaf_data().await?;
     
+    let dados_online = if enrich_online.unwrap_or(false) {
+        let cache_path = config::get_config_dir()?.join(consulta_cnpj::ARQUIVO_CACHE_CNPJ);
+        let cnpjs = sicaf_processor::cnpjs_nao_encontrados(&propostas, &sicaf_data, limiar);
+        Some(consulta_cnpj::consultar_lote(cnpjs, &cache_path, config_atual.cnpj_cache_days).await)
+    } else {
+        None
+    };
+    
     // Gerar relatório
     let output_dir = get_output_directory().await?;
     let output_path = PathBuf::from(&output_dir);
     
-    match sicaf_processor::gerar_relatorio_comparacao(&propostas, &sicaf_data, &output_path, true) {
-        Ok(()) => {
-            let relatorio_path = output_path.join("relatorio_sicaf_comparacao.json");
-            Ok(relatorio_path.to_string_lossy().to_string())
-        }
-        Err(e) => Err(TauriError {
-            error_type: "ProcessingError".to_string(),
-            message: format!("Erro ao gerar relatório de comparação: {}", e),
-            details: Some(output_dir),
-        })
+    match sicaf_processor::gerar_relatorio_comparacao(&propostas, &sicaf_data, &output_path, limiar, formato, filtro_status.as_deref(), dados_online.as_ref()) {
+        Ok(arquivos) => Ok(arquivos.iter().map(|path| path.to_string_lossy().to_string()).collect()),
+        Err(e) => Err(TauriError::traduzido(ErrorKind::ProcessingError, MsgKey::ComparisonReportFailed, &[("erro", &e)], output_dir))
     }
 }
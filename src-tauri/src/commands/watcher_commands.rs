@@ -0,0 +1,123 @@
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, State};
+use crate::types::{TauriError, WatcherEvent};
+use crate::watcher::{self, PdfWatcherState};
+use crate::{config, integridade, logs, pdf_processor};
+use crate::commands::directory_commands::{get_output_directory, get_pdf_directory};
+use crate::commands::pdf_commands::persistir_pdf_processado;
+
+/// Evento emitido para cada PDF processado pelo observador
+pub const EVENTO_WATCHER: &str = "watcher://processed";
+
+/// Observa a pasta PDF fixa e processa automaticamente os PDFs novos
+#[tauri::command]
+pub async fn start_pdf_watcher(
+    app: AppHandle,
+    watcher_state: State<'_, PdfWatcherState>,
+    config_state: State<'_, config::ConfigState>
+) -> Result<String, TauriError> {
+    if watcher_state.lock().unwrap_or_else(|e| e.into_inner()).is_some() {
+        return Err(TauriError::validation("O observador de PDFs já está em execução", None));
+    }
+    
+    let pdf_dir = PathBuf::from(get_pdf_directory().await?);
+    let output_dir = PathBuf::from(get_output_directory().await?);
+    
+    let observador = watcher::iniciar(&pdf_dir, watcher::ESTABILIDADE_PADRAO, {
+        let pdf_dir = pdf_dir.clone();
+        let config_state = config_state.inner().clone();
+        move |pdf_path| {
+            // Configuração lida a cada arquivo para refletir alterações feitas com o observador ativo
+            let app_config = config::ler_config(&config_state);
+            let file_path = pdf_path.to_string_lossy().to_string();
+            
+            let processamento = pdf_processor::processar_pdf_com_consolidacao(
+                pdf_path,
+                &output_dir,
+                &app_config.routing_rules,
+                pdf_processor::OpcoesProcessamento::from(&app_config),
+            ).and_then(|resultado| {
+                // Só depois de gravado nos JSONs e no índice o PDF pode sair da pasta observada
+                persistir_pdf_processado(pdf_path, &resultado.propostas, &output_dir, &app_config.routing_rules)
+                    .map(|_| resultado)
+            });
+            
+            let evento = match processamento {
+                Ok(resultado) => {
+                    let mut message = format!("Arquivo processado automaticamente: {} propostas encontradas", resultado.propostas.len());
+                    let mut avisos = resultado.avisos;
+                    
+                    if app_config.archive_processed {
+                        match pdf_processor::arquivar_pdf(pdf_path, &pdf_dir) {
+                            Ok(destino) => {
+                                message.push_str(", arquivo arquivado");
+                                if let Err(e) = integridade::mover_origem(&output_dir, pdf_path, &destino) {
+                                    avisos.push(format!("{:#}", e));
+                                }
+                            }
+                            Err(e) => avisos.push(format!("{:#}", e)),
+                        }
+                    }
+                    
+                    WatcherEvent {
+                        file_path: file_path.clone(),
+                        success: true,
+                        message,
+                        total_propostas: resultado.propostas.len(),
+                        avisos,
+                    }
+                }
+                Err(e) => WatcherEvent {
+                    file_path: file_path.clone(),
+                    success: false,
+                    message: format!("Erro ao processar arquivo: {:#}", e),
+                    total_propostas: 0,
+                    avisos: Vec::new(),
+                },
+            };
+            
+            let log_type = if evento.success { "success" } else { "error" };
+            let _ = config::append_processing_log(logs::novo_log(
+                format!("{}: {}", file_path, evento.message),
+                log_type.to_string(),
+                None,
+            ));
+            let _ = app.emit(EVENTO_WATCHER, evento);
+        }
+    }).map_err(|e| TauriError::filesystem(
+        format!("Erro ao iniciar observador de PDFs: {:#}", e),
+        pdf_dir.to_string_lossy().to_string(),
+    ))?;
+    
+    // Outra chamada pode ter iniciado um observador enquanto este era criado
+    let anterior = {
+        let mut state = watcher_state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.is_some() {
+            Some(observador)
+        } else {
+            *state = Some(observador);
+            None
+        }
+    };
+    if let Some(observador) = anterior {
+        observador.parar();
+        return Err(TauriError::validation("O observador de PDFs já está em execução", None));
+    }
+    
+    Ok(pdf_dir.to_string_lossy().to_string())
+}
+
+/// Encerra o observador de PDFs, retornando `false` se ele não estava em execução
+#[tauri::command]
+pub async fn stop_pdf_watcher(watcher_state: State<'_, PdfWatcherState>) -> Result<bool, TauriError> {
+    let observador = watcher_state.lock().unwrap_or_else(|e| e.into_inner()).take();
+    
+    match observador {
+        Some(observador) => {
+            // Aguarda o arquivo em andamento sem bloquear o runtime assíncrono
+            let _ = tokio::task::spawn_blocking(move || observador.parar()).await;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
@@ -6,6 +6,7 @@ use crate::types::*;
 use crate::pdf_processor;
 use crate::sicaf_processor;
 use crate::config;
+use crate::paths::{self, SubDir};
 use walkdir::WalkDir;
 use chrono::Utc;
 
@@ -26,29 +27,17 @@ pub async fn process_pdf_file(
     
     // Verificar se o arquivo existe
     if !input_path.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Arquivo não encontrado: {}", file_path),
-            details: Some(file_path.clone()),
-        });
+        return Err(TauriError::filesystem(format!("Arquivo não encontrado: {}", file_path), file_path.clone()));
     }
     
     // Verificar se é um arquivo PDF
     if input_path.extension().map_or(true, |ext| ext != "pdf") {
-        return Err(TauriError {
-            error_type: "ValidationError".to_string(),
-            message: "O arquivo deve ter extensão .pdf".to_string(),
-            details: Some(file_path.clone()),
-        });
+        return Err(TauriError::validation("O arquivo deve ter extensão .pdf", file_path.clone()));
     }
     
     // Criar diretório de saída se não existir
     if let Err(e) = std::fs::create_dir_all(&output_path) {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao criar diretório de saída: {}", e),
-            details: Some(output_dir.clone()),
-        });
+        return Err(TauriError::filesystem(format!("Erro ao criar diretório de saída: {}", e), output_dir.clone()));
     }
     
     // Inicializar estado de processamento
@@ -101,11 +90,7 @@ pub async fn process_pdf_file(
                 }
             }
             
-            Err(TauriError {
-                error_type: "ProcessingError".to_string(),
-                message: format!("Erro ao processar arquivo: {}", e),
-                details: Some(file_path),
-            })
+            Err(TauriError::processing(format!("Erro ao processar arquivo: {}", e), file_path))
         }
     }
 }
@@ -126,11 +111,10 @@ pub async fn process_pdf_directory(
     
     // Verificar se o diretório de entrada existe
     if !input_path.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Diretório de entrada não encontrado: {}", input_dir),
-            details: Some(input_dir.clone()),
-        });
+        return Err(TauriError::filesystem(
+            format!("Diretório de entrada não encontrado: {}", input_dir),
+            input_dir.clone(),
+        ));
     }
     
     // Contar arquivos PDF no diretório
@@ -142,11 +126,10 @@ pub async fn process_pdf_directory(
         .count();
     
     if total_files == 0 {
-        return Err(TauriError {
-            error_type: "ValidationError".to_string(),
-            message: "Nenhum arquivo PDF encontrado no diretório especificado".to_string(),
-            details: Some(input_dir.clone()),
-        });
+        return Err(TauriError::validation(
+            "Nenhum arquivo PDF encontrado no diretório especificado",
+            input_dir.clone(),
+        ));
     }
     
     // Inicializar estado de processamento
@@ -194,11 +177,10 @@ pub async fn process_pdf_directory(
             
             // Salvar JSON consolidado
             if let Err(e) = pdf_processor::salvar_json_consolidado(&propostas, &output_path, "consolidado.json", verbose) {
-                let error = TauriError {
-                    error_type: "ProcessingError".to_string(),
-                    message: format!("Erro ao salvar JSON consolidado: {}", e),
-                    details: Some(input_dir.clone()),
-                };
+                let error = TauriError::processing(
+                    format!("Erro ao salvar JSON consolidado: {}", e),
+                    input_dir.clone(),
+                );
                 
                 {
                     let mut state = processing_state.lock().unwrap();
@@ -222,11 +204,7 @@ pub async fn process_pdf_directory(
             })
         }
         Err(e) => {
-            let error = TauriError {
-                error_type: "ProcessingError".to_string(),
-                message: format!("Erro ao processar diretório: {}", e),
-                details: Some(input_dir.clone()),
-            };
+            let error = TauriError::processing(format!("Erro ao processar diretório: {}", e), input_dir.clone());
             
             // Atualizar estado com erro
             {
@@ -252,11 +230,10 @@ pub async fn get_processing_status(
     
     match state.get(&session_id) {
         Some(status) => Ok(status.clone()),
-        None => Err(TauriError {
-            error_type: "SessionNotFound".to_string(),
-            message: format!("Sessão de processamento não encontrada: {}", session_id),
-            details: Some(session_id),
-        })
+        None => Err(TauriError::not_found(
+            format!("Sessão de processamento não encontrada: {}", session_id),
+            session_id,
+        ))
     }
 }
 
@@ -266,11 +243,7 @@ pub async fn list_pdf_files(directory: String) -> Result<Vec<String>, TauriError
     let path = PathBuf::from(&directory);
     
     if !path.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Diretório não encontrado: {}", directory),
-            details: Some(directory),
-        });
+        return Err(TauriError::filesystem(format!("Diretório não encontrado: {}", directory), directory));
     }
     
     let mut pdf_files = Vec::new();
@@ -293,11 +266,7 @@ pub async fn validate_pdf_file(file_path: String) -> Result<bool, TauriError> {
     let path = PathBuf::from(&file_path);
     
     if !path.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Arquivo não encontrado: {}", file_path),
-            details: Some(file_path),
-        });
+        return Err(TauriError::filesystem(format!("Arquivo não encontrado: {}", file_path), file_path));
     }
     
     // Verificar se é um arquivo PDF
@@ -328,11 +297,7 @@ pub async fn clear_processing_state(
 pub async fn get_current_directory() -> Result<String, TauriError> {
     match std::env::current_dir() {
         Ok(path) => Ok(path.to_string_lossy().to_string()),
-        Err(e) => Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao obter diretório atual: {}", e),
-            details: None,
-        })
+        Err(e) => Err(TauriError::filesystem(format!("Erro ao obter diretório atual: {}", e), None))
     }
 }
 
@@ -351,44 +316,11 @@ pub async fn create_default_directories() -> Result<String, TauriError> {
 /// Inicializa toda a estrutura de pastas Database
 #[tauri::command]
 pub async fn initialize_database_structure() -> Result<String, TauriError> {
-    let current_exe = std::env::current_exe()
-        .map_err(|e| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao obter diretório do executável: {}", e),
-            details: None,
-        })?;
-    
-    let exe_dir = current_exe.parent()
-        .ok_or_else(|| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: "Não foi possível obter o diretório pai do executável".to_string(),
-            details: None,
-        })?;
-    
-    let database_dir = exe_dir.join("Database");
-    let subdirs = ["PDFs", "Resultados", "SICAF", "Config"];
-    
-    // Criar pasta Database principal
-    if !database_dir.exists() {
-        std::fs::create_dir_all(&database_dir)
-            .map_err(|e| TauriError {
-                error_type: "FileSystemError".to_string(),
-                message: format!("Erro ao criar pasta Database: {}", e),
-                details: Some(database_dir.to_string_lossy().to_string()),
-            })?;
-    }
+    let database_dir = paths::database_dir()?;
     
-    // Criar subpastas
-    for subdir in &subdirs {
-        let dir_path = database_dir.join(subdir);
-        if !dir_path.exists() {
-            std::fs::create_dir_all(&dir_path)
-                .map_err(|e| TauriError {
-                    error_type: "FileSystemError".to_string(),
-                    message: format!("Erro ao criar pasta {}: {}", subdir, e),
-                    details: Some(dir_path.to_string_lossy().to_string()),
-                })?;
-        }
+    // Criar subpastas (a pasta Database principal é criada junto)
+    for subdir in SubDir::ALL {
+        paths::database_subdir(subdir)?;
     }
     
     // Criar arquivo README na pasta Database
@@ -409,11 +341,10 @@ Esta estrutura é mantida durante atualizações do programa.
 "#;
         
         std::fs::write(&readme_path, readme_content)
-            .map_err(|e| TauriError {
-                error_type: "FileSystemError".to_string(),
-                message: format!("Erro ao criar README: {}", e),
-                details: Some(readme_path.to_string_lossy().to_string()),
-            })?;
+            .map_err(|e| TauriError::filesystem(
+                format!("Erro ao criar README: {}", e),
+                readme_path.to_string_lossy().to_string(),
+            ))?;
     }
     
     Ok(format!("Estrutura Database inicializada com sucesso em: {}", database_dir.to_string_lossy()))
@@ -425,11 +356,7 @@ pub async fn list_json_files(directory: String) -> Result<Vec<String>, TauriErro
     let path = PathBuf::from(&directory);
     
     if !path.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Diretório não encontrado: {}", directory),
-            details: Some(directory),
-        });
+        return Err(TauriError::filesystem(format!("Diretório não encontrado: {}", directory), directory));
     }
     
     let mut json_files = Vec::new();
@@ -467,37 +394,21 @@ pub async fn read_json_file(file_path: String) -> Result<serde_json::Value, Taur
     let path = PathBuf::from(&file_path);
     
     if !path.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Arquivo não encontrado: {}", file_path),
-            details: Some(file_path),
-        });
+        return Err(TauriError::filesystem(format!("Arquivo não encontrado: {}", file_path), file_path));
     }
     
     if path.extension().map_or(true, |ext| ext != "json") {
-        return Err(TauriError {
-            error_type: "ValidationError".to_string(),
-            message: "O arquivo deve ter extensão .json".to_string(),
-            details: Some(file_path),
-        });
+        return Err(TauriError::validation("O arquivo deve ter extensão .json", file_path));
     }
     
     match std::fs::read_to_string(&path) {
         Ok(content) => {
             match serde_json::from_str::<serde_json::Value>(&content) {
                 Ok(json) => Ok(json),
-                Err(e) => Err(TauriError {
-                    error_type: "ParseError".to_string(),
-                    message: format!("Erro ao analisar JSON: {}", e),
-                    details: Some(file_path),
-                })
+                Err(e) => Err(TauriError::parse(format!("Erro ao analisar JSON: {}", e), file_path))
             }
         }
-        Err(e) => Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao ler arquivo: {}", e),
-            details: Some(file_path),
-        })
+        Err(e) => Err(TauriError::filesystem(format!("Erro ao ler arquivo: {}", e), file_path))
     }
 }
 
@@ -507,18 +418,13 @@ pub async fn get_json_file_info(file_path: String) -> Result<serde_json::Value,
     let path = PathBuf::from(&file_path);
     
     if !path.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Arquivo não encontrado: {}", file_path),
-            details: Some(file_path),
-        });
+        return Err(TauriError::filesystem(format!("Arquivo não encontrado: {}", file_path), file_path));
     }
     
-    let metadata = std::fs::metadata(&path).map_err(|e| TauriError {
-        error_type: "FileSystemError".to_string(),
-        message: format!("Erro ao obter informações do arquivo: {}", e),
-        details: Some(file_path.clone()),
-    })?;
+    let metadata = std::fs::metadata(&path).map_err(|e| TauriError::filesystem(
+        format!("Erro ao obter informações do arquivo: {}", e),
+        file_path.clone(),
+    ))?;
     
     let file_name = path.file_name()
         .and_then(|name| name.to_str())
@@ -683,64 +589,14 @@ pub async fn get_config_directory() -> Result<String, TauriError> {
 /// Obtém o diretório da pasta PDF (Database/PDFs)
 #[tauri::command]
 pub async fn get_pdf_directory() -> Result<String, TauriError> {
-    let current_exe = std::env::current_exe()
-        .map_err(|e| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao obter diretório do executável: {}", e),
-            details: None,
-        })?;
-    
-    let exe_dir = current_exe.parent()
-        .ok_or_else(|| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: "Não foi possível obter o diretório pai do executável".to_string(),
-            details: None,
-        })?;
-    
-    let pdf_dir = exe_dir.join("Database").join("PDFs");
-    
-    // Criar a pasta se não existir
-    if !pdf_dir.exists() {
-        std::fs::create_dir_all(&pdf_dir)
-            .map_err(|e| TauriError {
-                error_type: "FileSystemError".to_string(),
-                message: format!("Erro ao criar pasta Database/PDFs: {}", e),
-                details: Some(pdf_dir.to_string_lossy().to_string()),
-            })?;
-    }
-    
+    let pdf_dir = paths::database_subdir(SubDir::Pdfs)?;
     Ok(pdf_dir.to_string_lossy().to_string())
 }
 
 /// Obtém o diretório da pasta de saída (Database/Resultados)
 #[tauri::command]
 pub async fn get_output_directory() -> Result<String, TauriError> {
-    let current_exe = std::env::current_exe()
-        .map_err(|e| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao obter diretório do executável: {}", e),
-            details: None,
-        })?;
-    
-    let exe_dir = current_exe.parent()
-        .ok_or_else(|| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: "Não foi possível obter o diretório pai do executável".to_string(),
-            details: None,
-        })?;
-    
-    let output_dir = exe_dir.join("Database").join("Resultados");
-    
-    // Criar a pasta se não existir
-    if !output_dir.exists() {
-        std::fs::create_dir_all(&output_dir)
-            .map_err(|e| TauriError {
-                error_type: "FileSystemError".to_string(),
-                message: format!("Erro ao criar pasta Database/Resultados: {}", e),
-                details: Some(output_dir.to_string_lossy().to_string()),
-            })?;
-    }
-    
+    let output_dir = paths::database_subdir(SubDir::Resultados)?;
     Ok(output_dir.to_string_lossy().to_string())
 }
 
@@ -751,11 +607,7 @@ pub async fn open_folder(path: String) -> Result<bool, TauriError> {
     
     // Verificar se o caminho existe
     if !path_buf.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Caminho não encontrado: {}", path),
-            details: Some(path.clone()),
-        });
+        return Err(TauriError::filesystem(format!("Caminho não encontrado: {}", path), path.clone()));
     }
     
     // Abrir pasta no sistema operacional
@@ -764,11 +616,7 @@ pub async fn open_folder(path: String) -> Result<bool, TauriError> {
         std::process::Command::new("explorer")
             .arg(&path)
             .spawn()
-            .map_err(|e| TauriError {
-                error_type: "SystemError".to_string(),
-                message: format!("Erro ao abrir pasta: {}", e),
-                details: Some(path.clone()),
-            })?;
+            .map_err(|e| TauriError::system(format!("Erro ao abrir pasta: {}", e), path.clone()))?;
     }
     
     #[cfg(target_os = "macos")]
@@ -776,11 +624,7 @@ pub async fn open_folder(path: String) -> Result<bool, TauriError> {
         std::process::Command::new("open")
             .arg(&path)
             .spawn()
-            .map_err(|e| TauriError {
-                error_type: "SystemError".to_string(),
-                message: format!("Erro ao abrir pasta: {}", e),
-                details: Some(path.clone()),
-            })?;
+            .map_err(|e| TauriError::system(format!("Erro ao abrir pasta: {}", e), path.clone()))?;
     }
     
     #[cfg(target_os = "linux")]
@@ -788,11 +632,7 @@ pub async fn open_folder(path: String) -> Result<bool, TauriError> {
         std::process::Command::new("xdg-open")
             .arg(&path)
             .spawn()
-            .map_err(|e| TauriError {
-                error_type: "SystemError".to_string(),
-                message: format!("Erro ao abrir pasta: {}", e),
-                details: Some(path.clone()),
-            })?;
+            .map_err(|e| TauriError::system(format!("Erro ao abrir pasta: {}", e), path.clone()))?;
     }
     
     Ok(true)
@@ -823,11 +663,10 @@ pub async fn process_pdf_fixed_directory(
         .count();
     
     if total_files == 0 {
-        return Err(TauriError {
-            error_type: "ValidationError".to_string(),
-            message: "Nenhum arquivo PDF encontrado na pasta PDF. Adicione arquivos PDF na pasta e tente novamente.".to_string(),
-            details: Some(input_dir.clone()),
-        });
+        return Err(TauriError::validation(
+            "Nenhum arquivo PDF encontrado na pasta PDF. Adicione arquivos PDF na pasta e tente novamente.",
+            input_dir.clone(),
+        ));
     }
     
     // Inicializar estado de processamento
@@ -875,11 +714,10 @@ pub async fn process_pdf_fixed_directory(
             
             // Salvar JSON consolidado
             if let Err(e) = pdf_processor::salvar_json_consolidado(&propostas, &output_path, "consolidado.json", verbose) {
-                let error = TauriError {
-                    error_type: "ProcessingError".to_string(),
-                    message: format!("Erro ao salvar JSON consolidado: {}", e),
-                    details: Some(input_dir.clone()),
-                };
+                let error = TauriError::processing(
+                    format!("Erro ao salvar JSON consolidado: {}", e),
+                    input_dir.clone(),
+                );
                 
                 {
                     let mut state = processing_state.lock().unwrap();
@@ -903,11 +741,7 @@ pub async fn process_pdf_fixed_directory(
             })
         }
         Err(e) => {
-            let error = TauriError {
-                error_type: "ProcessingError".to_string(),
-                message: format!("Erro ao processar pasta PDF: {}", e),
-                details: Some(input_dir.clone()),
-            };
+            let error = TauriError::processing(format!("Erro ao processar pasta PDF: {}", e), input_dir.clone());
             
             // Atualizar estado com erro
             {
@@ -954,18 +788,13 @@ pub async fn verify_output_directory() -> Result<String, TauriError> {
         
         let exemplo_path = output_path.join("exemplo.json");
         let json_content = serde_json::to_string_pretty(&exemplo_json)
-            .map_err(|e| TauriError {
-                error_type: "SerializationError".to_string(),
-                message: format!("Erro ao serializar JSON de exemplo: {}", e),
-                details: None,
-            })?;
+            .map_err(|e| TauriError::serialization(format!("Erro ao serializar JSON de exemplo: {}", e), None))?;
         
         std::fs::write(&exemplo_path, json_content)
-            .map_err(|e| TauriError {
-                error_type: "FileSystemError".to_string(),
-                message: format!("Erro ao criar arquivo de exemplo: {}", e),
-                details: Some(exemplo_path.to_string_lossy().to_string()),
-            })?;
+            .map_err(|e| TauriError::filesystem(
+                format!("Erro ao criar arquivo de exemplo: {}", e),
+                exemplo_path.to_string_lossy().to_string(),
+            ))?;
         
         Ok(format!("Diretório verificado. Criado arquivo de exemplo: {}", exemplo_path.to_string_lossy()))
     } else {
@@ -978,32 +807,7 @@ pub async fn verify_output_directory() -> Result<String, TauriError> {
 /// Obtém o diretório da pasta SICAF (Database/SICAF)
 #[tauri::command]
 pub async fn get_sicaf_directory() -> Result<String, TauriError> {
-    let current_exe = std::env::current_exe()
-        .map_err(|e| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao obter diretório do executável: {}", e),
-            details: None,
-        })?;
-    
-    let exe_dir = current_exe.parent()
-        .ok_or_else(|| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: "Não foi possível obter o diretório pai do executável".to_string(),
-            details: None,
-        })?;
-    
-    let sicaf_dir = exe_dir.join("Database").join("SICAF");
-    
-    // Criar a pasta se não existir
-    if !sicaf_dir.exists() {
-        std::fs::create_dir_all(&sicaf_dir)
-            .map_err(|e| TauriError {
-                error_type: "FileSystemError".to_string(),
-                message: format!("Erro ao criar pasta Database/SICAF: {}", e),
-                details: Some(sicaf_dir.to_string_lossy().to_string()),
-            })?;
-    }
-    
+    let sicaf_dir = paths::database_subdir(SubDir::Sicaf)?;
     Ok(sicaf_dir.to_string_lossy().to_string())
 }
 
@@ -1021,21 +825,13 @@ pub async fn process_sicaf_pdfs(verbose: bool) -> Result<ProcessingSicafResult,
                 let output_path = PathBuf::from(&output_dir);
                 
                 if let Err(e) = sicaf_processor::salvar_sicaf_json(&result.sicaf_data, &output_path, verbose) {
-                    return Err(TauriError {
-                        error_type: "ProcessingError".to_string(),
-                        message: format!("Erro ao salvar dados SICAF: {}", e),
-                        details: Some(sicaf_dir),
-                    });
+                    return Err(TauriError::processing(format!("Erro ao salvar dados SICAF: {}", e), sicaf_dir));
                 }
             }
             
             Ok(result)
         }
-        Err(e) => Err(TauriError {
-            error_type: "ProcessingError".to_string(),
-            message: format!("Erro ao processar PDFs SICAF: {}", e),
-            details: Some(sicaf_dir),
-        })
+        Err(e) => Err(TauriError::processing(format!("Erro ao processar PDFs SICAF: {}", e), sicaf_dir))
     }
 }
 
@@ -1051,11 +847,10 @@ pub async fn load_sicaf_data() -> Result<Vec<SicafData>, TauriError> {
     
     match sicaf_processor::carregar_sicaf_json(&sicaf_json_path) {
         Ok(data) => Ok(data),
-        Err(e) => Err(TauriError {
-            error_type: "ProcessingError".to_string(),
-            message: format!("Erro ao carregar dados SICAF: {}", e),
-            details: Some(sicaf_json_path.to_string_lossy().to_string()),
-        })
+        Err(e) => Err(TauriError::processing(
+            format!("Erro ao carregar dados SICAF: {}", e),
+            sicaf_json_path.to_string_lossy().to_string(),
+        ))
     }
 }
 
@@ -1087,11 +882,7 @@ pub async fn generate_sicaf_comparison_report(json_file_path: String) -> Result<
             serde_json::from_value(p.clone()).ok()
         }).collect()
     } else {
-        return Err(TauriError {
-            error_type: "ValidationError".to_string(),
-            message: "Arquivo JSON não contém propostas válidas".to_string(),
-            details: Some(json_file_path),
-        });
+        return Err(TauriError::validation("Arquivo JSON não contém propostas válidas", json_file_path));
     };
     
     // Carregar dados SICAF
@@ -1106,11 +897,7 @@ pub async fn generate_sicaf_comparison_report(json_file_path: String) -> Result<
             let relatorio_path = output_path.join("relatorio_sicaf_comparacao.json");
             Ok(relatorio_path.to_string_lossy().to_string())
         }
-        Err(e) => Err(TauriError {
-            error_type: "ProcessingError".to_string(),
-            message: format!("Erro ao gerar relatório de comparação: {}", e),
-            details: Some(output_dir),
-        })
+        Err(e) => Err(TauriError::processing(format!("Erro ao gerar relatório de comparação: {}", e), output_dir))
     }
 }
 
@@ -1120,18 +907,13 @@ pub async fn get_pdf_file_info(file_path: String) -> Result<serde_json::Value, T
     let path = PathBuf::from(&file_path);
     
     if !path.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Arquivo não encontrado: {}", file_path),
-            details: Some(file_path.clone()),
-        });
+        return Err(TauriError::filesystem(format!("Arquivo não encontrado: {}", file_path), file_path.clone()));
     }
     
-    let metadata = std::fs::metadata(&path).map_err(|e| TauriError {
-        error_type: "FileSystemError".to_string(),
-        message: format!("Erro ao ler metadados do arquivo: {}", e),
-        details: Some(file_path.clone()),
-    })?;
+    let metadata = std::fs::metadata(&path).map_err(|e| TauriError::filesystem(
+        format!("Erro ao ler metadados do arquivo: {}", e),
+        file_path.clone(),
+    ))?;
     
     let file_name = path.file_name()
         .and_then(|n| n.to_str())
@@ -1140,11 +922,7 @@ pub async fn get_pdf_file_info(file_path: String) -> Result<serde_json::Value, T
     
     let file_size = metadata.len();
     let modified = metadata.modified()
-        .map_err(|e| TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Erro ao ler data de modificação: {}", e),
-            details: Some(file_path.clone()),
-        })?;
+        .map_err(|e| TauriError::filesystem(format!("Erro ao ler data de modificação: {}", e), file_path.clone()))?;
     
     let modified_timestamp = modified.duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
@@ -1166,11 +944,7 @@ pub async fn get_pdf_files_info(directory: String) -> Result<Vec<serde_json::Val
     let path = PathBuf::from(&directory);
     
     if !path.exists() {
-        return Err(TauriError {
-            error_type: "FileSystemError".to_string(),
-            message: format!("Diretório não encontrado: {}", directory),
-            details: Some(directory),
-        });
+        return Err(TauriError::filesystem(format!("Diretório não encontrado: {}", directory), directory));
     }
     
     let mut pdf_files_info = Vec::new();
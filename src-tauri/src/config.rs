@@ -0,0 +1,242 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use chrono::Utc;
+use crate::types::{padroes_exclusao_padrao, AppConfig, LIMITE_LINHAS_TSV_PADRAO, PROFUNDIDADE_VARREDURA_PADRAO, ProcessingLog, RoutingRule, TauriError, RETENCAO_SESSOES_PADRAO, TIMEOUT_EXTRACAO_PADRAO_SECS, TAMANHO_IMPORTACAO_PADRAO_MB, LIMIAR_RAZAO_SOCIAL_PADRAO, VALIDADE_CACHE_CNPJ_PADRAO_DIAS};
+use crate::i18n::{self, Idioma};
+use crate::integridade;
+use crate::logs;
+use crate::paths::{self, SubDir};
+
+/// Nome do arquivo de configuração dentro de Database/Config
+pub const CONFIG_FILE_NAME: &str = "licitacao360_config.json";
+
+/// Obtém o diretório de configuração (Database/Config)
+pub fn get_config_dir() -> Result<PathBuf, TauriError> {
+    paths::database_subdir(SubDir::Config)
+}
+
+/// Pasta dos arquivos de log (Database/Config/logs)
+pub fn get_logs_dir() -> Result<PathBuf, TauriError> {
+    Ok(get_config_dir()?.join(logs::PASTA_LOGS))
+}
+
+/// Obtém o caminho completo do arquivo de configuração
+pub fn get_config_path() -> Result<PathBuf, TauriError> {
+    Ok(get_config_dir()?.join(CONFIG_FILE_NAME))
+}
+
+/// Cria uma configuração padrão
+pub fn create_default_config() -> AppConfig {
+    AppConfig {
+        last_input_directory: None,
+        last_output_directory: None,
+        verbose: false,
+        processing_logs: Vec::new(),
+        max_logs: 1000,
+        created_at: Utc::now().to_rfc3339(),
+        updated_at: Utc::now().to_rfc3339(),
+        routing_rules: Vec::new(),
+        max_parallel_jobs: 0,
+        http_api_bind_address: None,
+        ocr_enabled: false,
+        session_retention_minutes: RETENCAO_SESSOES_PADRAO,
+        archive_processed: false,
+        database_root: None,
+        pdf_extraction_timeout_secs: TIMEOUT_EXTRACAO_PADRAO_SECS,
+        max_import_size_mb: TAMANHO_IMPORTACAO_PADRAO_MB,
+        sicaf_name_match_threshold: LIMIAR_RAZAO_SOCIAL_PADRAO,
+        retention_policy: None,
+        language: Idioma::PtBr,
+        scan_max_depth: PROFUNDIDADE_VARREDURA_PADRAO,
+        scan_exclude_patterns: padroes_exclusao_padrao(),
+        clipboard_max_rows: LIMITE_LINHAS_TSV_PADRAO,
+        processed_bytes_total: 0,
+        processing_seconds_total: 0.0,
+        allowed_external_dirs: Vec::new(),
+        cnpj_cache_days: VALIDADE_CACHE_CNPJ_PADRAO_DIAS,
+    }
+}
+
+/// Carrega a configuração do disco, retornando a padrão se o arquivo não existir
+pub fn load_config() -> Result<AppConfig, TauriError> {
+    let config_path = get_config_path()?;
+
+    if !config_path.exists() {
+        return Ok(create_default_config());
+    }
+
+    let content = std::fs::read_to_string(&config_path)
+        .map_err(|e| TauriError::filesystem(
+            format!("Erro ao ler arquivo de configuração: {}", e),
+            config_path.to_string_lossy().to_string(),
+        ))?;
+
+    serde_json::from_str::<AppConfig>(&content)
+        .map_err(|e| TauriError::parse(
+            format!("Erro ao analisar configuração: {}", e),
+            config_path.to_string_lossy().to_string(),
+        ))
+}
+
+/// Salva a configuração no disco
+pub fn save_config(config: &AppConfig) -> Result<(), TauriError> {
+    save_config_in(&get_config_dir()?, config)
+}
+
+/// Salva a configuração na pasta de configuração informada
+pub fn save_config_in(config_dir: &Path, config: &AppConfig) -> Result<(), TauriError> {
+    std::fs::create_dir_all(config_dir)
+        .map_err(|e| TauriError::filesystem(
+            format!("Erro ao criar pasta de configuração: {}", e),
+            config_dir.to_string_lossy().to_string(),
+        ))?;
+    let config_path = config_dir.join(CONFIG_FILE_NAME);
+
+    // Os logs ficam nos arquivos de Database/Config/logs; o campo antigo é mantido vazio
+    let sem_logs;
+    let config = if config.processing_logs.is_empty() {
+        config
+    } else {
+        sem_logs = AppConfig { processing_logs: Vec::new(), ..config.clone() };
+        &sem_logs
+    };
+
+    // Escrita atômica: uma queda no meio da gravação não deixa a configuração truncada
+    integridade::escrever_json_atomico(&config_path, config)
+        .map_err(|e| TauriError::filesystem(
+            format!("Erro ao salvar arquivo de configuração: {:#}", e),
+            config_path.to_string_lossy().to_string(),
+        ))
+}
+
+/// Configuração carregada uma vez na inicialização e compartilhada pelos comandos
+pub type ConfigState = Arc<RwLock<AppConfig>>;
+
+/// Cria o estado da configuração a partir do disco (a padrão, em memória, se o arquivo estiver ilegível)
+pub fn carregar_estado() -> ConfigState {
+    let config = load_config().unwrap_or_else(|e| {
+        eprintln!("⚠️ Configuração ilegível, usando a padrão até o reparo: {}", e);
+        create_default_config()
+    });
+    i18n::definir_idioma(config.language);
+    Arc::new(RwLock::new(config))
+}
+
+/// Cópia da configuração em memória
+pub fn ler_config(estado: &RwLock<AppConfig>) -> AppConfig {
+    estado.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Único ponto de escrita da configuração
+///
+/// A alteração é aplicada a uma cópia sob o lock de escrita, gravada (atomicamente) e só então
+/// publicada no estado; se a gravação falhar, o estado em memória não muda. Como o lock é
+/// mantido durante a gravação, atualizações simultâneas são serializadas e nenhuma se perde.
+pub fn atualizar_config<F>(estado: &RwLock<AppConfig>, alteracao: F) -> Result<AppConfig, TauriError>
+where
+    F: FnOnce(&mut AppConfig) -> Result<(), TauriError>,
+{
+    atualizar_config_com(estado, alteracao, save_config)
+}
+
+fn atualizar_config_com<F, S>(estado: &RwLock<AppConfig>, alteracao: F, salvar: S) -> Result<AppConfig, TauriError>
+where
+    F: FnOnce(&mut AppConfig) -> Result<(), TauriError>,
+    S: FnOnce(&AppConfig) -> Result<(), TauriError>,
+{
+    let mut atual = estado.write().unwrap_or_else(|e| e.into_inner());
+    let mut nova = atual.clone();
+    alteracao(&mut nova)?;
+    nova.updated_at = Utc::now().to_rfc3339();
+
+    salvar(&nova)?;
+    i18n::definir_idioma(nova.language);
+    *atual = nova.clone();
+    Ok(nova)
+}
+
+/// Substitui o estado em memória pelo conteúdo atual do arquivo (após edição manual)
+pub fn recarregar_config(estado: &RwLock<AppConfig>) -> Result<AppConfig, TauriError> {
+    let mut atual = estado.write().unwrap_or_else(|e| e.into_inner());
+    let config = load_config()?;
+    i18n::definir_idioma(config.language);
+    *atual = config.clone();
+    Ok(config)
+}
+
+/// Regras de roteamento da configuração em memória
+pub fn load_routing_rules(estado: &RwLock<AppConfig>) -> Vec<RoutingRule> {
+    estado.read().unwrap_or_else(|e| e.into_inner()).routing_rules.clone()
+}
+
+/// Acrescenta um log ao arquivo do dia, sem ler nem regravar a configuração
+pub fn append_processing_log(entrada: ProcessingLog) -> Result<(), TauriError> {
+    let pasta = get_logs_dir()?;
+    logs::gravar_log(&pasta, &entrada)
+        .map_err(|e| TauriError::filesystem(format!("Erro ao gravar log: {:#}", e), pasta.to_string_lossy().to_string()))
+}
+
+/// Move os logs guardados no arquivo de configuração (versões anteriores) para os arquivos de log
+///
+/// Retorna quantas entradas foram migradas; sem logs antigos, a configuração não é regravada.
+pub fn migrar_logs_legados() -> Result<usize, TauriError> {
+    let config_dir = get_config_dir()?;
+    if !config_dir.join(CONFIG_FILE_NAME).exists() {
+        return Ok(0);
+    }
+    let mut config = load_config()?;
+    if config.processing_logs.is_empty() {
+        return Ok(0);
+    }
+
+    let pasta = config_dir.join(logs::PASTA_LOGS);
+    let entradas = std::mem::take(&mut config.processing_logs);
+    logs::gravar_logs_migrados(&pasta, &entradas)
+        .map_err(|e| TauriError::filesystem(format!("Erro ao migrar logs: {:#}", e), pasta.to_string_lossy().to_string()))?;
+    save_config_in(&config_dir, &config)?;
+    Ok(entradas.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atualizacoes_simultaneas_nao_se_perdem() {
+        let pasta = std::env::temp_dir().join(format!("licitacao360_config_lock_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&pasta);
+        let estado: ConfigState = Arc::new(RwLock::new(create_default_config()));
+
+        let threads: Vec<_> = (0..16).map(|index| {
+            let estado = estado.clone();
+            let pasta = pasta.clone();
+            std::thread::spawn(move || {
+                atualizar_config_com(&estado, |config| {
+                    config.routing_rules.push(RoutingRule {
+                        uasg_prefix: Some(index.to_string()),
+                        pregao_year: None,
+                        target_directory: format!("destino_{}", index),
+                    });
+                    Ok(())
+                }, |config| save_config_in(&pasta, config)).unwrap();
+            })
+        }).collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(ler_config(&estado).routing_rules.len(), 16);
+        let gravada: AppConfig = serde_json::from_str(&std::fs::read_to_string(pasta.join(CONFIG_FILE_NAME)).unwrap()).unwrap();
+        assert_eq!(gravada.routing_rules.len(), 16);
+
+        // Falha na gravação não altera o estado em memória
+        let resultado = atualizar_config_com(&estado, |config| {
+            config.verbose = true;
+            Ok(())
+        }, |_| Err(TauriError::filesystem("disco cheio", None)));
+        assert!(resultado.is_err());
+        assert!(!ler_config(&estado).verbose);
+
+        let _ = std::fs::remove_dir_all(&pasta);
+    }
+}
@@ -0,0 +1,271 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration as Dias, Utc};
+use once_cell::sync::Lazy;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::future::Future;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+use crate::integridade;
+use crate::types::{DadosCnpjOnline, FONTE_CNPJ_BRASIL_API, FONTE_CNPJ_CACHE, FONTE_CNPJ_OFFLINE};
+
+/// Endpoint de CNPJ da BrasilAPI; o CNPJ (só dígitos) vai no fim do caminho
+pub const URL_BRASIL_API_CNPJ: &str = "https://brasilapi.com.br/api/cnpj/v1";
+
+/// Cache das consultas dentro de Database/Config
+pub const ARQUIVO_CACHE_CNPJ: &str = "cnpj_cache.json";
+
+/// Tempo máximo de cada consulta à API
+const TIMEOUT_CONSULTA: Duration = Duration::from_secs(10);
+
+/// Intervalo mínimo entre duas consultas, para não estourar o limite público da API
+const INTERVALO_ENTRE_CONSULTAS: Duration = Duration::from_millis(500);
+
+/// Momento da última consulta à API; o lock também serializa as consultas
+static ULTIMA_CONSULTA: Lazy<Mutex<Option<Instant>>> = Lazy::new(|| Mutex::new(None));
+
+static CLIENTE: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(TIMEOUT_CONSULTA)
+        .user_agent(concat!("Licitacao360/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .unwrap_or_default()
+});
+
+type CacheCnpj = BTreeMap<String, DadosCnpjOnline>;
+
+fn somente_digitos(cnpj: &str) -> String {
+    cnpj.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+/// Lê o cache; arquivo ausente ou corrompido vale como cache vazio
+fn ler_cache(path: &Path) -> CacheCnpj {
+    let Ok(conteudo) = fs::read_to_string(path) else {
+        return CacheCnpj::new();
+    };
+    serde_json::from_str(&conteudo).unwrap_or_else(|e| {
+        warn!(arquivo = %path.display(), erro = %e, "Cache de CNPJ ilegível, ignorado");
+        CacheCnpj::new()
+    })
+}
+
+/// A consulta em cache foi feita há menos de `validade_dias`
+fn dentro_da_validade(dados: &DadosCnpjOnline, validade_dias: u64, agora: DateTime<Utc>) -> bool {
+    dados.consultado_em.as_deref()
+        .and_then(|data| DateTime::parse_from_rfc3339(data).ok())
+        .is_some_and(|consulta| agora.signed_duration_since(consulta) < Dias::days(validade_dias as i64))
+}
+
+/// Campos de interesse da resposta da BrasilAPI
+fn da_resposta(cnpj: &str, resposta: &serde_json::Value, consultado_em: DateTime<Utc>) -> DadosCnpjOnline {
+    let texto = |campo: &str| resposta.get(campo)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string);
+
+    DadosCnpjOnline {
+        cnpj: cnpj.to_string(),
+        razao_social: texto("razao_social"),
+        nome_fantasia: texto("nome_fantasia"),
+        situacao_cadastral: texto("descricao_situacao_cadastral"),
+        municipio: texto("municipio"),
+        uf: texto("uf"),
+        fonte: FONTE_CNPJ_BRASIL_API.to_string(),
+        consultado_em: Some(consultado_em.to_rfc3339()),
+        erro: None,
+    }
+}
+
+/// Registro para um CNPJ que não pôde ser consultado
+pub fn offline(cnpj: &str, erro: impl ToString) -> DadosCnpjOnline {
+    DadosCnpjOnline {
+        cnpj: somente_digitos(cnpj),
+        fonte: FONTE_CNPJ_OFFLINE.to_string(),
+        erro: Some(erro.to_string()),
+        ..Default::default()
+    }
+}
+
+async fn consultar_api(cnpj: &str) -> Result<DadosCnpjOnline> {
+    {
+        let mut ultima = ULTIMA_CONSULTA.lock().await;
+        if let Some(espera) = ultima.and_then(|u| INTERVALO_ENTRE_CONSULTAS.checked_sub(u.elapsed())) {
+            tokio::time::sleep(espera).await;
+        }
+        *ultima = Some(Instant::now());
+    }
+
+    let resposta = CLIENTE.get(format!("{}/{}", URL_BRASIL_API_CNPJ, cnpj))
+        .send()
+        .await
+        .context("Falha ao acessar a BrasilAPI")?;
+    let status = resposta.status();
+    if !status.is_success() {
+        bail!("BrasilAPI respondeu {} para o CNPJ {}", status, cnpj);
+    }
+    let corpo: serde_json::Value = resposta.json().await.context("Resposta inválida da BrasilAPI")?;
+    Ok(da_resposta(cnpj, &corpo, Utc::now()))
+}
+
+/// Falha de conexão ou timeout: as próximas consultas também falhariam
+fn falha_de_rede(erro: &anyhow::Error) -> bool {
+    erro.chain()
+        .filter_map(|causa| causa.downcast_ref::<reqwest::Error>())
+        .any(|e| e.is_connect() || e.is_timeout())
+}
+
+/// Consulta do cache ainda dentro da validade
+fn do_cache(cnpj: &str, cache_path: &Path, validade_dias: u64) -> Option<DadosCnpjOnline> {
+    ler_cache(cache_path).remove(&somente_digitos(cnpj))
+        .filter(|d| dentro_da_validade(d, validade_dias, Utc::now()))
+        .map(|dados| DadosCnpjOnline { fonte: FONTE_CNPJ_CACHE.to_string(), ..dados })
+}
+
+/// Consulta um CNPJ na BrasilAPI, usando o cache em `cache_path` enquanto a consulta for válida
+///
+/// Consultas novas são gravadas no cache. Erros de rede ou da API são devolvidos ao chamador.
+pub async fn consultar(cnpj: &str, cache_path: &Path, validade_dias: u64) -> Result<DadosCnpjOnline> {
+    consultar_com(cnpj, cache_path, validade_dias, |cnpj| async move { consultar_api(&cnpj).await }).await
+}
+
+async fn consultar_com<F, Fut>(cnpj: &str, cache_path: &Path, validade_dias: u64, api: F) -> Result<DadosCnpjOnline>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = Result<DadosCnpjOnline>>,
+{
+    let cnpj = somente_digitos(cnpj);
+    if cnpj.len() != 14 {
+        bail!("CNPJ deve ter 14 dígitos: {}", cnpj);
+    }
+
+    if let Some(dados) = do_cache(&cnpj, cache_path, validade_dias) {
+        return Ok(dados);
+    }
+
+    let dados = api(cnpj.clone()).await?;
+    info!(cnpj = %cnpj, "CNPJ consultado na BrasilAPI");
+
+    // Relido depois da consulta para não perder o que outra chamada gravou nesse meio-tempo
+    let mut cache = ler_cache(cache_path);
+    cache.insert(cnpj, dados.clone());
+    if let Err(e) = integridade::escrever_json_atomico(cache_path, &cache) {
+        warn!(arquivo = %cache_path.display(), erro = %e, "Erro ao gravar o cache de CNPJ");
+    }
+    Ok(dados)
+}
+
+/// Consulta vários CNPJs sem falhar; os que não puderem ser consultados voltam marcados como offline
+///
+/// Depois da primeira falha de conexão ou timeout a API não é mais chamada: os CNPJs restantes
+/// vêm só do cache, para um relatório sem rede não esperar o timeout de cada um.
+pub async fn consultar_lote(cnpjs: Vec<String>, cache_path: &Path, validade_dias: u64) -> HashMap<String, DadosCnpjOnline> {
+    consultar_lote_com(cnpjs, cache_path, validade_dias, |cnpj| async move { consultar_api(&cnpj).await }).await
+}
+
+async fn consultar_lote_com<F, Fut>(cnpjs: Vec<String>, cache_path: &Path, validade_dias: u64, mut api: F) -> HashMap<String, DadosCnpjOnline>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<DadosCnpjOnline>>,
+{
+    let mut dados_online = HashMap::new();
+    let mut sem_rede: Option<String> = None;
+    for cnpj in cnpjs {
+        let dados = match &sem_rede {
+            Some(erro) => do_cache(&cnpj, cache_path, validade_dias).unwrap_or_else(|| offline(&cnpj, erro)),
+            None => consultar_com(&cnpj, cache_path, validade_dias, &mut api).await.unwrap_or_else(|e| {
+                let erro = format!("{:#}", e);
+                warn!(cnpj = %cnpj, erro = %erro, "CNPJ não consultado");
+                if falha_de_rede(&e) {
+                    sem_rede = Some(erro.clone());
+                }
+                offline(&cnpj, erro)
+            }),
+        };
+        dados_online.insert(cnpj, dados);
+    }
+    dados_online
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resposta_da_brasil_api() {
+        let resposta = serde_json::json!({
+            "cnpj": "12345678000190",
+            "razao_social": "EMPRESA A LTDA",
+            "nome_fantasia": "",
+            "descricao_situacao_cadastral": "ATIVA",
+            "municipio": "BRASILIA",
+            "uf": "DF",
+            "capital_social": 1000
+        });
+        let agora = Utc::now();
+        let dados = da_resposta("12345678000190", &resposta, agora);
+        assert_eq!(dados.razao_social.as_deref(), Some("EMPRESA A LTDA"));
+        assert_eq!(dados.nome_fantasia, None);
+        assert_eq!(dados.situacao_cadastral.as_deref(), Some("ATIVA"));
+        assert_eq!((dados.municipio.as_deref(), dados.uf.as_deref()), (Some("BRASILIA"), Some("DF")));
+        assert_eq!(dados.fonte, FONTE_CNPJ_BRASIL_API);
+
+        assert!(dentro_da_validade(&dados, 30, agora + Dias::days(29)));
+        assert!(!dentro_da_validade(&dados, 30, agora + Dias::days(31)));
+        assert!(!dentro_da_validade(&offline("12.345.678/0001-90", "sem rede"), 30, agora));
+    }
+
+    #[tokio::test]
+    async fn test_consulta_valida_vem_do_cache() {
+        let root = std::env::temp_dir().join(format!("licitacao360_cache_cnpj_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let cache_path = root.join(ARQUIVO_CACHE_CNPJ);
+
+        let resposta = serde_json::json!({ "razao_social": "EMPRESA A LTDA", "uf": "DF" });
+        let cache = CacheCnpj::from([("12345678000190".to_string(), da_resposta("12345678000190", &resposta, Utc::now()))]);
+        integridade::escrever_json_atomico(&cache_path, &cache).unwrap();
+
+        let dados = consultar("12.345.678/0001-90", &cache_path, 30).await.unwrap();
+        assert_eq!(dados.fonte, FONTE_CNPJ_CACHE);
+        assert_eq!(dados.razao_social.as_deref(), Some("EMPRESA A LTDA"));
+
+        assert!(consultar("123", &cache_path, 30).await.is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn test_lote_para_de_chamar_a_api_depois_da_falha_de_rede() {
+        let root = std::env::temp_dir().join(format!("licitacao360_lote_cnpj_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let cache_path = root.join(ARQUIVO_CACHE_CNPJ);
+
+        let resposta = serde_json::json!({ "razao_social": "EMPRESA C LTDA" });
+        let cache = CacheCnpj::from([("33333333000133".to_string(), da_resposta("33333333000133", &resposta, Utc::now()))]);
+        integridade::escrever_json_atomico(&cache_path, &cache).unwrap();
+
+        // Porta fechada: a primeira chamada falha com erro de conexão
+        let chamadas = std::sync::atomic::AtomicUsize::new(0);
+        let api = |_cnpj: String| {
+            chamadas.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            async {
+                let resposta = CLIENTE.get("http://127.0.0.1:1").send().await.context("Falha ao acessar a BrasilAPI")?;
+                bail!("resposta inesperada: {}", resposta.status())
+            }
+        };
+        let cnpjs = vec!["11111111000111".to_string(), "22222222000122".to_string(), "33333333000133".to_string()];
+        let dados = consultar_lote_com(cnpjs, &cache_path, 30, api).await;
+
+        assert_eq!(chamadas.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(dados["11111111000111"].fonte, FONTE_CNPJ_OFFLINE);
+        assert_eq!(dados["22222222000122"].fonte, FONTE_CNPJ_OFFLINE);
+        assert_eq!(dados["33333333000133"].fonte, FONTE_CNPJ_CACHE);
+        assert_eq!(dados["33333333000133"].razao_social.as_deref(), Some("EMPRESA C LTDA"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
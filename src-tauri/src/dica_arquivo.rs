@@ -0,0 +1,131 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::BTreeMap;
+
+/// Valor de `origem` para campos recuperados do nome do arquivo
+pub const ORIGEM_NOME_ARQUIVO: &str = "nome_do_arquivo";
+
+static RE_UASG: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\bUASG[\s\-\.]*(\d{6})\b").unwrap());
+
+static RE_PREGAO: Lazy<Regex> = Lazy::new(|| Regex::new(
+    r"(?i)\b(?:PE|PREG[AÃ]O(?:[\s\-\.]*ELETR[OÔ]NICO)?)[\s\-\.]*(?:N[º°O]?\.?[\s\-\.]*)?(\d{1,5})[\s\-\.]+(20\d{2})\b"
+).unwrap());
+
+/// UASG e pregão reconhecidos no nome de um arquivo
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DicaNomeArquivo {
+    pub uasg: Option<String>,
+    pub pregao: Option<String>,
+}
+
+/// Campos recuperados do nome do arquivo, com origem e avisos para o relatório
+#[derive(Debug, Clone, Default)]
+pub struct CamposRecuperados {
+    pub origem: BTreeMap<String, String>,
+    pub avisos: Vec<String>,
+}
+
+/// Extrai UASG e pregão de nomes como "PE 90008-2024 UASG 787000.pdf"
+///
+/// O parser é conservador: só aceita valores precedidos do rótulo (UASG, PE, Pregão)
+/// e descarta o campo quando o nome traz mais de um valor distinto para ele.
+pub fn extrair_dica(nome_arquivo: &str) -> DicaNomeArquivo {
+    // Sublinhados viram espaços para que os limites de palavra funcionem
+    let nome = nome_arquivo.replace('_', " ");
+
+    let uasgs: Vec<String> = RE_UASG.captures_iter(&nome)
+        .map(|caps| caps[1].to_string())
+        .collect();
+
+    let pregoes: Vec<String> = RE_PREGAO.captures_iter(&nome)
+        .map(|caps| format!("{}/{}", &caps[1], &caps[2]))
+        .collect();
+
+    DicaNomeArquivo {
+        uasg: valor_unico(uasgs),
+        pregao: valor_unico(pregoes),
+    }
+}
+
+/// Preenche UASG/pregão ausentes ("N/A") com a dica do nome do arquivo
+pub fn completar_campos(uasg: &mut String, pregao: &mut String, nome_arquivo: &str) -> CamposRecuperados {
+    let mut recuperados = CamposRecuperados::default();
+
+    if uasg != "N/A" && pregao != "N/A" {
+        return recuperados;
+    }
+
+    let dica = extrair_dica(nome_arquivo);
+
+    if uasg == "N/A" {
+        if let Some(valor) = dica.uasg {
+            recuperados.avisos.push(format!("UASG {} obtida do nome do arquivo \"{}\"", valor, nome_arquivo));
+            recuperados.origem.insert("uasg".to_string(), ORIGEM_NOME_ARQUIVO.to_string());
+            *uasg = valor;
+        }
+    }
+
+    if pregao == "N/A" {
+        if let Some(valor) = dica.pregao {
+            recuperados.avisos.push(format!("Pregão {} obtido do nome do arquivo \"{}\"", valor, nome_arquivo));
+            recuperados.origem.insert("pregao".to_string(), ORIGEM_NOME_ARQUIVO.to_string());
+            *pregao = valor;
+        }
+    }
+
+    recuperados
+}
+
+/// Retorna o valor apenas se todas as ocorrências forem iguais
+fn valor_unico(mut valores: Vec<String>) -> Option<String> {
+    valores.dedup();
+    if valores.len() == 1 {
+        valores.pop()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dica(uasg: Option<&str>, pregao: Option<&str>) -> DicaNomeArquivo {
+        DicaNomeArquivo {
+            uasg: uasg.map(str::to_string),
+            pregao: pregao.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_estilos_de_nome_reconhecidos() {
+        assert_eq!(extrair_dica("PE 90008-2024 UASG 787000.pdf"), dica(Some("787000"), Some("90008/2024")));
+        assert_eq!(extrair_dica("Pregao_Eletronico_00012_2023_UASG_160001.pdf"), dica(Some("160001"), Some("00012/2023")));
+        assert_eq!(extrair_dica("Termo de Homologação - Pregão nº 90045.2024 - UASG 787010.pdf"), dica(Some("787010"), Some("90045/2024")));
+        assert_eq!(extrair_dica("homologacao_uasg787000_pe90008_2024.pdf"), dica(Some("787000"), Some("90008/2024")));
+        assert_eq!(extrair_dica("UASG-787000 PREGÃO ELETRÔNICO 15-2022.pdf"), dica(Some("787000"), Some("15/2022")));
+    }
+
+    #[test]
+    fn test_nomes_ambiguos_nao_casam() {
+        // Dois pregões diferentes no mesmo nome: nenhum é escolhido
+        assert_eq!(extrair_dica("PE 90008-2024 e PE 90010-2024 UASG 787000.pdf"), dica(Some("787000"), None));
+        // Números sem rótulo não são interpretados
+        assert_eq!(extrair_dica("90008 2024 787000.pdf"), dica(None, None));
+        // "PE" dentro de outra palavra e UASG com tamanho errado
+        assert_eq!(extrair_dica("PEDIDO 12 2024 UASG 78700.pdf"), dica(None, None));
+    }
+
+    #[test]
+    fn test_completar_apenas_campos_ausentes() {
+        let mut uasg = "160001".to_string();
+        let mut pregao = "N/A".to_string();
+        let recuperados = completar_campos(&mut uasg, &mut pregao, "PE 90008-2024 UASG 787000.pdf");
+
+        assert_eq!(uasg, "160001");
+        assert_eq!(pregao, "90008/2024");
+        assert_eq!(recuperados.origem.get("pregao").map(String::as_str), Some(ORIGEM_NOME_ARQUIVO));
+        assert!(!recuperados.origem.contains_key("uasg"));
+        assert_eq!(recuperados.avisos.len(), 1);
+    }
+}
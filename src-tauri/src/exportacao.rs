@@ -0,0 +1,412 @@
+use anyhow::Result;
+use rust_xlsxwriter::{Format, Workbook, Worksheet};
+use crate::pdf_processor::converter_valor_para_float;
+use crate::types::{formatar_valor, LicitacaoConsolidada, PropostaConsolidada, Valor};
+
+/// Separador de colunas usado pelo Excel em português
+pub const SEPARADOR_CSV: char = ';';
+
+/// BOM UTF-8, para o Excel reconhecer a codificação dos acentos
+pub const BOM_UTF8: &str = "\u{feff}";
+
+/// Cabeçalho das colunas exportadas
+pub const CABECALHO_PROPOSTAS: [&str; 16] = [
+    "UASG",
+    "Pregão",
+    "Processo",
+    "Item",
+    "Grupo",
+    "Quantidade",
+    "Descrição",
+    "Valor Estimado",
+    "Valor Adjudicado",
+    "Melhor Lance",
+    "Fornecedor",
+    "CNPJ",
+    "Marca/Fabricante",
+    "Modelo/Versão",
+    "Responsável",
+    "Formato",
+];
+
+/// Formata um número no padrão brasileiro: 1234.5 -> "1.234,50"
+pub fn formatar_numero_br(valor: f64) -> String {
+    let texto = format!("{:.2}", valor.abs());
+    let (inteiro, decimal) = texto.split_once('.').unwrap_or((&texto, "00"));
+
+    let mut agrupado = String::new();
+    for (index, digito) in inteiro.chars().enumerate() {
+        if index > 0 && (inteiro.len() - index) % 3 == 0 {
+            agrupado.push('.');
+        }
+        agrupado.push(digito);
+    }
+
+    let sinal = if valor < 0.0 { "-" } else { "" };
+    format!("{}{},{}", sinal, agrupado, decimal)
+}
+
+/// Protege um campo com aspas quando contém separador, aspas ou quebra de linha
+fn escapar_campo(campo: &str) -> String {
+    if campo.contains(SEPARADOR_CSV) || campo.contains('"') || campo.contains('\n') || campo.contains('\r') {
+        format!("\"{}\"", campo.replace('"', "\"\""))
+    } else {
+        campo.to_string()
+    }
+}
+
+/// Colunas de uma proposta, na ordem do cabeçalho
+pub fn colunas_proposta(proposta: &PropostaConsolidada) -> Vec<String> {
+    vec![
+        proposta.uasg.clone(),
+        proposta.pregao.clone(),
+        proposta.processo.clone(),
+        proposta.item.clone(),
+        proposta.grupo.clone().unwrap_or_default(),
+        proposta.quantidade.clone(),
+        proposta.descricao.clone(),
+        formatar_valor(proposta.valor_estimado),
+        formatar_valor(proposta.valor_adjudicado),
+        formatar_valor(proposta.melhor_lance),
+        proposta.fornecedor.clone(),
+        proposta.cnpj.clone(),
+        proposta.marca_fabricante.clone(),
+        proposta.modelo_versao.clone(),
+        proposta.responsavel.clone(),
+        proposta.tipo_formato.clone(),
+    ]
+}
+
+/// Gera um CSV (com BOM) no formato do Excel em português a partir de linhas já formatadas
+pub fn gerar_csv(cabecalho: &[&str], linhas: impl IntoIterator<Item = Vec<String>>) -> String {
+    let separador = SEPARADOR_CSV.to_string();
+    let mut csv = String::from(BOM_UTF8);
+
+    csv.push_str(&cabecalho.join(&separador));
+    csv.push_str("\r\n");
+
+    for colunas in linhas {
+        let linha: Vec<String> = colunas.iter()
+            .map(|campo| escapar_campo(campo))
+            .collect();
+        csv.push_str(&linha.join(&separador));
+        csv.push_str("\r\n");
+    }
+
+    csv
+}
+
+/// Gera o conteúdo CSV (com BOM) das propostas
+pub fn gerar_csv_propostas(propostas: &[PropostaConsolidada]) -> String {
+    gerar_csv(&CABECALHO_PROPOSTAS, propostas.iter().map(colunas_proposta))
+}
+
+/// Separador de colunas do texto copiado para planilhas (Excel, Google Sheets)
+pub const SEPARADOR_TSV: char = '\t';
+
+/// Tabs e quebras de linha dentro de um campo viram espaço, para não deslocar as células ao colar
+fn limpar_campo_tsv(campo: &str) -> String {
+    campo.split(['\t', '\r', '\n'])
+        .filter(|parte| !parte.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Compara números de item ignorando espaços e zeros à esquerda ("01" == "1")
+pub fn mesmo_item(item: &str, outro: &str) -> bool {
+    let normalizar = |item: &str| {
+        let item = item.trim().trim_start_matches('0');
+        if item.is_empty() { "0".to_string() } else { item.to_lowercase() }
+    };
+    normalizar(item) == normalizar(outro)
+}
+
+/// Gera o TSV das propostas (cabeçalho incluído), com no máximo `limite` linhas de dados (0 = sem limite)
+///
+/// Valores monetários saem no formato brasileiro, como no CSV.
+pub fn gerar_tsv_propostas<'a>(propostas: impl IntoIterator<Item = &'a PropostaConsolidada>, limite: usize) -> String {
+    let separador = SEPARADOR_TSV.to_string();
+    let mut tsv = CABECALHO_PROPOSTAS.join(&separador);
+    tsv.push_str("\r\n");
+
+    let limite = if limite == 0 { usize::MAX } else { limite };
+    for proposta in propostas.into_iter().take(limite) {
+        let linha: Vec<String> = colunas_proposta(proposta).iter()
+            .map(|campo| limpar_campo_tsv(campo))
+            .collect();
+        tsv.push_str(&linha.join(&separador));
+        tsv.push_str("\r\n");
+    }
+
+    tsv
+}
+
+/// Limite do Excel para nomes de abas
+const MAX_NOME_ABA: usize = 31;
+
+/// Nome da aba de totais da planilha consolidada
+pub const ABA_RESUMO: &str = "Resumo";
+
+/// Interpreta um texto numérico do PDF ("1.234,56"); outros textos retornam `None`
+pub fn valor_numerico(texto: &str) -> Option<f64> {
+    let texto = texto.trim();
+    let numerico = !texto.is_empty()
+        && texto.chars().any(|c| c.is_ascii_digit())
+        && texto.chars().all(|c| c.is_ascii_digit() || c == '.' || c == ',');
+
+    if numerico {
+        converter_valor_para_float(texto).ok()
+    } else {
+        None
+    }
+}
+
+/// Gera nomes de abas válidos e únicos (até 31 caracteres, sem `[]:*?/\`)
+///
+/// Nomes que colidem após o truncamento recebem um sufixo "~2", "~3", ...
+/// e "Resumo" fica reservado para a aba de totais.
+pub fn nomes_abas_unicos(bases: &[String]) -> Vec<String> {
+    let mut usados: Vec<String> = vec![ABA_RESUMO.to_lowercase()];
+    let mut nomes = Vec::new();
+
+    for base in bases {
+        let limpo: String = base.chars()
+            .map(|c| if "[]:*?/\\".contains(c) { '-' } else { c })
+            .collect();
+        let limpo = limpo.trim().trim_matches('\'');
+        let limpo = if limpo.is_empty() { "Licitacao" } else { limpo };
+
+        let mut nome: String = limpo.chars().take(MAX_NOME_ABA).collect();
+        let mut contador = 2;
+        while usados.contains(&nome.to_lowercase()) {
+            let sufixo = format!("~{}", contador);
+            let prefixo: String = limpo.chars().take(MAX_NOME_ABA - sufixo.chars().count()).collect();
+            nome = format!("{}{}", prefixo, sufixo);
+            contador += 1;
+        }
+
+        usados.push(nome.to_lowercase());
+        nomes.push(nome);
+    }
+
+    nomes
+}
+
+/// Escreve um texto numérico como número (com formato opcional) ou, se não for numérico, como texto
+fn escrever_valor(aba: &mut Worksheet, linha: u32, coluna: u16, texto: &str, formato: &Format) -> Result<()> {
+    match valor_numerico(texto) {
+        Some(valor) => { aba.write_number_with_format(linha, coluna, valor, formato)?; }
+        None => { aba.write_string(linha, coluna, texto)?; }
+    }
+    Ok(())
+}
+
+/// Escreve um valor monetário como número; valores ausentes ficam como "N/A"
+fn escrever_moeda(aba: &mut Worksheet, linha: u32, coluna: u16, valor: Option<Valor>, formato: &Format) -> Result<()> {
+    match valor {
+        Some(valor) => { aba.write_number_with_format(linha, coluna, valor.0, formato)?; }
+        None => { aba.write_string(linha, coluna, "N/A")?; }
+    }
+    Ok(())
+}
+
+/// Monta a planilha consolidada: uma aba por licitação e a aba "Resumo" com totais
+pub fn gerar_xlsx_consolidado(licitacoes: &[LicitacaoConsolidada]) -> Result<Vec<u8>> {
+    let mut workbook = Workbook::new();
+    let cabecalho = Format::new().set_bold();
+    let moeda = Format::new().set_num_format("\"R$\" #,##0.00");
+    let quantidade = Format::new().set_num_format("#,##0.##");
+
+    let bases: Vec<String> = licitacoes.iter()
+        .map(|l| format!("{} {}", l.uasg, l.pregao))
+        .collect();
+    let nomes = nomes_abas_unicos(&bases);
+
+    // Aba de resumo primeiro, para abrir no topo
+    {
+        let resumo = workbook.add_worksheet();
+        resumo.set_name(ABA_RESUMO)?;
+
+        for (coluna, titulo) in ["UASG", "Pregão", "Processo", "Propostas", "Valor Total", "Aba"].iter().enumerate() {
+            resumo.write_string_with_format(0, coluna as u16, *titulo, &cabecalho)?;
+        }
+        resumo.set_freeze_panes(1, 0)?;
+
+        for (index, (licitacao, nome)) in licitacoes.iter().zip(&nomes).enumerate() {
+            let linha = index as u32 + 1;
+            resumo.write_string(linha, 0, &licitacao.uasg)?;
+            resumo.write_string(linha, 1, &licitacao.pregao)?;
+            resumo.write_string(linha, 2, &licitacao.processo)?;
+            resumo.write_number(linha, 3, licitacao.total_propostas as f64)?;
+            resumo.write_number_with_format(linha, 4, licitacao.valor_total, &moeda)?;
+            resumo.write_string(linha, 5, nome)?;
+        }
+
+        let linha_total = licitacoes.len() as u32 + 1;
+        resumo.write_string_with_format(linha_total, 0, "Total", &cabecalho)?;
+        resumo.write_number_with_format(linha_total, 3, licitacoes.iter().map(|l| l.total_propostas as f64).sum::<f64>(), &cabecalho)?;
+        resumo.write_number_with_format(linha_total, 4, licitacoes.iter().map(|l| l.valor_total).sum::<f64>(), &moeda)?;
+    }
+
+    for (licitacao, nome) in licitacoes.iter().zip(&nomes) {
+        let aba = workbook.add_worksheet();
+        aba.set_name(nome)?;
+
+        for (coluna, titulo) in CABECALHO_PROPOSTAS.iter().enumerate() {
+            aba.write_string_with_format(0, coluna as u16, *titulo, &cabecalho)?;
+        }
+        aba.set_freeze_panes(1, 0)?;
+
+        for (index, proposta) in licitacao.propostas.iter().enumerate() {
+            let linha = index as u32 + 1;
+            let colunas = colunas_proposta(proposta);
+
+            for (coluna, texto) in colunas.iter().enumerate() {
+                let coluna = coluna as u16;
+                match CABECALHO_PROPOSTAS[coluna as usize] {
+                    "Quantidade" => escrever_valor(aba, linha, coluna, &proposta.quantidade, &quantidade)?,
+                    "Valor Estimado" => escrever_moeda(aba, linha, coluna, proposta.valor_estimado, &moeda)?,
+                    "Valor Adjudicado" => escrever_moeda(aba, linha, coluna, proposta.valor_adjudicado, &moeda)?,
+                    "Melhor Lance" => escrever_moeda(aba, linha, coluna, proposta.melhor_lance, &moeda)?,
+                    _ => { aba.write_string(linha, coluna, texto)?; }
+                }
+            }
+        }
+    }
+
+    Ok(workbook.save_to_buffer()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{MODALIDADE_PREGAO, SITUACAO_ADJUDICADO};
+
+    fn proposta(descricao: &str, valor: &str) -> PropostaConsolidada {
+        PropostaConsolidada {
+            uasg: "787000".to_string(),
+            pregao: "90008/2024".to_string(),
+            modalidade: MODALIDADE_PREGAO.to_string(),
+            processo: "123".to_string(),
+            item: "1".to_string(),
+            grupo: None,
+            quantidade: "10".to_string(),
+            descricao: descricao.to_string(),
+            valor_estimado: None,
+            valor_adjudicado: Valor::de_texto(valor),
+            valor_adjudicado_raw: valor.to_string(),
+            fornecedor: "EMPRESA LTDA".to_string(),
+            cnpj: "12.345.678/0001-90".to_string(),
+            marca_fabricante: "N/A".to_string(),
+            modelo_versao: "N/A".to_string(),
+            responsavel: "FULANO".to_string(),
+            melhor_lance: Valor::de_texto("1.500,0000"),
+            valor_unitario_estimado: None,
+            valor_unitario_adjudicado: None,
+            valor_total_item: Valor::de_texto(valor),
+            valor_calculado: false,
+            tipo_formato: "individual".to_string(),
+            situacao: SITUACAO_ADJUDICADO.to_string(),
+            cnpj_valido: true,
+            descricao_reparada: false,
+            descricao_original: None,
+            observacoes: None,
+            porte: None,
+            lances: Vec::new(),
+            participantes: Vec::new(),
+            data_homologacao: String::new(),
+            data_homologacao_iso: None,
+            responsavel_homologacao: None,
+            arquivo_origem: None,
+            origem: Default::default(),
+            avisos: Vec::new(),
+            economia_absoluta: None,
+            economia_percentual: None,
+        }
+    }
+
+    #[test]
+    fn test_formatar_numero_br() {
+        assert_eq!(formatar_numero_br(0.0), "0,00");
+        assert_eq!(formatar_numero_br(123.4), "123,40");
+        assert_eq!(formatar_numero_br(1234.56), "1.234,56");
+        assert_eq!(formatar_numero_br(1234567.891), "1.234.567,89");
+        assert_eq!(formatar_numero_br(-1000.0), "-1.000,00");
+        assert_eq!(formatar_valor(Valor::de_texto("1.234,5600")), "1.234,56");
+        assert_eq!(formatar_valor(Valor::de_texto("N/A")), "N/A");
+    }
+
+    #[test]
+    fn test_gerar_csv_propostas() {
+        let csv = gerar_csv_propostas(&[proposta("Caneta; azul \"esferográfica\"", "1.234,56")]);
+
+        assert!(csv.starts_with(BOM_UTF8));
+        let linhas: Vec<&str> = csv.trim_start_matches(BOM_UTF8).split("\r\n").collect();
+        assert!(linhas[0].starts_with("UASG;Pregão;Processo"));
+        assert_eq!(linhas[0].split(';').count(), CABECALHO_PROPOSTAS.len());
+        assert!(linhas[1].contains(";\"Caneta; azul \"\"esferográfica\"\"\";"));
+        assert!(linhas[1].contains(";N/A;1.234,56;1.500,00;"));
+    }
+
+    #[test]
+    fn test_gerar_tsv_propostas() {
+        let propostas = [
+            proposta("Caneta\tazul\r\nesferográfica", "1.234,56"),
+            proposta("Lápis", "10,00"),
+            proposta("Borracha", "5,00"),
+        ];
+        let tsv = gerar_tsv_propostas(&propostas, 2);
+
+        let linhas: Vec<&str> = tsv.trim_end().split("\r\n").collect();
+        assert_eq!(linhas.len(), 3);
+        assert!(linhas[0].starts_with("UASG\tPregão\tProcesso"));
+        for linha in &linhas {
+            assert_eq!(linha.split('\t').count(), CABECALHO_PROPOSTAS.len());
+        }
+        assert!(linhas[1].contains("\tCaneta azul esferográfica\tN/A\t1.234,56\t1.500,00\t"));
+        assert_eq!(gerar_tsv_propostas(&propostas, 0).trim_end().split("\r\n").count(), 4);
+
+        assert!(mesmo_item("01", " 1") && mesmo_item("0", "00") && !mesmo_item("10", "1"));
+    }
+
+    #[test]
+    fn test_nomes_abas_truncados_sem_colisao() {
+        let bases = vec![
+            "787000 90008/2024 Aquisição de material de expediente".to_string(),
+            "787000 90008/2024 Aquisição de material de limpeza".to_string(),
+            "resumo".to_string(),
+            "160001 12/2023".to_string(),
+        ];
+        let nomes = nomes_abas_unicos(&bases);
+
+        assert!(nomes.iter().all(|n| n.chars().count() <= 31));
+        assert!(nomes.iter().all(|n| !n.contains('/')));
+        assert_eq!(nomes[0], "787000 90008-2024 Aquisição de ");
+        assert_eq!(nomes[1], "787000 90008-2024 Aquisição d~2");
+        assert_eq!(nomes[2], "resumo~2");
+        assert_eq!(nomes[3], "160001 12-2023");
+    }
+
+    #[test]
+    fn test_gerar_xlsx_consolidado() {
+        let licitacao = LicitacaoConsolidada {
+            uasg: "787000".to_string(),
+            pregao: "90008/2024".to_string(),
+            modalidade: MODALIDADE_PREGAO.to_string(),
+            processo: "123".to_string(),
+            total_propostas: 1,
+            valor_total: 1234.56,
+            propostas: vec![proposta("Caneta", "1.234,56")],
+            itens_adjudicados: 1,
+            itens_desertos: 0,
+            itens_fracassados: 0,
+            itens_cancelados: 0,
+        };
+
+        let bytes = gerar_xlsx_consolidado(&[licitacao]).unwrap();
+        // Arquivos XLSX são pacotes ZIP
+        assert!(bytes.starts_with(b"PK"));
+        assert_eq!(valor_numerico("1.234,56"), Some(1234.56));
+        assert_eq!(valor_numerico("N/A"), None);
+    }
+}
@@ -0,0 +1,139 @@
+use once_cell::sync::Lazy;
+use regex::{Match, Regex};
+use crate::types::{OcorrenciaPadrao, RelatorioPadroes, ResultadoExtrator, ResultadoPadrao, SpanCaptura};
+use crate::{extratores, pdf_processor, sicaf_processor, texto};
+
+/// Maior texto aceito por `try_extraction_patterns` (uma ata inteira cabe com folga)
+pub const LIMITE_TEXTO_BYTES: usize = 2 * 1024 * 1024;
+
+/// Matches devolvidos por padrão; atas longas repetem a linha de adjudicação centenas de vezes
+pub const MAX_OCORRENCIAS_POR_PADRAO: usize = 50;
+
+fn span(m: Match) -> SpanCaptura {
+    SpanCaptura { inicio: m.start(), fim: m.end(), texto: m.as_str().to_string() }
+}
+
+/// Matches de um padrão no texto, com os grupos de captura
+pub fn aplicar_padrao(campo: &str, nome: &str, re: &Regex, text: &str) -> ResultadoPadrao {
+    let ocorrencias = re.captures_iter(text)
+        .take(MAX_OCORRENCIAS_POR_PADRAO)
+        .map(|caps| OcorrenciaPadrao {
+            span: span(caps.get(0).unwrap()),
+            grupos: re.capture_names().enumerate().skip(1)
+                .map(|(i, nome)| (nome.map_or_else(|| i.to_string(), str::to_string), caps.get(i).map(span)))
+                .collect(),
+        })
+        .collect();
+
+    ResultadoPadrao {
+        campo: campo.to_string(),
+        nome: nome.to_string(),
+        padrao: re.as_str().to_string(),
+        ocorrencias,
+    }
+}
+
+/// Padrões mostrados no relatório: (extração, nome, regex)
+static PADROES: Lazy<Vec<(&'static str, &'static str, &'static Regex)>> = Lazy::new(|| {
+    let mut padroes = vec![
+        ("uasg", "uasg", &*pdf_processor::RE_UASG),
+        ("processo", "processo", &*pdf_processor::RE_PROCESSO),
+        ("data_homologacao", "data_homologacao", &*pdf_processor::RE_DATA_HOMOLOGACAO),
+    ];
+    padroes.extend(pdf_processor::RE_CERTAMES.iter().map(|(modalidade, re)| ("certame", *modalidade, re)));
+    padroes.extend([
+        ("individual", "adjudicado_negociado", &*pdf_processor::RE_ADJUDICADO_NEGOCIADO),
+        ("individual", "adjucado_negociado", &*pdf_processor::RE_ADJUCADO_NEGOCIADO),
+        ("individual", "adjudicado", &*pdf_processor::RE_ADJUDICADO),
+        ("individual", "adjucado", &*pdf_processor::RE_ADJUCADO),
+        ("grupo", "item_do_grupo", &*pdf_processor::RE_GRUPO),
+        ("sicaf", "dados_fornecedor", &*sicaf_processor::RE_DADOS_SICAF),
+        ("sicaf", "responsavel_legal", &*sicaf_processor::RE_RESPONSAVEL_LEGAL),
+        ("sicaf", "secao_niveis", &*sicaf_processor::RE_SECAO_NIVEIS),
+        ("sicaf", "secao_ocorrencias", &*sicaf_processor::RE_SECAO_OCORRENCIAS),
+        ("sicaf", "emissao", &*sicaf_processor::RE_EMISSAO),
+    ]);
+    padroes
+});
+
+/// Roda todas as extrações de ata e de SICAF sobre um texto, como o processamento faria
+///
+/// O texto passa pela mesma normalização dos PDFs. Nada é gravado: serve para ajustar
+/// padrões de layouts novos sem recompilar.
+pub fn run_all(text: &str) -> RelatorioPadroes {
+    let text = texto::normalizar_texto(text);
+    let (modalidade, pregao) = pdf_processor::extrair_certame(&text);
+
+    RelatorioPadroes {
+        uasg: pdf_processor::extrair_uasg(&text),
+        modalidade,
+        pregao,
+        processo: pdf_processor::extrair_processo(&text),
+        data_homologacao: pdf_processor::extrair_data_homologacao(&text),
+        extrator_selecionado: extratores::selecionar(&text).map(|e| e.nome().to_string()),
+        extratores: extratores::registrados().iter()
+            .map(|extrator| ResultadoExtrator {
+                nome: extrator.nome().to_string(),
+                detecta: extrator.detecta(&text),
+                propostas: extrator.extrair(&text),
+            })
+            .collect(),
+        sicaf: sicaf_processor::dados_sicaf_do_texto(&text),
+        padroes: PADROES.iter()
+            .map(|(campo, nome, re)| aplicar_padrao(campo, nome, re, &text))
+            .collect(),
+        texto_normalizado: text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ATA: &str = "UASG 787000\nPREGÃO 90008/2024\nProcesso nº 123\n\
+Item 1 - Caneta esferográfica\nQuantidade: 100\nValor estimado: R$ 2,00\n\
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL, para EMPRESA A LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 1,50\n";
+
+    fn padrao<'a>(relatorio: &'a RelatorioPadroes, nome: &str) -> &'a ResultadoPadrao {
+        relatorio.padroes.iter().find(|p| p.nome == nome).unwrap()
+    }
+
+    #[test]
+    fn test_run_all_sobre_ata_individual() {
+        let relatorio = run_all(ATA);
+        assert_eq!(
+            (relatorio.uasg.as_str(), relatorio.pregao.as_str(), relatorio.processo.as_str()),
+            ("787000", "90008/2024", "123"),
+        );
+        assert_eq!(relatorio.data_homologacao, "N/A");
+        assert_eq!(relatorio.extrator_selecionado.as_deref(), Some("individual"));
+        assert!(relatorio.sicaf.is_none());
+
+        let individual = relatorio.extratores.iter().find(|e| e.nome == "individual").unwrap();
+        assert!(individual.detecta);
+        assert_eq!(individual.propostas[0].fornecedor, "EMPRESA A LTDA");
+        assert!(!relatorio.extratores.iter().find(|e| e.nome == "grupo").unwrap().detecta);
+
+        // Spans apontam para o texto normalizado, com os grupos pelo nome
+        let adjudicado = padrao(&relatorio, "adjudicado");
+        let ocorrencia = &adjudicado.ocorrencias[0];
+        let cnpj = ocorrencia.grupos["cnpj"].as_ref().unwrap();
+        assert_eq!(cnpj.texto, "12.345.678/0001-90");
+        assert_eq!(&relatorio.texto_normalizado[cnpj.inicio..cnpj.fim], cnpj.texto);
+        assert_eq!(padrao(&relatorio, "uasg").ocorrencias[0].grupos["1"].as_ref().unwrap().texto, "787000");
+
+        // Padrões que não casam aparecem vazios
+        assert!(padrao(&relatorio, "adjudicado_negociado").ocorrencias.is_empty());
+        assert!(padrao(&relatorio, "item_do_grupo").ocorrencias.is_empty());
+        assert!(padrao(&relatorio, "dados_fornecedor").ocorrencias.is_empty());
+    }
+
+    #[test]
+    fn test_run_all_sobre_sicaf() {
+        let relatorio = run_all(include_str!("../tests/fixtures/sicaf_pua_acentos_soltos.txt"));
+        let sicaf = relatorio.sicaf.as_ref().expect("SICAF não reconhecido");
+        assert!(!sicaf.cnpj.is_empty());
+        assert_eq!(padrao(&relatorio, "dados_fornecedor").ocorrencias.len(), 1);
+        assert!(relatorio.extrator_selecionado.is_none());
+    }
+}
@@ -0,0 +1,218 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::sync::{Arc, RwLock};
+use crate::pdf_processor::{self, ExtratorGrupo, ExtratorIndividual};
+use crate::types::{PropostaAdjudicada, Valor, SITUACAO_ADJUDICADO};
+use crate::validators;
+
+/// Extrator de propostas de um layout de ata
+///
+/// Cada órgão gera atas com pequenas variações; um layout novo é suportado registrando
+/// mais um extrator com `registrar`, sem mexer no processamento.
+pub trait ExtratorPropostas: Send + Sync {
+    /// Nome do layout, gravado em `tipo_formato` das propostas extraídas
+    fn nome(&self) -> &str;
+
+    /// O texto (uma seção de pregão) está no layout deste extrator
+    fn detecta(&self, text: &str) -> bool;
+
+    /// Propostas adjudicadas do texto; itens sem vencedor são extraídos à parte
+    fn extrair(&self, text: &str) -> Vec<PropostaAdjudicada>;
+}
+
+/// Extratores na ordem em que são testados; o de grupo vem antes porque o bloco de grupo
+/// também traz a linha de adjudicação do layout individual
+static REGISTRO: Lazy<RwLock<Vec<Arc<dyn ExtratorPropostas>>>> = Lazy::new(|| {
+    RwLock::new(vec![
+        Arc::new(ExtratorGrupo),
+        Arc::new(ExtratorIndividual),
+        Arc::new(ExtratorTermoAdjudicacao),
+    ])
+});
+
+/// Registra um extrator, com prioridade sobre os já registrados
+///
+/// Layouts específicos de um órgão costumam ser variações dos genéricos, por isso são testados antes.
+pub fn registrar(extrator: Arc<dyn ExtratorPropostas>) {
+    REGISTRO.write().unwrap_or_else(|e| e.into_inner()).insert(0, extrator);
+}
+
+/// Extratores registrados, na ordem em que são testados
+pub fn registrados() -> Vec<Arc<dyn ExtratorPropostas>> {
+    REGISTRO.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Nomes dos extratores registrados, na ordem em que são testados
+pub fn nomes_registrados() -> Vec<String> {
+    registrados().iter().map(|e| e.nome().to_string()).collect()
+}
+
+/// Primeiro extrator registrado que detecta o layout do texto
+pub fn selecionar(text: &str) -> Option<Arc<dyn ExtratorPropostas>> {
+    REGISTRO.read().unwrap_or_else(|e| e.into_inner()).iter().find(|e| e.detecta(text)).cloned()
+}
+
+static RE_TERMO_ADJUDICACAO: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)TERMO\s+DE\s+ADJUDICA[ÇC][ÃA]O").unwrap());
+
+/// Campo "Rótulo: valor" de uma linha do bloco do item
+fn campo<'a>(bloco: &'a str, re: &Regex) -> Option<&'a str> {
+    re.captures(bloco)
+        .and_then(|caps| caps.get(1))
+        .map(|valor| valor.as_str().trim())
+        .filter(|valor| !valor.is_empty())
+}
+
+/// Layout "Termo de Adjudicação" simples: um campo por linha em cada item
+///
+/// ```text
+/// TERMO DE ADJUDICAÇÃO
+/// Item: 1
+/// Descrição: Caneta esferográfica
+/// Quantidade: 100
+/// Valor estimado: R$ 2,00
+/// Adjudicatário: EMPRESA A LTDA
+/// CNPJ: 12.345.678/0001-90
+/// Valor adjudicado: R$ 1,50
+/// ```
+pub struct ExtratorTermoAdjudicacao;
+
+impl ExtratorPropostas for ExtratorTermoAdjudicacao {
+    fn nome(&self) -> &str {
+        "termo_adjudicacao"
+    }
+
+    fn detecta(&self, text: &str) -> bool {
+        RE_TERMO_ADJUDICACAO.is_match(text)
+    }
+
+    fn extrair(&self, text: &str) -> Vec<PropostaAdjudicada> {
+        static RE_DESCRICAO: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s*Descri[çc][ãa]o:\s*([^\n]+)").unwrap());
+        static RE_QUANTIDADE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s*Quantidade:\s*(\d+)").unwrap());
+        static RE_VALOR_ESTIMADO: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?mi)^\s*Valor\s+estimado:\s*R\$\s*([\d.,]+)").unwrap());
+        static RE_FORNECEDOR: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s*(?:Adjudicat[áa]rio|Fornecedor):\s*([^\n]+)").unwrap());
+        static RE_CNPJ: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s*CNPJ:\s*([\d./-]+)").unwrap());
+        static RE_VALOR_ADJUDICADO: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?mi)^\s*Valor\s+adjudicado:\s*R\$\s*([\d.,]+)").unwrap());
+        static RE_RESPONSAVEL: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?mi)^\s*Adjudicado\s+por:\s*([^\n]+)").unwrap());
+        static RE_MARCA: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s*Marca(?:/Fabricante)?:\s*([^\n]+)").unwrap());
+        static RE_MODELO: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s*Modelo(?:/[Vv]ers[ãa]o)?:\s*([^\n]+)").unwrap());
+
+        pdf_processor::blocos_de_itens(text).into_iter().filter_map(|(item, bloco)| {
+            let fornecedor = campo(bloco, &RE_FORNECEDOR)?;
+            let cnpj = campo(bloco, &RE_CNPJ)?;
+            let valor_adjudicado = campo(bloco, &RE_VALOR_ADJUDICADO)?;
+            let texto_ou_na = |re: &Regex| campo(bloco, re).unwrap_or("N/A").to_string();
+
+            Some(PropostaAdjudicada {
+                item,
+                grupo: None,
+                descricao: texto_ou_na(&RE_DESCRICAO),
+                quantidade: texto_ou_na(&RE_QUANTIDADE),
+                valor_estimado: campo(bloco, &RE_VALOR_ESTIMADO).and_then(Valor::de_texto),
+                valor_adjudicado: Valor::de_texto(valor_adjudicado),
+                valor_adjudicado_raw: valor_adjudicado.to_string(),
+                fornecedor: fornecedor.to_string(),
+                cnpj: cnpj.to_string(),
+                // O termo traz só o valor final, sem a disputa
+                melhor_lance: Valor::de_texto(valor_adjudicado),
+                responsavel: texto_ou_na(&RE_RESPONSAVEL),
+                cpf_responsavel: "N/A".to_string(),
+                marca_fabricante: texto_ou_na(&RE_MARCA),
+                modelo_versao: texto_ou_na(&RE_MODELO),
+                tipo_formato: self.nome().to_string(),
+                situacao: SITUACAO_ADJUDICADO.to_string(),
+                cnpj_valido: validators::validar_cnpj(cnpj),
+                descricao_reparada: false,
+                descricao_original: None,
+                observacoes: None,
+                porte: None,
+            })
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf_processor::processar_texto_licitacao;
+    use crate::texto;
+    use std::fs;
+
+    const ATA_INDIVIDUAL: &str = "UASG 787000\nPREGÃO 90008/2024\n\
+Item 1 - Caneta esferográfica\nQuantidade: 100\nValor estimado: R$ 2,00\n\
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL, para EMPRESA A LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 1,50\n";
+
+    const ATA_GRUPO: &str = "UASG 787000\nPREGÃO 90008/2024\n\
+Item 1 do Grupo G1 - Caneta esferográfica\nQuantidade: 100\nValor estimado: R$ 2,00\n\
+Situação: Adjudicado e Homologado\n\
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL para EMPRESA A LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 1,50\n";
+
+    const TERMO_ADJUDICACAO: &str = "TERMO DE ADJUDICAÇÃO\nUASG 787000\nPREGÃO 90008/2024\n\
+Item: 1\nDescrição: Caneta esferográfica\nQuantidade: 100\nValor estimado: R$ 2,00\n\
+Adjudicatário: EMPRESA A LTDA\nCNPJ: 12.345.678/0001-90\nValor adjudicado: R$ 1,50\nAdjudicado por: FULANO DE TAL\n\
+Item: 2\nDescrição: Papel A4\nQuantidade: 10\nValor estimado: R$ 30,00\n\
+Adjudicatário: EMPRESA B LTDA\nCNPJ: 11.222.333/0001-81\nValor adjudicado: R$ 25,00\n";
+
+    fn nome_selecionado(texto: &str) -> Option<String> {
+        selecionar(&texto::normalizar_texto(texto)).map(|extrator| extrator.nome().to_string())
+    }
+
+    #[test]
+    fn test_selecao_do_extrator_pelo_layout() {
+        assert_eq!(nome_selecionado(ATA_INDIVIDUAL).as_deref(), Some("individual"));
+        assert_eq!(nome_selecionado(ATA_GRUPO).as_deref(), Some("grupo"));
+        assert_eq!(nome_selecionado(TERMO_ADJUDICACAO).as_deref(), Some("termo_adjudicacao"));
+        assert_eq!(nome_selecionado("UASG 787000\nPREGÃO 90008/2024\nNenhum item"), None);
+
+        let nomes = nomes_registrados();
+        let posicao = |nome: &str| nomes.iter().position(|n| n == nome).unwrap();
+        assert!(posicao("grupo") < posicao("individual"));
+        assert!(posicao("individual") < posicao("termo_adjudicacao"));
+    }
+
+    struct ExtratorTeste;
+
+    impl ExtratorPropostas for ExtratorTeste {
+        fn nome(&self) -> &str {
+            "layout_teste"
+        }
+
+        fn detecta(&self, text: &str) -> bool {
+            text.contains("LAYOUT DE TESTE DO REGISTRO")
+        }
+
+        fn extrair(&self, _text: &str) -> Vec<PropostaAdjudicada> {
+            Vec::new()
+        }
+    }
+
+    #[test]
+    fn test_extrator_registrado_tem_prioridade() {
+        registrar(Arc::new(ExtratorTeste));
+        let texto = format!("LAYOUT DE TESTE DO REGISTRO\n{}", ATA_INDIVIDUAL);
+        assert_eq!(nome_selecionado(&texto).as_deref(), Some("layout_teste"));
+        // Sem a marca, os layouts existentes continuam valendo
+        assert_eq!(nome_selecionado(ATA_INDIVIDUAL).as_deref(), Some("individual"));
+    }
+
+    #[test]
+    fn test_termo_de_adjudicacao_no_processamento() {
+        let root = std::env::temp_dir().join(format!("licitacao360_termo_adjudicacao_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+
+        let texto = texto::normalizar_texto(TERMO_ADJUDICACAO);
+        let resultado = processar_texto_licitacao(&texto, &root.join("termo.pdf"), &root, &[], false, Vec::new()).unwrap();
+        let propostas: Vec<_> = resultado.propostas.iter()
+            .map(|p| (p.item.as_str(), p.descricao.as_str(), p.fornecedor.as_str(), p.valor_adjudicado, p.tipo_formato.as_str()))
+            .collect();
+        assert_eq!(propostas, [
+            ("1", "Caneta esferográfica", "EMPRESA A LTDA", Some(Valor(1.5)), "termo_adjudicacao"),
+            ("2", "Papel A4", "EMPRESA B LTDA", Some(Valor(25.0)), "termo_adjudicacao"),
+        ]);
+        assert_eq!(resultado.propostas[0].responsavel, "FULANO DE TAL");
+        assert_eq!(resultado.propostas[0].uasg, "787000");
+        assert!(resultado.propostas[1].cnpj_valido);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,423 @@
+use axum::extract::{Path as UrlPath, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+use tokio::task::{JoinError, JoinHandle};
+use tracing::warn;
+use crate::metrics;
+use crate::resultados::{self, FiltroPropostas};
+use crate::sicaf_processor;
+use crate::types::{ErrorKind, LicitacaoConsolidada, RoutingRule, TauriError};
+
+/// Endereço de escuta padrão: apenas a máquina local
+pub const HOST_PADRAO: &str = "127.0.0.1";
+
+/// Tamanho mínimo do token exigido pela API
+pub const TAMANHO_MINIMO_TOKEN: usize = 16;
+
+/// Dados compartilhados pelos handlers da API
+pub struct EstadoApi {
+    pub token: String,
+    pub output_dir: PathBuf,
+    pub regras: Vec<RoutingRule>,
+}
+
+/// Servidor em execução e o sinal para encerrá-lo
+pub struct ServidorHttp {
+    pub endereco: SocketAddr,
+    desligar: oneshot::Sender<()>,
+    tarefa: JoinHandle<()>,
+}
+
+/// Estado gerenciado pelo Tauri com o servidor ativo (se houver)
+pub type HttpApiState = Arc<Mutex<Option<ServidorHttp>>>;
+
+impl EstadoApi {
+    fn diretorios(&self) -> Vec<PathBuf> {
+        resultados::diretorios_resultados(&self.output_dir, &self.regras)
+    }
+
+    /// Carrega todas as licitações, ignorando arquivos ilegíveis
+    fn licitacoes(&self) -> Vec<(String, LicitacaoConsolidada)> {
+        resultados::listar_arquivos_licitacao(&self.diretorios())
+            .iter()
+            .filter_map(|path| {
+                resultados::carregar_licitacao(path)
+                    .ok()
+                    .map(|licitacao| (resultados::chave_licitacao(path), licitacao))
+            })
+            .collect()
+    }
+}
+
+/// Resposta de erro no mesmo formato usado pelos comandos
+fn erro(status: StatusCode, error_type: ErrorKind, message: String) -> Response {
+    (status, Json(TauriError::new(error_type, message, None))).into_response()
+}
+
+/// Compara os tokens sem encerrar na primeira diferença
+fn tokens_iguais(recebido: &str, esperado: &str) -> bool {
+    recebido.len() == esperado.len()
+        && recebido.bytes().zip(esperado.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// Roda a leitura dos arquivos numa thread de bloqueio, sem ocupar as threads do runtime
+async fn em_bloqueio<T, F>(estado: Arc<EstadoApi>, leitura: F) -> Result<T, JoinError>
+where
+    T: Send + 'static,
+    F: FnOnce(&EstadoApi) -> T + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || leitura(&estado)).await
+}
+
+fn erro_leitura(e: JoinError) -> Response {
+    erro(StatusCode::INTERNAL_SERVER_ERROR, ErrorKind::SystemError, format!("Leitura interrompida: {}", e))
+}
+
+/// Exige `Authorization: Bearer <token>` em todas as rotas
+async fn autenticar(State(estado): State<Arc<EstadoApi>>, request: Request, next: Next) -> Response {
+    let autorizado = request.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|valor| valor.to_str().ok())
+        .and_then(|valor| valor.strip_prefix("Bearer "))
+        .map(|token| tokens_iguais(token.trim(), &estado.token))
+        .unwrap_or(false);
+
+    if !autorizado {
+        return erro(StatusCode::UNAUTHORIZED, ErrorKind::Unauthorized, "Token de acesso inválido ou ausente".to_string());
+    }
+
+    next.run(request).await
+}
+
+async fn overview(State(estado): State<Arc<EstadoApi>>) -> Response {
+    let resumo = em_bloqueio(estado, |estado| {
+        let licitacoes = estado.licitacoes();
+
+        let total_propostas: usize = licitacoes.iter().map(|(_, l)| l.propostas.len()).sum();
+        let valor_total: f64 = licitacoes.iter().map(|(_, l)| l.valor_total).sum();
+        let fornecedores: HashSet<&str> = licitacoes.iter()
+            .flat_map(|(_, l)| l.propostas.iter().map(|p| p.cnpj.as_str()))
+            .collect();
+
+        let sicaf_path = estado.output_dir.join("sicaf_dados.json");
+        let total_registros_sicaf = if sicaf_path.exists() {
+            sicaf_processor::carregar_sicaf_json(&sicaf_path).map(|d| d.len()).unwrap_or(0)
+        } else {
+            0
+        };
+
+        serde_json::json!({
+            "total_licitacoes": licitacoes.len(),
+            "total_propostas": total_propostas,
+            "valor_total": valor_total,
+            "total_fornecedores": fornecedores.len(),
+            "total_registros_sicaf": total_registros_sicaf
+        })
+    }).await;
+
+    match resumo {
+        Ok(resumo) => Json(resumo).into_response(),
+        Err(e) => erro_leitura(e),
+    }
+}
+
+async fn listar_licitacoes(State(estado): State<Arc<EstadoApi>>) -> Response {
+    let lista = em_bloqueio(estado, |estado| {
+        estado.licitacoes()
+            .into_iter()
+            .map(|(chave, l)| serde_json::json!({
+                "key": chave,
+                "uasg": l.uasg,
+                "pregao": l.pregao,
+                "modalidade": l.modalidade,
+                "processo": l.processo,
+                "total_propostas": l.total_propostas,
+                "valor_total": l.valor_total
+            }))
+            .collect::<Vec<serde_json::Value>>()
+    }).await;
+
+    match lista {
+        Ok(lista) => Json(lista).into_response(),
+        Err(e) => erro_leitura(e),
+    }
+}
+
+async fn obter_licitacao(State(estado): State<Arc<EstadoApi>>, UrlPath(chave): UrlPath<String>) -> Response {
+    if !resultados::chave_valida(&chave) {
+        return erro(StatusCode::BAD_REQUEST, ErrorKind::ValidationError, format!("Chave de licitação inválida: {}", chave));
+    }
+
+    let busca = em_bloqueio(estado, {
+        let chave = chave.clone();
+        move |estado| resultados::encontrar_licitacao(&estado.diretorios(), &chave).map(|path| resultados::carregar_licitacao(&path))
+    }).await;
+
+    match busca {
+        Ok(Some(Ok(licitacao))) => Json(licitacao).into_response(),
+        Ok(Some(Err(e))) => erro(StatusCode::INTERNAL_SERVER_ERROR, ErrorKind::ParseError, format!("{:#}", e)),
+        Ok(None) => erro(StatusCode::NOT_FOUND, ErrorKind::NotFound, format!("Licitação não encontrada: {}", chave)),
+        Err(e) => erro_leitura(e),
+    }
+}
+
+async fn buscar_propostas(State(estado): State<Arc<EstadoApi>>, Query(filtro): Query<FiltroPropostas>) -> Response {
+    let busca = em_bloqueio(estado, move |estado| {
+        let licitacoes: Vec<LicitacaoConsolidada> = estado.licitacoes().into_iter().map(|(_, l)| l).collect();
+        resultados::buscar_propostas(&licitacoes, &filtro)
+    }).await;
+
+    match busca {
+        Ok(busca) => Json(busca).into_response(),
+        Err(e) => erro_leitura(e),
+    }
+}
+
+async fn obter_sicaf(State(estado): State<Arc<EstadoApi>>, UrlPath(cnpj): UrlPath<String>) -> Response {
+    let registros = em_bloqueio(estado, |estado| {
+        let sicaf_path = estado.output_dir.join("sicaf_dados.json");
+        if sicaf_path.exists() {
+            sicaf_processor::carregar_sicaf_json(&sicaf_path)
+        } else {
+            Ok(Vec::new())
+        }
+    }).await;
+
+    let registros = match registros {
+        Ok(Ok(registros)) => registros,
+        Ok(Err(e)) => return erro(StatusCode::INTERNAL_SERVER_ERROR, ErrorKind::ProcessingError, format!("{:#}", e)),
+        Err(e) => return erro_leitura(e),
+    };
+
+    match sicaf_processor::obter_dados_cnpj(&cnpj, &registros) {
+        Some(dados) => Json(dados.clone()).into_response(),
+        None => erro(StatusCode::NOT_FOUND, ErrorKind::NotFound, format!("CNPJ não encontrado no SICAF: {}", cnpj)),
+    }
+}
+
+/// Métricas no formato de exposição do Prometheus
+async fn obter_metricas() -> Response {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        metrics::texto_metricas(),
+    ).into_response()
+}
+
+/// Monta as rotas somente leitura da API, todas protegidas pelo token
+pub fn criar_router(estado: Arc<EstadoApi>) -> Router {
+    Router::new()
+        .route("/overview", get(overview))
+        .route("/licitacoes", get(listar_licitacoes))
+        .route("/licitacoes/{key}", get(obter_licitacao))
+        .route("/propostas/search", get(buscar_propostas))
+        .route("/sicaf/{cnpj}", get(obter_sicaf))
+        .route("/metrics", get(obter_metricas))
+        .route_layer(middleware::from_fn_with_state(estado.clone(), autenticar))
+        .with_state(estado)
+}
+
+/// Abre a porta e inicia o servidor em segundo plano
+pub async fn iniciar_servidor(host: &str, port: u16, estado: EstadoApi) -> std::io::Result<ServidorHttp> {
+    let listener = tokio::net::TcpListener::bind((host, port)).await?;
+    let endereco = listener.local_addr()?;
+    let (desligar, sinal) = oneshot::channel::<()>();
+    let router = criar_router(Arc::new(estado));
+
+    let tarefa = tokio::spawn(async move {
+        let resultado = axum::serve(listener, router)
+            .with_graceful_shutdown(async {
+                let _ = sinal.await;
+            })
+            .await;
+
+        if let Err(e) = resultado {
+            warn!(erro = %e, "Erro na API HTTP");
+        }
+    });
+
+    Ok(ServidorHttp { endereco, desligar, tarefa })
+}
+
+impl ServidorHttp {
+    /// Sinaliza o encerramento e aguarda as conexões em andamento terminarem
+    pub async fn parar(self) {
+        let _ = self.desligar.send(());
+        let _ = self.tarefa.await;
+    }
+}
+
+/// Sinaliza o encerramento do servidor ativo sem aguardar (usado na saída do aplicativo)
+pub fn encerrar(estado: &HttpApiState) {
+    let servidor = estado.lock().unwrap_or_else(|e| e.into_inner()).take();
+    if let Some(servidor) = servidor {
+        let _ = servidor.desligar.send(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    const TOKEN: &str = "token-de-teste-0123456789";
+
+    fn preparar(nome: &str) -> (PathBuf, Router) {
+        let dir = std::env::temp_dir().join(format!("licitacao360_api_{}_{}", nome, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let licitacao = serde_json::json!({
+            "uasg": "787000",
+            "pregao": "90008/2024",
+            "processo": "123",
+            "total_propostas": 2,
+            "valor_total": 300.0,
+            "propostas": [
+                proposta("1", "Caneta esferográfica azul", "12.345.678/0001-90", "100,00"),
+                proposta("2", "Papel A4", "98.765.432/0001-10", "200,00")
+            ]
+        });
+        std::fs::write(dir.join("licitacao_787000-90008_2024-123.json"), licitacao.to_string()).unwrap();
+
+        let sicaf = serde_json::json!({
+            "registros_sicaf": [{
+                "cnpj": "12.345.678/0001-90",
+                "duns": null,
+                "empresa": "EMPRESA LTDA",
+                "nome_fantasia": null,
+                "situacao_cadastro": "Credenciado",
+                "data_vencimento": null,
+                "cep": null,
+                "endereco": null,
+                "municipio": null,
+                "uf": null,
+                "telefone": null,
+                "email": null,
+                "cpf_responsavel": null,
+                "nome_responsavel": null
+            }]
+        });
+        std::fs::write(dir.join("sicaf_dados.json"), sicaf.to_string()).unwrap();
+
+        let router = criar_router(Arc::new(EstadoApi {
+            token: TOKEN.to_string(),
+            output_dir: dir.clone(),
+            regras: Vec::new(),
+        }));
+        (dir, router)
+    }
+
+    fn proposta(item: &str, descricao: &str, cnpj: &str, valor: &str) -> serde_json::Value {
+        serde_json::json!({
+            "uasg": "787000", "pregao": "90008/2024", "processo": "123", "item": item,
+            "grupo": null, "quantidade": "1", "descricao": descricao, "valor_estimado": valor,
+            "valor_adjudicado": valor, "fornecedor": "EMPRESA LTDA", "cnpj": cnpj,
+            "marca_fabricante": "N/A", "modelo_versao": "N/A", "responsavel": "FULANO",
+            "melhor_lance": valor, "tipo_formato": "individual"
+        })
+    }
+
+    async fn get(router: &Router, uri: &str, token: Option<&str>) -> (StatusCode, serde_json::Value) {
+        let mut request = Request::builder().uri(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+
+        let response = router.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_rotas_exigem_token() {
+        let (dir, router) = preparar("auth");
+
+        for uri in ["/overview", "/licitacoes", "/propostas/search", "/sicaf/12345678000190", "/metrics"] {
+            let (status, corpo) = get(&router, uri, None).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", uri);
+            assert_eq!(corpo["error_type"], "Unauthorized");
+
+            let (status, _) = get(&router, uri, Some("token-errado-0123456789")).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", uri);
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_endpoints_somente_leitura() {
+        let (dir, router) = preparar("endpoints");
+
+        let (status, corpo) = get(&router, "/overview", Some(TOKEN)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(corpo["total_licitacoes"], 1);
+        assert_eq!(corpo["total_propostas"], 2);
+        assert_eq!(corpo["total_fornecedores"], 2);
+        assert_eq!(corpo["total_registros_sicaf"], 1);
+
+        let (status, corpo) = get(&router, "/licitacoes", Some(TOKEN)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(corpo[0]["key"], "787000-90008_2024-123");
+
+        let (status, corpo) = get(&router, "/licitacoes/787000-90008_2024-123", Some(TOKEN)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(corpo["propostas"].as_array().unwrap().len(), 2);
+
+        let (status, _) = get(&router, "/licitacoes/inexistente", Some(TOKEN)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = get(&router, "/licitacoes/..%2F..%2Fconfig", Some(TOKEN)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, corpo) = get(&router, "/propostas/search?q=caneta", Some(TOKEN)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(corpo.as_array().unwrap().len(), 1);
+        assert_eq!(corpo[0]["item"], "1");
+
+        let (status, corpo) = get(&router, "/propostas/search?cnpj=98765432000110", Some(TOKEN)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(corpo[0]["item"], "2");
+
+        let (status, corpo) = get(&router, "/sicaf/12345678000190", Some(TOKEN)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(corpo["empresa"], "EMPRESA LTDA");
+
+        let (status, _) = get(&router, "/sicaf/00000000000000", Some(TOKEN)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_servidor_inicia_e_encerra() {
+        let dir = std::env::temp_dir();
+        let servidor = iniciar_servidor(HOST_PADRAO, 0, EstadoApi {
+            token: TOKEN.to_string(),
+            output_dir: dir,
+            regras: Vec::new(),
+        }).await.unwrap();
+
+        let endereco = servidor.endereco;
+        assert!(endereco.ip().is_loopback());
+
+        let mut stream = tokio::net::TcpStream::connect(endereco).await.unwrap();
+        tokio::io::AsyncWriteExt::write_all(&mut stream, b"GET /overview HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").await.unwrap();
+        let mut resposta = String::new();
+        tokio::io::AsyncReadExt::read_to_string(&mut stream, &mut resposta).await.unwrap();
+        assert!(resposta.starts_with("HTTP/1.1 401"));
+
+        servidor.parar().await;
+        assert!(tokio::net::TcpStream::connect(endereco).await.is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::sync::RwLock;
+
+/// Idioma das mensagens devolvidas ao frontend
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Idioma {
+    #[default]
+    #[serde(rename = "pt-BR")]
+    PtBr,
+    #[serde(rename = "es")]
+    Es,
+    #[serde(rename = "en")]
+    En,
+}
+
+/// Idioma em uso, atualizado sempre que a configuração é carregada ou alterada
+static IDIOMA_ATUAL: RwLock<Idioma> = RwLock::new(Idioma::PtBr);
+
+pub fn definir_idioma(idioma: Idioma) {
+    *IDIOMA_ATUAL.write().unwrap_or_else(|e| e.into_inner()) = idioma;
+}
+
+pub fn idioma_atual() -> Idioma {
+    *IDIOMA_ATUAL.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Argumentos nomeados de uma mensagem, substituídos nos `{nome}` do modelo
+pub type Args<'a> = [(&'a str, &'a dyn Display)];
+
+/// Chave de uma mensagem traduzível; o frontend recebe o nome em snake_case
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MsgKey {
+    FileNotFound,
+    DirectoryNotFound,
+    InputDirectoryNotFound,
+    InvalidAtaExtension,
+    NoAtaFilesFound,
+    InvalidExcludePattern,
+    OutputDirectoryCreateFailed,
+    LicitacaoJsonSaveFailed,
+    ConsolidatedJsonSaveFailed,
+    FileProcessingFailed,
+    DirectoryProcessingFailed,
+    ProcessedIndexLoadFailed,
+    SessionsLoadFailed,
+    SessionProposalsLoadFailed,
+    SessionNotFound,
+    SessionInProgress,
+    SessionAlreadyFinished,
+    SessionOrProposalsRequired,
+    PdfValidationFailed,
+    FileProcessed,
+    AllFilesAlreadyProcessed,
+    DryRunSummary,
+    ProcessingFinished,
+    ProcessingCancelled,
+    ProcessingStarted,
+    ResumedFromPreviousRun,
+    FilesByType,
+    PasswordProtectedFiles,
+    SkippedFiles,
+    DuplicateProposalsIgnored,
+    FilesArchived,
+    OldResultsArchived,
+    SicafExecutionFailed,
+    SicafProcessingFailed,
+    SicafLoadFailed,
+    SicafSaveFailed,
+    SicafCnpjNotFound,
+    SicafLayoutNotRecognized,
+    InvalidCnpj,
+    InvalidSimilarityThreshold,
+    JsonWithoutProposals,
+    ComparisonReportFailed,
+}
+
+impl MsgKey {
+    /// Todas as chaves, para conferir os catálogos
+    pub const TODAS: [MsgKey; 42] = [
+        MsgKey::FileNotFound, MsgKey::DirectoryNotFound, MsgKey::InputDirectoryNotFound, MsgKey::InvalidAtaExtension,
+        MsgKey::NoAtaFilesFound, MsgKey::InvalidExcludePattern, MsgKey::OutputDirectoryCreateFailed, MsgKey::LicitacaoJsonSaveFailed,
+        MsgKey::ConsolidatedJsonSaveFailed, MsgKey::FileProcessingFailed, MsgKey::DirectoryProcessingFailed,
+        MsgKey::ProcessedIndexLoadFailed, MsgKey::SessionsLoadFailed, MsgKey::SessionProposalsLoadFailed,
+        MsgKey::SessionNotFound, MsgKey::SessionInProgress, MsgKey::SessionAlreadyFinished,
+        MsgKey::SessionOrProposalsRequired, MsgKey::PdfValidationFailed, MsgKey::FileProcessed,
+        MsgKey::AllFilesAlreadyProcessed, MsgKey::DryRunSummary, MsgKey::ProcessingFinished, MsgKey::ProcessingCancelled,
+        MsgKey::ProcessingStarted, MsgKey::ResumedFromPreviousRun, MsgKey::FilesByType, MsgKey::PasswordProtectedFiles, MsgKey::SkippedFiles,
+        MsgKey::DuplicateProposalsIgnored, MsgKey::FilesArchived, MsgKey::OldResultsArchived,
+        MsgKey::SicafExecutionFailed, MsgKey::SicafProcessingFailed, MsgKey::SicafLoadFailed, MsgKey::SicafSaveFailed,
+        MsgKey::SicafCnpjNotFound, MsgKey::SicafLayoutNotRecognized, MsgKey::InvalidCnpj, MsgKey::InvalidSimilarityThreshold,
+        MsgKey::JsonWithoutProposals, MsgKey::ComparisonReportFailed,
+    ];
+
+    /// Modelos da mensagem em pt-BR, es e en
+    fn modelos(self) -> [&'static str; 3] {
+        match self {
+            MsgKey::FileNotFound => ["Arquivo não encontrado: {arquivo}", "Archivo no encontrado: {arquivo}", "File not found: {arquivo}"],
+            MsgKey::DirectoryNotFound => ["Diretório não encontrado: {diretorio}", "Directorio no encontrado: {diretorio}", "Directory not found: {diretorio}"],
+            MsgKey::InputDirectoryNotFound => ["Diretório de entrada não encontrado: {diretorio}", "Directorio de entrada no encontrado: {diretorio}", "Input directory not found: {diretorio}"],
+            MsgKey::InvalidAtaExtension => ["O arquivo deve ter extensão .pdf, .html, .htm ou .txt", "El archivo debe tener extensión .pdf, .html, .htm o .txt", "The file must have a .pdf, .html, .htm or .txt extension"],
+            MsgKey::NoAtaFilesFound => ["Nenhum arquivo PDF, HTML ou TXT encontrado no diretório especificado", "No se encontró ningún archivo PDF, HTML o TXT en el directorio indicado", "No PDF, HTML or TXT files found in the given directory"],
+            MsgKey::InvalidExcludePattern => ["Padrão de exclusão inválido: {erro}", "Patrón de exclusión inválido: {erro}", "Invalid exclude pattern: {erro}"],
+            MsgKey::OutputDirectoryCreateFailed => ["Erro ao criar diretório de saída: {erro}", "Error al crear el directorio de salida: {erro}", "Failed to create output directory: {erro}"],
+            MsgKey::LicitacaoJsonSaveFailed => ["Erro ao salvar JSON da licitação: {erro}", "Error al guardar el JSON de la licitación: {erro}", "Failed to save the tender JSON: {erro}"],
+            MsgKey::ConsolidatedJsonSaveFailed => ["Erro ao salvar JSON consolidado: {erro}", "Error al guardar el JSON consolidado: {erro}", "Failed to save the consolidated JSON: {erro}"],
+            MsgKey::FileProcessingFailed => ["Erro ao processar arquivo: {erro}", "Error al procesar el archivo: {erro}", "Failed to process file: {erro}"],
+            MsgKey::DirectoryProcessingFailed => ["Erro ao processar diretório: {erro}", "Error al procesar el directorio: {erro}", "Failed to process directory: {erro}"],
+            MsgKey::ProcessedIndexLoadFailed => ["Erro ao carregar índice de PDFs processados: {erro}", "Error al cargar el índice de PDF procesados: {erro}", "Failed to load the processed PDF index: {erro}"],
+            MsgKey::SessionsLoadFailed => ["Erro ao carregar sessões de processamento: {erro}", "Error al cargar las sesiones de procesamiento: {erro}", "Failed to load processing sessions: {erro}"],
+            MsgKey::SessionProposalsLoadFailed => ["Erro ao carregar propostas da sessão: {erro}", "Error al cargar las propuestas de la sesión: {erro}", "Failed to load the session proposals: {erro}"],
+            MsgKey::SessionNotFound => ["Sessão de processamento não encontrada: {sessao}", "Sesión de procesamiento no encontrada: {sessao}", "Processing session not found: {sessao}"],
+            MsgKey::SessionInProgress => ["A sessão {sessao} ainda está em andamento", "La sesión {sessao} todavía está en curso", "Session {sessao} is still running"],
+            MsgKey::SessionAlreadyFinished => ["A sessão {sessao} já foi concluída", "La sesión {sessao} ya fue concluida", "Session {sessao} has already finished"],
+            MsgKey::SessionOrProposalsRequired => ["Informe um session_id ou a lista de propostas", "Indique un session_id o la lista de propuestas", "Provide a session_id or the list of proposals"],
+            MsgKey::PdfValidationFailed => ["Erro ao validar o PDF: {erro}", "Error al validar el PDF: {erro}", "Failed to validate the PDF: {erro}"],
+            MsgKey::FileProcessed => ["Arquivo processado com sucesso: {propostas} propostas encontradas", "Archivo procesado con éxito: {propostas} propuestas encontradas", "File processed successfully: {propostas} proposals found"],
+            MsgKey::AllFilesAlreadyProcessed => ["Todos os {total} arquivos já foram processados", "Los {total} archivos ya fueron procesados", "All {total} files have already been processed"],
+            MsgKey::DryRunSummary => ["Simulação: {total} arquivos encontrados, {processados} já processados; nada foi gravado", "Simulación: {total} archivos encontrados, {processados} ya procesados; no se guardó nada", "Dry run: {total} files found, {processados} already processed; nothing was written"],
+            MsgKey::ProcessingFinished => ["Processamento concluído: {total} arquivos processados", "Procesamiento concluido: {total} archivos procesados", "Processing finished: {total} files processed"],
+            MsgKey::ProcessingCancelled => ["Processamento cancelado: {processados} de {total} arquivos processados", "Procesamiento cancelado: {processados} de {total} archivos procesados", "Processing cancelled: {processados} of {total} files processed"],
+            MsgKey::ProcessingStarted => ["Processamento iniciado em segundo plano: {total} arquivos na sessão {sessao}", "Procesamiento iniciado en segundo plano: {total} archivos en la sesión {sessao}", "Processing started in the background: {total} files in session {sessao}"],
+            MsgKey::ResumedFromPreviousRun => [", {quantidade} retomados de uma execução anterior", ", {quantidade} retomados de una ejecución anterior", ", {quantidade} resumed from a previous run"],
+            MsgKey::FilesByType => [" ({tipos})", " ({tipos})", " ({tipos})"],
+            MsgKey::PasswordProtectedFiles => [", {quantidade} arquivos protegidos por senha", ", {quantidade} archivos protegidos con contraseña", ", {quantidade} password-protected files"],
+            MsgKey::SkippedFiles => [", {quantidade} arquivos pulados (já processados)", ", {quantidade} archivos omitidos (ya procesados)", ", {quantidade} files skipped (already processed)"],
+            MsgKey::DuplicateProposalsIgnored => [", {quantidade} propostas duplicadas ignoradas", ", {quantidade} propuestas duplicadas ignoradas", ", {quantidade} duplicate proposals ignored"],
+            MsgKey::FilesArchived => [", {quantidade} arquivos arquivados em {pasta}", ", {quantidade} archivos archivados en {pasta}", ", {quantidade} files archived to {pasta}"],
+            MsgKey::OldResultsArchived => [", {quantidade} resultados antigos movidos para {pasta}", ", {quantidade} resultados antiguos movidos a {pasta}", ", {quantidade} old results moved to {pasta}"],
+            MsgKey::SicafExecutionFailed => ["Erro ao executar o processamento SICAF: {erro}", "Error al ejecutar el procesamiento SICAF: {erro}", "Failed to run SICAF processing: {erro}"],
+            MsgKey::SicafProcessingFailed => ["Erro ao processar PDFs SICAF: {erro}", "Error al procesar los PDF del SICAF: {erro}", "Failed to process SICAF PDFs: {erro}"],
+            MsgKey::SicafLoadFailed => ["Erro ao carregar dados SICAF: {erro}", "Error al cargar los datos del SICAF: {erro}", "Failed to load SICAF data: {erro}"],
+            MsgKey::SicafSaveFailed => ["Erro ao salvar dados SICAF: {erro}", "Error al guardar los datos del SICAF: {erro}", "Failed to save SICAF data: {erro}"],
+            MsgKey::SicafCnpjNotFound => ["CNPJ não encontrado nos dados SICAF: {cnpj}", "CNPJ no encontrado en los datos del SICAF: {cnpj}", "CNPJ not found in the SICAF data: {cnpj}"],
+            MsgKey::SicafLayoutNotRecognized => ["Dados SICAF não reconhecidos em {arquivo}; o trecho após \"CNPJ:\" está nos detalhes", "Datos del SICAF no reconocidos en {arquivo}; el fragmento después de \"CNPJ:\" está en los detalles", "SICAF data not recognized in {arquivo}; the text after \"CNPJ:\" is in the details"],
+            MsgKey::InvalidCnpj => ["CNPJ inválido: {cnpj}", "CNPJ inválido: {cnpj}", "Invalid CNPJ: {cnpj}"],
+            MsgKey::InvalidSimilarityThreshold => ["Limiar de similaridade inválido: {limiar} (esperado entre 0 e 1)", "Umbral de similitud inválido: {limiar} (se esperaba entre 0 y 1)", "Invalid similarity threshold: {limiar} (expected between 0 and 1)"],
+            MsgKey::JsonWithoutProposals => ["Arquivo JSON não contém propostas válidas", "El archivo JSON no contiene propuestas válidas", "The JSON file has no valid proposals"],
+            MsgKey::ComparisonReportFailed => ["Erro ao gerar relatório de comparação: {erro}", "Error al generar el informe de comparación: {erro}", "Failed to generate the comparison report: {erro}"],
+        }
+    }
+
+    fn modelo(self, idioma: Idioma) -> &'static str {
+        let [pt, es, en] = self.modelos();
+        match idioma {
+            Idioma::PtBr => pt,
+            Idioma::Es => es,
+            Idioma::En => en,
+        }
+    }
+}
+
+/// Mensagem no idioma informado; argumentos sem `{nome}` correspondente são ignorados
+pub fn traduzir(idioma: Idioma, chave: MsgKey, args: &Args) -> String {
+    args.iter().fold(chave.modelo(idioma).to_string(), |mensagem, (nome, valor)| {
+        mensagem.replace(&format!("{{{}}}", nome), &valor.to_string())
+    })
+}
+
+/// Mensagem no idioma configurado
+pub fn t(chave: MsgKey, args: &Args) -> String {
+    traduzir(idioma_atual(), chave, args)
+}
+
+/// Argumentos já formatados, como o `TauriError` os leva ao frontend
+pub fn args_texto(args: &Args) -> BTreeMap<String, String> {
+    args.iter().map(|(nome, valor)| (nome.to_string(), valor.to_string())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Nomes dos `{argumentos}` de um modelo, ordenados
+    fn argumentos(modelo: &str) -> Vec<&str> {
+        let mut nomes: Vec<&str> = modelo.split('{').skip(1).filter_map(|parte| parte.split_once('}')).map(|(nome, _)| nome).collect();
+        nomes.sort();
+        nomes
+    }
+
+    #[test]
+    fn test_catalogos_completos_e_com_os_mesmos_argumentos() {
+        for chave in MsgKey::TODAS {
+            let [pt, es, en] = chave.modelos();
+            assert!(!pt.is_empty() && !es.is_empty() && !en.is_empty(), "{:?}", chave);
+            assert_eq!(argumentos(pt), argumentos(es), "{:?}", chave);
+            assert_eq!(argumentos(pt), argumentos(en), "{:?}", chave);
+        }
+    }
+
+    #[test]
+    fn test_traduzir_substitui_argumentos() {
+        let args: &Args = &[("processados", &3), ("total", &10)];
+        assert_eq!(traduzir(Idioma::PtBr, MsgKey::ProcessingCancelled, args), "Processamento cancelado: 3 de 10 arquivos processados");
+        assert_eq!(traduzir(Idioma::Es, MsgKey::ProcessingCancelled, args), "Procesamiento cancelado: 3 de 10 archivos procesados");
+        assert_eq!(traduzir(Idioma::En, MsgKey::SessionNotFound, &[("sessao", &"pdf_1")]), "Processing session not found: pdf_1");
+        assert_eq!(serde_json::to_value(MsgKey::SicafCnpjNotFound).unwrap(), "sicaf_cnpj_not_found");
+        assert_eq!(serde_json::from_value::<Idioma>("pt-BR".into()).unwrap(), Idioma::PtBr);
+    }
+}
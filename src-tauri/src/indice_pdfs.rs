@@ -0,0 +1,226 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use crate::integridade;
+use crate::types::{DryRunArquivo, DryRunReport, GrupoPdfsDuplicados};
+
+/// Índice dos PDFs já processados, em Database/Config
+pub const ARQUIVO_INDICE: &str = "processed_index.json";
+
+/// Serializa as regravações do índice entre threads
+static ESCRITA: Mutex<()> = Mutex::new(());
+
+/// PDF processado, registrado pelo SHA-256 do conteúdo
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EntradaIndicePdf {
+    /// Caminho do PDF no último processamento
+    pub arquivo: String,
+    /// Data do último processamento (RFC 3339)
+    pub processado_em: String,
+    /// JSONs de licitação gerados a partir do PDF
+    #[serde(default)]
+    pub jsons: Vec<String>,
+}
+
+fn caminho_indice(config_dir: &Path) -> PathBuf {
+    config_dir.join(ARQUIVO_INDICE)
+}
+
+/// Carrega o índice hash → PDF processado (vazio se o arquivo ainda não existir)
+pub fn carregar_indice(config_dir: &Path) -> Result<BTreeMap<String, EntradaIndicePdf>> {
+    let path = caminho_indice(config_dir);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let content = fs::read_to_string(&path)
+        .context(format!("Erro ao ler {}", path.display()))?;
+    serde_json::from_str(&content)
+        .context(format!("Erro ao analisar {}", path.display()))
+}
+
+/// Registra (ou atualiza) os PDFs processados no índice
+pub fn registrar_processados(config_dir: &Path, entradas: Vec<(String, EntradaIndicePdf)>) -> Result<()> {
+    if entradas.is_empty() {
+        return Ok(());
+    }
+
+    let _guard = ESCRITA.lock().unwrap_or_else(|e| e.into_inner());
+    let mut indice = carregar_indice(config_dir)?;
+    indice.extend(entradas);
+    gravar_indice(config_dir, &indice)
+}
+
+/// Aponta os JSONs registrados no índice para os novos caminhos de arquivos movidos (ex.: pela retenção)
+pub fn mover_jsons(config_dir: &Path, movidos: &[(PathBuf, PathBuf)]) -> Result<()> {
+    let _guard = ESCRITA.lock().unwrap_or_else(|e| e.into_inner());
+    let mut indice = carregar_indice(config_dir)?;
+
+    let mut alterado = false;
+    for json in indice.values_mut().flat_map(|entrada| entrada.jsons.iter_mut()) {
+        if let Some((_, destino)) = movidos.iter().find(|(origem, _)| origem == Path::new(json.as_str())) {
+            *json = destino.to_string_lossy().to_string();
+            alterado = true;
+        }
+    }
+    if alterado {
+        gravar_indice(config_dir, &indice)?;
+    }
+    Ok(())
+}
+
+fn gravar_indice(config_dir: &Path, indice: &BTreeMap<String, EntradaIndicePdf>) -> Result<()> {
+    let content = serde_json::to_string_pretty(indice)
+        .context("Erro ao serializar índice de PDFs processados")?;
+    integridade::escrever_atomico(&caminho_indice(config_dir), content.as_bytes())
+}
+
+/// Separa os PDFs cujo conteúdo já está no índice, retornando (pendentes, pulados)
+///
+/// PDFs que não puderem ser lidos ficam entre os pendentes, para que o erro apareça no processamento.
+pub fn separar_ja_processados(
+    arquivos: Vec<PathBuf>,
+    indice: &BTreeMap<String, EntradaIndicePdf>,
+) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    arquivos.into_iter().partition(|path| {
+        integridade::sha256_arquivo(path).ok().is_none_or(|hash| !indice.contains_key(&hash))
+    })
+}
+
+/// Monta o relatório de um dry-run: tamanho de cada arquivo, se já foi processado e o tempo estimado
+///
+/// Só lê os arquivos para calcular o hash; nada é extraído nem gravado.
+pub fn simular_processamento(
+    input_dir: &str,
+    arquivos: &[PathBuf],
+    indice: &BTreeMap<String, EntradaIndicePdf>,
+    pular_processados: bool,
+    bytes_por_segundo: Option<f64>,
+) -> DryRunReport {
+    let arquivos: Vec<DryRunArquivo> = arquivos.iter()
+        .map(|path| DryRunArquivo {
+            caminho: path.to_string_lossy().to_string(),
+            tamanho: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+            ja_processado: integridade::sha256_arquivo(path).is_ok_and(|hash| indice.contains_key(&hash)),
+        })
+        .collect();
+    let total_bytes = arquivos.iter().map(|a| a.tamanho).sum();
+    let bytes_a_processar = arquivos.iter()
+        .filter(|a| !(pular_processados && a.ja_processado))
+        .map(|a| a.tamanho)
+        .sum::<u64>();
+    DryRunReport {
+        input_dir: input_dir.to_string(),
+        total_arquivos: arquivos.len(),
+        ja_processados: arquivos.iter().filter(|a| a.ja_processado).count(),
+        total_bytes,
+        bytes_a_processar,
+        bytes_por_segundo,
+        tempo_estimado_segundos: bytes_por_segundo.map(|vazao| bytes_a_processar as f64 / vazao),
+        arquivos,
+    }
+}
+
+/// Agrupa os arquivos com conteúdo idêntico
+///
+/// Só os arquivos com tamanho repetido têm o hash calculado.
+pub fn agrupar_duplicados(arquivos: &[PathBuf]) -> Vec<GrupoPdfsDuplicados> {
+    let mut por_tamanho: HashMap<u64, Vec<&PathBuf>> = HashMap::new();
+    for path in arquivos {
+        if let Ok(metadata) = fs::metadata(path) {
+            por_tamanho.entry(metadata.len()).or_default().push(path);
+        }
+    }
+
+    let mut por_hash: BTreeMap<String, (u64, Vec<String>)> = BTreeMap::new();
+    for (tamanho, candidatos) in por_tamanho.into_iter().filter(|(_, candidatos)| candidatos.len() > 1) {
+        for path in candidatos {
+            if let Ok(hash) = integridade::sha256_arquivo(path) {
+                por_hash.entry(hash).or_insert_with(|| (tamanho, Vec::new())).1.push(path.to_string_lossy().to_string());
+            }
+        }
+    }
+
+    por_hash.into_iter()
+        .filter(|(_, (_, arquivos))| arquivos.len() > 1)
+        .map(|(sha256, (tamanho, mut arquivos))| {
+            arquivos.sort();
+            GrupoPdfsDuplicados { sha256, tamanho, arquivos }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indice_pula_pdfs_processados_mesmo_renomeados() {
+        let dir = std::env::temp_dir().join(format!("licitacao360_indice_pdfs_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let original = dir.join("ata.pdf");
+        let renomeado = dir.join("ata (copia).pdf");
+        let outro = dir.join("outra.pdf");
+        let mesmo_tamanho = dir.join("outra_versao.pdf");
+        fs::write(&original, b"%PDF-1.4 ata").unwrap();
+        fs::write(&renomeado, b"%PDF-1.4 ata").unwrap();
+        fs::write(&outro, b"%PDF-1.4 outra ata").unwrap();
+        fs::write(&mesmo_tamanho, b"%PDF-1.4 ATA").unwrap();
+
+        let hash = integridade::sha256_arquivo(&original).unwrap();
+        registrar_processados(&dir, vec![(hash.clone(), EntradaIndicePdf {
+            arquivo: original.to_string_lossy().to_string(),
+            processado_em: "2024-05-01T10:00:00+00:00".to_string(),
+            jsons: vec!["licitacao_pregao_787000-90008_2024-123.json".to_string()],
+        })]).unwrap();
+
+        let indice = carregar_indice(&dir).unwrap();
+        assert_eq!(indice[&hash].jsons.len(), 1);
+        let (pendentes, pulados) = separar_ja_processados(vec![renomeado.clone(), outro.clone()], &indice);
+        assert_eq!(pendentes, vec![outro.clone()]);
+        assert_eq!(pulados, vec![renomeado.clone()]);
+
+        let grupos = agrupar_duplicados(&[original.clone(), renomeado.clone(), outro, mesmo_tamanho]);
+        assert_eq!(grupos.len(), 1);
+        assert_eq!(grupos[0].sha256, hash);
+        assert_eq!(grupos[0].arquivos, vec![renomeado.to_string_lossy().to_string(), original.to_string_lossy().to_string()]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_simulacao_estima_tempo_sem_gravar_nada() {
+        let dir = std::env::temp_dir().join(format!("licitacao360_dry_run_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let processado = dir.join("processado.pdf");
+        let novo = dir.join("novo.pdf");
+        fs::write(&processado, vec![b'a'; 300]).unwrap();
+        fs::write(&novo, vec![b'b'; 500]).unwrap();
+        let mut indice = BTreeMap::new();
+        indice.insert(integridade::sha256_arquivo(&processado).unwrap(), EntradaIndicePdf {
+            arquivo: processado.to_string_lossy().to_string(),
+            processado_em: "2024-05-01T10:00:00+00:00".to_string(),
+            jsons: Vec::new(),
+        });
+        let arquivos = [processado, novo];
+
+        let relatorio = simular_processamento("pdfs", &arquivos, &indice, true, Some(100.0));
+        assert_eq!((relatorio.total_arquivos, relatorio.ja_processados), (2, 1));
+        assert_eq!((relatorio.total_bytes, relatorio.bytes_a_processar), (800, 500));
+        assert_eq!(relatorio.tempo_estimado_segundos, Some(5.0));
+        assert!(relatorio.arquivos[0].ja_processado && !relatorio.arquivos[1].ja_processado);
+
+        // Sem pular os já processados, todos entram na estimativa; sem histórico, não há estimativa
+        let relatorio = simular_processamento("pdfs", &arquivos, &indice, false, None);
+        assert_eq!(relatorio.bytes_a_processar, 800);
+        assert_eq!(relatorio.tempo_estimado_segundos, None);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,476 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use walkdir::WalkDir;
+use crate::types::{ArquivoDivergente, ArquivoIlegivel, RelatorioIntegridade};
+
+/// Manifesto de checksums mantido na raiz da pasta de resultados
+pub const ARQUIVO_CHECKSUMS: &str = ".checksums.json";
+
+/// Pasta (dentro da raiz de resultados) para onde vão os arquivos descartados
+pub const PASTA_LIXEIRA: &str = "Lixeira";
+
+/// Serializa as atualizações do manifesto entre threads
+static MANIFESTO_LOCK: Mutex<()> = Mutex::new(());
+
+/// Checksum registrado para um artefato
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EntradaChecksum {
+    pub sha256: String,
+    pub tamanho: u64,
+    pub registrado_em: String,
+    /// PDF que originou o artefato, quando conhecido (permite regenerá-lo)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origem_pdf: Option<String>,
+    /// PDFs de onde vieram as propostas de um JSON de licitação (permitem regenerá-lo)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub origens_pdf: Vec<String>,
+}
+
+impl EntradaChecksum {
+    /// PDFs necessários para regenerar o artefato
+    pub fn pdfs_de_origem(&self) -> Vec<&str> {
+        self.origem_pdf.iter().chain(&self.origens_pdf).map(String::as_str).collect()
+    }
+
+    /// Todos os PDFs de origem do artefato ainda existem
+    pub fn pode_regenerar(&self) -> bool {
+        let pdfs = self.pdfs_de_origem();
+        !pdfs.is_empty() && pdfs.iter().all(|pdf| Path::new(pdf).exists())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ManifestoChecksums {
+    /// Chave: caminho relativo à raiz (ou absoluto, para destinos roteados fora dela)
+    pub arquivos: BTreeMap<String, EntradaChecksum>,
+}
+
+/// Calcula o SHA-256 de um conteúdo em hexadecimal
+pub fn sha256_bytes(conteudo: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(conteudo))
+}
+
+/// Calcula o SHA-256 de um arquivo lendo-o em blocos (PDFs grandes não vão inteiros para a memória)
+pub fn sha256_arquivo(path: &Path) -> Result<String> {
+    let mut arquivo = fs::File::open(path)
+        .context(format!("Erro ao ler arquivo: {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut arquivo, &mut hasher)
+        .context(format!("Erro ao ler arquivo: {}", path.display()))?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Escreve um arquivo de forma atômica: grava em um temporário, sincroniza e renomeia
+///
+/// Se o processo cair no meio da gravação, o destino continua com o conteúdo anterior.
+pub fn escrever_atomico(path: &Path, conteudo: &[u8]) -> Result<()> {
+    let nome = path.file_name().unwrap_or_default().to_string_lossy();
+    let temporario = path.with_file_name(format!(".{}.tmp", nome));
+
+    let resultado = gravar_temporario(&temporario, conteudo).and_then(|()| substituir(&temporario, path));
+    if resultado.is_err() {
+        let _ = fs::remove_file(&temporario);
+    }
+    resultado
+}
+
+fn gravar_temporario(temporario: &Path, conteudo: &[u8]) -> Result<()> {
+    let mut arquivo = fs::File::create(temporario)
+        .context(format!("Erro ao criar arquivo temporário: {}", temporario.display()))?;
+    arquivo.write_all(conteudo)
+        .context(format!("Erro ao gravar arquivo temporário: {}", temporario.display()))?;
+    arquivo.sync_all()
+        .context(format!("Erro ao sincronizar arquivo temporário: {}", temporario.display()))
+}
+
+/// Renomeia o temporário sobre o destino
+pub(crate) fn substituir(temporario: &Path, path: &Path) -> Result<()> {
+    let renomeado = fs::rename(temporario, path);
+
+    // No Windows o rename falha se o destino estiver aberto ou somente leitura;
+    // remove o destino e tenta de novo (o temporário já está completo em disco)
+    #[cfg(windows)]
+    let renomeado = renomeado.or_else(|_| {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        fs::rename(temporario, path)
+    });
+
+    renomeado.context(format!("Erro ao mover arquivo para {}", path.display()))?;
+
+    // Sincroniza a pasta para que o novo nome sobreviva a uma queda de energia
+    #[cfg(unix)]
+    if let Some(pasta) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        let _ = fs::File::open(pasta).and_then(|pasta| pasta.sync_all());
+    }
+
+    Ok(())
+}
+
+/// Serializa um valor como JSON formatado e o grava com `escrever_atomico`
+pub fn escrever_json_atomico<T: Serialize + ?Sized>(path: &Path, valor: &T) -> Result<()> {
+    let conteudo = serde_json::to_string_pretty(valor)
+        .context(format!("Erro ao serializar JSON: {}", path.display()))?;
+    escrever_atomico(path, conteudo.as_bytes())
+}
+
+/// Chave do manifesto para um caminho
+fn chave_manifesto(raiz: &Path, path: &Path) -> String {
+    match path.strip_prefix(raiz) {
+        Ok(relativo) => relativo.to_string_lossy().replace('\\', "/"),
+        Err(_) => path.to_string_lossy().to_string(),
+    }
+}
+
+/// Carrega o manifesto da raiz (vazio se não existir)
+pub fn carregar_manifesto(raiz: &Path) -> Result<ManifestoChecksums> {
+    let path = raiz.join(ARQUIVO_CHECKSUMS);
+    if !path.exists() {
+        return Ok(ManifestoChecksums::default());
+    }
+
+    let content = fs::read_to_string(&path)
+        .context("Erro ao ler manifesto de checksums")?;
+    serde_json::from_str(&content)
+        .context("Erro ao analisar manifesto de checksums")
+}
+
+fn salvar_manifesto(raiz: &Path, manifesto: &ManifestoChecksums) -> Result<()> {
+    let content = serde_json::to_string_pretty(manifesto)
+        .context("Erro ao serializar manifesto de checksums")?;
+    escrever_atomico(&raiz.join(ARQUIVO_CHECKSUMS), content.as_bytes())
+}
+
+/// Aplica uma alteração ao manifesto sob o lock global
+fn atualizar_manifesto<F>(raiz: &Path, alteracao: F) -> Result<()>
+where
+    F: FnOnce(&mut ManifestoChecksums),
+{
+    let _guard = MANIFESTO_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    // Um manifesto corrompido é recomeçado; os arquivos passam a aparecer como não rastreados
+    let mut manifesto = carregar_manifesto(raiz).unwrap_or_default();
+    alteracao(&mut manifesto);
+    salvar_manifesto(raiz, &manifesto)
+}
+
+/// Grava um artefato de resultado e registra seu checksum no manifesto da raiz
+///
+/// O hash só é registrado depois que o arquivo completo já está no lugar, então uma
+/// falha no meio da escrita nunca deixa um hash para um arquivo pela metade.
+pub fn gravar_artefato(raiz: &Path, path: &Path, conteudo: &[u8], origem_pdf: Option<&Path>) -> Result<()> {
+    escrever_atomico(path, conteudo)?;
+
+    let entrada = EntradaChecksum {
+        sha256: sha256_bytes(conteudo),
+        tamanho: conteudo.len() as u64,
+        registrado_em: Utc::now().to_rfc3339(),
+        origem_pdf: origem_pdf.map(|p| p.to_string_lossy().to_string()),
+        origens_pdf: Vec::new(),
+    };
+    let chave = chave_manifesto(raiz, path);
+
+    atualizar_manifesto(raiz, |manifesto| {
+        // Regravar o conteúdo não muda de quais PDFs ele veio
+        let origens_pdf = manifesto.arquivos.remove(&chave).map(|e| e.origens_pdf).unwrap_or_default();
+        manifesto.arquivos.insert(chave, EntradaChecksum { origens_pdf, ..entrada });
+    })
+}
+
+/// Acrescenta cada PDF às origens dos artefatos (já registrados) gerados a partir dele
+pub fn registrar_origens(raiz: &Path, origens: &[(String, Vec<String>)]) -> Result<()> {
+    if origens.iter().all(|(_, artefatos)| artefatos.is_empty()) {
+        return Ok(());
+    }
+
+    atualizar_manifesto(raiz, |manifesto| {
+        for (pdf, artefatos) in origens {
+            for artefato in artefatos {
+                if let Some(entrada) = manifesto.arquivos.get_mut(&chave_manifesto(raiz, Path::new(artefato))) {
+                    if !entrada.origens_pdf.contains(pdf) {
+                        entrada.origens_pdf.push(pdf.clone());
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Atualiza a origem dos artefatos de um PDF que mudou de lugar (ex.: arquivado em Processados)
+pub fn mover_origem(raiz: &Path, antigo: &Path, novo: &Path) -> Result<()> {
+    let (antigo, novo) = (antigo.to_string_lossy().to_string(), novo.to_string_lossy().to_string());
+    atualizar_manifesto(raiz, |manifesto| {
+        for entrada in manifesto.arquivos.values_mut() {
+            for origem in entrada.origem_pdf.iter_mut().chain(entrada.origens_pdf.iter_mut()) {
+                if *origem == antigo {
+                    origem.clone_from(&novo);
+                }
+            }
+        }
+    })
+}
+
+/// Remove a entrada de um arquivo do manifesto
+pub fn remover_do_manifesto(raiz: &Path, path: &Path) -> Result<()> {
+    let chave = chave_manifesto(raiz, path);
+    atualizar_manifesto(raiz, |manifesto| {
+        manifesto.arquivos.remove(&chave);
+    })
+}
+
+/// Renomeia um artefato, levando junto sua entrada no manifesto
+pub fn renomear_artefato(raiz: &Path, origem: &Path, destino: &Path) -> Result<()> {
+    fs::rename(origem, destino)
+        .context(format!("Erro ao renomear {} para {}", origem.display(), destino.display()))?;
+
+    let (antiga, nova) = (chave_manifesto(raiz, origem), chave_manifesto(raiz, destino));
+    atualizar_manifesto(raiz, |manifesto| {
+        if let Some(entrada) = manifesto.arquivos.remove(&antiga) {
+            manifesto.arquivos.insert(nova, entrada);
+        }
+    })
+}
+
+/// Resolve o caminho de um arquivo a partir da chave do manifesto
+pub fn caminho_do_arquivo(raiz: &Path, chave: &str) -> PathBuf {
+    raiz.join(chave)
+}
+
+/// Pastas ocultas e a lixeira não contêm resultados ativos
+pub fn eh_pasta_ignorada(nome: &std::ffi::OsStr) -> bool {
+    let nome = nome.to_string_lossy();
+    nome.starts_with('.') || nome == PASTA_LIXEIRA
+}
+
+/// Artefatos de resultado considerados na busca por arquivos não rastreados
+fn eh_artefato(path: &Path) -> bool {
+    let oculto = path.file_name()
+        .map(|n| n.to_string_lossy().starts_with('.'))
+        .unwrap_or(true);
+    let extensao = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+
+    !oculto && matches!(extensao.as_deref(), Some("json") | Some("md") | Some("html"))
+}
+
+/// Recalcula os hashes e compara com o manifesto
+pub fn verificar_integridade(raiz: &Path) -> Result<RelatorioIntegridade> {
+    let manifesto = carregar_manifesto(raiz)?;
+    let mut relatorio = RelatorioIntegridade::default();
+
+    for (chave, entrada) in &manifesto.arquivos {
+        let path = caminho_do_arquivo(raiz, chave);
+
+        if !path.exists() {
+            relatorio.ausentes.push(chave.clone());
+            continue;
+        }
+
+        // Um arquivo ilegível (bloqueado, sem permissão) não impede a verificação dos demais
+        let encontrado = match sha256_arquivo(&path) {
+            Ok(encontrado) => encontrado,
+            Err(e) => {
+                relatorio.ilegiveis.push(ArquivoIlegivel { arquivo: chave.clone(), erro: format!("{:#}", e) });
+                continue;
+            }
+        };
+        relatorio.verificados += 1;
+        if encontrado != entrada.sha256 {
+            let mut acoes = Vec::new();
+            if entrada.pode_regenerar() {
+                acoes.push("regenerar".to_string());
+            }
+            acoes.push("lixeira".to_string());
+
+            relatorio.divergentes.push(ArquivoDivergente {
+                arquivo: chave.clone(),
+                esperado: entrada.sha256.clone(),
+                encontrado,
+                origem_pdf: entrada.origem_pdf.clone(),
+                origens_pdf: entrada.origens_pdf.clone(),
+                acoes,
+            });
+        }
+    }
+
+    // Arquivos na raiz (sem entrar em pastas ocultas nem na lixeira) que não constam do manifesto
+    for entry in WalkDir::new(raiz)
+        .max_depth(2)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !eh_pasta_ignorada(e.file_name()))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && eh_artefato(e.path()))
+    {
+        let chave = chave_manifesto(raiz, entry.path());
+        if !manifesto.arquivos.contains_key(&chave) {
+            relatorio.nao_rastreados.push(chave);
+        }
+    }
+
+    Ok(relatorio)
+}
+
+/// Move um arquivo para a lixeira da raiz e o retira do manifesto
+pub fn mover_para_lixeira(raiz: &Path, path: &Path) -> Result<PathBuf> {
+    let lixeira = raiz.join(PASTA_LIXEIRA);
+    fs::create_dir_all(&lixeira)
+        .context("Erro ao criar pasta de lixeira")?;
+
+    let nome = path.file_name().unwrap_or_default().to_string_lossy();
+    let destino = lixeira.join(format!("{}_{}", Utc::now().format("%Y%m%d%H%M%S%3f"), nome));
+
+    fs::rename(path, &destino)
+        .context(format!("Erro ao mover {} para a lixeira", path.display()))?;
+    remover_do_manifesto(raiz, path)?;
+
+    Ok(destino)
+}
+
+/// Resolve um caminho garantindo que ele fica dentro da raiz (bloqueia `..` e links para fora)
+pub fn caminho_dentro_da_raiz(raiz: &Path, path: &Path) -> Option<PathBuf> {
+    let raiz = raiz.canonicalize().ok()?;
+    let path = path.canonicalize().ok()?;
+    (path != raiz && path.starts_with(&raiz)).then_some(path)
+}
+
+/// Apaga definitivamente os arquivos da lixeira, retornando quantos foram removidos
+pub fn esvaziar_lixeira(raiz: &Path) -> Result<usize> {
+    let lixeira = raiz.join(PASTA_LIXEIRA);
+    if !lixeira.exists() {
+        return Ok(0);
+    }
+
+    let mut removidos = 0;
+    for entry in fs::read_dir(&lixeira).context("Erro ao ler a lixeira")? {
+        let path = entry?.path();
+        if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        }
+        .context(format!("Erro ao remover {}", path.display()))?;
+        removidos += 1;
+    }
+
+    Ok(removidos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verificar_integridade_detecta_problemas() {
+        let raiz = std::env::temp_dir().join(format!("licitacao360_integridade_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&raiz);
+        fs::create_dir_all(&raiz).unwrap();
+
+        let pdf = raiz.join("origem.pdf");
+        fs::write(&pdf, b"%PDF").unwrap();
+
+        gravar_artefato(&raiz, &raiz.join("licitacao_ok.json"), b"{\"propostas\": []}", None).unwrap();
+        gravar_artefato(&raiz, &raiz.join("licitacao_truncado.json"), b"{\"propostas\": [1, 2]}", None).unwrap();
+        gravar_artefato(&raiz, &raiz.join("relatorio.md"), b"# Relatorio", Some(&pdf)).unwrap();
+        gravar_artefato(&raiz, &raiz.join("licitacao_apagado.json"), b"{}", None).unwrap();
+        gravar_artefato(&raiz, &raiz.join("licitacao_bloqueado.json"), b"{}", None).unwrap();
+        gravar_artefato(&raiz, &raiz.join("licitacao_com_origem.json"), b"{\"propostas\": []}", None).unwrap();
+        let pdf_texto = pdf.to_string_lossy().to_string();
+        registrar_origens(&raiz, &[(pdf_texto.clone(), vec![raiz.join("licitacao_com_origem.json").to_string_lossy().to_string()])]).unwrap();
+        // Regravar o JSON mantém as origens registradas
+        gravar_artefato(&raiz, &raiz.join("licitacao_com_origem.json"), b"{\"propostas\": [3]}", None).unwrap();
+
+        // Simular corrupção, remoção, um arquivo ilegível e um arquivo criado fora do pipeline
+        fs::write(raiz.join("licitacao_truncado.json"), b"{\"propostas\": [1,").unwrap();
+        fs::write(raiz.join("licitacao_com_origem.json"), b"{\"propostas\": [3,").unwrap();
+        fs::write(raiz.join("relatorio.md"), b"# Rel").unwrap();
+        fs::remove_file(raiz.join("licitacao_apagado.json")).unwrap();
+        fs::remove_file(raiz.join("licitacao_bloqueado.json")).unwrap();
+        fs::create_dir(raiz.join("licitacao_bloqueado.json")).unwrap();
+        fs::write(raiz.join("avulso.json"), b"{}").unwrap();
+
+        let relatorio = verificar_integridade(&raiz).unwrap();
+        assert_eq!(relatorio.verificados, 4);
+        assert_eq!(relatorio.ausentes, vec!["licitacao_apagado.json"]);
+        assert_eq!(relatorio.nao_rastreados, vec!["avulso.json"]);
+        let ilegiveis: Vec<_> = relatorio.ilegiveis.iter().map(|i| i.arquivo.as_str()).collect();
+        assert_eq!(ilegiveis, vec!["licitacao_bloqueado.json"]);
+
+        let divergentes: Vec<_> = relatorio.divergentes.iter().map(|d| (d.arquivo.as_str(), d.acoes.clone())).collect();
+        assert_eq!(divergentes, vec![
+            ("licitacao_com_origem.json", vec!["regenerar".to_string(), "lixeira".to_string()]),
+            ("licitacao_truncado.json", vec!["lixeira".to_string()]),
+            ("relatorio.md", vec!["regenerar".to_string(), "lixeira".to_string()]),
+        ]);
+        assert_eq!(relatorio.divergentes[0].origens_pdf, vec![pdf_texto.clone()]);
+
+        // PDF arquivado: as origens acompanham o novo caminho
+        let arquivado = raiz.join("Processados").join("origem.pdf");
+        mover_origem(&raiz, &pdf, &arquivado).unwrap();
+        let manifesto = carregar_manifesto(&raiz).unwrap();
+        assert_eq!(manifesto.arquivos["licitacao_com_origem.json"].pdfs_de_origem(), vec![arquivado.to_string_lossy()]);
+        assert_eq!(manifesto.arquivos["relatorio.md"].pdfs_de_origem(), vec![arquivado.to_string_lossy()]);
+        assert!(!manifesto.arquivos["relatorio.md"].pode_regenerar());
+
+        // Descartar remove o arquivo do manifesto
+        mover_para_lixeira(&raiz, &raiz.join("licitacao_truncado.json")).unwrap();
+        let relatorio = verificar_integridade(&raiz).unwrap();
+        assert!(relatorio.divergentes.iter().all(|d| d.arquivo != "licitacao_truncado.json"));
+        assert!(relatorio.nao_rastreados.iter().all(|n| !n.contains("truncado")));
+
+        assert_eq!(esvaziar_lixeira(&raiz).unwrap(), 1);
+        assert_eq!(esvaziar_lixeira(&raiz).unwrap(), 0);
+
+        let _ = fs::remove_dir_all(&raiz);
+    }
+
+    #[test]
+    fn test_escrita_atomica_nao_deixa_temporario() {
+        let pasta = std::env::temp_dir().join(format!("licitacao360_atomico_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&pasta);
+        fs::create_dir_all(&pasta).unwrap();
+        let arquivo = pasta.join("dados.json");
+
+        escrever_json_atomico(&arquivo, &serde_json::json!({"versao": 1})).unwrap();
+        // Sobrescrever um arquivo existente também é atômico
+        escrever_json_atomico(&arquivo, &serde_json::json!({"versao": 2})).unwrap();
+
+        let gravado: serde_json::Value = serde_json::from_str(&fs::read_to_string(&arquivo).unwrap()).unwrap();
+        assert_eq!(gravado["versao"], 2);
+        let nomes: Vec<String> = fs::read_dir(&pasta).unwrap()
+            .map(|entrada| entrada.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(nomes, vec!["dados.json"]);
+
+        // Falha na gravação (pasta inexistente) não deixa temporário nem destino
+        let sem_pasta = pasta.join("inexistente").join("dados.json");
+        assert!(escrever_atomico(&sem_pasta, b"{}").is_err());
+        assert!(!pasta.join("inexistente").exists());
+
+        let _ = fs::remove_dir_all(&pasta);
+    }
+
+    #[test]
+    fn test_caminho_dentro_da_raiz() {
+        let base = std::env::temp_dir().join(format!("licitacao360_raiz_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let raiz = base.join("Resultados");
+        fs::create_dir_all(raiz.join("sub")).unwrap();
+        fs::write(raiz.join("sub").join("licitacao_a.json"), b"{}").unwrap();
+        fs::write(base.join("fora.json"), b"{}").unwrap();
+
+        assert!(caminho_dentro_da_raiz(&raiz, &raiz.join("sub").join("licitacao_a.json")).is_some());
+        assert!(caminho_dentro_da_raiz(&raiz, &raiz.join("sub").join("..").join("..").join("fora.json")).is_none());
+        assert!(caminho_dentro_da_raiz(&raiz, &base.join("fora.json")).is_none());
+        assert!(caminho_dentro_da_raiz(&raiz, &raiz).is_none());
+        assert!(caminho_dentro_da_raiz(&raiz, &raiz.join("inexistente.json")).is_none());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+}
@@ -1,12 +1,43 @@
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use tauri::Manager;
 
 // Módulos
 pub mod types;
+pub mod i18n;
 pub mod pdf_processor;
 pub mod sicaf_processor;
 pub mod commands;
 pub mod config;
+pub mod paths;
+pub mod routing;
+pub mod catalogo_precos;
+pub mod logs;
+pub mod dica_arquivo;
+pub mod exportacao;
+pub mod integridade;
+pub mod resultados;
+pub mod http_api;
+pub mod ocr;
+pub mod metrics;
+pub mod validators;
+pub mod sessoes;
+pub mod watcher;
+pub mod report;
+pub mod storage;
+pub mod metadados_pdf;
+pub mod texto;
+pub mod migracoes;
+pub mod backup;
+pub mod extratores;
+pub mod extraction_debug;
+pub mod consulta_cnpj;
+pub mod revelar;
+pub mod indice_pdfs;
+pub mod uso_disco;
+pub mod retencao;
+pub mod validacao;
+pub mod rastreamento;
 
 // Re-export types for easy access
 pub use types::*;
@@ -19,14 +50,47 @@ fn greet(name: &str) -> String {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Retomar os contadores de métricas das execuções anteriores
+    metrics::inicializar();
+    // Logs de versões anteriores ficavam dentro do arquivo de configuração; migrados antes de carregá-la em memória
+    if let Err(e) = config::migrar_logs_legados() {
+        eprintln!("⚠️ Erro ao migrar logs da configuração: {}", e);
+    }
+    let config_state = config::carregar_estado();
+    // Trace do pipeline em Database/Config/logs/trace.log, visível também no app empacotado
+    match config::get_logs_dir() {
+        Ok(pasta) => {
+            let verbose = config::ler_config(&config_state).verbose;
+            if let Err(e) = rastreamento::iniciar(&pasta, verbose) {
+                eprintln!("⚠️ Erro ao iniciar o trace: {:#}", e);
+            }
+        }
+        Err(e) => eprintln!("⚠️ Pasta de logs indisponível para o trace: {}", e),
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .manage(Arc::new(Mutex::new(HashMap::<String, types::ProcessingStatus>::new())))
+        .manage(config_state)
+        .manage(http_api::HttpApiState::default())
+        .manage(watcher::PdfWatcherState::default())
+        .on_window_event(|window, event| {
+            // Sessões em andamento não podem ficar marcadas como "processing" depois de fechar a janela
+            if let tauri::WindowEvent::CloseRequested { .. } = event {
+                commands::encerrar_processamentos(
+                    window.state::<commands::ProcessingState>().inner(),
+                    commands::TEMPO_GRACA_ENCERRAMENTO,
+                );
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
             commands::process_pdf_file,
+            commands::reprocess_pdf,
+            commands::get_pdf_text_preview,
+            commands::try_extraction_patterns,
             commands::process_pdf_directory,
             commands::process_pdf_fixed_directory,
             commands::get_pdf_directory,
@@ -34,31 +98,72 @@ pub fn run() {
             commands::open_folder,
             commands::verify_output_directory,
             commands::get_processing_status,
+            commands::get_processing_result,
+            commands::cancel_processing,
+            commands::preview_consolidation,
             commands::list_pdf_files,
             commands::validate_pdf_file,
             commands::clear_processing_state,
+            commands::clear_all_processing_state,
+            commands::list_processing_sessions,
+            commands::resume_processing,
             commands::get_current_directory,
             commands::create_default_directories,
             commands::initialize_database_structure,
+            commands::set_database_root,
             commands::list_json_files,
+            commands::list_licitacao_files,
             commands::read_json_file,
+            commands::read_json_propostas_paginated,
             commands::get_json_file_info,
+            commands::delete_json_file,
+            commands::rename_result_file,
+            commands::rebuild_resumo_geral,
+            commands::compare_licitacao_jsons,
+            commands::validate_licitacao_json,
+            commands::validate_all_results,
+            commands::cleanup_old_results,
+            commands::search_propostas,
+            commands::get_fornecedor_history,
+            commands::get_licitacoes_summary,
+            commands::get_consolidated_json,
+            commands::get_consolidated_summary,
+            commands::merge_licitacao_jsons,
+            commands::normalize_uasg_results,
+            commands::empty_results_trash,
             commands::get_pdf_file_info,
             commands::get_pdf_files_info,
+            commands::find_duplicate_pdfs,
+            commands::import_pdf_files,
             commands::open_pdf_file,
+            commands::open_file,
+            commands::reveal_file_in_folder,
+            commands::open_report_html,
             commands::load_app_config,
             commands::save_app_config,
             commands::update_config_directories,
             commands::add_config_log,
+            commands::get_processing_logs,
+            commands::get_logs,
             commands::clear_config_logs,
+            commands::get_recent_trace_lines,
             commands::update_config_verbose,
+            commands::reload_config_from_disk,
+            commands::test_routing,
             commands::get_config_directory,
             commands::get_sicaf_directory,
+            commands::get_database_usage,
             commands::process_sicaf_pdfs,
+            commands::process_single_sicaf_pdf,
             commands::load_sicaf_data,
+            commands::list_sicaf_entries,
+            commands::delete_sicaf_entry,
+            commands::get_sicaf_stats,
             commands::verify_cnpj_sicaf,
+            commands::verify_cnpj_sicaf_detailed,
             commands::get_cnpj_sicaf_data,
             commands::generate_sicaf_comparison_report,
+            commands::lookup_cnpj_online,
             commands::debug_and_repair_config,
             commands::initialize_application,
             commands::get_app_directories_info,
@@ -67,8 +172,38 @@ pub fn run() {
             commands::ensure_directory_exists,
             commands::get_user_home_directory,
             commands::update_pdf_directory,
-            commands::update_output_directory
+            commands::update_output_directory,
+            commands::build_price_catalog,
+            commands::suggest_price,
+            commands::export_propostas_csv,
+            commands::get_propostas_tsv,
+            commands::export_consolidado_xlsx,
+            commands::generate_licitacao_pdf_report,
+            commands::verify_result_integrity,
+            commands::resolve_integrity_issue,
+            commands::start_http_api,
+            commands::stop_http_api,
+            commands::get_metrics_text,
+            commands::start_pdf_watcher,
+            commands::stop_pdf_watcher,
+            commands::query_propostas_sql_like,
+            commands::rebuild_database_from_json,
+            commands::backup_database,
+            commands::restore_database,
+            commands::get_app_version_and_migrations,
+            commands::migrate_all_results
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            // Encerrar processamentos, a API HTTP e o observador de PDFs junto com o aplicativo
+            if let tauri::RunEvent::Exit = event {
+                commands::encerrar_processamentos(
+                    app.state::<commands::ProcessingState>().inner(),
+                    commands::TEMPO_GRACA_ENCERRAMENTO,
+                );
+                http_api::encerrar(app.state::<http_api::HttpApiState>().inner());
+                watcher::encerrar(app.state::<watcher::PdfWatcherState>().inner());
+            }
+        });
 }
@@ -0,0 +1,503 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use crate::types::{ProcessingLog, ProcessingLogsView, ResumoProgresso};
+
+/// Subpasta de Database/Config com os arquivos de log
+pub const PASTA_LOGS: &str = "logs";
+
+/// Tamanho a partir do qual o arquivo do dia é rotacionado ("log-2024-05-01.1.jsonl", ".2"...)
+pub const TAMANHO_MAXIMO_ARQUIVO_LOG: u64 = 5 * 1024 * 1024;
+
+/// Arquivos de log mais antigos que isso são apagados quando o arquivo de um novo dia é criado
+pub const DIAS_RETENCAO_LOGS: i64 = 90;
+
+/// Serializa as gravações nos arquivos de log entre threads
+static LOG_LOCK: Mutex<()> = Mutex::new(());
+
+/// Categoria de um log, cada uma com seu próprio limite
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CategoriaLog {
+    Progresso,
+    Resumo,
+    Geral,
+    Erro,
+}
+
+/// Obtém a categoria de um log a partir do seu tipo
+pub fn categoria(log: &ProcessingLog) -> CategoriaLog {
+    match log.log_type.as_str() {
+        "progress" => CategoriaLog::Progresso,
+        "progress_summary" => CategoriaLog::Resumo,
+        "error" => CategoriaLog::Erro,
+        _ => CategoriaLog::Geral,
+    }
+}
+
+/// Limites independentes por categoria
+#[derive(Debug, Clone)]
+pub struct LimitesLogs {
+    pub progresso: usize,
+    pub resumos: usize,
+    pub geral: usize,
+    pub erros: usize,
+}
+
+impl LimitesLogs {
+    /// Deriva os limites do `max_logs` da configuração: progresso fica baixo e erros duram mais
+    pub fn para_max_logs(max_logs: usize) -> Self {
+        let max_logs = max_logs.max(1);
+        LimitesLogs {
+            progresso: (max_logs / 20).clamp(10, 50),
+            resumos: (max_logs / 10).clamp(10, 100),
+            geral: max_logs,
+            erros: max_logs * 2,
+        }
+    }
+}
+
+/// Cria uma entrada de log comum
+pub fn novo_log(message: String, log_type: String, session_id: Option<String>) -> ProcessingLog {
+    ProcessingLog {
+        timestamp: Utc::now().to_rfc3339(),
+        message,
+        log_type,
+        session_id,
+        file_index: None,
+        resumo: None,
+    }
+}
+
+/// Cria uma entrada de progresso por arquivo (posição começando em 1)
+pub fn novo_log_progresso(session_id: &str, file_index: usize, total: usize, arquivo: &str) -> ProcessingLog {
+    ProcessingLog {
+        timestamp: Utc::now().to_rfc3339(),
+        message: format!("Arquivo {}/{} processado: {}", file_index, total, arquivo),
+        log_type: "progress".to_string(),
+        session_id: Some(session_id.to_string()),
+        file_index: Some(file_index),
+        resumo: None,
+    }
+}
+
+/// Adiciona um log respeitando os limites de cada categoria
+///
+/// Entradas de progresso acima do limite são compactadas em resumos do tipo
+/// "Arquivos processados 120–180"; as demais categorias descartam as mais antigas.
+pub fn adicionar_log(logs: &mut Vec<ProcessingLog>, entrada: ProcessingLog, limites: &LimitesLogs) {
+    logs.push(entrada);
+
+    if contar(logs, CategoriaLog::Progresso) > limites.progresso {
+        compactar_progresso(logs, limites.progresso / 2);
+    }
+
+    aplicar_limite(logs, CategoriaLog::Resumo, limites.resumos);
+    aplicar_limite(logs, CategoriaLog::Geral, limites.geral);
+    aplicar_limite(logs, CategoriaLog::Erro, limites.erros);
+}
+
+/// Separa os logs comuns dos resumos compactados
+pub fn separar_resumos(logs: &[ProcessingLog]) -> ProcessingLogsView {
+    let (progress_summaries, logs): (Vec<ProcessingLog>, Vec<ProcessingLog>) = logs.iter()
+        .cloned()
+        .partition(|log| categoria(log) == CategoriaLog::Resumo);
+
+    ProcessingLogsView {
+        total_errors: logs.iter().filter(|log| categoria(log) == CategoriaLog::Erro).count(),
+        logs,
+        progress_summaries,
+    }
+}
+
+/// Arquivo de log de um dia: o atual (parte `None`) ou uma parte já rotacionada
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ArquivoLog {
+    data: NaiveDate,
+    parte: Option<u32>,
+    caminho: PathBuf,
+}
+
+fn nome_arquivo_log(data: NaiveDate) -> String {
+    format!("log-{}.jsonl", data.format("%Y-%m-%d"))
+}
+
+/// Interpreta "log-AAAA-MM-DD.jsonl" e "log-AAAA-MM-DD.N.jsonl"
+fn arquivo_log(caminho: &Path) -> Option<ArquivoLog> {
+    let nome = caminho.file_name()?.to_str()?;
+    let resto = nome.strip_prefix("log-")?.strip_suffix(".jsonl")?;
+    let data = NaiveDate::parse_from_str(resto.get(..10)?, "%Y-%m-%d").ok()?;
+    let parte = match &resto[10..] {
+        "" => None,
+        sufixo => Some(sufixo.strip_prefix('.')?.parse().ok()?),
+    };
+    Some(ArquivoLog { data, parte, caminho: caminho.to_path_buf() })
+}
+
+/// Arquivos de log da pasta em ordem cronológica (as partes rotacionadas antes do arquivo atual do dia)
+fn listar_arquivos_log(pasta: &Path) -> Vec<ArquivoLog> {
+    let mut arquivos: Vec<ArquivoLog> = fs::read_dir(pasta)
+        .map(|entradas| entradas.filter_map(|e| e.ok()).filter_map(|e| arquivo_log(&e.path())).collect())
+        .unwrap_or_default();
+    arquivos.sort_by_key(|a| (a.data, a.parte.unwrap_or(u32::MAX)));
+    arquivos
+}
+
+/// Dia (no fuso local) em que a entrada foi registrada
+fn data_do_log(entrada: &ProcessingLog) -> NaiveDate {
+    DateTime::parse_from_rfc3339(&entrada.timestamp)
+        .map(|momento| momento.with_timezone(&Local).date_naive())
+        .unwrap_or_else(|_| Local::now().date_naive())
+}
+
+/// Acrescenta uma entrada ao arquivo JSON Lines do dia dela
+pub fn gravar_log(pasta: &Path, entrada: &ProcessingLog) -> Result<()> {
+    gravar_log_com_limite(pasta, entrada, TAMANHO_MAXIMO_ARQUIVO_LOG)
+}
+
+/// Acrescenta entradas migradas de versões anteriores ao arquivo de hoje
+///
+/// Gravá-las nos arquivos dos dias originais faria a retenção apagá-las logo em seguida.
+pub fn gravar_logs_migrados(pasta: &Path, entradas: &[ProcessingLog]) -> Result<()> {
+    let hoje = Local::now().date_naive();
+    for entrada in entradas {
+        gravar_no_dia(pasta, hoje, entrada, TAMANHO_MAXIMO_ARQUIVO_LOG)?;
+    }
+    Ok(())
+}
+
+fn gravar_log_com_limite(pasta: &Path, entrada: &ProcessingLog, limite_bytes: u64) -> Result<()> {
+    gravar_no_dia(pasta, data_do_log(entrada), entrada, limite_bytes)
+}
+
+fn gravar_no_dia(pasta: &Path, data: NaiveDate, entrada: &ProcessingLog, limite_bytes: u64) -> Result<()> {
+    let _guard = LOG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    fs::create_dir_all(pasta)
+        .context(format!("Erro ao criar pasta de logs: {}", pasta.display()))?;
+
+    let caminho = pasta.join(nome_arquivo_log(data));
+    match fs::metadata(&caminho) {
+        Ok(metadata) if metadata.len() >= limite_bytes => rotacionar(pasta, data, &caminho)?,
+        Ok(_) => {}
+        Err(_) => remover_logs_antigos(pasta, data),
+    }
+
+    let mut linha = serde_json::to_string(entrada).context("Erro ao serializar log")?;
+    linha.push('\n');
+    let mut arquivo = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&caminho)
+        .context(format!("Erro ao abrir arquivo de log: {}", caminho.display()))?;
+    arquivo.write_all(linha.as_bytes())
+        .context(format!("Erro ao gravar arquivo de log: {}", caminho.display()))
+}
+
+/// Move o arquivo atual do dia para a próxima parte livre
+fn rotacionar(pasta: &Path, data: NaiveDate, caminho: &Path) -> Result<()> {
+    let proxima = listar_arquivos_log(pasta).iter()
+        .filter(|a| a.data == data)
+        .filter_map(|a| a.parte)
+        .max()
+        .unwrap_or(0) + 1;
+    let destino = pasta.join(format!("log-{}.{}.jsonl", data.format("%Y-%m-%d"), proxima));
+    fs::rename(caminho, &destino)
+        .context(format!("Erro ao rotacionar arquivo de log: {}", caminho.display()))
+}
+
+fn remover_logs_antigos(pasta: &Path, hoje: NaiveDate) {
+    let limite = hoje - chrono::Duration::days(DIAS_RETENCAO_LOGS);
+    for arquivo in listar_arquivos_log(pasta).into_iter().filter(|a| a.data < limite) {
+        let _ = fs::remove_file(&arquivo.caminho);
+    }
+}
+
+/// Lê as entradas dos arquivos de log, de um dia ou de todos, em ordem cronológica
+///
+/// Linhas ilegíveis (ex.: a última linha de uma gravação interrompida) são ignoradas.
+pub fn ler_logs(pasta: &Path, data: Option<NaiveDate>) -> Result<Vec<ProcessingLog>> {
+    let mut entradas = Vec::new();
+    for arquivo in listar_arquivos_log(pasta).into_iter().filter(|a| data.is_none() || data == Some(a.data)) {
+        let leitor = BufReader::new(fs::File::open(&arquivo.caminho)
+            .context(format!("Erro ao abrir arquivo de log: {}", arquivo.caminho.display()))?);
+        entradas.extend(leitor.lines()
+            .map_while(|linha| linha.ok())
+            .filter_map(|linha| serde_json::from_str::<ProcessingLog>(&linha).ok()));
+    }
+    Ok(entradas)
+}
+
+/// Apaga todos os arquivos de log, retornando quantos foram removidos
+pub fn limpar_logs(pasta: &Path) -> Result<usize> {
+    let _guard = LOG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let arquivos = listar_arquivos_log(pasta);
+    for arquivo in &arquivos {
+        fs::remove_file(&arquivo.caminho)
+            .context(format!("Erro ao remover arquivo de log: {}", arquivo.caminho.display()))?;
+    }
+    Ok(arquivos.len())
+}
+
+/// Monta a visão dos logs lidos, compactando o progresso com os mesmos limites da inserção
+pub fn visao_logs(entradas: Vec<ProcessingLog>, limites: &LimitesLogs) -> ProcessingLogsView {
+    let mut logs = Vec::new();
+    for entrada in entradas {
+        adicionar_log(&mut logs, entrada, limites);
+    }
+    separar_resumos(&logs)
+}
+
+fn contar(logs: &[ProcessingLog], cat: CategoriaLog) -> usize {
+    logs.iter().filter(|log| categoria(log) == cat).count()
+}
+
+/// Remove as entradas mais antigas de uma categoria até caber no limite
+fn aplicar_limite(logs: &mut Vec<ProcessingLog>, cat: CategoriaLog, limite: usize) {
+    let excesso = contar(logs, cat).saturating_sub(limite);
+    if excesso == 0 {
+        return;
+    }
+
+    let mut removidos = 0;
+    logs.retain(|log| {
+        if removidos < excesso && categoria(log) == cat {
+            removidos += 1;
+            false
+        } else {
+            true
+        }
+    });
+}
+
+fn mensagem_resumo(resumo: &ResumoProgresso) -> String {
+    if resumo.ultimo_arquivo == 0 {
+        format!("{} entradas de progresso compactadas", resumo.entradas_compactadas)
+    } else {
+        format!("Arquivos processados {}–{} ({} entradas compactadas)",
+                resumo.primeiro_arquivo, resumo.ultimo_arquivo, resumo.entradas_compactadas)
+    }
+}
+
+/// Compacta as entradas de progresso mais antigas, mantendo apenas as `manter` mais recentes
+fn compactar_progresso(logs: &mut Vec<ProcessingLog>, manter: usize) {
+    let indices: Vec<usize> = logs.iter()
+        .enumerate()
+        .filter(|(_, log)| categoria(log) == CategoriaLog::Progresso)
+        .map(|(index, _)| index)
+        .collect();
+
+    let total_compactar = indices.len().saturating_sub(manter);
+    if total_compactar == 0 {
+        return;
+    }
+    let compactar = &indices[..total_compactar];
+
+    // Agrupar por sessão, na ordem em que as sessões aparecem
+    let mut grupos: Vec<(Option<String>, Vec<usize>)> = Vec::new();
+    for &index in compactar {
+        let sessao = logs[index].session_id.clone();
+        match grupos.iter_mut().find(|(s, _)| *s == sessao) {
+            Some((_, membros)) => membros.push(index),
+            None => grupos.push((sessao, vec![index])),
+        }
+    }
+
+    let mut novos_resumos: Vec<(usize, ProcessingLog)> = Vec::new();
+
+    for (sessao, membros) in grupos {
+        let posicoes: Vec<usize> = membros.iter().filter_map(|&i| logs[i].file_index).collect();
+        let primeiro = posicoes.iter().copied().min().unwrap_or(0);
+        let ultimo = posicoes.iter().copied().max().unwrap_or(0);
+        let ultimo_timestamp = logs[*membros.last().unwrap()].timestamp.clone();
+
+        // Estender o resumo existente da mesma sessão, se houver
+        let existente = logs.iter_mut()
+            .rev()
+            .find(|log| categoria(log) == CategoriaLog::Resumo && log.session_id == sessao);
+
+        if let Some(log) = existente {
+            let resumo = log.resumo.get_or_insert(ResumoProgresso {
+                primeiro_arquivo: primeiro,
+                ultimo_arquivo: ultimo,
+                entradas_compactadas: 0,
+            });
+            if primeiro > 0 {
+                resumo.primeiro_arquivo = if resumo.primeiro_arquivo == 0 { primeiro } else { resumo.primeiro_arquivo.min(primeiro) };
+            }
+            resumo.ultimo_arquivo = resumo.ultimo_arquivo.max(ultimo);
+            resumo.entradas_compactadas += membros.len();
+            log.message = mensagem_resumo(resumo);
+            log.timestamp = ultimo_timestamp;
+        } else {
+            let resumo = ResumoProgresso {
+                primeiro_arquivo: primeiro,
+                ultimo_arquivo: ultimo,
+                entradas_compactadas: membros.len(),
+            };
+            novos_resumos.push((membros[0], ProcessingLog {
+                timestamp: ultimo_timestamp,
+                message: mensagem_resumo(&resumo),
+                log_type: "progress_summary".to_string(),
+                session_id: sessao,
+                file_index: None,
+                resumo: Some(resumo),
+            }));
+        }
+    }
+
+    // Reconstruir a lista: novos resumos entram na posição da primeira entrada compactada
+    let antigos = std::mem::take(logs);
+    for (index, log) in antigos.into_iter().enumerate() {
+        if let Some(pos) = novos_resumos.iter().position(|(i, _)| *i == index) {
+            logs.push(novos_resumos.remove(pos).1);
+        }
+        if !compactar.contains(&index) {
+            logs.push(log);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_execucao_grande_preserva_erros_e_compacta_progresso() {
+        let limites = LimitesLogs::para_max_logs(100);
+        let mut logs = Vec::new();
+
+        adicionar_log(&mut logs, novo_log("Início".to_string(), "info".to_string(), Some("s1".to_string())), &limites);
+
+        for index in 1..=500 {
+            adicionar_log(&mut logs, novo_log_progresso("s1", index, 500, &format!("arquivo_{}.pdf", index)), &limites);
+            if index % 50 == 0 {
+                adicionar_log(&mut logs, novo_log(format!("Erro no arquivo {}", index), "error".to_string(), Some("s1".to_string())), &limites);
+            }
+        }
+
+        // Todos os erros sobrevivem
+        let erros: Vec<_> = logs.iter().filter(|l| l.log_type == "error").collect();
+        assert_eq!(erros.len(), 10);
+
+        // Progresso respeita o limite
+        let progresso = logs.iter().filter(|l| l.log_type == "progress").count();
+        assert!(progresso <= limites.progresso);
+
+        // O resumo cobre tudo que foi compactado, sem buracos
+        let view = separar_resumos(&logs);
+        assert_eq!(view.total_errors, 10);
+        assert_eq!(view.progress_summaries.len(), 1);
+        let resumo = view.progress_summaries[0].resumo.as_ref().unwrap();
+        assert_eq!(resumo.primeiro_arquivo, 1);
+        assert_eq!(resumo.entradas_compactadas + progresso, 500);
+        assert_eq!(resumo.ultimo_arquivo, 500 - progresso);
+        assert!(view.progress_summaries[0].message.starts_with("Arquivos processados 1–"));
+        assert!(view.logs.iter().all(|l| l.log_type != "progress_summary"));
+
+        // A entrada informativa também continua lá
+        assert!(logs.iter().any(|l| l.message == "Início"));
+    }
+
+    #[test]
+    fn test_limites_independentes_por_categoria() {
+        let limites = LimitesLogs { progresso: 10, resumos: 2, geral: 3, erros: 5 };
+        let mut logs = Vec::new();
+
+        for index in 0..8 {
+            adicionar_log(&mut logs, novo_log(format!("info {}", index), "info".to_string(), None), &limites);
+            adicionar_log(&mut logs, novo_log(format!("erro {}", index), "error".to_string(), None), &limites);
+        }
+
+        let infos: Vec<_> = logs.iter().filter(|l| l.log_type == "info").map(|l| l.message.as_str()).collect();
+        let erros: Vec<_> = logs.iter().filter(|l| l.log_type == "error").map(|l| l.message.as_str()).collect();
+        assert_eq!(infos, vec!["info 5", "info 6", "info 7"]);
+        assert_eq!(erros, vec!["erro 3", "erro 4", "erro 5", "erro 6", "erro 7"]);
+    }
+
+    #[test]
+    fn test_resumos_separados_por_sessao() {
+        let limites = LimitesLogs { progresso: 2, resumos: 10, geral: 10, erros: 10 };
+        let mut logs = Vec::new();
+
+        for index in 1..=3 {
+            adicionar_log(&mut logs, novo_log_progresso("a", index, 3, "x.pdf"), &limites);
+        }
+        for index in 1..=3 {
+            adicionar_log(&mut logs, novo_log_progresso("b", index, 3, "y.pdf"), &limites);
+        }
+
+        let view = separar_resumos(&logs);
+        let sessoes: Vec<_> = view.progress_summaries.iter().map(|l| l.session_id.clone().unwrap()).collect();
+        assert_eq!(sessoes, vec!["a", "b"]);
+        assert_eq!(view.progress_summaries[0].resumo.as_ref().unwrap().ultimo_arquivo, 3);
+    }
+
+    fn pasta_temporaria(nome: &str) -> PathBuf {
+        let pasta = std::env::temp_dir().join(format!("licitacao360_{}_{}", nome, std::process::id()));
+        let _ = fs::remove_dir_all(&pasta);
+        pasta
+    }
+
+    fn log_em(timestamp: &str, mensagem: &str) -> ProcessingLog {
+        ProcessingLog { timestamp: timestamp.to_string(), ..novo_log(mensagem.to_string(), "info".to_string(), None) }
+    }
+
+    #[test]
+    fn test_logs_em_arquivos_diarios_com_rotacao() {
+        let pasta = pasta_temporaria("logs_rotacao");
+        let hoje = Local::now();
+        let ontem = hoje - chrono::Duration::days(1);
+
+        gravar_log(&pasta, &log_em(&ontem.to_rfc3339(), "ontem")).unwrap();
+        // Limite pequeno: a cada duas entradas o arquivo do dia é rotacionado
+        for index in 0..5 {
+            gravar_log_com_limite(&pasta, &log_em(&hoje.to_rfc3339(), &format!("hoje {}", index)), 200).unwrap();
+        }
+
+        let arquivos = listar_arquivos_log(&pasta);
+        assert!(arquivos.len() > 2, "esperava partes rotacionadas: {:?}", arquivos);
+        assert!(arquivos.iter().all(|a| fs::metadata(&a.caminho).unwrap().len() < 400));
+
+        let mensagens = |entradas: Vec<ProcessingLog>| entradas.into_iter().map(|l| l.message).collect::<Vec<_>>();
+        assert_eq!(mensagens(ler_logs(&pasta, Some(ontem.date_naive())).unwrap()), vec!["ontem"]);
+        assert_eq!(
+            mensagens(ler_logs(&pasta, None).unwrap()),
+            vec!["ontem", "hoje 0", "hoje 1", "hoje 2", "hoje 3", "hoje 4"]
+        );
+
+        // Uma linha truncada por uma queda não impede a leitura das demais
+        let atual = pasta.join(nome_arquivo_log(hoje.date_naive()));
+        OpenOptions::new().append(true).open(&atual).unwrap().write_all(b"{\"timestamp\": \"2024").unwrap();
+        assert_eq!(ler_logs(&pasta, Some(hoje.date_naive())).unwrap().len(), 5);
+
+        assert_eq!(limpar_logs(&pasta).unwrap(), arquivos.len());
+        assert!(ler_logs(&pasta, None).unwrap().is_empty());
+        let _ = fs::remove_dir_all(&pasta);
+    }
+
+    #[test]
+    fn test_logs_migrados_sobrevivem_a_retencao() {
+        let pasta = pasta_temporaria("logs_migrados");
+        let antigo = Local::now() - chrono::Duration::days(DIAS_RETENCAO_LOGS + 30);
+
+        gravar_logs_migrados(&pasta, &[log_em(&antigo.to_rfc3339(), "antigo")]).unwrap();
+        // O primeiro log de amanhã cria um arquivo novo e dispara a retenção
+        let amanha = Local::now() + chrono::Duration::days(1);
+        gravar_log(&pasta, &log_em(&amanha.to_rfc3339(), "amanhã")).unwrap();
+
+        let mensagens: Vec<_> = ler_logs(&pasta, None).unwrap().into_iter().map(|l| l.message).collect();
+        assert_eq!(mensagens, vec!["antigo", "amanhã"]);
+        let _ = fs::remove_dir_all(&pasta);
+    }
+
+    #[test]
+    fn test_nomes_de_arquivo_de_log() {
+        let data = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        assert_eq!(arquivo_log(Path::new("log-2024-05-01.jsonl")).map(|a| (a.data, a.parte)), Some((data, None)));
+        assert_eq!(arquivo_log(Path::new("log-2024-05-01.3.jsonl")).map(|a| (a.data, a.parte)), Some((data, Some(3))));
+        assert!(arquivo_log(Path::new("log-2024-05-01.x.jsonl")).is_none());
+        assert!(arquivo_log(Path::new("licitacao360_config.json")).is_none());
+    }
+}
@@ -0,0 +1,244 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, NaiveDateTime};
+use pdf_extract::{Dictionary, Document, PlainTextOutput};
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use crate::paths;
+use crate::types::PdfValidation;
+
+/// Erro de um PDF que não abre sem senha; reconhecido na cadeia do anyhow por `eh_pdf_protegido`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfEncrypted {
+    pub arquivo: String,
+}
+
+impl fmt::Display for PdfEncrypted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PDF protegido por senha: {}", self.arquivo)
+    }
+}
+
+impl std::error::Error for PdfEncrypted {}
+
+/// O erro (ou alguma das causas) é de PDF protegido por senha
+pub fn eh_pdf_protegido(erro: &anyhow::Error) -> bool {
+    erro.chain().any(|causa| causa.is::<PdfEncrypted>())
+}
+
+/// O documento está criptografado e não abre com a senha de usuário vazia
+fn exige_senha(doc: &mut Document) -> bool {
+    doc.is_encrypted() && doc.decrypt("").is_err()
+}
+
+/// Verifica se o PDF exige senha; o documento só é lido se o arquivo mencionar /Encrypt
+pub fn pdf_protegido_por_senha(pdf_path: &Path) -> bool {
+    let menciona_encrypt = fs::read(pdf_path).is_ok_and(|bytes| bytes.windows(8).any(|w| w == b"/Encrypt"));
+    menciona_encrypt && Document::load(pdf_path).is_ok_and(|mut doc| exige_senha(&mut doc))
+}
+
+/// Troca o erro da extração de texto por `PdfEncrypted` quando o PDF exige senha
+pub fn classificar_erro_extracao(pdf_path: &Path, erro: anyhow::Error) -> anyhow::Error {
+    if pdf_protegido_por_senha(pdf_path) {
+        anyhow::Error::new(PdfEncrypted { arquivo: pdf_path.to_string_lossy().to_string() })
+    } else {
+        erro
+    }
+}
+
+/// Classifica um arquivo antes do processamento: PDF legível, protegido, outro formato ou corrompido
+pub fn validar_pdf(path: &Path) -> PdfValidation {
+    let mut cabecalho = [0u8; 1024];
+    let lidos = match fs::File::open(path).and_then(|mut arquivo| arquivo.read(&mut cabecalho)) {
+        Ok(lidos) => lidos,
+        Err(_) => return PdfValidation::NotAPdf,
+    };
+    // O cabeçalho pode vir depois de alguns bytes de lixo, mas sempre no primeiro KB
+    if !paths::is_pdf_file(path) || !cabecalho[..lidos].windows(5).any(|w| w == b"%PDF-") {
+        return PdfValidation::NotAPdf;
+    }
+
+    let Ok(mut doc) = Document::load(path) else {
+        return PdfValidation::Corrupted;
+    };
+    if exige_senha(&mut doc) {
+        PdfValidation::Encrypted
+    } else {
+        PdfValidation::Valid
+    }
+}
+
+/// Metadados do dicionário Info e da primeira página de um PDF
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetadadosPdf {
+    pub paginas: usize,
+    pub titulo: Option<String>,
+    pub autor: Option<String>,
+    /// Data de criação em RFC 3339, ou o valor original se não estiver no formato do PDF
+    pub data_criacao: Option<String>,
+    /// A primeira página rendeu algum texto (falso em PDFs escaneados)
+    pub texto_extraivel: bool,
+}
+
+/// Decodifica uma string de texto do PDF: UTF-16BE com BOM, UTF-8 com BOM ou PDFDocEncoding
+fn texto_pdf(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(&[0xfe, 0xff]) {
+        let unidades: Vec<u16> = utf16.chunks_exact(2).map(|par| u16::from_be_bytes([par[0], par[1]])).collect();
+        String::from_utf16_lossy(&unidades)
+    } else if let Some(utf8) = bytes.strip_prefix(&[0xef, 0xbb, 0xbf]) {
+        String::from_utf8_lossy(utf8).into_owned()
+    } else {
+        // Os caracteres acentuados do PDFDocEncoding coincidem com o Latin-1
+        bytes.iter().map(|&b| b as char).collect()
+    }
+}
+
+fn campo_texto(info: &Dictionary, doc: &Document, chave: &[u8]) -> Option<String> {
+    let valor = info.get_deref(chave, doc).ok()?.as_str().ok()?;
+    let texto = texto_pdf(valor).trim().to_string();
+    (!texto.is_empty()).then_some(texto)
+}
+
+/// Converte datas do PDF ("D:20240115103000-03'00'") para RFC 3339
+pub fn converter_data_pdf(bruta: &str) -> Option<String> {
+    let data = bruta.trim().trim_start_matches("D:");
+    let fim_digitos = data.find(|c: char| !c.is_ascii_digit()).unwrap_or(data.len());
+    let (digitos, fuso) = data.split_at(fim_digitos);
+    if digitos.len() < 4 {
+        return None;
+    }
+
+    // Só o ano é obrigatório; os campos omitidos assumem o início do período
+    let campo = |inicio: usize, padrao: &'static str| digitos.get(inicio..inicio + 2).unwrap_or(padrao);
+    let local = format!(
+        "{}-{}-{}T{}:{}:{}",
+        &digitos[..4], campo(4, "01"), campo(6, "01"), campo(8, "00"), campo(10, "00"), campo(12, "00")
+    );
+    NaiveDateTime::parse_from_str(&local, "%Y-%m-%dT%H:%M:%S").ok()?;
+
+    let fuso: String = fuso.chars().filter(|c| *c != '\'').collect();
+    // Fusos malformados (curtos ou com caracteres multibyte) ficam de fora, sem fatiar no meio de um caractere
+    let horas = fuso.get(1..3).filter(|horas| horas.bytes().all(|b| b.is_ascii_digit()));
+    let deslocamento = match (fuso.chars().next(), horas) {
+        (Some('Z'), _) => "Z".to_string(),
+        (Some(sinal @ ('+' | '-')), Some(horas)) => {
+            format!("{}{}:{}", sinal, horas, fuso.get(3..5).unwrap_or("00"))
+        }
+        _ => return Some(local),
+    };
+    let completa = format!("{}{}", local, deslocamento);
+    DateTime::parse_from_rfc3339(&completa).ok().map(|_| completa)
+}
+
+/// Verifica se a primeira página produz texto; pânicos do pdf_extract contam como "não"
+fn primeira_pagina_tem_texto(doc: &Document) -> bool {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let mut texto = String::new();
+        let mut saida = PlainTextOutput::new(&mut texto);
+        pdf_extract::output_doc_page(doc, &mut saida, 1).is_ok() && texto.chars().any(|c| !c.is_whitespace())
+    }))
+    .unwrap_or(false)
+}
+
+/// Lê número de páginas, título, autor e data de criação de um PDF
+pub fn ler_metadados_pdf(pdf_path: &Path) -> Result<MetadadosPdf> {
+    let mut doc = Document::load(pdf_path).context("PDF inválido ou corrompido")?;
+    if doc.is_encrypted() {
+        doc.decrypt("").map_err(|_| anyhow!(PdfEncrypted { arquivo: pdf_path.to_string_lossy().to_string() }))?;
+    }
+
+    let info = doc.trailer.get_deref(b"Info", &doc).and_then(|info| info.as_dict()).ok();
+    let campo = |chave: &[u8]| info.and_then(|info| campo_texto(info, &doc, chave));
+
+    Ok(MetadadosPdf {
+        paginas: doc.get_pages().len(),
+        titulo: campo(b"Title"),
+        autor: campo(b"Author"),
+        data_criacao: campo(b"CreationDate").map(|data| converter_data_pdf(&data).unwrap_or(data)),
+        texto_extraivel: primeira_pagina_tem_texto(&doc),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_converter_data_pdf() {
+        assert_eq!(converter_data_pdf("D:20240115103000-03'00'").as_deref(), Some("2024-01-15T10:30:00-03:00"));
+        assert_eq!(converter_data_pdf("D:20240115103000Z").as_deref(), Some("2024-01-15T10:30:00Z"));
+        assert_eq!(converter_data_pdf("D:202401").as_deref(), Some("2024-01-01T00:00:00"));
+        assert_eq!(converter_data_pdf("D:20241345").as_deref(), None);
+        assert_eq!(converter_data_pdf("D:20240115103000+é").as_deref(), Some("2024-01-15T10:30:00"));
+        assert_eq!(converter_data_pdf("D:20240115103000-0").as_deref(), Some("2024-01-15T10:30:00"));
+        assert_eq!(converter_data_pdf("ontem"), None);
+    }
+
+    #[test]
+    fn test_texto_pdf_nas_codificacoes_do_pdf() {
+        assert_eq!(texto_pdf(&[0xfe, 0xff, 0x00, b'A', 0x00, 0xe7, 0x00, 0xe3, 0x00, b'o']), "Ação");
+        assert_eq!(texto_pdf(b"Preg\xe3o"), "Pregão");
+        assert_eq!(texto_pdf("\u{feff}Ata".as_bytes()), "Ata");
+    }
+
+    /// PDF com dicionário /Encrypt cuja senha de usuário não é vazia
+    fn salvar_pdf_com_senha(path: &Path) {
+        use pdf_extract::Object;
+
+        let mut doc = Document::with_version("1.4");
+        let mut encrypt = Dictionary::new();
+        encrypt.set("Filter", Object::Name(b"Standard".to_vec()));
+        encrypt.set("V", 1);
+        encrypt.set("R", 2);
+        encrypt.set("O", Object::string_literal(vec![0u8; 32]));
+        encrypt.set("U", Object::string_literal(vec![0u8; 32]));
+        encrypt.set("P", -1);
+        let id = doc.add_object(encrypt);
+        doc.trailer.set("Encrypt", Object::Reference(id));
+        doc.trailer.set("ID", Object::Array(vec![
+            Object::string_literal(vec![1u8; 16]),
+            Object::string_literal(vec![1u8; 16]),
+        ]));
+        doc.save(path).unwrap();
+    }
+
+    #[test]
+    fn test_validar_pdf_classifica_arquivos() {
+        let dir = std::env::temp_dir().join(format!("licitacao360_validar_pdf_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let protegido = dir.join("protegido.pdf");
+        salvar_pdf_com_senha(&protegido);
+        assert_eq!(validar_pdf(&protegido), PdfValidation::Encrypted);
+        assert!(pdf_protegido_por_senha(&protegido));
+        let erro = classificar_erro_extracao(&protegido, anyhow!("erro genérico do pdf_extract"));
+        assert!(eh_pdf_protegido(&erro));
+        assert!(eh_pdf_protegido(&ler_metadados_pdf(&protegido).unwrap_err()));
+
+        let valido = dir.join("valido.pdf");
+        Document::with_version("1.4").save(&valido).unwrap();
+        assert_eq!(validar_pdf(&valido), PdfValidation::Valid);
+        assert!(!eh_pdf_protegido(&classificar_erro_extracao(&valido, anyhow!("erro"))));
+
+        let corrompido = dir.join("corrompido.pdf");
+        fs::write(&corrompido, b"%PDF-1.4\nlixo").unwrap();
+        assert_eq!(validar_pdf(&corrompido), PdfValidation::Corrupted);
+
+        let renomeado = dir.join("planilha.pdf");
+        fs::write(&renomeado, b"PK\x03\x04").unwrap();
+        assert_eq!(validar_pdf(&renomeado), PdfValidation::NotAPdf);
+        assert_eq!(validar_pdf(&dir.join("inexistente.pdf")), PdfValidation::NotAPdf);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_pdf_corrompido_retorna_erro() {
+        let path = std::env::temp_dir().join(format!("licitacao360_metadados_{}.pdf", std::process::id()));
+        std::fs::write(&path, b"%PDF-1.4\nlixo").unwrap();
+        assert!(ler_metadados_pdf(&path).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,281 @@
+use anyhow::{anyhow, Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use crate::config;
+use crate::integridade;
+
+/// Nome do arquivo de métricas dentro de Database/Config
+pub const ARQUIVO_METRICAS: &str = "metrics.prom";
+
+static METRICAS: OnceLock<Mutex<Metricas>> = OnceLock::new();
+
+/// Modo da execução (rótulo `mode`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModoExecucao {
+    Arquivo,
+    Diretorio,
+    Agendado,
+}
+
+impl ModoExecucao {
+    pub fn rotulo(&self) -> &'static str {
+        match self {
+            ModoExecucao::Arquivo => "file",
+            ModoExecucao::Diretorio => "directory",
+            ModoExecucao::Agendado => "scheduled",
+        }
+    }
+}
+
+/// Resultado da execução (rótulo `outcome`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResultadoExecucao {
+    Sucesso,
+    Falha,
+    Cancelado,
+}
+
+impl ResultadoExecucao {
+    pub fn rotulo(&self) -> &'static str {
+        match self {
+            ResultadoExecucao::Sucesso => "success",
+            ResultadoExecucao::Falha => "failure",
+            ResultadoExecucao::Cancelado => "cancelled",
+        }
+    }
+}
+
+/// Categorias de falha (rótulo `category`)
+pub const FALHA_EXTRACAO: &str = "extraction";
+pub const FALHA_PERSISTENCIA: &str = "persistence";
+pub const FALHA_PROCESSAMENTO: &str = "processing";
+
+/// Resumo de uma execução do pipeline
+#[derive(Debug, Clone)]
+pub struct Execucao {
+    pub modo: ModoExecucao,
+    pub resultado: ResultadoExecucao,
+    pub arquivos_processados: usize,
+    pub propostas: usize,
+    pub duracao: Duration,
+    /// Quantidade de falhas por categoria
+    pub falhas: BTreeMap<&'static str, u64>,
+}
+
+impl Execucao {
+    pub fn nova(modo: ModoExecucao, resultado: ResultadoExecucao, duracao: Duration) -> Self {
+        Self {
+            modo,
+            resultado,
+            arquivos_processados: 0,
+            propostas: 0,
+            duracao,
+            falhas: BTreeMap::new(),
+        }
+    }
+}
+
+/// Contadores acumulados desde a primeira execução
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metricas {
+    /// (mode, outcome) -> execuções
+    pub execucoes: BTreeMap<(String, String), u64>,
+    pub arquivos_processados: u64,
+    pub propostas: u64,
+    pub duracao_ultima_execucao: f64,
+    /// category -> falhas
+    pub falhas: BTreeMap<String, u64>,
+}
+
+fn cabecalho(texto: &mut String, nome: &str, tipo: &str, ajuda: &str) {
+    texto.push_str(&format!("# HELP {} {}\n# TYPE {} {}\n", nome, ajuda, nome, tipo));
+}
+
+/// Separa `nome{a="1",b="2"} valor` em nome, rótulos e valor
+fn analisar_linha(linha: &str) -> Option<(String, BTreeMap<String, String>, f64)> {
+    let (serie, valor) = linha.rsplit_once(' ')?;
+    let valor: f64 = valor.trim().parse().ok()?;
+
+    let (nome, rotulos) = match serie.split_once('{') {
+        Some((nome, resto)) => {
+            let rotulos = resto.strip_suffix('}')?
+                .split(',')
+                .filter(|par| !par.is_empty())
+                .filter_map(|par| {
+                    let (chave, valor) = par.split_once('=')?;
+                    Some((chave.trim().to_string(), valor.trim().trim_matches('"').to_string()))
+                })
+                .collect();
+            (nome, rotulos)
+        }
+        None => (serie, BTreeMap::new()),
+    };
+
+    Some((nome.trim().to_string(), rotulos, valor))
+}
+
+impl Metricas {
+    /// Acumula uma execução nos contadores
+    pub fn registrar(&mut self, execucao: &Execucao) {
+        let chave = (execucao.modo.rotulo().to_string(), execucao.resultado.rotulo().to_string());
+        *self.execucoes.entry(chave).or_insert(0) += 1;
+        self.arquivos_processados += execucao.arquivos_processados as u64;
+        self.propostas += execucao.propostas as u64;
+        self.duracao_ultima_execucao = execucao.duracao.as_secs_f64();
+        for (categoria, quantidade) in &execucao.falhas {
+            *self.falhas.entry(categoria.to_string()).or_insert(0) += quantidade;
+        }
+    }
+
+    /// Gera o texto no formato de exposição do Prometheus
+    pub fn para_texto(&self) -> String {
+        let mut texto = String::new();
+
+        cabecalho(&mut texto, "runs_total", "counter", "Execuções do processamento por modo e resultado");
+        for ((modo, resultado), total) in &self.execucoes {
+            texto.push_str(&format!("runs_total{{mode=\"{}\",outcome=\"{}\"}} {}\n", modo, resultado, total));
+        }
+
+        cabecalho(&mut texto, "files_processed_total", "counter", "Arquivos PDF processados");
+        texto.push_str(&format!("files_processed_total {}\n", self.arquivos_processados));
+
+        cabecalho(&mut texto, "propostas_total", "counter", "Propostas extraídas");
+        texto.push_str(&format!("propostas_total {}\n", self.propostas));
+
+        cabecalho(&mut texto, "last_run_duration_seconds", "gauge", "Duração da última execução em segundos");
+        texto.push_str(&format!("last_run_duration_seconds {:.3}\n", self.duracao_ultima_execucao));
+
+        cabecalho(&mut texto, "failures_total", "counter", "Falhas por categoria");
+        for (categoria, total) in &self.falhas {
+            texto.push_str(&format!("failures_total{{category=\"{}\"}} {}\n", categoria, total));
+        }
+
+        texto
+    }
+
+    /// Reconstrói os contadores a partir de um arquivo gerado por `para_texto`
+    pub fn de_texto(texto: &str) -> Self {
+        let mut metricas = Metricas::default();
+
+        for linha in texto.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let Some((nome, rotulos, valor)) = analisar_linha(linha) else {
+                continue;
+            };
+            let rotulo = |chave: &str| rotulos.get(chave).cloned().unwrap_or_default();
+
+            match nome.as_str() {
+                "runs_total" => {
+                    metricas.execucoes.insert((rotulo("mode"), rotulo("outcome")), valor as u64);
+                }
+                "files_processed_total" => metricas.arquivos_processados = valor as u64,
+                "propostas_total" => metricas.propostas = valor as u64,
+                "last_run_duration_seconds" => metricas.duracao_ultima_execucao = valor,
+                "failures_total" => {
+                    metricas.falhas.insert(rotulo("category"), valor as u64);
+                }
+                _ => {}
+            }
+        }
+
+        metricas
+    }
+}
+
+/// Caminho do arquivo de métricas (Database/Config/metrics.prom)
+pub fn caminho_metricas() -> Result<PathBuf> {
+    let config_dir = config::get_config_dir().map_err(|e| anyhow!(e.message))?;
+    Ok(config_dir.join(ARQUIVO_METRICAS))
+}
+
+/// Contadores do processo, carregados do arquivo no primeiro uso
+fn estado() -> &'static Mutex<Metricas> {
+    METRICAS.get_or_init(|| {
+        let metricas = caminho_metricas()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|texto| Metricas::de_texto(&texto))
+            .unwrap_or_default();
+        Mutex::new(metricas)
+    })
+}
+
+/// Carrega os contadores das execuções anteriores (chamado na inicialização)
+pub fn inicializar() {
+    let _ = estado();
+}
+
+/// Registra uma execução e regrava o arquivo de métricas de forma atômica
+pub fn registrar_execucao(execucao: &Execucao) -> Result<()> {
+    let mut metricas = estado().lock().unwrap_or_else(|e| e.into_inner());
+    metricas.registrar(execucao);
+
+    let path = caminho_metricas()?;
+    integridade::escrever_atomico(&path, metricas.para_texto().as_bytes())
+        .context(format!("Erro ao salvar métricas: {}", path.display()))
+}
+
+/// Texto atual das métricas, o mesmo conteúdo de metrics.prom
+pub fn texto_metricas() -> String {
+    estado().lock().unwrap_or_else(|e| e.into_inner()).para_texto()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn execucoes_de_exemplo() -> Metricas {
+        let mut metricas = Metricas::default();
+
+        let mut execucao = Execucao::nova(ModoExecucao::Diretorio, ResultadoExecucao::Sucesso, Duration::from_millis(2500));
+        execucao.arquivos_processados = 4;
+        execucao.propostas = 12;
+        execucao.falhas.insert(FALHA_EXTRACAO, 1);
+        metricas.registrar(&execucao);
+
+        let mut execucao = Execucao::nova(ModoExecucao::Arquivo, ResultadoExecucao::Falha, Duration::from_millis(300));
+        execucao.falhas.insert(FALHA_PROCESSAMENTO, 1);
+        metricas.registrar(&execucao);
+
+        metricas.registrar(&Execucao::nova(ModoExecucao::Diretorio, ResultadoExecucao::Sucesso, Duration::from_secs(1)));
+        metricas
+    }
+
+    #[test]
+    fn test_formato_de_exposicao_valido() {
+        let texto = execucoes_de_exemplo().para_texto();
+        let scrape = prometheus_parse::Scrape::parse(texto.lines().map(|l| Ok(l.to_string()))).unwrap();
+
+        let valor = |nome: &str, rotulo: Option<(&str, &str)>| {
+            scrape.samples.iter()
+                .find(|s| s.metric == nome && rotulo.iter().all(|(k, v)| s.labels.get(k) == Some(*v)))
+                .map(|s| match s.value {
+                    prometheus_parse::Value::Counter(v) | prometheus_parse::Value::Gauge(v) => v,
+                    _ => panic!("tipo inesperado para {}", nome),
+                })
+                .unwrap()
+        };
+
+        assert_eq!(valor("runs_total", Some(("outcome", "success"))), 2.0);
+        assert_eq!(valor("runs_total", Some(("mode", "file"))), 1.0);
+        assert_eq!(valor("files_processed_total", None), 4.0);
+        assert_eq!(valor("propostas_total", None), 12.0);
+        assert_eq!(valor("last_run_duration_seconds", None), 1.0);
+        assert_eq!(valor("failures_total", Some(("category", "extraction"))), 1.0);
+        assert_eq!(valor("failures_total", Some(("category", "processing"))), 1.0);
+    }
+
+    #[test]
+    fn test_contadores_persistem_pelo_arquivo() {
+        let metricas = execucoes_de_exemplo();
+        let mut recarregadas = Metricas::de_texto(&metricas.para_texto());
+        assert_eq!(recarregadas, metricas);
+
+        // Após reiniciar, novas execuções continuam a contagem anterior
+        recarregadas.registrar(&Execucao::nova(ModoExecucao::Agendado, ResultadoExecucao::Cancelado, Duration::ZERO));
+        assert_eq!(recarregadas.execucoes.values().sum::<u64>(), 4);
+        assert_eq!(recarregadas.propostas, 12);
+    }
+}
@@ -0,0 +1,289 @@
+use anyhow::{bail, Context, Result};
+use chrono::Local;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::integridade;
+use crate::paths;
+use crate::pdf_processor::converter_valor_para_float;
+use crate::resultados::numero_tolerante;
+use crate::types::{MigracaoResultados, SITUACAO_ADJUDICADO};
+
+/// Versão atual do esquema dos JSONs de licitação e do sicaf_dados.json
+///
+/// 1: arquivos sem `schema_version` (gerados antes do versionamento)
+/// 2: totais e identificação da licitação sempre presentes; propostas com situação e validade do CNPJ
+/// 3: valores monetários como número (ou `null`), texto original em `valor_adjudicado_raw`
+pub const VERSAO_ESQUEMA: u32 = 3;
+
+/// Campo que guarda a versão do esquema no JSON
+pub const CAMPO_VERSAO: &str = "schema_version";
+
+/// Pasta (dentro da raiz de resultados) com as cópias dos arquivos anteriores à migração
+pub const PASTA_BACKUP_MIGRACAO: &str = ".migracoes";
+
+/// Campos monetários de uma proposta, gravados como número a partir da versão 3
+const CAMPOS_VALOR_PROPOSTA: [&str; 3] = ["valor_estimado", "valor_adjudicado", "melhor_lance"];
+
+/// Campos de texto obrigatórios de uma proposta
+const CAMPOS_TEXTO_PROPOSTA: [&str; 15] = [
+    "uasg", "pregao", "processo", "item", "quantidade", "descricao", "valor_estimado", "valor_adjudicado",
+    "fornecedor", "cnpj", "marca_fabricante", "modelo_versao", "responsavel", "melhor_lance", "tipo_formato",
+];
+
+/// Versão do esquema de um JSON (1 quando o campo não existe)
+pub fn versao_esquema(valor: &Value) -> u32 {
+    valor.get(CAMPO_VERSAO)
+        .and_then(Value::as_u64)
+        .map_or(1, |versao| versao as u32)
+}
+
+/// O JSON foi gravado com um esquema anterior ao atual
+pub fn precisa_migrar(valor: &Value) -> bool {
+    versao_esquema(valor) < VERSAO_ESQUEMA
+}
+
+/// Atualiza um JSON de licitação para o esquema atual, preenchendo os campos com os padrões
+pub fn migrar_licitacao_json(mut valor: Value) -> Result<Value> {
+    let versao = versao_esquema(&valor);
+    if versao > VERSAO_ESQUEMA {
+        bail!("JSON gravado com um esquema mais novo ({}) que o suportado ({})", versao, VERSAO_ESQUEMA);
+    }
+
+    let objeto = valor.as_object_mut().context("O JSON de licitação deve ser um objeto")?;
+    if versao < 2 {
+        migrar_v1_para_v2(objeto)?;
+    }
+    if versao < 3 {
+        migrar_v2_para_v3(objeto)?;
+    }
+    objeto.insert(CAMPO_VERSAO.to_string(), VERSAO_ESQUEMA.into());
+    Ok(valor)
+}
+
+fn migrar_v1_para_v2(objeto: &mut Map<String, Value>) -> Result<()> {
+    let propostas = objeto.get_mut("propostas")
+        .and_then(Value::as_array_mut)
+        .context("JSON de licitação sem a lista 'propostas'")?;
+
+    for proposta in propostas.iter_mut().filter_map(Value::as_object_mut) {
+        for campo in CAMPOS_TEXTO_PROPOSTA {
+            let atual = proposta.entry(campo).or_insert(Value::Null);
+            if atual.is_null() {
+                *atual = Value::String(String::new());
+            }
+        }
+        proposta.entry("grupo").or_insert(Value::Null);
+        proposta.entry("situacao").or_insert_with(|| SITUACAO_ADJUDICADO.into());
+        proposta.entry("cnpj_valido").or_insert(true.into());
+        proposta.entry("descricao_reparada").or_insert(false.into());
+    }
+
+    let total_propostas = propostas.len();
+    let valor_total: f64 = propostas.iter()
+        .filter_map(|proposta| proposta.get("valor_adjudicado")?.as_str())
+        .filter_map(|valor| converter_valor_para_float(valor).ok())
+        .sum();
+    // Arquivos antigos só tinham a identificação dentro das propostas
+    let identificacao: Vec<(&str, Value)> = ["uasg", "pregao", "processo"].into_iter()
+        .map(|campo| (campo, propostas.first().and_then(|p| p.get(campo)).cloned().unwrap_or_else(|| "".into())))
+        .collect();
+
+    for (campo, valor) in identificacao {
+        objeto.entry(campo).or_insert(valor);
+    }
+    // Totais antigos podem estar como texto ("12", "1.234,50"); ilegíveis são recalculados
+    let total_lido = objeto.get("total_propostas")
+        .and_then(numero_tolerante)
+        .filter(|total| *total >= 0.0 && total.fract() == 0.0)
+        .map(|total| total as u64);
+    let valor_lido = objeto.get("valor_total").and_then(numero_tolerante);
+    objeto.insert("total_propostas".into(), total_lido.unwrap_or(total_propostas as u64).into());
+    objeto.insert("valor_total".into(), valor_lido.unwrap_or(valor_total).into());
+    objeto.entry("processamento_interrompido").or_insert(false.into());
+    Ok(())
+}
+
+fn migrar_v2_para_v3(objeto: &mut Map<String, Value>) -> Result<()> {
+    let propostas = objeto.get_mut("propostas")
+        .and_then(Value::as_array_mut)
+        .context("JSON de licitação sem a lista 'propostas'")?;
+
+    for proposta in propostas.iter_mut().filter_map(Value::as_object_mut) {
+        if let Some(original) = proposta.get("valor_adjudicado").and_then(Value::as_str) {
+            let original = original.to_string();
+            proposta.entry("valor_adjudicado_raw").or_insert(original.into());
+        }
+        for campo in CAMPOS_VALOR_PROPOSTA {
+            if let Some(Value::String(texto)) = proposta.get(campo) {
+                let numero = converter_valor_para_float(texto).map_or(Value::Null, Value::from);
+                proposta.insert(campo.to_string(), numero);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Migra um arquivo de licitação no lugar, copiando o original para `pasta_backup`
+///
+/// Retorna `false` quando o arquivo já está no esquema atual.
+pub fn migrar_arquivo_licitacao(raiz: &Path, path: &Path, pasta_backup: &Path) -> Result<bool> {
+    let content = fs::read_to_string(path)
+        .context(format!("Erro ao ler arquivo: {}", path.display()))?;
+    let valor: Value = serde_json::from_str(&content)
+        .context(format!("Erro ao analisar JSON: {}", path.display()))?;
+    if !precisa_migrar(&valor) {
+        return Ok(false);
+    }
+    let migrado = migrar_licitacao_json(valor)?;
+
+    fs::create_dir_all(pasta_backup)
+        .context(format!("Erro ao criar pasta de backup: {}", pasta_backup.display()))?;
+    let backup = paths::caminho_sem_colisao(pasta_backup, path.file_name().unwrap_or_default());
+    fs::copy(path, &backup)
+        .context(format!("Erro ao copiar {} para o backup", path.display()))?;
+
+    let conteudo = serde_json::to_string_pretty(&migrado)
+        .context("Erro ao serializar JSON migrado")?;
+    integridade::gravar_artefato(raiz, path, conteudo.as_bytes(), None)?;
+    Ok(true)
+}
+
+/// Migra os arquivos de licitação informados; o backup fica em `<raiz>/.migracoes/<data>`
+pub fn migrar_resultados(raiz: &Path, arquivos: &[PathBuf]) -> MigracaoResultados {
+    let pasta_backup = raiz
+        .join(PASTA_BACKUP_MIGRACAO)
+        .join(Local::now().format("%Y%m%d_%H%M%S").to_string());
+    let mut resultado = MigracaoResultados::default();
+
+    for arquivo in arquivos {
+        match migrar_arquivo_licitacao(raiz, arquivo, &pasta_backup) {
+            Ok(true) => resultado.migrados.push(arquivo.to_string_lossy().to_string()),
+            Ok(false) => resultado.ja_atualizados += 1,
+            Err(e) => resultado.erros.push(format!("{}: {:#}", arquivo.display(), e)),
+        }
+    }
+
+    if !resultado.migrados.is_empty() {
+        resultado.pasta_backup = Some(pasta_backup.to_string_lossy().to_string());
+    }
+    resultado
+}
+
+/// Conta os arquivos gravados com um esquema anterior ao atual (ilegíveis não contam)
+pub fn contar_desatualizados(arquivos: &[PathBuf]) -> usize {
+    #[derive(Deserialize)]
+    struct SoVersao {
+        schema_version: Option<u32>,
+    }
+
+    arquivos.iter()
+        .filter_map(|arquivo| fs::read_to_string(arquivo).ok())
+        .filter_map(|content| serde_json::from_str::<SoVersao>(&content).ok())
+        .filter(|json| json.schema_version.unwrap_or(1) < VERSAO_ESQUEMA)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrar_licitacao_v1_preenche_padroes() {
+        let antigo = serde_json::json!({
+            "propostas": [
+                {"uasg": "787000", "pregao": "90008/2024", "processo": "123", "item": "1",
+                 "valor_adjudicado": "R$ 1.234,50", "fornecedor": "EMPRESA A", "cnpj": null},
+                {"item": "2", "valor_adjudicado": "R$ 100,00"}
+            ]
+        });
+
+        let migrado = migrar_licitacao_json(antigo).unwrap();
+        assert_eq!(migrado[CAMPO_VERSAO], VERSAO_ESQUEMA);
+        assert_eq!(migrado["uasg"], "787000");
+        assert_eq!(migrado["total_propostas"], 2);
+        assert_eq!(migrado["valor_total"], 1334.5);
+        assert_eq!(migrado["processamento_interrompido"], false);
+        assert_eq!(migrado["propostas"][0]["cnpj"], "");
+        assert_eq!(migrado["propostas"][1]["situacao"], SITUACAO_ADJUDICADO);
+        assert_eq!(migrado["propostas"][1]["cnpj_valido"], true);
+        assert_eq!(migrado["propostas"][0]["valor_adjudicado"], 1234.5);
+        assert_eq!(migrado["propostas"][0]["valor_adjudicado_raw"], "R$ 1.234,50");
+        assert!(migrado["propostas"][0]["valor_estimado"].is_null());
+
+        // O resultado desserializa no formato atual
+        let licitacao: crate::types::LicitacaoConsolidada = serde_json::from_value(migrado).unwrap();
+        assert_eq!(licitacao.propostas.len(), 2);
+
+        // Totais gravados como texto viram número
+        let com_texto = serde_json::json!({
+            "total_propostas": "1",
+            "valor_total": "1.234,50",
+            "propostas": [{"item": "1", "valor_adjudicado": "R$ 1.234,50"}]
+        });
+        let migrado = migrar_licitacao_json(com_texto).unwrap();
+        assert_eq!(migrado["total_propostas"], 1);
+        assert_eq!(migrado["valor_total"], 1234.5);
+        let licitacao: crate::types::LicitacaoConsolidada = serde_json::from_value(migrado).unwrap();
+        assert_eq!(licitacao.total_propostas, 1);
+
+        let futuro = serde_json::json!({"schema_version": VERSAO_ESQUEMA + 1, "propostas": []});
+        assert!(migrar_licitacao_json(futuro).is_err());
+    }
+
+    #[test]
+    fn test_migrar_resultados_com_backup() {
+        let raiz = std::env::temp_dir().join(format!("licitacao360_migracoes_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&raiz);
+        fs::create_dir_all(&raiz).unwrap();
+        let antigo = raiz.join("licitacao_antiga.json");
+        let atual = raiz.join("licitacao_atual.json");
+        fs::write(&antigo, r#"{"propostas": []}"#).unwrap();
+        fs::write(&atual, format!(r#"{{"schema_version": {}, "propostas": []}}"#, VERSAO_ESQUEMA)).unwrap();
+        let arquivos = vec![antigo.clone(), atual];
+
+        assert_eq!(contar_desatualizados(&arquivos), 1);
+        let resultado = migrar_resultados(&raiz, &arquivos);
+        assert_eq!(resultado.migrados.len(), 1);
+        assert_eq!(resultado.ja_atualizados, 1);
+        assert!(resultado.erros.is_empty());
+
+        let backup = PathBuf::from(resultado.pasta_backup.unwrap()).join("licitacao_antiga.json");
+        assert_eq!(fs::read_to_string(backup).unwrap(), r#"{"propostas": []}"#);
+        assert_eq!(contar_desatualizados(&arquivos), 0);
+
+        let _ = fs::remove_dir_all(&raiz);
+    }
+
+    #[test]
+    fn test_licitacao_v2_com_valores_em_texto_desserializa() {
+        let v2 = serde_json::json!({
+            "schema_version": 2, "uasg": "787000", "pregao": "90008/2024", "processo": "123",
+            "total_propostas": 1, "valor_total": 1234.5, "processamento_interrompido": false,
+            "propostas": [{
+                "uasg": "787000", "pregao": "90008/2024", "processo": "123", "item": "1", "grupo": null,
+                "quantidade": "1", "descricao": "Caneta", "valor_estimado": "R$ 1.500,00",
+                "valor_adjudicado": "R$ 1.234,50", "fornecedor": "EMPRESA A", "cnpj": "11.222.333/0001-81",
+                "marca_fabricante": "N/A", "modelo_versao": "N/A", "responsavel": "JOÃO",
+                "melhor_lance": "N/A", "tipo_formato": "individual", "situacao": SITUACAO_ADJUDICADO,
+                "cnpj_valido": true, "descricao_reparada": false
+            }]
+        });
+
+        // Sem migrar: os textos antigos são lidos como número, e "N/A" como ausente
+        let licitacao: crate::types::LicitacaoConsolidada = serde_json::from_value(v2.clone()).unwrap();
+        let proposta = &licitacao.propostas[0];
+        assert_eq!(proposta.valor_adjudicado, Some(crate::types::Valor(1234.5)));
+        assert_eq!(proposta.valor_estimado, Some(crate::types::Valor(1500.0)));
+        assert_eq!(proposta.melhor_lance, None);
+
+        // Migrado: gravado como número e com o texto original preservado
+        let migrado = migrar_licitacao_json(v2).unwrap();
+        assert_eq!(migrado["propostas"][0]["valor_estimado"], 1500.0);
+        assert!(migrado["propostas"][0]["melhor_lance"].is_null());
+        let licitacao: crate::types::LicitacaoConsolidada = serde_json::from_value(migrado).unwrap();
+        assert_eq!(licitacao.propostas[0].valor_adjudicado_raw, "R$ 1.234,50");
+        assert_eq!(serde_json::to_value(&licitacao.propostas[0]).unwrap()["valor_adjudicado"], 1234.5);
+    }
+}
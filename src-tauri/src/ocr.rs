@@ -0,0 +1,151 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Mínimo de caracteres alfanuméricos para considerar que o PDF tem camada de texto
+pub const MIN_CARACTERES_UTEIS: usize = 200;
+
+/// Idioma usado pelo tesseract
+const IDIOMA_OCR: &str = "por";
+
+/// Resolução da rasterização das páginas antes do OCR
+const DPI_OCR: &str = "300";
+
+static CONTADOR_PASTAS: AtomicUsize = AtomicUsize::new(0);
+
+/// Texto de um PDF após a verificação de PDF escaneado
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextoExtraido {
+    pub texto: String,
+    pub provavelmente_escaneado: bool,
+    /// Explica o que aconteceu quando o PDF parece escaneado
+    pub aviso: Option<String>,
+}
+
+/// Conta letras e dígitos, ignorando espaços e símbolos que o extrator gera em páginas escaneadas
+pub fn contar_caracteres_uteis(texto: &str) -> usize {
+    texto.chars().filter(|c| c.is_alphanumeric()).count()
+}
+
+/// Texto vazio ou com pouquíssimo conteúdo útil indica PDF escaneado
+pub fn provavelmente_escaneado(texto: &str) -> bool {
+    contar_caracteres_uteis(texto) < MIN_CARACTERES_UTEIS
+}
+
+fn comando_disponivel(programa: &str, argumento: &str) -> bool {
+    Command::new(programa)
+        .arg(argumento)
+        .output()
+        .is_ok_and(|saida| saida.status.success())
+}
+
+/// Verifica se o tesseract e o pdftoppm (poppler) estão no PATH
+pub fn ocr_disponivel() -> bool {
+    comando_disponivel("tesseract", "--version") && comando_disponivel("pdftoppm", "-v")
+}
+
+/// Remove a pasta temporária das páginas rasterizadas ao sair do escopo
+struct PastaTemporaria(PathBuf);
+
+impl Drop for PastaTemporaria {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Rasteriza as páginas com o pdftoppm e extrai o texto de cada uma com o tesseract
+pub fn extrair_texto_ocr(pdf_path: &Path) -> Result<String> {
+    let pasta = PastaTemporaria(std::env::temp_dir().join(format!(
+        "licitacao360_ocr_{}_{}",
+        std::process::id(),
+        CONTADOR_PASTAS.fetch_add(1, Ordering::SeqCst)
+    )));
+    fs::create_dir_all(&pasta.0).context("Erro ao criar pasta temporária do OCR")?;
+
+    let saida = Command::new("pdftoppm")
+        .args(["-r", DPI_OCR, "-gray", "-png"])
+        .arg(pdf_path)
+        .arg(pasta.0.join("pagina"))
+        .output()
+        .context("Erro ao executar pdftoppm")?;
+    if !saida.status.success() {
+        bail!("pdftoppm falhou: {}", String::from_utf8_lossy(&saida.stderr).trim());
+    }
+
+    let mut paginas: Vec<PathBuf> = fs::read_dir(&pasta.0)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "png"))
+        .collect();
+    paginas.sort();
+
+    let mut texto = String::new();
+    for pagina in &paginas {
+        let saida = Command::new("tesseract")
+            .arg(pagina)
+            .arg("stdout")
+            .args(["-l", IDIOMA_OCR])
+            .output()
+            .context("Erro ao executar tesseract")?;
+        if !saida.status.success() {
+            bail!("tesseract falhou: {}", String::from_utf8_lossy(&saida.stderr).trim());
+        }
+        texto.push_str(&String::from_utf8_lossy(&saida.stdout));
+        texto.push('\n');
+    }
+
+    Ok(texto)
+}
+
+/// Verifica o texto extraído e, se o PDF parecer escaneado, tenta o OCR quando habilitado
+pub fn verificar_texto(pdf_path: &Path, texto: String, ocr_habilitado: bool) -> TextoExtraido {
+    if !provavelmente_escaneado(&texto) {
+        return TextoExtraido { texto, provavelmente_escaneado: false, aviso: None };
+    }
+
+    let uteis = contar_caracteres_uteis(&texto);
+    let aviso = if !ocr_habilitado {
+        format!("PDF provavelmente escaneado ({} caracteres úteis extraídos); habilite o OCR para tentar extrair o texto", uteis)
+    } else if !ocr_disponivel() {
+        format!("PDF provavelmente escaneado ({} caracteres úteis extraídos); OCR habilitado, mas tesseract/pdftoppm não foram encontrados", uteis)
+    } else {
+        match extrair_texto_ocr(pdf_path) {
+            Ok(texto_ocr) if !provavelmente_escaneado(&texto_ocr) => {
+                return TextoExtraido {
+                    texto: texto_ocr,
+                    provavelmente_escaneado: true,
+                    aviso: Some("PDF provavelmente escaneado; texto obtido por OCR, confira os valores extraídos".to_string()),
+                };
+            }
+            Ok(texto_ocr) => format!(
+                "PDF provavelmente escaneado; o OCR recuperou apenas {} caracteres úteis",
+                contar_caracteres_uteis(&texto_ocr)
+            ),
+            Err(e) => format!("PDF provavelmente escaneado; falha no OCR: {}", e),
+        }
+    };
+
+    TextoExtraido { texto, provavelmente_escaneado: true, aviso: Some(aviso) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_texto_escaneado_sem_ocr_gera_aviso() {
+        let lixo = "\n\n \u{c} \n .. \n".repeat(50);
+        let resultado = verificar_texto(Path::new("inexistente.pdf"), lixo.clone(), false);
+
+        assert!(resultado.provavelmente_escaneado);
+        assert_eq!(resultado.texto, lixo);
+        assert!(resultado.aviso.unwrap().contains("habilite o OCR"));
+
+        let texto = "Termo de Homologação do Pregão Eletrônico ".repeat(10);
+        let resultado = verificar_texto(Path::new("inexistente.pdf"), texto, false);
+        assert!(!resultado.provavelmente_escaneado);
+        assert!(resultado.aviso.is_none());
+    }
+}
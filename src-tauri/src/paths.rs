@@ -0,0 +1,774 @@
+use std::path::{Path, PathBuf};
+use crate::config;
+use crate::integridade;
+use crate::routing;
+use crate::types::{AppConfig, TauriError};
+
+/// Nome da pasta raiz de dados quando criada ao lado do executável
+pub const DATABASE_DIR: &str = "Database";
+
+/// Subpastas da estrutura Database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubDir {
+    Pdfs,
+    Resultados,
+    Sicaf,
+    Config,
+}
+
+impl SubDir {
+    /// Todas as subpastas, na ordem em que são criadas
+    pub const ALL: [SubDir; 4] = [SubDir::Pdfs, SubDir::Resultados, SubDir::Sicaf, SubDir::Config];
+
+    /// Nome da pasta no disco
+    pub fn folder_name(&self) -> &'static str {
+        match self {
+            SubDir::Pdfs => "PDFs",
+            SubDir::Resultados => "Resultados",
+            SubDir::Sicaf => "SICAF",
+            SubDir::Config => "Config",
+        }
+    }
+}
+
+/// Subpasta da pasta de PDFs que recebe os arquivos já processados com sucesso
+pub const PASTA_PROCESSADOS: &str = "Processados";
+
+/// A pasta de processados não é percorrida ao buscar PDFs pendentes
+pub fn eh_pasta_processados(nome: &std::ffi::OsStr) -> bool {
+    nome == PASTA_PROCESSADOS
+}
+
+/// Caminho livre para `nome` dentro de `pasta`, com sufixo "_1", "_2"... em caso de colisão
+pub fn caminho_sem_colisao(pasta: &Path, nome: &std::ffi::OsStr) -> PathBuf {
+    caminho_livre(pasta, nome, |stem, n, extensao| format!("{}_{}{}", stem, n, extensao))
+}
+
+/// Caminho livre para uma cópia de `nome`, no estilo do explorador: "edital (1).pdf", "edital (2).pdf"...
+pub fn caminho_para_copia(pasta: &Path, nome: &std::ffi::OsStr) -> PathBuf {
+    caminho_livre(pasta, nome, |stem, n, extensao| format!("{} ({}){}", stem, n, extensao))
+}
+
+fn caminho_livre(pasta: &Path, nome: &std::ffi::OsStr, com_sufixo: impl Fn(&str, usize, &str) -> String) -> PathBuf {
+    let candidato = pasta.join(nome);
+    if !candidato.exists() {
+        return candidato;
+    }
+
+    let nome = Path::new(nome);
+    let stem = nome.file_stem().unwrap_or_default().to_string_lossy();
+    let extensao = nome.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    (1..)
+        .map(|n| pasta.join(com_sufixo(&stem, n, &extensao)))
+        .find(|p| !p.exists())
+        .unwrap_or(candidato)
+}
+
+/// Tamanho máximo (extensão e hash incluídos) dos nomes gerados por `sanitize_filename`
+pub const LIMITE_NOME_ARQUIVO: usize = 150;
+
+/// Nomes de dispositivo que o Windows não aceita como nome de arquivo, com qualquer extensão
+const NOMES_RESERVADOS_WINDOWS: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Letra sem acento para as letras acentuadas do português (e vizinhas)
+pub(crate) fn sem_acento(c: char) -> Option<char> {
+    let base = match c {
+        'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+        'Á' | 'À' | 'Â' | 'Ã' | 'Ä' => 'A',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'É' | 'È' | 'Ê' | 'Ë' => 'E',
+        'í' | 'ì' | 'î' | 'ï' => 'i',
+        'Í' | 'Ì' | 'Î' | 'Ï' => 'I',
+        'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+        'Ó' | 'Ò' | 'Ô' | 'Õ' | 'Ö' => 'O',
+        'ú' | 'ù' | 'û' | 'ü' => 'u',
+        'Ú' | 'Ù' | 'Û' | 'Ü' => 'U',
+        'ç' => 'c',
+        'Ç' => 'C',
+        'ñ' => 'n',
+        'Ñ' => 'N',
+        _ => return None,
+    };
+    Some(base)
+}
+
+/// Nome de arquivo válido em Windows, macOS e Linux a partir de um texto qualquer
+///
+/// Mantém letras e dígitos ASCII, `-`, `.` e `_`; barras e espaços viram `_`, como nos nomes
+/// gerados até aqui. Qualquer outra alteração (caractere proibido, acento, nome reservado,
+/// truncamento em `LIMITE_NOME_ARQUIVO`) acrescenta um hash curto do texto original, para que
+/// textos distintos não resultem no mesmo nome.
+pub fn sanitize_filename(nome: &str) -> String {
+    // A extensão é preservada à parte para não ser cortada pelo limite
+    let (stem, extensao) = match nome.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && !ext.is_empty() && ext.len() <= 10
+            && ext.chars().all(|c| c.is_ascii_alphanumeric()) => (stem, format!(".{}", ext)),
+        _ => (nome, String::new()),
+    };
+
+    let mut alterado = false;
+    let mut limpo = String::with_capacity(stem.len());
+    for c in stem.chars() {
+        match c {
+            c if c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_') => limpo.push(c),
+            '/' | '\\' | ' ' => limpo.push('_'),
+            // Acentos combinados (ex.: "a" + U+0301) somem; a letra base fica
+            '\u{0300}'..='\u{036f}' => alterado = true,
+            c => {
+                alterado = true;
+                limpo.push(sem_acento(c).unwrap_or('_'));
+            }
+        }
+    }
+
+    // Windows não aceita nomes terminados em ponto nem nomes de dispositivo
+    let sem_pontos_finais = limpo.trim_end_matches('.');
+    if sem_pontos_finais.len() != limpo.len() {
+        alterado = true;
+        limpo = sem_pontos_finais.to_string();
+    }
+    if limpo.is_empty() {
+        alterado = true;
+        limpo.push_str("arquivo");
+    }
+    let base = limpo.split('.').next().unwrap_or_default();
+    if NOMES_RESERVADOS_WINDOWS.iter().any(|reservado| base.eq_ignore_ascii_case(reservado)) {
+        alterado = true;
+        limpo.insert(0, '_');
+    }
+
+    let hash = format!("_{}", &integridade::sha256_bytes(nome.as_bytes())[..8]);
+    if limpo.len() + extensao.len() > LIMITE_NOME_ARQUIVO {
+        alterado = true;
+        limpo.truncate(LIMITE_NOME_ARQUIVO.saturating_sub(extensao.len() + hash.len()));
+    }
+    if alterado {
+        limpo.push_str(&hash);
+    }
+    format!("{}{}", limpo, extensao)
+}
+
+/// Compara a extensão sem diferenciar maiúsculas (".pdf", ".PDF", ".Pdf")
+fn tem_extensao(path: &Path, extensao: &str) -> bool {
+    path.extension().is_some_and(|ext| ext.to_string_lossy().eq_ignore_ascii_case(extensao))
+}
+
+/// Verifica se o caminho é de um PDF, inclusive ".PDF" gerado por scanners
+pub fn is_pdf_file(path: &Path) -> bool {
+    tem_extensao(path, "pdf")
+}
+
+/// Formato de um arquivo de ata aceito na pasta PDFs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TipoArquivoAta {
+    Pdf,
+    /// Ata baixada do Comprasnet em HTML (.html ou .htm)
+    Html,
+    Texto,
+}
+
+impl TipoArquivoAta {
+    /// Identifica o formato pela extensão, em qualquer caixa
+    pub fn do_caminho(path: &Path) -> Option<Self> {
+        if is_pdf_file(path) {
+            Some(Self::Pdf)
+        } else if tem_extensao(path, "html") || tem_extensao(path, "htm") {
+            Some(Self::Html)
+        } else if tem_extensao(path, "txt") {
+            Some(Self::Texto)
+        } else {
+            None
+        }
+    }
+
+    /// Nome usado nas contagens por tipo ("pdf", "html", "txt")
+    pub fn rotulo(self) -> &'static str {
+        match self {
+            Self::Pdf => "pdf",
+            Self::Html => "html",
+            Self::Texto => "txt",
+        }
+    }
+}
+
+/// Verifica se o caminho é de uma ata processável: PDF, HTML ou TXT
+pub fn is_ata_file(path: &Path) -> bool {
+    TipoArquivoAta::do_caminho(path).is_some()
+}
+
+/// Verifica se o caminho é de um JSON, em qualquer caixa
+pub fn is_json_file(path: &Path) -> bool {
+    tem_extensao(path, "json")
+}
+
+/// Verifica se a extensão está entre as permitidas (com ou sem ponto, em qualquer caixa)
+pub fn extensao_permitida(path: &Path, permitidas: &[String]) -> bool {
+    permitidas.iter().any(|ext| tem_extensao(path, ext.trim().trim_start_matches('.')))
+}
+
+/// Pastas que os comandos de arquivo podem acessar: a Database, os destinos das regras de
+/// roteamento e as pastas liberadas em `allowed_external_dirs`
+pub fn pastas_permitidas(config: &AppConfig) -> Result<Vec<PathBuf>, TauriError> {
+    let mut pastas = vec![resolve_database_root()?];
+    pastas.extend(routing::diretorios_roteados(&config.routing_rules));
+    pastas.extend(config.allowed_external_dirs.iter().map(PathBuf::from));
+    Ok(pastas)
+}
+
+/// Caminho canônico de um arquivo vindo do frontend, se estiver numa das pastas permitidas
+///
+/// Fora delas retorna `PermissionDenied`: o webview pode ser induzido a pedir qualquer caminho.
+pub fn ensure_within_allowed(path: &Path, config: &AppConfig) -> Result<PathBuf, TauriError> {
+    ensure_within(path, &pastas_permitidas(config)?)
+}
+
+/// Caminho canônico de `path`, se ele existir dentro de uma das pastas
+///
+/// Links simbólicos e `..` são resolvidos antes da comparação.
+pub fn ensure_within(path: &Path, pastas: &[PathBuf]) -> Result<PathBuf, TauriError> {
+    let caminho = path.to_string_lossy().to_string();
+    let canonico = path.canonicalize()
+        .map(|p| sem_prefixo_verbatim(&p))
+        .map_err(|_| TauriError::filesystem(format!("Arquivo não encontrado: {}", caminho), caminho.clone()))?;
+
+    let permitido = pastas.iter()
+        .filter_map(|pasta| pasta.canonicalize().ok())
+        .any(|pasta| canonico.starts_with(sem_prefixo_verbatim(&pasta)));
+    if !permitido {
+        return Err(TauriError::permission_denied(
+            "Acesso negado: o arquivo está fora da pasta Database e das pastas liberadas",
+            caminho,
+        ));
+    }
+
+    Ok(canonico)
+}
+
+/// Remove os prefixos `\\?\` e `\\?\UNC\` que `canonicalize` acrescenta no Windows
+///
+/// Sem isso, `C:\Database` configurado e `\\?\C:\Database\a.json` canonicalizado não se comparam.
+pub fn sem_prefixo_verbatim(path: &Path) -> PathBuf {
+    let texto = path.to_string_lossy();
+    if let Some(resto) = texto.strip_prefix(r"\\?\UNC\") {
+        PathBuf::from(format!(r"\\{}", resto))
+    } else if let Some(resto) = texto.strip_prefix(r"\\?\") {
+        PathBuf::from(resto)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Variável de ambiente que define a pasta de dados
+pub const ENV_DATA_DIR: &str = "LICITACAO360_DATA_DIR";
+
+/// Pasta do aplicativo dentro do diretório de dados do usuário
+pub const APP_DATA_DIR: &str = "Licitacao360";
+
+/// Fontes usadas para resolver a pasta Database
+pub trait RootProvider {
+    fn executable_path(&self) -> std::io::Result<PathBuf>;
+
+    /// Valor de `LICITACAO360_DATA_DIR`, se definido
+    fn env_data_dir(&self) -> Option<PathBuf>;
+
+    /// Diretório de dados do usuário (AppData, ~/Library/Application Support, ~/.local/share)
+    fn user_data_dir(&self) -> Option<PathBuf>;
+}
+
+/// Provider padrão baseado em `std::env::current_exe`, no ambiente e em `dirs::data_dir`
+pub struct CurrentExe;
+
+impl RootProvider for CurrentExe {
+    fn executable_path(&self) -> std::io::Result<PathBuf> {
+        std::env::current_exe()
+    }
+
+    fn env_data_dir(&self) -> Option<PathBuf> {
+        std::env::var_os(ENV_DATA_DIR)
+            .filter(|valor| !valor.is_empty())
+            .map(PathBuf::from)
+    }
+
+    fn user_data_dir(&self) -> Option<PathBuf> {
+        dirs::data_dir()
+    }
+}
+
+/// Pasta Database ao lado do executável (comportamento das versões anteriores)
+fn pasta_do_executavel(provider: &dyn RootProvider) -> Result<PathBuf, TauriError> {
+    let current_exe = provider.executable_path()
+        .map_err(|e| TauriError::filesystem(format!("Erro ao obter diretório do executável: {}", e), None))?;
+
+    let exe_dir = current_exe.parent()
+        .ok_or_else(|| TauriError::filesystem("Não foi possível obter o diretório pai do executável", None))?;
+
+    Ok(exe_dir.join(DATABASE_DIR))
+}
+
+/// Pasta de dados sem considerar a configuração, nesta ordem: `LICITACAO360_DATA_DIR`, uma pasta
+/// Database já existente ao lado do executável, `dirs::data_dir()/Licitacao360` e, sem diretório
+/// do usuário, a pasta ao lado do executável
+///
+/// A pasta legada vem antes do diretório do usuário para que instalações anteriores continuem
+/// encontrando seus dados.
+pub fn default_database_root_with(provider: &dyn RootProvider) -> Result<PathBuf, TauriError> {
+    if let Some(dir) = provider.env_data_dir() {
+        return Ok(dir);
+    }
+
+    let legado = pasta_do_executavel(provider);
+    if let Ok(dir) = &legado {
+        if dir.is_dir() {
+            return legado;
+        }
+    }
+
+    match provider.user_data_dir() {
+        Some(dir) => Ok(dir.join(APP_DATA_DIR)),
+        None => legado,
+    }
+}
+
+/// Pasta de dados definida em `database_root` na configuração da pasta padrão
+fn database_root_configurado(padrao: &Path) -> Option<PathBuf> {
+    let content = std::fs::read_to_string(
+        padrao.join(SubDir::Config.folder_name()).join(config::CONFIG_FILE_NAME)
+    ).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let root = PathBuf::from(json.get("database_root")?.as_str()?.trim());
+
+    root.is_absolute().then_some(root)
+}
+
+/// Resolve a pasta Database: o `database_root` da configuração guardada na pasta padrão ou,
+/// sem ele, a própria pasta padrão (ver `default_database_root_with`)
+pub fn resolve_database_root() -> Result<PathBuf, TauriError> {
+    resolve_database_root_with(&CurrentExe)
+}
+
+/// Resolve a pasta Database a partir de um provider específico (sem criá-la)
+///
+/// O `database_root` é lido da configuração guardada na pasta padrão, que funciona
+/// como ponteiro para a pasta escolhida.
+pub fn resolve_database_root_with(provider: &dyn RootProvider) -> Result<PathBuf, TauriError> {
+    let padrao = default_database_root_with(provider)?;
+    Ok(database_root_configurado(&padrao).unwrap_or(padrao))
+}
+
+/// Obtém a pasta Database (sem criá-la)
+pub fn database_dir() -> Result<PathBuf, TauriError> {
+    resolve_database_root()
+}
+
+/// Copia a estrutura de `origem` para `destino` sem sobrescrever arquivos já existentes
+///
+/// Retorna quantos arquivos foram copiados.
+pub fn copiar_estrutura(origem: &Path, destino: &Path) -> Result<usize, TauriError> {
+    let mut copiados = 0;
+
+    for entry in walkdir::WalkDir::new(origem).into_iter().filter_map(|e| e.ok()) {
+        let Ok(relativo) = entry.path().strip_prefix(origem) else {
+            continue;
+        };
+        let alvo = destino.join(relativo);
+
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&alvo)
+                .map_err(|e| TauriError::filesystem(
+                    format!("Erro ao criar pasta {}: {}", alvo.display(), e),
+                    alvo.to_string_lossy().to_string(),
+                ))?;
+        } else if entry.file_type().is_file() && !alvo.exists() {
+            std::fs::copy(entry.path(), &alvo)
+                .map_err(|e| TauriError::filesystem(
+                    format!("Erro ao copiar {}: {}", entry.path().display(), e),
+                    entry.path().to_string_lossy().to_string(),
+                ))?;
+            copiados += 1;
+        }
+    }
+
+    Ok(copiados)
+}
+
+/// Obtém uma subpasta da estrutura Database, criando-a se não existir
+pub fn database_subdir(subdir: SubDir) -> Result<PathBuf, TauriError> {
+    database_subdir_with(&CurrentExe, subdir)
+}
+
+/// Obtém uma subpasta da estrutura Database a partir de um provider específico
+pub fn database_subdir_with(provider: &dyn RootProvider, subdir: SubDir) -> Result<PathBuf, TauriError> {
+    let dir = resolve_database_root_with(provider)?.join(subdir.folder_name());
+
+    // Criar a pasta se não existir
+    if !dir.exists() {
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| TauriError::filesystem(
+                format!("Erro ao criar pasta {}/{}: {}", DATABASE_DIR, subdir.folder_name(), e),
+                dir.to_string_lossy().to_string(),
+            ))?;
+    }
+
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ErrorKind;
+
+    struct FixedExe(PathBuf);
+
+    impl RootProvider for FixedExe {
+        fn executable_path(&self) -> std::io::Result<PathBuf> {
+            Ok(self.0.clone())
+        }
+
+        fn env_data_dir(&self) -> Option<PathBuf> {
+            None
+        }
+
+        fn user_data_dir(&self) -> Option<PathBuf> {
+            None
+        }
+    }
+
+    struct FailingExe;
+
+    impl RootProvider for FailingExe {
+        fn executable_path(&self) -> std::io::Result<PathBuf> {
+            Err(std::io::Error::new(std::io::ErrorKind::NotFound, "sem executável"))
+        }
+
+        fn env_data_dir(&self) -> Option<PathBuf> {
+            None
+        }
+
+        fn user_data_dir(&self) -> Option<PathBuf> {
+            None
+        }
+    }
+
+    struct Fontes {
+        exe: PathBuf,
+        env: Option<PathBuf>,
+        usuario: Option<PathBuf>,
+    }
+
+    impl RootProvider for Fontes {
+        fn executable_path(&self) -> std::io::Result<PathBuf> {
+            Ok(self.exe.clone())
+        }
+
+        fn env_data_dir(&self) -> Option<PathBuf> {
+            self.env.clone()
+        }
+
+        fn user_data_dir(&self) -> Option<PathBuf> {
+            self.usuario.clone()
+        }
+    }
+
+    fn temp_root(nome: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("licitacao360_paths_{}_{}", nome, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    #[test]
+    fn test_extensoes_sem_diferenciar_caixa() {
+        for nome in ["ata.pdf", "ata.PDF", "ata.Pdf", "Scan 001.pDf", "dir.com.ponto/ata.PDF"] {
+            assert!(is_pdf_file(Path::new(nome)), "{}", nome);
+        }
+        for nome in ["ata.pdf.txt", "ata", "pdf", ".pdf", "ata.pdfx", "ata.json"] {
+            assert!(!is_pdf_file(Path::new(nome)), "{}", nome);
+        }
+
+        assert_eq!(TipoArquivoAta::do_caminho(Path::new("ata.HTM")), Some(TipoArquivoAta::Html));
+        assert_eq!(TipoArquivoAta::do_caminho(Path::new("ata.Txt")), Some(TipoArquivoAta::Texto));
+        assert_eq!(TipoArquivoAta::do_caminho(Path::new("ata.PDF")), Some(TipoArquivoAta::Pdf));
+        assert!(!is_ata_file(Path::new("ata.docx")));
+        assert!(is_json_file(Path::new("licitacao_1.JSON")));
+        assert!(is_json_file(Path::new("licitacao_1.json")));
+        assert!(!is_json_file(Path::new("licitacao_1.jsonl")));
+        assert!(!is_json_file(Path::new("licitacao_1.PDF")));
+    }
+
+    #[test]
+    fn test_database_subdir_paths_inalterados() {
+        let root = temp_root("inalterados");
+        let provider = FixedExe(root.join("licitacao360.exe"));
+
+        for subdir in SubDir::ALL {
+            let dir = database_subdir_with(&provider, subdir).unwrap();
+            assert_eq!(dir, root.join("Database").join(subdir.folder_name()));
+            assert!(dir.is_dir());
+        }
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_database_subdir_sem_diretorio_pai() {
+        let erro = database_subdir_with(&FixedExe(PathBuf::from("/")), SubDir::Pdfs).unwrap_err();
+        assert_eq!(erro.error_type, ErrorKind::FileSystemError);
+        assert_eq!(erro.message, "Não foi possível obter o diretório pai do executável");
+    }
+
+    #[test]
+    fn test_database_subdir_erro_do_provider() {
+        let erro = database_subdir_with(&FailingExe, SubDir::Config).unwrap_err();
+        assert_eq!(erro.error_type, ErrorKind::FileSystemError);
+        assert!(erro.message.starts_with("Erro ao obter diretório do executável"));
+    }
+
+    #[test]
+    fn test_database_subdir_falha_ao_criar() {
+        let root = temp_root("falha_criar");
+        // Um arquivo comum no lugar do diretório do executável impede a criação
+        let bloqueio = root.join("bloqueio");
+        std::fs::write(&bloqueio, b"").unwrap();
+
+        let erro = database_subdir_with(&FixedExe(bloqueio.join("app.exe")), SubDir::Resultados).unwrap_err();
+        assert_eq!(erro.error_type, ErrorKind::FileSystemError);
+        assert!(erro.message.starts_with("Erro ao criar pasta Database/Resultados"));
+        assert_eq!(erro.details, Some(bloqueio.join("Database").join("Resultados").to_string_lossy().to_string()));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_ordem_de_resolucao_da_pasta_de_dados() {
+        let root = temp_root("resolucao");
+        let mut fontes = Fontes {
+            exe: root.join("Program Files").join("licitacao360.exe"),
+            env: None,
+            usuario: Some(root.join("AppData")),
+        };
+
+        // Sem pasta ao lado do executável, o diretório do usuário vem antes
+        assert_eq!(resolve_database_root_with(&fontes).unwrap(), root.join("AppData").join(APP_DATA_DIR));
+
+        // Instalação anterior com Database ao lado do executável continua sendo usada
+        let legado = root.join("Program Files").join(DATABASE_DIR);
+        std::fs::create_dir_all(&legado).unwrap();
+        assert_eq!(resolve_database_root_with(&fontes).unwrap(), legado);
+
+        fontes.env = Some(root.join("env"));
+        assert_eq!(resolve_database_root_with(&fontes).unwrap(), root.join("env"));
+
+        // A configuração da pasta padrão aponta para a pasta escolhida
+        let config_dir = root.join("env").join(SubDir::Config.folder_name());
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let escolhida = root.join("Dados");
+        std::fs::write(
+            config_dir.join(config::CONFIG_FILE_NAME),
+            serde_json::json!({ "database_root": escolhida }).to_string(),
+        ).unwrap();
+        assert_eq!(resolve_database_root_with(&fontes).unwrap(), escolhida);
+        assert_eq!(database_subdir_with(&fontes, SubDir::Sicaf).unwrap(), escolhida.join("SICAF"));
+
+        // Caminho relativo na configuração é ignorado
+        std::fs::write(config_dir.join(config::CONFIG_FILE_NAME), r#"{"database_root": "Dados"}"#).unwrap();
+        assert_eq!(resolve_database_root_with(&fontes).unwrap(), root.join("env"));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_precedencia_da_pasta_padrao() {
+        let root = temp_root("precedencia");
+        let exe = root.join("bin").join("licitacao360.exe");
+        let legado = root.join("bin").join(DATABASE_DIR);
+        let usuario = root.join("AppData").join(APP_DATA_DIR);
+        let com = |env: bool, usuario: bool| Fontes {
+            exe: exe.clone(),
+            env: env.then(|| root.join("env")),
+            usuario: usuario.then(|| root.join("AppData")),
+        };
+
+        // Sem pasta legada: ambiente > diretório do usuário > ao lado do executável
+        assert_eq!(default_database_root_with(&com(true, true)).unwrap(), root.join("env"));
+        assert_eq!(default_database_root_with(&com(false, true)).unwrap(), usuario);
+        assert_eq!(default_database_root_with(&com(false, false)).unwrap(), legado);
+
+        // A pasta legada existente passa à frente do diretório do usuário, mas não do ambiente
+        std::fs::create_dir_all(&legado).unwrap();
+        assert_eq!(default_database_root_with(&com(true, true)).unwrap(), root.join("env"));
+        assert_eq!(default_database_root_with(&com(false, true)).unwrap(), legado);
+
+        // O `database_root` da configuração só vale para a resolução final
+        let config_dir = legado.join(SubDir::Config.folder_name());
+        std::fs::create_dir_all(&config_dir).unwrap();
+        std::fs::write(
+            config_dir.join(config::CONFIG_FILE_NAME),
+            serde_json::json!({ "database_root": root.join("Dados") }).to_string(),
+        ).unwrap();
+        assert_eq!(default_database_root_with(&com(false, true)).unwrap(), legado);
+        assert_eq!(resolve_database_root_with(&com(false, true)).unwrap(), root.join("Dados"));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_arquivo_para_abrir_dentro_da_pasta_e_com_extensao_permitida() {
+        let root = temp_root("abrir");
+        let database = root.join("Database");
+        std::fs::create_dir_all(database.join("Resultados")).unwrap();
+        let relatorio = database.join("Resultados").join("ata.HTML");
+        std::fs::write(&relatorio, "<html></html>").unwrap();
+        std::fs::write(root.join("fora.html"), "").unwrap();
+        let pastas = vec![database.clone()];
+
+        assert!(ensure_within(&relatorio, &pastas).is_ok());
+        // ".." não escapa da pasta, e arquivos inexistentes são recusados
+        assert!(ensure_within(&database.join("Resultados").join("..").join("..").join("fora.html"), &pastas).is_err());
+        assert!(ensure_within(&database.join("nao_existe.html"), &pastas).is_err());
+
+        let permitidas = vec![".html".to_string(), "json".to_string()];
+        assert!(extensao_permitida(&relatorio, &permitidas));
+        assert!(!extensao_permitida(Path::new("script.bat"), &permitidas));
+        assert!(!extensao_permitida(Path::new("sem_extensao"), &permitidas));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_ensure_within_recusa_caminhos_fora_das_pastas() {
+        let root = temp_root("permitidas");
+        let database = root.join("Database");
+        let externa = root.join("Externa");
+        std::fs::create_dir_all(database.join("Resultados")).unwrap();
+        std::fs::create_dir_all(&externa).unwrap();
+        let resultado = database.join("Resultados").join("licitacao_a.json");
+        std::fs::write(&resultado, "{}").unwrap();
+        std::fs::write(externa.join("b.json"), "{}").unwrap();
+        std::fs::write(root.join("segredo.json"), "{}").unwrap();
+        let pastas = vec![database.clone()];
+
+        assert_eq!(ensure_within(&resultado, &pastas).unwrap(), resultado.canonicalize().unwrap());
+        let escapando = database.join("Resultados").join("..").join("..").join("segredo.json");
+        assert_eq!(ensure_within(&escapando, &pastas).unwrap_err().error_type, ErrorKind::PermissionDenied);
+        assert_eq!(ensure_within(&database.join("nao_existe.json"), &pastas).unwrap_err().error_type, ErrorKind::FileSystemError);
+
+        // Pasta externa só vale quando liberada
+        assert_eq!(ensure_within(&externa.join("b.json"), &pastas).unwrap_err().error_type, ErrorKind::PermissionDenied);
+        assert!(ensure_within(&externa.join("b.json"), &[database.clone(), externa.clone()]).is_ok());
+
+        // Um link dentro da Database não dá acesso ao que está fora dela
+        #[cfg(unix)]
+        {
+            let link = database.join("Resultados").join("atalho.json");
+            std::os::unix::fs::symlink(root.join("segredo.json"), &link).unwrap();
+            assert_eq!(ensure_within(&link, &pastas).unwrap_err().error_type, ErrorKind::PermissionDenied);
+
+            let pasta_link = database.join("Externa");
+            std::os::unix::fs::symlink(&externa, &pasta_link).unwrap();
+            assert_eq!(ensure_within(&pasta_link.join("b.json"), &pastas).unwrap_err().error_type, ErrorKind::PermissionDenied);
+        }
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_sem_prefixo_verbatim() {
+        assert_eq!(sem_prefixo_verbatim(Path::new(r"\\?\C:\Database\a.json")), PathBuf::from(r"C:\Database\a.json"));
+        assert_eq!(
+            sem_prefixo_verbatim(Path::new(r"\\?\UNC\servidor\compartilhado\a.json")),
+            PathBuf::from(r"\\servidor\compartilhado\a.json"),
+        );
+        assert_eq!(sem_prefixo_verbatim(Path::new("/tmp/Database/a.json")), PathBuf::from("/tmp/Database/a.json"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_ensure_within_caminhos_unc_no_windows() {
+        let root = temp_root("unc");
+        let database = root.join("Database");
+        std::fs::create_dir_all(&database).unwrap();
+        let arquivo = database.join("a.json");
+        std::fs::write(&arquivo, "{}").unwrap();
+        let pastas = vec![database.clone()];
+
+        // A forma verbatim do mesmo caminho continua dentro da Database
+        let verbatim = PathBuf::from(format!(r"\\?\{}", arquivo.display()));
+        assert!(ensure_within(&verbatim, &pastas).is_ok());
+
+        // Compartilhamento de rede nunca é tratado como a Database local
+        let texto = arquivo.to_string_lossy();
+        let (unidade, resto) = texto.split_once(':').unwrap();
+        let unc = PathBuf::from(format!(r"\\localhost\{}$\{}", unidade, resto.trim_start_matches('\\')));
+        assert!(ensure_within(&unc, &pastas).is_err());
+        assert!(ensure_within(Path::new(r"\\servidor-inexistente\compartilhado\a.json"), &pastas).is_err());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_copiar_estrutura_sem_sobrescrever() {
+        let root = temp_root("copiar");
+        let origem = root.join("origem");
+        std::fs::create_dir_all(origem.join("Resultados").join("Lixeira")).unwrap();
+        std::fs::write(origem.join("Resultados").join("licitacao_1.json"), "{}").unwrap();
+        std::fs::write(origem.join("README.txt"), "antigo").unwrap();
+
+        let destino = root.join("destino");
+        std::fs::create_dir_all(&destino).unwrap();
+        std::fs::write(destino.join("README.txt"), "novo").unwrap();
+
+        assert_eq!(copiar_estrutura(&origem, &destino).unwrap(), 1);
+        assert!(destino.join("Resultados").join("Lixeira").is_dir());
+        assert!(destino.join("Resultados").join("licitacao_1.json").is_file());
+        assert_eq!(std::fs::read_to_string(destino.join("README.txt")).unwrap(), "novo");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_nomes_sem_colisao() {
+        let root = temp_root("colisao");
+        std::fs::create_dir_all(&root).unwrap();
+        let nome = std::ffi::OsStr::new("edital.pdf");
+        assert_eq!(caminho_para_copia(&root, nome), root.join("edital.pdf"));
+
+        std::fs::write(root.join("edital.pdf"), "a").unwrap();
+        assert_eq!(caminho_para_copia(&root, nome), root.join("edital (1).pdf"));
+        assert_eq!(caminho_sem_colisao(&root, nome), root.join("edital_1.pdf"));
+
+        std::fs::write(root.join("edital (1).pdf"), "b").unwrap();
+        assert_eq!(caminho_para_copia(&root, nome), root.join("edital (2).pdf"));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        // Barras e espaços seguem o padrão antigo, sem hash
+        assert_eq!(sanitize_filename("licitacao_787000-90008/2024-123.json"), "licitacao_787000-90008_2024-123.json");
+        assert_eq!(sanitize_filename("licitacao_N/A-N/A-N/A.json"), "licitacao_N_A-N_A-N_A.json");
+
+        // Caracteres proibidos no Windows levam hash, e chaves diferentes não colidem
+        let dois_pontos = sanitize_filename("licitacao_787000-90008:2024-123.json");
+        let asterisco = sanitize_filename("licitacao_787000-90008*2024-123.json");
+        assert!(dois_pontos.starts_with("licitacao_787000-90008_2024-123_") && dois_pontos.ends_with(".json"));
+        assert_ne!(dois_pontos, asterisco);
+        assert!(!dois_pontos.contains(':'));
+
+        // Acentos (compostos ou combinados) viram ASCII
+        assert!(sanitize_filename("Pregão").starts_with("Pregao_"));
+        assert!(sanitize_filename("Prega\u{0303}o").starts_with("Pregao_"));
+        assert!(sanitize_filename("CON.json").starts_with("_CON_"));
+        assert!(sanitize_filename("ata.").starts_with("ata_"));
+
+        // Nomes longos respeitam o limite e continuam distintos
+        let longo_a = sanitize_filename(&format!("licitacao_{}a.json", "9".repeat(300)));
+        let longo_b = sanitize_filename(&format!("licitacao_{}b.json", "9".repeat(300)));
+        assert_eq!(longo_a.len(), LIMITE_NOME_ARQUIVO);
+        assert!(longo_a.ends_with(".json"));
+        assert_ne!(longo_a, longo_b);
+    }
+}
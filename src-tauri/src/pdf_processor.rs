@@ -1,64 +1,487 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use chrono::Utc;
+use once_cell::sync::Lazy;
 use regex::Regex;
 use std::fs;
-use std::path::Path;
-use walkdir::WalkDir;
+use std::path::{Path, PathBuf};
+use walkdir::{DirEntry, WalkDir};
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use pdf_extract::extract_text;
-use std::collections::{HashSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashSet, HashMap};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::sync::atomic::{AtomicBool, Ordering};
+use rayon::prelude::*;
+use tracing::{debug, info, warn};
 use crate::types::*;
+use crate::routing;
+use crate::dica_arquivo;
+use crate::integridade;
+use crate::metadados_pdf;
+use crate::paths;
+use crate::validators;
+use crate::ocr;
+use crate::report;
+use crate::resultados;
+use crate::storage;
+use crate::texto;
+use crate::migracoes;
+use crate::extratores::{self, ExtratorPropostas};
 
-/// Processa um arquivo PDF específico e retorna as propostas consolidadas
-pub fn processar_pdf_com_consolidacao(pdf_path: &Path, output_dir: &Path, verbose: bool) -> Result<Vec<PropostaConsolidada>> {
-    if verbose {
-        println!("📄 Processando: {}", pdf_path.display());
+/// Função que extrai o texto de um PDF
+pub type ExtratorTexto = fn(&Path) -> Result<String>;
+
+/// Extrator padrão, baseado no pdf_extract
+pub fn extrair_texto_pdf(pdf_path: &Path) -> Result<String> {
+    Ok(extract_text(pdf_path)?)
+}
+
+/// Extrai o texto de uma ata em PDF, HTML ou TXT, conforme a extensão
+///
+/// HTML perde as tags e tem as entidades decodificadas; TXT é lido como está. Arquivos
+/// que não estão em UTF-8 são lidos como Latin-1.
+pub fn extrair_texto_arquivo(path: &Path) -> Result<String> {
+    let Some(tipo) = paths::TipoArquivoAta::do_caminho(path) else {
+        bail!("Formato de arquivo não suportado: {}", path.display());
+    };
+    if tipo == paths::TipoArquivoAta::Pdf {
+        return extrair_texto_pdf(path);
+    }
+
+    let bytes = fs::read(path).context(format!("Erro ao ler {}", path.display()))?;
+    let conteudo = texto::decodificar_bytes(&bytes);
+    Ok(match tipo {
+        paths::TipoArquivoAta::Html => texto::html_para_texto(&conteudo),
+        _ => conteudo,
+    })
+}
+
+/// Limite do texto devolvido por `previa_texto`, para não travar o IPC com atas enormes
+pub const LIMITE_PREVIA_BYTES: usize = 50 * 1024;
+
+/// Rótulos contados na prévia: sem eles as regex de extração não encontram nada
+pub const ROTULOS_DIAGNOSTICO: [&str; 3] = ["Adjudicado", "CNPJ", "UASG"];
+
+/// Texto bruto de uma ata (ou de uma página do PDF), cortado em `max_chars` e em `LIMITE_PREVIA_BYTES`
+///
+/// As ocorrências dos rótulos são contadas no texto normalizado, que é o que as regex recebem.
+pub fn previa_texto(path: &Path, max_chars: usize, pagina: Option<usize>, timeout: Option<Duration>) -> Result<PreviaTextoPdf> {
+    let (bruto, total_paginas) = match pagina {
+        None if paths::is_pdf_file(path) => (extrair_texto_com_timeout(path, timeout, extrair_texto_pdf)?, None),
+        None => (extrair_texto_arquivo(path)?, None),
+        Some(_) if !paths::is_pdf_file(path) => bail!("Só PDFs podem ser lidos por página"),
+        Some(0) => bail!("As páginas começam em 1"),
+        Some(numero) => {
+            let caminho = path.to_path_buf();
+            let mut paginas = executar_com_timeout(timeout, move || Ok(pdf_extract::extract_text_by_pages(&caminho)?))?;
+            let total = paginas.len();
+            if numero > total {
+                bail!("Página {} não existe: o PDF tem {} páginas", numero, total);
+            }
+            (paginas.swap_remove(numero - 1), Some(total))
+        }
+    };
+
+    let normalizado = texto::normalizar_texto(&bruto);
+    let ocorrencias = ROTULOS_DIAGNOSTICO.iter()
+        .map(|rotulo| (rotulo.to_string(), normalizado.matches(rotulo).count()))
+        .collect();
+
+    let mut texto = String::new();
+    for c in bruto.chars().take(max_chars) {
+        if texto.len() + c.len_utf8() > LIMITE_PREVIA_BYTES {
+            break;
+        }
+        texto.push(c);
+    }
+
+    Ok(PreviaTextoPdf {
+        arquivo: path.to_string_lossy().to_string(),
+        pagina,
+        total_paginas,
+        total_caracteres: bruto.chars().count(),
+        truncado: texto.len() < bruto.len(),
+        texto,
+        ocorrencias,
+    })
+}
+
+/// Opções do processamento lidas da configuração
+#[derive(Debug, Clone, Copy)]
+pub struct OpcoesProcessamento {
+    /// Número de PDFs processados em paralelo (0 = um por núcleo)
+    pub max_parallel_jobs: usize,
+    /// Tentar OCR externo quando o PDF parecer escaneado
+    pub ocr_habilitado: bool,
+    /// Tempo máximo para extrair o texto de cada PDF (None = sem limite)
+    pub timeout_extracao: Option<Duration>,
+    /// Extração de texto (substituída nos testes)
+    pub extrator: ExtratorTexto,
+}
+
+impl Default for OpcoesProcessamento {
+    fn default() -> Self {
+        Self {
+            max_parallel_jobs: 0,
+            ocr_habilitado: false,
+            timeout_extracao: Some(Duration::from_secs(TIMEOUT_EXTRACAO_PADRAO_SECS)),
+            extrator: extrair_texto_pdf,
+        }
+    }
+}
+
+impl From<&AppConfig> for OpcoesProcessamento {
+    fn from(config: &AppConfig) -> Self {
+        Self {
+            max_parallel_jobs: config.max_parallel_jobs,
+            ocr_habilitado: config.ocr_enabled,
+            timeout_extracao: (config.pdf_extraction_timeout_secs > 0)
+                .then(|| Duration::from_secs(config.pdf_extraction_timeout_secs)),
+            ..Default::default()
+        }
+    }
+}
+
+/// Extrai o texto em uma thread dedicada, desistindo após `timeout`
+///
+/// Um PDF corrompido pode prender o pdf_extract por minutos (ou derrubá-lo com pânico);
+/// nesses casos o arquivo falha e o lote segue. A thread presa não pode ser interrompida
+/// e é abandonada até terminar por conta própria.
+pub fn extrair_texto_com_timeout(pdf_path: &Path, timeout: Option<Duration>, extrator: ExtratorTexto) -> Result<String> {
+    let path = pdf_path.to_path_buf();
+    executar_com_timeout(timeout, move || extrator(&path))
+}
+
+/// Executa uma extração em thread dedicada, como em `extrair_texto_com_timeout`
+fn executar_com_timeout<T: Send + 'static>(
+    timeout: Option<Duration>,
+    extracao: impl FnOnce() -> Result<T> + Send + 'static,
+) -> Result<T> {
+    let Some(timeout) = timeout else {
+        return extracao();
+    };
+
+    let (tx, rx) = mpsc::channel();
+    std::thread::Builder::new()
+        .name("extracao-pdf".to_string())
+        .spawn(move || {
+            let _ = tx.send(extracao());
+        })
+        .context("Erro ao iniciar a extração de texto")?;
+
+    match rx.recv_timeout(timeout) {
+        Ok(resultado) => resultado,
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            bail!("timeout: a extração de texto excedeu {}s", timeout.as_secs_f64())
+        }
+        Err(mpsc::RecvTimeoutError::Disconnected) => {
+            bail!("A extração de texto foi interrompida por um erro interno do leitor de PDF")
+        }
+    }
+}
+
+/// Resultado do processamento de um PDF
+#[derive(Debug, Clone)]
+pub struct ResultadoPdf {
+    pub propostas: Vec<PropostaConsolidada>,
+    /// O PDF tinha pouco ou nenhum texto extraível
+    pub provavelmente_escaneado: bool,
+    /// Avisos da extração que devem aparecer no status do processamento
+    pub avisos: Vec<String>,
+    /// Tempos preenchidos por `processar_pdf_com_consolidacao`; zerados quando o texto já veio pronto
+    pub metricas: FileProcessingMetrics,
+}
+
+/// Processa um arquivo de ata (PDF, HTML ou TXT) e retorna as propostas consolidadas
+///
+/// Os eventos do arquivo ficam no span "ata", com o caminho do arquivo.
+#[tracing::instrument(name = "ata", skip_all, fields(arquivo = %pdf_path.display()))]
+pub fn processar_pdf_com_consolidacao(
+    pdf_path: &Path,
+    output_dir: &Path,
+    regras: &[RoutingRule],
+    opcoes: OpcoesProcessamento,
+) -> Result<ResultadoPdf> {
+    info!("Processando arquivo");
+    let inicio = Instant::now();
+    
+    // HTML e TXT já trazem o texto: sem timeout nem OCR, mas com as mesmas regex
+    let (text, provavelmente_escaneado, avisos_status) = if paths::is_pdf_file(pdf_path) {
+        // Extrair texto do PDF, recorrendo ao OCR se ele parecer escaneado
+        let texto = extrair_texto_com_timeout(pdf_path, opcoes.timeout_extracao, opcoes.extrator)
+            .map_err(|e| metadados_pdf::classificar_erro_extracao(pdf_path, e))?;
+        let extraido = ocr::verificar_texto(pdf_path, texto, opcoes.ocr_habilitado);
+        let avisos_status: Vec<String> = extraido.aviso.into_iter().collect();
+        for aviso in &avisos_status {
+            warn!("{}", aviso);
+        }
+        (texto::normalizar_texto(&extraido.texto), extraido.provavelmente_escaneado, avisos_status)
+    } else {
+        (texto::normalizar_texto(&extrair_texto_arquivo(pdf_path)?), false, Vec::new())
+    };
+    let extracao = inicio.elapsed();
+    
+    debug!(caracteres = text.len(), "Texto extraído");
+    
+    let inicio_analise = Instant::now();
+    let mut resultado = processar_texto_licitacao(&text, pdf_path, output_dir, regras, provavelmente_escaneado, avisos_status)?;
+    resultado.metricas.extract_ms = extracao.as_millis() as u64;
+    resultado.metricas.parse_ms = inicio_analise.elapsed().as_millis() as u64;
+    debug!(
+        extract_ms = resultado.metricas.extract_ms,
+        parse_ms = resultado.metricas.parse_ms,
+        propostas = resultado.metricas.propostas,
+        formato = %resultado.metricas.formato,
+        "Métricas do arquivo",
+    );
+    
+    Ok(resultado)
+}
+
+static RE_LINHA_UASG: Lazy<Regex> = Lazy::new(|| Regex::new(r"UASG\s*\d+").unwrap());
+
+/// Linhas acima do marcador de pregão em que a UASG do mesmo cabeçalho é procurada
+const LINHAS_CABECALHO_PREGAO: usize = 3;
+
+/// Divide o texto em uma seção por certame (pregão, dispensa ou concorrência)
+///
+/// Os marcadores são os cabeçalhos de `RE_CERTAMES` escritos em maiúsculas ("PREGÃO 90008/2024",
+/// "DISPENSA ELETRÔNICA Nº 7/2024"); menções no corpo ("conforme o Pregão nº ...") não contam.
+/// Uma seção nova começa quando aparece um certame diferente do anterior (repetições do mesmo,
+/// como em cabeçalhos de página, ficam na mesma seção). O corte é feito no início da linha do
+/// marcador ou, se a UASG estiver logo acima, no início da linha da UASG. Textos com um só
+/// certame voltam inteiros.
+pub fn segmentar_por_pregao(text: &str) -> Vec<&str> {
+    let mut marcadores: Vec<(usize, &str, String)> = RE_CERTAMES.iter()
+        .flat_map(|(modalidade, re)| re.captures_iter(text).map(move |caps| (*modalidade, caps)))
+        .filter(|(_, caps)| caps[0].chars().take(2).all(char::is_uppercase))
+        .map(|(modalidade, caps)| (caps.get(0).unwrap().start(), modalidade, caps[1].replace('.', "")))
+        .collect();
+    marcadores.sort_by_key(|(inicio, _, _)| *inicio);
+
+    let mut cortes: Vec<usize> = vec![0];
+    let mut certame_atual: Option<(&str, String)> = None;
+
+    for (marcador, modalidade, numero) in marcadores {
+        let certame = (modalidade, numero);
+        let anterior = certame_atual.replace(certame.clone());
+        if anterior.is_none() || anterior == Some(certame) {
+            continue;
+        }
+
+        let inicio_secao = *cortes.last().unwrap();
+        let mut corte = text[..marcador].rfind('\n').map_or(0, |i| i + 1).max(inicio_secao);
+
+        // A UASG do novo cabeçalho costuma vir na linha de cima
+        let mut inicio_linha = corte;
+        for _ in 0..LINHAS_CABECALHO_PREGAO {
+            if inicio_linha <= inicio_secao {
+                break;
+            }
+            inicio_linha = text[..inicio_linha - 1].rfind('\n').map_or(0, |i| i + 1).max(inicio_secao);
+            if RE_LINHA_UASG.is_match(&text[inicio_linha..corte]) {
+                corte = inicio_linha;
+                break;
+            }
+        }
+
+        if corte > inicio_secao {
+            cortes.push(corte);
+        }
+    }
+
+    cortes.iter()
+        .zip(cortes.iter().skip(1).chain(std::iter::once(&text.len())))
+        .map(|(&inicio, &fim)| &text[inicio..fim])
+        .collect()
+}
+
+/// Extrai as propostas do texto já normalizado de um PDF e grava os relatórios Markdown e HTML
+///
+/// `avisos_status` traz os avisos da extração do texto (ex.: OCR) e recebe os avisos das propostas.
+/// PDFs com vários pregões concatenados são processados por seção, cada uma com a sua
+/// UASG/pregão/processo; o Markdown traz uma seção por pregão.
+pub fn processar_texto_licitacao(
+    text: &str,
+    pdf_path: &Path,
+    output_dir: &Path,
+    regras: &[RoutingRule],
+    provavelmente_escaneado: bool,
+    mut avisos_status: Vec<String>,
+) -> Result<ResultadoPdf> {
+    let secoes = segmentar_por_pregao(text);
+    if secoes.len() > 1 {
+        debug!(pregoes = secoes.len(), "Vários pregões no mesmo arquivo");
+    }
+    
+    let avisos_extracao = avisos_status.clone();
+    let arquivo_origem = pdf_path.file_name().map(|nome| nome.to_string_lossy().to_string());
+    let mut relatorios: Vec<RelatorioLicitacao> = Vec::new();
+    // Propostas consolidadas de cada seção, na mesma ordem das propostas do relatório
+    let mut consolidadas_por_secao: Vec<Vec<PropostaConsolidada>> = Vec::new();
+    for secao in &secoes {
+        let mut relatorio = extrair_relatorio_secao(secao, text, pdf_path, provavelmente_escaneado, &avisos_extracao, &mut avisos_status);
+        // As regex capturam na ordem do texto; a tabela do markdown segue a ordem dos itens
+        ordenar_por_item(&mut relatorio.propostas, |p| (p.grupo.as_deref(), p.item.as_str()));
+        consolidadas_por_secao.push(propostas_do_relatorio(&relatorio, secao, arquivo_origem.as_deref()));
+        relatorios.push(relatorio);
     }
     
-    // Extrair texto do PDF
-    let text = extract_text(pdf_path)?;
+    gravar_relatorios(&relatorios, &consolidadas_por_secao, pdf_path, output_dir, regras)?;
+    let propostas_consolidadas: Vec<PropostaConsolidada> = consolidadas_por_secao.into_iter().flatten().collect();
     
-    if verbose {
-        println!("📝 Texto extraído: {} caracteres", text.len());
+    let mut formatos: Vec<&str> = Vec::new();
+    for proposta in relatorios.iter().flat_map(|r| &r.propostas) {
+        if !formatos.contains(&proposta.tipo_formato.as_str()) {
+            formatos.push(&proposta.tipo_formato);
+        }
     }
+    let metricas = FileProcessingMetrics {
+        file: pdf_path.to_string_lossy().to_string(),
+        bytes: fs::metadata(pdf_path).map(|m| m.len()).unwrap_or(0),
+        propostas: propostas_consolidadas.len(),
+        formato: formatos.join(", "),
+        ..Default::default()
+    };
+    
+    Ok(ResultadoPdf {
+        propostas: propostas_consolidadas,
+        provavelmente_escaneado,
+        avisos: avisos_status,
+        metricas,
+    })
+}
+
+/// Monta o relatório de uma seção (um pregão); campos ausentes na seção vêm do documento inteiro
+fn extrair_relatorio_secao(
+    secao: &str,
+    documento: &str,
+    pdf_path: &Path,
+    provavelmente_escaneado: bool,
+    avisos_extracao: &[String],
+    avisos_status: &mut Vec<String>,
+) -> RelatorioLicitacao {
+    let ou_documento = |valor: String, extrair: fn(&str) -> String| {
+        if valor == "N/A" { extrair(documento) } else { valor }
+    };
     
     // Extrair informações gerais
+    let (modalidade, pregao) = extrair_certame(secao);
     let mut relatorio = RelatorioLicitacao {
-        uasg: extrair_uasg(&text),
-        pregao: extrair_pregao(&text),
-        processo: extrair_processo(&text),
-        data_homologacao: extrair_data_homologacao(&text),
-        responsavel: extrair_responsavel(&text),
+        uasg: ou_documento(extrair_uasg(secao), extrair_uasg),
+        pregao,
+        modalidade,
+        processo: extrair_processo(secao),
+        data_homologacao: ou_documento(extrair_data_homologacao(secao), extrair_data_homologacao),
+        data_homologacao_iso: None,
+        responsavel: String::new(),
+        responsavel_nome: String::new(),
+        responsavel_cargo: None,
+        responsavel_matricula: None,
         valor_total: 0.0,
         propostas: Vec::new(),
+        origem: Default::default(),
+        avisos: Vec::new(),
+        provavelmente_escaneado,
     };
     
-    // Tentar extrair propostas no formato de grupo primeiro
-    let mut propostas_grupo = extrair_propostas_grupo(&text, verbose);
+    relatorio.data_homologacao_iso = converter_data_homologacao(&relatorio.data_homologacao);
+    let responsavel = extrair_responsavel(secao).or_else(|| extrair_responsavel(documento));
+    relatorio.responsavel = responsavel.as_ref().map_or_else(|| "N/A".to_string(), |r| r.texto());
+    if let Some(responsavel) = responsavel {
+        relatorio.responsavel_nome = responsavel.nome;
+        relatorio.responsavel_cargo = responsavel.cargo;
+        relatorio.responsavel_matricula = responsavel.matricula;
+    } else {
+        relatorio.responsavel_nome = "N/A".to_string();
+    }
+    
+    // Sem UASG/pregão no cabeçalho, tentar recuperá-los do nome do arquivo
+    let nome_pdf = pdf_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let recuperados = dica_arquivo::completar_campos(&mut relatorio.uasg, &mut relatorio.pregao, &nome_pdf);
+    for aviso in &recuperados.avisos {
+        warn!("{}", aviso);
+    }
+    relatorio.origem = recuperados.origem;
+    relatorio.avisos = recuperados.avisos;
+    relatorio.avisos.extend(avisos_extracao.iter().cloned());
     
-    // Se não encontrou propostas de grupo, tentar formato individual
-    if propostas_grupo.is_empty() {
-        let mut propostas_individuais = extrair_propostas_individuais(&text, verbose);
-        relatorio.propostas.append(&mut propostas_individuais);
-        
-        if verbose {
-            println!("📊 Formato individual detectado: {} propostas encontradas", relatorio.propostas.len());
+    // O primeiro extrator registrado que reconhece o layout extrai as propostas da seção
+    if let Some(extrator) = extratores::selecionar(secao) {
+        relatorio.propostas = extrator.extrair(secao);
+        for proposta in &mut relatorio.propostas {
+            proposta.tipo_formato = extrator.nome().to_string();
         }
-    } else {
-        relatorio.propostas.append(&mut propostas_grupo);
-        
-        if verbose {
-            println!("📊 Formato de grupo detectado: {} propostas encontradas", relatorio.propostas.len());
+        debug!(propostas = relatorio.propostas.len(), formato = extrator.nome(), "Layout detectado");
+    }
+    
+    // Itens sem vencedor não aparecem nos padrões de adjudicação
+    let itens_adjudicados: HashSet<(Option<String>, String)> = relatorio.propostas.iter()
+        .map(|p| (p.grupo.clone(), p.item.clone()))
+        .collect();
+    let nao_adjudicados: Vec<PropostaAdjudicada> = extrair_itens_nao_adjudicados(secao)
+        .into_iter()
+        .filter(|p| !itens_adjudicados.contains(&(p.grupo.clone(), p.item.clone())))
+        .collect();
+    if !nao_adjudicados.is_empty() {
+        debug!(itens = nao_adjudicados.len(), "Itens não adjudicados (desertos, fracassados ou cancelados)");
+    }
+    relatorio.propostas.extend(nao_adjudicados);
+    
+    for proposta in &mut relatorio.propostas {
+        proposta.observacoes = extrair_observacoes_do_item(secao, proposta.grupo.as_deref(), &proposta.item);
+        if proposta.situacao == SITUACAO_ADJUDICADO {
+            proposta.porte = extrair_porte_do_contexto(secao, proposta.grupo.as_deref(), &proposta.item, &proposta.cnpj);
         }
     }
     
-    // Calcular valor total
+    // CNPJs truncados ou com dígitos trocados na extração
+    let avisos_cnpj: Vec<String> = relatorio.propostas.iter()
+        .filter(|p| !p.cnpj_valido)
+        .map(|p| format!("CNPJ inválido no item {}: {}", p.item, p.cnpj))
+        .collect();
+    for aviso in &avisos_cnpj {
+        warn!("{}", aviso);
+    }
+    relatorio.avisos.extend(avisos_cnpj.iter().cloned());
+    avisos_status.extend(avisos_cnpj);
+    
+    // Calcular valor total pelos totais dos itens; valores ilegíveis não somam, mas ficam registrados
+    let unitario = layout_valor_unitario(secao);
+    let mut avisos_valor = Vec::new();
     relatorio.valor_total = relatorio.propostas.iter()
-        .map(|p| converter_valor_para_float(&p.valor_adjudicado))
+        .filter(|p| p.situacao == SITUACAO_ADJUDICADO)
+        .map(|p| match (valores_do_item(p, unitario).total, converter_valor_para_float(&p.valor_adjudicado_raw)) {
+            (Some(valor), _) => valor.0,
+            (None, Err(e)) => {
+                avisos_valor.push(format!("Item {}: {}", p.item, e));
+                0.0
+            }
+            (None, Ok(_)) => 0.0,
+        })
         .sum();
-    
-    if verbose {
-        println!("💰 Valor total calculado: R$ {:.2}", relatorio.valor_total);
+    for aviso in &avisos_valor {
+        warn!("{}", aviso);
     }
+    relatorio.avisos.extend(avisos_valor.iter().cloned());
+    avisos_status.extend(avisos_valor);
+    
+    debug!(valor_total = relatorio.valor_total, "Valor total calculado");
+    
+    relatorio
+}
+
+/// Grava o Markdown (uma seção por pregão) e o HTML de cada pregão na pasta da primeira licitação
+fn gravar_relatorios(
+    relatorios: &[RelatorioLicitacao],
+    consolidadas_por_secao: &[Vec<PropostaConsolidada>],
+    pdf_path: &Path,
+    output_dir: &Path,
+    regras: &[RoutingRule],
+) -> Result<()> {
+    let Some(primeiro) = relatorios.first() else {
+        return Ok(());
+    };
     
     // Gerar nome do arquivo de saída
     let nome_arquivo = pdf_path
@@ -66,134 +489,382 @@ pub fn processar_pdf_com_consolidacao(pdf_path: &Path, output_dir: &Path, verbos
         .unwrap_or_default()
         .to_string_lossy();
     
-    let output_path = output_dir.join(format!("{}.md", nome_arquivo));
+    // Aplicar regras de roteamento da licitação
+    let destino = routing::diretorio_destino(regras, output_dir, &primeiro.uasg, &primeiro.pregao);
+    if !destino.exists() {
+        fs::create_dir_all(&destino)
+            .context(format!("Erro ao criar diretório de destino: {}", destino.display()))?;
+    }
+    
+    let output_path = destino.join(format!("{}.md", nome_arquivo));
     
     // Gerar Markdown estruturado
-    let markdown = gerar_markdown(&relatorio)?;
+    let markdown = gerar_markdown(relatorios, consolidadas_por_secao)?;
     
-    // Salvar arquivo
-    fs::write(&output_path, markdown)
+    // Salvar arquivo registrando o checksum na pasta de resultados
+    integridade::gravar_artefato(output_dir, &output_path, markdown.as_bytes(), Some(pdf_path))
         .context("Erro ao salvar arquivo Markdown")?;
     
-    if verbose {
-        println!("Arquivo salvo em: {:?}", output_path);
+    // Mesmo relatório em HTML, para abrir no navegador; um arquivo por pregão quando há vários
+    for (relatorio, consolidadas) in relatorios.iter().zip(consolidadas_por_secao) {
+        let html_path = if relatorios.len() == 1 {
+            destino.join(format!("{}.html", nome_arquivo))
+        } else {
+            destino.join(paths::sanitize_filename(&format!("{}_pregao_{}.html", nome_arquivo, relatorio.pregao)))
+        };
+        integridade::gravar_artefato(output_dir, &html_path, report::gerar_html(relatorio, consolidadas).as_bytes(), Some(pdf_path))
+            .context("Erro ao salvar relatório HTML")?;
+        debug!(caminho = %html_path.display(), "Relatório HTML salvo");
     }
     
-    // Converter propostas para formato consolidado
-    let propostas_consolidadas: Vec<PropostaConsolidada> = relatorio.propostas.iter().map(|p| {
-        PropostaConsolidada {
+    debug!(caminho = %output_path.display(), "Relatório Markdown salvo");
+    
+    Ok(())
+}
+
+/// Rótulo das atas em que o valor estimado (e o adjudicado) é por unidade
+static RE_VALOR_UNITARIO: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)Valor\s+unit[áa]rio\s+estimado").unwrap());
+
+/// A seção traz valores unitários ("Valor unitário estimado")
+fn layout_valor_unitario(text: &str) -> bool {
+    RE_VALOR_UNITARIO.is_match(text)
+}
+
+/// Valores de um item conforme o layout da ata
+struct ValoresItem {
+    unitario_estimado: Option<Valor>,
+    unitario_adjudicado: Option<Valor>,
+    total: Option<Valor>,
+    calculado: bool,
+}
+
+/// Separa os valores unitários do total do item
+///
+/// Heurística: se a seção traz "Valor unitário estimado", os valores extraídos são
+/// unitários e o total é quantidade × valor adjudicado (`calculado`). Caso contrário, o
+/// valor adjudicado já é o total do item e os campos unitários ficam vazios.
+fn valores_do_item(proposta: &PropostaAdjudicada, unitario: bool) -> ValoresItem {
+    if !unitario {
+        return ValoresItem {
+            unitario_estimado: None,
+            unitario_adjudicado: None,
+            total: proposta.valor_adjudicado,
+            calculado: false,
+        };
+    }
+
+    let quantidade = converter_valor_para_float(&proposta.quantidade).ok();
+    ValoresItem {
+        unitario_estimado: proposta.valor_estimado,
+        unitario_adjudicado: proposta.valor_adjudicado,
+        total: proposta.valor_adjudicado.zip(quantidade).map(|(valor, quantidade)| Valor(valor.0 * quantidade)),
+        calculado: true,
+    }
+}
+
+/// Converte as propostas do relatório para o formato consolidado, com lances e participantes da seção
+fn propostas_do_relatorio(relatorio: &RelatorioLicitacao, text: &str, arquivo_origem: Option<&str>) -> Vec<PropostaConsolidada> {
+    let unitario = layout_valor_unitario(text);
+    relatorio.propostas.iter().map(|p| {
+        let valores = valores_do_item(p, unitario);
+        let mut proposta = PropostaConsolidada {
             uasg: relatorio.uasg.clone(),
             pregao: relatorio.pregao.clone(),
+            modalidade: relatorio.modalidade.clone(),
             processo: relatorio.processo.clone(),
             item: p.item.clone(),
             grupo: p.grupo.clone(),
             quantidade: p.quantidade.clone(),
             descricao: p.descricao.clone(),
-            valor_estimado: p.valor_estimado.clone(),
-            valor_adjudicado: p.valor_adjudicado.clone(),
+            valor_estimado: p.valor_estimado,
+            valor_adjudicado: p.valor_adjudicado,
+            valor_adjudicado_raw: p.valor_adjudicado_raw.clone(),
             fornecedor: p.fornecedor.clone(),
             cnpj: p.cnpj.clone(),
             marca_fabricante: p.marca_fabricante.clone(),
             modelo_versao: p.modelo_versao.clone(),
             responsavel: p.responsavel.clone(),
-            melhor_lance: p.melhor_lance.clone(),
+            melhor_lance: p.melhor_lance,
+            valor_unitario_estimado: valores.unitario_estimado,
+            valor_unitario_adjudicado: valores.unitario_adjudicado,
+            valor_total_item: valores.total,
+            valor_calculado: valores.calculado && valores.total.is_some(),
             tipo_formato: p.tipo_formato.clone(),
+            situacao: p.situacao.clone(),
+            cnpj_valido: p.cnpj_valido,
+            descricao_reparada: p.descricao_reparada,
+            descricao_original: p.descricao_original.clone(),
+            observacoes: p.observacoes.clone(),
+            porte: p.porte.clone(),
+            lances: extrair_lances(text, p.grupo.as_deref(), &p.item),
+            participantes: extrair_participantes_item(text, p.grupo.as_deref(), &p.item),
+            data_homologacao: relatorio.data_homologacao.clone(),
+            data_homologacao_iso: relatorio.data_homologacao_iso.clone(),
+            responsavel_homologacao: (relatorio.responsavel_nome != "N/A").then(|| ResponsavelHomologacao {
+                nome: relatorio.responsavel_nome.clone(),
+                cargo: relatorio.responsavel_cargo.clone(),
+                matricula: relatorio.responsavel_matricula.clone(),
+            }),
+            arquivo_origem: arquivo_origem.map(str::to_string),
+            origem: relatorio.origem.clone(),
+            avisos: relatorio.avisos.clone(),
+            economia_absoluta: None,
+            economia_percentual: None,
+        };
+        proposta.calcular_economia();
+        proposta
+    }).collect()
+}
+
+/// Evento enviado ao callback de progresso do processamento de diretório
+#[derive(Debug, Clone)]
+pub enum EventoProgresso {
+    /// O arquivo começou a ser processado
+    Iniciado(String),
+    /// O arquivo foi processado, mas com um aviso (arquivo, aviso); precede o `Concluido`
+    Aviso(String, String),
+    /// O arquivo foi processado com sucesso (arquivo, propostas extraídas dele, tempos da extração)
+    Concluido(String, Vec<PropostaConsolidada>, FileProcessingMetrics),
+    /// O processamento do arquivo falhou (arquivo, erro); os demais continuam
+    Falhou(String, String),
+    /// O PDF exige senha e não foi processado; os demais continuam
+    Protegido(String),
+}
+
+/// Critério de varredura já compilado: profundidade, padrões excluídos e pastas ignoradas
+///
+/// A contagem inicial e o processamento usam a mesma lista, produzida por `listar_atas`.
+#[derive(Debug, Clone, Default)]
+pub struct FiltroVarredura {
+    max_depth: usize,
+    excluidos: GlobSet,
+    pastas_ignoradas: Vec<PathBuf>,
+}
+
+impl FiltroVarredura {
+    /// Compila os padrões; a pasta de saída é ignorada quando estiver dentro da pasta de entrada
+    pub fn novo(input_dir: &Path, opcoes: &OpcoesVarredura, output_dir: Option<&Path>) -> Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for padrao in &opcoes.exclude_patterns {
+            builder.add(Glob::new(padrao).context(format!("Padrão de exclusão inválido: {}", padrao))?);
         }
-    }).collect();
-    
-    Ok(propostas_consolidadas)
+        let excluidos = builder.build().context("Erro ao compilar padrões de exclusão")?;
+        let entrada = fs::canonicalize(input_dir).ok();
+        let pastas_ignoradas = output_dir
+            .and_then(|saida| fs::canonicalize(saida).ok())
+            .filter(|saida| entrada.as_ref().is_some_and(|entrada| saida != entrada && saida.starts_with(entrada)))
+            .into_iter()
+            .collect();
+        Ok(Self { max_depth: opcoes.max_depth, excluidos, pastas_ignoradas })
+    }
+
+    fn ignorado(&self, input_dir: &Path, entrada: &DirEntry) -> bool {
+        if entrada.depth() == 0 {
+            return false;
+        }
+        if entrada.file_type().is_dir() {
+            if paths::eh_pasta_processados(entrada.file_name()) {
+                return true;
+            }
+            if !self.pastas_ignoradas.is_empty()
+                && fs::canonicalize(entrada.path()).is_ok_and(|pasta| self.pastas_ignoradas.contains(&pasta))
+            {
+                return true;
+            }
+        }
+        entrada.path().strip_prefix(input_dir).is_ok_and(|relativo| self.excluidos.is_match(relativo))
+    }
+}
+
+/// PDFs de um diretório e subpastas (exceto Processados), ordenados pelo caminho
+pub fn listar_pdfs(input_dir: &Path, filtro: &FiltroVarredura) -> Vec<PathBuf> {
+    listar_arquivos(input_dir, filtro, paths::is_pdf_file)
+}
+
+/// Atas de um diretório e subpastas (PDF, HTML e TXT, exceto Processados), ordenadas pelo caminho
+pub fn listar_atas(input_dir: &Path, filtro: &FiltroVarredura) -> Vec<PathBuf> {
+    listar_arquivos(input_dir, filtro, paths::is_ata_file)
+}
+
+fn listar_arquivos(input_dir: &Path, filtro: &FiltroVarredura, aceito: fn(&Path) -> bool) -> Vec<PathBuf> {
+    let mut walker = WalkDir::new(input_dir).sort_by_file_name();
+    if filtro.max_depth > 0 {
+        walker = walker.max_depth(filtro.max_depth);
+    }
+    walker
+        .into_iter()
+        .filter_entry(|e| !filtro.ignorado(input_dir, e))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| aceito(e.path()))
+        .map(|e| e.into_path())
+        .collect()
 }
 
 /// Processa todos os arquivos PDF de um diretório
+///
+/// Equivale a `processar_pdfs_com_progresso` com os arquivos de `listar_atas`.
 pub fn processar_diretorio_pdfs_com_progresso<F>(
     input_dir: &Path, 
     output_dir: &Path, 
-    verbose: bool,
-    mut progress_callback: F
+    filtro: &FiltroVarredura,
+    regras: &[RoutingRule],
+    opcoes: OpcoesProcessamento,
+    cancelado: &AtomicBool,
+    progress_callback: F
 ) -> Result<Vec<PropostaConsolidada>> 
 where
-    F: FnMut(usize, usize, Option<String>),
+    F: FnMut(usize, usize, EventoProgresso) + Send,
+{
+    let pdf_files = listar_atas(input_dir, filtro);
+    processar_pdfs_com_progresso(&pdf_files, output_dir, regras, opcoes, cancelado, progress_callback)
+}
+
+/// Processa uma lista de arquivos PDF
+///
+/// Os arquivos são processados em paralelo por até `opcoes.max_parallel_jobs` workers
+/// (0 = um por núcleo), mas as propostas retornam na ordem dos arquivos. O callback
+/// recebe o número de arquivos já concluídos, o total e o evento, e nunca é chamado
+/// por duas threads ao mesmo tempo. O sinal `cancelado` é verificado antes de cada
+/// arquivo; ao ser acionado, os arquivos restantes são ignorados e as propostas
+/// extraídas até o momento são devolvidas.
+pub fn processar_pdfs_com_progresso<F>(
+    pdf_files: &[PathBuf],
+    output_dir: &Path, 
+    regras: &[RoutingRule],
+    opcoes: OpcoesProcessamento,
+    cancelado: &AtomicBool,
+    progress_callback: F
+) -> Result<Vec<PropostaConsolidada>> 
+where
+    F: FnMut(usize, usize, EventoProgresso) + Send,
 {
-    let mut todas_propostas: Vec<PropostaConsolidada> = Vec::new();
-    
     // Criar diretório de saída se não existir
     if !output_dir.exists() {
         fs::create_dir_all(output_dir)
             .context("Erro ao criar diretório de saída")?;
     }
     
-    // Coletar todos os arquivos PDF primeiro
-    let pdf_files: Vec<_> = WalkDir::new(input_dir)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-        .filter(|e| e.path().extension().map_or(false, |ext| ext == "pdf"))
-        .collect();
-    
     let total_files = pdf_files.len();
     
-    // Processar cada arquivo
-    for (index, entry) in pdf_files.iter().enumerate() {
-        let current_file = entry.path().to_string_lossy().to_string();
-        
-        // Atualizar progresso antes de processar o arquivo
-        progress_callback(index, total_files, Some(current_file.clone()));
-        
-        if verbose {
-            println!("Processando: {:?}", entry.path());
+    // Callback serializado: contador de concluídos e chamada ficam sob o mesmo lock
+    let callback = Mutex::new((0usize, progress_callback));
+    let notificar = |evento: EventoProgresso, concluido: bool| {
+        if let Ok(mut guard) = callback.lock() {
+            let (processados, progress_callback) = &mut *guard;
+            if concluido {
+                *processados += 1;
+            }
+            progress_callback(*processados, total_files, evento);
         }
-        
-        match processar_pdf_com_consolidacao(entry.path(), output_dir, verbose) {
-            Ok(propostas) => {
-                todas_propostas.extend(propostas);
-                if verbose {
-                    println!("✓ Processado com sucesso: {:?}", entry.path());
-                }
+    };
+    
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(opcoes.max_parallel_jobs)
+        .build()
+        .context("Erro ao criar pool de processamento paralelo")?;
+    
+    info!(arquivos = total_files, workers = pool.current_num_threads(), "Processando arquivos");
+    
+    // Processar os arquivos em paralelo; `collect` preserva a ordem original
+    // As threads do pool entram no span da sessão para herdar o `verbose` dela
+    let sessao = tracing::Span::current();
+    let resultados: Vec<Option<Vec<PropostaConsolidada>>> = pool.install(|| {
+        pdf_files.par_iter().map(|pdf_path| {
+            let _sessao = sessao.enter();
+            // Não iniciar novos arquivos se o cancelamento foi solicitado
+            if cancelado.load(Ordering::SeqCst) {
+                return None;
             }
-            Err(e) => {
-                eprintln!("✗ Erro ao processar {:?}: {}", entry.path(), e);
+            
+            let current_file = pdf_path.to_string_lossy().to_string();
+            notificar(EventoProgresso::Iniciado(current_file.clone()), false);
+            
+            match processar_pdf_com_consolidacao(pdf_path, output_dir, regras, opcoes) {
+                Ok(resultado) => {
+                    debug!(arquivo = %pdf_path.display(), propostas = resultado.propostas.len(), "Processado com sucesso");
+                    for aviso in resultado.avisos {
+                        notificar(EventoProgresso::Aviso(current_file.clone(), aviso), false);
+                    }
+                    notificar(EventoProgresso::Concluido(current_file, resultado.propostas.clone(), resultado.metricas), true);
+                    Some(resultado.propostas)
+                }
+                Err(e) if metadados_pdf::eh_pdf_protegido(&e) => {
+                    warn!("{}", e);
+                    notificar(EventoProgresso::Protegido(current_file), true);
+                    None
+                }
+                Err(e) => {
+                    warn!(arquivo = %pdf_path.display(), "Erro ao processar: {:#}", e);
+                    notificar(EventoProgresso::Falhou(current_file, e.to_string()), true);
+                    None
+                }
             }
-        }
-        
-        // Atualizar progresso após processar o arquivo
-        progress_callback(index + 1, total_files, None);
+        }).collect()
+    });
+    
+    if cancelado.load(Ordering::SeqCst) {
+        let processados = resultados.iter().filter(|r| r.is_some()).count();
+        info!("Processamento cancelado: {} de {} arquivos com propostas extraídas", processados, total_files);
     }
     
+    let todas_propostas: Vec<PropostaConsolidada> = resultados.into_iter()
+        .flatten()
+        .flatten()
+        .collect();
+    
     Ok(todas_propostas)
 }
 
 /// Processa todos os arquivos PDF de um diretório (versão original mantida para compatibilidade)
-pub fn processar_diretorio_pdfs(input_dir: &Path, output_dir: &Path, verbose: bool) -> Result<Vec<PropostaConsolidada>> {
-    processar_diretorio_pdfs_com_progresso(input_dir, output_dir, verbose, |_, _, _| {})
+pub fn processar_diretorio_pdfs(input_dir: &Path, output_dir: &Path) -> Result<Vec<PropostaConsolidada>> {
+    processar_diretorio_pdfs_com_progresso(input_dir, output_dir, &FiltroVarredura::default(), &[], OpcoesProcessamento::default(), &AtomicBool::new(false), |_, _, _| {})
 }
 
-/// Extrai propostas no formato individual
-fn extrair_propostas_individuais(text: &str, verbose: bool) -> Vec<PropostaAdjudicada> {
-    let mut propostas = Vec::new();
-    let mut cnpjs_processados = HashSet::new();
+pub(crate) static RE_ADJUCADO_NEGOCIADO: Lazy<Regex> = Lazy::new(|| Regex::new(
+    r"Adjucado e Homologado por CPF\s*(?P<cpf>[\d\.\-\*]+)\s*-\s*(?P<responsavel>[^,]+),?\s*para\s+(?P<fornecedor>[^,]+),\s*CNPJ\s*(?P<cnpj>[\d\.\-/]+),\s*melhor\s+lance:\s*R\$\s*(?P<melhor_lance>[\d,\.]+).*?valor\s+negociado:\s*R\$\s*(?P<valor_negociado>[\d,\.]+)"
+).unwrap());
+
+pub(crate) static RE_ADJUDICADO_NEGOCIADO: Lazy<Regex> = Lazy::new(|| Regex::new(
+    r"Adjudicado e Homologado por CPF\s*(?P<cpf>[\d\.\-\*]+)\s*-\s*(?P<responsavel>[^,]+),?\s*para\s+(?P<fornecedor>[^,]+),\s*CNPJ\s*(?P<cnpj>[\d\.\-/]+),\s*melhor\s+lance:\s*R\$\s*(?P<melhor_lance>[\d,\.]+).*?valor\s+negociado:\s*R\$\s*(?P<valor_negociado>[\d,\.]+)"
+).unwrap());
 
-    // Padrões para formato individual
-    let re_adjucado_negociado = Regex::new(
-        r"Adjucado e Homologado por CPF\s*(?P<cpf>[\d\.\-\*]+)\s*-\s*(?P<responsavel>[^,]+),?\s*para\s+(?P<fornecedor>[^,]+),\s*CNPJ\s*(?P<cnpj>[\d\.\-/]+),\s*melhor\s+lance:\s*R\$\s*(?P<melhor_lance>[\d,\.]+).*?valor\s+negociado:\s*R\$\s*(?P<valor_negociado>[\d,\.]+)"
-    ).unwrap();
+pub(crate) static RE_ADJUCADO: Lazy<Regex> = Lazy::new(|| Regex::new(
+    r"Adjucado e Homologado por CPF\s*(?P<cpf>[\d\.\-\*]+)\s*-\s*(?P<responsavel>[^,]+),?\s*para\s+(?P<fornecedor>[^,]+),\s*CNPJ\s*(?P<cnpj>[\d\.\-/]+),\s*melhor\s+lance:\s*R\$\s*(?P<melhor_lance>[\d,\.]+)"
+).unwrap());
 
-    let re_adjudicado_negociado = Regex::new(
-        r"Adjudicado e Homologado por CPF\s*(?P<cpf>[\d\.\-\*]+)\s*-\s*(?P<responsavel>[^,]+),?\s*para\s+(?P<fornecedor>[^,]+),\s*CNPJ\s*(?P<cnpj>[\d\.\-/]+),\s*melhor\s+lance:\s*R\$\s*(?P<melhor_lance>[\d,\.]+).*?valor\s+negociado:\s*R\$\s*(?P<valor_negociado>[\d,\.]+)"
-    ).unwrap();
+pub(crate) static RE_ADJUDICADO: Lazy<Regex> = Lazy::new(|| Regex::new(
+    r"Adjudicado e Homologado por CPF\s*(?P<cpf>[\d\.\-\*]+)\s*-\s*(?P<responsavel>[^,]+),?\s*para\s+(?P<fornecedor>[^,]+),\s*CNPJ\s*(?P<cnpj>[\d\.\-/]+),\s*melhor\s+lance:\s*R\$\s*(?P<melhor_lance>[\d,\.]+)"
+).unwrap());
 
-    let re_adjucado = Regex::new(
-        r"Adjucado e Homologado por CPF\s*(?P<cpf>[\d\.\-\*]+)\s*-\s*(?P<responsavel>[^,]+),?\s*para\s+(?P<fornecedor>[^,]+),\s*CNPJ\s*(?P<cnpj>[\d\.\-/]+),\s*melhor\s+lance:\s*R\$\s*(?P<melhor_lance>[\d,\.]+)"
-    ).unwrap();
+/// Layout individual: "Adjudicado e Homologado por CPF ... para FORNECEDOR, CNPJ ..., melhor lance: ..."
+pub struct ExtratorIndividual;
 
-    let re_adjudicado = Regex::new(
-        r"Adjudicado e Homologado por CPF\s*(?P<cpf>[\d\.\-\*]+)\s*-\s*(?P<responsavel>[^,]+),?\s*para\s+(?P<fornecedor>[^,]+),\s*CNPJ\s*(?P<cnpj>[\d\.\-/]+),\s*melhor\s+lance:\s*R\$\s*(?P<melhor_lance>[\d,\.]+)"
-    ).unwrap();
+impl ExtratorPropostas for ExtratorIndividual {
+    fn nome(&self) -> &str {
+        "individual"
+    }
+
+    fn detecta(&self, text: &str) -> bool {
+        RE_ADJUDICADO.is_match(text) || RE_ADJUCADO.is_match(text)
+    }
+
+    fn extrair(&self, text: &str) -> Vec<PropostaAdjudicada> {
+        extrair_propostas_individuais(text)
+    }
+}
+
+/// Extrai propostas no formato individual
+fn extrair_propostas_individuais(text: &str) -> Vec<PropostaAdjudicada> {
+    let mut propostas = Vec::new();
+    let mut cnpjs_processados = HashSet::new();
 
-    let padroes_adjudicacao = vec![
-        (&re_adjucado_negociado, true),
-        (&re_adjudicado_negociado, true),
-        (&re_adjucado, false),
-        (&re_adjudicado, false),
+    // Padrões para formato individual, com e sem o erro de grafia "Adjucado" das atas antigas
+    let padroes_adjudicacao = [
+        (&*RE_ADJUCADO_NEGOCIADO, true),
+        (&*RE_ADJUDICADO_NEGOCIADO, true),
+        (&*RE_ADJUCADO, false),
+        (&*RE_ADJUDICADO, false),
     ];
 
     for (regex, tem_valor_negociado) in padroes_adjudicacao {
@@ -212,27 +883,34 @@ fn extrair_propostas_individuais(text: &str, verbose: bool) -> Vec<PropostaAdjud
                 melhor_lance
             };
 
+            let descricao = extrair_descricao_do_contexto(text, cnpj);
+
             let proposta = PropostaAdjudicada {
                 item: extrair_item_do_contexto(text, cnpj),
                 grupo: None,
-                descricao: extrair_descricao_do_contexto(text, cnpj),
+                descricao: descricao.descricao,
                 quantidade: extrair_quantidade_do_contexto(text, cnpj),
-                valor_estimado: extrair_valor_estimado_do_contexto(text, cnpj),
-                valor_adjudicado: valor_adjudicado.to_string(),
+                valor_estimado: Valor::de_texto(&extrair_valor_estimado_do_contexto(text, cnpj)),
+                valor_adjudicado: Valor::de_texto(valor_adjudicado),
+                valor_adjudicado_raw: valor_adjudicado.to_string(),
                 fornecedor: caps_adjudicado.get(3).unwrap().as_str().trim().to_string(),
                 cnpj: cnpj.to_string(),
-                melhor_lance: melhor_lance.to_string(),
+                melhor_lance: Valor::de_texto(melhor_lance),
                 responsavel: caps_adjudicado.get(2).unwrap().as_str().trim().to_string(),
                 cpf_responsavel: caps_adjudicado.get(1).unwrap().as_str().trim().to_string(),
                 marca_fabricante: extrair_marca_fabricante_do_contexto(text, cnpj),
                 modelo_versao: extrair_modelo_versao_do_contexto(text, cnpj),
                 tipo_formato: "individual".to_string(),
+                situacao: SITUACAO_ADJUDICADO.to_string(),
+                cnpj_valido: validators::validar_cnpj(cnpj),
+                descricao_reparada: descricao.original.is_some(),
+                descricao_original: descricao.original,
+                observacoes: None,
+                porte: None,
             };
 
-            if verbose {
-                println!("✅ Proposta individual extraída - Item: {}, Fornecedor: {}, CNPJ: {}, Valor: R$ {}", 
-                         proposta.item, proposta.fornecedor, proposta.cnpj, proposta.valor_adjudicado);
-            }
+            debug!(item = %proposta.item, fornecedor = %proposta.fornecedor, cnpj = %proposta.cnpj,
+                   valor = %proposta.valor_adjudicado_raw, "Proposta individual extraída");
 
             propostas.push(proposta);
         }
@@ -241,17 +919,38 @@ fn extrair_propostas_individuais(text: &str, verbose: bool) -> Vec<PropostaAdjud
     propostas
 }
 
+/// Padrão para formato de grupo, com o valor negociado opcional
+pub(crate) static RE_GRUPO: Lazy<Regex> = Lazy::new(|| Regex::new(
+    r"Item\s+(?P<item>\d+)\s+do\s+Grupo\s+G(?P<grupo>\d+)\s*-\s*(?P<descricao>[^\n]+)[\s\S]*?Quantidade:\s*(?P<quantidade>\d+)[\s\S]*?Valor\s+(?:unit[áa]rio\s+)?estimado:\s*R\$\s*(?P<valor>[\d,\.]+)[\s\S]*?Situa[çc][ãa]o:\s*(?P<situacao>Adjudicado e Homologado)[\s\S]*?Adjudicado e Homologado por CPF[^-]+-\s*(?P<responsavel>[^,]+?)\s*para\s+(?P<fornecedor>[^,]+),\s*CNPJ\s*(?P<cnpj>[\d\.\-/]+),\s*melhor\s+lance:\s*R\$\s*(?P<melhor_lance>\d[\d\.]*(?:,\d+)?)(?:.*?valor\s+negociado:\s*R\$\s*(?P<valor_negociado>\d[\d\.]*(?:,\d+)?))?"
+).unwrap());
+
+/// Layout de grupo: "Item N do Grupo GN - ..." com a situação e a adjudicação no bloco do item
+pub struct ExtratorGrupo;
+
+impl ExtratorPropostas for ExtratorGrupo {
+    fn nome(&self) -> &str {
+        "grupo"
+    }
+
+    fn detecta(&self, text: &str) -> bool {
+        blocos_de_itens(text).iter().any(|(_, bloco)| RE_GRUPO.is_match(bloco))
+    }
+
+    fn extrair(&self, text: &str) -> Vec<PropostaAdjudicada> {
+        extrair_propostas_grupo(text)
+    }
+}
+
 /// Extrai propostas no formato de grupo
-fn extrair_propostas_grupo(text: &str, verbose: bool) -> Vec<PropostaAdjudicada> {
+///
+/// Quando a ata traz "valor negociado" na linha da adjudicação, ele vira o valor adjudicado;
+/// o melhor lance continua registrado à parte.
+fn extrair_propostas_grupo(text: &str) -> Vec<PropostaAdjudicada> {
     let mut propostas = Vec::new();
     let mut cnpjs_processados = HashSet::new();
 
-    // Padrão para formato de grupo
-    let padrao_grupo = r"Item\s+(?P<item>\d+)\s+do\s+Grupo\s+G(?P<grupo>\d+)\s*-\s*(?P<descricao>[^\n]+)[\s\S]*?Quantidade:\s*(?P<quantidade>\d+)[\s\S]*?Valor\s+estimado:\s*R\$\s*(?P<valor>[\d,\.]+)[\s\S]*?Situação:\s*(?P<situacao>Adjudicado e Homologado)[\s\S]*?Adjudicado e Homologado por CPF[^-]+-\s*(?P<responsavel>[^,]+?)\s*para\s+(?P<fornecedor>[^,]+),\s*CNPJ\s*(?P<cnpj>[\d\.\-/]+),\s*melhor\s+lance:\s*R\$\s*(?P<melhor_lance>[\d,\.]+)";
-
-    let re_grupo = Regex::new(padrao_grupo).unwrap();
-
-    for caps in re_grupo.captures_iter(text) {
+    // Um padrão por bloco de item: itens sem adjudicação não "emprestam" o vencedor do item seguinte
+    for (bloco, caps) in blocos_de_itens(text).into_iter().filter_map(|(_, bloco)| RE_GRUPO.captures(bloco).map(|caps| (bloco, caps))) {
         let cnpj = caps.name("cnpj").unwrap().as_str().trim();
         let item = caps.name("item").unwrap().as_str().trim();
         let key = format!("{}-{}", item, cnpj);
@@ -261,27 +960,39 @@ fn extrair_propostas_grupo(text: &str, verbose: bool) -> Vec<PropostaAdjudicada>
         }
         cnpjs_processados.insert(key);
 
+        // Linhas entre a descrição e "Quantidade:" pertencem ao mesmo bloco do item
+        let descricao_match = caps.name("descricao").unwrap();
+        let continuacao = &bloco[descricao_match.end()..caps.name("quantidade").unwrap().start()];
+        let descricao = reparar_descricao(descricao_match.as_str(), continuacao);
+
+        let melhor_lance = caps.name("melhor_lance").unwrap().as_str().trim();
+        let valor_adjudicado = caps.name("valor_negociado").map_or(melhor_lance, |negociado| negociado.as_str().trim());
         let proposta = PropostaAdjudicada {
             item: item.to_string(),
             grupo: Some(format!("G{}", caps.name("grupo").unwrap().as_str())),
-            descricao: caps.name("descricao").unwrap().as_str().trim().to_string(),
+            descricao: descricao.descricao,
             quantidade: caps.name("quantidade").unwrap().as_str().trim().to_string(),
-            valor_estimado: caps.name("valor").unwrap().as_str().trim().to_string(),
-            valor_adjudicado: caps.name("melhor_lance").unwrap().as_str().trim().to_string(),
+            valor_estimado: Valor::de_texto(caps.name("valor").unwrap().as_str()),
+            valor_adjudicado: Valor::de_texto(valor_adjudicado),
+            valor_adjudicado_raw: valor_adjudicado.to_string(),
             fornecedor: caps.name("fornecedor").unwrap().as_str().trim().to_string(),
             cnpj: cnpj.to_string(),
-            melhor_lance: caps.name("melhor_lance").unwrap().as_str().trim().to_string(),
+            melhor_lance: Valor::de_texto(melhor_lance),
             responsavel: caps.name("responsavel").unwrap().as_str().trim().to_string(),
             cpf_responsavel: extrair_cpf_do_responsavel(&caps.name("responsavel").unwrap().as_str()),
             marca_fabricante: "N/A".to_string(),
             modelo_versao: "N/A".to_string(),
             tipo_formato: "grupo".to_string(),
+            situacao: SITUACAO_ADJUDICADO.to_string(),
+            cnpj_valido: validators::validar_cnpj(cnpj),
+            descricao_reparada: descricao.original.is_some(),
+            descricao_original: descricao.original,
+            observacoes: None,
+            porte: None,
         };
 
-        if verbose {
-            println!("✅ Proposta de grupo extraída - Item: {}, Grupo: {}, Fornecedor: {}, CNPJ: {}, Valor: R$ {}", 
-                     proposta.item, proposta.grupo.as_ref().unwrap(), proposta.fornecedor, proposta.cnpj, proposta.valor_adjudicado);
-        }
+        debug!(item = %proposta.item, grupo = proposta.grupo.as_deref().unwrap_or_default(), fornecedor = %proposta.fornecedor,
+               cnpj = %proposta.cnpj, valor = %proposta.valor_adjudicado_raw, "Proposta de grupo extraída");
 
         propostas.push(proposta);
     }
@@ -291,107 +1002,525 @@ fn extrair_propostas_grupo(text: &str, verbose: bool) -> Vec<PropostaAdjudicada>
 
 /// Extrai CPF do responsável
 fn extrair_cpf_do_responsavel(responsavel: &str) -> String {
-    let re_cpf = Regex::new(r"(\*{3}\.\d{3}\.\*{3}-\*\d)").unwrap();
-    if let Some(caps) = re_cpf.captures(responsavel) {
+    static RE_CPF: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\*{3}\.\d{3}\.\*{3}-\*\d)").unwrap());
+    if let Some(caps) = RE_CPF.captures(responsavel) {
         caps.get(1).unwrap().as_str().to_string()
     } else {
         "N/A".to_string()
     }
 }
 
+/// Padrões que dependem do CNPJ do fornecedor, compilados uma vez por CNPJ
+struct PadroesCnpj {
+    item: Regex,
+    descricao: Regex,
+    quantidade: [Regex; 2],
+    valor_estimado: [Regex; 2],
+    marca_fabricante: Regex,
+    modelo_versao: Regex,
+}
+
+impl PadroesCnpj {
+    fn novo(cnpj: &str) -> Self {
+        let cnpj = regex::escape(cnpj);
+        let compilar = |padrao: String| Regex::new(&padrao).unwrap();
+        Self {
+            item: compilar(format!(r"Item\s+(\d+)[^#]*?{}", cnpj)),
+            descricao: compilar(format!(r"Item\s+\d+[^#]*?([^#]*?){}", cnpj)),
+            quantidade: [
+                compilar(format!(r"Quantidade:\s*(\d+)[^#]*?{}", cnpj)),
+                compilar(format!(r"Unidade\s+(\d+)[^#]*?{}", cnpj)),
+            ],
+            valor_estimado: [
+                compilar(format!(r"Valor\s+(?:unit[áa]rio\s+)?estimado:\s*R\$\s*([\d,\.]+)[^#]*?{}", cnpj)),
+                compilar(format!(r"R\$\s*([\d,\.]+)Quantidade:[^#]*?{}", cnpj)),
+            ],
+            marca_fabricante: compilar(format!(r"{}[\s\S]*?Proposta adjudicada[\s\S]*?Marca/Fabricante:\s*([^\n\r]+)", cnpj)),
+            modelo_versao: compilar(format!(r"{}[\s\S]*?Proposta adjudicada[\s\S]*?Modelo/vers[ãa]o:\s*([^\n\r]+)", cnpj)),
+        }
+    }
+}
+
+/// Limite de CNPJs guardados no cache de padrões; ao atingi-lo, o cache é esvaziado
+const LIMITE_CACHE_PADROES_CNPJ: usize = 2048;
+
+static CACHE_PADROES_CNPJ: Lazy<Mutex<HashMap<String, Arc<PadroesCnpj>>>> = Lazy::new(Default::default);
+
+/// Padrões do CNPJ, reaproveitados entre propostas e arquivos do mesmo lote
+///
+/// O mesmo fornecedor vence itens em várias atas; compilar os regex de novo para cada
+/// proposta dominava o tempo de processamento de lotes grandes.
+fn padroes_cnpj(cnpj: &str) -> Arc<PadroesCnpj> {
+    if let Some(padroes) = CACHE_PADROES_CNPJ.lock().unwrap_or_else(|e| e.into_inner()).get(cnpj) {
+        return Arc::clone(padroes);
+    }
+
+    // Compilar fora do lock para não serializar os arquivos processados em paralelo
+    let padroes = Arc::new(PadroesCnpj::novo(cnpj));
+    let mut cache = CACHE_PADROES_CNPJ.lock().unwrap_or_else(|e| e.into_inner());
+    if cache.len() >= LIMITE_CACHE_PADROES_CNPJ {
+        cache.clear();
+    }
+    Arc::clone(cache.entry(cnpj.to_string()).or_insert(padroes))
+}
+
+/// Primeira captura do primeiro padrão que casar
+fn primeira_captura<'a>(text: &str, padroes: impl IntoIterator<Item = &'a Regex>) -> String {
+    padroes.into_iter()
+        .find_map(|re| re.captures(text))
+        .map_or("N/A".to_string(), |caps| caps.get(1).unwrap().as_str().to_string())
+}
+
 /// Extrai item do contexto baseado no CNPJ
 fn extrair_item_do_contexto(text: &str, cnpj: &str) -> String {
-    let padrao = format!(r"Item\s+(\d+)[^#]*?{}", regex::escape(cnpj));
-    let re = Regex::new(&padrao).unwrap();
-    
-    if let Some(caps) = re.captures(text) {
-        caps.get(1).unwrap().as_str().to_string()
+    primeira_captura(text, [&padroes_cnpj(cnpj).item])
+}
+
+/// Limite de caracteres de uma descrição reparada
+const MAX_DESCRICAO_REPARADA: usize = 400;
+
+/// Descrição extraída, com o texto original quando precisou ser reparada
+struct DescricaoExtraida {
+    descricao: String,
+    original: Option<String>,
+}
+
+/// Verifica se a linha começa com um rótulo de campo ("Quantidade:", "unidade de fornecimento:")
+fn eh_rotulo_de_campo(linha: &str) -> bool {
+    static RE_ROTULO: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[\p{L}][\p{L}/ ]{0,40}:").unwrap());
+    RE_ROTULO.is_match(linha)
+}
+
+/// Completa uma descrição cortada pela quebra de coluna ou pelo rodapé
+///
+/// Só junta as linhas seguintes quando a descrição não termina em pontuação final e
+/// a continuação começa em minúscula; para em linhas vazias, rótulos de campo ou no limite.
+fn reparar_descricao(descricao: &str, continuacao: &str) -> DescricaoExtraida {
+    let descricao = descricao.trim();
+    let sem_reparo = DescricaoExtraida { descricao: descricao.to_string(), original: None };
+
+    let termina_frase = |texto: &str| texto.ends_with(['.', '!', '?', ';']);
+    if descricao.is_empty() || descricao == "N/A" || termina_frase(descricao) {
+        return sem_reparo;
+    }
+
+    let mut reparada = descricao.to_string();
+    for linha in continuacao.lines().map(str::trim).skip_while(|l| l.is_empty()) {
+        let continua = linha.chars().next().is_some_and(|c| c.is_lowercase());
+        if !continua || eh_rotulo_de_campo(linha) {
+            break;
+        }
+        if reparada.chars().count() + 1 + linha.chars().count() > MAX_DESCRICAO_REPARADA {
+            break;
+        }
+
+        reparada.push(' ');
+        reparada.push_str(linha);
+
+        if termina_frase(linha) {
+            break;
+        }
+    }
+
+    if reparada == descricao {
+        sem_reparo
     } else {
-        "N/A".to_string()
+        DescricaoExtraida { descricao: reparada, original: Some(descricao.to_string()) }
     }
 }
 
 /// Extrai descrição do contexto baseado no CNPJ
-fn extrair_descricao_do_contexto(text: &str, cnpj: &str) -> String {
-    let padrao = format!(r"Item\s+\d+[^#]*?([^#]*?){}", regex::escape(cnpj));
-    let re = Regex::new(&padrao).unwrap();
-    
-    if let Some(caps) = re.captures(text) {
+fn extrair_descricao_do_contexto(text: &str, cnpj: &str) -> DescricaoExtraida {
+    if let Some(caps) = padroes_cnpj(cnpj).descricao.captures(text) {
         let desc = caps.get(1).unwrap().as_str();
-        desc.split('\n').next().unwrap_or("N/A").trim().to_string()
+        let (primeira_linha, continuacao) = desc.split_once('\n').unwrap_or((desc, ""));
+        reparar_descricao(primeira_linha, continuacao)
     } else {
-        "N/A".to_string()
+        DescricaoExtraida { descricao: "N/A".to_string(), original: None }
     }
 }
 
 /// Extrai quantidade do contexto baseado no CNPJ
 fn extrair_quantidade_do_contexto(text: &str, cnpj: &str) -> String {
-    let padroes = vec![
-        format!(r"Quantidade:\s*(\d+)[^#]*?{}", regex::escape(cnpj)),
-        format!(r"Unidade\s+(\d+)[^#]*?{}", regex::escape(cnpj)),
-    ];
-    
-    for padrao in padroes {
-        let re = Regex::new(&padrao).unwrap();
-        if let Some(caps) = re.captures(text) {
-            return caps.get(1).unwrap().as_str().to_string();
-        }
-    }
-    
-    "N/A".to_string()
+    primeira_captura(text, &padroes_cnpj(cnpj).quantidade)
 }
 
 /// Extrai valor estimado do contexto baseado no CNPJ
 fn extrair_valor_estimado_do_contexto(text: &str, cnpj: &str) -> String {
-    let padroes = vec![
-        format!(r"Valor\s+estimado:\s*R\$\s*([\d,\.]+)[^#]*?{}", regex::escape(cnpj)),
-        format!(r"R\$\s*([\d,\.]+)Quantidade:[^#]*?{}", regex::escape(cnpj)),
-    ];
-    
-    for padrao in padroes {
-        let re = Regex::new(&padrao).unwrap();
-        if let Some(caps) = re.captures(text) {
-            return caps.get(1).unwrap().as_str().to_string();
-        }
-    }
-    
-    "N/A".to_string()
+    primeira_captura(text, &padroes_cnpj(cnpj).valor_estimado)
 }
 
 /// Extrai marca/fabricante do contexto baseado no CNPJ
 fn extrair_marca_fabricante_do_contexto(text: &str, cnpj: &str) -> String {
-    let padrao = format!(r"{}[\s\S]*?Proposta adjudicada[\s\S]*?Marca/Fabricante:\s*([^\n\r]+)", regex::escape(cnpj));
-    let re = Regex::new(&padrao).unwrap();
-    
-    if let Some(caps) = re.captures(text) {
-        return caps.get(1).unwrap().as_str().trim().to_string();
-    }
-    
-    "N/A".to_string()
+    primeira_captura(text, [&padroes_cnpj(cnpj).marca_fabricante]).trim().to_string()
 }
 
 /// Extrai modelo/versão do contexto baseado no CNPJ
 fn extrair_modelo_versao_do_contexto(text: &str, cnpj: &str) -> String {
-    let padrao = format!(r"{}[\s\S]*?Proposta adjudicada[\s\S]*?Modelo/versão:\s*([^\n\r]+)", regex::escape(cnpj));
-    let re = Regex::new(&padrao).unwrap();
-    
-    if let Some(caps) = re.captures(text) {
-        return caps.get(1).unwrap().as_str().trim().to_string();
+    primeira_captura(text, [&padroes_cnpj(cnpj).modelo_versao]).trim().to_string()
+}
+
+/// Divide o texto em blocos de item: cada um vai do cabeçalho "Item N" até o próximo item
+pub(crate) fn blocos_de_itens(text: &str) -> Vec<(String, &str)> {
+    static RE_ITEM: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s*Item:?\s+(\d+)\b").unwrap());
+    let cabecalhos: Vec<_> = RE_ITEM.captures_iter(text).collect();
+
+    cabecalhos.iter().enumerate().map(|(posicao, caps)| {
+        let inicio = caps.get(0).unwrap().start();
+        let fim = cabecalhos.get(posicao + 1).map_or(text.len(), |proximo| proximo.get(0).unwrap().start());
+        (caps[1].to_string(), &text[inicio..fim])
+    }).collect()
+}
+
+/// Grupo ("G1") declarado no cabeçalho de um bloco de item ("Item 1 do Grupo G1 - ...")
+fn grupo_do_bloco(bloco: &str) -> Option<String> {
+    static RE_GRUPO_CABECALHO: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*Item:?\s+\d+\s+do\s+Grupo\s+G(\d+)").unwrap());
+    RE_GRUPO_CABECALHO.captures(bloco).map(|caps| format!("G{}", &caps[1]))
+}
+
+/// Bloco de texto de um item, identificado pelo par (grupo, item)
+///
+/// Atas com grupos repetem a numeração dos itens ("Item 1 do Grupo G1", "Item 1 do Grupo G2").
+/// Sem grupo informado, vale o bloco sem grupo ou, na falta dele, o primeiro com o número.
+fn bloco_do_item<'a>(text: &'a str, grupo: Option<&str>, item: &str) -> Option<&'a str> {
+    let blocos: Vec<(String, &str)> = blocos_de_itens(text).into_iter().filter(|(numero, _)| numero == item).collect();
+    let mesmo_grupo = |bloco: &str| grupo_do_bloco(bloco).as_deref().map(chave_grupo) == grupo.map(chave_grupo);
+
+    blocos.iter()
+        .find(|(_, bloco)| mesmo_grupo(bloco))
+        .or_else(|| blocos.first().filter(|_| grupo.is_none()))
+        .map(|(_, bloco)| *bloco)
+}
+
+/// Linhas antes e depois da linha do CNPJ em que o porte do fornecedor é procurado
+const LINHAS_CONTEXTO_PORTE: usize = 2;
+
+/// Marcações de porte: "Porte da empresa: ...", "Demais (Diferente de ME/EPP)" e "ME/EPP"
+static RE_PORTE: Lazy<Regex> = Lazy::new(|| Regex::new(
+    r"(?i)Porte(?:\s+da\s+empresa)?\s*:\s*[^\n]{1,60}|(?:Demais\s*\(\s*)?Diferente\s+de\s+ME\s*/\s*EPP\s*\)?|\bME\s*/\s*EPP\b"
+).unwrap());
+
+/// Porte canônico (`PORTE_ME_EPP` ou `PORTE_DEMAIS`) de uma marcação da ata
+///
+/// As siglas soltas (ME, EPP, MEI) só valem em maiúsculas: "me" é palavra comum no texto.
+fn porte_canonico(marcacao: &str) -> Option<&'static str> {
+    static RE_DEMAIS: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\bdemais\b|diferente\s+de\s+ME").unwrap());
+    static RE_ME_EPP: Lazy<Regex> = Lazy::new(|| Regex::new(
+        r"(?i)\bME\s*/\s*EPP\b|micro\s*-?\s*empresa|pequeno\s+porte|(?-i:\b(?:ME|EPP|MEI)\b)"
+    ).unwrap());
+
+    if RE_DEMAIS.is_match(marcacao) {
+        Some(PORTE_DEMAIS)
+    } else if RE_ME_EPP.is_match(marcacao) {
+        Some(PORTE_ME_EPP)
+    } else {
+        None
     }
-    
-    "N/A".to_string()
+}
+
+/// Porte do fornecedor declarado junto do CNPJ no bloco do item
+///
+/// A marcação pode vir antes ou depois do CNPJ ("ME/EPP" na linha da adjudicação ou
+/// "Porte da empresa: Micro Empresa" nas linhas vizinhas); vale a mais próxima do CNPJ.
+fn extrair_porte_do_contexto(text: &str, grupo: Option<&str>, item: &str, cnpj: &str) -> Option<String> {
+    let bloco = bloco_do_item(text, grupo, item).unwrap_or(text);
+    let posicao_cnpj = bloco.find(cnpj)?;
+
+    // Janela de linhas em volta da linha do CNPJ
+    let inicio_linha = bloco[..posicao_cnpj].rfind('\n').map_or(0, |i| i + 1);
+    let inicio = bloco[..inicio_linha].match_indices('\n')
+        .nth_back(LINHAS_CONTEXTO_PORTE)
+        .map_or(0, |(i, _)| i + 1);
+    let fim = bloco[posicao_cnpj..].match_indices('\n')
+        .nth(LINHAS_CONTEXTO_PORTE)
+        .map_or(bloco.len(), |(i, _)| posicao_cnpj + i);
+    let janela = &bloco[inicio..fim];
+    let posicao_cnpj = posicao_cnpj - inicio;
+
+    RE_PORTE.find_iter(janela)
+        .filter_map(|m| porte_canonico(m.as_str()).map(|porte| (m.start().abs_diff(posicao_cnpj), porte)))
+        .min_by_key(|(distancia, _)| *distancia)
+        .map(|(_, porte)| porte.to_string())
+}
+
+/// Limite de caracteres das observações de um item
+const MAX_OBSERVACOES: usize = 500;
+
+/// Extrai os parágrafos de "Justificativa"/"Observação" do bloco do item, em um só parágrafo
+///
+/// Cada parágrafo vai do rótulo até a próxima linha vazia, rótulo de campo ou linha de
+/// adjudicação; as quebras de linha viram espaço e o resultado é cortado em `MAX_OBSERVACOES`.
+fn extrair_observacoes_do_item(text: &str, grupo: Option<&str>, item: &str) -> Option<String> {
+    static RE_ROTULO_OBSERVACAO: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(?i)^(?:Justificativa|Observa[çc](?:[ãa]o|[õo]es)|Motivo)(?:\s+d[oa]s?\s+[\p{L} ]{1,30}?)?\s*:\s*").unwrap()
+    });
+    static RE_ADJUDICACAO: Lazy<Regex> = Lazy::new(|| Regex::new(r"^Adju(?:di)?cado e Homologado por\b").unwrap());
+
+    let bloco = bloco_do_item(text, grupo, item)?;
+    let mut paragrafos: Vec<String> = Vec::new();
+    let mut atual: Option<Vec<&str>> = None;
+    for linha in bloco.lines().map(str::trim) {
+        if let Some(rotulo) = RE_ROTULO_OBSERVACAO.find(linha) {
+            paragrafos.extend(atual.take().map(|linhas| linhas.join(" ")));
+            atual = Some(vec![&linha[rotulo.end()..]]);
+            continue;
+        }
+        let Some(linhas) = atual.as_mut() else {
+            continue;
+        };
+        if linha.is_empty() || eh_rotulo_de_campo(linha) || RE_ADJUDICACAO.is_match(linha) {
+            paragrafos.extend(atual.take().map(|linhas| linhas.join(" ")));
+        } else {
+            linhas.push(linha);
+        }
+    }
+    paragrafos.extend(atual.map(|linhas| linhas.join(" ")));
+
+    let observacoes = paragrafos.join(" ").split_whitespace().collect::<Vec<_>>().join(" ");
+    let observacoes: String = observacoes.chars().take(MAX_OBSERVACOES).collect();
+    let observacoes = observacoes.trim_end();
+    (!observacoes.is_empty()).then(|| observacoes.to_string())
+}
+
+/// Extrai itens desertos, fracassados ou cancelados no julgamento (sem fornecedor)
+fn extrair_itens_nao_adjudicados(text: &str) -> Vec<PropostaAdjudicada> {
+    static RE_CABECALHO: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^\s*Item:?\s+\d+(?:\s+do\s+Grupo\s+G(?P<grupo>\d+))?\s*-?\s*(?P<descricao>[^\n]*)").unwrap()
+    });
+    static RE_SITUACAO: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(?i)Situa[çc][ãa]o:\s*(?P<situacao>Deserto|Fracassado|Cancelad[oa](?:\s+no\s+julgamento)?)").unwrap()
+    });
+    static RE_QUANTIDADE: Lazy<Regex> = Lazy::new(|| Regex::new(r"Quantidade:\s*(\d+)").unwrap());
+    static RE_VALOR: Lazy<Regex> = Lazy::new(|| Regex::new(r"Valor\s+(?:unit[áa]rio\s+)?estimado:\s*R\$\s*([\d,\.]+)").unwrap());
+
+    blocos_de_itens(text).into_iter().filter_map(|(item, bloco)| {
+        let situacao = RE_SITUACAO.captures(bloco)?;
+        let situacao = match situacao["situacao"].to_lowercase() {
+            s if s.starts_with("deserto") => SITUACAO_DESERTO,
+            s if s.starts_with("fracassado") => SITUACAO_FRACASSADO,
+            _ => SITUACAO_CANCELADO,
+        };
+
+        let cabecalho = RE_CABECALHO.captures(bloco);
+        let grupo = cabecalho.as_ref()
+            .and_then(|caps| caps.name("grupo"))
+            .map(|g| format!("G{}", g.as_str()));
+        let descricao = cabecalho.as_ref()
+            .and_then(|caps| caps.name("descricao"))
+            .map(|d| d.as_str().trim())
+            .filter(|d| !d.is_empty())
+            .unwrap_or("N/A");
+        let capturar = |re: &Regex| re.captures(bloco).map_or("N/A".to_string(), |caps| caps[1].trim().to_string());
+
+        Some(PropostaAdjudicada {
+            item,
+            grupo: grupo.clone(),
+            descricao: descricao.to_string(),
+            quantidade: capturar(&RE_QUANTIDADE),
+            valor_estimado: Valor::de_texto(&capturar(&RE_VALOR)),
+            valor_adjudicado: None,
+            valor_adjudicado_raw: String::new(),
+            fornecedor: "N/A".to_string(),
+            cnpj: "N/A".to_string(),
+            melhor_lance: None,
+            responsavel: "N/A".to_string(),
+            cpf_responsavel: "N/A".to_string(),
+            marca_fabricante: "N/A".to_string(),
+            modelo_versao: "N/A".to_string(),
+            tipo_formato: if grupo.is_some() { "grupo" } else { "individual" }.to_string(),
+            situacao: situacao.to_string(),
+            // Sem fornecedor, não há CNPJ a validar
+            cnpj_valido: true,
+            descricao_reparada: false,
+            descricao_original: None,
+            observacoes: None,
+            porte: None,
+        })
+    }).collect()
+}
+
+/// Extrai os lances de um item das tabelas "Lances"/"Eventos do Item", em ordem cronológica
+///
+/// Cada linha com data/hora e valor vira um lance; o restante da linha é o participante.
+pub fn extrair_lances(text: &str, grupo: Option<&str>, item: &str) -> Vec<LanceItem> {
+    let Some(bloco) = bloco_do_item(text, grupo, item) else {
+        return Vec::new();
+    };
+
+    static RE_SECAO: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?mi)^\s*(Lances|Eventos do Item)\b").unwrap());
+    let Some(secao) = RE_SECAO.find(bloco) else {
+        return Vec::new();
+    };
+
+    static RE_DATA_HORA: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(\d{2}/\d{2}/\d{4})\s+(\d{2}:\d{2}:\d{2})(?::(\d{1,3}))?").unwrap()
+    });
+    static RE_VALOR_LANCE: Lazy<Regex> = Lazy::new(|| Regex::new(r"R\$\s*([\d\.]+,\d+)").unwrap());
+
+    let mut lances: Vec<(chrono::NaiveDateTime, LanceItem)> = bloco[secao.end()..].lines()
+        .filter_map(|linha| {
+            let data_hora = RE_DATA_HORA.captures(linha)?;
+            let valor = RE_VALOR_LANCE.captures(linha)?;
+
+            let milissegundos: i64 = data_hora.get(3).map_or(0, |m| m.as_str().parse().unwrap_or(0));
+            let instante = chrono::NaiveDateTime::parse_from_str(
+                &format!("{} {}", &data_hora[1], &data_hora[2]),
+                "%d/%m/%Y %H:%M:%S",
+            ).ok()? + chrono::Duration::milliseconds(milissegundos);
+
+            let participante = linha
+                .replace(&data_hora[0], " ")
+                .replace(&valor[0], " ");
+            let participante = participante.split_whitespace().collect::<Vec<_>>().join(" ");
+
+            Some((instante, LanceItem {
+                data_hora: data_hora[0].to_string(),
+                participante: if participante.is_empty() { "N/A".to_string() } else { participante },
+                valor: valor[1].to_string(),
+            }))
+        })
+        .collect();
+
+    // Ordenação estável: lances no mesmo instante mantêm a ordem do documento
+    lances.sort_by_key(|(instante, _)| *instante);
+    lances.into_iter().map(|(_, lance)| lance).collect()
+}
+
+/// Extrai todos os participantes da tabela "Propostas do Item", inclusive desclassificados
+///
+/// Cada linha que começa com um CNPJ é uma proposta; linhas de continuação (motivos de
+/// desclassificação, por exemplo) são ignoradas. A tabela termina na seção de lances/eventos.
+pub fn extrair_participantes_item(text: &str, grupo: Option<&str>, item: &str) -> Vec<ParticipanteItem> {
+    let Some(bloco) = bloco_do_item(text, grupo, item) else {
+        return Vec::new();
+    };
+
+    static RE_SECAO: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?mi)^\s*Propostas do Item\b").unwrap());
+    let Some(secao) = RE_SECAO.find(bloco) else {
+        return Vec::new();
+    };
+
+    static RE_FIM: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^\s*(Lances|Eventos do Item)\b").unwrap());
+    static RE_PARTICIPANTE: Lazy<Regex> = Lazy::new(|| Regex::new(
+        r"^\s*(?P<cnpj>\d{2}\.\d{3}\.\d{3}/\d{4}-\d{2})\s+(?P<fornecedor>.+?)\s*(?:\b(?P<porte>Sim|Não|Nao)\b)?\s*(?:R\$\s*(?P<valor>[\d\.]+,\d+))?\s*(?P<classificacao>Aceita|Recusada|Desclassificada|Classificada|Adjudicada|Habilitada|Inabilitada)?\s*$"
+    ).unwrap());
+
+    bloco[secao.end()..].lines()
+        .take_while(|linha| !RE_FIM.is_match(linha))
+        .filter_map(|linha| RE_PARTICIPANTE.captures(linha))
+        .map(|caps| {
+            let texto = |nome: &str| caps.name(nome).map_or("N/A".to_string(), |m| m.as_str().trim().to_string());
+            ParticipanteItem {
+                fornecedor: texto("fornecedor"),
+                cnpj: texto("cnpj"),
+                valor_proposta: texto("valor"),
+                porte_me_epp: caps.name("porte").is_some_and(|m| m.as_str() == "Sim"),
+                classificacao: texto("classificacao"),
+            }
+        })
+        .collect()
+}
+
+/// Valor monetário que não pôde ser interpretado
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub valor: String,
+    pub motivo: &'static str,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "valor '{}' não interpretado: {}", self.valor, self.motivo)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Move um PDF processado para a subpasta Processados do diretório de entrada
+///
+/// O nome é preservado; colisões recebem sufixo numérico. Arquivos abertos em outro
+/// programa (bloqueados no Windows) retornam erro e permanecem onde estão.
+pub fn arquivar_pdf(pdf_path: &Path, input_dir: &Path) -> Result<PathBuf> {
+    let pasta = input_dir.join(paths::PASTA_PROCESSADOS);
+    fs::create_dir_all(&pasta)
+        .context(format!("Erro ao criar pasta {}", pasta.display()))?;
+
+    let destino = paths::caminho_sem_colisao(&pasta, pdf_path.file_name().unwrap_or_default());
+    fs::rename(pdf_path, &destino)
+        .context(format!("Erro ao arquivar {}", pdf_path.display()))?;
+
+    Ok(destino)
 }
 
 /// Converte string de valor para float
-pub fn converter_valor_para_float(valor_str: &str) -> f64 {
-    valor_str.replace(".", "")
-        .replace(",", ".")
-        .parse::<f64>()
-        .unwrap_or(0.0)
+///
+/// Aceita "R$", espaços, sinal negativo (ou parênteses) e os formatos brasileiro
+/// ("1.234,56") e com ponto decimal ("1234.56"). O separador decimal é o último
+/// separador quando há ponto e vírgula; com um único ponto seguido de três dígitos
+/// ("1.234") o ponto é de milhar.
+pub fn converter_valor_para_float(valor_str: &str) -> Result<f64, ParseError> {
+    let erro = |motivo| ParseError { valor: valor_str.to_string(), motivo };
+
+    let compacto: String = valor_str.chars().filter(|c| !c.is_whitespace()).collect();
+    let (texto, entre_parenteses) = match compacto.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        Some(interno) => (interno, true),
+        None => (compacto.as_str(), false),
+    };
+
+    // O sinal pode vir antes ou depois do "R$"
+    let (texto, sinal_antes) = texto.strip_prefix('-').map_or((texto, false), |t| (t, true));
+    let texto = texto.strip_prefix("R$").unwrap_or(texto);
+    let (texto, sinal_depois) = texto.strip_prefix('-').map_or((texto, false), |t| (t, true));
+    let sinais = [entre_parenteses, sinal_antes, sinal_depois].iter().filter(|&&s| s).count();
+    if sinais > 1 {
+        return Err(erro("sinal negativo repetido"));
+    }
+    let negativo = sinais == 1;
+
+    if !texto.chars().any(|c| c.is_ascii_digit()) {
+        return Err(erro("nenhum dígito encontrado"));
+    }
+    if texto.chars().any(|c| !c.is_ascii_digit() && c != '.' && c != ',') {
+        return Err(erro("caractere inesperado"));
+    }
+
+    let pontos = texto.matches('.').count();
+    let virgulas = texto.matches(',').count();
+    let decimal = match (pontos, virgulas) {
+        (0, 0) => None,
+        // Formato misto: o último separador é o decimal
+        (p, v) if p > 0 && v > 0 => texto.rfind(['.', ',']).map(|pos| texto.as_bytes()[pos] as char),
+        (1, 0) => {
+            let (inteiro, fracao) = texto.split_once('.').unwrap_or_default();
+            let milhar = fracao.len() == 3 && !inteiro.is_empty() && inteiro != "0";
+            if milhar { None } else { Some('.') }
+        }
+        (0, 1) => Some(','),
+        // Vários separadores iguais: todos de milhar
+        _ => None,
+    };
+
+    let (inteiro, fracao) = match decimal {
+        Some(separador) => texto.rsplit_once(separador).unwrap_or((texto, "")),
+        None => (texto, ""),
+    };
+    if decimal.is_some_and(|separador| fracao.is_empty() || inteiro.contains(separador)) {
+        return Err(erro("separador decimal inválido"));
+    }
+
+    // O separador de milhar deve agrupar de três em três dígitos
+    let grupos: Vec<&str> = inteiro.split(['.', ',']).collect();
+    if grupos.len() > 1 && (grupos[0].is_empty() || grupos[0].len() > 3 || grupos[1..].iter().any(|g| g.len() != 3)) {
+        return Err(erro("separador de milhar inválido"));
+    }
+
+    let numero = format!("{}.{}", grupos.concat(), if fracao.is_empty() { "0" } else { fracao });
+    let valor: f64 = numero.parse().map_err(|_| erro("número inválido"))?;
+    Ok(if negativo { -valor } else { valor })
 }
 
-/// Gera markdown a partir do relatório
-fn gerar_markdown(relatorio: &RelatorioLicitacao) -> Result<String> {
+/// Gera markdown a partir dos relatórios (um por pregão encontrado no PDF)
+fn gerar_markdown(relatorios: &[RelatorioLicitacao], consolidadas_por_secao: &[Vec<PropostaConsolidada>]) -> Result<String> {
     let mut markdown = String::new();
     
     // Cabeçalho
@@ -403,238 +1532,2022 @@ fn gerar_markdown(relatorio: &RelatorioLicitacao) -> Result<String> {
     // Título
     markdown.push_str("# RELATÓRIO DE LICITAÇÃO - PROPOSTAS ADJUDICADAS\n\n");
     
+    if let ([relatorio], [consolidadas]) = (relatorios, consolidadas_por_secao) {
+        secao_markdown(&mut markdown, relatorio, consolidadas, 2);
+    } else {
+        // Vários pregões no mesmo PDF: uma seção para cada, com os títulos um nível abaixo
+        for (relatorio, consolidadas) in relatorios.iter().zip(consolidadas_por_secao) {
+            markdown.push_str(&format!("## Pregão {} - UASG {}\n\n", relatorio.pregao, relatorio.uasg));
+            secao_markdown(&mut markdown, relatorio, consolidadas, 3);
+            markdown.push('\n');
+        }
+    }
+    
+    Ok(markdown)
+}
+
+/// Informações, propostas e resumo de um pregão; `nivel` é o nível dos títulos das subseções
+///
+/// `consolidadas` são as propostas do relatório já consolidadas, na mesma ordem: delas vêm os
+/// valores do item inteiro (nas atas com valores unitários, quantidade × valor) da economia.
+fn secao_markdown(markdown: &mut String, relatorio: &RelatorioLicitacao, consolidadas: &[PropostaConsolidada], nivel: usize) {
+    let titulo = "#".repeat(nivel);
+    let subtitulo = "#".repeat(nivel + 1);
+    
     // Informações gerais
-    markdown.push_str("## Informações Gerais\n\n");
+    markdown.push_str(&format!("{} Informações Gerais\n\n", titulo));
     markdown.push_str(&format!("- **UASG**: {}\n", relatorio.uasg));
     markdown.push_str(&format!("- **Pregão**: {}\n", relatorio.pregao));
     markdown.push_str(&format!("- **Processo**: {}\n", relatorio.processo));
     markdown.push_str(&format!("- **Data de Homologação**: {}\n", relatorio.data_homologacao));
-    markdown.push_str(&format!("- **Responsável**: {}\n", relatorio.responsavel));
+    markdown.push_str(&format!("- **Responsável**: {}\n", relatorio.responsavel_nome));
+    if let Some(cargo) = &relatorio.responsavel_cargo {
+        markdown.push_str(&format!("- **Cargo**: {}\n", cargo));
+    }
+    if let Some(matricula) = &relatorio.responsavel_matricula {
+        markdown.push_str(&format!("- **Matrícula**: {}\n", matricula));
+    }
     markdown.push_str(&format!("- **Valor Total**: R$ {:.2}\n\n", relatorio.valor_total));
     
+    for aviso in &relatorio.avisos {
+        markdown.push_str(&format!("> ⚠️ {}\n\n", aviso));
+    }
+    
     // Tabela de propostas
-    markdown.push_str("## Propostas Adjudicadas\n\n");
+    markdown.push_str(&format!("{} Propostas Adjudicadas\n\n", titulo));
+    
+    let (adjudicadas, nao_adjudicadas): (Vec<_>, Vec<_>) = relatorio.propostas
+        .iter()
+        .zip(consolidadas)
+        .partition(|(p, _)| p.situacao == SITUACAO_ADJUDICADO);
     
     // Verificar se há propostas por grupo
-    let tem_grupos = relatorio.propostas.iter().any(|p| p.grupo.is_some());
+    let tem_grupos = adjudicadas.iter().any(|(p, _)| p.grupo.is_some());
     
     if tem_grupos {
-        markdown.push_str("| Item | Grupo | Descrição | Quantidade | Valor Estimado | Valor Adjudicado | Fornecedor | CNPJ | Marca/Fabricante | Modelo/Versão |\n");
-        markdown.push_str("|------|--------|-----------|------------|----------------|------------------|------------|------|------------------|---------------|\n");
+        markdown.push_str("| Item | Grupo | Descrição | Quantidade | Valor Estimado | Valor Adjudicado | Economia | Fornecedor | CNPJ | Porte | Marca/Fabricante | Modelo/Versão |\n");
+        markdown.push_str("|------|--------|-----------|------------|----------------|------------------|----------|------------|------|-------|------------------|---------------|\n");
     } else {
-        markdown.push_str("| Item | Descrição | Quantidade | Valor Estimado | Valor Adjudicado | Fornecedor | CNPJ | Marca/Fabricante | Modelo/Versão |\n");
-        markdown.push_str("|------|-----------|------------|----------------|------------------|------------|------|------------------|---------------|\n");
+        markdown.push_str("| Item | Descrição | Quantidade | Valor Estimado | Valor Adjudicado | Economia | Fornecedor | CNPJ | Porte | Marca/Fabricante | Modelo/Versão |\n");
+        markdown.push_str("|------|-----------|------------|----------------|------------------|----------|------------|------|-------|------------------|---------------|\n");
     }
     
-    for proposta in &relatorio.propostas {
+    for (proposta, consolidada) in &adjudicadas {
+        let (economia_absoluta, economia_percentual) = economia(consolidada.estimado_do_item(), consolidada.total_do_item());
         if tem_grupos {
             markdown.push_str(&format!(
-                "| {} | {} | {} | {} | R$ {} | R$ {} | {} | {} | {} | {} |\n",
+                "| {} | {} | {} | {} | R$ {} | R$ {} | {} | {} | {} | {} | {} | {} |\n",
                 proposta.item,
                 proposta.grupo.as_ref().unwrap_or(&"N/A".to_string()),
                 proposta.descricao,
                 proposta.quantidade,
-                proposta.valor_estimado,
-                proposta.valor_adjudicado,
+                formatar_valor(proposta.valor_estimado),
+                formatar_valor(proposta.valor_adjudicado),
+                formatar_economia(economia_absoluta, economia_percentual),
                 proposta.fornecedor,
                 proposta.cnpj,
+                proposta.porte.as_deref().unwrap_or("N/A"),
                 proposta.marca_fabricante,
                 proposta.modelo_versao
             ));
         } else {
             markdown.push_str(&format!(
-                "| {} | {} | {} | R$ {} | R$ {} | {} | {} | {} | {} |\n",
+                "| {} | {} | {} | R$ {} | R$ {} | {} | {} | {} | {} | {} | {} |\n",
                 proposta.item,
                 proposta.descricao,
                 proposta.quantidade,
-                proposta.valor_estimado,
-                proposta.valor_adjudicado,
+                formatar_valor(proposta.valor_estimado),
+                formatar_valor(proposta.valor_adjudicado),
+                formatar_economia(economia_absoluta, economia_percentual),
                 proposta.fornecedor,
                 proposta.cnpj,
+                proposta.porte.as_deref().unwrap_or("N/A"),
                 proposta.marca_fabricante,
                 proposta.modelo_versao
             ));
         }
     }
     
+    // Linha de total: só os itens com valor estimado entram na comparação
+    let resumo_economia = ResumoEconomia::somar(adjudicadas.iter().map(|(_, c)| (c.estimado_do_item(), c.total_do_item())));
+    if !adjudicadas.is_empty() {
+        let colunas_antes = if tem_grupos { "| **Total** | | | " } else { "| **Total** | | " };
+        markdown.push_str(&format!(
+            "{}| **R$ {}** | **R$ {}** | **{}** | | | | | |\n",
+            colunas_antes,
+            Valor(resumo_economia.valor_estimado_total),
+            Valor(resumo_economia.valor_adjudicado_total),
+            formatar_economia(Some(Valor(resumo_economia.economia_total)), resumo_economia.economia_percentual),
+        ));
+    }
+    
     // Detalhes das propostas
-    markdown.push_str("\n## Detalhes das Propostas\n\n");
+    markdown.push_str(&format!("\n{} Detalhes das Propostas\n\n", titulo));
     
-    for proposta in &relatorio.propostas {
+    for (proposta, _) in &adjudicadas {
         let grupo_info = if let Some(grupo) = &proposta.grupo {
             format!(" ({}) ", grupo)
         } else {
             " ".to_string()
         };
         
-        markdown.push_str(&format!("### Item {}{}- {}\n\n", proposta.item, grupo_info, proposta.descricao));
+        markdown.push_str(&format!("{} Item {}{}- {}\n\n", subtitulo, proposta.item, grupo_info, proposta.descricao));
         markdown.push_str(&format!("- **Quantidade**: {}\n", proposta.quantidade));
-        markdown.push_str(&format!("- **Valor Estimado**: R$ {}\n", proposta.valor_estimado));
-        markdown.push_str(&format!("- **Valor Adjudicado**: R$ {}\n", proposta.valor_adjudicado));
+        markdown.push_str(&format!("- **Valor Estimado**: R$ {}\n", formatar_valor(proposta.valor_estimado)));
+        markdown.push_str(&format!("- **Valor Adjudicado**: R$ {}\n", formatar_valor(proposta.valor_adjudicado)));
         markdown.push_str(&format!("- **Fornecedor**: {}\n", proposta.fornecedor));
         markdown.push_str(&format!("- **CNPJ**: {}\n", proposta.cnpj));
-        markdown.push_str(&format!("- **Melhor Lance**: R$ {}\n", proposta.melhor_lance));
+        if let Some(porte) = &proposta.porte {
+            markdown.push_str(&format!("- **Porte**: {}\n", porte));
+        }
+        markdown.push_str(&format!("- **Melhor Lance**: R$ {}\n", formatar_valor(proposta.melhor_lance)));
         markdown.push_str(&format!("- **Responsável**: {}\n", proposta.responsavel));
         markdown.push_str(&format!("- **CPF Responsável**: {}\n", proposta.cpf_responsavel));
         markdown.push_str(&format!("- **Marca/Fabricante**: {}\n", proposta.marca_fabricante));
-        markdown.push_str(&format!("- **Modelo/Versão**: {}\n\n", proposta.modelo_versao));
+        markdown.push_str(&format!("- **Modelo/Versão**: {}\n", proposta.modelo_versao));
+        if let Some(observacoes) = &proposta.observacoes {
+            markdown.push_str(&format!("- **Observações**: {}\n", observacoes));
+        }
+        markdown.push('\n');
+    }
+    
+    // Itens desertos, fracassados ou cancelados também contam no total do pregão
+    if !nao_adjudicadas.is_empty() {
+        markdown.push_str(&format!("{} Itens não adjudicados\n\n", titulo));
+        markdown.push_str("| Item | Grupo | Descrição | Quantidade | Valor Estimado | Situação |\n");
+        markdown.push_str("|------|-------|-----------|------------|----------------|----------|\n");
+        
+        for (proposta, _) in &nao_adjudicadas {
+            markdown.push_str(&format!(
+                "| {} | {} | {} | {} | R$ {} | {} |\n",
+                proposta.item,
+                proposta.grupo.as_deref().unwrap_or("N/A"),
+                proposta.descricao,
+                proposta.quantidade,
+                formatar_valor(proposta.valor_estimado),
+                proposta.situacao
+            ));
+        }
+        markdown.push('\n');
     }
     
     // Resumo estatístico
-    markdown.push_str("## Resumo Estatístico\n\n");
-    markdown.push_str(&format!("- **Total de Itens Adjudicados**: {}\n", relatorio.propostas.len()));
+    markdown.push_str(&format!("{} Resumo Estatístico\n\n", titulo));
+    markdown.push_str(&format!("- **Total de Itens Adjudicados**: {}\n", adjudicadas.len()));
+    if !nao_adjudicadas.is_empty() {
+        markdown.push_str(&format!("- **Total de Itens Não Adjudicados**: {}\n", nao_adjudicadas.len()));
+    }
     markdown.push_str(&format!("- **Valor Total das Adjudicações**: R$ {:.2}\n", relatorio.valor_total));
     
-    if !relatorio.propostas.is_empty() {
-        let valor_medio = relatorio.valor_total / relatorio.propostas.len() as f64;
+    if !adjudicadas.is_empty() {
+        let valor_medio = relatorio.valor_total / adjudicadas.len() as f64;
         markdown.push_str(&format!("- **Valor Médio por Item**: R$ {:.2}\n", valor_medio));
+        markdown.push_str(&format!(
+            "- **Economia Total**: {}\n",
+            formatar_economia(Some(Valor(resumo_economia.economia_total)), resumo_economia.economia_percentual),
+        ));
+        if resumo_economia.itens_sem_valor_estimado > 0 {
+            markdown.push_str(&format!(
+                "- **Itens sem Valor Estimado** (fora do cálculo da economia): {}\n",
+                resumo_economia.itens_sem_valor_estimado,
+            ));
+        }
+        
+        // Cota reservada da LC 123: quanto foi para ME/EPP
+        let resumo_porte = ResumoPorte::somar(adjudicadas.iter().map(|(p, c)| (p.porte.as_deref(), c.total_do_item())));
+        if resumo_porte.itens_me_epp + resumo_porte.itens_demais > 0 {
+            let percentual = resumo_porte.percentual_me_epp
+                .map(|p| format!(" ({}%)", crate::exportacao::formatar_numero_br(p)))
+                .unwrap_or_default();
+            markdown.push_str(&format!(
+                "- **Adjudicado a ME/EPP**: R$ {} em {} itens{}\n",
+                Valor(resumo_porte.valor_me_epp), resumo_porte.itens_me_epp, percentual,
+            ));
+            markdown.push_str(&format!(
+                "- **Adjudicado aos Demais**: R$ {} em {} itens\n",
+                Valor(resumo_porte.valor_demais), resumo_porte.itens_demais,
+            ));
+            if resumo_porte.itens_sem_porte > 0 {
+                markdown.push_str(&format!("- **Itens sem Porte Informado**: {}\n", resumo_porte.itens_sem_porte));
+            }
+        }
     }
-    
-    Ok(markdown)
 }
 
-/// Extrai UASG do texto
-fn extrair_uasg(text: &str) -> String {
-    let re = Regex::new(r"UASG\s*(\d+)").unwrap();
-    if let Some(caps) = re.captures(text) {
-        caps.get(1).unwrap().as_str().to_string()
-    } else {
-        "N/A".to_string()
+/// Economia para relatórios: "R$ 10,00 (5,00%)", sem o percentual quando não há estimado positivo
+pub(crate) fn formatar_economia(absoluta: Option<Valor>, percentual: Option<f64>) -> String {
+    match (absoluta, percentual) {
+        (Some(absoluta), Some(percentual)) => format!("R$ {} ({}%)", absoluta, crate::exportacao::formatar_numero_br(percentual)),
+        (Some(absoluta), None) => format!("R$ {}", absoluta),
+        _ => "N/A".to_string(),
     }
 }
 
-/// Extrai pregão do texto
-fn extrair_pregao(text: &str) -> String {
-    let re = Regex::new(r"PREGÃO\s*(\d+/\d+)").unwrap();
-    if let Some(caps) = re.captures(text) {
-        caps.get(1).unwrap().as_str().to_string()
-    } else {
-        "N/A".to_string()
-    }
+pub(crate) static RE_UASG: Lazy<Regex> = Lazy::new(|| Regex::new(r"UASG\s*(\d+)").unwrap());
+
+/// Extrai UASG do texto, já com 6 dígitos
+pub(crate) fn extrair_uasg(text: &str) -> String {
+    RE_UASG.captures(text)
+        .and_then(|caps| validators::normalizar_uasg(&caps[1]))
+        .unwrap_or_else(|| "N/A".to_string())
 }
 
+/// UASG na forma usada nas chaves: 6 dígitos quando válida, ou o texto original (ex.: "N/A")
+pub fn uasg_canonica(uasg: &str) -> String {
+    validators::normalizar_uasg(uasg).unwrap_or_else(|| uasg.trim().to_string())
+}
+
+/// Complemento opcional entre a modalidade e o número: "Eletrônico", "de Licitação", "SRP", "nº"
+const COMPLEMENTO_CERTAME: &str = r"(?:\s+ELETR[ÔO]NIC[OA])?(?:\s+DE\s+LICITA[ÇC][ÃA]O)?(?:\s*[-–(]?\s*SRP\)?)?\s*(?:N\.?\s*[º°O]\.?\s*)?(\d[\d.]*/\d{4})";
+
+/// Padrões de cabeçalho de cada modalidade, tentados em conjunto (vale o que aparece primeiro)
+pub(crate) static RE_CERTAMES: Lazy<Vec<(&'static str, Regex)>> = Lazy::new(|| {
+    [
+        (MODALIDADE_PREGAO, r"PREG[ÃA]O"),
+        (MODALIDADE_DISPENSA, r"DISPENSA"),
+        (MODALIDADE_CONCORRENCIA, r"CONCORR[ÊE]NCIA"),
+    ]
+    .into_iter()
+    .map(|(modalidade, palavra)| (modalidade, Regex::new(&format!("(?i){}{}", palavra, COMPLEMENTO_CERTAME)).unwrap()))
+    .collect()
+});
+
+/// Extrai a modalidade e o número do certame do cabeçalho
+///
+/// O número perde os pontos de milhar ("90.012/2024" vira "90012/2024"). Sem cabeçalho
+/// reconhecido, retorna a modalidade pregão com o número "N/A".
+pub fn extrair_certame(text: &str) -> (String, String) {
+    RE_CERTAMES.iter()
+        .filter_map(|(modalidade, re)| re.captures(text).map(|caps| (caps.get(0).unwrap().start(), *modalidade, caps)))
+        .min_by_key(|(inicio, _, _)| *inicio)
+        .map_or_else(
+            || (MODALIDADE_PREGAO.to_string(), "N/A".to_string()),
+            |(_, modalidade, caps)| (modalidade.to_string(), caps[1].replace('.', "")),
+        )
+}
+
+pub(crate) static RE_PROCESSO: Lazy<Regex> = Lazy::new(|| Regex::new(r"Processo\s*n[ºo°]?\s*(\d+)").unwrap());
+
 /// Extrai processo do texto
-fn extrair_processo(text: &str) -> String {
-    let re = Regex::new(r"Processo\s*n[ºo°]?\s*(\d+)").unwrap();
-    if let Some(caps) = re.captures(text) {
+pub(crate) fn extrair_processo(text: &str) -> String {
+    if let Some(caps) = RE_PROCESSO.captures(text) {
         caps.get(1).unwrap().as_str().to_string()
     } else {
         "N/A".to_string()
     }
 }
 
+pub(crate) static RE_DATA_HOMOLOGACAO: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:[ÀA]s\s*([\d:]+)\s*horas\s*)?do\s*dia\s*([\d]+)\s*de\s*(\w+)\s*do\s*ano\s*de\s*([\d]+)").unwrap());
+
 /// Extrai data de homologação do texto
-fn extrair_data_homologacao(text: &str) -> String {
-    let re = Regex::new(r"Às\s*([\d:]+)\s*horas\s*do\s*dia\s*([\d]+)\s*de\s*(\w+)\s*do\s*ano\s*de\s*([\d]+)").unwrap();
-    if let Some(caps) = re.captures(text) {
-        format!("Às {} horas do dia {} de {} do ano de {}", 
-                caps.get(1).unwrap().as_str(),
-                caps.get(2).unwrap().as_str(),
-                caps.get(3).unwrap().as_str(),
-                caps.get(4).unwrap().as_str())
-    } else {
-        "N/A".to_string()
+pub(crate) fn extrair_data_homologacao(text: &str) -> String {
+    let Some(caps) = RE_DATA_HOMOLOGACAO.captures(text) else {
+        return "N/A".to_string();
+    };
+    let data = format!("do dia {} de {} do ano de {}", &caps[2], &caps[3], &caps[4]);
+    match caps.get(1) {
+        Some(hora) => format!("Às {} horas {}", hora.as_str(), data),
+        None => format!("Dia {}", data.trim_start_matches("do dia ")),
     }
 }
 
-/// Extrai responsável do texto
-fn extrair_responsavel(text: &str) -> String {
-    let re = Regex::new(r"HOMOLOGA\s*a\s*adjudicação.*?([A-Z][A-Z\s]+),").unwrap();
-    if let Some(caps) = re.captures(text) {
-        caps.get(1).unwrap().as_str().trim().to_string()
-    } else {
-        "N/A".to_string()
+/// Fuso das atas do Compras.gov.br (horário de Brasília, sem horário de verão desde 2019)
+const FUSO_BRASILIA_SEGUNDOS: i32 = -3 * 3600;
+
+/// Número do mês por extenso, sem diferenciar caixa nem acentos ("Março", "marco")
+fn numero_do_mes(nome: &str) -> Option<u32> {
+    const MESES: [&str; 12] = [
+        "janeiro", "fevereiro", "marco", "abril", "maio", "junho",
+        "julho", "agosto", "setembro", "outubro", "novembro", "dezembro",
+    ];
+    let nome = resultados::normalizar_busca(nome);
+    MESES.iter().position(|mes| *mes == nome).map(|indice| indice as u32 + 1)
+}
+
+/// Converte a data de homologação por extenso em RFC 3339 no horário de Brasília
+///
+/// Aceita o texto com ou sem hora ("Às 10:32 horas do dia 5 de março do ano de 2024",
+/// "dia 5 de marco de 24"); sem hora, vale a meia-noite. Anos de dois dígitos são do século 21.
+pub fn converter_data_homologacao(texto: &str) -> Option<String> {
+    static RE: Lazy<Regex> = Lazy::new(|| Regex::new(
+        r"(?i)(?:[àa]s\s*(\d{1,2})(?::(\d{2}))?\s*h(?:oras?)?\s*)?(?:do\s+)?dia\s+(\d{1,2})\s+de\s+(\w+)\s+(?:do\s+ano\s+)?de\s+(\d{4}|\d{2})\b"
+    ).unwrap());
+    let caps = RE.captures(texto)?;
+    let numero = |indice: usize| caps.get(indice).and_then(|m| m.as_str().parse::<u32>().ok());
+
+    let ano = match caps[5].len() {
+        2 => 2000 + caps[5].parse::<i32>().ok()?,
+        _ => caps[5].parse::<i32>().ok()?,
+    };
+    let data = chrono::NaiveDate::from_ymd_opt(ano, numero_do_mes(&caps[4])?, numero(3)?)?;
+    let momento = data.and_hms_opt(numero(1).unwrap_or(0), numero(2).unwrap_or(0), 0)?;
+    let fuso = chrono::FixedOffset::east_opt(FUSO_BRASILIA_SEGUNDOS)?;
+    Some(momento.and_local_timezone(fuso).single()?.to_rfc3339())
+}
+
+/// Extrai a assinatura da homologação: o nome em caixa alta e o que vem depois dele na linha
+fn extrair_responsavel(text: &str) -> Option<ResponsavelHomologacao> {
+    static RE: Lazy<Regex> = Lazy::new(|| Regex::new(
+        r"HOMOLOGA\s*a\s*adjudica[çc][ãa]o.*?\b(\p{Lu}{2,}(?:\s+\p{Lu}+\b)*[^\n]*)"
+    ).unwrap());
+    separar_responsavel(&RE.captures(text)?[1])
+}
+
+/// Separa nome, cargo e matrícula de uma assinatura
+///
+/// O nome é a sequência inicial de palavras em caixa alta; a matrícula vem depois de "matrícula"
+/// (com ou sem "nº"/":"); o cargo é o que sobra entre os dois, sem depender das vírgulas.
+fn separar_responsavel(assinatura: &str) -> Option<ResponsavelHomologacao> {
+    static RE_NOME: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*\p{Lu}+\b(?:\s+\p{Lu}+\b)*").unwrap());
+    static RE_MATRICULA: Lazy<Regex> = Lazy::new(|| Regex::new(
+        r"(?i)[,;\-–]?\s*matr[íi]cula(?:\s*(?:n[º°o]\.?|:))?\s*(\d[\d.\-/]*\d|\d)"
+    ).unwrap());
+    const SEPARADORES: &[char] = &[',', ';', '-', '–', ':', '.', ' '];
+
+    let nome = RE_NOME.find(assinatura)?;
+    let resto = &assinatura[nome.end()..];
+    let (cargo, matricula) = match RE_MATRICULA.captures(resto) {
+        Some(caps) => (&resto[..caps.get(0).unwrap().start()], Some(caps[1].to_string())),
+        None => (resto, None),
+    };
+    let cargo = cargo.trim_matches(SEPARADORES);
+    Some(ResponsavelHomologacao {
+        nome: nome.as_str().trim().to_string(),
+        cargo: (!cargo.is_empty()).then(|| cargo.to_string()),
+        matricula,
+    })
+}
+
+/// Posição de um item na ordem natural: números em ordem numérica, o resto depois
+fn chave_natural(texto: &str) -> (bool, u64, &str) {
+    let texto = texto.trim();
+    match texto.parse::<u64>() {
+        Ok(numero) => (false, numero, ""),
+        Err(_) => (true, 0, texto),
     }
 }
 
-/// Salva JSON consolidado
-pub fn salvar_json_consolidado(
-    propostas: &[PropostaConsolidada], 
-    output_dir: &Path, 
-    _nome_arquivo: &str, 
-    verbose: bool
-) -> Result<()> {
-    let valor_total_geral: f64 = propostas.iter()
-        .map(|p| converter_valor_para_float(&p.valor_adjudicado))
-        .sum();
-    
-    // Agrupar propostas por UASG + Pregão + Processo
-    let mut licitacoes: HashMap<String, LicitacaoConsolidada> = HashMap::new();
+/// Posição de um grupo na ordem natural
+///
+/// Os grupos são gravados como "G1", "G2"...; o prefixo é ignorado para que "G10" venha depois de "G2".
+/// Nos itens ele não é removido: um item "G5" não é o item 5.
+fn chave_grupo(grupo: &str) -> (bool, u64, &str) {
+    let grupo = grupo.trim();
+    match chave_natural(grupo.strip_prefix(['G', 'g']).unwrap_or(grupo)) {
+        (false, numero, _) => (false, numero, ""),
+        _ => (true, 0, grupo),
+    }
+}
+
+/// Compara pares (grupo, item): itens sem grupo primeiro, depois grupo e item em ordem natural
+pub fn comparar_por_item((grupo_a, item_a): (Option<&str>, &str), (grupo_b, item_b): (Option<&str>, &str)) -> std::cmp::Ordering {
+    grupo_a.map(chave_grupo).cmp(&grupo_b.map(chave_grupo))
+        .then_with(|| chave_natural(item_a).cmp(&chave_natural(item_b)))
+}
+
+/// Ordena por grupo (itens sem grupo primeiro) e item, em ordem natural
+///
+/// A ordenação é estável: propostas do mesmo item mantêm a ordem da extração.
+pub fn ordenar_por_item<T>(itens: &mut [T], grupo_e_item: impl Fn(&T) -> (Option<&str>, &str)) {
+    itens.sort_by(|a, b| comparar_por_item(grupo_e_item(a), grupo_e_item(b)));
+}
+
+/// Ordena as propostas por grupo e item ("1, 2, 10" em vez de "1, 10, 2"); itens não numéricos
+/// ("N/A", "1A") ficam no final, em ordem alfabética
+pub fn ordenar_propostas(propostas: &mut [PropostaConsolidada]) {
+    ordenar_por_item(propostas, |p| (p.grupo.as_deref(), p.item.as_str()));
+}
+
+/// Chave que identifica uma proposta repetida (mesmo PDF processado mais de uma vez)
+///
+/// O grupo faz parte da chave: atas com grupos repetem a numeração dos itens em cada grupo.
+pub fn chave_deduplicacao(proposta: &PropostaConsolidada) -> (String, String, String, String, String, String) {
+    (
+        uasg_canonica(&proposta.uasg),
+        proposta.pregao.trim().to_string(),
+        proposta.processo.trim().to_string(),
+        proposta.grupo.as_deref().map(|grupo| grupo.trim().to_uppercase()).unwrap_or_default(),
+        proposta.item.trim().to_string(),
+        proposta.cnpj.chars().filter(|c| c.is_ascii_digit()).collect(),
+    )
+}
+
+/// Remove propostas repetidas por (uasg, pregão, processo, grupo, item, cnpj), mantendo a primeira
+///
+/// Retorna as propostas únicas e a quantidade de duplicatas descartadas.
+pub fn deduplicar_propostas(propostas: &[PropostaConsolidada]) -> (Vec<PropostaConsolidada>, usize) {
+    let mut vistas = HashSet::new();
+    let unicas: Vec<PropostaConsolidada> = propostas.iter()
+        .filter(|p| vistas.insert(chave_deduplicacao(p)))
+        .cloned()
+        .collect();
+
+    let descartadas = propostas.len() - unicas.len();
+    (unicas, descartadas)
+}
+
+/// Arquivo com todas as licitações de uma gravação, ao lado do resumo geral
+pub const ARQUIVO_CONSOLIDADO: &str = "consolidado.json";
+
+/// Resumo da última gravação, com a lista dos arquivos gerados
+pub const ARQUIVO_RESUMO_GERAL: &str = "resumo_geral.json";
+
+/// Licitação agrupada e o arquivo em que será gravada
+#[derive(Debug, Clone)]
+pub struct LicitacaoAgrupada {
+    pub chave: String,
+    pub nome_arquivo: String,
+    pub destino: PathBuf,
+    pub licitacao: LicitacaoConsolidada,
+}
+
+/// Resultado puro do agrupamento das propostas, antes de qualquer escrita
+#[derive(Debug, Clone)]
+pub struct Consolidacao {
+    /// Ordenadas pela chave, para que a prévia e a gravação sigam a mesma ordem
+    pub licitacoes: Vec<LicitacaoAgrupada>,
+    pub total_propostas: usize,
+    pub valor_total_geral: f64,
+    pub propostas_duplicadas: usize,
+    pub conflitos: Vec<String>,
+    pub itens_duplicados: Vec<String>,
+    /// Valores adjudicados que não puderam ser interpretados (não somados ao total)
+    pub valores_invalidos: Vec<String>,
+    /// Pasta de resultados da consolidação; destinos roteados para fora dela aparecem com o caminho completo
+    pub output_dir: PathBuf,
+}
+
+/// Chave da licitação de uma proposta na consolidação (modalidade, UASG normalizada, número e processo)
+pub fn chave_licitacao(proposta: &PropostaConsolidada) -> String {
+    format!("{}_{}-{}-{}", proposta.modalidade, uasg_canonica(&proposta.uasg), proposta.pregao, proposta.processo)
+}
+
+/// Agrupa as propostas por UASG + Pregão + Processo sem gravar nada
+pub fn consolidar(propostas: &[PropostaConsolidada], output_dir: &Path, regras: &[RoutingRule]) -> Consolidacao {
+    // PDFs repetidos na pasta não podem somar o mesmo valor duas vezes
+    let (propostas, propostas_duplicadas) = deduplicar_propostas(propostas);
     
-    for proposta in propostas {
-        let chave = format!("{}-{}-{}", proposta.uasg, proposta.pregao, proposta.processo);
+    let mut grupos: BTreeMap<String, LicitacaoConsolidada> = BTreeMap::new();
+    let mut valores_invalidos = Vec::new();
+    for proposta in &propostas {
+        let chave = chave_licitacao(proposta);
+        // JSONs antigos podem ter a UASG sem o zero à esquerda e não trazer a economia
+        let mut proposta = PropostaConsolidada { uasg: uasg_canonica(&proposta.uasg), ..proposta.clone() };
+        proposta.calcular_economia();
+        
+        // Itens não adjudicados não têm valor e não contam como valor inválido
+        let valor = match proposta.total_do_item() {
+            Some(valor) => valor.0,
+            None => {
+                if proposta.situacao == SITUACAO_ADJUDICADO {
+                    let motivo = converter_valor_para_float(&proposta.valor_adjudicado_raw)
+                        .err()
+                        .map_or_else(|| "valor ausente".to_string(), |e| e.to_string());
+                    valores_invalidos.push(format!("{}: item {}: {}", chave, proposta.item, motivo));
+                }
+                0.0
+            }
+        };
         
-        let licitacao = licitacoes.entry(chave).or_insert_with(|| LicitacaoConsolidada {
+        let licitacao = grupos.entry(chave).or_insert_with(|| LicitacaoConsolidada {
             uasg: proposta.uasg.clone(),
             pregao: proposta.pregao.clone(),
+            modalidade: proposta.modalidade.clone(),
             processo: proposta.processo.clone(),
             total_propostas: 0,
             valor_total: 0.0,
             propostas: Vec::new(),
+            itens_adjudicados: 0,
+            itens_desertos: 0,
+            itens_fracassados: 0,
+            itens_cancelados: 0,
         });
         
-        licitacao.propostas.push(proposta.clone());
+        match proposta.situacao.as_str() {
+            SITUACAO_DESERTO => licitacao.itens_desertos += 1,
+            SITUACAO_FRACASSADO => licitacao.itens_fracassados += 1,
+            SITUACAO_CANCELADO => licitacao.itens_cancelados += 1,
+            _ => licitacao.itens_adjudicados += 1,
+        }
+        licitacao.propostas.push(proposta);
         licitacao.total_propostas += 1;
-        licitacao.valor_total += converter_valor_para_float(&proposta.valor_adjudicado);
+        licitacao.valor_total += valor;
     }
     
-    let data_geracao = Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string();
-    let mut arquivos_salvos = 0;
+    let mut conflitos = Vec::new();
+    let mut itens_duplicados = Vec::new();
+    let mut arquivos_por_destino: HashMap<PathBuf, String> = HashMap::new();
+    let mut pregoes: HashMap<(String, String), String> = HashMap::new();
+    let mut licitacoes = Vec::new();
     
-    // Salvar um arquivo JSON para cada licitação
-    for (chave, licitacao) in &licitacoes {
-        let nome_arquivo_licitacao = format!("licitacao_{}.json", 
-            chave.replace("/", "_").replace(" ", "_"));
+    for (chave, mut licitacao) in grupos {
+        ordenar_propostas(&mut licitacao.propostas);
+        let nome_arquivo = paths::sanitize_filename(&format!("licitacao_{}.json", chave));
+        let destino = routing::diretorio_destino(regras, output_dir, &licitacao.uasg, &licitacao.pregao);
         
-        let json_licitacao = serde_json::json!({
-            "data_geracao": data_geracao,
-            "uasg": licitacao.uasg,
-            "pregao": licitacao.pregao,
-            "processo": licitacao.processo,
-            "total_propostas": licitacao.total_propostas,
-            "valor_total": licitacao.valor_total,
-            "propostas": licitacao.propostas
-        });
+        if [&licitacao.uasg, &licitacao.pregao, &licitacao.processo].iter().any(|campo| campo.as_str() == "N/A") {
+            conflitos.push(format!("{}: chave com campo N/A ({} propostas)", chave, licitacao.total_propostas));
+        }
         
-        let json_path = output_dir.join(&nome_arquivo_licitacao);
-        let json_content = serde_json::to_string_pretty(&json_licitacao)
-            .context("Erro ao serializar JSON da licitação")?;
+        // Chaves diferentes que resultariam no mesmo arquivo se sobrescreveriam
+        if let Some(outra) = arquivos_por_destino.insert(destino.join(&nome_arquivo), chave.clone()) {
+            conflitos.push(format!("{} e {} gravariam o mesmo arquivo {}", outra, chave, nome_arquivo));
+        }
         
-        fs::write(&json_path, json_content)
-            .context(format!("Erro ao salvar arquivo JSON: {}", nome_arquivo_licitacao))?;
+        if let Some(outro_processo) = pregoes.insert((licitacao.uasg.clone(), licitacao.pregao.clone()), licitacao.processo.clone()) {
+            conflitos.push(format!(
+                "UASG {} pregão {} aparece com processos diferentes ({} e {})",
+                licitacao.uasg, licitacao.pregao, outro_processo, licitacao.processo
+            ));
+        }
         
-        arquivos_salvos += 1;
+        // Mesmo item adjudicado a fornecedores diferentes
+        let mut fornecedores_por_item: BTreeMap<(Option<String>, String), BTreeSet<String>> = BTreeMap::new();
+        for proposta in licitacao.propostas.iter().filter(|p| p.situacao == SITUACAO_ADJUDICADO) {
+            fornecedores_por_item
+                .entry((proposta.grupo.clone(), proposta.item.clone()))
+                .or_default()
+                .insert(proposta.cnpj.clone());
+        }
+        for ((grupo, item), cnpjs) in fornecedores_por_item {
+            if cnpjs.len() > 1 {
+                let grupo = grupo.map(|g| format!(" ({})", g)).unwrap_or_default();
+                itens_duplicados.push(format!("{}: item {}{} com {} fornecedores", chave, item, grupo, cnpjs.len()));
+            }
+        }
         
-        if verbose {
-            println!("📄 JSON licitação salvo: {:?} ({} propostas, R$ {:.2})", 
-                     json_path, licitacao.total_propostas, licitacao.valor_total);
+        licitacoes.push(LicitacaoAgrupada { chave, nome_arquivo, destino, licitacao });
+    }
+    
+    Consolidacao {
+        total_propostas: propostas.len(),
+        valor_total_geral: licitacoes.iter().map(|l| l.licitacao.valor_total).sum(),
+        licitacoes,
+        propostas_duplicadas,
+        conflitos,
+        itens_duplicados,
+        valores_invalidos,
+        output_dir: output_dir.to_path_buf(),
+    }
+}
+
+impl Consolidacao {
+    /// Caminho do JSON de cada licitação, já com o destino das regras de roteamento
+    pub fn caminhos_licitacoes(&self) -> Vec<PathBuf> {
+        self.licitacoes.iter().map(|l| l.destino.join(&l.nome_arquivo)).collect()
+    }
+
+    /// Caminhos dos JSONs em que as propostas informadas foram gravadas
+    pub fn caminhos_das_propostas(&self, propostas: &[PropostaConsolidada]) -> Vec<PathBuf> {
+        let chaves: HashSet<String> = propostas.iter().map(chave_licitacao).collect();
+        self.licitacoes.iter()
+            .filter(|l| chaves.contains(&l.chave))
+            .map(|l| l.destino.join(&l.nome_arquivo))
+            .collect()
+    }
+
+    /// Como o JSON de uma licitação aparece nos arquivos gerados: só o nome na pasta de
+    /// resultados, o caminho completo quando roteado para fora dela
+    fn arquivo_gerado(&self, agrupada: &LicitacaoAgrupada) -> String {
+        if agrupada.destino == self.output_dir {
+            agrupada.nome_arquivo.clone()
+        } else {
+            agrupada.destino.join(&agrupada.nome_arquivo).to_string_lossy().to_string()
+        }
+    }
+
+    /// Resumo do agrupamento para exibir antes da gravação
+    pub fn previa(&self) -> PreviaConsolidacao {
+        PreviaConsolidacao {
+            licitacoes: self.licitacoes.iter().map(|l| PreviaLicitacao {
+                chave: l.chave.clone(),
+                uasg: l.licitacao.uasg.clone(),
+                pregao: l.licitacao.pregao.clone(),
+                processo: l.licitacao.processo.clone(),
+                total_propostas: l.licitacao.total_propostas,
+                valor_total: l.licitacao.valor_total,
+                arquivo: l.nome_arquivo.clone(),
+            }).collect(),
+            total_propostas: self.total_propostas,
+            valor_total_geral: self.valor_total_geral,
+            propostas_duplicadas_ignoradas: self.propostas_duplicadas,
+            conflitos: self.conflitos.clone(),
+            itens_duplicados: self.itens_duplicados.clone(),
+            valores_invalidos: self.valores_invalidos.clone(),
+            arquivos: self.licitacoes.iter()
+                .map(|l| self.arquivo_gerado(l))
+                .chain([ARQUIVO_CONSOLIDADO.to_string(), ARQUIVO_RESUMO_GERAL.to_string()])
+                .collect(),
         }
     }
+
+    /// Todas as licitações num único documento tipado, pela chave de consolidação
+    pub fn consolidado_json(&self, data_geracao: &str, interrompido: bool) -> ConsolidadoJson {
+        ConsolidadoJson {
+            schema_version: migracoes::VERSAO_ESQUEMA,
+            data_geracao: data_geracao.to_string(),
+            total_licitacoes: self.licitacoes.len(),
+            total_propostas: self.total_propostas,
+            valor_total_geral: self.valor_total_geral,
+            processamento_interrompido: interrompido,
+            licitacoes: self.licitacoes.iter()
+                .map(|l| (l.chave.clone(), l.licitacao.clone()))
+                .collect(),
+        }
+    }
+}
+
+/// Lê um consolidado.json gravado por `persistir`
+pub fn carregar_consolidado(path: &Path) -> Result<ConsolidadoJson> {
+    let content = fs::read_to_string(path)
+        .context(format!("Erro ao ler {}", path.display()))?;
+    serde_json::from_str(&content)
+        .context(format!("Erro ao analisar {}", path.display()))
+}
+
+/// Grava os JSONs de uma consolidação e o resumo geral, retornando os arquivos gerados
+///
+/// As métricas dos arquivos processados, quando houver, são resumidas no resumo geral.
+pub fn persistir(
+    consolidacao: &Consolidacao,
+    output_dir: &Path,
+    interrompido: bool,
+    metricas: &[FileProcessingMetrics],
+) -> Result<Vec<String>> {
+    let data_geracao = Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string();
+    let mut arquivos_gerados: Vec<String> = Vec::new();
+    
+    // Salvar um arquivo JSON para cada licitação
+    for agrupada in &consolidacao.licitacoes {
+        gravar_licitacao(agrupada, output_dir, &data_geracao, interrompido)?;
+        arquivos_gerados.push(consolidacao.arquivo_gerado(agrupada));
+    }
+    
+    // Todas as licitações num só arquivo, no formato de ConsolidadoJson
+    let consolidado_path = output_dir.join(ARQUIVO_CONSOLIDADO);
+    let consolidado_content = serde_json::to_string_pretty(&consolidacao.consolidado_json(&data_geracao, interrompido))
+        .context("Erro ao serializar JSON consolidado")?;
+    
+    integridade::gravar_artefato(output_dir, &consolidado_path, consolidado_content.as_bytes(), None)
+        .context(format!("Erro ao salvar {}", ARQUIVO_CONSOLIDADO))?;
+    arquivos_gerados.push(ARQUIVO_CONSOLIDADO.to_string());
     
     // Salvar também um arquivo resumo geral
-    let resumo_geral = serde_json::json!({
-        "data_geracao": data_geracao,
-        "total_licitacoes": licitacoes.len(),
-        "total_propostas": propostas.len(),
-        "valor_total_geral": valor_total_geral,
-        "arquivos_gerados": licitacoes.keys().map(|k| format!("licitacao_{}.json", 
-            k.replace("/", "_").replace(" ", "_"))).collect::<Vec<_>>()
-    });
+    let resumo_geral = ResumoGeral {
+        data_geracao,
+        total_licitacoes: consolidacao.licitacoes.len(),
+        total_propostas: consolidacao.total_propostas,
+        valor_total_geral: consolidacao.valor_total_geral,
+        processamento_interrompido: interrompido,
+        propostas_duplicadas_ignoradas: consolidacao.propostas_duplicadas,
+        economia: ResumoEconomia::das_propostas(consolidacao.licitacoes.iter().flat_map(|l| &l.licitacao.propostas)),
+        porte: ResumoPorte::das_propostas(consolidacao.licitacoes.iter().flat_map(|l| &l.licitacao.propostas)),
+        arquivos_gerados: arquivos_gerados.clone(),
+        desempenho: ResumoDesempenho::das_metricas(metricas),
+    };
     
-    let resumo_path = output_dir.join("resumo_geral.json");
+    let resumo_path = output_dir.join(ARQUIVO_RESUMO_GERAL);
     let resumo_content = serde_json::to_string_pretty(&resumo_geral)
         .context("Erro ao serializar resumo geral")?;
     
-    fs::write(&resumo_path, resumo_content)
+    integridade::gravar_artefato(output_dir, &resumo_path, resumo_content.as_bytes(), None)
         .context("Erro ao salvar arquivo de resumo geral")?;
+    arquivos_gerados.push(ARQUIVO_RESUMO_GERAL.to_string());
     
-    if verbose {
-        println!("📊 Resumo geral:");
-        println!("   - {} arquivos JSON de licitações salvos", arquivos_salvos);
-        println!("   - {} propostas totais processadas", propostas.len());
-        println!("   - Valor total geral: R$ {:.2}", valor_total_geral);
-        println!("📄 Resumo geral salvo em: {:?}", resumo_path);
+    info!(
+        licitacoes = consolidacao.licitacoes.len(),
+        propostas = consolidacao.total_propostas,
+        valor_total_geral = consolidacao.valor_total_geral,
+        caminho = %resumo_path.display(),
+        "Resumo geral salvo",
+    );
+    
+    Ok(arquivos_gerados)
+}
+
+/// Grava o JSON de uma licitação agrupada no seu destino, retornando o caminho gravado
+pub fn gravar_licitacao(agrupada: &LicitacaoAgrupada, output_dir: &Path, data_geracao: &str, interrompido: bool) -> Result<PathBuf> {
+    let licitacao = &agrupada.licitacao;
+    let destino = &agrupada.destino;
+    
+    if !destino.exists() {
+        fs::create_dir_all(destino)
+            .context(format!("Erro ao criar diretório de destino: {}", destino.display()))?;
     }
     
-    Ok(())
-} 
\ No newline at end of file
+    let economia = ResumoEconomia::das_propostas(&licitacao.propostas);
+    let json_licitacao = serde_json::json!({
+        "schema_version": migracoes::VERSAO_ESQUEMA,
+        "data_geracao": data_geracao,
+        "uasg": licitacao.uasg,
+        "pregao": licitacao.pregao,
+        "modalidade": licitacao.modalidade,
+        "processo": licitacao.processo,
+        "data_homologacao_iso": licitacao.propostas.iter().find_map(|p| p.data_homologacao_iso.clone()),
+        "responsavel_homologacao": licitacao.propostas.iter().find_map(|p| p.responsavel_homologacao.clone()),
+        "total_propostas": licitacao.total_propostas,
+        "valor_total": licitacao.valor_total,
+        "valor_estimado_total": economia.valor_estimado_total,
+        "valor_adjudicado_total": economia.valor_adjudicado_total,
+        "economia_total": economia.economia_total,
+        "economia_percentual": economia.economia_percentual,
+        "processamento_interrompido": interrompido,
+        "propostas": licitacao.propostas
+    });
+    
+    let json_path = destino.join(&agrupada.nome_arquivo);
+    let json_content = serde_json::to_string_pretty(&json_licitacao)
+        .context("Erro ao serializar JSON da licitação")?;
+    
+    integridade::gravar_artefato(output_dir, &json_path, json_content.as_bytes(), None)
+        .context(format!("Erro ao salvar arquivo JSON: {}", agrupada.nome_arquivo))?;
+    
+    debug!(caminho = %json_path.display(), propostas = licitacao.total_propostas, valor_total = licitacao.valor_total,
+           "JSON da licitação salvo");
+    Ok(json_path)
+}
+
+/// Salva um JSON por licitação e o resumo geral, retornando a consolidação gravada
+pub fn salvar_json_consolidado(
+    propostas: &[PropostaConsolidada], 
+    output_dir: &Path, 
+    _nome_arquivo: &str, 
+    regras: &[RoutingRule],
+    interrompido: bool,
+) -> Result<Consolidacao> {
+    let consolidacao = consolidar(propostas, output_dir, regras);
+    
+    if consolidacao.propostas_duplicadas > 0 {
+        debug!(propostas = consolidacao.propostas_duplicadas, "Propostas duplicadas ignoradas");
+    }
+    for aviso in &consolidacao.valores_invalidos {
+        warn!("{}", aviso);
+    }
+    
+    persistir(&consolidacao, output_dir, interrompido, &[])?;
+    
+    // O banco acompanha os JSONs; uma falha nele não invalida o que já foi gravado
+    if let Err(e) = storage::registrar_consolidacao(output_dir, &consolidacao) {
+        warn!("Erro ao atualizar o banco SQLite: {:#}", e);
+    }
+    Ok(consolidacao)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diretorio_paralelo_erros_nao_interrompem() {
+        let root = std::env::temp_dir().join(format!("licitacao360_paralelo_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let entrada = root.join("pdfs");
+        fs::create_dir_all(&entrada).unwrap();
+
+        // Arquivos inválidos: todos falham, mas cada um deve ser reportado
+        for index in 0..6 {
+            fs::write(entrada.join(format!("arquivo_{}.pdf", index)), b"nao e um pdf").unwrap();
+        }
+        fs::write(entrada.join("ignorado.docx"), b"texto").unwrap();
+
+        let mut eventos = Vec::new();
+        let propostas = processar_diretorio_pdfs_com_progresso(
+            &entrada, &root.join("saida"), &FiltroVarredura::default(), &[], OpcoesProcessamento { max_parallel_jobs: 3, ..Default::default() }, &AtomicBool::new(false),
+            |processados, total, evento| eventos.push((processados, total, evento)),
+        ).unwrap();
+
+        assert!(propostas.is_empty());
+        let falhas: Vec<_> = eventos.iter().filter(|(_, _, e)| matches!(e, EventoProgresso::Falhou(..))).collect();
+        assert_eq!(falhas.len(), 6);
+        assert!(eventos.iter().all(|(_, total, _)| *total == 6));
+
+        // O contador de concluídos cresce de 1 em 1, mesmo com vários workers
+        let concluidos: Vec<usize> = falhas.iter().map(|(processados, _, _)| *processados).collect();
+        assert_eq!(concluidos, (1..=6).collect::<Vec<_>>());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    /// Extrator que fica preso nos arquivos "travado*.pdf"
+    fn extrator_lento(pdf_path: &Path) -> Result<String> {
+        if pdf_path.file_name().unwrap_or_default().to_string_lossy().starts_with("travado") {
+            std::thread::sleep(Duration::from_secs(5));
+        }
+        Ok("UASG 787000 Pregão Nº 90008/2024".to_string())
+    }
+
+    #[test]
+    fn test_timeout_na_extracao_nao_trava_o_lote() {
+        let root = std::env::temp_dir().join(format!("licitacao360_timeout_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let entrada = root.join("pdfs");
+        fs::create_dir_all(&entrada).unwrap();
+        for nome in ["a.pdf", "travado.pdf", "z.pdf"] {
+            fs::write(entrada.join(nome), b"%PDF").unwrap();
+        }
+
+        let opcoes = OpcoesProcessamento {
+            max_parallel_jobs: 1,
+            timeout_extracao: Some(Duration::from_millis(200)),
+            extrator: extrator_lento,
+            ..Default::default()
+        };
+        let inicio = std::time::Instant::now();
+        let mut eventos = Vec::new();
+        processar_diretorio_pdfs_com_progresso(
+            &entrada, &root.join("saida"), &FiltroVarredura::default(), &[], opcoes, &AtomicBool::new(false),
+            |_, _, evento| eventos.push(evento),
+        ).unwrap();
+        assert!(inicio.elapsed() < Duration::from_secs(4));
+
+        let falhas: Vec<&String> = eventos.iter()
+            .filter_map(|e| match e {
+                EventoProgresso::Falhou(arquivo, erro) => {
+                    assert!(arquivo.ends_with("travado.pdf"));
+                    Some(erro)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(falhas.len(), 1);
+        assert!(falhas[0].starts_with("timeout"));
+        assert_eq!(eventos.iter().filter(|e| matches!(e, EventoProgresso::Concluido(..))).count(), 2);
+
+        // Sem limite, o extrator é chamado diretamente
+        assert!(extrair_texto_com_timeout(&entrada.join("a.pdf"), None, extrator_lento).is_ok());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_diretorio_aceita_pdf_em_qualquer_caixa() {
+        let root = std::env::temp_dir().join(format!("licitacao360_caixa_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        for nome in ["a.pdf", "b.PDF", "c.Pdf", "d.docx", "e.pdf.bak", "f.PDFX"] {
+            fs::write(root.join(nome), b"nao e um pdf").unwrap();
+        }
+
+        let mut iniciados = Vec::new();
+        processar_diretorio_pdfs_com_progresso(
+            &root, &root.join("saida"), &FiltroVarredura::default(), &[], OpcoesProcessamento { max_parallel_jobs: 1, ..Default::default() }, &AtomicBool::new(false),
+            |_, _, evento| {
+                if let EventoProgresso::Iniciado(arquivo) = evento {
+                    iniciados.push(Path::new(&arquivo).file_name().unwrap().to_string_lossy().to_string());
+                }
+            },
+        ).unwrap();
+
+        assert_eq!(iniciados, vec!["a.pdf", "b.PDF", "c.Pdf"]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_varredura_com_profundidade_exclusoes_e_saida_aninhada() {
+        let root = std::env::temp_dir().join(format!("licitacao360_varredura_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for arquivo in [
+            "raiz.pdf",
+            "a/nivel1.pdf",
+            "a/b/nivel2.pdf",
+            "a/b/c/nivel3.pdf",
+            "rascunhos/ignorado.pdf",
+            "a/rascunho_ata.pdf",
+            ".oculta/ata.pdf",
+            "Resultados/ata_copiada.pdf",
+        ] {
+            let caminho = root.join(arquivo);
+            fs::create_dir_all(caminho.parent().unwrap()).unwrap();
+            fs::write(caminho, b"%PDF").unwrap();
+        }
+        let nomes = |arquivos: Vec<PathBuf>| -> Vec<String> {
+            arquivos.iter().map(|a| a.strip_prefix(&root).unwrap().to_string_lossy().replace('\\', "/")).collect()
+        };
+
+        let opcoes = OpcoesVarredura {
+            max_depth: 3,
+            exclude_patterns: padroes_exclusao_padrao().into_iter().chain(["rascunhos".to_string(), "**/rascunho_*".to_string()]).collect(),
+        };
+        let filtro = FiltroVarredura::novo(&root, &opcoes, Some(&root.join("Resultados"))).unwrap();
+        assert_eq!(nomes(listar_atas(&root, &filtro)), ["a/b/nivel2.pdf", "a/nivel1.pdf", "raiz.pdf"]);
+
+        // O processamento usa exatamente a mesma lista: o total do progresso bate com a listagem
+        let mut totais = Vec::new();
+        processar_diretorio_pdfs_com_progresso(
+            &root, &root.join("Resultados"), &filtro, &[], OpcoesProcessamento::default(), &AtomicBool::new(false),
+            |_, total, _| totais.push(total),
+        ).unwrap();
+        assert!(!totais.is_empty() && totais.iter().all(|&total| total == 3));
+
+        // Sem critério, só Processados continua de fora; padrão inválido é erro
+        assert_eq!(listar_atas(&root, &FiltroVarredura::default()).len(), 8);
+        assert!(FiltroVarredura::novo(&root, &OpcoesVarredura { max_depth: 0, exclude_patterns: vec!["a/[b".to_string()] }, None).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_arquivar_pdf_resolve_colisoes_e_sai_da_fila() {
+        let root = std::env::temp_dir().join(format!("licitacao360_arquivar_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+
+        fs::write(root.join("ata.pdf"), b"primeira").unwrap();
+        let destino = arquivar_pdf(&root.join("ata.pdf"), &root).unwrap();
+        assert_eq!(destino, root.join(paths::PASTA_PROCESSADOS).join("ata.pdf"));
+
+        // Mesmo nome processado de novo não sobrescreve o arquivado
+        fs::write(root.join("ata.pdf"), b"segunda").unwrap();
+        let destino = arquivar_pdf(&root.join("ata.pdf"), &root).unwrap();
+        assert_eq!(destino, root.join(paths::PASTA_PROCESSADOS).join("ata_1.pdf"));
+        assert_eq!(fs::read(&destino).unwrap(), b"segunda");
+        assert!(!root.join("ata.pdf").exists());
+
+        assert!(arquivar_pdf(&root.join("inexistente.pdf"), &root).is_err());
+
+        // Os arquivados não voltam a ser processados
+        fs::write(root.join("nova.pdf"), b"nao e um pdf").unwrap();
+        let mut iniciados = Vec::new();
+        processar_diretorio_pdfs_com_progresso(
+            &root, &root.join("saida"), &FiltroVarredura::default(), &[], OpcoesProcessamento::default(), &AtomicBool::new(false),
+            |_, _, evento| {
+                if let EventoProgresso::Iniciado(arquivo) = evento {
+                    iniciados.push(Path::new(&arquivo).file_name().unwrap().to_string_lossy().to_string());
+                }
+            },
+        ).unwrap();
+        assert_eq!(iniciados, vec!["nova.pdf"]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_callback_progresso_em_ordem_com_um_worker() {
+        let root = std::env::temp_dir().join(format!("licitacao360_ordem_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        for nome in ["c.pdf", "a.pdf", "b.pdf"] {
+            fs::write(root.join(nome), b"nao e um pdf").unwrap();
+        }
+
+        let mut eventos = Vec::new();
+        processar_diretorio_pdfs_com_progresso(
+            &root, &root.join("saida"), &FiltroVarredura::default(), &[], OpcoesProcessamento { max_parallel_jobs: 1, ..Default::default() }, &AtomicBool::new(false),
+            |processados, total, evento| {
+                let (tipo, arquivo) = match evento {
+                    EventoProgresso::Iniciado(arquivo) => ("inicio", arquivo),
+                    EventoProgresso::Aviso(arquivo, _) => ("aviso", arquivo),
+                    EventoProgresso::Concluido(arquivo, ..)
+                    | EventoProgresso::Falhou(arquivo, _)
+                    | EventoProgresso::Protegido(arquivo) => ("fim", arquivo),
+                };
+                let nome = Path::new(&arquivo).file_name().unwrap().to_string_lossy().to_string();
+                eventos.push((processados, total, tipo, nome));
+            },
+        ).unwrap();
+
+        // Arquivos em ordem alfabética, cada início seguido do seu fim
+        let esperado = vec![
+            (0, 3, "inicio", "a.pdf".to_string()),
+            (1, 3, "fim", "a.pdf".to_string()),
+            (1, 3, "inicio", "b.pdf".to_string()),
+            (2, 3, "fim", "b.pdf".to_string()),
+            (2, 3, "inicio", "c.pdf".to_string()),
+            (3, 3, "fim", "c.pdf".to_string()),
+        ];
+        assert_eq!(eventos, esperado);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    fn proposta(item: &str, cnpj: &str, valor: &str) -> PropostaConsolidada {
+        PropostaConsolidada {
+            uasg: "787000".to_string(),
+            pregao: "90008/2024".to_string(),
+            modalidade: MODALIDADE_PREGAO.to_string(),
+            processo: "123".to_string(),
+            item: item.to_string(),
+            grupo: None,
+            quantidade: "1".to_string(),
+            descricao: format!("Item {}", item),
+            valor_estimado: Valor::de_texto(valor),
+            valor_adjudicado: Valor::de_texto(valor),
+            valor_adjudicado_raw: valor.to_string(),
+            fornecedor: "EMPRESA LTDA".to_string(),
+            cnpj: cnpj.to_string(),
+            marca_fabricante: "N/A".to_string(),
+            modelo_versao: "N/A".to_string(),
+            responsavel: "FULANO".to_string(),
+            melhor_lance: Valor::de_texto(valor),
+            valor_unitario_estimado: None,
+            valor_unitario_adjudicado: None,
+            valor_total_item: Valor::de_texto(valor),
+            valor_calculado: false,
+            tipo_formato: "individual".to_string(),
+            situacao: SITUACAO_ADJUDICADO.to_string(),
+            cnpj_valido: true,
+            descricao_reparada: false,
+            descricao_original: None,
+            observacoes: None,
+            porte: None,
+            lances: Vec::new(),
+            participantes: Vec::new(),
+            data_homologacao: String::new(),
+            data_homologacao_iso: None,
+            responsavel_homologacao: None,
+            arquivo_origem: None,
+            origem: Default::default(),
+            avisos: Vec::new(),
+            economia_absoluta: None,
+            economia_percentual: None,
+        }
+    }
+
+    #[test]
+    fn test_pdf_repetido_nao_dobra_valor_total() {
+        let root = std::env::temp_dir().join(format!("licitacao360_dedup_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+
+        let lista = vec![
+            proposta("1", "12.345.678/0001-90", "1.000,00"),
+            proposta("2", "98.765.432/0001-10", "500,00"),
+        ];
+        // Mesma lista duas vezes, como se o PDF estivesse repetido na pasta
+        let mut propostas = lista.clone();
+        propostas.extend(lista);
+        // CNPJ com outra formatação ainda é a mesma proposta
+        propostas.push(proposta("1", "12345678000190", "1.000,00"));
+        // O mesmo item e fornecedor em grupos diferentes são propostas distintas
+        let (mut grupo1, mut grupo2) = (proposta("1", "12.345.678/0001-90", "1.000,00"), proposta("1", "12.345.678/0001-90", "1.000,00"));
+        grupo1.grupo = Some("G1".to_string());
+        grupo2.grupo = Some("G2".to_string());
+        let (unicas, descartadas) = deduplicar_propostas(&[grupo1.clone(), grupo2, grupo1]);
+        assert_eq!((unicas.len(), descartadas), (2, 1));
+
+        let consolidacao = salvar_json_consolidado(&propostas, &root, "consolidado.json", &[], false).unwrap();
+        assert_eq!(consolidacao.propostas_duplicadas, 3);
+
+        let resumo: serde_json::Value = serde_json::from_str(&fs::read_to_string(root.join("resumo_geral.json")).unwrap()).unwrap();
+        assert_eq!(resumo["total_propostas"], 2);
+        assert_eq!(resumo["valor_total_geral"], 1500.0);
+        assert_eq!(resumo["propostas_duplicadas_ignoradas"], 3);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_uasg_sem_zero_a_esquerda_na_mesma_licitacao() {
+        assert_eq!(extrair_uasg("UASG 70011 - COMANDO DO 1º DISTRITO NAVAL"), "070011");
+        assert_eq!(extrair_uasg("UASG 1234567"), "N/A");
+
+        let mut antiga = proposta("1", "12.345.678/0001-90", "1.000,00");
+        antiga.uasg = "70011".to_string();
+        let mut nova = proposta("2", "98.765.432/0001-10", "500,00");
+        nova.uasg = "070011".to_string();
+        // A mesma proposta com as duas grafias da UASG é uma só
+        let mut repetida = antiga.clone();
+        repetida.uasg = "070011".to_string();
+
+        let consolidacao = consolidar(&[antiga, nova, repetida], Path::new("."), &[]);
+        assert_eq!(consolidacao.propostas_duplicadas, 1);
+        assert_eq!(consolidacao.licitacoes.len(), 1);
+        let agrupada = &consolidacao.licitacoes[0];
+        assert_eq!(agrupada.nome_arquivo, "licitacao_pregao_070011-90008_2024-123.json");
+        assert_eq!(agrupada.licitacao.uasg, "070011");
+        assert!(agrupada.licitacao.propostas.iter().all(|p| p.uasg == "070011"));
+    }
+
+    #[test]
+    fn test_consolidado_json_round_trip() {
+        let root = std::env::temp_dir().join(format!("licitacao360_consolidado_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+
+        let mut outra = proposta("1", "98.765.432/0001-10", "250,00");
+        outra.pregao = "90009/2024".to_string();
+        let propostas = vec![
+            proposta("1", "12.345.678/0001-90", "1.000,00"),
+            proposta("2", "12.345.678/0001-90", "500,00"),
+            outra,
+        ];
+
+        let consolidacao = salvar_json_consolidado(&propostas, &root, "consolidado.json", &[], true).unwrap();
+        let lido = carregar_consolidado(&root.join(ARQUIVO_CONSOLIDADO)).unwrap();
+        assert_eq!(lido.schema_version, migracoes::VERSAO_ESQUEMA);
+        assert_eq!(lido.total_licitacoes, 2);
+        assert_eq!(lido.total_propostas, 3);
+        assert_eq!(lido.valor_total_geral, 1750.0);
+        assert!(lido.processamento_interrompido);
+
+        // Cada licitação gravada em arquivo próprio está no consolidado pela mesma chave
+        for agrupada in &consolidacao.licitacoes {
+            let licitacao = &lido.licitacoes[&agrupada.chave];
+            assert_eq!(licitacao.valor_total, agrupada.licitacao.valor_total);
+            assert_eq!(serde_json::to_value(&licitacao.propostas).unwrap(), serde_json::to_value(&agrupada.licitacao.propostas).unwrap());
+        }
+
+        // Serializar de novo o que foi lido não perde nem muda campos
+        let original: serde_json::Value = serde_json::from_str(&fs::read_to_string(root.join(ARQUIVO_CONSOLIDADO)).unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(&lido).unwrap(), original);
+
+        let resumo = crate::resultados::resumir_consolidado(&lido);
+        assert_eq!(resumo.total_licitacoes, 2);
+        assert_eq!(resumo.valor_total, 1750.0);
+        assert_eq!(resumo.top_fornecedores[0].valor_adjudicado, 1500.0);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_previa_igual_ao_resultado_gravado() {
+        let root = std::env::temp_dir().join(format!("licitacao360_previa_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+
+        let mut sem_uasg = proposta("3", "11.111.111/0001-11", "10,00");
+        sem_uasg.uasg = "N/A".to_string();
+        let propostas = vec![
+            proposta("1", "12.345.678/0001-90", "1.000,00"),
+            proposta("1", "98.765.432/0001-10", "900,00"),
+            proposta("2", "98.765.432/0001-10", "500,00"),
+            proposta("2", "98.765.432/0001-10", "500,00"),
+            sem_uasg,
+        ];
+
+        let previa = consolidar(&propostas, &root, &[]).previa();
+        assert_eq!(previa.propostas_duplicadas_ignoradas, 1);
+        assert_eq!(previa.itens_duplicados.len(), 1);
+        assert!(previa.conflitos.iter().any(|c| c.contains("N/A")));
+
+        let gravados = persistir(&consolidar(&propostas, &root, &[]), &root, false, &[]).unwrap();
+        assert_eq!(gravados, previa.arquivos);
+
+        // Cada licitação da prévia corresponde ao arquivo gravado
+        for licitacao in &previa.licitacoes {
+            let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(root.join(&licitacao.arquivo)).unwrap()).unwrap();
+            assert_eq!(json["total_propostas"], licitacao.total_propostas);
+            assert_eq!(json["valor_total"], licitacao.valor_total);
+        }
+
+        let resumo: serde_json::Value = serde_json::from_str(&fs::read_to_string(root.join("resumo_geral.json")).unwrap()).unwrap();
+        assert_eq!(resumo["total_licitacoes"], previa.licitacoes.len());
+        assert_eq!(resumo["total_propostas"], previa.total_propostas);
+        assert_eq!(resumo["valor_total_geral"], previa.valor_total_geral);
+
+        // Destinos roteados aparecem com o caminho completo na prévia e na gravação
+        let regras = [RoutingRule { uasg_prefix: Some("787".to_string()), pregao_year: None, target_directory: root.join("roteado").to_string_lossy().to_string() }];
+        let previa = consolidar(&propostas, &root, &regras).previa();
+        let gravados = persistir(&consolidar(&propostas, &root, &regras), &root, false, &[]).unwrap();
+        assert_eq!(gravados, previa.arquivos);
+        assert!(previa.arquivos.iter().any(|arquivo| Path::new(arquivo).starts_with(root.join("roteado"))), "{:?}", previa.arquivos);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    const BLOCO_GRUPO: &str = "Item 1 do Grupo G1 - Caneta esferográfica azul com corpo
+transparente e tampa ventilada.
+Quantidade: 100
+Valor estimado: R$ 1,50
+Situação: Adjudicado e Homologado
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL para EMPRESA LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 1,20
+Item 2 do Grupo G1 - Papel sulfite A4 75g
+unidade de fornecimento: resma
+Quantidade: 10
+Valor estimado: R$ 25,00
+Situação: Adjudicado e Homologado
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL para EMPRESA LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 22,00
+";
+
+    #[test]
+    fn test_reparo_de_descricao_no_formato_grupo() {
+        let propostas = extrair_propostas_grupo(BLOCO_GRUPO);
+        assert_eq!(propostas.len(), 2);
+
+        // Descrição dividida em duas linhas sai inteira
+        assert_eq!(propostas[0].descricao, "Caneta esferográfica azul com corpo transparente e tampa ventilada.");
+        assert!(propostas[0].descricao_reparada);
+        assert_eq!(propostas[0].descricao_original.as_deref(), Some("Caneta esferográfica azul com corpo"));
+
+        // A linha seguinte é um rótulo de campo: nada é juntado
+        assert_eq!(propostas[1].descricao, "Papel sulfite A4 75g");
+        assert!(!propostas[1].descricao_reparada);
+        assert!(propostas[1].descricao_original.is_none());
+    }
+
+    #[test]
+    fn test_valor_negociado_no_formato_grupo() {
+        let texto = BLOCO_GRUPO.replacen("melhor lance: R$ 1,20", "melhor lance: R$ 1,20, valor negociado: R$ 1,05", 1);
+        let propostas = extrair_propostas_grupo(&texto);
+        assert_eq!(propostas.len(), 2);
+
+        assert_eq!(propostas[0].melhor_lance, Some(Valor(1.2)));
+        assert_eq!(propostas[0].valor_adjudicado, Some(Valor(1.05)));
+        assert_eq!(propostas[0].valor_adjudicado_raw, "1,05");
+        // Sem negociação, o adjudicado é o melhor lance; a negociação do item 1 não vaza para o 2
+        assert_eq!(propostas[1].melhor_lance, Some(Valor(22.0)));
+        assert_eq!(propostas[1].valor_adjudicado, Some(Valor(22.0)));
+
+        let root = std::env::temp_dir().join(format!("licitacao360_grupo_negociado_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let texto = format!("UASG 787000\nPREGÃO 90008/2024\n{}", texto);
+        let resultado = processar_texto_licitacao(&texto, &root.join("grupo.pdf"), &root, &[], false, Vec::new()).unwrap();
+        assert_eq!(resultado.propostas[0].valor_adjudicado, Some(Valor(1.05)));
+        assert_eq!(resultado.propostas[0].melhor_lance, Some(Valor(1.2)));
+        // O valor total do relatório soma o negociado, não o melhor lance
+        let markdown = fs::read_to_string(root.join("grupo.md")).unwrap();
+        assert!(markdown.contains("R$ 23.05"), "{}", markdown);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_extrair_lances_em_ordem_cronologica() {
+        let texto = "Item 1 - Caneta esferográfica azul
+Lances
+CNPJ/CPF Valor do Lance Data/Hora Registro
+12.345.678/0001-90 R$ 1,4500 10/03/2023 09:03:10:120
+98.765.432/0001-10 R$ 1,5000 10/03/2023 09:01:05:450
+12.345.678/0001-90 R$ 1,5500 10/03/2023 09:00:40:001
+98.765.432/0001-10 R$ 1,4000 10/03/2023 09:03:10:900
+Item 2 - Papel sulfite A4
+Lances
+11.111.111/0001-11 R$ 25,0000 10/03/2023 10:00:00:000
+";
+
+        let lances = extrair_lances(texto, None, "1");
+        let valores: Vec<&str> = lances.iter().map(|l| l.valor.as_str()).collect();
+        assert_eq!(valores, vec!["1,5500", "1,5000", "1,4500", "1,4000"]);
+        assert_eq!(lances[0].participante, "12.345.678/0001-90");
+        assert_eq!(lances[0].data_hora, "10/03/2023 09:00:40:001");
+
+        // Lances de outros itens não se misturam
+        assert_eq!(extrair_lances(texto, None, "2").len(), 1);
+        assert!(extrair_lances(texto, None, "3").is_empty());
+
+        // Grupos repetem a numeração: cada item 1 tem os seus lances
+        let texto = "Item 1 do Grupo G1 - Caneta esferográfica azul
+Lances
+12.345.678/0001-90 R$ 1,4500 10/03/2023 09:03:10:120
+Item 1 do Grupo G2 - Papel sulfite A4
+Lances
+11.111.111/0001-11 R$ 25,0000 10/03/2023 10:00:00:000
+11.111.111/0001-11 R$ 24,0000 10/03/2023 10:01:00:000
+";
+        assert_eq!(extrair_lances(texto, Some("G1"), "1").len(), 1);
+        let lances = extrair_lances(texto, Some("G2"), "1");
+        assert_eq!(lances.len(), 2);
+        assert_eq!(lances[0].valor, "25,0000");
+        assert!(extrair_lances(texto, Some("G3"), "1").is_empty());
+    }
+
+    #[test]
+    fn test_extrair_participantes_item() {
+        let texto = "Item 1 - Caneta esferográfica azul
+Propostas do Item
+CNPJ/CPF Fornecedor Porte ME/EPP Valor Proposto Situação
+12.345.678/0001-90 EMPRESA LTDA Sim R$ 1,5000 Aceita
+98.765.432/0001-10 OUTRA COMERCIO S.A. Não R$ 1,8000 Classificada
+11.222.333/0001-44 TERCEIRA ME Sim Desclassificada
+Motivo: proposta sem marca e modelo
+Lances
+12.345.678/0001-90 R$ 1,4500 10/03/2023 09:03:10:120
+Item 2 - Papel sulfite A4
+Propostas do Item
+55.666.777/0001-88 UNICA DISTRIBUIDORA Não R$ 25,0000 Aceita
+";
+
+        let participantes = extrair_participantes_item(texto, None, "1");
+        assert_eq!(participantes.len(), 3);
+        assert_eq!(participantes[0], ParticipanteItem {
+            fornecedor: "EMPRESA LTDA".to_string(),
+            cnpj: "12.345.678/0001-90".to_string(),
+            valor_proposta: "1,5000".to_string(),
+            porte_me_epp: true,
+            classificacao: "Aceita".to_string(),
+        });
+        assert_eq!(participantes[1].fornecedor, "OUTRA COMERCIO S.A.");
+        assert!(!participantes[1].porte_me_epp);
+
+        // Proposta desclassificada sem valor e a linha de motivo não vira participante
+        assert_eq!(participantes[2].classificacao, "Desclassificada");
+        assert_eq!(participantes[2].valor_proposta, "N/A");
+        assert!(participantes[2].porte_me_epp);
+
+        // Item com um único participante
+        let participantes = extrair_participantes_item(texto, None, "2");
+        assert_eq!(participantes.len(), 1);
+        assert_eq!(participantes[0].fornecedor, "UNICA DISTRIBUIDORA");
+    }
+
+    #[test]
+    fn test_itens_nao_adjudicados_por_situacao() {
+        let texto = "Item 1 do Grupo G1 - Caneta esferográfica azul
+Quantidade: 100
+Valor estimado: R$ 1,50
+Situação: Deserto
+Item 2 do Grupo G1 - Lápis preto nº 2
+Quantidade: 50
+Valor estimado: R$ 0,80
+Situação: Fracassado
+Item 3 do Grupo G1 - Borracha branca
+Quantidade: 30
+Valor estimado: R$ 0,50
+Situação: Cancelado no julgamento
+Item 4 do Grupo G1 - Papel sulfite A4 75g
+Quantidade: 10
+Valor estimado: R$ 25,00
+Situação: Adjudicado e Homologado
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL para EMPRESA LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 22,00
+";
+
+        // O item deserto não pode herdar o vencedor do item 4
+        let adjudicadas = extrair_propostas_grupo(texto);
+        assert_eq!(adjudicadas.len(), 1);
+        assert_eq!(adjudicadas[0].item, "4");
+
+        let nao_adjudicadas = extrair_itens_nao_adjudicados(texto);
+        let situacoes: Vec<(&str, &str)> = nao_adjudicadas.iter().map(|p| (p.item.as_str(), p.situacao.as_str())).collect();
+        assert_eq!(situacoes, vec![("1", SITUACAO_DESERTO), ("2", SITUACAO_FRACASSADO), ("3", SITUACAO_CANCELADO)]);
+        assert_eq!(nao_adjudicadas[0].descricao, "Caneta esferográfica azul");
+        assert_eq!(nao_adjudicadas[0].grupo.as_deref(), Some("G1"));
+        assert_eq!(nao_adjudicadas[0].valor_estimado, Some(Valor(1.5)));
+        assert_eq!(nao_adjudicadas[0].cnpj, "N/A");
+
+        let mut propostas: Vec<PropostaConsolidada> = ["1", "2", "3"].iter().map(|item| proposta(item, "N/A", "N/A")).collect();
+        for (proposta, situacao) in propostas.iter_mut().zip([SITUACAO_DESERTO, SITUACAO_FRACASSADO, SITUACAO_CANCELADO]) {
+            proposta.situacao = situacao.to_string();
+        }
+        propostas.push(proposta("4", "12.345.678/0001-90", "22,00"));
+
+        let consolidacao = consolidar(&propostas, Path::new("saida"), &[]);
+        let licitacao = &consolidacao.licitacoes[0].licitacao;
+        assert_eq!(
+            (licitacao.itens_adjudicados, licitacao.itens_desertos, licitacao.itens_fracassados, licitacao.itens_cancelados),
+            (1, 1, 1, 1)
+        );
+        assert_eq!(licitacao.valor_total, 22.0);
+        assert!(consolidacao.itens_duplicados.is_empty());
+        // "N/A" de itens não adjudicados não é valor inválido
+        assert!(consolidacao.valores_invalidos.is_empty());
+    }
+
+    #[test]
+    fn test_converter_valor_para_float_formatos() {
+        let casos: &[(&str, f64)] = &[
+            ("1.234,56", 1234.56),
+            ("1234,56", 1234.56),
+            ("1234.56", 1234.56),
+            ("1,234.56", 1234.56),
+            ("1.234", 1234.0),
+            ("1.234.567", 1234567.0),
+            ("1.234.567,89", 1234567.89),
+            ("1,234,567.89", 1234567.89),
+            ("0,5", 0.5),
+            ("0.123", 0.123),
+            ("12,3456", 12.3456),
+            ("R$ 1.234,56", 1234.56),
+            ("R$1.234,56", 1234.56),
+            ("  22,00 ", 22.0),
+            ("R$ 1 234,56", 1234.56),
+            ("-1.234,56", -1234.56),
+            ("R$ -10,00", -10.0),
+            ("-R$ 10,00", -10.0),
+            ("(1.234,56)", -1234.56),
+            ("450", 450.0),
+        ];
+        for (texto, esperado) in casos {
+            assert_eq!(converter_valor_para_float(texto), Ok(*esperado), "{}", texto);
+        }
+
+        for invalido in ["", "N/A", "R$", "1.2.3", "1,23,45", "12a,00", "1.234,56.7", "1,", "--10,00", "(-5,00)"] {
+            assert!(converter_valor_para_float(invalido).is_err(), "{}", invalido);
+        }
+    }
+
+    #[test]
+    fn test_valor_invalido_registrado_na_consolidacao() {
+        let propostas = vec![proposta("1", "12.345.678/0001-90", "1234.56"), proposta("2", "11.222.333/0001-81", "ilegível")];
+        let consolidacao = consolidar(&propostas, Path::new("saida"), &[]);
+
+        // Ponto decimal não pode inflar o total em 100x
+        assert_eq!(consolidacao.valor_total_geral, 1234.56);
+        assert_eq!(consolidacao.valores_invalidos.len(), 1);
+        assert!(consolidacao.valores_invalidos[0].contains("item 2"));
+    }
+
+    #[test]
+    fn test_reparo_de_descricao_no_formato_individual() {
+        let texto = "Item 7 - Cadeira giratória com braços reguláveis e
+assento estofado em espuma injetada
+Quantidade: 5
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL, para EMPRESA LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 450,00";
+
+        let descricao = extrair_descricao_do_contexto(texto, "12.345.678/0001-90");
+        assert_eq!(descricao.descricao, "- Cadeira giratória com braços reguláveis e assento estofado em espuma injetada");
+        assert_eq!(descricao.original.as_deref(), Some("- Cadeira giratória com braços reguláveis e"));
+
+        let descricao = reparar_descricao("Mesa de escritório.", "em madeira");
+        assert!(descricao.original.is_none());
+    }
+
+    #[test]
+    fn test_texto_normalizado_casa_com_padrao_de_adjudicacao() {
+        let texto = "Item 3 - Grampeador de mesa
+Quantidade: 2
+Adjudi-
+cado e Homologado por CPF ***.123.***-*4 - FULANO  DE TAL, para EMPRESA\u{00a0}LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 35,00";
+
+        // Sem normalização, a hifenização do pdf_extract impede o casamento
+        assert!(extrair_propostas_individuais(texto).is_empty());
+
+        let propostas = extrair_propostas_individuais(&texto::normalizar_texto(texto));
+        assert_eq!(propostas.len(), 1);
+        assert_eq!(propostas[0].cnpj, "12.345.678/0001-90");
+        assert_eq!(propostas[0].fornecedor, "EMPRESA LTDA");
+        assert_eq!(propostas[0].responsavel, "FULANO DE TAL");
+    }
+
+    #[test]
+    fn test_extracao_de_texto_grande_com_padroes_em_cache() {
+        // 300 itens de 60 fornecedores: cada CNPJ vence cinco itens
+        let cnpj = |n: usize| format!("{:02}.{:03}.{:03}/0001-{:02}", 10 + n, n, n, n);
+        let mut texto = String::from("UASG 787000\nPREGÃO 90008/2024\n");
+        for item in 1..=300 {
+            let fornecedor = item % 60;
+            texto.push_str(&format!(
+                "Item {item} - Produto {item}\nQuantidade: {item}\nValor estimado: R$ {item},00\n\
+                 Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL, para EMPRESA {fornecedor} LTDA, \
+                 CNPJ {}, melhor lance: R$ {item},50\nProposta adjudicada\nMarca/Fabricante: MARCA {fornecedor}\n\
+                 Modelo/versão: MODELO {fornecedor}\n",
+                cnpj(fornecedor)
+            ));
+        }
+
+        let propostas = extrair_propostas_individuais(&texto);
+
+        // Uma proposta por CNPJ, com os campos do primeiro item que ele venceu
+        assert_eq!(propostas.len(), 60);
+        for proposta in &propostas {
+            let fornecedor: usize = proposta.fornecedor.trim_start_matches("EMPRESA ").trim_end_matches(" LTDA").parse().unwrap();
+            let primeiro_item = if fornecedor == 0 { 60 } else { fornecedor };
+            assert_eq!(proposta.cnpj, cnpj(fornecedor));
+            assert_eq!(proposta.valor_adjudicado_raw, format!("{},50", primeiro_item));
+            assert_eq!(proposta.valor_adjudicado, Some(Valor(primeiro_item as f64 + 0.5)));
+            assert_eq!(proposta.marca_fabricante, format!("MARCA {}", fornecedor));
+            assert_eq!(proposta.modelo_versao, format!("MODELO {}", fornecedor));
+            // Os padrões de contexto partem do primeiro "Item" do texto (comportamento mantido)
+            assert_eq!(proposta.item, "1");
+            assert_eq!(proposta.quantidade, "1");
+            assert_eq!(proposta.valor_estimado, Some(Valor(1.0)));
+            assert_eq!(proposta.descricao, "- Produto 1");
+        }
+
+        // Com os padrões em cache, o resultado é o mesmo e nenhum regex é recompilado
+        let padroes = padroes_cnpj(&cnpj(7));
+        let repetidas = extrair_propostas_individuais(&texto);
+        assert!(Arc::ptr_eq(&padroes, &padroes_cnpj(&cnpj(7))));
+        assert_eq!(
+            serde_json::to_value(&propostas).unwrap(),
+            serde_json::to_value(&repetidas).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_diretorio_cancelado_nao_inicia_arquivos() {
+        let root = std::env::temp_dir().join(format!("licitacao360_cancelado_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("a.pdf"), b"nao e um pdf").unwrap();
+
+        let mut chamadas = 0;
+        let propostas = processar_diretorio_pdfs_com_progresso(
+            &root, &root.join("saida"), &FiltroVarredura::default(), &[], OpcoesProcessamento::default(), &AtomicBool::new(true),
+            |_, _, _| chamadas += 1,
+        ).unwrap();
+
+        assert!(propostas.is_empty());
+        assert_eq!(chamadas, 0);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_ordenar_propostas_em_ordem_natural() {
+        let proposta = |grupo: Option<&str>, item: &str, fornecedor: &str| -> PropostaConsolidada {
+            serde_json::from_value(serde_json::json!({
+                "uasg": "787000", "pregao": "90008/2024", "processo": "123", "item": item, "grupo": grupo,
+                "quantidade": "1", "descricao": "Caneta", "valor_estimado": "1,00", "valor_adjudicado": "1,00",
+                "fornecedor": fornecedor, "cnpj": "11.222.333/0001-81", "marca_fabricante": "N/A", "modelo_versao": "N/A",
+                "responsavel": "N/A", "melhor_lance": "1,00", "tipo_formato": "individual", "data_homologacao": "N/A"
+            })).unwrap()
+        };
+        let mut propostas = vec![
+            proposta(None, "10", "A"),
+            proposta(Some("G10"), "1", "B"),
+            proposta(None, "N/A", "C"),
+            proposta(None, "2", "D"),
+            proposta(Some("G2"), "11", "E"),
+            proposta(None, "1A", "F"),
+            proposta(Some("G2"), "3", "G"),
+            proposta(None, " 1 ", "H"),
+            proposta(None, "2", "I"),
+        ];
+        ordenar_propostas(&mut propostas);
+
+        let ordem: Vec<(Option<&str>, &str, &str)> = propostas.iter()
+            .map(|p| (p.grupo.as_deref(), p.item.as_str(), p.fornecedor.as_str()))
+            .collect();
+        assert_eq!(ordem, [
+            (None, " 1 ", "H"),
+            (None, "2", "D"),
+            (None, "2", "I"),
+            (None, "10", "A"),
+            (None, "1A", "F"),
+            (None, "N/A", "C"),
+            (Some("G2"), "3", "G"),
+            (Some("G2"), "11", "E"),
+            (Some("G10"), "1", "B"),
+        ]);
+    }
+
+    #[test]
+    fn test_prefixo_g_so_vale_para_o_grupo() {
+        let mut itens = vec![
+            (Some("G2"), "G5"),
+            (Some("G2"), "10"),
+            (Some("G10"), "1"),
+            (Some("G2"), "3"),
+            (None, "G1"),
+            (None, "4"),
+        ];
+        ordenar_por_item(&mut itens, |(grupo, item)| (*grupo, *item));
+
+        assert_eq!(itens, [
+            (None, "4"),
+            (None, "G1"),
+            (Some("G2"), "3"),
+            (Some("G2"), "10"),
+            (Some("G2"), "G5"),
+            (Some("G10"), "1"),
+        ]);
+    }
+
+    #[test]
+    fn test_previa_do_texto_extraido() {
+        let root = std::env::temp_dir().join(format!("licitacao360_previa_texto_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let ata = root.join("ata.txt");
+        fs::write(&ata, "UASG 787000\nPREGÃO 90008/2024\nItem 1 - Caneta\n\
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO, para EMPRESA A LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 1,50\n").unwrap();
+
+        let previa = previa_texto(&ata, 11, None, None).unwrap();
+        assert_eq!(previa.texto, "UASG 787000");
+        assert!(previa.truncado);
+        assert_eq!(previa.total_caracteres, fs::read_to_string(&ata).unwrap().chars().count());
+        assert_eq!(previa.ocorrencias, BTreeMap::from([
+            ("Adjudicado".to_string(), 1), ("CNPJ".to_string(), 1), ("UASG".to_string(), 1),
+        ]));
+        assert!(!previa_texto(&ata, usize::MAX, None, None).unwrap().truncado);
+
+        // O limite em bytes vale mesmo com max_chars maior, sem cortar um caractere ao meio
+        let grande = root.join("grande.txt");
+        fs::write(&grande, "ã".repeat(LIMITE_PREVIA_BYTES)).unwrap();
+        let previa = previa_texto(&grande, usize::MAX, None, None).unwrap();
+        assert_eq!(previa.texto.len(), LIMITE_PREVIA_BYTES);
+        assert!(previa.truncado);
+
+        assert!(previa_texto(&ata, 100, Some(1), None).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_metricas_por_arquivo_no_resumo_geral() {
+        let root = std::env::temp_dir().join(format!("licitacao360_metricas_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let ata = root.join("ata.txt");
+        fs::write(&ata, "UASG 787000\nPREGÃO 90008/2024\n\
+Item 1 - Caneta esferográfica\nQuantidade: 100\nValor estimado: R$ 2,00\n\
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL, para EMPRESA A LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 1,50\n").unwrap();
+        let vazio = root.join("vazio.txt");
+        fs::write(&vazio, "Nenhuma ata aqui\n").unwrap();
+
+        let mut metricas = Vec::new();
+        let propostas = processar_pdfs_com_progresso(
+            &[ata.clone(), vazio.clone()], &root, &[], OpcoesProcessamento::default(), &AtomicBool::new(false),
+            |_, _, evento| if let EventoProgresso::Concluido(_, _, m) = evento { metricas.push(m) },
+        ).unwrap();
+        metricas.sort_by(|a: &FileProcessingMetrics, b| a.file.cmp(&b.file));
+
+        let resumo: Vec<_> = metricas.iter().map(|m| (m.file.as_str(), m.bytes, m.propostas, m.formato.as_str())).collect();
+        assert_eq!(resumo, [
+            (&*ata.to_string_lossy(), fs::metadata(&ata).unwrap().len(), 1, "individual"),
+            (&*vazio.to_string_lossy(), fs::metadata(&vazio).unwrap().len(), 0, ""),
+        ]);
+
+        persistir(&consolidar(&propostas, &root, &[]), &root, false, &metricas).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(root.join(ARQUIVO_RESUMO_GERAL)).unwrap()).unwrap();
+        let desempenho: ResumoDesempenho = serde_json::from_value(json["desempenho"].clone()).unwrap();
+        assert_eq!(Some(desempenho), ResumoDesempenho::das_metricas(&metricas));
+
+        // Sem métricas (reconstrução, mesclagem) o resumo não traz o bloco
+        persistir(&consolidar(&propostas, &root, &[]), &root, false, &[]).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(root.join(ARQUIVO_RESUMO_GERAL)).unwrap()).unwrap();
+        assert!(json.get("desempenho").is_none());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_arquivo_unico_grava_json_da_licitacao() {
+        let root = std::env::temp_dir().join(format!("licitacao360_arquivo_unico_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let texto = texto::normalizar_texto("UASG 787000\nPREGÃO 90008/2024\n\
+Às 10:32 horas do dia 5 de março do ano de 2024\n\
+Item 1 - Caneta esferográfica\nQuantidade: 100\nValor estimado: R$ 2,00\n\
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL, para EMPRESA A LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 1,50\n\
+Item 2 - Grampeador\nQuantidade: 5\nValor estimado: R$ 40,00\n\
+Adjudicado e Homologado por CPF ***.456.***-*7 - CICLANO, para EMPRESA B LTDA, CNPJ 98.765.432/0001-10, melhor lance: R$ 35,00\n\
+HOMOLOGA a adjudicação dos itens. JOÃO DA SILVA, Ordenador de Despesas, matrícula 123456\n");
+
+        let resultado = processar_texto_licitacao(&texto, &root.join("ata.pdf"), &root, &[], false, Vec::new()).unwrap();
+        assert_eq!(resultado.propostas.len(), 2);
+        assert!(root.join("ata.md").exists());
+
+        let consolidacao = salvar_json_consolidado(&resultado.propostas, &root, "ata", &[], false).unwrap();
+        let caminhos = consolidacao.caminhos_licitacoes();
+        assert_eq!(caminhos.len(), 1);
+        assert!(caminhos[0].exists(), "JSON não gravado: {}", caminhos[0].display());
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&caminhos[0]).unwrap()).unwrap();
+        assert_eq!(json["uasg"], "787000");
+        assert_eq!(json["modalidade"], MODALIDADE_PREGAO);
+        assert_eq!(json["total_propostas"], 2);
+        assert_eq!(json["data_homologacao_iso"], "2024-03-05T10:32:00-03:00");
+        assert_eq!(json["responsavel_homologacao"], serde_json::json!({
+            "nome": "JOÃO DA SILVA", "cargo": "Ordenador de Despesas", "matricula": "123456",
+        }));
+        let markdown = fs::read_to_string(root.join("ata.md")).unwrap();
+        assert!(markdown.contains("- **Cargo**: Ordenador de Despesas\n- **Matrícula**: 123456\n"), "{}", markdown);
+        assert_eq!(json["propostas"][0]["data_homologacao"], "Às 10:32 horas do dia 5 de março do ano de 2024");
+        assert!(caminhos[0].file_name().unwrap().to_string_lossy().starts_with("licitacao_pregao_787000-90008_2024"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_economia_no_relatorio_e_nos_json() {
+        let root = std::env::temp_dir().join(format!("licitacao360_economia_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let texto = texto::normalizar_texto("UASG 787000\nPREGÃO 90008/2024\n\
+Item 1 - Caneta esferográfica\nQuantidade: 100\nValor estimado: R$ 2,00\n\
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL, para EMPRESA A LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 1,50\n");
+
+        let resultado = processar_texto_licitacao(&texto, &root.join("ata.pdf"), &root, &[], false, Vec::new()).unwrap();
+        let economias: Vec<_> = resultado.propostas.iter().map(|p| (p.economia_absoluta, p.economia_percentual)).collect();
+        assert_eq!(economias, vec![(Some(Valor(0.5)), Some(25.0))]);
+
+        let markdown = fs::read_to_string(root.join("ata.md")).unwrap();
+        assert!(markdown.contains("| R$ 2,00 | R$ 1,50 | R$ 0,50 (25,00%) |"), "{}", markdown);
+        assert!(markdown.contains("| **Total** | | | **R$ 2,00** | **R$ 1,50** | **R$ 0,50 (25,00%)** |"), "{}", markdown);
+        assert!(markdown.contains("- **Economia Total**: R$ 0,50 (25,00%)"));
+
+        let consolidacao = salvar_json_consolidado(&resultado.propostas, &root, "ata", &[], false).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&consolidacao.caminhos_licitacoes()[0]).unwrap()).unwrap();
+        assert_eq!(json["valor_estimado_total"], 2.0);
+        assert_eq!(json["valor_adjudicado_total"], 1.5);
+        assert_eq!(json["economia_total"], 0.5);
+        assert_eq!(json["propostas"][0]["economia_percentual"], 25.0);
+        let resumo: ResumoGeral = serde_json::from_str(&fs::read_to_string(root.join(ARQUIVO_RESUMO_GERAL)).unwrap()).unwrap();
+        assert_eq!(resumo.economia, ResumoEconomia::das_propostas(&resultado.propostas));
+
+        // Atas com valores unitários: a economia do relatório é a do item inteiro
+        let texto = texto::normalizar_texto("UASG 787000\nPREGÃO 90009/2024\n\
+Item 1 - Caneta esferográfica\nQuantidade: 100\nValor unitário estimado: R$ 2,00\n\
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL, para EMPRESA A LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 1,50\n\
+Porte da empresa: Micro Empresa\n");
+        processar_texto_licitacao(&texto, &root.join("unitario.pdf"), &root, &[], false, Vec::new()).unwrap();
+        let markdown = fs::read_to_string(root.join("unitario.md")).unwrap();
+        assert!(markdown.contains("| R$ 2,00 | R$ 1,50 | R$ 50,00 (25,00%) |"), "{}", markdown);
+        assert!(markdown.contains("| **Total** | | | **R$ 200,00** | **R$ 150,00** | **R$ 50,00 (25,00%)** |"), "{}", markdown);
+        assert!(markdown.contains("- **Adjudicado a ME/EPP**: R$ 150,00 em 1 itens (100,00%)"), "{}", markdown);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_observacoes_do_item() {
+        let root = std::env::temp_dir().join(format!("licitacao360_observacoes_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let texto = texto::normalizar_texto("UASG 787000\nPREGÃO 90008/2024\n\
+Item 1 - Caneta esferográfica\nQuantidade: 100\nValor estimado: R$ 2,00\n\
+Justificativa: Adjudicado em favor de EMPRESA A LTDA por apresentar\n   menor preço após negociação.\n\
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL, para EMPRESA A LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 1,50\n\
+Observação do pregoeiro: amostra aprovada.\n\n\
+Item 2 - Papel sulfite\nQuantidade: 10\nValor estimado: R$ 25,00\nSituação: Deserto\n");
+
+        let resultado = processar_texto_licitacao(&texto, &root.join("ata.pdf"), &root, &[], false, Vec::new()).unwrap();
+        let observacoes: Vec<_> = resultado.propostas.iter().map(|p| (p.item.as_str(), p.observacoes.as_deref())).collect();
+        assert_eq!(observacoes, [
+            ("1", Some("Adjudicado em favor de EMPRESA A LTDA por apresentar menor preço após negociação. amostra aprovada.")),
+            ("2", None),
+        ]);
+
+        let markdown = fs::read_to_string(root.join("ata.md")).unwrap();
+        assert!(markdown.contains("- **Observações**: Adjudicado em favor de EMPRESA A LTDA"), "{}", markdown);
+        assert_eq!(markdown.matches("- **Observações**").count(), 1);
+
+        // Parágrafos longos são cortados no limite
+        let longo = format!("Item 3 - Clipe\nMotivo: {}\n", "palavra ".repeat(100));
+        assert_eq!(extrair_observacoes_do_item(&longo, None, "3").unwrap().chars().count(), MAX_OBSERVACOES);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_porte_nao_confunde_a_palavra_me() {
+        assert_eq!(porte_canonico("Porte da empresa: não informado, consulte-me antes"), None);
+        assert_eq!(porte_canonico("Porte: a empresa me enviou a declaração depois"), None);
+
+        let texto = "Item 1 - Caneta esferográfica\nQuantidade: 100\n\
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL, para EMPRESA A LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 150,00\n\
+Porte: não declarado, avise-me se mudar\n";
+        assert_eq!(extrair_porte_do_contexto(texto, None, "1", "12.345.678/0001-90"), None);
+    }
+
+    #[test]
+    fn test_porte_do_fornecedor_antes_ou_depois_do_cnpj() {
+        let texto = "Item 1 - Caneta esferográfica\nQuantidade: 100\n\
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL, para EMPRESA A LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 150,00\n\
+Porte da empresa: Micro Empresa\n\
+Item 2 - Papel sulfite\nQuantidade: 10\n\
+Porte da empresa: Demais (Diferente de ME/EPP)\n\
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL, para EMPRESA B LTDA, CNPJ 11.222.333/0001-81, melhor lance: R$ 50,00\n\
+Item 3 - Clipe\nQuantidade: 5\n\
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL, para EMPRESA C LTDA ME/EPP, CNPJ 44.555.666/0001-77, melhor lance: R$ 10,00\n\
+Item 4 - Borracha\nQuantidade: 5\n\
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL, para EMPRESA D LTDA, CNPJ 77.888.999/0001-00, melhor lance: R$ 2,00\n";
+        let portes: Vec<_> = [("1", "12.345.678/0001-90"), ("2", "11.222.333/0001-81"), ("3", "44.555.666/0001-77"), ("4", "77.888.999/0001-00")]
+            .into_iter()
+            .map(|(item, cnpj)| extrair_porte_do_contexto(texto, None, item, cnpj))
+            .collect();
+        // O porte do item 1 (logo abaixo) não vaza para o item 2 nem o do 2 para o 1
+        assert_eq!(portes, [Some(PORTE_ME_EPP.to_string()), Some(PORTE_DEMAIS.to_string()), Some(PORTE_ME_EPP.to_string()), None]);
+        assert_eq!(porte_canonico("Porte da empresa: Empresa de Pequeno Porte"), Some(PORTE_ME_EPP));
+        assert_eq!(porte_canonico("Porte da empresa: Não informado"), None);
+        assert_eq!(porte_canonico("Porte: ME"), Some(PORTE_ME_EPP));
+
+        let resumo = ResumoPorte::somar([
+            (Some(PORTE_ME_EPP), Some(Valor(150.0))),
+            (Some(PORTE_DEMAIS), Some(Valor(50.0))),
+            (Some(PORTE_ME_EPP), Some(Valor(10.0))),
+            (None, Some(Valor(2.0))),
+        ]);
+        assert_eq!((resumo.valor_me_epp, resumo.itens_me_epp), (160.0, 2));
+        assert_eq!((resumo.valor_demais, resumo.itens_demais, resumo.itens_sem_porte), (50.0, 1, 1));
+        assert_eq!(resumo.percentual_me_epp, Some(160.0 / 210.0 * 100.0));
+
+        // No processamento, o porte vai para a proposta e para o markdown
+        let root = std::env::temp_dir().join(format!("licitacao360_porte_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let ata = texto::normalizar_texto(&format!("UASG 787000\nPREGÃO 90008/2024\n{}", texto.split("Item 2").next().unwrap()));
+        let resultado = processar_texto_licitacao(&ata, &root.join("ata.pdf"), &root, &[], false, Vec::new()).unwrap();
+        assert_eq!(resultado.propostas[0].porte.as_deref(), Some(PORTE_ME_EPP));
+
+        let markdown = fs::read_to_string(root.join("ata.md")).unwrap();
+        assert!(markdown.contains("| 12.345.678/0001-90 | ME/EPP |"), "{}", markdown);
+        assert!(markdown.contains("- **Adjudicado a ME/EPP**: R$ 150,00 em 1 itens (100,00%)"), "{}", markdown);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_recupera_ata_com_uso_privado_e_acentos_soltos() {
+        // Ata de impressora virtual: "Ã" como U+F0C3, ligadura "fi" em U+F001 e "Situac¸˜ao" do LaTeX
+        let bruto = include_str!("../tests/fixtures/ata_pua_acentos_soltos.txt");
+        assert!(extrair_propostas_grupo(bruto).is_empty());
+
+        let texto = texto::normalizar_texto(bruto);
+        let propostas = extrair_propostas_grupo(&texto);
+        let descricoes: Vec<_> = propostas.iter().map(|p| p.descricao.as_str()).collect();
+        assert_eq!(descricoes, ["Caneta esferográfica azul", "Parafuso de aço com especificação"]);
+        assert_eq!(propostas[1].valor_adjudicado, Some(Valor(22.0)));
+
+        let nao_adjudicados = extrair_itens_nao_adjudicados(&texto);
+        assert_eq!(nao_adjudicados.len(), 1);
+        assert_eq!(nao_adjudicados[0].descricao, "Clipe nº 2");
+        assert_eq!(extrair_data_homologacao(&texto), "Às 10:00 horas do dia 12 de março do ano de 2024");
+
+        let root = std::env::temp_dir().join(format!("licitacao360_ata_pua_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let resultado = processar_texto_licitacao(&texto, &root.join("ata.pdf"), &root, &[], false, Vec::new()).unwrap();
+        assert!(resultado.propostas.iter().all(|p| p.uasg == "787000" && p.pregao == "90008/2024"));
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_ata_html_e_txt_usam_as_mesmas_regex() {
+        let root = std::env::temp_dir().join(format!("licitacao360_ata_html_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let entrada = root.join("pdfs");
+        fs::create_dir_all(&entrada).unwrap();
+
+        // Como no HTML do Comprasnet: quebras do código-fonte no meio das frases e entidades
+        fs::write(entrada.join("ata.html"), "<html><body>\n\
+            <p>UASG 787000</p><p>PREG&Atilde;O 90008/2024</p>\n\
+            <table><tr><td>Item 1 -</td><td>Caneta esferogr&aacute;fica</td></tr></table>\n\
+            <p>Quantidade: 100</p><p>Valor estimado: R$ 2,00</p>\n\
+            <p>Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL, para\n\
+               EMPRESA A &amp; FILHOS LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 1,50</p>\n\
+            </body></html>").unwrap();
+        fs::write(entrada.join("ata.txt"), "UASG 160001\nPREGÃO 00012/2024\nItem 1 - Grampeador\nQuantidade: 5\n\
+Adjudicado e Homologado por CPF ***.456.***-*7 - CICLANO, para EMPRESA B LTDA, CNPJ 98.765.432/0001-10, melhor lance: R$ 35,00\n").unwrap();
+
+        let propostas = processar_diretorio_pdfs_com_progresso(
+            &entrada, &root.join("saida"), &FiltroVarredura::default(), &[], OpcoesProcessamento::default(), &AtomicBool::new(false), |_, _, _| {},
+        ).unwrap();
+
+        assert_eq!(propostas.len(), 2);
+        let html = propostas.iter().find(|p| p.uasg == "787000").unwrap();
+        assert_eq!(html.pregao, "90008/2024");
+        assert_eq!(html.fornecedor, "EMPRESA A & FILHOS LTDA");
+        assert_eq!(html.cnpj, "12.345.678/0001-90");
+        assert_eq!(html.valor_adjudicado, Some(Valor(1.5)));
+        let txt = propostas.iter().find(|p| p.uasg == "160001").unwrap();
+        assert_eq!(txt.fornecedor, "EMPRESA B LTDA");
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_total_do_item_conforme_layout_de_valor() {
+        let root = std::env::temp_dir().join(format!("licitacao360_total_item_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let ata = |rotulo: &str| texto::normalizar_texto(&format!("UASG 787000\nPREGÃO 90008/2024\n\
+Item 1 - Caneta esferográfica\nQuantidade: 100\n{}: R$ 2,00\n\
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL, para EMPRESA A LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 1,50\n", rotulo));
+
+        // "Valor estimado": o valor adjudicado já é o total do item
+        let total = processar_texto_licitacao(&ata("Valor estimado"), &root.join("total.pdf"), &root, &[], false, Vec::new()).unwrap();
+        let proposta = &total.propostas[0];
+        assert_eq!(proposta.valor_total_item, Some(Valor(1.5)));
+        assert_eq!(proposta.valor_unitario_adjudicado, None);
+        assert!(!proposta.valor_calculado);
+
+        // "Valor unitário estimado": total = quantidade × valor adjudicado
+        let unitario = processar_texto_licitacao(&ata("Valor unitário estimado"), &root.join("unitario.pdf"), &root, &[], false, Vec::new()).unwrap();
+        let proposta = &unitario.propostas[0];
+        assert_eq!(proposta.valor_unitario_estimado, Some(Valor(2.0)));
+        assert_eq!(proposta.valor_unitario_adjudicado, Some(Valor(1.5)));
+        assert_eq!(proposta.valor_total_item, Some(Valor(150.0)));
+        assert!(proposta.valor_calculado);
+        assert!(fs::read_to_string(root.join("unitario.md")).unwrap().contains("R$ 150.00"));
+
+        let consolidacao = salvar_json_consolidado(&unitario.propostas, &root, "unitario", &[], false).unwrap();
+        let caminhos = consolidacao.caminhos_licitacoes();
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&caminhos[0]).unwrap()).unwrap();
+        assert_eq!(json["valor_total"], 150.0);
+        assert_eq!(json["propostas"][0]["valor_calculado"], true);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_pdf_com_dois_pregoes_processado_por_secao() {
+        let root = std::env::temp_dir().join(format!("licitacao360_dois_pregoes_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let texto = texto::normalizar_texto("TERMO DE HOMOLOGAÇÃO\nUASG 787000\nPREGÃO 90008/2024\nProcesso nº 111\n\
+Item 1 - Caneta esferográfica\nQuantidade: 100\nValor estimado: R$ 2,00\n\
+Adjudicado e Homologado por CPF ***.123.***-*4 - FULANO DE TAL, para EMPRESA A LTDA, CNPJ 12.345.678/0001-90, melhor lance: R$ 1,50\n\
+Página 2 - PREGÃO 90008/2024\n\
+TERMO DE HOMOLOGAÇÃO\nUASG 160001\nPREGÃO 90015/2024\nProcesso nº 222\n\
+Item 1 - Papel A4\nQuantidade: 50\nValor estimado: R$ 30,00\n\
+Adjudicado e Homologado por CPF ***.456.***-*7 - CICLANO, para EMPRESA B LTDA, CNPJ 98.765.432/0001-10, melhor lance: R$ 25,00\n");
+
+        // A repetição do mesmo pregão no rodapé não abre seção nova; a UASG acima do marcador vai junto
+        let secoes = segmentar_por_pregao(&texto);
+        assert_eq!(secoes.len(), 2);
+        assert!(secoes[0].contains("Página 2 - PREGÃO 90008/2024"));
+        assert!(secoes[1].starts_with("UASG 160001\nPREGÃO 90015/2024"));
+        assert_eq!(segmentar_por_pregao("UASG 1\nPREGÃO 1/2024\nPREGÃO 1/2024"), vec!["UASG 1\nPREGÃO 1/2024\nPREGÃO 1/2024"]);
+
+        // Cabeçalhos de outras modalidades também separam; menções no corpo do texto, não
+        let secoes = segmentar_por_pregao("UASG 787000\nPREGÃO ELETRÔNICO Nº 90.008/2024\nItem 1\n\
+UASG 160001\nDISPENSA ELETRÔNICA Nº 90015/2024\nconforme o Pregão nº 90008/2024\nItem 1\n\
+Página 2 - DISPENSA ELETRÔNICA Nº 90.015/2024\n");
+        assert_eq!(secoes.len(), 2);
+        assert!(secoes[1].starts_with("UASG 160001\nDISPENSA ELETRÔNICA"));
+        assert!(secoes[1].ends_with("Página 2 - DISPENSA ELETRÔNICA Nº 90.015/2024\n"));
+
+        let resultado = processar_texto_licitacao(&texto, &root.join("atas.pdf"), &root, &[], false, Vec::new()).unwrap();
+        let atribuicoes: Vec<(&str, &str, &str, &str)> = resultado.propostas.iter()
+            .map(|p| (p.uasg.as_str(), p.pregao.as_str(), p.processo.as_str(), p.fornecedor.as_str()))
+            .collect();
+        assert_eq!(atribuicoes, vec![
+            ("787000", "90008/2024", "111", "EMPRESA A LTDA"),
+            ("160001", "90015/2024", "222", "EMPRESA B LTDA"),
+        ]);
+
+        let markdown = fs::read_to_string(root.join("atas.md")).unwrap();
+        assert!(markdown.contains("## Pregão 90008/2024 - UASG 787000"));
+        assert!(markdown.contains("## Pregão 90015/2024 - UASG 160001"));
+        assert_eq!(markdown.matches("### Informações Gerais").count(), 2);
+        assert!(root.join("atas_pregao_90015_2024.html").exists());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_extrair_certame_formatos_de_cabecalho() {
+        let casos = [
+            ("UASG 787000\nPREGÃO 90008/2024\n", MODALIDADE_PREGAO, "90008/2024"),
+            ("Pregão Eletrônico nº 90.012/2024 - UASG 787000", MODALIDADE_PREGAO, "90012/2024"),
+            ("PREGÃO ELETRÔNICO SRP Nº 90005/2025", MODALIDADE_PREGAO, "90005/2025"),
+            ("Pregão Eletrônico (SRP) N° 12/2024", MODALIDADE_PREGAO, "12/2024"),
+            ("Dispensa Eletrônica nº 90015/2024\nProcesso nº 123", MODALIDADE_DISPENSA, "90015/2024"),
+            ("DISPENSA DE LICITAÇÃO Nº 7/2024", MODALIDADE_DISPENSA, "7/2024"),
+            ("Concorrência nº 1/2025", MODALIDADE_CONCORRENCIA, "1/2025"),
+            ("CONCORRÊNCIA ELETRÔNICA Nº 90.001/2025", MODALIDADE_CONCORRENCIA, "90001/2025"),
+            // Vale o cabeçalho, não a menção posterior a outra modalidade
+            ("Dispensa Eletrônica nº 90020/2024\nconforme o Pregão nº 90008/2024", MODALIDADE_DISPENSA, "90020/2024"),
+            ("Ata sem cabeçalho reconhecível", MODALIDADE_PREGAO, "N/A"),
+        ];
+
+        for (texto, modalidade, numero) in casos {
+            assert_eq!(extrair_certame(texto), (modalidade.to_string(), numero.to_string()), "{}", texto);
+        }
+    }
+
+    #[test]
+    fn test_converter_data_homologacao_meses_e_formatos() {
+        let meses = [
+            "janeiro", "fevereiro", "março", "abril", "maio", "junho",
+            "julho", "agosto", "setembro", "outubro", "novembro", "dezembro",
+        ];
+        for (indice, mes) in meses.iter().enumerate() {
+            let texto = format!("Às 10:32 horas do dia 5 de {} do ano de 2024", mes);
+            assert_eq!(converter_data_homologacao(&texto), Some(format!("2024-{:02}-05T10:32:00-03:00", indice + 1)), "{}", mes);
+            // Caixa alta e sem hora
+            let texto = format!("DIA 5 DE {} DO ANO DE 2024", mes.to_uppercase());
+            assert_eq!(converter_data_homologacao(&texto), Some(format!("2024-{:02}-05T00:00:00-03:00", indice + 1)), "{}", mes);
+        }
+
+        let casos = [
+            ("Às 9:05 horas do dia 31 de Marco do ano de 2023", Some("2023-03-31T09:05:00-03:00")),
+            ("Às 14 horas do dia 1 de abril do ano de 2025", Some("2025-04-01T14:00:00-03:00")),
+            ("dia 7 de fevereiro de 24", Some("2024-02-07T00:00:00-03:00")),
+            ("Dia 12 de dezembro do ano de 2024", Some("2024-12-12T00:00:00-03:00")),
+            ("Às 10:00 horas do dia 30 de fevereiro do ano de 2024", None),
+            ("dia 5 de primavera de 2024", None),
+            ("N/A", None),
+        ];
+        for (texto, esperado) in casos {
+            assert_eq!(converter_data_homologacao(texto).as_deref(), esperado, "{}", texto);
+        }
+
+        // A frase por extenso sem hora também é extraída da ata
+        assert_eq!(extrair_data_homologacao("Homologação do dia 5 de março do ano de 2024."), "Dia 5 de março do ano de 2024");
+    }
+
+    #[test]
+    fn test_extrair_responsavel_formatos_de_assinatura() {
+        let casos = [
+            (
+                "HOMOLOGA a adjudicação do pregão. FULANO DE TAL, Ordenador de Despesas, matrícula 123456",
+                ("FULANO DE TAL", Some("Ordenador de Despesas"), Some("123456")),
+                "FULANO DE TAL, Ordenador de Despesas, matrícula 123456",
+            ),
+            (
+                "HOMOLOGA a adjudicação MARIA APARECIDA SOUZA - Pregoeira - Matrícula: 98.765-4.",
+                ("MARIA APARECIDA SOUZA", Some("Pregoeira"), Some("98.765-4")),
+                "MARIA APARECIDA SOUZA, Pregoeira, matrícula 98.765-4",
+            ),
+            (
+                "HOMOLOGA a adjudicação dos itens JOSÉ PEREIRA Chefe do Departamento de Licitações e Contratos\nItem 1",
+                ("JOSÉ PEREIRA", Some("Chefe do Departamento de Licitações e Contratos"), None),
+                "JOSÉ PEREIRA, Chefe do Departamento de Licitações e Contratos",
+            ),
+        ];
+        for (texto, (nome, cargo, matricula), concatenado) in casos {
+            let responsavel = extrair_responsavel(texto).unwrap();
+            assert_eq!(responsavel.nome, nome);
+            assert_eq!(responsavel.cargo.as_deref(), cargo);
+            assert_eq!(responsavel.matricula.as_deref(), matricula);
+            assert_eq!(responsavel.texto(), concatenado);
+        }
+
+        assert_eq!(extrair_responsavel("Ata sem assinatura de homologação"), None);
+        assert_eq!(separar_responsavel("CICLANO").unwrap().texto(), "CICLANO");
+    }
+}
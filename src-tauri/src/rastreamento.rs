@@ -0,0 +1,238 @@
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Level, Metadata, Span, Subscriber};
+use tracing_subscriber::filter::{DynFilterFn, LevelFilter};
+use tracing_subscriber::layer::{self, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, Layer};
+
+/// Arquivo de trace do pipeline, dentro de Database/Config/logs
+pub const ARQUIVO_TRACE: &str = "trace.log";
+
+/// Tamanho a partir do qual o trace.log é rotacionado
+pub const TAMANHO_MAXIMO_TRACE: u64 = 5 * 1024 * 1024;
+
+/// Quantidade de arquivos rotacionados mantidos (trace.log.1 é o mais recente)
+pub const ARQUIVOS_TRACE_ANTIGOS: usize = 3;
+
+/// DEBUG para todo o trace (configuração `verbose`), trocado por `definir_verbose`
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// Subscriber global já instalado
+static INICIADO: OnceLock<()> = OnceLock::new();
+
+/// Marca guardada nos spans de sessão abertos com `verbose = true`
+struct SpanVerbose;
+
+/// Lê o campo `verbose` de um span
+struct CampoVerbose(bool);
+
+impl Visit for CampoVerbose {
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        if field.name() == "verbose" {
+            self.0 = value;
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+/// Marca com `SpanVerbose` os spans criados com `verbose = true`
+struct MarcarSpansVerbose;
+
+impl<S> Layer<S> for MarcarSpansVerbose
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: layer::Context<'_, S>) {
+        let mut campo = CampoVerbose(false);
+        attrs.record(&mut campo);
+        if let Some(span) = ctx.span(id).filter(|_| campo.0) {
+            span.extensions_mut().insert(SpanVerbose);
+        }
+    }
+}
+
+/// INFO e acima sempre; DEBUG com a configuração `verbose` ou dentro de uma sessão verbose
+fn habilitado<S>(metadata: &Metadata<'_>, ctx: &layer::Context<'_, S>) -> bool
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    match *metadata.level() {
+        nivel if nivel <= Level::INFO => true,
+        Level::DEBUG => VERBOSE.load(Ordering::Relaxed)
+            || ctx.lookup_current().is_some_and(|span| span.scope().any(|s| s.extensions().get::<SpanVerbose>().is_some())),
+        _ => false,
+    }
+}
+
+fn caminho_rotacionado(pasta: &Path, indice: usize) -> PathBuf {
+    pasta.join(format!("{}.{}", ARQUIVO_TRACE, indice))
+}
+
+/// trace.log com rotação por tamanho: trace.log -> trace.log.1 -> ... -> trace.log.N (descartado)
+pub struct ArquivoRotativo {
+    pasta: PathBuf,
+    limite_bytes: u64,
+    arquivo: File,
+    tamanho: u64,
+}
+
+impl ArquivoRotativo {
+    pub fn abrir(pasta: &Path, limite_bytes: u64) -> Result<Self> {
+        fs::create_dir_all(pasta)
+            .context(format!("Erro ao criar pasta de logs: {}", pasta.display()))?;
+        let caminho = pasta.join(ARQUIVO_TRACE);
+        let arquivo = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&caminho)
+            .context(format!("Erro ao abrir arquivo de trace: {}", caminho.display()))?;
+        let tamanho = arquivo.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self { pasta: pasta.to_path_buf(), limite_bytes, arquivo, tamanho })
+    }
+
+    fn rotacionar(&mut self) -> io::Result<()> {
+        let _ = fs::remove_file(caminho_rotacionado(&self.pasta, ARQUIVOS_TRACE_ANTIGOS));
+        for indice in (1..ARQUIVOS_TRACE_ANTIGOS).rev() {
+            let origem = caminho_rotacionado(&self.pasta, indice);
+            if origem.exists() {
+                fs::rename(&origem, caminho_rotacionado(&self.pasta, indice + 1))?;
+            }
+        }
+        fs::rename(self.pasta.join(ARQUIVO_TRACE), caminho_rotacionado(&self.pasta, 1))?;
+        self.arquivo = OpenOptions::new().create(true).append(true).open(self.pasta.join(ARQUIVO_TRACE))?;
+        self.tamanho = 0;
+        Ok(())
+    }
+}
+
+impl Write for ArquivoRotativo {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.tamanho > 0 && self.tamanho + buf.len() as u64 > self.limite_bytes {
+            self.rotacionar()?;
+        }
+        let escritos = self.arquivo.write(buf)?;
+        self.tamanho += escritos as u64;
+        Ok(escritos)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.arquivo.flush()
+    }
+}
+
+/// Instala o subscriber global: trace.log (com rotação) e stderr, no nível INFO ou DEBUG
+///
+/// Chamado uma vez na inicialização; chamadas seguintes não fazem nada.
+pub fn iniciar(pasta: &Path, verbose: bool) -> Result<()> {
+    if INICIADO.get().is_some() {
+        return Ok(());
+    }
+    let arquivo = ArquivoRotativo::abrir(pasta, TAMANHO_MAXIMO_TRACE)?;
+    definir_verbose(verbose);
+
+    tracing_subscriber::registry()
+        .with(MarcarSpansVerbose)
+        .with(fmt::layer().with_ansi(false).with_writer(Mutex::new(arquivo)).with_filter(DynFilterFn::new(habilitado)))
+        .with(fmt::layer().with_writer(io::stderr).with_filter(LevelFilter::WARN))
+        .try_init()
+        .context("Erro ao instalar o subscriber de trace")?;
+    let _ = INICIADO.set(());
+    Ok(())
+}
+
+/// Nível do trace inteiro: DEBUG com `verbose`, INFO sem (segue a configuração)
+pub fn definir_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+/// Span de uma sessão de processamento; com `verbose`, só os eventos DEBUG dentro dele vão
+/// para o trace, sem mudar o nível das outras sessões
+pub fn span_sessao(id: &str, verbose: bool) -> Span {
+    tracing::info_span!("sessao", id = %id, verbose)
+}
+
+/// Últimas `n` linhas do trace, completando com o arquivo rotacionado mais recente se preciso
+pub fn linhas_recentes(pasta: &Path, n: usize) -> Result<Vec<String>> {
+    let mut linhas = Vec::new();
+    for caminho in [pasta.join(ARQUIVO_TRACE), caminho_rotacionado(pasta, 1)] {
+        if linhas.len() >= n {
+            break;
+        }
+        let conteudo = match fs::read(&caminho) {
+            Ok(conteudo) => String::from_utf8_lossy(&conteudo).into_owned(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e).context(format!("Erro ao ler arquivo de trace: {}", caminho.display())),
+        };
+        let faltam = n - linhas.len();
+        let mut anteriores: Vec<String> = conteudo.lines().rev().take(faltam).map(str::to_string).collect();
+        anteriores.reverse();
+        anteriores.append(&mut linhas);
+        linhas = anteriores;
+    }
+    Ok(linhas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotacao_e_linhas_recentes() {
+        let pasta = std::env::temp_dir().join(format!("licitacao360_trace_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&pasta);
+
+        let mut arquivo = ArquivoRotativo::abrir(&pasta, 30).unwrap();
+        for indice in 1..=12 {
+            writeln!(arquivo, "linha {:02}", indice).unwrap();
+        }
+        // 9 bytes por linha e limite de 30: três linhas por arquivo, só os três rotacionados mais recentes ficam
+        assert_eq!(fs::read_to_string(pasta.join(ARQUIVO_TRACE)).unwrap(), "linha 10\nlinha 11\nlinha 12\n");
+        assert!(caminho_rotacionado(&pasta, 3).exists());
+        assert!(!caminho_rotacionado(&pasta, 4).exists());
+
+        assert_eq!(linhas_recentes(&pasta, 2).unwrap(), ["linha 11", "linha 12"]);
+        assert_eq!(linhas_recentes(&pasta, 5).unwrap(), ["linha 08", "linha 09", "linha 10", "linha 11", "linha 12"]);
+        // Só o arquivo atual e o último rotacionado são lidos
+        assert_eq!(linhas_recentes(&pasta, 100).unwrap().len(), 6);
+
+        let _ = fs::remove_dir_all(&pasta);
+    }
+
+    #[test]
+    fn test_debug_so_dentro_da_sessao_verbose() {
+        let pasta = std::env::temp_dir().join(format!("licitacao360_trace_sessao_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&pasta);
+
+        let arquivo = ArquivoRotativo::abrir(&pasta, TAMANHO_MAXIMO_TRACE).unwrap();
+        let subscriber = tracing_subscriber::registry()
+            .with(MarcarSpansVerbose)
+            .with(fmt::layer().with_ansi(false).with_writer(Mutex::new(arquivo)).with_filter(DynFilterFn::new(habilitado)));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!("debug fora de sessão");
+            span_sessao("a", true).in_scope(|| {
+                tracing::debug!("debug da sessão verbose");
+                tracing::debug_span!("arquivo").in_scope(|| tracing::debug!("debug aninhado"));
+            });
+            span_sessao("b", false).in_scope(|| tracing::debug!("debug da sessão silenciosa"));
+            tracing::info!("info sempre");
+        });
+
+        let trace = fs::read_to_string(pasta.join(ARQUIVO_TRACE)).unwrap();
+        assert!(trace.contains("debug da sessão verbose"));
+        assert!(trace.contains("debug aninhado"));
+        assert!(trace.contains("info sempre"));
+        assert!(!trace.contains("debug fora de sessão"));
+        assert!(!trace.contains("debug da sessão silenciosa"));
+
+        let _ = fs::remove_dir_all(&pasta);
+    }
+}
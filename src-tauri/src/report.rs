@@ -0,0 +1,633 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use printpdf::path::PaintMode;
+use printpdf::{BuiltinFont, Color, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Rect, Rgb};
+use crate::exportacao::formatar_numero_br;
+use crate::pdf_processor::{converter_valor_para_float, formatar_economia};
+use crate::types::{
+    economia, formatar_valor, LicitacaoConsolidada, PropostaAdjudicada, PropostaConsolidada, RelatorioLicitacao, ResumoEconomia, ResumoPorte, Valor, SITUACAO_ADJUDICADO,
+};
+
+/// Estilos do relatório, embutidos para que o arquivo abra sem dependências
+const CSS: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Roboto, Arial, sans-serif; margin: 2rem; color: #1f2933; }
+h1 { font-size: 1.5rem; margin-bottom: 0.25rem; }
+h2 { font-size: 1.2rem; margin-top: 2rem; border-bottom: 1px solid #d9e2ec; padding-bottom: 0.25rem; }
+h3 { font-size: 1rem; margin-bottom: 0.25rem; }
+.gerado { color: #829ab1; font-size: 0.85rem; }
+dl { display: grid; grid-template-columns: max-content 1fr; gap: 0.25rem 1rem; }
+dt { font-weight: 600; }
+dd { margin: 0; }
+.aviso { background: #fff8e1; border-left: 4px solid #f0b429; padding: 0.5rem 0.75rem; margin: 0.5rem 0; }
+table { border-collapse: collapse; width: 100%; font-size: 0.9rem; }
+th, td { border: 1px solid #d9e2ec; padding: 0.4rem 0.6rem; text-align: left; vertical-align: top; }
+th { background: #f0f4f8; cursor: pointer; user-select: none; white-space: nowrap; }
+th[aria-sort="ascending"]::after { content: " ▲"; }
+th[aria-sort="descending"]::after { content: " ▼"; }
+tbody tr:nth-child(even) { background: #f8fafc; }
+td.numero { text-align: right; white-space: nowrap; }
+tfoot td { font-weight: 600; background: #f0f4f8; }
+.detalhe { margin-bottom: 1rem; }
+"#;
+
+/// Ordenação das tabelas: clique no cabeçalho alterna entre crescente e decrescente
+const SCRIPT: &str = r#"
+document.querySelectorAll("table.ordenavel").forEach(function (tabela) {
+  tabela.querySelectorAll("th").forEach(function (th, coluna) {
+    th.addEventListener("click", function () {
+      var crescente = th.getAttribute("aria-sort") !== "ascending";
+      tabela.querySelectorAll("th").forEach(function (outro) { outro.removeAttribute("aria-sort"); });
+      th.setAttribute("aria-sort", crescente ? "ascending" : "descending");
+      var corpo = tabela.tBodies[0];
+      var linhas = Array.prototype.slice.call(corpo.rows);
+      linhas.sort(function (a, b) {
+        var x = a.cells[coluna], y = b.cells[coluna];
+        var nx = parseFloat(x.dataset.valor), ny = parseFloat(y.dataset.valor);
+        var ordem = (!isNaN(nx) && !isNaN(ny))
+          ? nx - ny
+          : x.textContent.localeCompare(y.textContent, "pt-BR", { numeric: true });
+        return crescente ? ordem : -ordem;
+      });
+      linhas.forEach(function (linha) { corpo.appendChild(linha); });
+    });
+  });
+});
+"#;
+
+/// Escapa os caracteres especiais do HTML (descrições podem trazer "<", "&" e aspas)
+pub fn escapar_html(texto: &str) -> String {
+    let mut escapado = String::with_capacity(texto.len());
+    for c in texto.chars() {
+        match c {
+            '&' => escapado.push_str("&amp;"),
+            '<' => escapado.push_str("&lt;"),
+            '>' => escapado.push_str("&gt;"),
+            '"' => escapado.push_str("&quot;"),
+            '\'' => escapado.push_str("&#39;"),
+            outro => escapado.push(outro),
+        }
+    }
+    escapado
+}
+
+fn celula(texto: &str) -> String {
+    format!("<td>{}</td>", escapar_html(texto))
+}
+
+/// Célula ordenada pelo valor numérico (item "10" depois do "9", valores em reais)
+fn celula_numerica(texto: &str, valor: Option<f64>, prefixo: &str) -> String {
+    let valor = valor.map(|v| format!(" data-valor=\"{}\"", v)).unwrap_or_default();
+    format!("<td class=\"numero\"{}>{}{}</td>", valor, prefixo, escapar_html(texto))
+}
+
+/// Célula de valor monetário; sem valor, mostra "N/A" sem o prefixo
+fn celula_moeda(valor: Option<Valor>) -> String {
+    match valor {
+        Some(valor) => celula_numerica(&valor.to_string(), Some(valor.0), "R$ "),
+        None => celula_numerica("N/A", None, ""),
+    }
+}
+
+fn numero_item(item: &str) -> Option<f64> {
+    let digitos: String = item.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digitos.parse().ok()
+}
+
+fn cabecalho_tabela(html: &mut String, colunas: &[&str]) {
+    html.push_str("<table class=\"ordenavel\">\n<thead><tr>");
+    for coluna in colunas {
+        html.push_str(&format!("<th>{}</th>", coluna));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+}
+
+fn linha_adjudicada(proposta: &PropostaAdjudicada, consolidada: &PropostaConsolidada, tem_grupos: bool) -> String {
+    let (economia_absoluta, economia_percentual) = economia(consolidada.estimado_do_item(), consolidada.total_do_item());
+    let mut linha = String::from("<tr>");
+    linha.push_str(&celula_numerica(&proposta.item, numero_item(&proposta.item), ""));
+    if tem_grupos {
+        linha.push_str(&celula(proposta.grupo.as_deref().unwrap_or("N/A")));
+    }
+    linha.push_str(&celula(&proposta.descricao));
+    linha.push_str(&celula_numerica(&proposta.quantidade, converter_valor_para_float(&proposta.quantidade).ok(), ""));
+    linha.push_str(&celula_moeda(proposta.valor_estimado));
+    linha.push_str(&celula_moeda(proposta.valor_adjudicado));
+    linha.push_str(&celula_numerica(&formatar_economia(economia_absoluta, economia_percentual), economia_absoluta.map(|v| v.0), ""));
+    linha.push_str(&celula(&proposta.fornecedor));
+    linha.push_str(&celula(&proposta.cnpj));
+    linha.push_str(&celula(proposta.porte.as_deref().unwrap_or("N/A")));
+    linha.push_str(&celula(&proposta.marca_fabricante));
+    linha.push_str(&celula(&proposta.modelo_versao));
+    linha.push_str("</tr>\n");
+    linha
+}
+
+fn item_definicao(html: &mut String, termo: &str, valor: &str) {
+    html.push_str(&format!("<dt>{}</dt><dd>{}</dd>\n", termo, escapar_html(valor)));
+}
+
+/// Gera o relatório da licitação como um HTML standalone, com o mesmo conteúdo do Markdown
+///
+/// `consolidadas` são as propostas do relatório já consolidadas, na mesma ordem; a economia
+/// e o porte somam os valores do item inteiro, como no Markdown.
+pub fn gerar_html(relatorio: &RelatorioLicitacao, consolidadas: &[PropostaConsolidada]) -> String {
+    let (adjudicadas, nao_adjudicadas): (Vec<_>, Vec<_>) = relatorio.propostas
+        .iter()
+        .zip(consolidadas)
+        .partition(|(p, _)| p.situacao == SITUACAO_ADJUDICADO);
+    let tem_grupos = adjudicadas.iter().any(|(p, _)| p.grupo.is_some());
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"pt-BR\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!(
+        "<title>Licitação {} - UASG {}</title>\n<style>{}</style>\n</head>\n<body>\n",
+        escapar_html(&relatorio.pregao), escapar_html(&relatorio.uasg), CSS
+    ));
+
+    html.push_str("<h1>Relatório de Licitação - Propostas Adjudicadas</h1>\n");
+    html.push_str(&format!("<p class=\"gerado\">Gerado em {}</p>\n", Utc::now().format("%Y-%m-%d %H:%M:%S UTC")));
+
+    // Informações gerais
+    html.push_str("<h2>Informações Gerais</h2>\n<dl>\n");
+    item_definicao(&mut html, "UASG", &relatorio.uasg);
+    item_definicao(&mut html, "Pregão", &relatorio.pregao);
+    item_definicao(&mut html, "Processo", &relatorio.processo);
+    item_definicao(&mut html, "Data de Homologação", &relatorio.data_homologacao);
+    item_definicao(&mut html, "Responsável", &relatorio.responsavel_nome);
+    if let Some(cargo) = &relatorio.responsavel_cargo {
+        item_definicao(&mut html, "Cargo", cargo);
+    }
+    if let Some(matricula) = &relatorio.responsavel_matricula {
+        item_definicao(&mut html, "Matrícula", matricula);
+    }
+    item_definicao(&mut html, "Valor Total", &format!("R$ {:.2}", relatorio.valor_total));
+    html.push_str("</dl>\n");
+
+    for aviso in &relatorio.avisos {
+        html.push_str(&format!("<p class=\"aviso\">⚠️ {}</p>\n", escapar_html(aviso)));
+    }
+
+    // Tabela de propostas
+    html.push_str("<h2>Propostas Adjudicadas</h2>\n");
+    let mut colunas = vec!["Item"];
+    if tem_grupos {
+        colunas.push("Grupo");
+    }
+    colunas.extend(["Descrição", "Quantidade", "Valor Estimado", "Valor Adjudicado", "Economia", "Fornecedor", "CNPJ", "Porte", "Marca/Fabricante", "Modelo/Versão"]);
+    cabecalho_tabela(&mut html, &colunas);
+    for (proposta, consolidada) in &adjudicadas {
+        html.push_str(&linha_adjudicada(proposta, consolidada, tem_grupos));
+    }
+    html.push_str("</tbody>\n");
+
+    // Linha de total: só os itens com valor estimado entram na comparação
+    let resumo_economia = ResumoEconomia::somar(adjudicadas.iter().map(|(_, c)| (c.estimado_do_item(), c.total_do_item())));
+    if !adjudicadas.is_empty() {
+        let colunas_antes = if tem_grupos { 4 } else { 3 };
+        html.push_str(&format!("<tfoot><tr><td colspan=\"{}\">Total</td>", colunas_antes));
+        html.push_str(&celula_moeda(Some(Valor(resumo_economia.valor_estimado_total))));
+        html.push_str(&celula_moeda(Some(Valor(resumo_economia.valor_adjudicado_total))));
+        html.push_str(&celula_numerica(
+            &formatar_economia(Some(Valor(resumo_economia.economia_total)), resumo_economia.economia_percentual),
+            Some(resumo_economia.economia_total),
+            "",
+        ));
+        html.push_str("<td colspan=\"5\"></td></tr></tfoot>\n");
+    }
+    html.push_str("</table>\n");
+
+    // Detalhes das propostas
+    html.push_str("<h2>Detalhes das Propostas</h2>\n");
+    for (proposta, _) in &adjudicadas {
+        let grupo = proposta.grupo.as_ref().map(|g| format!(" ({})", escapar_html(g))).unwrap_or_default();
+        html.push_str(&format!(
+            "<div class=\"detalhe\">\n<h3>Item {}{} - {}</h3>\n<dl>\n",
+            escapar_html(&proposta.item), grupo, escapar_html(&proposta.descricao)
+        ));
+        item_definicao(&mut html, "Quantidade", &proposta.quantidade);
+        item_definicao(&mut html, "Valor Estimado", &format!("R$ {}", formatar_valor(proposta.valor_estimado)));
+        item_definicao(&mut html, "Valor Adjudicado", &format!("R$ {}", formatar_valor(proposta.valor_adjudicado)));
+        item_definicao(&mut html, "Fornecedor", &proposta.fornecedor);
+        item_definicao(&mut html, "CNPJ", &proposta.cnpj);
+        if let Some(porte) = &proposta.porte {
+            item_definicao(&mut html, "Porte", porte);
+        }
+        item_definicao(&mut html, "Melhor Lance", &format!("R$ {}", formatar_valor(proposta.melhor_lance)));
+        item_definicao(&mut html, "Responsável", &proposta.responsavel);
+        item_definicao(&mut html, "CPF Responsável", &proposta.cpf_responsavel);
+        item_definicao(&mut html, "Marca/Fabricante", &proposta.marca_fabricante);
+        item_definicao(&mut html, "Modelo/Versão", &proposta.modelo_versao);
+        if let Some(observacoes) = &proposta.observacoes {
+            item_definicao(&mut html, "Observações", observacoes);
+        }
+        html.push_str("</dl>\n</div>\n");
+    }
+
+    // Itens desertos, fracassados ou cancelados
+    if !nao_adjudicadas.is_empty() {
+        html.push_str("<h2>Itens não adjudicados</h2>\n");
+        cabecalho_tabela(&mut html, &["Item", "Grupo", "Descrição", "Quantidade", "Valor Estimado", "Situação"]);
+        for (proposta, _) in &nao_adjudicadas {
+            html.push_str("<tr>");
+            html.push_str(&celula_numerica(&proposta.item, numero_item(&proposta.item), ""));
+            html.push_str(&celula(proposta.grupo.as_deref().unwrap_or("N/A")));
+            html.push_str(&celula(&proposta.descricao));
+            html.push_str(&celula_numerica(&proposta.quantidade, converter_valor_para_float(&proposta.quantidade).ok(), ""));
+            html.push_str(&celula_moeda(proposta.valor_estimado));
+            html.push_str(&celula(&proposta.situacao));
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</tbody>\n</table>\n");
+    }
+
+    // Resumo estatístico
+    html.push_str("<h2>Resumo Estatístico</h2>\n<dl>\n");
+    item_definicao(&mut html, "Total de Itens Adjudicados", &adjudicadas.len().to_string());
+    if !nao_adjudicadas.is_empty() {
+        item_definicao(&mut html, "Total de Itens Não Adjudicados", &nao_adjudicadas.len().to_string());
+    }
+    item_definicao(&mut html, "Valor Total das Adjudicações", &format!("R$ {:.2}", relatorio.valor_total));
+    if !adjudicadas.is_empty() {
+        let valor_medio = relatorio.valor_total / adjudicadas.len() as f64;
+        item_definicao(&mut html, "Valor Médio por Item", &format!("R$ {:.2}", valor_medio));
+        item_definicao(
+            &mut html,
+            "Economia Total",
+            &formatar_economia(Some(Valor(resumo_economia.economia_total)), resumo_economia.economia_percentual),
+        );
+        if resumo_economia.itens_sem_valor_estimado > 0 {
+            item_definicao(&mut html, "Itens sem Valor Estimado (fora do cálculo da economia)", &resumo_economia.itens_sem_valor_estimado.to_string());
+        }
+
+        // Cota reservada da LC 123: quanto foi para ME/EPP
+        let resumo_porte = ResumoPorte::somar(adjudicadas.iter().map(|(p, c)| (p.porte.as_deref(), c.total_do_item())));
+        if resumo_porte.itens_me_epp + resumo_porte.itens_demais > 0 {
+            let percentual = resumo_porte.percentual_me_epp
+                .map(|p| format!(" ({}%)", formatar_numero_br(p)))
+                .unwrap_or_default();
+            item_definicao(&mut html, "Adjudicado a ME/EPP", &format!(
+                "R$ {} em {} itens{}", Valor(resumo_porte.valor_me_epp), resumo_porte.itens_me_epp, percentual,
+            ));
+            item_definicao(&mut html, "Adjudicado aos Demais", &format!(
+                "R$ {} em {} itens", Valor(resumo_porte.valor_demais), resumo_porte.itens_demais,
+            ));
+            if resumo_porte.itens_sem_porte > 0 {
+                item_definicao(&mut html, "Itens sem Porte Informado", &resumo_porte.itens_sem_porte.to_string());
+            }
+        }
+    }
+    html.push_str("</dl>\n");
+
+    html.push_str(&format!("<script>{}</script>\n</body>\n</html>\n", SCRIPT));
+    html
+}
+
+/// Página do relatório em PDF: A4 paisagem, medidas em mm
+const LARGURA_PAGINA: f32 = 297.0;
+const ALTURA_PAGINA: f32 = 210.0;
+const MARGEM: f32 = 10.0;
+/// Espaço reservado no pé da página para a numeração
+const ALTURA_RODAPE: f32 = 8.0;
+const FONTE_TABELA: f32 = 8.0;
+const ALTURA_LINHA_TABELA: f32 = 3.5;
+const RECUO_CELULA: f32 = 1.2;
+
+/// Colunas da tabela de propostas do PDF (título, largura em mm); somam a largura útil da página
+const COLUNAS_PDF: [(&str, f32); 8] = [
+    ("Item", 12.0),
+    ("Descrição", 88.0),
+    ("Qtd.", 16.0),
+    ("Valor Estimado", 26.0),
+    ("Valor Adjudicado", 26.0),
+    ("Fornecedor", 55.0),
+    ("CNPJ", 34.0),
+    ("Situação", 20.0),
+];
+
+/// Largura aproximada de um caractere da Helvetica, em milésimos do tamanho da fonte
+fn largura_caractere(c: char) -> f32 {
+    match c {
+        ' ' | '.' | ',' | ':' | ';' | '!' | '\'' | '|' | 'i' | 'j' | 'l' | 'í' => 278.0,
+        'f' | 't' | 'r' | 'I' | 'Í' | '/' | '(' | ')' | '-' | '"' => 333.0,
+        'm' | 'M' => 833.0,
+        'w' => 722.0,
+        'W' => 944.0,
+        '@' | '%' => 889.0,
+        c if c.is_uppercase() => 722.0,
+        _ => 556.0,
+    }
+}
+
+/// Largura do texto em mm para o tamanho de fonte informado (em pontos)
+pub fn largura_texto(texto: &str, tamanho_fonte: f32) -> f32 {
+    const MM_POR_PONTO: f32 = 25.4 / 72.0;
+    texto.chars().map(largura_caractere).sum::<f32>() / 1000.0 * tamanho_fonte * MM_POR_PONTO
+}
+
+/// Quebra o texto em linhas que cabem na largura; palavras maiores que a linha são cortadas
+pub fn quebrar_linhas(texto: &str, largura_maxima: f32, tamanho_fonte: f32) -> Vec<String> {
+    let mut linhas: Vec<String> = Vec::new();
+    let mut atual = String::new();
+
+    for palavra in texto.split_whitespace() {
+        let candidata = if atual.is_empty() { palavra.to_string() } else { format!("{} {}", atual, palavra) };
+        if largura_texto(&candidata, tamanho_fonte) <= largura_maxima {
+            atual = candidata;
+            continue;
+        }
+        if !atual.is_empty() {
+            linhas.push(std::mem::take(&mut atual));
+        }
+        for c in palavra.chars() {
+            atual.push(c);
+            if largura_texto(&atual, tamanho_fonte) > largura_maxima && atual.chars().count() > 1 {
+                atual.pop();
+                linhas.push(std::mem::replace(&mut atual, c.to_string()));
+            }
+        }
+    }
+
+    if !atual.is_empty() || linhas.is_empty() {
+        linhas.push(atual);
+    }
+    linhas
+}
+
+/// Data de homologação gravada nas propostas (a mesma para toda a ata)
+fn data_homologacao(licitacao: &LicitacaoConsolidada) -> &str {
+    licitacao.propostas.iter()
+        .map(|p| p.data_homologacao.as_str())
+        .find(|data| !data.is_empty() && *data != "N/A")
+        .unwrap_or("N/A")
+}
+
+
+/// Documento em construção: controla a posição vertical e abre páginas novas quando falta espaço
+struct EscritorPdf {
+    documento: PdfDocumentReference,
+    camadas: Vec<PdfLayerReference>,
+    fonte: IndirectFontRef,
+    fonte_negrito: IndirectFontRef,
+    y: f32,
+}
+
+impl EscritorPdf {
+    fn novo(titulo: &str) -> Result<Self> {
+        let (documento, pagina, camada) = PdfDocument::new(titulo, Mm(LARGURA_PAGINA), Mm(ALTURA_PAGINA), "Camada 1");
+        let fonte = documento.add_builtin_font(BuiltinFont::Helvetica)
+            .map_err(|e| anyhow!("Erro ao carregar fonte do PDF: {}", e))?;
+        let fonte_negrito = documento.add_builtin_font(BuiltinFont::HelveticaBold)
+            .map_err(|e| anyhow!("Erro ao carregar fonte do PDF: {}", e))?;
+        let camada = documento.get_page(pagina).get_layer(camada);
+        Ok(Self { documento, camadas: vec![camada], fonte, fonte_negrito, y: ALTURA_PAGINA - MARGEM })
+    }
+
+    fn camada(&self) -> &PdfLayerReference {
+        self.camadas.last().expect("o documento sempre tem uma página")
+    }
+
+    fn nova_pagina(&mut self) {
+        let (pagina, camada) = self.documento.add_page(Mm(LARGURA_PAGINA), Mm(ALTURA_PAGINA), "Camada 1");
+        self.camadas.push(self.documento.get_page(pagina).get_layer(camada));
+        self.y = ALTURA_PAGINA - MARGEM;
+    }
+
+    /// Garante `altura` mm livres antes do rodapé; retorna `true` quando abriu uma página nova
+    fn garantir_espaco(&mut self, altura: f32) -> bool {
+        if self.y - altura < MARGEM + ALTURA_RODAPE {
+            self.nova_pagina();
+            return true;
+        }
+        false
+    }
+
+    fn texto(&self, texto: &str, tamanho: f32, x: f32, y: f32, negrito: bool) {
+        let fonte = if negrito { &self.fonte_negrito } else { &self.fonte };
+        self.camada().use_text(texto, tamanho, Mm(x), Mm(y), fonte);
+    }
+
+    /// Escreve uma linha de texto corrido e avança o cursor
+    fn linha(&mut self, texto: &str, tamanho: f32, negrito: bool) {
+        let altura = tamanho * 0.5;
+        self.garantir_espaco(altura);
+        self.y -= altura;
+        self.texto(texto, tamanho, MARGEM, self.y, negrito);
+    }
+
+    /// Desenha uma linha da tabela; cada célula quebra o texto na largura da sua coluna
+    fn linha_tabela(&mut self, celulas: &[String], cabecalho: bool) {
+        let linhas_celulas: Vec<Vec<String>> = celulas.iter()
+            .zip(COLUNAS_PDF)
+            .map(|(texto, (_, largura))| quebrar_linhas(texto, largura - 2.0 * RECUO_CELULA, FONTE_TABELA))
+            .collect();
+        let linhas = linhas_celulas.iter().map(Vec::len).max().unwrap_or(1);
+        let altura = linhas as f32 * ALTURA_LINHA_TABELA + 2.0 * RECUO_CELULA;
+
+        if self.garantir_espaco(altura) && !cabecalho {
+            self.cabecalho_tabela();
+        }
+
+        let topo = self.y;
+        let base = topo - altura;
+        let camada = self.camada().clone();
+        if cabecalho {
+            camada.set_fill_color(Color::Rgb(Rgb::new(0.94, 0.96, 0.97, None)));
+            camada.add_rect(Rect::new(Mm(MARGEM), Mm(base), Mm(LARGURA_PAGINA - MARGEM), Mm(topo)));
+            camada.set_fill_color(Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None)));
+        }
+        camada.set_outline_color(Color::Rgb(Rgb::new(0.85, 0.89, 0.93, None)));
+        camada.set_outline_thickness(0.5);
+
+        let mut x = MARGEM;
+        for (texto_celula, (_, largura)) in linhas_celulas.iter().zip(COLUNAS_PDF) {
+            camada.add_rect(Rect::new(Mm(x), Mm(base), Mm(x + largura), Mm(topo)).with_mode(PaintMode::Stroke));
+            for (indice, linha) in texto_celula.iter().enumerate() {
+                let y = topo - RECUO_CELULA - (indice as f32 + 1.0) * ALTURA_LINHA_TABELA + 0.8;
+                self.texto(linha, FONTE_TABELA, x + RECUO_CELULA, y, cabecalho);
+            }
+            x += largura;
+        }
+        self.y = base;
+    }
+
+    fn cabecalho_tabela(&mut self) {
+        let titulos: Vec<String> = COLUNAS_PDF.iter().map(|(titulo, _)| titulo.to_string()).collect();
+        self.linha_tabela(&titulos, true);
+    }
+
+    /// Numera as páginas ("Página 1 de 3") e serializa o documento
+    fn finalizar(self) -> Result<Vec<u8>> {
+        let total = self.camadas.len();
+        for (indice, camada) in self.camadas.iter().enumerate() {
+            let rotulo = format!("Página {} de {}", indice + 1, total);
+            let x = LARGURA_PAGINA - MARGEM - largura_texto(&rotulo, FONTE_TABELA);
+            camada.use_text(rotulo, FONTE_TABELA, Mm(x), Mm(MARGEM), &self.fonte);
+        }
+        self.documento.save_to_bytes()
+            .map_err(|e| anyhow!("Erro ao gerar PDF: {}", e))
+    }
+}
+
+/// Gera o relatório resumido da licitação em PDF: cabeçalho, tabela de propostas e totais
+pub fn gerar_pdf(licitacao: &LicitacaoConsolidada) -> Result<Vec<u8>> {
+    let titulo = format!("Licitação {} - UASG {}", licitacao.pregao, licitacao.uasg);
+    let mut pdf = EscritorPdf::novo(&titulo)?;
+
+    // Cabeçalho
+    pdf.linha("Relatório de Licitação", 16.0, true);
+    pdf.y -= 2.0;
+    pdf.linha(&format!("UASG: {}    Pregão: {}    Processo: {}", licitacao.uasg, licitacao.pregao, licitacao.processo), 10.0, false);
+    pdf.linha(&format!("Data de Homologação: {}", data_homologacao(licitacao)), 10.0, false);
+    pdf.linha(&format!("Gerado em {}", Utc::now().format("%Y-%m-%d %H:%M:%S UTC")), 8.0, false);
+    pdf.y -= 4.0;
+
+    // Tabela de propostas
+    pdf.cabecalho_tabela();
+    for proposta in &licitacao.propostas {
+        let adjudicada = proposta.situacao == SITUACAO_ADJUDICADO;
+        let celulas = [
+            proposta.item.clone(),
+            proposta.descricao.clone(),
+            proposta.quantidade.clone(),
+            formatar_valor(proposta.valor_estimado),
+            if adjudicada { formatar_valor(proposta.valor_adjudicado) } else { String::new() },
+            proposta.fornecedor.clone(),
+            proposta.cnpj.clone(),
+            proposta.situacao.clone(),
+        ];
+        pdf.linha_tabela(&celulas, false);
+    }
+
+    // Rodapé com os totais
+    let adjudicadas = licitacao.propostas.iter().filter(|p| p.situacao == SITUACAO_ADJUDICADO).count();
+    let valor_estimado: f64 = licitacao.propostas.iter().filter_map(|p| p.valor_estimado).map(|valor| valor.0).sum();
+    pdf.y -= 3.0;
+    pdf.linha(&format!(
+        "Total de itens: {}    Adjudicados: {}    Não adjudicados: {}",
+        licitacao.propostas.len(), adjudicadas, licitacao.propostas.len() - adjudicadas
+    ), 10.0, true);
+    pdf.linha(&format!(
+        "Valor total estimado: R$ {}    Valor total adjudicado: R$ {}",
+        formatar_numero_br(valor_estimado), formatar_numero_br(licitacao.valor_total)
+    ), 10.0, true);
+
+    pdf.finalizar()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MODALIDADE_PREGAO;
+
+    fn proposta(item: &str, descricao: &str, situacao: &str) -> PropostaAdjudicada {
+        serde_json::from_value(serde_json::json!({
+            "item": item, "grupo": null, "descricao": descricao, "quantidade": "10",
+            "valor_estimado": "1.000,00", "valor_adjudicado": "950,00", "fornecedor": "A & B <Comércio> LTDA",
+            "cnpj": "11.222.333/0001-81", "melhor_lance": "950,00", "responsavel": "N/A", "cpf_responsavel": "N/A",
+            "marca_fabricante": "N/A", "modelo_versao": "N/A", "tipo_formato": "individual", "situacao": situacao
+        })).unwrap()
+    }
+
+    fn consolidada(item: &str, situacao: &str) -> PropostaConsolidada {
+        serde_json::from_value(serde_json::json!({
+            "uasg": "787000", "pregao": "90008/2024", "processo": "123", "item": item, "grupo": null,
+            "quantidade": "10", "descricao": "N/A", "valor_estimado": "1.000,00", "valor_adjudicado": "950,00",
+            "fornecedor": "N/A", "cnpj": "N/A", "marca_fabricante": "N/A", "modelo_versao": "N/A", "responsavel": "N/A",
+            "melhor_lance": "950,00", "tipo_formato": "individual", "situacao": situacao
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_gerar_html_escapa_conteudo_do_pdf() {
+        let mut relatorio = RelatorioLicitacao {
+            uasg: "787000".to_string(),
+            pregao: "90008/2024".to_string(),
+            modalidade: MODALIDADE_PREGAO.to_string(),
+            processo: "123".to_string(),
+            data_homologacao: "01/02/2024".to_string(),
+            data_homologacao_iso: None,
+            responsavel: "N/A".to_string(),
+            responsavel_nome: "N/A".to_string(),
+            responsavel_cargo: None,
+            responsavel_matricula: None,
+            valor_total: 950.0,
+            propostas: vec![
+                proposta("10", "Cabo <script>alert(\"x\")</script> 2,5mm² 'flex'", SITUACAO_ADJUDICADO),
+                proposta("2", "Item sem lances", "Deserto"),
+            ],
+            origem: Default::default(),
+            avisos: vec!["Valor > estimado".to_string()],
+            provavelmente_escaneado: false,
+        };
+
+        relatorio.propostas[0].porte = Some(crate::types::PORTE_ME_EPP.to_string());
+        relatorio.propostas[0].observacoes = Some("Amostra aprovada".to_string());
+        let consolidadas = [consolidada("10", SITUACAO_ADJUDICADO), consolidada("2", "Deserto")];
+
+        let html = gerar_html(&relatorio, &consolidadas);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(!html.contains("<script>alert"));
+        assert!(html.contains("Cabo &lt;script&gt;alert(&quot;x&quot;)&lt;/script&gt; 2,5mm² &#39;flex&#39;"));
+        assert!(html.contains("A &amp; B &lt;Comércio&gt; LTDA"));
+        assert!(html.contains("⚠️ Valor &gt; estimado"));
+
+        // Células numéricas carregam o valor para a ordenação
+        assert!(html.contains("<td class=\"numero\" data-valor=\"10\">10</td>"));
+        assert!(html.contains("data-valor=\"950\">R$ 950,00</td>"));
+        assert!(html.contains("Itens não adjudicados"));
+        assert_eq!(html.matches("<table class=\"ordenavel\">").count(), 2);
+
+        // Mesmas seções do Markdown: economia por item e no total, porte e observações
+        assert!(html.contains("<th>Economia</th>") && html.contains("<th>Porte</th>"));
+        assert!(html.contains("data-valor=\"50\">R$ 50,00 (5,00%)</td>"));
+        assert!(html.contains("<tfoot><tr><td colspan=\"3\">Total</td>"));
+        assert!(html.contains("<dt>Economia Total</dt><dd>R$ 50,00 (5,00%)</dd>"));
+        assert!(html.contains("<dt>Observações</dt><dd>Amostra aprovada</dd>"));
+        assert!(html.contains("<dt>Adjudicado a ME/EPP</dt><dd>R$ 950,00 em 1 itens (100,00%)</dd>"));
+    }
+
+    #[test]
+    fn test_quebrar_linhas_respeita_a_largura() {
+        let descricao = "CABO ELÉTRICO FLEXÍVEL 2,5MM² ISOLAMENTO 750V ROLO COM 100 METROS NA COR AZUL";
+        let linhas = quebrar_linhas(descricao, 40.0, FONTE_TABELA);
+        assert!(linhas.len() > 1);
+        assert!(linhas.iter().all(|linha| largura_texto(linha, FONTE_TABELA) <= 40.0));
+        assert_eq!(linhas.join(" "), descricao);
+
+        // Palavra maior que a coluna é cortada em vez de vazar da célula
+        let cortada = quebrar_linhas(&"A".repeat(60), 20.0, FONTE_TABELA);
+        assert!(cortada.len() > 1);
+        assert_eq!(cortada.concat(), "A".repeat(60));
+        assert_eq!(quebrar_linhas("", 20.0, FONTE_TABELA), vec![String::new()]);
+    }
+
+    #[test]
+    fn test_gerar_pdf_quebra_paginas() {
+        let propostas: Vec<crate::types::PropostaConsolidada> = (1..=80).map(|item| serde_json::from_value(serde_json::json!({
+            "uasg": "787000", "pregao": "90008/2024", "processo": "123", "item": item.to_string(), "grupo": null,
+            "quantidade": "10", "descricao": "Descrição longa de material de consumo ".repeat(4),
+            "valor_estimado": "1.000,00", "valor_adjudicado": "950,00", "fornecedor": "EMPRESA LTDA",
+            "cnpj": "11.222.333/0001-81", "marca_fabricante": "N/A", "modelo_versao": "N/A", "responsavel": "N/A",
+            "melhor_lance": "950,00", "tipo_formato": "individual", "data_homologacao": "01/02/2024"
+        })).unwrap()).collect();
+        let licitacao = LicitacaoConsolidada {
+            uasg: "787000".to_string(),
+            pregao: "90008/2024".to_string(),
+            modalidade: MODALIDADE_PREGAO.to_string(),
+            processo: "123".to_string(),
+            total_propostas: propostas.len(),
+            valor_total: 950.0 * 80.0,
+            propostas,
+            itens_adjudicados: 80,
+            itens_desertos: 0,
+            itens_fracassados: 0,
+            itens_cancelados: 0,
+        };
+        assert_eq!(data_homologacao(&licitacao), "01/02/2024");
+
+        let bytes = gerar_pdf(&licitacao).unwrap();
+        assert!(bytes.starts_with(b"%PDF"));
+        let paginas = printpdf::lopdf::Document::load_mem(&bytes).unwrap().get_pages().len();
+        assert!(paginas > 1, "esperava mais de uma página, obteve {}", paginas);
+    }
+}
@@ -0,0 +1,1585 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+use walkdir::WalkDir;
+use crate::{integridade, migracoes, paths, pdf_processor, routing, storage, validators};
+use crate::types::{
+    formatar_valor, valor_opcional, ConsolidadoJson, FiltroArquivosLicitacao, FornecedorHistory, FornecedorResumo, ItemFornecedor, LicitacaoConsolidada, LicitacaoFileInfo, LicitacoesSummary, PropostaConsolidada, PropostaEncontrada,
+    CampoAlterado, ComparacaoLicitacoes, ParticipacaoFornecedor, PropostaAlterada, PropostasPaginadas, ReprocessamentoPdf, ResultadoBuscaPropostas, ResumoEconomia, ResumoGeral, ResumoPorte, RoutingRule, UasgResumo, Valor, MODALIDADE_PREGAO, SITUACAO_ADJUDICADO,
+};
+
+/// Prefixo dos arquivos JSON gerados por licitação
+pub const PREFIXO_LICITACAO: &str = "licitacao_";
+
+/// Campos pesquisáveis por `pesquisar_propostas`
+pub const CAMPOS_BUSCA: [&str; 3] = ["fornecedor", "cnpj", "descricao"];
+
+/// Limite padrão de resultados de uma busca
+pub const LIMITE_BUSCA_PADRAO: usize = 100;
+
+/// Quantidade de fornecedores no ranking do resumo
+pub const TOP_FORNECEDORES: usize = 10;
+
+/// Contagem e valor total de um JSON de licitação, de qualquer geração do formato
+#[derive(Debug, Clone, PartialEq)]
+pub struct TotaisArquivo {
+    pub total_propostas: Option<u64>,
+    pub valor_total: Option<f64>,
+    /// Algum dos valores foi derivado das propostas em vez de lido do arquivo
+    pub inferido: bool,
+}
+
+/// Número gravado como number ou como texto ("12", "1.234,56")
+pub(crate) fn numero_tolerante(valor: &serde_json::Value) -> Option<f64> {
+    match valor {
+        serde_json::Value::Number(numero) => numero.as_f64(),
+        serde_json::Value::String(texto) => Valor::de_texto(texto).map(|valor| valor.0),
+        _ => None,
+    }
+}
+
+/// Lê total_propostas e valor_total aceitando number ou texto
+///
+/// Sem o campo (ou com um valor ilegível), a contagem vem de `propostas.len()` e o valor total
+/// da soma dos itens; nesses casos `inferido` fica verdadeiro.
+pub fn totais_do_arquivo(json: &serde_json::Value) -> TotaisArquivo {
+    let propostas = json.get("propostas").and_then(|p| p.as_array());
+    let mut inferido = false;
+
+    let lido = json.get("total_propostas")
+        .and_then(numero_tolerante)
+        .filter(|total| *total >= 0.0 && total.fract() == 0.0)
+        .map(|total| total as u64);
+    let total_propostas = match lido {
+        Some(total) => Some(total),
+        None => {
+            inferido |= propostas.is_some();
+            propostas.map(|p| p.len() as u64)
+        }
+    };
+
+    let valor_total = match json.get("valor_total").and_then(numero_tolerante) {
+        Some(valor) => Some(valor),
+        None => {
+            let soma = propostas
+                .and_then(|p| serde_json::from_value::<Vec<PropostaConsolidada>>(serde_json::Value::Array(p.clone())).ok())
+                .map(|p| p.iter().filter_map(|proposta| proposta.total_do_item()).map(|valor| valor.0).sum());
+            inferido |= soma.is_some();
+            soma
+        }
+    };
+
+    TotaisArquivo { total_propostas, valor_total, inferido }
+}
+
+/// Pasta de resultados mais os destinos das regras de roteamento que existem
+pub fn diretorios_resultados(output_dir: &Path, regras: &[RoutingRule]) -> Vec<PathBuf> {
+    let mut diretorios = vec![output_dir.to_path_buf()];
+    for destino in routing::diretorios_roteados(regras) {
+        if destino.exists() && !diretorios.contains(&destino) {
+            diretorios.push(destino);
+        }
+    }
+    diretorios
+}
+
+/// Lista os JSONs de licitação dos diretórios, ordenados e sem repetição
+pub fn listar_arquivos_licitacao(diretorios: &[PathBuf]) -> Vec<PathBuf> {
+    let mut arquivos: Vec<PathBuf> = diretorios.iter()
+        .flat_map(|dir| WalkDir::new(dir).max_depth(2).into_iter().filter_map(|e| e.ok()))
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| e.file_name().to_string_lossy().starts_with(PREFIXO_LICITACAO) && paths::is_json_file(e.path()))
+        .map(|e| e.path().to_path_buf())
+        .collect();
+
+    arquivos.sort();
+    arquivos.dedup();
+    arquivos
+}
+
+/// Chave pública de uma licitação: o nome do arquivo sem o prefixo e a extensão
+pub fn chave_licitacao(path: &Path) -> String {
+    let nome = path.file_stem().unwrap_or_default().to_string_lossy();
+    nome.strip_prefix(PREFIXO_LICITACAO).unwrap_or(&nome).to_string()
+}
+
+/// Aceita apenas chaves que não saem da pasta de resultados
+pub fn chave_valida(chave: &str) -> bool {
+    !chave.is_empty()
+        && !chave.contains("..")
+        && !chave.contains('/')
+        && !chave.contains('\\')
+        && !chave.contains(':')
+}
+
+/// Localiza o arquivo de uma licitação pela chave
+pub fn encontrar_licitacao(diretorios: &[PathBuf], chave: &str) -> Option<PathBuf> {
+    if !chave_valida(chave) {
+        return None;
+    }
+
+    listar_arquivos_licitacao(diretorios)
+        .into_iter()
+        .find(|path| chave_licitacao(path) == chave)
+}
+
+/// Carrega um JSON de licitação gerado por `salvar_json_consolidado`, migrando esquemas antigos
+pub fn carregar_licitacao(path: &Path) -> Result<LicitacaoConsolidada> {
+    let content = fs::read_to_string(path)
+        .context(format!("Erro ao ler arquivo: {}", path.display()))?;
+    let mut json: serde_json::Value = serde_json::from_str(&content)
+        .context(format!("Erro ao analisar JSON de licitação: {}", path.display()))?;
+    if migracoes::precisa_migrar(&json) {
+        json = migracoes::migrar_licitacao_json(json)
+            .context(format!("Erro ao migrar JSON de licitação: {}", path.display()))?;
+    }
+    serde_json::from_value(json)
+        .context(format!("Erro ao analisar JSON de licitação: {}", path.display()))
+}
+
+/// Filtros da busca de propostas (todos opcionais e combinados com "e")
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FiltroPropostas {
+    /// Texto procurado na descrição ou no fornecedor
+    pub q: Option<String>,
+    pub cnpj: Option<String>,
+    pub uasg: Option<String>,
+    pub limite: Option<usize>,
+}
+
+fn somente_digitos(texto: &str) -> String {
+    texto.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+/// Minúsculas e sem acentos, para comparar "Licitação" com "licitacao"
+pub fn normalizar_busca(texto: &str) -> String {
+    texto.to_lowercase().chars().map(|c| match c {
+        'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'í' | 'ì' | 'î' | 'ï' => 'i',
+        'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+        'ú' | 'ù' | 'û' | 'ü' => 'u',
+        'ç' => 'c',
+        'ñ' => 'n',
+        outro => outro,
+    }).collect()
+}
+
+/// Verifica se uma proposta atende aos critérios já normalizados
+fn proposta_corresponde(proposta: &PropostaConsolidada, termo: Option<&str>, cnpj: Option<&str>, uasg: Option<&str>) -> bool {
+    if let Some(termo) = termo {
+        if !normalizar_busca(&proposta.descricao).contains(termo) && !normalizar_busca(&proposta.fornecedor).contains(termo) {
+            return false;
+        }
+    }
+
+    if let Some(cnpj) = cnpj {
+        if somente_digitos(&proposta.cnpj) != cnpj {
+            return false;
+        }
+    }
+
+    if let Some(uasg) = uasg {
+        if pdf_processor::uasg_canonica(&proposta.uasg) != uasg {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Busca propostas nas licitações carregadas
+pub fn buscar_propostas(licitacoes: &[LicitacaoConsolidada], filtro: &FiltroPropostas) -> Vec<PropostaConsolidada> {
+    let termo = filtro.q.as_deref().map(|q| normalizar_busca(q.trim())).filter(|q| !q.is_empty());
+    let cnpj = filtro.cnpj.as_deref().map(somente_digitos).filter(|c| !c.is_empty());
+    let uasg = filtro.uasg.as_deref().map(pdf_processor::uasg_canonica).filter(|u| !u.is_empty());
+
+    licitacoes.iter()
+        .flat_map(|l| l.propostas.iter())
+        .filter(|p| proposta_corresponde(p, termo.as_deref(), cnpj.as_deref(), uasg.as_deref()))
+        .take(filtro.limite.unwrap_or(100))
+        .cloned()
+        .collect()
+}
+
+/// Verifica se algum dos campos pedidos contém a consulta já normalizada
+fn campo_corresponde(proposta: &PropostaConsolidada, campo: &str, termo: &str, digitos: &str) -> bool {
+    match campo {
+        "fornecedor" => normalizar_busca(&proposta.fornecedor).contains(termo),
+        "descricao" => normalizar_busca(&proposta.descricao).contains(termo),
+        // CNPJ compara só os dígitos: "12.345.678" encontra "12345678000190"
+        "cnpj" => !digitos.is_empty() && somente_digitos(&proposta.cnpj).contains(digitos),
+        _ => false,
+    }
+}
+
+/// Pesquisa a consulta nos campos pedidos de todos os JSONs de licitação
+///
+/// Campos vazios pesquisam em todos os `CAMPOS_BUSCA`. Arquivos ilegíveis são ignorados.
+pub fn pesquisar_propostas(arquivos: &[PathBuf], consulta: &str, campos: &[String], limite: usize) -> ResultadoBuscaPropostas {
+    let termo = normalizar_busca(consulta.trim());
+    let digitos = somente_digitos(consulta);
+    let campos: Vec<&str> = if campos.is_empty() {
+        CAMPOS_BUSCA.to_vec()
+    } else {
+        campos.iter().map(String::as_str).collect()
+    };
+
+    let mut propostas = Vec::new();
+    let mut truncated = false;
+
+    for path in arquivos {
+        let Ok(licitacao) = carregar_licitacao(path) else {
+            continue;
+        };
+
+        for proposta in licitacao.propostas {
+            if !campos.iter().any(|campo| campo_corresponde(&proposta, campo, &termo, &digitos)) {
+                continue;
+            }
+            if propostas.len() == limite {
+                truncated = true;
+                return ResultadoBuscaPropostas { propostas, truncated };
+            }
+            propostas.push(PropostaEncontrada {
+                proposta,
+                arquivo_origem: path.to_string_lossy().to_string(),
+            });
+        }
+    }
+
+    ResultadoBuscaPropostas { propostas, truncated }
+}
+
+/// Propostas de várias execuções reunidas por `mesclar_licitacoes`
+#[derive(Debug, Default)]
+pub struct Mesclagem {
+    pub propostas: Vec<PropostaConsolidada>,
+    pub arquivos_lidos: Vec<PathBuf>,
+    pub duplicatas_removidas: usize,
+    pub conflitos: Vec<String>,
+    pub arquivos_com_erro: Vec<String>,
+}
+
+/// Reúne as propostas dos JSONs de licitação, sem repetir item + CNPJ da mesma licitação
+///
+/// Os arquivos mais recentes são lidos primeiro e prevalecem; a mesma proposta com
+/// valor adjudicado diferente em outro arquivo é registrada como conflito.
+pub fn mesclar_licitacoes(arquivos: &[PathBuf]) -> Mesclagem {
+    let mut ordenados = arquivos.to_vec();
+    ordenados.sort_by_key(|path| std::cmp::Reverse(fs::metadata(path).and_then(|m| m.modified()).ok()));
+
+    let mut mesclagem = Mesclagem::default();
+    let mut vistas: HashMap<_, (Option<Valor>, String, PathBuf)> = HashMap::new();
+
+    for path in ordenados {
+        let Ok(licitacao) = carregar_licitacao(&path) else {
+            mesclagem.arquivos_com_erro.push(path.to_string_lossy().to_string());
+            continue;
+        };
+
+        for proposta in licitacao.propostas {
+            let chave = pdf_processor::chave_deduplicacao(&proposta);
+            match vistas.get(&chave) {
+                Some((valor, valor_bruto, origem)) => {
+                    mesclagem.duplicatas_removidas += 1;
+                    let iguais = match (valor, proposta.valor_adjudicado) {
+                        (Some(a), Some(b)) => *a == b,
+                        (None, None) => valor_bruto.trim() == proposta.valor_adjudicado_raw.trim(),
+                        _ => false,
+                    };
+                    if !iguais {
+                        mesclagem.conflitos.push(format!(
+                            "{}-{}-{}: item {}, CNPJ {} com valores diferentes ({} em {}, {} em {})",
+                            proposta.uasg, proposta.pregao, proposta.processo, proposta.item, proposta.cnpj,
+                            formatar_valor(*valor), nome_arquivo(origem), formatar_valor(proposta.valor_adjudicado), nome_arquivo(&path)
+                        ));
+                    }
+                }
+                None => {
+                    vistas.insert(chave, (proposta.valor_adjudicado, proposta.valor_adjudicado_raw.clone(), path.clone()));
+                    mesclagem.propostas.push(proposta);
+                }
+            }
+        }
+
+        mesclagem.arquivos_lidos.push(path);
+    }
+
+    mesclagem
+}
+
+fn nome_arquivo(path: &Path) -> String {
+    path.file_name().unwrap_or_default().to_string_lossy().to_string()
+}
+
+/// Maior página aceita por `paginar_propostas`
+pub const LIMITE_PAGINA_MAXIMO: usize = 1000;
+
+/// Campos aceitos para ordenar a leitura paginada
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CampoOrdenacao {
+    Item,
+    ValorAdjudicado,
+    Fornecedor,
+}
+
+impl CampoOrdenacao {
+    pub const NOMES: [&'static str; 3] = ["item", "valor_adjudicado", "fornecedor"];
+
+    pub fn de_nome(nome: &str) -> Option<Self> {
+        match nome {
+            "item" => Some(CampoOrdenacao::Item),
+            "valor_adjudicado" => Some(CampoOrdenacao::ValorAdjudicado),
+            "fornecedor" => Some(CampoOrdenacao::Fornecedor),
+            _ => None,
+        }
+    }
+}
+
+/// JSON de licitação (ou qualquer objeto com `propostas`) sem interpretar as propostas
+#[derive(Deserialize)]
+struct PropostasBrutas<'a> {
+    #[serde(borrow)]
+    propostas: Vec<&'a RawValue>,
+}
+
+/// Apenas os campos usados para ordenar
+#[derive(Deserialize, Default)]
+struct CamposOrdenacao {
+    #[serde(default)]
+    item: String,
+    #[serde(default)]
+    grupo: Option<String>,
+    #[serde(default, deserialize_with = "valor_opcional")]
+    valor_adjudicado: Option<Valor>,
+    #[serde(default)]
+    fornecedor: String,
+}
+
+/// Chave de ordenação; `None` (valor não numérico) fica sempre no fim
+#[derive(Debug, PartialEq, PartialOrd)]
+enum Chave {
+    /// Grupo e item, na ordem de `pdf_processor::ordenar_por_item`
+    Item(Option<String>, String),
+    Numero(Option<f64>, String),
+    Texto(String),
+}
+
+fn chave_ordenacao(proposta: &RawValue, campo: CampoOrdenacao) -> Chave {
+    let campos: CamposOrdenacao = serde_json::from_str(proposta.get()).unwrap_or_default();
+    match campo {
+        CampoOrdenacao::Item => Chave::Item(campos.grupo, campos.item),
+        CampoOrdenacao::ValorAdjudicado => {
+            Chave::Numero(campos.valor_adjudicado.map(|valor| valor.0), String::new())
+        }
+        CampoOrdenacao::Fornecedor => Chave::Texto(normalizar_busca(campos.fornecedor.trim())),
+    }
+}
+
+fn comparar_chaves(a: &Chave, b: &Chave, decrescente: bool) -> Ordering {
+    let ordem = match (a, b) {
+        (Chave::Item(grupo_a, item_a), Chave::Item(grupo_b, item_b)) => {
+            pdf_processor::comparar_por_item((grupo_a.as_deref(), item_a), (grupo_b.as_deref(), item_b))
+        }
+        (Chave::Numero(Some(x), tx), Chave::Numero(Some(y), ty)) => x.total_cmp(y).then_with(|| tx.cmp(ty)),
+        (Chave::Numero(Some(_), _), Chave::Numero(None, _)) => return Ordering::Less,
+        (Chave::Numero(None, _), Chave::Numero(Some(_), _)) => return Ordering::Greater,
+        (Chave::Numero(None, tx), Chave::Numero(None, ty)) => tx.cmp(ty),
+        _ => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+    };
+    if decrescente { ordem.reverse() } else { ordem }
+}
+
+/// Uma página das propostas de um JSON de resultado
+///
+/// Aceita o formato `licitacao_*.json` (objeto com `propostas`) e uma lista de propostas na
+/// raiz. Apenas os campos de ordenação de cada proposta e as propostas da página são
+/// interpretados; o restante do arquivo é só validado.
+pub fn paginar_propostas(
+    content: &str,
+    offset: usize,
+    limit: usize,
+    ordenacao: Option<(CampoOrdenacao, bool)>,
+) -> Result<PropostasPaginadas> {
+    let propostas: Vec<&RawValue> = if content.trim_start().starts_with('[') {
+        serde_json::from_str(content).context("Lista de propostas inválida")?
+    } else {
+        serde_json::from_str::<PropostasBrutas>(content)
+            .context("JSON sem lista de propostas")?
+            .propostas
+    };
+    let total = propostas.len();
+    let limit = limit.min(LIMITE_PAGINA_MAXIMO);
+
+    let pagina: Vec<&RawValue> = match ordenacao {
+        Some((campo, decrescente)) => {
+            let mut chaves: Vec<(Chave, &RawValue)> = propostas.into_iter()
+                .map(|proposta| (chave_ordenacao(proposta, campo), proposta))
+                .collect();
+            chaves.sort_by(|(a, _), (b, _)| comparar_chaves(a, b, decrescente));
+            chaves.into_iter().skip(offset).take(limit).map(|(_, proposta)| proposta).collect()
+        }
+        None => propostas.into_iter().skip(offset).take(limit).collect(),
+    };
+
+    let propostas = pagina.into_iter()
+        .map(|proposta| serde_json::from_str(proposta.get()))
+        .collect::<serde_json::Result<Vec<serde_json::Value>>>()
+        .context("Proposta inválida")?;
+
+    Ok(PropostasPaginadas { total, offset, limit, propostas })
+}
+
+/// Prefixo dos arquivos de exemplo criados por `verify_output_directory`
+pub const PREFIXO_EXEMPLO: &str = "exemplo";
+
+/// Ordenações aceitas pela listagem de arquivos de licitação (sempre decrescente)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OrdenacaoArquivos {
+    #[default]
+    ModificadoEm,
+    ValorTotal,
+    TotalPropostas,
+}
+
+impl OrdenacaoArquivos {
+    pub const NOMES: [&'static str; 3] = ["mtime", "valor_total", "total_propostas"];
+
+    pub fn de_nome(nome: &str) -> Option<Self> {
+        match nome {
+            "mtime" => Some(OrdenacaoArquivos::ModificadoEm),
+            "valor_total" => Some(OrdenacaoArquivos::ValorTotal),
+            "total_propostas" => Some(OrdenacaoArquivos::TotalPropostas),
+            _ => None,
+        }
+    }
+}
+
+/// Cabeçalho de um JSON de licitação; as propostas são puladas sem serem interpretadas
+///
+/// Os totais aceitam number ou texto, como em `totais_do_arquivo`: arquivos antigos com
+/// "1.234,50" continuam listados.
+#[derive(Debug, Deserialize, Default)]
+struct CabecalhoLicitacao {
+    uasg: Option<String>,
+    pregao: Option<String>,
+    modalidade: Option<String>,
+    #[serde(default, deserialize_with = "contagem_tolerante")]
+    total_propostas: Option<usize>,
+    #[serde(default, deserialize_with = "valor_tolerante")]
+    valor_total: Option<f64>,
+    data_geracao: Option<String>,
+    data_homologacao_iso: Option<String>,
+}
+
+/// Número inteiro e não negativo gravado como number ou texto; ilegível vira None
+fn contagem_tolerante<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<usize>, D::Error> {
+    let valor = serde_json::Value::deserialize(deserializer)?;
+    Ok(numero_tolerante(&valor)
+        .filter(|total| *total >= 0.0 && total.fract() == 0.0)
+        .map(|total| total as usize))
+}
+
+/// Número gravado como number ou texto; ilegível vira None
+fn valor_tolerante<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    let valor = serde_json::Value::deserialize(deserializer)?;
+    Ok(numero_tolerante(&valor))
+}
+
+/// JSONs de licitação cuja UASG ainda não está na forma de 6 dígitos (ex.: "70011")
+pub fn arquivos_com_uasg_nao_normalizada(arquivos: &[PathBuf]) -> Vec<PathBuf> {
+    arquivos.iter()
+        .filter(|path| {
+            fs::read_to_string(path).ok()
+                .and_then(|content| serde_json::from_str::<CabecalhoLicitacao>(&content).ok())
+                .and_then(|cabecalho| cabecalho.uasg)
+                .is_some_and(|uasg| validators::normalizar_uasg(&uasg).is_some_and(|normalizada| normalizada != uasg))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Dia da homologação, no fuso gravado (o de Brasília), a partir do RFC 3339
+fn data_homologacao(iso: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(iso).ok().map(|momento| momento.date_naive())
+}
+
+/// Data (AAAA-MM-DD) de geração do arquivo: a do cabeçalho ou, na falta dela, a de modificação
+fn data_do_arquivo(cabecalho: &CabecalhoLicitacao, modificado: Option<DateTime<Local>>) -> Option<NaiveDate> {
+    cabecalho.data_geracao.as_deref()
+        .and_then(|data| NaiveDate::parse_from_str(data.get(..10)?, "%Y-%m-%d").ok())
+        .or_else(|| modificado.map(|m| m.date_naive()))
+}
+
+/// Lista os JSONs de licitação com os filtros aplicados sobre o cabeçalho
+///
+/// `date_from`/`date_to` devem ser datas AAAA-MM-DD válidas (a validação fica com o chamador).
+/// Arquivos ilegíveis não aparecem na listagem.
+pub fn listar_licitacoes_filtradas(
+    diretorios: &[PathBuf],
+    filtro: &FiltroArquivosLicitacao,
+    ordenacao: OrdenacaoArquivos,
+) -> Vec<LicitacaoFileInfo> {
+    let mut arquivos = listar_arquivos_licitacao(diretorios);
+    if filtro.include_examples {
+        arquivos.extend(diretorios.iter()
+            .flat_map(|dir| WalkDir::new(dir).max_depth(2).into_iter().filter_map(|e| e.ok()))
+            .filter(|e| e.file_type().is_file())
+            .filter(|e| e.file_name().to_string_lossy().starts_with(PREFIXO_EXEMPLO) && paths::is_json_file(e.path()))
+            .map(|e| e.path().to_path_buf()));
+    }
+
+    let data = |texto: &Option<String>| texto.as_deref().and_then(|d| NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d").ok());
+    let (inicio, fim) = (data(&filtro.date_from), data(&filtro.date_to));
+    let (homologado_de, homologado_ate) = (data(&filtro.homologation_from), data(&filtro.homologation_to));
+    let uasg = filtro.uasg.as_deref().map(|u| pdf_processor::uasg_canonica(&somente_digitos(u))).filter(|u| !u.is_empty());
+    let pregao = filtro.pregao.as_deref().map(|p| normalizar_busca(p.trim())).filter(|p| !p.is_empty());
+    let modalidade = filtro.modalidade.as_deref().map(|m| normalizar_busca(m.trim())).filter(|m| !m.is_empty());
+
+    let mut listados: Vec<(Option<std::time::SystemTime>, LicitacaoFileInfo)> = Vec::new();
+    for path in arquivos {
+        let Some(cabecalho) = fs::read_to_string(&path).ok()
+            .and_then(|content| serde_json::from_str::<CabecalhoLicitacao>(&content).ok())
+        else {
+            continue;
+        };
+        let modificado = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        let modificado_local = modificado.map(DateTime::<Local>::from);
+
+        if let Some(uasg) = &uasg {
+            if cabecalho.uasg.as_deref().map(|u| pdf_processor::uasg_canonica(&somente_digitos(u))).as_ref() != Some(uasg) {
+                continue;
+            }
+        }
+        if let Some(pregao) = &pregao {
+            if !cabecalho.pregao.as_deref().is_some_and(|p| normalizar_busca(p).contains(pregao.as_str())) {
+                continue;
+            }
+        }
+        // Arquivos anteriores à modalidade são todos de pregão
+        let modalidade_arquivo = cabecalho.modalidade.clone().unwrap_or_else(|| MODALIDADE_PREGAO.to_string());
+        if modalidade.as_ref().is_some_and(|m| normalizar_busca(&modalidade_arquivo) != *m) {
+            continue;
+        }
+        if inicio.is_some() || fim.is_some() {
+            let Some(dia) = data_do_arquivo(&cabecalho, modificado_local) else { continue };
+            if inicio.is_some_and(|inicio| dia < inicio) || fim.is_some_and(|fim| dia > fim) {
+                continue;
+            }
+        }
+        // Arquivos sem a data de homologação interpretada ficam de fora quando o intervalo é informado
+        if homologado_de.is_some() || homologado_ate.is_some() {
+            let Some(dia) = cabecalho.data_homologacao_iso.as_deref().and_then(data_homologacao) else { continue };
+            if homologado_de.is_some_and(|inicio| dia < inicio) || homologado_ate.is_some_and(|fim| dia > fim) {
+                continue;
+            }
+        }
+
+        listados.push((modificado, LicitacaoFileInfo {
+            nome: nome_arquivo(&path),
+            caminho: path.to_string_lossy().to_string(),
+            uasg: cabecalho.uasg,
+            pregao: cabecalho.pregao,
+            modalidade: modalidade_arquivo,
+            total_propostas: cabecalho.total_propostas.unwrap_or(0),
+            valor_total: cabecalho.valor_total.unwrap_or(0.0),
+            data_geracao: cabecalho.data_geracao,
+            data_homologacao_iso: cabecalho.data_homologacao_iso,
+            modificado_em: modificado_local.map(|m| m.to_rfc3339()),
+        }));
+    }
+
+    listados.sort_by(|(mtime_a, a), (mtime_b, b)| match ordenacao {
+        OrdenacaoArquivos::ModificadoEm => mtime_b.cmp(mtime_a),
+        OrdenacaoArquivos::ValorTotal => b.valor_total.total_cmp(&a.valor_total),
+        OrdenacaoArquivos::TotalPropostas => b.total_propostas.cmp(&a.total_propostas),
+    }.then_with(|| a.caminho.cmp(&b.caminho)));
+    listados.into_iter().map(|(_, info)| info).collect()
+}
+
+/// Apenas os campos de uma proposta usados no resumo
+#[derive(Debug, Deserialize)]
+struct PropostaResumida {
+    fornecedor: String,
+    cnpj: String,
+    #[serde(default, deserialize_with = "valor_opcional")]
+    valor_adjudicado: Option<Valor>,
+    #[serde(default)]
+    situacao: Option<String>,
+}
+
+/// Apenas os campos de uma licitação usados no resumo
+#[derive(Debug, Deserialize)]
+struct LicitacaoResumida {
+    uasg: String,
+    valor_total: f64,
+    propostas: Vec<PropostaResumida>,
+}
+
+impl From<&LicitacaoConsolidada> for LicitacaoResumida {
+    fn from(licitacao: &LicitacaoConsolidada) -> Self {
+        LicitacaoResumida {
+            uasg: licitacao.uasg.clone(),
+            valor_total: licitacao.valor_total,
+            propostas: licitacao.propostas.iter().map(|p| PropostaResumida {
+                fornecedor: p.fornecedor.clone(),
+                cnpj: p.cnpj.clone(),
+                valor_adjudicado: p.valor_adjudicado,
+                situacao: Some(p.situacao.clone()),
+            }).collect(),
+        }
+    }
+}
+
+/// Agrega totais, ranking de fornecedores e distribuição por UASG dos JSONs de licitação
+///
+/// Arquivos ilegíveis não interrompem o resumo e são listados em `arquivos_com_erro`.
+pub fn resumir_licitacoes(arquivos: &[PathBuf]) -> LicitacoesSummary {
+    resumir(arquivos.iter().map(|path| {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .ok_or_else(|| path.to_string_lossy().to_string())
+    }))
+}
+
+/// Mesmo resumo de `resumir_licitacoes`, a partir de um consolidado.json já carregado
+pub fn resumir_consolidado(consolidado: &ConsolidadoJson) -> LicitacoesSummary {
+    // Ordenadas pela chave, para que o nome de um fornecedor não dependa da ordem do mapa
+    let licitacoes: BTreeMap<&String, &LicitacaoConsolidada> = consolidado.licitacoes.iter().collect();
+    resumir(licitacoes.into_values().map(|licitacao| Ok(LicitacaoResumida::from(licitacao))))
+}
+
+/// Agrega as licitações lidas; as que falharam trazem o caminho do arquivo
+fn resumir(licitacoes: impl Iterator<Item = std::result::Result<LicitacaoResumida, String>>) -> LicitacoesSummary {
+    let mut resumo = LicitacoesSummary::default();
+    let mut fornecedores: HashMap<String, FornecedorResumo> = HashMap::new();
+    let mut por_uasg: BTreeMap<String, UasgResumo> = BTreeMap::new();
+
+    for lida in licitacoes {
+        let licitacao = match lida {
+            Ok(licitacao) => licitacao,
+            Err(arquivo) => {
+                resumo.arquivos_com_erro.push(arquivo);
+                continue;
+            }
+        };
+
+        resumo.total_licitacoes += 1;
+        resumo.total_propostas += licitacao.propostas.len();
+        resumo.valor_total += licitacao.valor_total;
+
+        let uasg = por_uasg.entry(licitacao.uasg.clone()).or_insert_with(|| UasgResumo {
+            uasg: licitacao.uasg.clone(),
+            licitacoes: 0,
+            propostas: 0,
+            valor_total: 0.0,
+        });
+        uasg.licitacoes += 1;
+        uasg.propostas += licitacao.propostas.len();
+        uasg.valor_total += licitacao.valor_total;
+
+        let adjudicadas = licitacao.propostas.iter()
+            .filter(|p| p.situacao.as_deref().unwrap_or(SITUACAO_ADJUDICADO) == SITUACAO_ADJUDICADO);
+        for proposta in adjudicadas {
+            // O mesmo fornecedor aparece com CNPJ formatado de jeitos diferentes
+            let digitos = somente_digitos(&proposta.cnpj);
+            let chave = if digitos.is_empty() { proposta.fornecedor.clone() } else { digitos };
+            let fornecedor = fornecedores.entry(chave).or_insert_with(|| FornecedorResumo {
+                fornecedor: proposta.fornecedor.clone(),
+                cnpj: proposta.cnpj.clone(),
+                valor_adjudicado: 0.0,
+                itens: 0,
+            });
+            fornecedor.itens += 1;
+            fornecedor.valor_adjudicado += proposta.valor_adjudicado.map_or(0.0, |valor| valor.0);
+        }
+    }
+
+    let mut ranking: Vec<FornecedorResumo> = fornecedores.into_values().collect();
+    ranking.sort_by(|a, b| b.valor_adjudicado.total_cmp(&a.valor_adjudicado).then_with(|| a.fornecedor.cmp(&b.fornecedor)));
+    ranking.truncate(TOP_FORNECEDORES);
+
+    resumo.top_fornecedores = ranking;
+    resumo.por_uasg = por_uasg.into_values().collect();
+    resumo
+}
+
+/// Reúne os itens adjudicados ao CNPJ (comparado só pelos dígitos) em todos os JSONs de licitação
+///
+/// O nome do fornecedor é o da participação mais recente na ordem dos arquivos.
+pub fn historico_fornecedor(arquivos: &[PathBuf], cnpj: &str) -> FornecedorHistory {
+    let digitos = somente_digitos(cnpj);
+    let mut historico = FornecedorHistory { cnpj: cnpj.trim().to_string(), ..Default::default() };
+
+    for path in arquivos {
+        let licitacao = match carregar_licitacao(path) {
+            Ok(licitacao) => licitacao,
+            Err(_) => {
+                historico.arquivos_com_erro.push(path.to_string_lossy().to_string());
+                continue;
+            }
+        };
+
+        let propostas: Vec<&PropostaConsolidada> = licitacao.propostas.iter()
+            .filter(|p| p.situacao == SITUACAO_ADJUDICADO && somente_digitos(&p.cnpj) == digitos)
+            .collect();
+        let Some(primeira) = propostas.first() else {
+            continue;
+        };
+        historico.cnpj = primeira.cnpj.clone();
+        historico.fornecedor = primeira.fornecedor.clone();
+
+        let itens: Vec<ItemFornecedor> = propostas.iter().map(|p| ItemFornecedor {
+            item: p.item.clone(),
+            descricao: p.descricao.clone(),
+            quantidade: p.quantidade.clone(),
+            valor_adjudicado: formatar_valor(p.valor_adjudicado),
+            valor: p.valor_adjudicado.map_or(0.0, |valor| valor.0),
+        }).collect();
+        let valor_adjudicado: f64 = itens.iter().map(|item| item.valor).sum();
+
+        historico.total_itens += itens.len();
+        historico.valor_total_adjudicado += valor_adjudicado;
+        historico.licitacoes.push(ParticipacaoFornecedor {
+            uasg: licitacao.uasg,
+            pregao: licitacao.pregao,
+            processo: licitacao.processo,
+            arquivo: path.to_string_lossy().to_string(),
+            itens,
+            valor_adjudicado,
+        });
+    }
+
+    historico
+}
+
+/// Entrada de `arquivos_gerados`: só o nome na pasta de resultados, o caminho completo fora dela
+fn entrada_arquivo_gerado(output_dir: &Path, path: &Path) -> String {
+    match path.parent() {
+        Some(pasta) if pasta == output_dir => path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        _ => path.to_string_lossy().to_string(),
+    }
+}
+
+/// Uma entrada de `arquivos_gerados` aponta para o arquivo (comparando as pastas pelo caminho canônico)
+fn entrada_aponta_para(output_dir: &Path, entrada: &str, path: &Path) -> bool {
+    let candidato = output_dir.join(entrada);
+    let pasta_canonica = |p: &Path| p.parent().and_then(|pasta| pasta.canonicalize().ok());
+    candidato.file_name() == path.file_name() && pasta_canonica(&candidato) == pasta_canonica(path)
+}
+
+fn ler_resumo_geral(output_dir: &Path) -> Option<ResumoGeral> {
+    fs::read_to_string(output_dir.join(pdf_processor::ARQUIVO_RESUMO_GERAL)).ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+}
+
+fn gravar_resumo_geral(output_dir: &Path, resumo: &ResumoGeral) -> Result<()> {
+    let content = serde_json::to_string_pretty(resumo)
+        .context("Erro ao serializar resumo geral")?;
+    integridade::gravar_artefato(output_dir, &output_dir.join(pdf_processor::ARQUIVO_RESUMO_GERAL), content.as_bytes(), None)
+        .context("Erro ao salvar arquivo de resumo geral")
+}
+
+/// Regrava o resumo_geral.json a partir dos JSONs de licitação presentes
+///
+/// As propostas duplicadas ignoradas só são conhecidas no processamento e ficam zeradas.
+/// Arquivos ilegíveis não entram no resumo.
+pub fn reconstruir_resumo_geral(output_dir: &Path, arquivos: &[PathBuf]) -> Result<ResumoGeral> {
+    let mut resumo = ResumoGeral {
+        data_geracao: Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        ..Default::default()
+    };
+
+    let mut propostas = Vec::new();
+    for path in arquivos {
+        let Ok(licitacao) = carregar_licitacao(path) else {
+            continue;
+        };
+        resumo.total_licitacoes += 1;
+        resumo.total_propostas += licitacao.total_propostas;
+        resumo.valor_total_geral += licitacao.valor_total;
+        resumo.arquivos_gerados.push(entrada_arquivo_gerado(output_dir, path));
+        propostas.extend(licitacao.propostas);
+    }
+    resumo.economia = ResumoEconomia::das_propostas(&propostas);
+    resumo.porte = ResumoPorte::das_propostas(&propostas);
+    if output_dir.join(pdf_processor::ARQUIVO_CONSOLIDADO).exists() {
+        resumo.arquivos_gerados.push(pdf_processor::ARQUIVO_CONSOLIDADO.to_string());
+    }
+
+    gravar_resumo_geral(output_dir, &resumo)?;
+    Ok(resumo)
+}
+
+/// Troca no resumo geral a entrada de um arquivo renomeado
+///
+/// Sem resumo na pasta (ou com um ilegível), ele é reconstruído a partir dos JSONs de `diretorios`.
+pub fn renomear_no_resumo_geral(output_dir: &Path, diretorios: &[PathBuf], antigo: &Path, novo: &Path) -> Result<ResumoGeral> {
+    let Some(mut resumo) = ler_resumo_geral(output_dir) else {
+        return reconstruir_resumo_geral(output_dir, &listar_arquivos_licitacao(diretorios));
+    };
+
+    let nova_entrada = entrada_arquivo_gerado(output_dir, novo);
+    match resumo.arquivos_gerados.iter_mut().find(|entrada| entrada_aponta_para(output_dir, entrada, antigo)) {
+        Some(entrada) => *entrada = nova_entrada,
+        None => resumo.arquivos_gerados.push(nova_entrada),
+    }
+
+    gravar_resumo_geral(output_dir, &resumo)?;
+    Ok(resumo)
+}
+
+/// Aponta as entradas do resumo geral para os novos caminhos de arquivos movidos (ex.: pela retenção)
+///
+/// Sem resumo legível na pasta não há o que atualizar.
+pub fn mover_no_resumo_geral(output_dir: &Path, movidos: &[(PathBuf, PathBuf)]) -> Result<()> {
+    let Some(mut resumo) = ler_resumo_geral(output_dir) else {
+        return Ok(());
+    };
+
+    let mut alterado = false;
+    for entrada in resumo.arquivos_gerados.iter_mut() {
+        if let Some((_, destino)) = movidos.iter().find(|(origem, _)| entrada_aponta_para(output_dir, entrada, origem)) {
+            *entrada = entrada_arquivo_gerado(output_dir, destino);
+            alterado = true;
+        }
+    }
+    if alterado {
+        gravar_resumo_geral(output_dir, &resumo)?;
+    }
+    Ok(())
+}
+
+/// Troca nos resultados existentes as propostas extraídas de `arquivo_origem` pelas `novas`
+///
+/// Só as licitações das novas propostas são tocadas. Nelas saem as propostas antigas do mesmo
+/// arquivo e, nos JSONs anteriores ao `arquivo_origem`, as que têm a chave de deduplicação de
+/// uma nova. Propostas de outros arquivos do mesmo pregão ficam e prevalecem numa colisão.
+/// O consolidado.json (se existir), o resumo geral e o banco são atualizados em seguida.
+pub fn substituir_propostas(
+    output_dir: &Path,
+    regras: &[RoutingRule],
+    arquivo_origem: &str,
+    novas: &[PropostaConsolidada],
+) -> Result<ReprocessamentoPdf> {
+    let mut reprocessamento = ReprocessamentoPdf {
+        arquivo: arquivo_origem.to_string(),
+        ..Default::default()
+    };
+
+    let mut propostas = Vec::new();
+    for agrupada in pdf_processor::consolidar(novas, output_dir, regras).licitacoes {
+        let path = agrupada.destino.join(&agrupada.nome_arquivo);
+        let chaves_novas: HashSet<_> = agrupada.licitacao.propostas.iter().map(pdf_processor::chave_deduplicacao).collect();
+        if path.exists() {
+            let existente = carregar_licitacao(&path)?;
+            let total_antigo = existente.propostas.len();
+            let mantidas: Vec<_> = existente.propostas.into_iter()
+                .filter(|p| match &p.arquivo_origem {
+                    Some(origem) => origem != arquivo_origem,
+                    None => !chaves_novas.contains(&pdf_processor::chave_deduplicacao(p)),
+                })
+                .collect();
+            reprocessamento.propostas_removidas += total_antigo - mantidas.len();
+            propostas.extend(mantidas);
+        }
+        propostas.extend(agrupada.licitacao.propostas);
+    }
+
+    // As mantidas vêm antes: numa colisão a deduplicação fica com a proposta do outro arquivo
+    let consolidacao = pdf_processor::consolidar(&propostas, output_dir, regras);
+    reprocessamento.propostas_ignoradas = consolidacao.propostas_duplicadas;
+    reprocessamento.propostas_inseridas = novas.len().saturating_sub(consolidacao.propostas_duplicadas);
+
+    let data_geracao = Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string();
+    for agrupada in &consolidacao.licitacoes {
+        let path = pdf_processor::gravar_licitacao(agrupada, output_dir, &data_geracao, false)?;
+        reprocessamento.arquivos_alterados.push(path.to_string_lossy().to_string());
+    }
+
+    atualizar_consolidado(output_dir, &consolidacao, &data_geracao)?;
+    reconstruir_resumo_geral(output_dir, &listar_arquivos_licitacao(&diretorios_resultados(output_dir, regras)))?;
+    if let Err(e) = storage::registrar_consolidacao(output_dir, &consolidacao) {
+        warn!("Erro ao atualizar o banco SQLite: {:#}", e);
+    }
+
+    Ok(reprocessamento)
+}
+
+/// Refaz um JSON de licitação divergente com as propostas reextraídas dos seus PDFs de origem
+///
+/// O JSON divergente vai para a lixeira e cada PDF entra como num reprocessamento, o que também
+/// atualiza o consolidado.json, o resumo geral e o banco. Os PDFs voltam a constar como origem
+/// dos JSONs regravados; a lista devolvida traz esses JSONs.
+pub fn regenerar_licitacao(
+    output_dir: &Path,
+    path: &Path,
+    regras: &[RoutingRule],
+    reextraidas: &[(PathBuf, Vec<PropostaConsolidada>)],
+) -> Result<Vec<String>> {
+    if reextraidas.iter().all(|(_, propostas)| propostas.is_empty()) {
+        anyhow::bail!("Nenhuma proposta extraída dos PDFs de origem de {}", path.display());
+    }
+    integridade::mover_para_lixeira(output_dir, path)?;
+
+    let mut origens = Vec::new();
+    for (pdf, propostas) in reextraidas.iter().filter(|(_, propostas)| !propostas.is_empty()) {
+        let arquivo_origem = pdf.file_name().unwrap_or_default().to_string_lossy();
+        let reprocessamento = substituir_propostas(output_dir, regras, &arquivo_origem, propostas)?;
+        origens.push((pdf.to_string_lossy().to_string(), reprocessamento.arquivos_alterados));
+    }
+    integridade::registrar_origens(output_dir, &origens)?;
+
+    let mut regravados: Vec<String> = origens.into_iter().flat_map(|(_, jsons)| jsons).collect();
+    regravados.sort();
+    regravados.dedup();
+    Ok(regravados)
+}
+
+/// Substitui no consolidado.json as licitações regravadas e refaz os totais
+fn atualizar_consolidado(output_dir: &Path, consolidacao: &pdf_processor::Consolidacao, data_geracao: &str) -> Result<()> {
+    let path = output_dir.join(pdf_processor::ARQUIVO_CONSOLIDADO);
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let mut consolidado = pdf_processor::carregar_consolidado(&path)?;
+    for agrupada in &consolidacao.licitacoes {
+        consolidado.licitacoes.insert(agrupada.chave.clone(), agrupada.licitacao.clone());
+    }
+    consolidado.data_geracao = data_geracao.to_string();
+    consolidado.total_licitacoes = consolidado.licitacoes.len();
+    consolidado.total_propostas = consolidado.licitacoes.values().map(|l| l.total_propostas).sum();
+    consolidado.valor_total_geral = consolidado.licitacoes.values().map(|l| l.valor_total).sum();
+
+    let content = serde_json::to_string_pretty(&consolidado)
+        .context("Erro ao serializar JSON consolidado")?;
+    integridade::gravar_artefato(output_dir, &path, content.as_bytes(), None)
+        .context(format!("Erro ao salvar {}", pdf_processor::ARQUIVO_CONSOLIDADO))
+}
+
+/// Diferença abaixo da qual dois valores são considerados iguais (meio centavo)
+const TOLERANCIA_COMPARACAO: f64 = 0.005;
+
+/// Campos de texto com números formatados, comparados pelo valor ("1.000,00" = "1000,00")
+const CAMPOS_TEXTO_NUMERICO: [&str; 2] = ["valor_adjudicado_raw", "quantidade"];
+
+/// Campos do cabeçalho da licitação comparados por `comparar_licitacoes`
+const CAMPOS_CABECALHO: [&str; 6] = ["uasg", "pregao", "modalidade", "processo", "total_propostas", "valor_total"];
+
+fn valores_equivalentes(campo: &str, antes: &serde_json::Value, depois: &serde_json::Value) -> bool {
+    if antes == depois {
+        return true;
+    }
+    if campo == "cnpj" {
+        return matches!((antes.as_str(), depois.as_str()), (Some(a), Some(b)) if somente_digitos(a) == somente_digitos(b));
+    }
+    let numerico = antes.is_number() || depois.is_number() || CAMPOS_TEXTO_NUMERICO.contains(&campo);
+    match (numero_tolerante(antes), numero_tolerante(depois)) {
+        (Some(a), Some(b)) if numerico => (a - b).abs() < TOLERANCIA_COMPARACAO,
+        _ => false,
+    }
+}
+
+/// Campos de dois objetos JSON que divergem, em ordem alfabética; sem `campos`, compara todos
+fn campos_alterados(antes: &serde_json::Value, depois: &serde_json::Value, campos: Option<&[&str]>) -> Vec<CampoAlterado> {
+    let vazio = serde_json::Map::new();
+    let antes = antes.as_object().unwrap_or(&vazio);
+    let depois = depois.as_object().unwrap_or(&vazio);
+    let nomes: BTreeSet<&str> = match campos {
+        Some(campos) => campos.iter().copied().collect(),
+        None => antes.keys().chain(depois.keys()).map(String::as_str).collect(),
+    };
+
+    nomes.into_iter()
+        .filter_map(|campo| {
+            let a = antes.get(campo).unwrap_or(&serde_json::Value::Null);
+            let b = depois.get(campo).unwrap_or(&serde_json::Value::Null);
+            (!valores_equivalentes(campo, a, b)).then(|| CampoAlterado {
+                campo: campo.to_string(),
+                antes: a.clone(),
+                depois: b.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Chave que alinha a mesma proposta nas duas versões: grupo, item e dígitos do CNPJ
+fn chave_comparacao(proposta: &PropostaConsolidada) -> (Option<String>, String, String) {
+    (proposta.grupo.clone(), proposta.item.trim().to_string(), somente_digitos(&proposta.cnpj))
+}
+
+/// Compara duas versões de uma licitação, alinhando as propostas por grupo, item e CNPJ
+///
+/// Valores que só diferem na formatação ("1.000,00" e "1000,00") não contam como alteração.
+/// Propostas repetidas com a mesma chave são pareadas na ordem em que aparecem.
+pub fn comparar_licitacoes(a: &LicitacaoConsolidada, b: &LicitacaoConsolidada) -> ComparacaoLicitacoes {
+    fn json<T: serde::Serialize>(valor: &T) -> serde_json::Value {
+        serde_json::to_value(valor).unwrap_or_default()
+    }
+
+    let mut comparacao = ComparacaoLicitacoes {
+        licitacao: campos_alterados(&json(a), &json(b), Some(&CAMPOS_CABECALHO)),
+        ..Default::default()
+    };
+
+    let mut pendentes: HashMap<_, VecDeque<&PropostaConsolidada>> = HashMap::new();
+    for proposta in &b.propostas {
+        pendentes.entry(chave_comparacao(proposta)).or_default().push_back(proposta);
+    }
+
+    for antes in &a.propostas {
+        let Some(depois) = pendentes.get_mut(&chave_comparacao(antes)).and_then(VecDeque::pop_front) else {
+            comparacao.removidas.push(antes.clone());
+            continue;
+        };
+        let campos = campos_alterados(&json(antes), &json(depois), None);
+        if campos.is_empty() {
+            comparacao.inalteradas += 1;
+        } else {
+            comparacao.alteradas.push(PropostaAlterada {
+                grupo: depois.grupo.clone(),
+                item: depois.item.clone(),
+                cnpj: depois.cnpj.clone(),
+                fornecedor: depois.fornecedor.clone(),
+                campos,
+            });
+        }
+    }
+
+    // O que sobrou nas filas de B não teve par em A
+    comparacao.adicionadas = pendentes.into_values().flatten().cloned().collect();
+    pdf_processor::ordenar_propostas(&mut comparacao.adicionadas);
+    pdf_processor::ordenar_propostas(&mut comparacao.removidas);
+    comparacao
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MODALIDADE_DISPENSA;
+    use crate::pdf_processor::converter_valor_para_float;
+
+    fn licitacao_json(propostas: serde_json::Value) -> String {
+        serde_json::json!({
+            "uasg": "787000", "pregao": "90008/2024", "processo": "123",
+            "total_propostas": 2, "valor_total": 0.0, "propostas": propostas
+        }).to_string()
+    }
+
+    fn proposta(descricao: &str, fornecedor: &str, cnpj: &str) -> serde_json::Value {
+        serde_json::json!({
+            "uasg": "787000", "pregao": "90008/2024", "processo": "123", "item": "1", "grupo": null,
+            "quantidade": "1", "descricao": descricao, "valor_estimado": "1,00", "valor_adjudicado": "1,00",
+            "fornecedor": fornecedor, "cnpj": cnpj, "marca_fabricante": "N/A", "modelo_versao": "N/A",
+            "responsavel": "N/A", "melhor_lance": "1,00", "tipo_formato": "individual"
+        })
+    }
+
+    #[test]
+    fn test_comparar_licitacoes_alinha_por_item_e_cnpj() {
+        let licitacao = |propostas: serde_json::Value| -> LicitacaoConsolidada {
+            serde_json::from_str(&licitacao_json(propostas)).unwrap()
+        };
+        let com = |mut proposta: serde_json::Value, campos: serde_json::Value| {
+            for (campo, valor) in campos.as_object().unwrap() {
+                proposta[campo] = valor.clone();
+            }
+            proposta
+        };
+
+        let a = licitacao(serde_json::json!([
+            com(proposta("Caneta", "EMPRESA A", "12.345.678/0001-90"), serde_json::json!({ "valor_adjudicado": "1.000,00" })),
+            com(proposta("Lápis", "EMPRESA B", "98.765.432/0001-10"), serde_json::json!({ "item": "2" })),
+            com(proposta("Borracha", "EMPRESA C", "11.222.333/0001-81"), serde_json::json!({ "item": "3" })),
+        ]));
+        let b = licitacao(serde_json::json!([
+            // Só a formatação do valor e do CNPJ mudou
+            com(proposta("Caneta", "EMPRESA A", "12345678000190"), serde_json::json!({ "valor_adjudicado": "1000,00" })),
+            com(proposta("Lápis HB", "EMPRESA B", "98.765.432/0001-10"), serde_json::json!({ "item": "2", "valor_adjudicado": 0.5 })),
+            com(proposta("Borracha", "EMPRESA D", "44.555.666/0001-77"), serde_json::json!({ "item": "3" })),
+        ]));
+
+        let comparacao = comparar_licitacoes(&a, &b);
+        assert_eq!(comparacao.inalteradas, 1);
+        assert!(comparacao.licitacao.is_empty());
+        assert_eq!(comparacao.removidas.iter().map(|p| p.fornecedor.as_str()).collect::<Vec<_>>(), ["EMPRESA C"]);
+        assert_eq!(comparacao.adicionadas.iter().map(|p| p.fornecedor.as_str()).collect::<Vec<_>>(), ["EMPRESA D"]);
+
+        assert_eq!(comparacao.alteradas.len(), 1);
+        let alterada = &comparacao.alteradas[0];
+        assert_eq!(alterada.item, "2");
+        let campos: Vec<_> = alterada.campos.iter().map(|c| c.campo.as_str()).collect();
+        assert_eq!(campos, ["descricao", "valor_adjudicado"]);
+        assert_eq!(alterada.campos[0].antes, "Lápis");
+        assert_eq!(alterada.campos[0].depois, "Lápis HB");
+
+        // Cabeçalho
+        let mut outra = b.clone();
+        outra.processo = "456".to_string();
+        let cabecalho = comparar_licitacoes(&a, &outra).licitacao;
+        assert_eq!(cabecalho, [CampoAlterado { campo: "processo".into(), antes: "123".into(), depois: "456".into() }]);
+    }
+
+    #[test]
+    fn test_totais_do_arquivo_nas_tres_geracoes_de_formato() {
+        let propostas = serde_json::json!([
+            proposta("Caneta", "EMPRESA A", "12.345.678/0001-90"),
+            proposta("Lápis", "EMPRESA B", "98.765.432/0001-10"),
+        ]);
+
+        // Atual: números
+        let atual = serde_json::json!({ "total_propostas": 2, "valor_total": 1234.5, "propostas": propostas });
+        assert_eq!(totais_do_arquivo(&atual), TotaisArquivo { total_propostas: Some(2), valor_total: Some(1234.5), inferido: false });
+
+        // Intermediária: textos, com vírgula decimal
+        let textos = serde_json::json!({ "total_propostas": "2", "valor_total": "1.234,50", "propostas": propostas });
+        assert_eq!(totais_do_arquivo(&textos), TotaisArquivo { total_propostas: Some(2), valor_total: Some(1234.5), inferido: false });
+
+        // Mais antiga: só as propostas
+        let antiga = serde_json::json!({ "uasg": "787000", "propostas": propostas });
+        assert_eq!(totais_do_arquivo(&antiga), TotaisArquivo { total_propostas: Some(2), valor_total: Some(2.0), inferido: true });
+
+        // Texto ilegível também cai na contagem das propostas
+        let ilegivel = serde_json::json!({ "total_propostas": "N/A", "valor_total": 2.0, "propostas": propostas });
+        assert_eq!(totais_do_arquivo(&ilegivel).total_propostas, Some(2));
+        assert!(totais_do_arquivo(&ilegivel).inferido);
+        assert_eq!(totais_do_arquivo(&serde_json::json!({})), TotaisArquivo { total_propostas: None, valor_total: None, inferido: false });
+    }
+
+    #[test]
+    fn test_pesquisar_propostas_sem_acentos_e_cnpj_sem_pontuacao() {
+        let dir = std::env::temp_dir().join(format!("licitacao360_busca_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let arquivo = dir.join("licitacao_787000-90008_2024-123.json");
+        fs::write(&arquivo, licitacao_json(serde_json::json!([
+            proposta("Caneta esferográfica azul", "PAPELARIA SÃO JOSÉ LTDA", "12.345.678/0001-90"),
+            proposta("Papel sulfite A4", "DISTRIBUIDORA CENTRAL", "98.765.432/0001-10"),
+        ]))).unwrap();
+        fs::write(dir.join("licitacao_corrompido.json"), "{").unwrap();
+        let arquivos = listar_arquivos_licitacao(std::slice::from_ref(&dir));
+
+        let resultado = pesquisar_propostas(&arquivos, "ESFEROGRAFICA", &[], 10);
+        assert_eq!(resultado.propostas.len(), 1);
+        assert_eq!(resultado.propostas[0].arquivo_origem, arquivo.to_string_lossy());
+        assert!(!resultado.truncated);
+
+        let resultado = pesquisar_propostas(&arquivos, "sao jose", &["fornecedor".to_string()], 10);
+        assert_eq!(resultado.propostas.len(), 1);
+
+        let resultado = pesquisar_propostas(&arquivos, "12345678000190", &["cnpj".to_string()], 10);
+        assert_eq!(resultado.propostas[0].proposta.fornecedor, "PAPELARIA SÃO JOSÉ LTDA");
+
+        // Texto sem dígitos nunca casa com o campo CNPJ
+        assert!(pesquisar_propostas(&arquivos, "papel", &["cnpj".to_string()], 10).propostas.is_empty());
+
+        let resultado = pesquisar_propostas(&arquivos, "a", &[], 1);
+        assert_eq!(resultado.propostas.len(), 1);
+        assert!(resultado.truncated);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_resumir_licitacoes() {
+        let dir = std::env::temp_dir().join(format!("licitacao360_resumo_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut deserta = proposta("Borracha", "N/A", "N/A");
+        deserta["situacao"] = "Deserto".into();
+        deserta["valor_adjudicado"] = "N/A".into();
+        let mut primeira = proposta("Caneta", "PAPELARIA LTDA", "12.345.678/0001-90");
+        primeira["valor_adjudicado"] = "1.000,00".into();
+        fs::write(dir.join("licitacao_a.json"), licitacao_json(serde_json::json!([
+            primeira, proposta("Papel", "CENTRAL", "98.765.432/0001-10"), deserta,
+        ]))).unwrap();
+
+        // Mesmo fornecedor com CNPJ sem máscara em outra licitação
+        let mut segunda = proposta("Lápis", "PAPELARIA LTDA", "12345678000190");
+        segunda["uasg"] = "160001".into();
+        let mut json: serde_json::Value = serde_json::from_str(&licitacao_json(serde_json::json!([segunda]))).unwrap();
+        json["uasg"] = "160001".into();
+        json["valor_total"] = 1.0.into();
+        fs::write(dir.join("licitacao_b.json"), json.to_string()).unwrap();
+
+        fs::write(dir.join("licitacao_corrompido.json"), "{").unwrap();
+        fs::write(dir.join("resumo_geral.json"), "{}").unwrap();
+        fs::write(dir.join("exemplo.json"), "{}").unwrap();
+
+        let resumo = resumir_licitacoes(&listar_arquivos_licitacao(std::slice::from_ref(&dir)));
+        assert_eq!(resumo.total_licitacoes, 2);
+        assert_eq!(resumo.total_propostas, 4);
+        assert_eq!(resumo.valor_total, 1.0);
+        assert_eq!(resumo.arquivos_com_erro.len(), 1);
+        assert!(resumo.arquivos_com_erro[0].ends_with("licitacao_corrompido.json"));
+
+        assert_eq!(resumo.top_fornecedores.len(), 2);
+        assert_eq!(resumo.top_fornecedores[0].fornecedor, "PAPELARIA LTDA");
+        assert_eq!(resumo.top_fornecedores[0].valor_adjudicado, 1001.0);
+        assert_eq!(resumo.top_fornecedores[0].itens, 2);
+
+        let uasgs: Vec<(&str, usize)> = resumo.por_uasg.iter().map(|u| (u.uasg.as_str(), u.propostas)).collect();
+        assert_eq!(uasgs, vec![("160001", 1), ("787000", 3)]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_mesclar_licitacoes_de_execucoes_diferentes() {
+        let dir = std::env::temp_dir().join(format!("licitacao360_mesclar_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut item2 = proposta("Papel", "CENTRAL", "98.765.432/0001-10");
+        item2["item"] = "2".into();
+        let antiga = dir.join("licitacao_787000-90008_2024-123.json");
+        fs::write(&antiga, licitacao_json(serde_json::json!([
+            proposta("Caneta", "PAPELARIA LTDA", "12.345.678/0001-90"), item2.clone(),
+        ]))).unwrap();
+
+        // Execução mais recente repete os itens 1 e 2 (o 2 com outro valor) e traz o item 3
+        let mut repetido = proposta("Caneta", "PAPELARIA LTDA", "12345678000190");
+        repetido["valor_adjudicado"] = "1.00".into();
+        item2["valor_adjudicado"] = "2,50".into();
+        let mut item3 = proposta("Lápis", "CENTRAL", "98.765.432/0001-10");
+        item3["item"] = "3".into();
+        let recente = dir.join("licitacao_787000-90008_2024-123_v2.json");
+        fs::write(&recente, licitacao_json(serde_json::json!([repetido, item2, item3]))).unwrap();
+        fs::write(dir.join("licitacao_corrompido.json"), "{").unwrap();
+
+        let agora = std::time::SystemTime::now();
+        fs::File::options().write(true).open(&antiga).unwrap()
+            .set_modified(agora - std::time::Duration::from_secs(3600)).unwrap();
+        fs::File::options().write(true).open(&recente).unwrap().set_modified(agora).unwrap();
+
+        let mesclagem = mesclar_licitacoes(&listar_arquivos_licitacao(std::slice::from_ref(&dir)));
+        assert_eq!(mesclagem.arquivos_lidos, vec![recente, antiga]);
+        assert_eq!(mesclagem.arquivos_com_erro.len(), 1);
+        assert_eq!(mesclagem.propostas.len(), 3);
+        assert_eq!(mesclagem.duplicatas_removidas, 2);
+
+        // "1,00" e "1.00" são o mesmo valor; só o item 2 diverge, e prevalece o mais recente
+        assert_eq!(mesclagem.conflitos.len(), 1);
+        assert!(mesclagem.conflitos[0].contains("item 2"));
+        let item2 = mesclagem.propostas.iter().find(|p| p.item == "2").unwrap();
+        assert_eq!(item2.valor_adjudicado, Some(Valor(2.5)));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn propostas_sinteticas(quantidade: usize) -> Vec<serde_json::Value> {
+        (1..=quantidade).map(|n| {
+            let mut p = proposta(&format!("Item {}", n), &format!("Fornecedor {:05}", (n * 7919) % quantidade), "N/A");
+            p["item"] = n.to_string().into();
+            // Valores em ordem diferente da dos itens, alguns sem adjudicação
+            p["valor_adjudicado"] = if n % 1000 == 0 {
+                "N/A".into()
+            } else {
+                format!("R$ {},{:02}", (n * 37) % 5000, n % 100).into()
+            };
+            p
+        }).collect()
+    }
+
+    #[test]
+    fn test_paginar_propostas_com_10_mil_itens() {
+        let propostas = propostas_sinteticas(10_000);
+        let content = serde_json::json!({
+            "uasg": "787000", "pregao": "90008/2024", "processo": "123",
+            "total_propostas": 10_000, "valor_total": 0.0, "propostas": propostas,
+        }).to_string();
+
+        let pagina = paginar_propostas(&content, 0, 50, None).unwrap();
+        assert_eq!(pagina.total, 10_000);
+        assert_eq!(pagina.propostas.len(), 50);
+        assert_eq!(pagina.propostas[0]["item"], "1");
+
+        // Ordem numérica: o item 10 vem depois do 9
+        let pagina = paginar_propostas(&content, 8, 3, Some((CampoOrdenacao::Item, false))).unwrap();
+        let itens: Vec<&str> = pagina.propostas.iter().map(|p| p["item"].as_str().unwrap()).collect();
+        assert_eq!(itens, vec!["9", "10", "11"]);
+
+        let pagina = paginar_propostas(&content, 0, 2, Some((CampoOrdenacao::Item, true))).unwrap();
+        assert_eq!(pagina.propostas[0]["item"], "10000");
+
+        // Valores numéricos crescentes, "N/A" no fim em qualquer ordem
+        let todas = paginar_propostas(&content, 0, LIMITE_PAGINA_MAXIMO, Some((CampoOrdenacao::ValorAdjudicado, false))).unwrap();
+        let valores: Vec<f64> = todas.propostas.iter()
+            .map(|p| converter_valor_para_float(p["valor_adjudicado"].as_str().unwrap()).unwrap())
+            .collect();
+        assert!(valores.windows(2).all(|par| par[0] <= par[1]));
+        let fim = paginar_propostas(&content, 9_995, 10, Some((CampoOrdenacao::ValorAdjudicado, true))).unwrap();
+        assert_eq!(fim.propostas.len(), 5);
+        assert!(fim.propostas.iter().all(|p| p["valor_adjudicado"] == "N/A"));
+
+        let pagina = paginar_propostas(&content, 0, 2, Some((CampoOrdenacao::Fornecedor, false))).unwrap();
+        assert_eq!(pagina.propostas[0]["fornecedor"], "Fornecedor 00000");
+
+        // Página além do fim e limite acima do máximo
+        assert!(paginar_propostas(&content, 20_000, 10, None).unwrap().propostas.is_empty());
+        assert_eq!(paginar_propostas(&content, 0, 50_000, None).unwrap().limit, LIMITE_PAGINA_MAXIMO);
+    }
+
+    #[test]
+    fn test_paginar_propostas_em_lista_na_raiz() {
+        let content = serde_json::Value::from(propostas_sinteticas(30)).to_string();
+        let pagina = paginar_propostas(&content, 10, 5, None).unwrap();
+        assert_eq!(pagina.total, 30);
+        assert_eq!(pagina.propostas[0]["item"], "11");
+
+        assert!(paginar_propostas(r#"{"uasg": "787000"}"#, 0, 10, None).is_err());
+        assert!(paginar_propostas(r#"{"propostas": [1, "#, 0, 10, None).is_err());
+
+        // Por item, o grupo vem antes: "G2" antes de "G10", itens sem grupo primeiro
+        let content = serde_json::json!([
+            {"grupo": "G10", "item": "1"}, {"grupo": "G2", "item": "2"}, {"grupo": null, "item": "5"}, {"grupo": "G2", "item": "1"},
+        ]).to_string();
+        let pagina = paginar_propostas(&content, 0, 10, Some((CampoOrdenacao::Item, false))).unwrap();
+        let ordem: Vec<(&str, &str)> = pagina.propostas.iter()
+            .map(|p| (p["grupo"].as_str().unwrap_or("-"), p["item"].as_str().unwrap()))
+            .collect();
+        assert_eq!(ordem, [("-", "5"), ("G2", "1"), ("G2", "2"), ("G10", "1")]);
+    }
+
+    #[test]
+    fn test_listar_licitacoes_filtradas() {
+        let dir = std::env::temp_dir().join(format!("licitacao360_listagem_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let cabecalho = |uasg: &str, pregao: &str, data: &str, total: usize, valor: f64| serde_json::json!({
+            "data_geracao": data, "uasg": uasg, "pregao": pregao, "processo": "1",
+            "total_propostas": total, "valor_total": valor, "propostas": [{"item": "não interpretado"}]
+        }).to_string();
+        fs::write(dir.join("licitacao_a.json"), cabecalho("787000", "90008/2024", "2024-03-10 12:00:00 UTC", 3, 50.0)).unwrap();
+        fs::write(dir.join("licitacao_b.json"), cabecalho("787000", "90010/2024", "2024-05-02 08:00:00 UTC", 1, 900.0)).unwrap();
+        fs::write(dir.join("licitacao_c.json"), cabecalho("160001", "90008/2024", "2024-05-20 08:00:00 UTC", 8, 10.0)).unwrap();
+        let mut dispensa: serde_json::Value = serde_json::from_str(&cabecalho("160001", "90015/2024", "2024-06-01 08:00:00 UTC", 2, 5.0)).unwrap();
+        dispensa["modalidade"] = MODALIDADE_DISPENSA.into();
+        fs::write(dir.join("licitacao_dispensa_d.json"), dispensa.to_string()).unwrap();
+        for (arquivo, homologacao) in [("licitacao_a.json", "2024-03-05T10:32:00-03:00"), ("licitacao_c.json", "2024-04-30T23:30:00-03:00")] {
+            let mut json: serde_json::Value = serde_json::from_str(&fs::read_to_string(dir.join(arquivo)).unwrap()).unwrap();
+            json["data_homologacao_iso"] = homologacao.into();
+            fs::write(dir.join(arquivo), json.to_string()).unwrap();
+        }
+        fs::write(dir.join("resumo_geral.json"), "{}").unwrap();
+        fs::write(dir.join("exemplo_resultado.json"), r#"{"info": "exemplo"}"#).unwrap();
+        let diretorios = vec![dir.clone()];
+
+        let nomes = |filtro: &FiltroArquivosLicitacao, ordenacao| -> Vec<String> {
+            listar_licitacoes_filtradas(&diretorios, filtro, ordenacao).into_iter().map(|i| i.nome).collect()
+        };
+
+        let todos = FiltroArquivosLicitacao::default();
+        assert_eq!(nomes(&todos, OrdenacaoArquivos::ValorTotal), vec!["licitacao_b.json", "licitacao_a.json", "licitacao_c.json", "licitacao_dispensa_d.json"]);
+        assert_eq!(nomes(&todos, OrdenacaoArquivos::TotalPropostas), vec!["licitacao_c.json", "licitacao_a.json", "licitacao_dispensa_d.json", "licitacao_b.json"]);
+
+        // Sem o campo, o arquivo é de pregão
+        let filtro = FiltroArquivosLicitacao { modalidade: Some("Dispensa".to_string()), ..Default::default() };
+        assert_eq!(nomes(&filtro, OrdenacaoArquivos::ModificadoEm), vec!["licitacao_dispensa_d.json"]);
+        let filtro = FiltroArquivosLicitacao { modalidade: Some(MODALIDADE_PREGAO.to_string()), ..Default::default() };
+        assert_eq!(nomes(&filtro, OrdenacaoArquivos::ValorTotal), vec!["licitacao_b.json", "licitacao_a.json", "licitacao_c.json"]);
+
+        let filtro = FiltroArquivosLicitacao { uasg: Some("787.000".to_string()), date_from: Some("2024-04-01".to_string()), ..Default::default() };
+        assert_eq!(nomes(&filtro, OrdenacaoArquivos::ModificadoEm), vec!["licitacao_b.json"]);
+
+        let filtro = FiltroArquivosLicitacao { pregao: Some("90008".to_string()), date_to: Some("2024-05-20".to_string()), ..Default::default() };
+        assert_eq!(nomes(&filtro, OrdenacaoArquivos::TotalPropostas), vec!["licitacao_c.json", "licitacao_a.json"]);
+
+        // Intervalo de homologação no dia de Brasília; arquivos sem a data ficam de fora
+        let filtro = FiltroArquivosLicitacao { homologation_from: Some("2024-04-30".to_string()), ..Default::default() };
+        assert_eq!(nomes(&filtro, OrdenacaoArquivos::ModificadoEm), vec!["licitacao_c.json"]);
+        let filtro = FiltroArquivosLicitacao { homologation_to: Some("2024-04-29".to_string()), ..Default::default() };
+        assert_eq!(nomes(&filtro, OrdenacaoArquivos::ModificadoEm), vec!["licitacao_a.json"]);
+
+        let com_exemplos = FiltroArquivosLicitacao { include_examples: true, ..Default::default() };
+        assert!(nomes(&com_exemplos, OrdenacaoArquivos::ModificadoEm).contains(&"exemplo_resultado.json".to_string()));
+
+        // Totais gravados como texto não tiram o arquivo da listagem
+        let legado = dir.join("licitacao_legado.json");
+        fs::write(&legado, serde_json::json!({
+            "uasg": "70011", "pregao": "90099/2023", "total_propostas": "12", "valor_total": "1.234,50", "propostas": []
+        }).to_string()).unwrap();
+        let filtro = FiltroArquivosLicitacao { pregao: Some("90099".to_string()), ..Default::default() };
+        let listados = listar_licitacoes_filtradas(&diretorios, &filtro, OrdenacaoArquivos::ModificadoEm);
+        assert_eq!(listados.len(), 1);
+        assert_eq!((listados[0].total_propostas, listados[0].valor_total), (12, 1234.5));
+        assert_eq!(arquivos_com_uasg_nao_normalizada(std::slice::from_ref(&legado)), vec![legado]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_historico_fornecedor_por_cnpj_normalizado() {
+        let dir = std::env::temp_dir().join(format!("licitacao360_historico_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut item_1 = proposta("Caneta azul", "PAPELARIA CENTRAL LTDA", "11.222.333/0001-81");
+        item_1["valor_adjudicado"] = "R$ 1.234,50".into();
+        let mut deserto = proposta("Grampeador", "PAPELARIA CENTRAL LTDA", "11.222.333/0001-81");
+        deserto["situacao"] = "Deserto".into();
+        let outro = proposta("Papel A4", "OUTRA EMPRESA", "99.888.777/0001-66");
+        let mut item_2 = proposta("Lápis", "Papelaria Central", "11222333000181");
+        item_2["uasg"] = "160001".into();
+        item_2["valor_adjudicado"] = "10,00".into();
+
+        let primeira = dir.join("licitacao_787000-90008_2024-123.json");
+        let segunda = dir.join("licitacao_160001-90001_2024-9.json");
+        let sem_participacao = dir.join("licitacao_787000-90009_2024-5.json");
+        let quebrado = dir.join("licitacao_quebrado.json");
+        fs::write(&primeira, licitacao_json(serde_json::json!([item_1, deserto, outro.clone()]))).unwrap();
+        fs::write(&segunda, licitacao_json(serde_json::json!([item_2]))).unwrap();
+        fs::write(&sem_participacao, licitacao_json(serde_json::json!([outro]))).unwrap();
+        fs::write(&quebrado, "{").unwrap();
+
+        let historico = historico_fornecedor(&[primeira, segunda, sem_participacao, quebrado], "11222333/0001-81");
+        assert_eq!(historico.total_itens, 2);
+        assert_eq!(historico.licitacoes.len(), 2);
+        assert!((historico.valor_total_adjudicado - 1244.5).abs() < 1e-9);
+        assert_eq!(historico.licitacoes[0].itens[0].descricao, "Caneta azul");
+        assert_eq!(historico.licitacoes[0].valor_adjudicado, 1234.5);
+        assert_eq!(historico.fornecedor, "Papelaria Central");
+        assert_eq!(historico.arquivos_com_erro.len(), 1);
+
+        let vazio = historico_fornecedor(&[dir.join("licitacao_787000-90009_2024-5.json")], "11.222.333/0001-81");
+        assert_eq!(vazio.total_itens, 0);
+        assert!(vazio.licitacoes.is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_renomear_no_resumo_geral_e_reconstruir() {
+        let dir = std::env::temp_dir().join(format!("licitacao360_renomear_resumo_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+        let antigo = dir.join("licitacao_pregao_787000-90008_2024-123.json");
+        let novo = dir.join("licitacao_canetas_2024.json");
+        fs::write(&antigo, licitacao_json(serde_json::json!([proposta("Caneta", "EMPRESA A", "12.345.678/0001-90")]))).unwrap();
+        fs::write(dir.join(pdf_processor::ARQUIVO_RESUMO_GERAL), serde_json::json!({
+            "data_geracao": "2024-05-01 10:00:00 UTC", "total_licitacoes": 1, "total_propostas": 2,
+            "propostas_duplicadas_ignoradas": 3,
+            "arquivos_gerados": ["licitacao_pregao_787000-90008_2024-123.json", pdf_processor::ARQUIVO_CONSOLIDADO]
+        }).to_string()).unwrap();
+
+        integridade::renomear_artefato(&dir, &antigo, &novo).unwrap();
+        let resumo = renomear_no_resumo_geral(&dir, std::slice::from_ref(&dir), &antigo, &novo).unwrap();
+        assert_eq!(resumo.arquivos_gerados, vec!["licitacao_canetas_2024.json", pdf_processor::ARQUIVO_CONSOLIDADO]);
+        assert_eq!(resumo.propostas_duplicadas_ignoradas, 3);
+
+        // Sem resumo, ele é refeito a partir dos arquivos presentes
+        fs::remove_file(dir.join(pdf_processor::ARQUIVO_RESUMO_GERAL)).unwrap();
+        let resumo = renomear_no_resumo_geral(&dir, std::slice::from_ref(&dir), &antigo, &novo).unwrap();
+        assert_eq!(resumo.arquivos_gerados, vec!["licitacao_canetas_2024.json"]);
+        assert_eq!((resumo.total_licitacoes, resumo.total_propostas), (1, 2));
+        let gravado: ResumoGeral = serde_json::from_str(&fs::read_to_string(dir.join(pdf_processor::ARQUIVO_RESUMO_GERAL)).unwrap()).unwrap();
+        assert_eq!(gravado.arquivos_gerados, resumo.arquivos_gerados);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_substituir_propostas_preserva_outros_arquivos() {
+        let dir = std::env::temp_dir().join(format!("licitacao360_substituir_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let proposta_de = |item: &str, cnpj: &str, valor: &str, origem: Option<&str>| -> PropostaConsolidada {
+            let mut json = proposta("Caneta", "EMPRESA", cnpj);
+            json["item"] = item.into();
+            json["valor_adjudicado"] = valor.into();
+            json["arquivo_origem"] = origem.into();
+            serde_json::from_value(json).unwrap()
+        };
+        let (a, b) = ("12.345.678/0001-90", "11.222.333/0001-81");
+
+        // Itens 4 e 5 vêm de um JSON anterior ao campo arquivo_origem
+        let existentes = [
+            proposta_de("1", a, "1,00", Some("ata.pdf")),
+            proposta_de("2", a, "2,00", Some("ata.pdf")),
+            proposta_de("3", b, "3,00", Some("outra.pdf")),
+            proposta_de("4", b, "4,00", None),
+            proposta_de("5", a, "5,00", None),
+        ];
+        pdf_processor::salvar_json_consolidado(&existentes, &dir, "", &[], false).unwrap();
+
+        let novas = [
+            proposta_de("1", a, "10,00", Some("ata.pdf")),
+            proposta_de("3", b, "30,00", Some("ata.pdf")),
+            proposta_de("5", a, "50,00", Some("ata.pdf")),
+        ];
+        let reprocessamento = substituir_propostas(&dir, &[], "ata.pdf", &novas).unwrap();
+        assert_eq!(reprocessamento.arquivos_alterados.len(), 1);
+        assert_eq!(reprocessamento.propostas_removidas, 3);
+        assert_eq!((reprocessamento.propostas_inseridas, reprocessamento.propostas_ignoradas), (2, 1));
+
+        let licitacao = carregar_licitacao(Path::new(&reprocessamento.arquivos_alterados[0])).unwrap();
+        let itens: Vec<(&str, f64)> = licitacao.propostas.iter()
+            .map(|p| (p.item.as_str(), p.total_do_item().unwrap().0))
+            .collect();
+        // O JSON sai em ordem de item, independentemente de onde cada proposta veio
+        assert_eq!(itens, [("1", 10.0), ("3", 3.0), ("4", 4.0), ("5", 50.0)]);
+        assert_eq!((licitacao.total_propostas, licitacao.valor_total), (4, 67.0));
+
+        let consolidado = pdf_processor::carregar_consolidado(&dir.join(pdf_processor::ARQUIVO_CONSOLIDADO)).unwrap();
+        assert_eq!((consolidado.total_propostas, consolidado.valor_total_geral), (4, 67.0));
+        let resumo: ResumoGeral = serde_json::from_str(&fs::read_to_string(dir.join(pdf_processor::ARQUIVO_RESUMO_GERAL)).unwrap()).unwrap();
+        assert_eq!((resumo.total_licitacoes, resumo.total_propostas), (1, 4));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_regenerar_licitacao_com_os_pdfs_de_origem() {
+        let dir = std::env::temp_dir().join(format!("licitacao360_regenerar_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let proposta_de = |item: &str, valor: &str, origem: &str| -> PropostaConsolidada {
+            let mut json = proposta("Caneta", "EMPRESA", "12.345.678/0001-90");
+            json["item"] = item.into();
+            json["valor_adjudicado"] = valor.into();
+            json["arquivo_origem"] = origem.into();
+            serde_json::from_value(json).unwrap()
+        };
+        let reextraidas = vec![
+            (dir.join("ata.pdf"), vec![proposta_de("1", "1,00", "ata.pdf")]),
+            (dir.join("outra.pdf"), vec![proposta_de("2", "2,00", "outra.pdf")]),
+        ];
+        let todas: Vec<_> = reextraidas.iter().flat_map(|(_, propostas)| propostas.clone()).collect();
+        let consolidacao = pdf_processor::salvar_json_consolidado(&todas, &dir, "", &[], false).unwrap();
+        let path = consolidacao.caminhos_licitacoes().remove(0);
+        fs::write(&path, b"{\"propostas\": [").unwrap();
+
+        let regravados = regenerar_licitacao(&dir, &path, &[], &reextraidas).unwrap();
+        assert_eq!(regravados, vec![path.to_string_lossy().to_string()]);
+
+        let licitacao = carregar_licitacao(&path).unwrap();
+        let itens: Vec<&str> = licitacao.propostas.iter().map(|p| p.item.as_str()).collect();
+        assert_eq!(itens, ["1", "2"]);
+        assert_eq!(fs::read_dir(dir.join(integridade::PASTA_LIXEIRA)).unwrap().count(), 1);
+
+        let relatorio = integridade::verificar_integridade(&dir).unwrap();
+        assert!(relatorio.divergentes.is_empty());
+        let manifesto = integridade::carregar_manifesto(&dir).unwrap();
+        let entrada = &manifesto.arquivos[path.file_name().unwrap().to_str().unwrap()];
+        assert_eq!(entrada.origens_pdf, [dir.join("ata.pdf").to_string_lossy(), dir.join("outra.pdf").to_string_lossy()]);
+
+        // Sem nenhuma proposta reextraída o JSON existente é mantido
+        let vazias = vec![(dir.join("ata.pdf"), Vec::new())];
+        assert!(regenerar_licitacao(&dir, &path, &[], &vazias).is_err());
+        assert!(path.exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,207 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Local};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+use crate::types::{ArquivoMovido, LimpezaResultados, RetentionPolicy};
+use crate::{indice_pdfs, integridade, paths, pdf_processor, resultados};
+
+/// Subpasta de Resultados que recebe os resultados antigos, separados por mês (AAAA-MM)
+pub const PASTA_ARQUIVO: &str = "Arquivo";
+
+/// Só JSONs de licitação e relatórios Markdown são arquivados; resumo geral, SICAF e exemplos ficam
+fn eh_arquivavel(path: &Path) -> bool {
+    let nome = path.file_name().unwrap_or_default().to_string_lossy();
+    (nome.starts_with(resultados::PREFIXO_LICITACAO) && paths::is_json_file(path))
+        || path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+}
+
+/// Resultados da pasta (e das subpastas de roteamento) que a política manda arquivar, com a data de modificação
+///
+/// `keep_last_n` preserva os N mais recentes de cada tipo (JSON e Markdown contados à parte) e
+/// `max_age_days` só libera os modificados há mais dias que isso; com os dois, o arquivo precisa
+/// se enquadrar em ambos. A lixeira, pastas ocultas e o próprio arquivo morto não são percorridos.
+pub fn arquivos_elegiveis(output_dir: &Path, politica: &RetentionPolicy, agora: DateTime<Local>) -> Result<Vec<(PathBuf, DateTime<Local>)>> {
+    if politica.max_age_days.is_none() && politica.keep_last_n.is_none() {
+        bail!("Informe max_age_days ou keep_last_n na política de retenção");
+    }
+
+    let mut por_tipo: HashMap<String, Vec<(PathBuf, DateTime<Local>)>> = HashMap::new();
+    let entradas = WalkDir::new(output_dir)
+        .max_depth(2)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !(integridade::eh_pasta_ignorada(e.file_name()) || e.file_name() == PASTA_ARQUIVO))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && eh_arquivavel(e.path()));
+    for entry in entradas {
+        let Some(modificado) = entry.metadata().ok().and_then(|m| m.modified().ok()) else {
+            continue;
+        };
+        let tipo = entry.path().extension().unwrap_or_default().to_string_lossy().to_lowercase();
+        por_tipo.entry(tipo).or_default().push((entry.into_path(), modificado.into()));
+    }
+
+    let limite = politica.max_age_days.map(|dias| agora - Duration::days(i64::from(dias)));
+    let mut elegiveis: Vec<(PathBuf, DateTime<Local>)> = por_tipo.into_values()
+        .flat_map(|mut arquivos| {
+            // Mais recentes primeiro; empates pelo caminho, para o resultado não depender da ordem da listagem
+            arquivos.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            arquivos.into_iter().skip(politica.keep_last_n.unwrap_or(0))
+        })
+        .filter(|(_, modificado)| limite.is_none_or(|limite| *modificado < limite))
+        .collect();
+    elegiveis.sort();
+    Ok(elegiveis)
+}
+
+/// Move os resultados elegíveis para Arquivo/AAAA-MM (mês da modificação), ou só os lista em `dry_run`
+///
+/// Nada é apagado; nomes repetidos no mesmo mês recebem sufixo. Falhas em um arquivo
+/// não interrompem os demais e ficam em `erros`. Depois de mover, o resumo geral e o índice
+/// de PDFs processados (em `config_dir`) passam a apontar para os novos caminhos.
+pub fn aplicar_politica(output_dir: &Path, config_dir: Option<&Path>, politica: &RetentionPolicy, agora: DateTime<Local>) -> Result<LimpezaResultados> {
+    let mut limpeza = LimpezaResultados { dry_run: politica.dry_run, ..Default::default() };
+    let mut movidos: Vec<(PathBuf, PathBuf)> = Vec::new();
+
+    for (origem, modificado) in arquivos_elegiveis(output_dir, politica, agora)? {
+        let pasta = output_dir.join(PASTA_ARQUIVO).join(modificado.format("%Y-%m").to_string());
+        let nome = origem.file_name().unwrap_or_default();
+        if politica.dry_run {
+            limpeza.arquivos.push(ArquivoMovido {
+                origem: origem.to_string_lossy().to_string(),
+                destino: pasta.join(nome).to_string_lossy().to_string(),
+            });
+            continue;
+        }
+
+        let destino = paths::caminho_sem_colisao(&pasta, nome);
+        let movido = fs::create_dir_all(&pasta).map_err(anyhow::Error::from)
+            .and_then(|_| integridade::renomear_artefato(output_dir, &origem, &destino));
+        match movido {
+            Ok(()) => {
+                limpeza.arquivos.push(ArquivoMovido {
+                    origem: origem.to_string_lossy().to_string(),
+                    destino: destino.to_string_lossy().to_string(),
+                });
+                movidos.push((origem, destino));
+            }
+            Err(e) => limpeza.erros.push(format!("{}: {:#}", origem.display(), e)),
+        }
+    }
+
+    if !movidos.is_empty() {
+        if let Err(e) = resultados::mover_no_resumo_geral(output_dir, &movidos) {
+            limpeza.erros.push(format!("{}: {:#}", pdf_processor::ARQUIVO_RESUMO_GERAL, e));
+        }
+        if let Some(config_dir) = config_dir {
+            if let Err(e) = indice_pdfs::mover_jsons(config_dir, &movidos) {
+                limpeza.erros.push(format!("{}: {:#}", indice_pdfs::ARQUIVO_INDICE, e));
+            }
+        }
+    }
+
+    Ok(limpeza)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::time::SystemTime;
+
+    fn criar(path: &Path, modificado: DateTime<Local>) {
+        fs::write(path, b"{}").unwrap();
+        fs::File::options().write(true).open(path).unwrap().set_modified(SystemTime::from(modificado)).unwrap();
+    }
+
+    #[test]
+    fn test_politica_de_retencao_arquiva_por_idade_e_quantidade() {
+        let dir = std::env::temp_dir().join(format!("licitacao360_retencao_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("Lixeira")).unwrap();
+        let agora = Local.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap();
+        let dias_atras = |dias: i64| agora - Duration::days(dias);
+
+        criar(&dir.join("licitacao_a.json"), dias_atras(100));
+        criar(&dir.join("licitacao_b.json"), dias_atras(40));
+        criar(&dir.join("licitacao_c.json"), dias_atras(1));
+        criar(&dir.join("ata_antiga.md"), dias_atras(200));
+        criar(&dir.join("resumo_geral.json"), dias_atras(300));
+        criar(&dir.join("sicaf_dados.json"), dias_atras(300));
+        criar(&dir.join("exemplo.json"), dias_atras(300));
+        criar(&dir.join("Lixeira").join("licitacao_velha.json"), dias_atras(300));
+
+        let politica = |max_age_days, keep_last_n, dry_run| RetentionPolicy { max_age_days, keep_last_n, dry_run };
+        assert!(arquivos_elegiveis(&dir, &politica(None, None, true), agora).is_err());
+
+        let nomes = |politica: RetentionPolicy| -> Vec<String> {
+            arquivos_elegiveis(&dir, &politica, agora).unwrap().iter()
+                .map(|(path, _)| path.file_name().unwrap().to_string_lossy().to_string())
+                .collect()
+        };
+        assert_eq!(nomes(politica(Some(30), None, true)), vec!["ata_antiga.md", "licitacao_a.json", "licitacao_b.json"]);
+        // Os 2 JSONs mais recentes ficam, mesmo o de 40 dias; o único .md também
+        assert_eq!(nomes(politica(None, Some(2), true)), vec!["licitacao_a.json"]);
+        assert_eq!(nomes(politica(Some(90), Some(1), true)), vec!["licitacao_a.json"]);
+
+        // Simulação não move nada
+        let simulacao = aplicar_politica(&dir, None, &politica(Some(30), None, true), agora).unwrap();
+        assert_eq!(simulacao.arquivos.len(), 3);
+        assert!(dir.join("licitacao_a.json").exists());
+
+        let maio = dir.join(PASTA_ARQUIVO).join("2024-05");
+        fs::create_dir_all(&maio).unwrap();
+        criar(&maio.join("licitacao_b.json"), dias_atras(0));
+        let limpeza = aplicar_politica(&dir, None, &politica(Some(30), None, false), agora).unwrap();
+        assert!(limpeza.erros.is_empty(), "{:?}", limpeza.erros);
+        assert_eq!(limpeza.arquivos.len(), 3);
+        assert!(!dir.join("licitacao_a.json").exists());
+        assert!(dir.join(PASTA_ARQUIVO).join(dias_atras(100).format("%Y-%m").to_string()).join("licitacao_a.json").exists());
+        // Já havia um licitacao_b.json no mês de destino
+        assert!(maio.join("licitacao_b_1.json").exists());
+        assert!(dir.join("resumo_geral.json").exists() && dir.join("sicaf_dados.json").exists() && dir.join("exemplo.json").exists());
+
+        // O arquivo morto não é percorrido de novo
+        assert!(arquivos_elegiveis(&dir, &politica(Some(30), None, true), agora).unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_arquivamento_atualiza_resumo_geral_e_indice() {
+        let dir = std::env::temp_dir().join(format!("licitacao360_retencao_resumo_{}", std::process::id()));
+        let config_dir = dir.join("Config");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&config_dir).unwrap();
+        let agora = Local.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap();
+        let antigo = dir.join("licitacao_a.json");
+        criar(&antigo, agora - Duration::days(100));
+        criar(&dir.join("licitacao_b.json"), agora - Duration::days(1));
+
+        let resumo = crate::types::ResumoGeral {
+            arquivos_gerados: vec!["licitacao_a.json".to_string(), "licitacao_b.json".to_string()],
+            ..Default::default()
+        };
+        fs::write(dir.join(pdf_processor::ARQUIVO_RESUMO_GERAL), serde_json::to_string(&resumo).unwrap()).unwrap();
+        indice_pdfs::registrar_processados(&config_dir, vec![("abc".to_string(), indice_pdfs::EntradaIndicePdf {
+            arquivo: "ata.pdf".to_string(),
+            processado_em: agora.to_rfc3339(),
+            jsons: vec![antigo.to_string_lossy().to_string()],
+        })]).unwrap();
+
+        let politica = RetentionPolicy { max_age_days: Some(30), keep_last_n: None, dry_run: false };
+        let limpeza = aplicar_politica(&dir, Some(&config_dir), &politica, agora).unwrap();
+        assert!(limpeza.erros.is_empty(), "{:?}", limpeza.erros);
+        let destino = PathBuf::from(&limpeza.arquivos[0].destino);
+
+        let resumo: crate::types::ResumoGeral = serde_json::from_str(
+            &fs::read_to_string(dir.join(pdf_processor::ARQUIVO_RESUMO_GERAL)).unwrap()
+        ).unwrap();
+        assert_eq!(resumo.arquivos_gerados, vec![destino.to_string_lossy().to_string(), "licitacao_b.json".to_string()]);
+        let indice = indice_pdfs::carregar_indice(&config_dir).unwrap();
+        assert_eq!(indice["abc"].jsons, vec![destino.to_string_lossy().to_string()]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
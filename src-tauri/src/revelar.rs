@@ -0,0 +1,120 @@
+use std::path::Path;
+
+/// Sistema operacional para o qual os comandos são montados
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Plataforma {
+    Windows,
+    MacOs,
+    Linux,
+}
+
+impl Plataforma {
+    /// Plataforma em que o aplicativo está rodando (outros Unix usam o caminho do Linux)
+    pub fn atual() -> Self {
+        if cfg!(target_os = "windows") {
+            Plataforma::Windows
+        } else if cfg!(target_os = "macos") {
+            Plataforma::MacOs
+        } else {
+            Plataforma::Linux
+        }
+    }
+}
+
+/// Programa e argumentos de um comando do sistema
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComandoSistema {
+    pub programa: String,
+    pub args: Vec<String>,
+    /// Os argumentos vão para a linha de comando sem as aspas automáticas do Windows
+    pub argumentos_brutos: bool,
+}
+
+impl ComandoSistema {
+    fn novo(programa: &str, args: Vec<String>) -> Self {
+        Self { programa: programa.to_string(), args, argumentos_brutos: false }
+    }
+}
+
+/// URI `file://` com o caminho codificado em porcentagem
+///
+/// Além de espaços e acentos, vírgulas também são codificadas, porque o `dbus-send`
+/// as usa para separar os elementos de um array.
+pub fn uri_arquivo(path: &Path) -> String {
+    let caminho = path.to_string_lossy();
+    let mut uri = String::from("file://");
+    for byte in caminho.as_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => uri.push(*byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+/// Comandos que abrem o gerenciador de arquivos com o arquivo selecionado, na ordem de tentativa
+///
+/// - Windows: `explorer /select,"caminho"`, com as aspas montadas aqui (o explorer não
+///   entende o argumento inteiro entre aspas, como o Rust faria por padrão)
+/// - macOS: `open -R caminho`
+/// - Linux: `ShowItems` do `org.freedesktop.FileManager1` via D-Bus e, se não houver
+///   gerenciador que o implemente, `xdg-open` na pasta pai (sem seleção)
+pub fn comandos_revelar(plataforma: Plataforma, arquivo: &Path) -> Vec<ComandoSistema> {
+    match plataforma {
+        Plataforma::Windows => vec![ComandoSistema {
+            programa: "explorer".to_string(),
+            // Caminhos do Windows não podem conter aspas, então não há o que escapar
+            args: vec![format!("/select,\"{}\"", arquivo.display())],
+            argumentos_brutos: true,
+        }],
+        Plataforma::MacOs => vec![ComandoSistema::novo(
+            "open",
+            vec!["-R".to_string(), arquivo.to_string_lossy().to_string()],
+        )],
+        Plataforma::Linux => {
+            let pasta = arquivo.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+            vec![
+                ComandoSistema::novo("dbus-send", vec![
+                    "--session".to_string(),
+                    "--print-reply".to_string(),
+                    "--dest=org.freedesktop.FileManager1".to_string(),
+                    "--type=method_call".to_string(),
+                    "/org/freedesktop/FileManager1".to_string(),
+                    "org.freedesktop.FileManager1.ShowItems".to_string(),
+                    format!("array:string:{}", uri_arquivo(arquivo)),
+                    "string:".to_string(),
+                ]),
+                ComandoSistema::novo("xdg-open", vec![pasta.to_string_lossy().to_string()]),
+            ]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comandos_revelar_por_plataforma() {
+        let windows = comandos_revelar(Plataforma::Windows, Path::new(r"C:\Licitação 360\Resultados\licitacao_pregao_1.json"));
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].programa, "explorer");
+        assert_eq!(windows[0].args, vec![r#"/select,"C:\Licitação 360\Resultados\licitacao_pregao_1.json""#]);
+        assert!(windows[0].argumentos_brutos);
+
+        let macos = comandos_revelar(Plataforma::MacOs, Path::new("/Users/ana/Licitação 360/ata (1).json"));
+        assert_eq!(macos, vec![ComandoSistema::novo("open", vec!["-R".to_string(), "/Users/ana/Licitação 360/ata (1).json".to_string()])]);
+
+        let linux = comandos_revelar(Plataforma::Linux, Path::new("/home/ana/Resultados 2024/ata, final.json"));
+        assert_eq!(linux.len(), 2);
+        assert_eq!(linux[0].programa, "dbus-send");
+        assert!(linux[0].args.contains(&"array:string:file:///home/ana/Resultados%202024/ata%2C%20final.json".to_string()));
+        assert!(!linux[0].argumentos_brutos);
+        assert_eq!(linux[1], ComandoSistema::novo("xdg-open", vec!["/home/ana/Resultados 2024".to_string()]));
+    }
+
+    #[test]
+    fn test_uri_arquivo_codifica_acentos_e_especiais() {
+        assert_eq!(uri_arquivo(Path::new("/dados/Licitação #1/a&b.json")), "file:///dados/Licita%C3%A7%C3%A3o%20%231/a%26b.json");
+    }
+}
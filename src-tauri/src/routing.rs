@@ -0,0 +1,187 @@
+use std::path::{Path, PathBuf};
+use crate::types::RoutingRule;
+
+/// Extrai o ano de um pregão no formato "90008/2024" (anos com 2 dígitos viram 20xx)
+pub fn extrair_ano_pregao(pregao: &str) -> Option<String> {
+    let ano = pregao.rsplit('/').next()?.trim();
+
+    if pregao.contains('/') && !ano.is_empty() && ano.chars().all(|c| c.is_ascii_digit()) {
+        match ano.len() {
+            2 => Some(format!("20{}", ano)),
+            4 => Some(ano.to_string()),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
+/// Verifica se uma regra se aplica à licitação (todos os critérios informados devem casar)
+pub fn regra_corresponde(regra: &RoutingRule, uasg: &str, pregao: &str) -> bool {
+    let prefixo = regra.uasg_prefix.as_deref().map(str::trim).filter(|p| !p.is_empty());
+    let ano = regra.pregao_year.as_deref().map(str::trim).filter(|a| !a.is_empty());
+
+    // Regra sem critérios nunca casa (é rejeitada na validação)
+    if prefixo.is_none() && ano.is_none() {
+        return false;
+    }
+
+    if let Some(prefixo) = prefixo {
+        if !uasg.trim().starts_with(prefixo) {
+            return false;
+        }
+    }
+
+    if let Some(ano) = ano {
+        if extrair_ano_pregao(pregao).as_deref() != Some(ano) {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Retorna a primeira regra que casa com a licitação, junto com sua posição
+pub fn encontrar_regra<'a>(regras: &'a [RoutingRule], uasg: &str, pregao: &str) -> Option<(usize, &'a RoutingRule)> {
+    regras.iter()
+        .enumerate()
+        .find(|(_, regra)| regra_corresponde(regra, uasg, pregao))
+}
+
+/// Decide o diretório de saída de uma licitação, usando o padrão quando nenhuma regra casa
+pub fn diretorio_destino(regras: &[RoutingRule], output_dir: &Path, uasg: &str, pregao: &str) -> PathBuf {
+    match encontrar_regra(regras, uasg, pregao) {
+        Some((_, regra)) => PathBuf::from(&regra.target_directory),
+        None => output_dir.to_path_buf(),
+    }
+}
+
+/// Lista os diretórios de destino distintos das regras, na ordem em que aparecem
+pub fn diretorios_roteados(regras: &[RoutingRule]) -> Vec<PathBuf> {
+    let mut diretorios: Vec<PathBuf> = Vec::new();
+
+    for regra in regras {
+        let dir = PathBuf::from(&regra.target_directory);
+        if !diretorios.contains(&dir) {
+            diretorios.push(dir);
+        }
+    }
+
+    diretorios
+}
+
+/// Valida as regras: cada uma precisa de critério e de um destino gravável
+pub fn validar_regras(regras: &[RoutingRule]) -> Result<(), String> {
+    for (index, regra) in regras.iter().enumerate() {
+        let prefixo = regra.uasg_prefix.as_deref().map(str::trim).filter(|p| !p.is_empty());
+        let ano = regra.pregao_year.as_deref().map(str::trim).filter(|a| !a.is_empty());
+
+        if prefixo.is_none() && ano.is_none() {
+            return Err(format!("Regra {}: informe um prefixo de UASG ou um ano de pregão", index + 1));
+        }
+
+        if let Some(ano) = ano {
+            if ano.len() != 4 || !ano.chars().all(|c| c.is_ascii_digit()) {
+                return Err(format!("Regra {}: ano de pregão inválido: {}", index + 1, ano));
+            }
+        }
+
+        if regra.target_directory.trim().is_empty() {
+            return Err(format!("Regra {}: diretório de destino não informado", index + 1));
+        }
+
+        verificar_gravavel(Path::new(&regra.target_directory))
+            .map_err(|e| format!("Regra {}: diretório de destino não gravável ({}): {}", index + 1, regra.target_directory, e))?;
+    }
+
+    Ok(())
+}
+
+/// Garante que o diretório existe e aceita escrita
+fn verificar_gravavel(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+
+    let teste = dir.join(".licitacao360_teste_escrita");
+    std::fs::write(&teste, b"")?;
+    std::fs::remove_file(&teste)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regra(uasg_prefix: Option<&str>, pregao_year: Option<&str>, target: &str) -> RoutingRule {
+        RoutingRule {
+            uasg_prefix: uasg_prefix.map(str::to_string),
+            pregao_year: pregao_year.map(str::to_string),
+            target_directory: target.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_extrair_ano_pregao() {
+        assert_eq!(extrair_ano_pregao("90008/2024"), Some("2024".to_string()));
+        assert_eq!(extrair_ano_pregao("787000-90008/24"), Some("2024".to_string()));
+        assert_eq!(extrair_ano_pregao("N/A"), None);
+        assert_eq!(extrair_ano_pregao("2024"), None);
+    }
+
+    #[test]
+    fn test_regras_sobrepostas_primeira_vence() {
+        let regras = vec![
+            regra(Some("7870"), Some("2024"), "/destino/a"),
+            regra(Some("787"), None, "/destino/b"),
+            regra(None, Some("2024"), "/destino/c"),
+        ];
+
+        // Casa com as três: vence a primeira
+        let (index, escolhida) = encontrar_regra(&regras, "787000", "90008/2024").unwrap();
+        assert_eq!(index, 0);
+        assert_eq!(escolhida.target_directory, "/destino/a");
+
+        // Ano diferente: a primeira não casa mais, vence a segunda
+        let (index, _) = encontrar_regra(&regras, "787000", "90008/2023").unwrap();
+        assert_eq!(index, 1);
+
+        // Só o ano casa
+        let (index, _) = encontrar_regra(&regras, "160000", "00012/2024").unwrap();
+        assert_eq!(index, 2);
+
+        // Nada casa: cai no diretório padrão
+        assert!(encontrar_regra(&regras, "160000", "00012/2023").is_none());
+        assert_eq!(
+            diretorio_destino(&regras, Path::new("/padrao"), "160000", "00012/2023"),
+            PathBuf::from("/padrao")
+        );
+    }
+
+    #[test]
+    fn test_regra_sem_criterio_nunca_casa() {
+        let regras = vec![regra(None, Some("  "), "/destino")];
+        assert!(encontrar_regra(&regras, "787000", "90008/2024").is_none());
+        assert!(validar_regras(&regras).is_err());
+    }
+
+    #[test]
+    fn test_validar_regras_destino() {
+        let root = std::env::temp_dir().join(format!("licitacao360_routing_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+
+        let destino = root.join("uasg_787");
+        let regras = vec![regra(Some("787"), None, &destino.to_string_lossy())];
+        assert!(validar_regras(&regras).is_ok());
+        assert!(destino.is_dir());
+
+        // Um arquivo comum no caminho do destino impede a escrita
+        let bloqueio = root.join("bloqueio");
+        std::fs::write(&bloqueio, b"").unwrap();
+        let regras = vec![regra(Some("787"), None, &bloqueio.join("sub").to_string_lossy())];
+        assert!(validar_regras(&regras).is_err());
+
+        let regras = vec![regra(None, Some("24"), &destino.to_string_lossy())];
+        assert!(validar_regras(&regras).is_err());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,602 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::Ordering;
+use std::time::UNIX_EPOCH;
+use tracing::warn;
+use crate::integridade;
+use crate::i18n::MsgKey;
+use crate::types::{CompletedFile, ErrorKind, PersistedSession, ProcessingResult, ProcessingSession, ProcessingStatus, PropostaConsolidada, TauriError};
+
+/// Espelho das sessões em Database/Config
+pub const ARQUIVO_SESSOES: &str = "processing_sessions.json";
+
+/// Pasta (em Database/Config) com as propostas já extraídas de cada sessão, uma linha por PDF
+pub const PASTA_PARCIAIS: &str = "sessoes";
+
+/// Erro registrado nas sessões interrompidas pelo fechamento do aplicativo
+pub const ERRO_APLICACAO_ENCERRADA: &str = "aplicação encerrada";
+
+/// Serializa as regravações do espelho entre threads
+static ESCRITA: Mutex<()> = Mutex::new(());
+
+/// Texto de um panic capturado
+fn mensagem_panic(erro: &(dyn std::any::Any + Send)) -> &str {
+    erro.downcast_ref::<&str>().copied()
+        .or_else(|| erro.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("panic sem mensagem")
+}
+
+/// Executa `f` com o estado das sessões em memória sem derrubar o backend por causa de um panic
+///
+/// Um mutex envenenado por um panic anterior é recuperado, com um aviso no trace; um panic
+/// dentro de `f` vira um erro do comando e não envenena o mutex para os próximos acessos.
+pub fn with_state<R>(
+    state: &Mutex<HashMap<String, ProcessingStatus>>,
+    f: impl FnOnce(&mut HashMap<String, ProcessingStatus>) -> R,
+) -> std::result::Result<R, TauriError> {
+    let mut guard = state.lock().unwrap_or_else(|envenenado| {
+        warn!("Estado de processamento envenenado por um panic anterior; recuperando o conteúdo");
+        state.clear_poison();
+        envenenado.into_inner()
+    });
+    panic::catch_unwind(AssertUnwindSafe(|| f(&mut guard))).map_err(|erro| {
+        let mensagem = mensagem_panic(erro.as_ref());
+        warn!("Panic ao acessar o estado de processamento: {}", mensagem);
+        TauriError::system(format!("Falha interna ao acessar o estado de processamento: {}", mensagem), None)
+    })
+}
+
+/// Marca a sessão como finalizada, registrando o horário de término
+pub fn finalizar(status: &mut ProcessingStatus) {
+    status.is_processing = false;
+    status.finished_at = Some(Utc::now().to_rfc3339());
+}
+
+/// Sinaliza o cancelamento das sessões em andamento, retornando seus ids
+pub fn cancelar_ativas(sessoes: &mut HashMap<String, ProcessingStatus>) -> Vec<String> {
+    sessoes.iter_mut()
+        .filter(|(_, status)| status.is_processing)
+        .map(|(session_id, status)| {
+            status.cancel_flag.store(true, Ordering::SeqCst);
+            status.cancelled = true;
+            session_id.clone()
+        })
+        .collect()
+}
+
+/// Finaliza como canceladas as sessões ainda em andamento, registrando `motivo` como erro
+pub fn interromper_ativas(sessoes: &mut HashMap<String, ProcessingStatus>, motivo: &str) -> Vec<String> {
+    let ids = cancelar_ativas(sessoes);
+    for session_id in &ids {
+        if let Some(status) = sessoes.get_mut(session_id) {
+            status.errors.push(motivo.to_string());
+            finalizar(status);
+        }
+    }
+    ids
+}
+
+/// Sessão finalizada há mais tempo que a retenção (sessões em andamento nunca expiram)
+fn expirada(status: &ProcessingStatus, limite: DateTime<Utc>) -> bool {
+    if status.is_processing {
+        return false;
+    }
+
+    status.finished_at
+        .as_deref()
+        .and_then(|fim| DateTime::parse_from_rfc3339(fim).ok())
+        .is_some_and(|fim| fim.with_timezone(&Utc) < limite)
+}
+
+/// Remove as sessões finalizadas há mais de `retencao_minutos`, retornando quantas foram removidas
+pub fn remover_expiradas(
+    sessoes: &mut HashMap<String, ProcessingStatus>,
+    retencao_minutos: u64,
+    agora: DateTime<Utc>,
+) -> usize {
+    // Retenções grandes demais para o calendário equivalem a nunca expirar
+    let Some(limite) = i64::try_from(retencao_minutos)
+        .ok()
+        .and_then(Duration::try_minutes)
+        .and_then(|retencao| agora.checked_sub_signed(retencao))
+    else {
+        return 0;
+    };
+    let antes = sessoes.len();
+    sessoes.retain(|_, status| !expirada(status, limite));
+    antes - sessoes.len()
+}
+
+/// Insere uma nova sessão, descartando antes as sessões expiradas
+pub fn registrar(
+    sessoes: &mut HashMap<String, ProcessingStatus>,
+    session_id: String,
+    status: ProcessingStatus,
+    retencao_minutos: u64,
+) {
+    remover_expiradas(sessoes, retencao_minutos, Utc::now());
+    sessoes.insert(session_id, status);
+}
+
+/// Payload do evento de conclusão: o próprio resultado ou, numa falha, um resultado sem sucesso com a mensagem do erro
+pub fn resultado_do_evento(
+    session_id: &str,
+    resultado: std::result::Result<ProcessingResult, TauriError>,
+    total_processed: usize,
+    pulados: Vec<String>,
+) -> ProcessingResult {
+    resultado.unwrap_or_else(|erro| ProcessingResult {
+        success: false,
+        message: erro.message,
+        propostas: Vec::new(),
+        total_processed,
+        json_file_path: None,
+        json_file_paths: Vec::new(),
+        session_id: Some(session_id.to_string()),
+        skipped_files: pulados,
+        arquivos_por_tipo: BTreeMap::new(),
+        dry_run: None,
+        metrics: Vec::new(),
+    })
+}
+
+/// Encerra uma sessão executada em segundo plano, guardando o resultado para `get_processing_result`
+///
+/// Devolve o payload do evento de conclusão, emitido qualquer que seja o desfecho.
+pub fn concluir(
+    sessoes: &mut HashMap<String, ProcessingStatus>,
+    session_id: &str,
+    resultado: std::result::Result<ProcessingResult, TauriError>,
+    pulados: Vec<String>,
+) -> ProcessingResult {
+    let mut total_processed = 0;
+    if let Some(status) = sessoes.get_mut(session_id) {
+        // Uma falha antes do fim deixa a sessão ainda marcada como em andamento
+        if status.is_processing {
+            finalizar(status);
+        }
+        status.resultado = Some(Arc::new(resultado.clone()));
+        total_processed = status.processed_files;
+    }
+    resultado_do_evento(session_id, resultado, total_processed, pulados)
+}
+
+/// Resultado guardado por `concluir`; sessões ainda em andamento ou desconhecidas são erro
+pub fn consultar_resultado(sessoes: &HashMap<String, ProcessingStatus>, session_id: &str) -> std::result::Result<ProcessingResult, TauriError> {
+    match sessoes.get(session_id).map(|status| status.resultado.as_deref()) {
+        Some(Some(resultado)) => resultado.clone(),
+        Some(None) => Err(TauriError::traduzido(ErrorKind::ValidationError, MsgKey::SessionInProgress, &[("sessao", &session_id)], session_id.to_string())),
+        None => Err(TauriError::traduzido(ErrorKind::NotFound, MsgKey::SessionNotFound, &[("sessao", &session_id)], session_id.to_string())),
+    }
+}
+
+/// Status de uma sessão que só existe em disco (de uma execução anterior do app)
+pub fn status_persistido(sessao: &PersistedSession) -> ProcessingStatus {
+    let processados = sessao.completed_files.len();
+    ProcessingStatus {
+        is_processing: false,
+        current_file: None,
+        processed_files: processados,
+        total_files: sessao.total_files,
+        errors: sessao.errors.clone(),
+        progress_percentage: if sessao.total_files > 0 { processados as f64 / sessao.total_files as f64 * 100.0 } else { 0.0 },
+        cancelled: sessao.cancelled,
+        cancel_flag: Default::default(),
+        propostas: Default::default(),
+        started_at: sessao.started_at.clone(),
+        finished_at: sessao.finished_at.clone(),
+        resultado: None,
+    }
+}
+
+/// Lista as sessões em memória e as persistidas que não estão em memória, das mais recentes para as mais antigas
+pub fn listar(sessoes: &HashMap<String, ProcessingStatus>, persistidas: &BTreeMap<String, PersistedSession>) -> Vec<ProcessingSession> {
+    let mut lista: Vec<ProcessingSession> = sessoes
+        .iter()
+        .map(|(session_id, status)| ProcessingSession {
+            session_id: session_id.clone(),
+            status: status.clone(),
+        })
+        .chain(persistidas.values()
+            .filter(|p| !sessoes.contains_key(&p.session_id))
+            .map(|p| ProcessingSession {
+                session_id: p.session_id.clone(),
+                status: status_persistido(p),
+            }))
+        .collect();
+    lista.sort_by(|a, b| b.status.started_at.cmp(&a.status.started_at));
+    lista
+}
+
+/// Data de modificação em milissegundos, usada junto com o caminho para reconhecer um PDF já processado
+pub fn modificado_ms(path: &Path) -> Option<u64> {
+    let modificado = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    modificado.duration_since(UNIX_EPOCH).ok().map(|d| d.as_millis() as u64)
+}
+
+fn caminho_espelho(config_dir: &Path) -> PathBuf {
+    config_dir.join(ARQUIVO_SESSOES)
+}
+
+/// Arquivo de propostas parciais da sessão (o id vem do frontend e não pode escapar da pasta)
+fn caminho_parcial(config_dir: &Path, session_id: &str) -> PathBuf {
+    let nome: String = session_id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    config_dir.join(PASTA_PARCIAIS).join(format!("{}.jsonl", nome))
+}
+
+/// Carrega as sessões espelhadas (vazio se o arquivo ainda não existir)
+pub fn carregar_persistidas(config_dir: &Path) -> Result<BTreeMap<String, PersistedSession>> {
+    let path = caminho_espelho(config_dir);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let content = fs::read_to_string(&path)
+        .context(format!("Erro ao ler {}", path.display()))?;
+    serde_json::from_str(&content)
+        .context(format!("Erro ao analisar {}", path.display()))
+}
+
+/// Aplica uma alteração ao espelho e o regrava de forma atômica
+fn alterar_persistidas<F>(config_dir: &Path, alteracao: F) -> Result<()>
+where
+    F: FnOnce(&mut BTreeMap<String, PersistedSession>),
+{
+    let _guard = ESCRITA.lock().unwrap_or_else(|e| e.into_inner());
+    let mut sessoes = carregar_persistidas(config_dir)?;
+    alteracao(&mut sessoes);
+
+    let content = serde_json::to_string_pretty(&sessoes)
+        .context("Erro ao serializar sessões de processamento")?;
+    integridade::escrever_atomico(&caminho_espelho(config_dir), content.as_bytes())
+}
+
+/// Grava uma sessão nova (ou retomada), descartando as finalizadas há mais de `retencao_minutos`
+pub fn persistir_sessao(config_dir: &Path, sessao: PersistedSession, retencao_minutos: u64) -> Result<()> {
+    let limite = i64::try_from(retencao_minutos)
+        .ok()
+        .and_then(Duration::try_minutes)
+        .and_then(|retencao| Utc::now().checked_sub_signed(retencao));
+
+    alterar_persistidas(config_dir, |sessoes| {
+        let expiradas: Vec<String> = sessoes.values()
+            .filter(|s| {
+                let fim = s.finished_at.as_deref().and_then(|f| DateTime::parse_from_rfc3339(f).ok());
+                matches!((fim, limite), (Some(fim), Some(limite)) if fim.with_timezone(&Utc) < limite)
+            })
+            .map(|s| s.session_id.clone())
+            .collect();
+        for session_id in expiradas {
+            sessoes.remove(&session_id);
+            let _ = fs::remove_file(caminho_parcial(config_dir, &session_id));
+        }
+
+        sessoes.insert(sessao.session_id.clone(), sessao);
+    })
+}
+
+/// Linha do arquivo de propostas parciais
+#[derive(Debug, Serialize, Deserialize)]
+struct LinhaParcial {
+    #[serde(flatten)]
+    arquivo: CompletedFile,
+    propostas: Vec<PropostaConsolidada>,
+}
+
+/// Registra um PDF concluído: anexa suas propostas ao arquivo parcial e atualiza o espelho
+pub fn registrar_concluido(config_dir: &Path, session_id: &str, pdf_path: &Path, propostas: &[PropostaConsolidada]) -> Result<()> {
+    let arquivo = CompletedFile {
+        path: pdf_path.to_string_lossy().to_string(),
+        modified_ms: modificado_ms(pdf_path),
+    };
+
+    let parcial = caminho_parcial(config_dir, session_id);
+    if let Some(pasta) = parcial.parent() {
+        fs::create_dir_all(pasta).context("Erro ao criar pasta de sessões")?;
+    }
+    let linha = serde_json::to_string(&LinhaParcial { arquivo: arquivo.clone(), propostas: propostas.to_vec() })
+        .context("Erro ao serializar propostas parciais")?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&parcial)
+        .context(format!("Erro ao abrir {}", parcial.display()))?;
+    writeln!(file, "{}", linha).context(format!("Erro ao gravar {}", parcial.display()))?;
+
+    alterar_persistidas(config_dir, |sessoes| {
+        if let Some(sessao) = sessoes.get_mut(session_id) {
+            sessao.completed_files.push(arquivo);
+        }
+    })
+}
+
+/// Registra no espelho o erro de um arquivo
+pub fn registrar_erro(config_dir: &Path, session_id: &str, erro: String) -> Result<()> {
+    alterar_persistidas(config_dir, |sessoes| {
+        if let Some(sessao) = sessoes.get_mut(session_id) {
+            sessao.errors.push(erro);
+        }
+    })
+}
+
+/// Marca a sessão como finalizada; concluída sem cancelamento, as propostas parciais não são mais necessárias
+pub fn finalizar_persistida(config_dir: &Path, session_id: &str, cancelled: bool) -> Result<()> {
+    alterar_persistidas(config_dir, |sessoes| {
+        if let Some(sessao) = sessoes.get_mut(session_id) {
+            sessao.finished_at = Some(Utc::now().to_rfc3339());
+            sessao.cancelled = cancelled;
+        }
+    })?;
+
+    if !cancelled {
+        let _ = fs::remove_file(caminho_parcial(config_dir, session_id));
+    }
+    Ok(())
+}
+
+/// Marca no espelho as sessões como interrompidas (canceladas, com `motivo` como erro)
+///
+/// As propostas parciais são mantidas para que as sessões possam ser retomadas.
+pub fn interromper_persistidas(config_dir: &Path, session_ids: &[String], motivo: &str) -> Result<()> {
+    if session_ids.is_empty() {
+        return Ok(());
+    }
+
+    alterar_persistidas(config_dir, |sessoes| {
+        for session_id in session_ids {
+            if let Some(sessao) = sessoes.get_mut(session_id) {
+                sessao.cancelled = true;
+                sessao.finished_at.get_or_insert_with(|| Utc::now().to_rfc3339());
+                if !sessao.errors.iter().any(|erro| erro == motivo) {
+                    sessao.errors.push(motivo.to_string());
+                }
+            }
+        }
+    })
+}
+
+/// Arquivos concluídos e propostas já extraídas numa sessão interrompida
+///
+/// Uma última linha truncada (app fechado durante a gravação) é ignorada, e o PDF
+/// correspondente volta a ser processado.
+pub fn carregar_parcial(config_dir: &Path, session_id: &str) -> Result<(Vec<CompletedFile>, Vec<PropostaConsolidada>)> {
+    let parcial = caminho_parcial(config_dir, session_id);
+    if !parcial.exists() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+
+    let content = fs::read_to_string(&parcial)
+        .context(format!("Erro ao ler {}", parcial.display()))?;
+    let mut concluidos = Vec::new();
+    let mut propostas = Vec::new();
+    for linha in content.lines().filter_map(|l| serde_json::from_str::<LinhaParcial>(l).ok()) {
+        concluidos.push(linha.arquivo);
+        propostas.extend(linha.propostas);
+    }
+
+    Ok((concluidos, propostas))
+}
+
+/// Remove da lista os PDFs já concluídos e não modificados desde então
+pub fn arquivos_pendentes(arquivos: Vec<PathBuf>, concluidos: &[CompletedFile]) -> Vec<PathBuf> {
+    arquivos.into_iter()
+        .filter(|path| {
+            let caminho = path.to_string_lossy();
+            let modificado = modificado_ms(path);
+            !concluidos.iter().any(|c| c.path == caminho && c.modified_ms == modificado)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sessao(is_processing: bool, inicio: DateTime<Utc>, fim: Option<DateTime<Utc>>) -> ProcessingStatus {
+        ProcessingStatus {
+            is_processing,
+            current_file: None,
+            processed_files: 0,
+            total_files: 1,
+            errors: Vec::new(),
+            progress_percentage: 0.0,
+            cancelled: false,
+            cancel_flag: Default::default(),
+            propostas: Default::default(),
+            started_at: inicio.to_rfc3339(),
+            finished_at: fim.map(|f| f.to_rfc3339()),
+            resultado: None,
+        }
+    }
+
+    #[test]
+    fn test_remove_apenas_sessoes_finalizadas_expiradas() {
+        let agora = Utc::now();
+        let mut sessoes = HashMap::new();
+        sessoes.insert("antiga".to_string(), sessao(false, agora - Duration::hours(2), Some(agora - Duration::minutes(90))));
+        sessoes.insert("recente".to_string(), sessao(false, agora - Duration::minutes(10), Some(agora - Duration::minutes(5))));
+        sessoes.insert("em_andamento".to_string(), sessao(true, agora - Duration::hours(3), None));
+
+        assert_eq!(remover_expiradas(&mut sessoes, 30, agora), 1);
+        assert!(!sessoes.contains_key("antiga"));
+
+        let lista = listar(&sessoes, &BTreeMap::new());
+        let ids: Vec<&str> = lista.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, vec!["recente", "em_andamento"]);
+
+        // Retenção zero descarta toda sessão já finalizada
+        assert_eq!(remover_expiradas(&mut sessoes, 0, agora), 1);
+        assert!(sessoes.contains_key("em_andamento"));
+    }
+
+    fn proposta(item: &str) -> PropostaConsolidada {
+        serde_json::from_value(serde_json::json!({
+            "uasg": "787000", "pregao": "90008/2024", "processo": "123", "item": item, "grupo": null,
+            "quantidade": "1", "descricao": "Caneta", "valor_estimado": "1,00", "valor_adjudicado": "1,00",
+            "fornecedor": "EMPRESA LTDA", "cnpj": "11.222.333/0001-81", "marca_fabricante": "N/A",
+            "modelo_versao": "N/A", "responsavel": "N/A", "melhor_lance": "1,00", "tipo_formato": "individual"
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_sessao_persistida_retoma_sem_repetir_arquivos() {
+        let dir = std::env::temp_dir().join(format!("licitacao360_sessoes_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let pdfs = dir.join("PDFs");
+        fs::create_dir_all(&pdfs).unwrap();
+        let arquivos: Vec<PathBuf> = ["a.pdf", "b.pdf", "c.pdf"].iter().map(|n| pdfs.join(n)).collect();
+        for arquivo in &arquivos {
+            fs::write(arquivo, b"%PDF").unwrap();
+        }
+
+        persistir_sessao(&dir, PersistedSession {
+            session_id: "lote/1".to_string(),
+            input_dir: pdfs.to_string_lossy().to_string(),
+            output_dir: dir.join("Resultados").to_string_lossy().to_string(),
+            verbose: false,
+            total_files: 3,
+            started_at: Utc::now().to_rfc3339(),
+            finished_at: None,
+            cancelled: false,
+            completed_files: Vec::new(),
+            errors: Vec::new(),
+            varredura: Default::default(),
+        }, 30).unwrap();
+        registrar_concluido(&dir, "lote/1", &arquivos[0], &[proposta("1"), proposta("2")]).unwrap();
+        registrar_concluido(&dir, "lote/1", &arquivos[1], &[proposta("3")]).unwrap();
+
+        // App fechado durante a gravação da terceira linha
+        let parcial = caminho_parcial(&dir, "lote/1");
+        assert!(parcial.starts_with(dir.join(PASTA_PARCIAIS)));
+        let mut file = OpenOptions::new().append(true).open(&parcial).unwrap();
+        write!(file, "{{\"path\": \"c.pdf\", \"propo").unwrap();
+
+        let persistida = &carregar_persistidas(&dir).unwrap()["lote/1"];
+        assert_eq!(persistida.completed_files.len(), 2);
+        assert!(persistida.finished_at.is_none());
+
+        let (concluidos, propostas) = carregar_parcial(&dir, "lote/1").unwrap();
+        assert_eq!(propostas.len(), 3);
+        assert_eq!(arquivos_pendentes(arquivos.clone(), &concluidos), vec![arquivos[2].clone()]);
+
+        // PDF substituído depois de processado volta para a fila
+        let mut modificado = concluidos.clone();
+        modificado[0].modified_ms = modificado[0].modified_ms.map(|ms| ms - 1000);
+        assert_eq!(arquivos_pendentes(arquivos.clone(), &modificado).len(), 2);
+
+        finalizar_persistida(&dir, "lote/1", false).unwrap();
+        assert!(!parcial.exists());
+        assert!(carregar_persistidas(&dir).unwrap()["lote/1"].finished_at.is_some());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_interromper_sessoes_ativas_no_encerramento() {
+        let dir = std::env::temp_dir().join(format!("licitacao360_sessoes_encerradas_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let agora = Utc::now();
+        let mut sessoes = HashMap::new();
+        sessoes.insert("ativa".to_string(), sessao(true, agora, None));
+        sessoes.insert("concluida".to_string(), sessao(false, agora, Some(agora)));
+
+        persistir_sessao(&dir, PersistedSession {
+            session_id: "ativa".to_string(),
+            input_dir: String::new(),
+            output_dir: String::new(),
+            verbose: false,
+            total_files: 1,
+            started_at: agora.to_rfc3339(),
+            finished_at: None,
+            cancelled: false,
+            completed_files: Vec::new(),
+            errors: Vec::new(),
+            varredura: Default::default(),
+        }, 30).unwrap();
+
+        let ids = interromper_ativas(&mut sessoes, ERRO_APLICACAO_ENCERRADA);
+        assert_eq!(ids, vec!["ativa".to_string()]);
+        let ativa = &sessoes["ativa"];
+        assert!(!ativa.is_processing && ativa.cancelled && ativa.finished_at.is_some());
+        assert!(ativa.cancel_flag.load(Ordering::SeqCst));
+        assert_eq!(ativa.errors, vec![ERRO_APLICACAO_ENCERRADA.to_string()]);
+        assert!(sessoes["concluida"].errors.is_empty());
+
+        // Repetir não duplica o erro no espelho
+        interromper_persistidas(&dir, &ids, ERRO_APLICACAO_ENCERRADA).unwrap();
+        interromper_persistidas(&dir, &ids, ERRO_APLICACAO_ENCERRADA).unwrap();
+        let persistida = &carregar_persistidas(&dir).unwrap()["ativa"];
+        assert!(persistida.cancelled && persistida.finished_at.is_some());
+        assert_eq!(persistida.errors.len(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_estado_envenenado_continua_respondendo() {
+        let state = std::sync::Arc::new(Mutex::new(HashMap::new()));
+        state.lock().unwrap().insert("a".to_string(), sessao(true, Utc::now(), None));
+
+        // Panic com o lock tomado, como numa região crítica que estoura
+        let envenenador = state.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = envenenador.lock().unwrap();
+            panic!("JSON gigante");
+        }).join();
+        assert!(state.is_poisoned());
+
+        let ids = with_state(&state, |sessoes| sessoes.keys().cloned().collect::<Vec<_>>()).unwrap();
+        assert_eq!(ids, ["a"]);
+        assert!(!state.is_poisoned());
+
+        // Panic dentro do acesso vira erro e não envenena de novo
+        let erro = with_state(&state, |_| -> usize { panic!("falha no acesso") }).unwrap_err();
+        assert!(erro.message.contains("falha no acesso"), "{}", erro.message);
+        assert!(!state.is_poisoned());
+        assert_eq!(with_state(&state, cancelar_ativas).unwrap(), ["a"]);
+    }
+
+    #[test]
+    fn test_conclusao_guarda_resultado_e_gera_evento() {
+        let agora = Utc::now();
+        let mut sessoes = HashMap::new();
+        sessoes.insert("ok".to_string(), sessao(true, agora, None));
+        sessoes.insert("falha".to_string(), ProcessingStatus { processed_files: 2, ..sessao(true, agora, None) });
+
+        // Em andamento ainda não há resultado; sessão desconhecida é outro erro
+        assert_eq!(consultar_resultado(&sessoes, "ok").unwrap_err().message_key, Some(MsgKey::SessionInProgress));
+        assert_eq!(consultar_resultado(&sessoes, "outra").unwrap_err().message_key, Some(MsgKey::SessionNotFound));
+
+        let sucesso: ProcessingResult = serde_json::from_value(serde_json::json!({
+            "success": true, "message": "3 propostas", "propostas": [], "total_processed": 1,
+            "json_file_path": null, "session_id": "ok"
+        })).unwrap();
+        let evento = concluir(&mut sessoes, "ok", Ok(sucesso), Vec::new());
+        assert!(evento.success);
+        assert_eq!(evento.message, "3 propostas");
+        assert!(!sessoes["ok"].is_processing);
+        assert_eq!(consultar_resultado(&sessoes, "ok").unwrap().message, "3 propostas");
+
+        // Falha (inclusive panic na thread de bloqueio) também finaliza a sessão e gera o evento
+        let evento = concluir(&mut sessoes, "falha", Err(TauriError::system("tarefa terminou inesperadamente", None)), vec!["a.pdf".to_string()]);
+        assert!(!evento.success);
+        assert_eq!(evento.message, "tarefa terminou inesperadamente");
+        assert_eq!((evento.session_id.as_deref(), evento.total_processed), (Some("falha"), 2));
+        assert_eq!(evento.skipped_files, ["a.pdf"]);
+        assert!(!sessoes["falha"].is_processing && sessoes["falha"].finished_at.is_some());
+        assert_eq!(consultar_resultado(&sessoes, "falha").unwrap_err().message, "tarefa terminou inesperadamente");
+
+        // Sem a sessão no estado o evento sai do mesmo jeito
+        assert_eq!(concluir(&mut sessoes, "removida", Err(TauriError::system("erro", None)), Vec::new()).session_id.as_deref(), Some("removida"));
+    }
+}
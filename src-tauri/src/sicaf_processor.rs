@@ -1,14 +1,59 @@
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{Local, NaiveDate, NaiveDateTime, Utc};
+use once_cell::sync::Lazy;
 use regex::Regex;
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{debug, info, warn};
 use walkdir::WalkDir;
 use pdf_extract::extract_text;
-use crate::types::{SicafData, ProcessingSicafResult, PropostaConsolidada};
+use crate::types::{formatar_valor, DadosCnpjOnline, EstatisticasSicaf, OcorrenciaSicaf, SicafData, ProcessingSicafResult, PropostaConsolidada, VerificacaoSicaf, FONTE_CNPJ_OFFLINE};
+use crate::consulta_cnpj;
+use crate::exportacao;
+use crate::integridade;
+use crate::metadados_pdf;
+use crate::migracoes;
+use crate::paths;
+use crate::resultados;
+use crate::validators;
+use crate::storage;
+use crate::texto;
+
+/// Evento enviado ao callback de progresso do processamento SICAF
+#[derive(Debug, Clone)]
+pub enum EventoProgressoSicaf {
+    /// O arquivo começou a ser processado
+    Iniciado(String),
+    /// Os dados SICAF do arquivo foram extraídos
+    Concluido(String),
+    /// O arquivo foi lido, mas não tem dados SICAF reconhecíveis
+    SemDados(String),
+    /// O processamento do arquivo falhou (arquivo, erro); os demais continuam
+    Falhou(String, String),
+    /// O PDF exige senha e não foi lido; os demais continuam
+    Protegido(String),
+}
 
 /// Processa todos os arquivos PDF SICAF de um diretório
-pub fn processar_sicaf_pdfs(sicaf_dir: &Path, verbose: bool) -> Result<ProcessingSicafResult> {
+pub fn processar_sicaf_pdfs(sicaf_dir: &Path) -> Result<ProcessingSicafResult> {
+    processar_sicaf_pdfs_com_progresso(sicaf_dir, &AtomicBool::new(false), |_, _, _| {})
+}
+
+/// Processa os PDFs SICAF de um diretório, informando o progresso a cada arquivo
+///
+/// O callback recebe o número de arquivos já concluídos, o total e o evento. O sinal
+/// `cancelado` é verificado antes de cada arquivo; ao ser acionado, os restantes são
+/// ignorados e os dados extraídos até o momento são devolvidos.
+pub fn processar_sicaf_pdfs_com_progresso<F>(
+    sicaf_dir: &Path,
+    cancelado: &AtomicBool,
+    mut progress_callback: F,
+) -> Result<ProcessingSicafResult>
+where
+    F: FnMut(usize, usize, EventoProgressoSicaf),
+{
     if !sicaf_dir.exists() {
         return Err(anyhow::anyhow!("Diretório SICAF não encontrado: {}", sicaf_dir.display()));
     }
@@ -21,7 +66,7 @@ pub fn processar_sicaf_pdfs(sicaf_dir: &Path, verbose: bool) -> Result<Processin
         .into_iter()
         .filter_map(|e| e.ok())
         .filter(|e| e.file_type().is_file())
-        .filter(|e| e.path().extension().map_or(false, |ext| ext == "pdf"))
+        .filter(|e| paths::is_pdf_file(e.path()))
         .collect();
 
     if pdf_files.is_empty() {
@@ -31,80 +76,190 @@ pub fn processar_sicaf_pdfs(sicaf_dir: &Path, verbose: bool) -> Result<Processin
             processed_count: 0,
             sicaf_data: Vec::new(),
             session_id: None,
+            adicionados: 0,
+            atualizados: 0,
+            mantidos: 0,
         });
     }
 
+    let total = pdf_files.len();
+    let mut concluidos = 0;
+    let mut protegidos = 0;
     for entry in pdf_files {
-        if verbose {
-            println!("Processando arquivo SICAF: {:?}", entry.path());
+        if cancelado.load(Ordering::SeqCst) {
+            break;
         }
 
-        match processar_pdf_sicaf(entry.path(), verbose) {
+        let arquivo = entry.path().to_string_lossy().to_string();
+        progress_callback(concluidos, total, EventoProgressoSicaf::Iniciado(arquivo.clone()));
+
+        let evento = match processar_pdf_sicaf(entry.path()) {
             Ok(Some(sicaf_data)) => {
                 sicaf_data_list.push(sicaf_data);
                 processed_count += 1;
-                if verbose {
-                    println!("✓ Arquivo processado com sucesso: {:?}", entry.path());
-                }
+                EventoProgressoSicaf::Concluido(arquivo)
             }
             Ok(None) => {
-                if verbose {
-                    println!("⚠ Dados SICAF não encontrados no arquivo: {:?}", entry.path());
-                }
+                info!(arquivo = %entry.path().display(), "Dados SICAF não encontrados no arquivo");
+                EventoProgressoSicaf::SemDados(arquivo)
+            }
+            Err(e) if metadados_pdf::eh_pdf_protegido(&e) => {
+                warn!("{}", e);
+                protegidos += 1;
+                EventoProgressoSicaf::Protegido(arquivo)
             }
             Err(e) => {
-                eprintln!("✗ Erro ao processar {:?}: {}", entry.path(), e);
+                warn!(arquivo = %entry.path().display(), "Erro ao processar: {:#}", e);
+                EventoProgressoSicaf::Falhou(arquivo, format!("{:#}", e))
             }
-        }
+        };
+        concluidos += 1;
+        progress_callback(concluidos, total, evento);
+    }
+
+    let mut message = if concluidos < total {
+        format!("Processamento cancelado: {} de {} arquivos lidos, {} com dados SICAF", concluidos, total, processed_count)
+    } else {
+        format!("Processamento concluído: {} arquivos processados", processed_count)
+    };
+    if protegidos > 0 {
+        message.push_str(&format!(", {} arquivos protegidos por senha", protegidos));
     }
 
     Ok(ProcessingSicafResult {
         success: true,
-        message: format!("Processamento concluído: {} arquivos processados", processed_count),
+        message,
         processed_count,
         sicaf_data: sicaf_data_list,
         session_id: Some(format!("sicaf_{}", Utc::now().timestamp_millis())),
+        adicionados: 0,
+        atualizados: 0,
+        mantidos: 0,
     })
 }
 
+/// Caracteres mostrados depois de "CNPJ:" quando o layout do relatório não é reconhecido
+pub const TAMANHO_TRECHO_DIAGNOSTICO: usize = 500;
+
+/// Resultado da leitura de um PDF SICAF
+#[derive(Debug)]
+pub enum LeituraSicaf {
+    Extraido(Box<SicafData>),
+    /// O texto foi lido, mas não segue o layout esperado; `trecho` vem de `trecho_diagnostico`
+    NaoReconhecido { trecho: String },
+}
+
+/// Trecho do texto a partir de "CNPJ:" (ou do início, sem ele), para diagnosticar layouts novos
+pub fn trecho_diagnostico(texto: &str) -> String {
+    let inicio = texto.find("CNPJ:").map_or(0, |i| i + "CNPJ:".len());
+    texto[inicio..].trim_start().chars().take(TAMANHO_TRECHO_DIAGNOSTICO).collect()
+}
+
 /// Processa um único arquivo PDF SICAF
-fn processar_pdf_sicaf(pdf_path: &Path, verbose: bool) -> Result<Option<SicafData>> {
+fn processar_pdf_sicaf(pdf_path: &Path) -> Result<Option<SicafData>> {
+    match ler_pdf_sicaf(pdf_path)? {
+        LeituraSicaf::Extraido(sicaf_data) => Ok(Some(*sicaf_data)),
+        LeituraSicaf::NaoReconhecido { .. } => Ok(None),
+    }
+}
+
+/// Lê um PDF SICAF, devolvendo o trecho do texto quando o layout não é reconhecido
+#[tracing::instrument(name = "sicaf", skip_all, fields(arquivo = %pdf_path.display()))]
+pub fn ler_pdf_sicaf(pdf_path: &Path) -> Result<LeituraSicaf> {
+    info!("Processando arquivo SICAF");
     // Extrair texto do PDF
-    let text = extract_text(pdf_path)?;
+    let extraido = extract_text(pdf_path)
+        .map_err(|e| metadados_pdf::classificar_erro_extracao(pdf_path, e.into()))?;
+    let text = texto::normalizar_texto(&extraido);
     
-    if verbose {
-        println!("📝 Texto extraído do SICAF: {} caracteres", text.len());
+    debug!(caracteres = text.len(), "Texto extraído");
+
+    let Some(mut sicaf_data) = dados_sicaf_do_texto(&text) else {
+        return Ok(LeituraSicaf::NaoReconhecido { trecho: trecho_diagnostico(&text) });
+    };
+    sicaf_data.arquivo_modificado_em = fs::metadata(pdf_path)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(|modificado| chrono::DateTime::<Utc>::from(modificado).to_rfc3339());
+
+    let hoje = Local::now().date_naive();
+    sicaf_data.cadastro_vencido = sicaf_data.data_vencimento.as_deref()
+        .and_then(|data| dias_para_vencer(data, hoje))
+        .map(|dias| dias < 0);
+
+    debug!(cnpj = %sicaf_data.cnpj, empresa = %sicaf_data.empresa, "Dados SICAF extraídos");
+    if !sicaf_data.cnpj_valido {
+        warn!("CNPJ com dígitos verificadores inválidos: {}", sicaf_data.cnpj);
+    }
+    if sicaf_data.cadastro_vencido == Some(true) {
+        warn!("Cadastro SICAF vencido em {}", sicaf_data.data_vencimento.as_deref().unwrap_or("N/A"));
     }
+    if !sicaf_data.ocorrencias.is_empty() {
+        warn!("{} ocorrência(s) registrada(s) no SICAF", sicaf_data.ocorrencias.len());
+    }
+
+    Ok(LeituraSicaf::Extraido(Box::new(sicaf_data)))
+}
 
+/// Dados SICAF de um texto já normalizado, sem os campos que dependem do arquivo
+pub(crate) fn dados_sicaf_do_texto(text: &str) -> Option<SicafData> {
     // Extrair dados principais do SICAF
-    let mut sicaf_data = match extrair_dados_sicaf(&text) {
-        Some(data) => data,
-        None => return Ok(None),
-    };
+    let mut sicaf_data = extrair_dados_sicaf(text)?;
 
     // Extrair dados do responsável legal
-    if let Some(responsavel_data) = extrair_dados_responsavel(&text) {
+    if let Some(responsavel_data) = extrair_dados_responsavel(text) {
         sicaf_data.cpf_responsavel = Some(responsavel_data.cpf);
         sicaf_data.nome_responsavel = Some(responsavel_data.nome);
     }
 
-    if verbose {
-        println!("✅ Dados SICAF extraídos - CNPJ: {}, Empresa: {}", sicaf_data.cnpj, sicaf_data.empresa);
+    sicaf_data.niveis = extrair_niveis(text);
+    sicaf_data.ocorrencias = extrair_ocorrencias(text);
+    sicaf_data.data_emissao = extrair_data_emissao(text);
+    Some(sicaf_data)
+}
+
+/// Copia um PDF escolhido fora da pasta SICAF para dentro dela, sem sobrescrever outro arquivo
+///
+/// Um arquivo que já está na pasta (ou uma cópia idêntica com o mesmo nome) não é copiado
+/// de novo; o caminho dele é devolvido.
+pub fn copiar_para_pasta_sicaf(pdf_path: &Path, sicaf_dir: &Path) -> Result<PathBuf> {
+    fs::create_dir_all(sicaf_dir)
+        .context(format!("Erro ao criar pasta {}", sicaf_dir.display()))?;
+    let pasta = sicaf_dir.canonicalize().unwrap_or_else(|_| sicaf_dir.to_path_buf());
+    let origem = pdf_path.canonicalize().unwrap_or_else(|_| pdf_path.to_path_buf());
+    if origem.parent() == Some(pasta.as_path()) {
+        return Ok(origem);
+    }
+
+    let mesmo_nome = pasta.join(pdf_path.file_name().unwrap_or_default());
+    if mesmo_nome.exists() && integridade::sha256_arquivo(&mesmo_nome).ok() == Some(integridade::sha256_arquivo(pdf_path)?) {
+        return Ok(mesmo_nome);
     }
 
-    Ok(Some(sicaf_data))
+    let destino = paths::caminho_sem_colisao(&pasta, pdf_path.file_name().unwrap_or_default());
+    fs::copy(pdf_path, &destino)
+        .context(format!("Erro ao copiar {} para {}", pdf_path.display(), pasta.display()))?;
+    Ok(destino)
 }
 
 /// Extrai dados principais do SICAF usando regex
-fn extrair_dados_sicaf(texto: &str) -> Option<SicafData> {
-    // Padrão regex baseado no exemplo Python
-    let dados_sicaf_pattern = r"(?s)CNPJ:\s*(?P<cnpj>[\d./-]+)\s*(?:DUNS®:\s*(?P<duns>[\d]+)\s*)?Razão Social:\s*(?P<empresa>.*?)\s*Nome Fantasia:\s*(?P<nome_fantasia>.*?)\s*Situação do Fornecedor:\s*(?P<situacao_cadastro>.*?)\s*Data de Vencimento do Cadastro:\s*(?P<data_vencimento>\d{2}/\d{2}/\d{4})\s*Dados do Nível.*?Dados para Contato\s*CEP:\s*(?P<cep>[\d.-]+)\s*Endereço:\s*(?P<endereco>.*?)\s*Município\s*/\s*UF:\s*(?P<municipio>.*?)\s*/\s*(?P<uf>.*?)\s*Telefone:\s*(?P<telefone>.*?)\s*E-mail:\s*(?P<email>.*?)\s*Dados do Responsável Legal";
+// Padrão regex baseado no exemplo Python
+pub(crate) static RE_DADOS_SICAF: Lazy<Regex> = Lazy::new(|| Regex::new(
+    r"(?s)CNPJ:\s*(?P<cnpj>[\d./-]+)\s*(?:DUNS®?:\s*(?P<duns>[\d]+)\s*)?Raz[ãa]o Social:\s*(?P<empresa>.*?)\s*Nome Fantasia:\s*(?P<nome_fantasia>.*?)\s*Situa[çc][ãa]o do Fornecedor:\s*(?P<situacao_cadastro>.*?)\s*Data de Vencimento do Cadastro:\s*(?P<data_vencimento>\d{2}/\d{2}/\d{4})\s*Dados do N[íi]vel.*?Dados para Contato\s*CEP:\s*(?P<cep>[\d.-]+)\s*Endere[çc]o:\s*(?P<endereco>.*?)\s*Munic[íi]pio\s*/\s*UF:\s*(?P<municipio>.*?)\s*/\s*(?P<uf>.*?)\s*Telefone:\s*(?P<telefone>.*?)\s*E-mail:\s*(?P<email>.*?)\s*Dados do Respons[áa]vel Legal"
+).unwrap());
 
-    let re = Regex::new(dados_sicaf_pattern).ok()?;
-    
-    if let Some(caps) = re.captures(texto) {
+fn extrair_dados_sicaf(texto: &str) -> Option<SicafData> {
+    if let Some(caps) = RE_DADOS_SICAF.captures(texto) {
+        let cnpj = caps.name("cnpj")?.as_str().trim().to_string();
+        let telefone_raw = caps.name("telefone").map_or("", |m| m.as_str());
+        let email_raw = caps.name("email").map_or("", |m| m.as_str());
+        let emails = validators::extrair_emails(email_raw);
+        let contato_raw = caps.name("telefone").zip(caps.name("email"))
+            .map(|(inicio, fim)| texto[inicio.start()..fim.end()].trim().to_string())
+            .filter(|s| !s.is_empty());
         Some(SicafData {
-            cnpj: caps.name("cnpj")?.as_str().trim().to_string(),
+            cnpj_valido: validators::validar_cnpj(&cnpj),
+            cnpj,
             duns: caps.name("duns").map(|m| m.as_str().trim().to_string()),
             empresa: caps.name("empresa")?.as_str().trim().to_string(),
             nome_fantasia: caps.name("nome_fantasia")
@@ -128,14 +283,17 @@ fn extrair_dados_sicaf(texto: &str) -> Option<SicafData> {
             uf: caps.name("uf")
                 .map(|m| m.as_str().trim().to_string())
                 .filter(|s| !s.is_empty()),
-            telefone: caps.name("telefone")
-                .map(|m| m.as_str().trim().to_string())
-                .filter(|s| !s.is_empty()),
-            email: caps.name("email")
-                .map(|m| m.as_str().trim().to_string())
-                .filter(|s| !s.is_empty()),
+            telefone: validators::normalizar_telefone(telefone_raw),
+            email: emails.first().cloned(),
+            emails,
+            contato_raw,
             cpf_responsavel: None,
             nome_responsavel: None,
+            cadastro_vencido: None,
+            niveis: Vec::new(),
+            ocorrencias: Vec::new(),
+            data_emissao: None,
+            arquivo_modificado_em: None,
         })
     } else {
         None
@@ -149,12 +307,12 @@ struct ResponsavelData {
 }
 
 /// Extrai dados do responsável legal usando regex
-fn extrair_dados_responsavel(texto: &str) -> Option<ResponsavelData> {
-    let dados_responsavel_pattern = r"(?s)Dados do Responsável Legal\s*CPF:\s*(?P<cpf>\d{3}\.\d{3}\.\d{3}-\d{2})\s*Nome:\s*(?P<nome>[^\n\r]*?)(?:\s*Dados do Responsável pelo Cadastro|\s*Emitido em:|\s*CPF:|$)";
+pub(crate) static RE_RESPONSAVEL_LEGAL: Lazy<Regex> = Lazy::new(|| Regex::new(
+    r"(?s)Dados do Respons[áa]vel Legal\s*CPF:\s*(?P<cpf>\d{3}\.\d{3}\.\d{3}-\d{2})\s*Nome:\s*(?P<nome>[^\n\r]*?)(?:\s*Dados do Respons[áa]vel pelo Cadastro|\s*Emitido em:|\s*CPF:|$)"
+).unwrap());
 
-    let re = Regex::new(dados_responsavel_pattern).ok()?;
-    
-    if let Some(caps) = re.captures(texto) {
+fn extrair_dados_responsavel(texto: &str) -> Option<ResponsavelData> {
+    if let Some(caps) = RE_RESPONSAVEL_LEGAL.captures(texto) {
         Some(ResponsavelData {
             cpf: caps.name("cpf")?.as_str().trim().to_string(),
             nome: caps.name("nome")?.as_str().trim().to_string(),
@@ -164,25 +322,204 @@ fn extrair_dados_responsavel(texto: &str) -> Option<ResponsavelData> {
     }
 }
 
+pub(crate) static RE_SECAO_NIVEIS: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)N[íi]veis\s+cadastrados:?|Dados\s+do\s+N[íi]vel").unwrap());
+
+static RE_FIM_SECAO_NIVEIS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)Dados\s+para\s+Contato|Ocorr[êe]ncias|Dados\s+do\s+Respons[áa]vel").unwrap()
+});
+
+static RE_NIVEL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(?:N[íi]vel\s+)?(?P<nivel>[IVX]+|\d+)\s*-\s*(?P<descricao>\S.*?)\s*$").unwrap()
+});
+
+/// Extrai os níveis cadastrados ("I - Credenciamento", "II - Habilitação Jurídica"...)
+fn extrair_niveis(texto: &str) -> Vec<String> {
+    let Some(inicio) = RE_SECAO_NIVEIS.find(texto) else {
+        return Vec::new();
+    };
+    let secao = &texto[inicio.end()..];
+    let secao = RE_FIM_SECAO_NIVEIS.find(secao).map_or(secao, |fim| &secao[..fim.start()]);
+
+    let mut niveis: Vec<String> = Vec::new();
+    for caps in secao.lines().filter_map(|linha| RE_NIVEL.captures(linha)) {
+        let nivel = format!("{} - {}", &caps["nivel"], &caps["descricao"]);
+        if !niveis.contains(&nivel) {
+            niveis.push(nivel);
+        }
+    }
+    niveis
+}
+
+pub(crate) static RE_SECAO_OCORRENCIAS: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?im)^\s*Ocorr[êe]ncias\b.*$").unwrap());
+
+static RE_FIM_SECAO_OCORRENCIAS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)Dados\s+do\s+Respons[áa]vel|Emitido\s+em:").unwrap()
+});
+
+static RE_OCORRENCIA: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)Ocorr[êe]ncia:\s*").unwrap());
+
+static RE_ORGAO: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)[ÓO]rg[ãa]o(?:\s+Sancionador)?:\s*(?P<valor>[^\n]*)").unwrap());
+
+static RE_DATA_INICIO: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)Data\s+(?:de\s+)?In[íi]cio:\s*(?P<valor>\d{2}/\d{2}/\d{4})").unwrap());
+
+static RE_DATA_FIM: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)Data\s+(?:de\s+)?Fim:\s*(?P<valor>\d{2}/\d{2}/\d{4})").unwrap());
+
+/// Extrai as ocorrências (impedimentos e sanções) da seção "Ocorrências"
+///
+/// Cada ocorrência começa em "Ocorrência:" e vai até a próxima; órgão e datas são opcionais.
+fn extrair_ocorrencias(texto: &str) -> Vec<OcorrenciaSicaf> {
+    let Some(inicio) = RE_SECAO_OCORRENCIAS.find(texto) else {
+        return Vec::new();
+    };
+    let secao = &texto[inicio.end()..];
+    let secao = RE_FIM_SECAO_OCORRENCIAS.find(secao).map_or(secao, |fim| &secao[..fim.start()]);
+
+    let inicios: Vec<_> = RE_OCORRENCIA.find_iter(secao).collect();
+    inicios.iter().enumerate().filter_map(|(posicao, marcador)| {
+        let fim = inicios.get(posicao + 1).map_or(secao.len(), |proximo| proximo.start());
+        let bloco = &secao[marcador.end()..fim];
+        let descricao = bloco.lines().next().unwrap_or_default().trim();
+        if descricao.is_empty() {
+            return None;
+        }
+
+        let campo = |re: &Regex| re.captures(bloco)
+            .map(|caps| caps["valor"].trim().to_string())
+            .filter(|valor| !valor.is_empty());
+        Some(OcorrenciaSicaf {
+            descricao: descricao.to_string(),
+            orgao: campo(&RE_ORGAO),
+            data_inicio: campo(&RE_DATA_INICIO),
+            data_fim: campo(&RE_DATA_FIM),
+        })
+    }).collect()
+}
+
+/// Ocorrência que impede o fornecedor de contratar e ainda está em vigor em `hoje`
+pub fn ocorrencia_impeditiva_vigente(ocorrencia: &OcorrenciaSicaf, hoje: NaiveDate) -> bool {
+    let descricao = ocorrencia.descricao.to_lowercase();
+    let impeditiva = ["impedimento", "suspensão", "suspensao", "inidoneidade", "inidôneo", "proibição", "proibicao"]
+        .iter()
+        .any(|termo| descricao.contains(termo));
+
+    let data = |valor: &Option<String>| valor.as_deref().and_then(|d| NaiveDate::parse_from_str(d, "%d/%m/%Y").ok());
+    let futura = data(&ocorrencia.data_inicio).is_some_and(|inicio| inicio > hoje);
+    let encerrada = data(&ocorrencia.data_fim).is_some_and(|fim| fim < hoje);
+
+    impeditiva && !futura && !encerrada
+}
+
+pub(crate) static RE_EMISSAO: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)Emitido\s+em:?\s*(?P<data>\d{2}/\d{2}/\d{4})(?:\s*(?:às|as|-)?\s*(?P<hora>\d{2}:\d{2}(?::\d{2})?))?").unwrap()
+});
+
+/// Extrai a data de emissão do relatório ("Emitido em: 15/01/2024 10:32")
+fn extrair_data_emissao(texto: &str) -> Option<String> {
+    let caps = RE_EMISSAO.captures(texto)?;
+    Some(match caps.name("hora") {
+        Some(hora) => format!("{} {}", &caps["data"], hora.as_str()),
+        None => caps["data"].to_string(),
+    })
+}
+
+/// Momento do relatório: a data de emissão, se interpretável, senão a data de modificação do PDF
+fn momento_do_relatorio(dados: &SicafData) -> Option<NaiveDateTime> {
+    let emissao = dados.data_emissao.as_deref().and_then(|data| {
+        ["%d/%m/%Y %H:%M:%S", "%d/%m/%Y %H:%M"].iter()
+            .find_map(|formato| NaiveDateTime::parse_from_str(data, formato).ok())
+            .or_else(|| NaiveDate::parse_from_str(data, "%d/%m/%Y").ok().and_then(|d| d.and_hms_opt(0, 0, 0)))
+    });
+    emissao.or_else(|| {
+        let modificado = chrono::DateTime::parse_from_rfc3339(dados.arquivo_modificado_em.as_deref()?).ok()?;
+        Some(modificado.with_timezone(&Local).naive_local())
+    })
+}
+
+/// CNPJ só com os dígitos, para comparar registros com e sem formatação
+fn normalizar_cnpj(cnpj: &str) -> String {
+    cnpj.replace(".", "").replace("/", "").replace("-", "")
+}
+
+/// Resultado da mesclagem dos registros SICAF novos com os existentes
+#[derive(Debug, Clone, Default)]
+pub struct MesclagemSicaf {
+    pub registros: Vec<SicafData>,
+    pub adicionados: usize,
+    pub atualizados: usize,
+    pub mantidos: usize,
+}
+
+/// Mescla registros por CNPJ: o relatório mais recente vence, e em caso de empate o novo
+///
+/// Os existentes mantêm a ordem do arquivo; CNPJs novos entram no final.
+pub fn mesclar_sicaf(existentes: Vec<SicafData>, novos: Vec<SicafData>) -> MesclagemSicaf {
+    let mais_recente = |novo: &SicafData, atual: &SicafData| momento_do_relatorio(novo) >= momento_do_relatorio(atual);
+
+    // Mais de um PDF do mesmo CNPJ na mesma rodada: fica o mais recente
+    let mut novos_por_cnpj: Vec<SicafData> = Vec::new();
+    for novo in novos {
+        match novos_por_cnpj.iter_mut().find(|atual| normalizar_cnpj(&atual.cnpj) == normalizar_cnpj(&novo.cnpj)) {
+            Some(atual) if mais_recente(&novo, atual) => *atual = novo,
+            Some(_) => {}
+            None => novos_por_cnpj.push(novo),
+        }
+    }
+
+    let mut mesclagem = MesclagemSicaf { registros: existentes, ..Default::default() };
+    let total_existentes = mesclagem.registros.len();
+    for novo in novos_por_cnpj {
+        let cnpj = normalizar_cnpj(&novo.cnpj);
+        match mesclagem.registros.iter_mut().find(|atual| normalizar_cnpj(&atual.cnpj) == cnpj) {
+            Some(atual) => {
+                if mais_recente(&novo, atual) {
+                    *atual = novo;
+                    mesclagem.atualizados += 1;
+                }
+            }
+            None => {
+                mesclagem.registros.push(novo);
+                mesclagem.adicionados += 1;
+            }
+        }
+    }
+    mesclagem.mantidos = total_existentes - mesclagem.atualizados;
+    mesclagem
+}
+
+/// Mescla os registros novos ao sicaf_dados.json existente e grava o conjunto completo
+pub fn salvar_sicaf_incremental(novos: &[SicafData], output_dir: &Path) -> Result<MesclagemSicaf> {
+    let json_path = output_dir.join("sicaf_dados.json");
+    // Um arquivo ilegível não é sobrescrito: os registros anteriores se perderiam
+    let existentes = if json_path.exists() {
+        carregar_sicaf_json(&json_path)?
+    } else {
+        Vec::new()
+    };
+
+    let mesclagem = mesclar_sicaf(existentes, novos.to_vec());
+    salvar_sicaf_json(&mesclagem.registros, output_dir)?;
+    Ok(mesclagem)
+}
+
 /// Salva dados SICAF em arquivo JSON
-pub fn salvar_sicaf_json(sicaf_data: &[SicafData], output_dir: &Path, verbose: bool) -> Result<()> {
+pub fn salvar_sicaf_json(sicaf_data: &[SicafData], output_dir: &Path) -> Result<()> {
     let data_geracao = Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string();
     
     let sicaf_json = serde_json::json!({
+        "schema_version": migracoes::VERSAO_ESQUEMA,
         "data_geracao": data_geracao,
         "total_registros": sicaf_data.len(),
         "registros_sicaf": sicaf_data
     });
 
     let json_path = output_dir.join("sicaf_dados.json");
-    let json_content = serde_json::to_string_pretty(&sicaf_json)
-        .context("Erro ao serializar dados SICAF")?;
-
-    fs::write(&json_path, json_content)
+    integridade::escrever_json_atomico(&json_path, &sicaf_json)
         .context("Erro ao salvar arquivo JSON SICAF")?;
 
-    if verbose {
-        println!("📄 Dados SICAF salvos em: {:?}", json_path);
+    info!(caminho = %json_path.display(), registros = sicaf_data.len(), "Dados SICAF salvos");
+
+    if let Err(e) = storage::registrar_sicaf(output_dir, sicaf_data) {
+        warn!("Erro ao atualizar o banco SQLite: {:#}", e);
     }
 
     Ok(())
@@ -212,79 +549,369 @@ pub fn carregar_sicaf_json(json_path: &Path) -> Result<Vec<SicafData>> {
 
 /// Verifica se um CNPJ existe nos dados SICAF
 pub fn verificar_cnpj_sicaf(cnpj: &str, sicaf_data: &[SicafData]) -> bool {
-    // Normalizar CNPJ removendo formatação
-    let cnpj_normalizado = cnpj.replace(".", "").replace("/", "").replace("-", "");
-    
-    sicaf_data.iter().any(|data| {
-        let cnpj_data_normalizado = data.cnpj.replace(".", "").replace("/", "").replace("-", "");
-        cnpj_data_normalizado == cnpj_normalizado
-    })
+    obter_dados_cnpj(cnpj, sicaf_data).is_some()
 }
 
 /// Obtém dados SICAF para um CNPJ específico
 pub fn obter_dados_cnpj<'a>(cnpj: &str, sicaf_data: &'a [SicafData]) -> Option<&'a SicafData> {
-    // Normalizar CNPJ removendo formatação
-    let cnpj_normalizado = cnpj.replace(".", "").replace("/", "").replace("-", "");
-    
-    sicaf_data.iter().find(|data| {
-        let cnpj_data_normalizado = data.cnpj.replace(".", "").replace("/", "").replace("-", "");
-        cnpj_data_normalizado == cnpj_normalizado
-    })
+    let cnpj_normalizado = normalizar_cnpj(cnpj);
+    sicaf_data.iter().find(|data| normalizar_cnpj(&data.cnpj) == cnpj_normalizado)
+}
+
+/// Registros cuja razão social, nome fantasia ou CNPJ contêm o filtro (sem diferenciar maiúsculas)
+pub fn filtrar_sicaf<'a>(sicaf_data: &'a [SicafData], filtro: &str) -> Vec<&'a SicafData> {
+    let filtro = filtro.trim().to_lowercase();
+    if filtro.is_empty() {
+        return sicaf_data.iter().collect();
+    }
+    let filtro_cnpj = normalizar_cnpj(&filtro);
+
+    sicaf_data.iter().filter(|dados| {
+        dados.empresa.to_lowercase().contains(&filtro)
+            || dados.nome_fantasia.as_deref().is_some_and(|nome| nome.to_lowercase().contains(&filtro))
+            || dados.cnpj.contains(&filtro)
+            || (!filtro_cnpj.is_empty() && normalizar_cnpj(&dados.cnpj).contains(&filtro_cnpj))
+    }).collect()
+}
+
+/// Remove o registro do CNPJ, devolvendo-o; None se o CNPJ não estiver cadastrado
+pub fn remover_cnpj_sicaf(sicaf_data: &mut Vec<SicafData>, cnpj: &str) -> Option<SicafData> {
+    let cnpj_normalizado = normalizar_cnpj(cnpj);
+    let posicao = sicaf_data.iter().position(|data| normalizar_cnpj(&data.cnpj) == cnpj_normalizado)?;
+    Some(sicaf_data.remove(posicao))
+}
+
+/// Total de registros, cadastros vencidos em `hoje` e UFs representadas
+pub fn estatisticas_sicaf(sicaf_data: &[SicafData], hoje: NaiveDate) -> EstatisticasSicaf {
+    let vencidos = sicaf_data.iter()
+        .filter(|dados| {
+            dados.data_vencimento.as_deref().and_then(|data| dias_para_vencer(data, hoje)).is_some_and(|dias| dias < 0)
+        })
+        .count();
+
+    let mut ufs: Vec<String> = sicaf_data.iter()
+        .filter_map(|dados| dados.uf.as_deref())
+        .map(|uf| uf.trim().to_uppercase())
+        .filter(|uf| !uf.is_empty())
+        .collect();
+    ufs.sort();
+    ufs.dedup();
+
+    EstatisticasSicaf { total: sicaf_data.len(), vencidos, ufs }
+}
+
+/// Dias entre `hoje` e a data de vencimento (dd/mm/aaaa); negativo se já venceu, None se a data for inválida
+pub fn dias_para_vencer(data_vencimento: &str, hoje: NaiveDate) -> Option<i64> {
+    let vencimento = NaiveDate::parse_from_str(data_vencimento.trim(), "%d/%m/%Y").ok()?;
+    Some((vencimento - hoje).num_days())
+}
+
+/// Situação do CNPJ nos dados SICAF em uma data
+pub fn verificar_cnpj_sicaf_detalhado(cnpj: &str, sicaf_data: &[SicafData], hoje: NaiveDate) -> VerificacaoSicaf {
+    let dias = obter_dados_cnpj(cnpj, sicaf_data)
+        .map(|dados| dados.data_vencimento.as_deref().and_then(|data| dias_para_vencer(data, hoje)));
+
+    VerificacaoSicaf {
+        encontrado: dias.is_some(),
+        vencido: dias.flatten().map(|dias| dias < 0),
+        dias_para_vencer: dias.flatten(),
+    }
+}
+
+/// Termos de natureza jurídica ignorados na comparação de razões sociais
+const SUFIXOS_SOCIETARIOS: [&str; 8] = ["LTDA", "ME", "EPP", "EIRELI", "MEI", "SA", "CIA", "SLU"];
+
+/// Status da comparação quando o registro SICAF foi encontrado apenas pela razão social
+pub const STATUS_SICAF_PROVAVEL: &str = "SICAF Provável (por razão social)";
+pub const STATUS_SICAF_ENCONTRADO: &str = "SICAF Encontrado";
+pub const STATUS_SICAF_VENCIDO: &str = "SICAF Vencido";
+pub const STATUS_SICAF_NAO_ENCONTRADO: &str = "SICAF Não Encontrado";
+
+/// Status possíveis de uma proposta no relatório de comparação
+pub const STATUS_SICAF: [&str; 4] = [STATUS_SICAF_ENCONTRADO, STATUS_SICAF_VENCIDO, STATUS_SICAF_PROVAVEL, STATUS_SICAF_NAO_ENCONTRADO];
+
+/// Relatório de comparação gravado na pasta de resultados (sem a extensão)
+pub const ARQUIVO_RELATORIO_COMPARACAO: &str = "relatorio_sicaf_comparacao";
+
+/// Cabeçalho do relatório de comparação em CSV
+pub const CABECALHO_COMPARACAO: [&str; 12] = [
+    "CNPJ",
+    "Fornecedor",
+    "E-mail",
+    "Status SICAF",
+    "Situação do Cadastro",
+    "Data de Vencimento",
+    "Município/UF",
+    "UASG",
+    "Pregão",
+    "Item",
+    "Valor Adjudicado",
+    "Impedimento Vigente",
+];
+
+/// Razão social em maiúsculas, sem acentos, pontuação nem termos como "LTDA", "ME" e "EIRELI"
+pub fn normalizar_razao_social(nome: &str) -> String {
+    let maiusculas: String = nome.to_uppercase()
+        .chars()
+        .map(|c| paths::sem_acento(c).unwrap_or(c))
+        .collect();
+
+    maiusculas
+        // "S/A" e "S.A." viram "SA" antes de separar as palavras
+        .replace("S/A", "SA")
+        .replace("S.A.", "SA")
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|palavra| !palavra.is_empty() && !SUFIXOS_SOCIETARIOS.contains(palavra))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Similaridade Jaro-Winkler (0 a 1) entre duas razões sociais normalizadas
+pub fn similaridade_razao_social(a: &str, b: &str) -> f64 {
+    let (a, b) = (normalizar_razao_social(a), normalizar_razao_social(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    strsim::jaro_winkler(&a, &b)
+}
+
+/// Registro SICAF com a razão social (ou nome fantasia) mais parecida, se atingir o limiar
+pub fn buscar_por_razao_social<'a>(fornecedor: &str, sicaf_data: &'a [SicafData], limiar: f64) -> Option<(&'a SicafData, f64)> {
+    sicaf_data.iter()
+        .map(|dados| {
+            let score = std::iter::once(dados.empresa.as_str())
+                .chain(dados.nome_fantasia.as_deref())
+                .map(|nome| similaridade_razao_social(fornecedor, nome))
+                .fold(0.0, f64::max);
+            (dados, score)
+        })
+        .filter(|(_, score)| *score >= limiar)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+}
+
+/// Formatos em que o relatório de comparação pode ser gravado
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FormatoRelatorio {
+    #[default]
+    Json,
+    Csv,
+    Ambos,
+}
+
+impl FormatoRelatorio {
+    pub const NOMES: [&'static str; 3] = ["json", "csv", "ambos"];
+
+    pub fn de_nome(nome: &str) -> Option<Self> {
+        match nome {
+            "json" => Some(FormatoRelatorio::Json),
+            "csv" => Some(FormatoRelatorio::Csv),
+            "ambos" => Some(FormatoRelatorio::Ambos),
+            _ => None,
+        }
+    }
+
+    fn json(self) -> bool {
+        self != FormatoRelatorio::Csv
+    }
+
+    fn csv(self) -> bool {
+        self != FormatoRelatorio::Json
+    }
+}
+
+/// Status informado pelo usuário no formato de `STATUS_SICAF`, ignorando acentos e caixa
+pub fn status_sicaf_canonico(status: &str) -> Option<&'static str> {
+    let procurado = resultados::normalizar_busca(status.trim());
+    STATUS_SICAF.into_iter().find(|s| resultados::normalizar_busca(s) == procurado)
+}
+
+/// E-mails válidos do registro; nos registros gravados antes de `emails`, validados a partir de `email`
+pub fn emails_validos(dados: &SicafData) -> Vec<String> {
+    if dados.emails.is_empty() {
+        dados.email.as_deref().map(validators::extrair_emails).unwrap_or_default()
+    } else {
+        dados.emails.clone()
+    }
+}
+
+/// Uma proposta comparada com os dados SICAF
+struct LinhaComparacao<'a> {
+    proposta: &'a PropostaConsolidada,
+    status: &'static str,
+    score_razao_social: Option<f64>,
+    dados_sicaf: Option<&'a SicafData>,
+    ocorrencias_impeditivas: Vec<&'a OcorrenciaSicaf>,
+    /// Dados da BrasilAPI, só para os não encontrados quando o enriquecimento está ligado
+    dados_online: Option<DadosCnpjOnline>,
+}
+
+impl LinhaComparacao<'_> {
+    fn json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "cnpj": self.proposta.cnpj,
+            "fornecedor": self.proposta.fornecedor,
+            "emails": self.dados_sicaf.map(emails_validos).unwrap_or_default(),
+            "status_sicaf": self.status,
+            "score_razao_social": self.score_razao_social,
+            "impedimento_vigente": !self.ocorrencias_impeditivas.is_empty(),
+            "ocorrencias_impeditivas": self.ocorrencias_impeditivas,
+            "dados_sicaf": self.dados_sicaf,
+            "dados_online": self.dados_online,
+            "proposta": {
+                "item": self.proposta.item,
+                "valor_adjudicado": self.proposta.valor_adjudicado,
+                "uasg": self.proposta.uasg,
+                "pregao": self.proposta.pregao
+            }
+        })
+    }
+
+    fn colunas(&self) -> Vec<String> {
+        let dados = self.dados_sicaf;
+        let online = self.dados_online.as_ref();
+        let campo = |valor: Option<&Option<String>>| valor.and_then(|v| v.clone()).unwrap_or_default();
+        let municipio = campo(dados.map(|d| &d.municipio).or(online.map(|d| &d.municipio)));
+        let uf = campo(dados.map(|d| &d.uf).or(online.map(|d| &d.uf)));
+        let municipio_uf = match (municipio, uf) {
+            (municipio, uf) if municipio.is_empty() || uf.is_empty() => format!("{}{}", municipio, uf),
+            (municipio, uf) => format!("{}/{}", municipio, uf),
+        };
+        vec![
+            self.proposta.cnpj.clone(),
+            self.proposta.fornecedor.clone(),
+            dados.map(emails_validos).unwrap_or_default().join("; "),
+            self.status.to_string(),
+            campo(dados.map(|d| &d.situacao_cadastro)),
+            campo(dados.map(|d| &d.data_vencimento)),
+            municipio_uf,
+            self.proposta.uasg.clone(),
+            self.proposta.pregao.clone(),
+            self.proposta.item.clone(),
+            formatar_valor(self.proposta.valor_adjudicado),
+            if self.ocorrencias_impeditivas.is_empty() { "Não" } else { "Sim" }.to_string(),
+        ]
+    }
+}
+
+/// Status SICAF de uma proposta na data `hoje`, com o registro aceito e o score quando veio pela razão social
+fn classificar_proposta<'a>(
+    proposta: &PropostaConsolidada,
+    sicaf_data: &'a [SicafData],
+    limiar_razao_social: f64,
+    hoje: NaiveDate,
+) -> (&'static str, Option<&'a SicafData>, Option<f64>) {
+    // O vencimento é avaliado na data do relatório, não na do processamento do PDF
+    match verificar_cnpj_sicaf_detalhado(&proposta.cnpj, sicaf_data, hoje) {
+        VerificacaoSicaf { encontrado: false, .. } => {
+            match buscar_por_razao_social(&proposta.fornecedor, sicaf_data, limiar_razao_social) {
+                Some((dados, score)) => (STATUS_SICAF_PROVAVEL, Some(dados), Some(score)),
+                None => (STATUS_SICAF_NAO_ENCONTRADO, None, None),
+            }
+        }
+        VerificacaoSicaf { vencido: Some(true), .. } => (STATUS_SICAF_VENCIDO, obter_dados_cnpj(&proposta.cnpj, sicaf_data), None),
+        _ => (STATUS_SICAF_ENCONTRADO, obter_dados_cnpj(&proposta.cnpj, sicaf_data), None),
+    }
+}
+
+/// CNPJs (só dígitos, sem repetição) das propostas que nem o CNPJ nem a razão social acham no SICAF
+///
+/// São os candidatos à consulta na BrasilAPI antes de `gerar_relatorio_comparacao`.
+pub fn cnpjs_nao_encontrados(propostas: &[PropostaConsolidada], sicaf_data: &[SicafData], limiar_razao_social: f64) -> Vec<String> {
+    let hoje = Local::now().date_naive();
+    let mut cnpjs: Vec<String> = propostas.iter()
+        .filter(|p| classificar_proposta(p, sicaf_data, limiar_razao_social, hoje).0 == STATUS_SICAF_NAO_ENCONTRADO)
+        .map(|p| normalizar_cnpj(&p.cnpj))
+        .filter(|cnpj| !cnpj.is_empty())
+        .collect();
+    cnpjs.sort();
+    cnpjs.dedup();
+    cnpjs
 }
 
 /// Gera relatório de comparação entre licitação e SICAF
+///
+/// Propostas cujo CNPJ não está no SICAF passam por um segundo passe pela razão social;
+/// o registro aceito acima de `limiar_razao_social` fica marcado como provável, com o score.
+/// Com `filtro_status`, só as propostas nesses status (de `STATUS_SICAF`) entram na listagem;
+/// os totais do JSON continuam contando todas. Com `dados_online` (por CNPJ, só dígitos, ver
+/// `cnpjs_nao_encontrados`), os não encontrados levam os dados da BrasilAPI; CNPJ ausente do mapa
+/// sai como offline. Retorna os caminhos dos arquivos gravados.
 pub fn gerar_relatorio_comparacao(
     propostas: &[PropostaConsolidada],
     sicaf_data: &[SicafData],
     output_dir: &Path,
-    verbose: bool,
-) -> Result<()> {
-    let mut relatorio = Vec::new();
+    limiar_razao_social: f64,
+    formato: FormatoRelatorio,
+    filtro_status: Option<&[&str]>,
+    dados_online: Option<&HashMap<String, DadosCnpjOnline>>,
+) -> Result<Vec<PathBuf>> {
+    let mut linhas = Vec::new();
+    let hoje = Local::now().date_naive();
     
     for proposta in propostas {
-        let sicaf_encontrado = obter_dados_cnpj(&proposta.cnpj, sicaf_data);
+        let (status, sicaf_encontrado, score_razao_social) = classificar_proposta(proposta, sicaf_data, limiar_razao_social, hoje);
         
-        let status = if sicaf_encontrado.is_some() {
-            "SICAF Encontrado"
-        } else {
-            "SICAF Não Encontrado"
-        };
+        let online = dados_online
+            .filter(|_| status == STATUS_SICAF_NAO_ENCONTRADO)
+            .map(|dados_online| dados_online.get(&normalizar_cnpj(&proposta.cnpj))
+                .cloned()
+                .unwrap_or_else(|| consulta_cnpj::offline(&proposta.cnpj, "CNPJ não consultado")));
         
-        relatorio.push(serde_json::json!({
-            "cnpj": proposta.cnpj,
-            "fornecedor": proposta.fornecedor,
-            "status_sicaf": status,
-            "dados_sicaf": sicaf_encontrado,
-            "proposta": {
-                "item": proposta.item,
-                "valor_adjudicado": proposta.valor_adjudicado,
-                "uasg": proposta.uasg,
-                "pregao": proposta.pregao
-            }
-        }));
+        let ocorrencias_impeditivas: Vec<&OcorrenciaSicaf> = sicaf_encontrado
+            .map(|dados| dados.ocorrencias.iter().filter(|o| ocorrencia_impeditiva_vigente(o, hoje)).collect())
+            .unwrap_or_default();
+        
+        linhas.push(LinhaComparacao {
+            proposta,
+            status,
+            score_razao_social,
+            dados_sicaf: sicaf_encontrado,
+            ocorrencias_impeditivas,
+            dados_online: online,
+        });
     }
     
-    let data_geracao = Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string();
-    let relatorio_final = serde_json::json!({
-        "data_geracao": data_geracao,
-        "total_propostas": propostas.len(),
-        "sicaf_encontrados": relatorio.iter().filter(|r| r["status_sicaf"] == "SICAF Encontrado").count(),
-        "sicaf_nao_encontrados": relatorio.iter().filter(|r| r["status_sicaf"] == "SICAF Não Encontrado").count(),
-        "relatorio": relatorio
-    });
-
-    let relatorio_path = output_dir.join("relatorio_sicaf_comparacao.json");
-    let relatorio_content = serde_json::to_string_pretty(&relatorio_final)
-        .context("Erro ao serializar relatório de comparação")?;
-
-    fs::write(&relatorio_path, relatorio_content)
-        .context("Erro ao salvar relatório de comparação")?;
+    let contar = |status: &str| linhas.iter().filter(|l| l.status == status).count();
+    let com_impedimento_vigente = linhas.iter().filter(|l| !l.ocorrencias_impeditivas.is_empty()).count();
+    let listadas: Vec<&LinhaComparacao> = linhas.iter()
+        .filter(|l| filtro_status.is_none_or(|filtro| filtro.contains(&l.status)))
+        .collect();
+    
+    let mut arquivos = Vec::new();
+    if formato.json() {
+        let data_geracao = Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string();
+        let relatorio_final = serde_json::json!({
+            "data_geracao": data_geracao,
+            "total_propostas": propostas.len(),
+            "sicaf_encontrados": contar(STATUS_SICAF_ENCONTRADO),
+            "sicaf_vencidos": contar(STATUS_SICAF_VENCIDO),
+            "sicaf_provaveis": contar(STATUS_SICAF_PROVAVEL),
+            "com_impedimento_vigente": com_impedimento_vigente,
+            "sicaf_nao_encontrados": contar(STATUS_SICAF_NAO_ENCONTRADO),
+            "enriquecidos_online": linhas.iter()
+                .filter(|l| l.dados_online.as_ref().is_some_and(|d| d.fonte != FONTE_CNPJ_OFFLINE))
+                .count(),
+            "filtro_status": filtro_status,
+            "relatorio": listadas.iter().map(|l| l.json()).collect::<Vec<_>>()
+        });
 
-    if verbose {
-        println!("📊 Relatório de comparação salvo em: {:?}", relatorio_path);
+        let relatorio_path = output_dir.join(format!("{}.json", ARQUIVO_RELATORIO_COMPARACAO));
+        integridade::escrever_json_atomico(&relatorio_path, &relatorio_final)
+            .context("Erro ao salvar relatório de comparação")?;
+        arquivos.push(relatorio_path);
+    }
+    
+    if formato.csv() {
+        let csv = exportacao::gerar_csv(&CABECALHO_COMPARACAO, listadas.iter().map(|l| l.colunas()));
+        let relatorio_path = output_dir.join(format!("{}.csv", ARQUIVO_RELATORIO_COMPARACAO));
+        integridade::escrever_atomico(&relatorio_path, csv.as_bytes())
+            .context("Erro ao salvar relatório de comparação em CSV")?;
+        arquivos.push(relatorio_path);
     }
 
-    Ok(())
+    info!(arquivos = arquivos.len(), propostas = propostas.len(), listadas = listadas.len(), "Relatório de comparação salvo");
+
+    Ok(arquivos)
 }
 
 #[cfg(test)]
@@ -323,8 +950,26 @@ mod tests {
         assert_eq!(dados.endereco, Some("RUA TESTE, 123 - CENTRO".to_string()));
         assert_eq!(dados.municipio, Some("SÃO PAULO".to_string()));
         assert_eq!(dados.uf, Some("SP".to_string()));
-        assert_eq!(dados.telefone, Some("(11) 1234-5678".to_string()));
+        assert_eq!(dados.telefone, Some("1112345678".to_string()));
         assert_eq!(dados.email, Some("teste@empresa.com.br".to_string()));
+        assert_eq!(dados.emails, ["teste@empresa.com.br"]);
+        assert_eq!(dados.contato_raw.as_deref(), Some("(11) 1234-5678\n            E-mail: teste@empresa.com.br"));
+    }
+
+    #[test]
+    fn test_recupera_sicaf_com_uso_privado_e_acentos_soltos() {
+        let bruto = include_str!("../tests/fixtures/sicaf_pua_acentos_soltos.txt");
+        assert!(extrair_dados_sicaf(bruto).is_none());
+
+        let texto = texto::normalizar_texto(bruto);
+        let dados = extrair_dados_sicaf(&texto).unwrap();
+        assert_eq!(dados.duns.as_deref(), Some("123456789"));
+        assert_eq!(dados.empresa, "EMPRESA A LTDA");
+        assert_eq!(dados.situacao_cadastro.as_deref(), Some("Credenciado"));
+        assert_eq!(dados.endereco.as_deref(), Some("RUA DAS FLORES, 100 - CENTRO"));
+        assert_eq!(dados.municipio.as_deref(), Some("Brasília"));
+        assert_eq!(dados.uf.as_deref(), Some("DF"));
+        assert_eq!(extrair_dados_responsavel(&texto).unwrap().nome, "FULANO DE TAL");
     }
 
     #[test]
@@ -349,6 +994,7 @@ mod tests {
         let sicaf_data = vec![
             SicafData {
                 cnpj: "12.345.678/0001-90".to_string(),
+                cnpj_valido: true,
                 duns: None,
                 empresa: "TESTE LTDA".to_string(),
                 nome_fantasia: None,
@@ -360,8 +1006,15 @@ mod tests {
                 uf: None,
                 telefone: None,
                 email: None,
+                emails: Vec::new(),
+                contato_raw: None,
                 cpf_responsavel: None,
                 nome_responsavel: None,
+                cadastro_vencido: None,
+                niveis: Vec::new(),
+                ocorrencias: Vec::new(),
+                data_emissao: None,
+                arquivo_modificado_em: None,
             }
         ];
 
@@ -374,4 +1027,308 @@ mod tests {
         // Não deve encontrar CNPJ inexistente
         assert!(!verificar_cnpj_sicaf("98.765.432/0001-10", &sicaf_data));
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_vencimento_do_cadastro() {
+        let hoje = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        assert_eq!(dias_para_vencer("31/12/2024", hoje), Some(199));
+        assert_eq!(dias_para_vencer("15/06/2024", hoje), Some(0));
+        assert_eq!(dias_para_vencer("10/06/2024", hoje), Some(-5));
+        assert_eq!(dias_para_vencer("31/02/2024", hoje), None);
+        assert_eq!(dias_para_vencer("", hoje), None);
+
+        let dados: SicafData = serde_json::from_value(serde_json::json!({
+            "cnpj": "12.345.678/0001-90", "duns": null, "empresa": "TESTE LTDA", "nome_fantasia": null,
+            "situacao_cadastro": null, "data_vencimento": "10/06/2024", "cep": null, "endereco": null,
+            "municipio": null, "uf": null, "telefone": null, "email": null,
+            "cpf_responsavel": null, "nome_responsavel": null
+        })).unwrap();
+        let verificacao = verificar_cnpj_sicaf_detalhado("12345678000190", std::slice::from_ref(&dados), hoje);
+        assert!(verificacao.encontrado);
+        assert_eq!(verificacao.vencido, Some(true));
+        assert_eq!(verificacao.dias_para_vencer, Some(-5));
+
+        let ausente = verificar_cnpj_sicaf_detalhado("98.765.432/0001-10", &[dados], hoje);
+        assert!(!ausente.encontrado);
+        assert_eq!(ausente.vencido, None);
+    }
+
+    #[test]
+    fn test_extrair_niveis_e_ocorrencias() {
+        let texto = "CNPJ: 12.345.678/0001-90
+Níveis cadastrados:
+I - Credenciamento
+II - Habilitação Jurídica
+III - Regularidade Fiscal e Trabalhista Federal
+Dados para Contato
+CEP: 01234-567
+Ocorrências e Impedimentos
+Ocorrência: Impedimento de licitar e contratar - Lei 14.133/2021, art. 156, III
+Órgão Sancionador: COMANDO DA MARINHA
+Data de Início: 01/02/2024
+Data de Fim: 01/02/2027
+Ocorrência: Advertência
+Órgão: PREFEITURA MUNICIPAL DE SANTOS
+Data de Início: 10/03/2023
+Dados do Responsável Legal
+CPF: 123.456.789-00";
+
+        assert_eq!(extrair_niveis(texto), vec![
+            "I - Credenciamento",
+            "II - Habilitação Jurídica",
+            "III - Regularidade Fiscal e Trabalhista Federal",
+        ]);
+
+        let ocorrencias = extrair_ocorrencias(texto);
+        assert_eq!(ocorrencias, vec![
+            OcorrenciaSicaf {
+                descricao: "Impedimento de licitar e contratar - Lei 14.133/2021, art. 156, III".to_string(),
+                orgao: Some("COMANDO DA MARINHA".to_string()),
+                data_inicio: Some("01/02/2024".to_string()),
+                data_fim: Some("01/02/2027".to_string()),
+            },
+            OcorrenciaSicaf {
+                descricao: "Advertência".to_string(),
+                orgao: Some("PREFEITURA MUNICIPAL DE SANTOS".to_string()),
+                data_inicio: Some("10/03/2023".to_string()),
+                data_fim: None,
+            },
+        ]);
+
+        // Só o impedimento em vigor bloqueia a contratação
+        let durante = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        let depois = NaiveDate::from_ymd_opt(2027, 6, 1).unwrap();
+        assert!(ocorrencia_impeditiva_vigente(&ocorrencias[0], durante));
+        assert!(!ocorrencia_impeditiva_vigente(&ocorrencias[0], depois));
+        assert!(!ocorrencia_impeditiva_vigente(&ocorrencias[1], durante));
+
+        // Relatório sem seção de ocorrências não inventa nenhuma
+        assert!(extrair_ocorrencias("Dados do Nível 1 - Credenciamento").is_empty());
+        assert_eq!(extrair_niveis("Dados do Nível 1 - Credenciamento\nDados para Contato"), vec!["1 - Credenciamento"]);
+    }
+
+    #[test]
+    fn test_progresso_do_processamento_sicaf() {
+        let root = std::env::temp_dir().join(format!("licitacao360_sicaf_progresso_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("a.pdf"), b"nao e um pdf").unwrap();
+        fs::write(root.join("b.pdf"), b"nao e um pdf").unwrap();
+
+        let mut eventos = Vec::new();
+        let resultado = processar_sicaf_pdfs_com_progresso(&root, &AtomicBool::new(false), |processados, total, evento| {
+            eventos.push((processados, total, matches!(evento, EventoProgressoSicaf::Falhou(..))));
+        }).unwrap();
+
+        // Início e fim de cada arquivo; PDFs ilegíveis falham sem interromper o lote
+        assert_eq!(resultado.processed_count, 0);
+        assert_eq!(eventos.len(), 4);
+        assert_eq!(eventos.last(), Some(&(2, 2, true)));
+
+        // Cancelado antes de começar, nenhum arquivo é lido
+        let mut chamadas = 0;
+        let cancelado = processar_sicaf_pdfs_com_progresso(&root, &AtomicBool::new(true), |_, _, _| chamadas += 1).unwrap();
+        assert_eq!(chamadas, 0);
+        assert!(cancelado.message.starts_with("Processamento cancelado"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    fn registro(cnpj: &str, empresa: &str, data_emissao: Option<&str>, modificado_em: Option<&str>) -> SicafData {
+        serde_json::from_value(serde_json::json!({
+            "cnpj": cnpj, "duns": null, "empresa": empresa, "nome_fantasia": null, "situacao_cadastro": null,
+            "data_vencimento": null, "cep": null, "endereco": null, "municipio": null, "uf": null,
+            "telefone": null, "email": null, "cpf_responsavel": null, "nome_responsavel": null,
+            "data_emissao": data_emissao, "arquivo_modificado_em": modificado_em
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_mesclar_sicaf_por_cnpj() {
+        let existentes = vec![
+            registro("12.345.678/0001-90", "ANTIGA", Some("10/01/2024 09:00"), None),
+            registro("11.222.333/0001-81", "MAIS RECENTE", Some("20/05/2024"), None),
+            registro("98.765.432/0001-10", "INTOCADA", None, None),
+        ];
+        let novos = vec![
+            // Mesmo CNPJ sem formatação, relatório mais novo: atualiza
+            registro("12345678000190", "NOVA", Some("15/03/2024 14:30"), None),
+            // Relatório mais antigo que o já cadastrado: mantém o existente
+            registro("11.222.333/0001-81", "DESATUALIZADA", None, Some("2024-02-01T10:00:00+00:00")),
+            registro("44.555.666/0001-77", "ADICIONADA", None, None),
+        ];
+
+        let mesclagem = mesclar_sicaf(existentes, novos);
+        let empresas: Vec<&str> = mesclagem.registros.iter().map(|r| r.empresa.as_str()).collect();
+        assert_eq!(empresas, vec!["NOVA", "MAIS RECENTE", "INTOCADA", "ADICIONADA"]);
+        assert_eq!((mesclagem.adicionados, mesclagem.atualizados, mesclagem.mantidos), (1, 1, 2));
+
+        assert_eq!(
+            extrair_data_emissao("Emitido em: 15/01/2024 10:32 pelo usuário").as_deref(),
+            Some("15/01/2024 10:32")
+        );
+    }
+
+    #[test]
+    fn test_filtrar_remover_e_estatisticas_sicaf() {
+        let mut registros = vec![
+            registro("12.345.678/0001-90", "Papelaria Central LTDA", None, None),
+            registro("98.765.432/0001-10", "Comercial Norte", None, None),
+        ];
+        registros[0].uf = Some("df".to_string());
+        registros[0].data_vencimento = Some("10/01/2024".to_string());
+        registros[1].uf = Some("AM".to_string());
+        registros[1].data_vencimento = Some("10/01/2025".to_string());
+
+        assert_eq!(filtrar_sicaf(&registros, "papelaria").len(), 1);
+        assert_eq!(filtrar_sicaf(&registros, "98765432").len(), 1);
+        assert_eq!(filtrar_sicaf(&registros, "  ").len(), 2);
+
+        let estatisticas = estatisticas_sicaf(&registros, NaiveDate::from_ymd_opt(2024, 6, 1).unwrap());
+        assert_eq!((estatisticas.total, estatisticas.vencidos), (2, 1));
+        assert_eq!(estatisticas.ufs, vec!["AM", "DF"]);
+
+        let removido = remover_cnpj_sicaf(&mut registros, "12345678000190").unwrap();
+        assert_eq!(removido.empresa, "Papelaria Central LTDA");
+        assert_eq!(registros.len(), 1);
+        assert!(remover_cnpj_sicaf(&mut registros, "12.345.678/0001-90").is_none());
+    }
+
+    #[test]
+    fn test_similaridade_razao_social() {
+        assert_eq!(normalizar_razao_social("Papelaria São João Ltda. - ME"), "PAPELARIA SAO JOAO");
+        assert_eq!(normalizar_razao_social("Comércio Norte S/A"), "COMERCIO NORTE");
+
+        // Quase iguais: sufixo societário, acento e erro de digitação
+        assert_eq!(similaridade_razao_social("PAPELARIA SAO JOAO LTDA", "Papelaria São João EIRELI"), 1.0);
+        assert!(similaridade_razao_social("DISTRIBUIDORA ALVORADA LTDA", "DISTRIBUIDORA ALVORDA LTDA ME") > 0.95);
+
+        // Claramente diferentes
+        assert!(similaridade_razao_social("PAPELARIA SAO JOAO LTDA", "CONSTRUTORA HORIZONTE LTDA") < 0.7);
+        assert_eq!(similaridade_razao_social("LTDA", "EMPRESA LTDA"), 0.0);
+    }
+
+    #[test]
+    fn test_buscar_por_razao_social_respeita_o_limiar() {
+        let mut registros = vec![
+            registro("12.345.678/0001-90", "DISTRIBUIDORA ALVORADA LTDA", None, None),
+            registro("98.765.432/0001-10", "CONSTRUTORA HORIZONTE LTDA", None, None),
+        ];
+        registros[1].nome_fantasia = Some("Horizonte Obras".to_string());
+
+        let (dados, score) = buscar_por_razao_social("Distribuidora Alvorada - ME", &registros, 0.9).unwrap();
+        assert_eq!(dados.cnpj, "12.345.678/0001-90");
+        assert_eq!(score, 1.0);
+
+        // O nome fantasia também é comparado
+        let (dados, _) = buscar_por_razao_social("HORIZONTE OBRAS EIRELI", &registros, 0.9).unwrap();
+        assert_eq!(dados.cnpj, "98.765.432/0001-10");
+
+        assert!(buscar_por_razao_social("MERCADO BOA VISTA LTDA", &registros, 0.9).is_none());
+        assert!(buscar_por_razao_social("DISTRIBUIDORA ALVORDA", &registros, 1.0).is_none());
+    }
+
+    #[test]
+    fn test_relatorio_comparacao_em_csv_com_filtro_de_status() {
+        let dir = std::env::temp_dir().join(format!("licitacao360_comparacao_csv_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut cadastrado = registro("12.345.678/0001-90", "ALVORADA LTDA", None, None);
+        cadastrado.situacao_cadastro = Some("HABILITADO".to_string());
+        cadastrado.data_vencimento = Some("31/12/2099".to_string());
+        cadastrado.municipio = Some("RECIFE".to_string());
+        cadastrado.uf = Some("PE".to_string());
+        cadastrado.email = Some("licitacoes@alvorada.com.br".to_string());
+        let proposta = |item: &str, fornecedor: &str, cnpj: &str| -> PropostaConsolidada {
+            serde_json::from_value(serde_json::json!({
+                "uasg": "787000", "pregao": "90008/2024", "processo": "123", "item": item, "grupo": null,
+                "quantidade": "1", "descricao": "Caneta", "valor_estimado": "10,00", "valor_adjudicado": "1.234,50",
+                "fornecedor": fornecedor, "cnpj": cnpj, "marca_fabricante": "N/A", "modelo_versao": "N/A",
+                "responsavel": "N/A", "melhor_lance": null, "tipo_formato": "individual"
+            })).unwrap()
+        };
+        let propostas = [
+            proposta("1", "ALVORADA LTDA", "12.345.678/0001-90"),
+            proposta("2", "HORIZONTE; OBRAS", "98.765.432/0001-10"),
+        ];
+
+        assert_eq!(status_sicaf_canonico("sicaf nao encontrado"), Some(STATUS_SICAF_NAO_ENCONTRADO));
+        let arquivos = gerar_relatorio_comparacao(
+            &propostas, std::slice::from_ref(&cadastrado), &dir, 0.9, FormatoRelatorio::Ambos, Some(&[STATUS_SICAF_NAO_ENCONTRADO]), None,
+        ).unwrap();
+        assert_eq!(arquivos, [dir.join("relatorio_sicaf_comparacao.json"), dir.join("relatorio_sicaf_comparacao.csv")]);
+
+        let csv = fs::read_to_string(&arquivos[1]).unwrap();
+        let linhas: Vec<&str> = csv.trim_start_matches(exportacao::BOM_UTF8).lines().collect();
+        assert_eq!(linhas, [
+            CABECALHO_COMPARACAO.join(";").as_str(),
+            "98.765.432/0001-10;\"HORIZONTE; OBRAS\";;SICAF Não Encontrado;;;;787000;90008/2024;2;1.234,50;Não",
+        ]);
+
+        // Os totais do JSON contam todas as propostas, mesmo as fora do filtro
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&arquivos[0]).unwrap()).unwrap();
+        assert_eq!((json["sicaf_encontrados"].as_u64(), json["sicaf_nao_encontrados"].as_u64()), (Some(1), Some(1)));
+        assert_eq!(json["relatorio"].as_array().unwrap().len(), 1);
+
+        // Sem filtro, o CSV traz os dados do cadastro encontrado
+        let arquivos = gerar_relatorio_comparacao(&propostas, std::slice::from_ref(&cadastrado), &dir, 0.9, FormatoRelatorio::Csv, None, None).unwrap();
+        assert_eq!(arquivos.len(), 1);
+        let csv = fs::read_to_string(&arquivos[0]).unwrap();
+        assert_eq!(csv.lines().nth(1), Some("12.345.678/0001-90;ALVORADA LTDA;licitacoes@alvorada.com.br;SICAF Encontrado;HABILITADO;31/12/2099;RECIFE/PE;787000;90008/2024;1;1.234,50;Não"));
+
+        // Enriquecimento pela BrasilAPI: só os não encontrados, e quem ficou sem consulta sai offline
+        let nao_encontrados = cnpjs_nao_encontrados(&propostas, std::slice::from_ref(&cadastrado), 0.9);
+        assert_eq!(nao_encontrados, ["98765432000110"]);
+        let online = DadosCnpjOnline {
+            cnpj: "98765432000110".to_string(),
+            razao_social: Some("HORIZONTE OBRAS LTDA".to_string()),
+            municipio: Some("NATAL".to_string()),
+            uf: Some("RN".to_string()),
+            fonte: crate::types::FONTE_CNPJ_CACHE.to_string(),
+            ..Default::default()
+        };
+        let dados_online = HashMap::from([(nao_encontrados[0].clone(), online)]);
+        let arquivos = gerar_relatorio_comparacao(&propostas, std::slice::from_ref(&cadastrado), &dir, 0.9, FormatoRelatorio::Ambos, None, Some(&dados_online)).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&arquivos[0]).unwrap()).unwrap();
+        assert_eq!(json["enriquecidos_online"], 1);
+        assert!(json["relatorio"][0]["dados_online"].is_null());
+        assert_eq!(json["relatorio"][1]["dados_online"]["razao_social"], "HORIZONTE OBRAS LTDA");
+        let csv = fs::read_to_string(&arquivos[1]).unwrap();
+        assert!(csv.lines().nth(2).unwrap().contains(";NATAL/RN;"));
+
+        let arquivos = gerar_relatorio_comparacao(&propostas, &[cadastrado], &dir, 0.9, FormatoRelatorio::Json, None, Some(&HashMap::new())).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&arquivos[0]).unwrap()).unwrap();
+        assert_eq!(json["enriquecidos_online"], 0);
+        assert_eq!(json["relatorio"][1]["dados_online"]["fonte"], FONTE_CNPJ_OFFLINE);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_trecho_diagnostico_e_copia_para_pasta_sicaf() {
+        let texto = format!("Relatório\nCNPJ: 12.345.678/0001-90\nRazão  Social: {}", "X".repeat(600));
+        let trecho = trecho_diagnostico(&texto);
+        assert!(trecho.starts_with("12.345.678/0001-90\nRazão  Social:"));
+        assert_eq!(trecho.chars().count(), TAMANHO_TRECHO_DIAGNOSTICO);
+        assert_eq!(trecho_diagnostico("Layout sem o rótulo"), "Layout sem o rótulo");
+
+        let dir = std::env::temp_dir().join(format!("licitacao360_copia_sicaf_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let (fora, pasta) = (dir.join("Downloads"), dir.join("SICAF"));
+        fs::create_dir_all(&fora).unwrap();
+        fs::write(fora.join("sicaf.pdf"), b"pdf a").unwrap();
+
+        let copia = copiar_para_pasta_sicaf(&fora.join("sicaf.pdf"), &pasta).unwrap();
+        assert_eq!(copia.file_name().unwrap(), "sicaf.pdf");
+        assert!(fora.join("sicaf.pdf").exists());
+        // A mesma cópia não é repetida; um arquivo diferente com o mesmo nome não sobrescreve
+        assert_eq!(copiar_para_pasta_sicaf(&fora.join("sicaf.pdf"), &pasta).unwrap(), copia);
+        assert_eq!(copiar_para_pasta_sicaf(&copia, &pasta).unwrap(), copia);
+        fs::write(fora.join("sicaf.pdf"), b"pdf b").unwrap();
+        let outra = copiar_para_pasta_sicaf(&fora.join("sicaf.pdf"), &pasta).unwrap();
+        assert_eq!(outra.file_name().unwrap(), "sicaf_1.pdf");
+        assert_eq!(fs::read(&copia).unwrap(), b"pdf a");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
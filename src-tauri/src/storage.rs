@@ -0,0 +1,406 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, params_from_iter, Connection, Transaction};
+use std::path::Path;
+use std::time::Duration;
+use chrono::Utc;
+use crate::pdf_processor;
+use crate::resultados;
+use crate::types::{
+    FiltroPropostasSql, LicitacaoConsolidada, PropostaConsolidada, ReconstrucaoBanco,
+    ResultadoConsultaPropostas, SicafData,
+};
+
+/// Banco SQLite dentro da pasta de resultados (os JSONs continuam sendo gravados)
+pub const ARQUIVO_BANCO: &str = "licitacoes.db";
+
+/// Limite padrão de uma consulta
+pub const LIMITE_CONSULTA_PADRAO: usize = 100;
+
+/// Migrações do esquema, aplicadas em ordem; a versão fica em `PRAGMA user_version`
+const MIGRACOES: &[&str] = &[
+    // 1: tabelas iniciais
+    r#"
+    CREATE TABLE licitacoes (
+        chave TEXT PRIMARY KEY,
+        uasg TEXT NOT NULL,
+        pregao TEXT NOT NULL,
+        processo TEXT NOT NULL,
+        total_propostas INTEGER NOT NULL,
+        valor_total REAL NOT NULL,
+        arquivo TEXT NOT NULL,
+        atualizado_em TEXT NOT NULL
+    );
+    CREATE TABLE propostas (
+        id INTEGER PRIMARY KEY,
+        licitacao TEXT NOT NULL REFERENCES licitacoes(chave) ON DELETE CASCADE,
+        uasg TEXT NOT NULL,
+        pregao TEXT NOT NULL,
+        processo TEXT NOT NULL,
+        item TEXT NOT NULL,
+        grupo TEXT,
+        descricao TEXT NOT NULL,
+        descricao_busca TEXT NOT NULL,
+        fornecedor TEXT NOT NULL,
+        fornecedor_busca TEXT NOT NULL,
+        cnpj TEXT NOT NULL,
+        cnpj_digitos TEXT NOT NULL,
+        situacao TEXT NOT NULL,
+        valor_adjudicado REAL,
+        dados TEXT NOT NULL
+    );
+    CREATE TABLE sicaf (
+        cnpj_digitos TEXT PRIMARY KEY,
+        cnpj TEXT NOT NULL,
+        empresa TEXT NOT NULL,
+        situacao_cadastro TEXT,
+        data_vencimento TEXT,
+        municipio TEXT,
+        uf TEXT,
+        dados TEXT NOT NULL
+    );
+    CREATE INDEX idx_licitacoes_uasg ON licitacoes(uasg);
+    CREATE INDEX idx_licitacoes_pregao ON licitacoes(pregao);
+    CREATE INDEX idx_propostas_licitacao ON propostas(licitacao);
+    CREATE INDEX idx_propostas_cnpj ON propostas(cnpj_digitos);
+    CREATE INDEX idx_propostas_uasg ON propostas(uasg);
+    CREATE INDEX idx_propostas_pregao ON propostas(pregao);
+    "#,
+];
+
+/// Versão do esquema após todas as migrações
+pub const VERSAO_ESQUEMA: usize = MIGRACOES.len();
+
+fn somente_digitos(texto: &str) -> String {
+    texto.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+/// Aplica as migrações ainda não aplicadas, cada uma em sua transação
+pub fn migrar(conn: &mut Connection) -> Result<()> {
+    let versao: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+        .context("Erro ao ler a versão do esquema")?;
+
+    for (indice, migracao) in MIGRACOES.iter().enumerate().skip(versao) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migracao)
+            .context(format!("Erro ao aplicar a migração {} do banco", indice + 1))?;
+        tx.pragma_update(None, "user_version", indice + 1)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+/// Abre (criando se preciso) o banco da pasta de resultados, já migrado
+pub fn abrir(output_dir: &Path) -> Result<Connection> {
+    let path = output_dir.join(ARQUIVO_BANCO);
+    let mut conn = Connection::open(&path)
+        .context(format!("Erro ao abrir o banco {}", path.display()))?;
+    // Processamento e consultas podem usar o banco ao mesmo tempo
+    conn.busy_timeout(Duration::from_secs(5))?;
+    conn.pragma_update(None, "foreign_keys", true)?;
+    migrar(&mut conn)?;
+    Ok(conn)
+}
+
+/// Substitui a licitação e suas propostas
+fn gravar_licitacao(tx: &Transaction, licitacao: &LicitacaoConsolidada, arquivo: &Path) -> Result<()> {
+    let chave = format!("{}-{}-{}", licitacao.uasg, licitacao.pregao, licitacao.processo);
+    tx.execute("DELETE FROM licitacoes WHERE chave = ?1", params![chave])?;
+    tx.execute(
+        "INSERT INTO licitacoes (chave, uasg, pregao, processo, total_propostas, valor_total, arquivo, atualizado_em)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            chave,
+            licitacao.uasg,
+            licitacao.pregao,
+            licitacao.processo,
+            licitacao.total_propostas as i64,
+            licitacao.valor_total,
+            arquivo.to_string_lossy(),
+            Utc::now().to_rfc3339(),
+        ],
+    )?;
+
+    let mut insert = tx.prepare_cached(
+        "INSERT INTO propostas (licitacao, uasg, pregao, processo, item, grupo, descricao, descricao_busca,
+             fornecedor, fornecedor_busca, cnpj, cnpj_digitos, situacao, valor_adjudicado, dados)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+    )?;
+    for proposta in &licitacao.propostas {
+        insert.execute(params![
+            chave,
+            proposta.uasg,
+            proposta.pregao,
+            proposta.processo,
+            proposta.item,
+            proposta.grupo,
+            proposta.descricao,
+            resultados::normalizar_busca(&proposta.descricao),
+            proposta.fornecedor,
+            resultados::normalizar_busca(&proposta.fornecedor),
+            proposta.cnpj,
+            somente_digitos(&proposta.cnpj),
+            proposta.situacao,
+            proposta.valor_adjudicado.map(|valor| valor.0),
+            serde_json::to_string(proposta)?,
+        ])?;
+    }
+
+    Ok(())
+}
+
+/// Grava as licitações (com o caminho do JSON de cada uma), substituindo as já existentes
+pub fn gravar_licitacoes<'a, I>(conn: &mut Connection, licitacoes: I) -> Result<usize>
+where
+    I: IntoIterator<Item = (&'a LicitacaoConsolidada, &'a Path)>,
+{
+    let tx = conn.transaction()?;
+    let mut total = 0;
+    for (licitacao, arquivo) in licitacoes {
+        gravar_licitacao(&tx, licitacao, arquivo)?;
+        total += 1;
+    }
+    tx.commit()?;
+    Ok(total)
+}
+
+/// Substitui os registros SICAF (o JSON do SICAF também é regravado por inteiro)
+pub fn gravar_sicaf(conn: &mut Connection, registros: &[SicafData]) -> Result<usize> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM sicaf", [])?;
+    {
+        let mut insert = tx.prepare(
+            "INSERT OR REPLACE INTO sicaf (cnpj_digitos, cnpj, empresa, situacao_cadastro, data_vencimento, municipio, uf, dados)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        for registro in registros {
+            insert.execute(params![
+                somente_digitos(&registro.cnpj),
+                registro.cnpj,
+                registro.empresa,
+                registro.situacao_cadastro,
+                registro.data_vencimento,
+                registro.municipio,
+                registro.uf,
+                serde_json::to_string(registro)?,
+            ])?;
+        }
+    }
+    tx.commit()?;
+    Ok(registros.len())
+}
+
+/// Atualiza o banco com o resultado de uma consolidação já gravada em JSON
+pub fn registrar_consolidacao(output_dir: &Path, consolidacao: &pdf_processor::Consolidacao) -> Result<usize> {
+    let arquivos: Vec<_> = consolidacao.licitacoes.iter()
+        .map(|agrupada| agrupada.destino.join(&agrupada.nome_arquivo))
+        .collect();
+    let mut conn = abrir(output_dir)?;
+    gravar_licitacoes(&mut conn, consolidacao.licitacoes.iter()
+        .zip(&arquivos)
+        .map(|(agrupada, arquivo)| (&agrupada.licitacao, arquivo.as_path())))
+}
+
+/// Atualiza a tabela SICAF da pasta de resultados
+pub fn registrar_sicaf(output_dir: &Path, registros: &[SicafData]) -> Result<usize> {
+    let mut conn = abrir(output_dir)?;
+    gravar_sicaf(&mut conn, registros)
+}
+
+/// Termo do usuário como padrão LIKE "contém", sem que `%` e `_` virem curingas
+fn padrao_contem(termo: &str) -> String {
+    let escapado = termo.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escapado)
+}
+
+/// Consulta propostas com filtros combinados (todos opcionais)
+///
+/// Fornecedor e descrição ignoram acentos e caixa; CNPJ compara apenas os dígitos.
+pub fn consultar_propostas(conn: &Connection, filtro: &FiltroPropostasSql) -> Result<ResultadoConsultaPropostas> {
+    let mut condicoes: Vec<&str> = Vec::new();
+    let mut valores: Vec<rusqlite::types::Value> = Vec::new();
+
+    let texto = |valor: &Option<String>| valor.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+
+    if let Some(fornecedor) = texto(&filtro.fornecedor) {
+        condicoes.push("fornecedor_busca LIKE ? ESCAPE '\\'");
+        valores.push(padrao_contem(&resultados::normalizar_busca(&fornecedor)).into());
+    }
+    if let Some(descricao) = texto(&filtro.descricao) {
+        condicoes.push("descricao_busca LIKE ? ESCAPE '\\'");
+        valores.push(padrao_contem(&resultados::normalizar_busca(&descricao)).into());
+    }
+    if let Some(cnpj) = texto(&filtro.cnpj).map(|c| somente_digitos(&c)).filter(|c| !c.is_empty()) {
+        condicoes.push("cnpj_digitos LIKE ? ESCAPE '\\'");
+        valores.push(padrao_contem(&cnpj).into());
+    }
+    if let Some(uasg) = texto(&filtro.uasg) {
+        condicoes.push("uasg = ?");
+        valores.push(uasg.into());
+    }
+    if let Some(pregao) = texto(&filtro.pregao) {
+        condicoes.push("pregao LIKE ? ESCAPE '\\'");
+        valores.push(padrao_contem(&pregao).into());
+    }
+    if let Some(minimo) = filtro.valor_min {
+        condicoes.push("valor_adjudicado >= ?");
+        valores.push(minimo.into());
+    }
+    if let Some(maximo) = filtro.valor_max {
+        condicoes.push("valor_adjudicado <= ?");
+        valores.push(maximo.into());
+    }
+
+    let onde = if condicoes.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", condicoes.join(" AND "))
+    };
+
+    let total: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM propostas{}", onde),
+        params_from_iter(valores.iter()),
+        |row| row.get(0),
+    )?;
+
+    let limite = filtro.limit.unwrap_or(LIMITE_CONSULTA_PADRAO);
+    let mut paginacao = valores.clone();
+    paginacao.push((limite as i64).into());
+    paginacao.push((filtro.offset.unwrap_or(0) as i64).into());
+
+    let mut consulta = conn.prepare(&format!(
+        "SELECT dados FROM propostas{} ORDER BY uasg, pregao, processo, CAST(item AS INTEGER), item, id LIMIT ? OFFSET ?",
+        onde
+    ))?;
+    let propostas = consulta
+        .query_map(params_from_iter(paginacao.iter()), |row| row.get::<_, String>(0))?
+        .map(|dados| -> Result<PropostaConsolidada> {
+            serde_json::from_str(&dados?).context("Proposta inválida no banco")
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(ResultadoConsultaPropostas { total: total as usize, propostas })
+}
+
+/// Descarta o conteúdo do banco e o preenche de novo a partir dos JSONs
+pub fn reconstruir(conn: &mut Connection, arquivos_licitacao: &[std::path::PathBuf], sicaf_json: Option<&Path>) -> Result<ReconstrucaoBanco> {
+    let mut reconstrucao = ReconstrucaoBanco::default();
+    let mut licitacoes = Vec::new();
+    for path in arquivos_licitacao {
+        match resultados::carregar_licitacao(path) {
+            Ok(licitacao) => licitacoes.push((licitacao, path.clone())),
+            Err(e) => reconstrucao.arquivos_com_erro.push(format!("{}: {:#}", path.display(), e)),
+        }
+    }
+
+    {
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM propostas", [])?;
+        tx.execute("DELETE FROM licitacoes", [])?;
+        tx.commit()?;
+    }
+    reconstrucao.licitacoes = gravar_licitacoes(conn, licitacoes.iter().map(|(l, p)| (l, p.as_path())))?;
+    reconstrucao.propostas = licitacoes.iter().map(|(l, _)| l.propostas.len()).sum();
+
+    let registros = match sicaf_json.filter(|p| p.exists()) {
+        Some(path) => match crate::sicaf_processor::carregar_sicaf_json(path) {
+            Ok(registros) => registros,
+            Err(e) => {
+                reconstrucao.arquivos_com_erro.push(format!("{}: {:#}", path.display(), e));
+                Vec::new()
+            }
+        },
+        None => Vec::new(),
+    };
+    reconstrucao.registros_sicaf = gravar_sicaf(conn, &registros)?;
+
+    Ok(reconstrucao)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proposta(item: &str, fornecedor: &str, cnpj: &str, valor: &str) -> PropostaConsolidada {
+        serde_json::from_value(serde_json::json!({
+            "uasg": "787000", "pregao": "90008/2024", "processo": "123", "item": item, "grupo": null,
+            "quantidade": "1", "descricao": "Caneta esferográfica 100% azul", "valor_estimado": "1,00",
+            "valor_adjudicado": valor, "fornecedor": fornecedor, "cnpj": cnpj, "marca_fabricante": "N/A",
+            "modelo_versao": "N/A", "responsavel": "N/A", "melhor_lance": "1,00", "tipo_formato": "individual"
+        })).unwrap()
+    }
+
+    fn licitacao(propostas: Vec<PropostaConsolidada>) -> LicitacaoConsolidada {
+        serde_json::from_value(serde_json::json!({
+            "uasg": "787000", "pregao": "90008/2024", "processo": "123",
+            "total_propostas": propostas.len(), "valor_total": 0.0, "propostas": propostas
+        })).unwrap()
+    }
+
+    fn banco(nome: &str) -> (std::path::PathBuf, Connection) {
+        let dir = std::env::temp_dir().join(format!("licitacao360_storage_{}_{}", nome, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let conn = abrir(&dir).unwrap();
+        (dir, conn)
+    }
+
+    #[test]
+    fn test_migracoes_versionadas_e_idempotentes() {
+        let (dir, conn) = banco("migracoes");
+        let versao: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap();
+        assert_eq!(versao, VERSAO_ESQUEMA);
+        drop(conn);
+
+        // Reabrir não reaplica as migrações
+        let conn = abrir(&dir).unwrap();
+        let indices: Vec<String> = conn
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'index' AND name LIKE 'idx_%' ORDER BY name").unwrap()
+            .query_map([], |row| row.get(0)).unwrap()
+            .map(|n| n.unwrap())
+            .collect();
+        assert!(indices.contains(&"idx_propostas_cnpj".to_string()));
+        assert!(indices.contains(&"idx_propostas_uasg".to_string()));
+        assert!(indices.contains(&"idx_propostas_pregao".to_string()));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_gravar_e_consultar_propostas() {
+        let (dir, mut conn) = banco("consulta");
+        let arquivo = dir.join("licitacao_787000-90008_2024-123.json");
+        let primeira = licitacao(vec![
+            proposta("1", "PAPELARIA SÃO JOSÉ LTDA", "12.345.678/0001-90", "1.000,00"),
+            proposta("2", "DISTRIBUIDORA 100% CENTRAL", "98.765.432/0001-10", "10,00"),
+        ]);
+        gravar_licitacoes(&mut conn, [(&primeira, arquivo.as_path())]).unwrap();
+
+        let filtro = |f: serde_json::Value| -> FiltroPropostasSql { serde_json::from_value(f).unwrap() };
+        let todos = consultar_propostas(&conn, &FiltroPropostasSql::default()).unwrap();
+        assert_eq!(todos.total, 2);
+
+        let resultado = consultar_propostas(&conn, &filtro(serde_json::json!({ "fornecedor": "sao jose" }))).unwrap();
+        assert_eq!(resultado.total, 1);
+        assert_eq!(resultado.propostas[0].item, "1");
+
+        let resultado = consultar_propostas(&conn, &filtro(serde_json::json!({ "cnpj": "98765432" }))).unwrap();
+        assert_eq!(resultado.propostas[0].fornecedor, "DISTRIBUIDORA 100% CENTRAL");
+
+        // "%" do usuário é literal, não curinga
+        assert_eq!(consultar_propostas(&conn, &filtro(serde_json::json!({ "fornecedor": "100%" }))).unwrap().total, 1);
+        assert_eq!(consultar_propostas(&conn, &filtro(serde_json::json!({ "fornecedor": "%" }))).unwrap().total, 1);
+
+        let resultado = consultar_propostas(&conn, &filtro(serde_json::json!({ "valor_min": 500.0, "uasg": "787000" }))).unwrap();
+        assert_eq!(resultado.total, 1);
+
+        // Regravar a mesma licitação substitui as propostas anteriores
+        let segunda = licitacao(vec![proposta("3", "OUTRA LTDA", "11.222.333/0001-81", "5,00")]);
+        gravar_licitacoes(&mut conn, [(&segunda, arquivo.as_path())]).unwrap();
+        let todos = consultar_propostas(&conn, &filtro(serde_json::json!({ "limit": 10 }))).unwrap();
+        assert_eq!(todos.total, 1);
+        assert_eq!(todos.propostas[0].item, "3");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
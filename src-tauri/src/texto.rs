@@ -0,0 +1,274 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use unicode_normalization::UnicodeNormalization;
+
+static RE_HIFENIZACAO: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\p{L})-[ \t]*\n[ \t]*(\p{Ll})").unwrap());
+
+static RE_ESPACOS: Lazy<Regex> = Lazy::new(|| Regex::new(r"[ \t]+").unwrap());
+
+/// Troca aspas e travessões tipográficos pelos equivalentes ASCII usados nos padrões
+fn caractere_normalizado(c: char) -> Option<char> {
+    match c {
+        '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{00ab}' | '\u{00bb}' => Some('"'),
+        '\u{2018}' | '\u{2019}' | '\u{201a}' => Some('\''),
+        '\u{2010}' | '\u{2011}' | '\u{2013}' | '\u{2014}' | '\u{2212}' => Some('-'),
+        // Espaços não separáveis viram espaço comum
+        '\u{00a0}' | '\u{2007}' | '\u{202f}' => Some(' '),
+        // Quebra de página do pdf_extract separa parágrafos
+        '\u{000c}' => Some('\n'),
+        '\n' | '\t' => Some(c),
+        // Hífen condicional e caracteres de largura zero não aparecem no texto
+        '\u{00ad}' | '\u{200b}' | '\u{200c}' | '\u{200d}' | '\u{feff}' => None,
+        c if c.is_control() => None,
+        c => Some(c),
+    }
+}
+
+/// Ligaduras que algumas fontes colocam na área de uso privado, fora do alcance do NFKC
+const LIGADURAS_USO_PRIVADO: [(char, &str); 2] = [('\u{f001}', "fi"), ('\u{f002}', "fl")];
+
+/// Caracteres que o NFKC trocaria por letras ou dígitos comuns ("Nº" viraria "No", "m²" viraria "m2")
+const PRESERVADOS_NFKC: [char; 4] = ['º', 'ª', '²', '³'];
+
+/// Troca os códigos de área de uso privado conhecidos pelo texto que representam
+///
+/// Impressoras virtuais embutem fontes "simbólicas" em que cada letra vira U+F000 + o código
+/// Latin-1 ("ç" sai como U+F0E7); a faixa de controle U+F080–U+F09F fica como está.
+fn substituir_uso_privado(c: char, saida: &mut String) {
+    if let Some((_, ligadura)) = LIGADURAS_USO_PRIVADO.iter().find(|(codigo, _)| *codigo == c) {
+        saida.push_str(ligadura);
+        return;
+    }
+    let latin1 = match c as u32 {
+        codigo @ (0xf020..=0xf07e | 0xf0a0..=0xf0ff) => char::from_u32(codigo - 0xf000),
+        _ => None,
+    };
+    saida.push(latin1.unwrap_or(c));
+}
+
+/// Acento combinante equivalente a um acento solto ("˜" → U+0303) e as letras que ele pode acentuar
+fn acento_solto(c: char) -> Option<(char, &'static str)> {
+    match c {
+        '\u{00b4}' => Some(('\u{0301}', "aeiouıAEIOU")),
+        '\u{02cb}' => Some(('\u{0300}', "aA")),
+        '\u{02dc}' => Some(('\u{0303}', "aonAON")),
+        '\u{02c6}' => Some(('\u{0302}', "aeoAEO")),
+        '\u{00a8}' => Some(('\u{0308}', "uU")),
+        '\u{00b8}' => Some(('\u{0327}', "cC")),
+        _ => None,
+    }
+}
+
+/// Junta acentos soltos à letra ("Situac¸˜ao" → "Situação", "Munic´ıpio" → "Município")
+///
+/// É o texto de PDFs gerados com LaTeX e de algumas impressoras virtuais: a cedilha vem depois
+/// do "c" e os demais acentos antes da vogal. Acento sem letra compatível fica como está.
+fn juntar_acentos_soltos(texto: &str) -> String {
+    let chars: Vec<char> = texto.chars().collect();
+    let mut saida = String::with_capacity(texto.len());
+    let mut i = 0;
+    while i < chars.len() {
+        match acento_solto(chars[i]) {
+            Some(('\u{0327}', _)) if saida.ends_with(['c', 'C']) => {
+                saida.push('\u{0327}');
+                i += 1;
+                continue;
+            }
+            Some((combinante, letras)) if combinante != '\u{0327}' => {
+                if let Some(&letra) = chars.get(i + 1).filter(|letra| letras.contains(**letra)) {
+                    saida.push(if letra == 'ı' { 'i' } else { letra });
+                    saida.push(combinante);
+                    i += 2;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        saida.push(chars[i]);
+        i += 1;
+    }
+    saida
+}
+
+/// NFKC (ligaduras "ﬁ" → "fi", letras decompostas recompostas), sem tocar em `PRESERVADOS_NFKC`
+fn nfkc_preservando(texto: &str) -> String {
+    let mut saida = String::with_capacity(texto.len());
+    let mut inicio = 0;
+    for (posicao, preservado) in texto.match_indices(PRESERVADOS_NFKC) {
+        saida.extend(texto[inicio..posicao].nfkc());
+        saida.push_str(preservado);
+        inicio = posicao + preservado.len();
+    }
+    saida.extend(texto[inicio..].nfkc());
+    saida
+}
+
+/// Normaliza o texto extraído do PDF antes da aplicação das regex
+///
+/// Antes de tudo, recupera acentos e ligaduras quebrados por impressoras virtuais: códigos
+/// de uso privado conhecidos, acentos soltos e normalização Unicode NFKC.
+/// Junta palavras hifenizadas na quebra de linha ("Adjudi-\ncado" → "Adjudicado"),
+/// colapsa espaços repetidos, normaliza aspas e remove caracteres de controle.
+/// As quebras de linha são mantidas (os padrões dependem delas), mas sequências
+/// de linhas em branco viram uma só.
+pub fn normalizar_texto(texto: &str) -> String {
+    let texto = texto.replace("\r\n", "\n").replace('\r', "\n");
+    let mut recuperado = String::with_capacity(texto.len());
+    for c in texto.chars() {
+        substituir_uso_privado(c, &mut recuperado);
+    }
+    let texto = nfkc_preservando(&juntar_acentos_soltos(&recuperado));
+    let texto: String = texto.chars().filter_map(caractere_normalizado).collect();
+
+    // Só junta quando a linha seguinte continua a palavra em minúscula,
+    // preservando "Item 1 -" e CNPJs/CPFs quebrados após o traço
+    let texto = RE_HIFENIZACAO.replace_all(&texto, "$1$2");
+
+    let mut linhas: Vec<String> = Vec::new();
+    for linha in texto.split('\n') {
+        let linha = RE_ESPACOS.replace_all(linha, " ").trim().to_string();
+        if linha.is_empty() && linhas.last().is_some_and(|anterior| anterior.is_empty()) {
+            continue;
+        }
+        linhas.push(linha);
+    }
+    linhas.join("\n")
+}
+
+static RE_HTML_IGNORADO: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)<!--.*?-->|<script\b.*?</script\s*>|<style\b.*?</style\s*>|<head\b.*?</head\s*>").unwrap()
+});
+
+static RE_ESPACOS_HTML: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+").unwrap());
+
+/// Tags que iniciam ou terminam um bloco e viram quebra de linha
+static RE_BLOCO_HTML: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)</?(?:br|p|div|tr|li|ul|ol|h[1-6]|table|thead|tbody|tfoot|section|article|caption)\b[^>]*>").unwrap()
+});
+
+static RE_TAG_HTML: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<[^>]*>").unwrap());
+
+static RE_ENTIDADE_HTML: Lazy<Regex> = Lazy::new(|| Regex::new(r"&(#[0-9]{1,7}|#[xX][0-9a-fA-F]{1,6}|[a-zA-Z]{2,8});").unwrap());
+
+/// Letras acentuadas do português pelo nome da entidade HTML em minúsculas
+const LETRAS_ACENTUADAS: [(&str, char); 17] = [
+    ("aacute", 'á'), ("agrave", 'à'), ("acirc", 'â'), ("atilde", 'ã'), ("auml", 'ä'),
+    ("eacute", 'é'), ("egrave", 'è'), ("ecirc", 'ê'),
+    ("iacute", 'í'),
+    ("oacute", 'ó'), ("ocirc", 'ô'), ("otilde", 'õ'), ("ouml", 'ö'),
+    ("uacute", 'ú'), ("uuml", 'ü'),
+    ("ccedil", 'ç'), ("ntilde", 'ñ'),
+];
+
+/// Caractere de uma entidade nomeada ("amp", "nbsp", "ccedil", "Atilde"...)
+fn entidade_nomeada(nome: &str) -> Option<char> {
+    let simbolo = match nome {
+        "nbsp" => ' ',
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "ordm" => 'º',
+        "ordf" => 'ª',
+        "deg" => '°',
+        "sect" => '§',
+        "ndash" => '–',
+        "mdash" => '—',
+        "laquo" => '«',
+        "raquo" => '»',
+        _ => {
+            // "Ccedil" é a maiúscula de "ccedil"
+            let maiuscula = nome.starts_with(|c: char| c.is_ascii_uppercase());
+            let minusculo = nome.to_ascii_lowercase();
+            let letra = LETRAS_ACENTUADAS.iter().find(|(entidade, _)| *entidade == minusculo)?.1;
+            return if maiuscula { letra.to_uppercase().next() } else { Some(letra) };
+        }
+    };
+    Some(simbolo)
+}
+
+/// Decodifica entidades nomeadas e numéricas; as desconhecidas ficam como estão
+pub fn decodificar_entidades_html(texto: &str) -> String {
+    RE_ENTIDADE_HTML.replace_all(texto, |caps: &regex::Captures| {
+        let entidade = &caps[1];
+        let caractere = if let Some(hex) = entidade.strip_prefix("#x").or_else(|| entidade.strip_prefix("#X")) {
+            u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+        } else if let Some(decimal) = entidade.strip_prefix('#') {
+            decimal.parse().ok().and_then(char::from_u32)
+        } else {
+            entidade_nomeada(entidade)
+        };
+        caractere.map_or_else(|| caps[0].to_string(), String::from)
+    }).into_owned()
+}
+
+/// Converte o HTML de uma ata em texto com as quebras de linha dos blocos
+///
+/// Como no navegador, as quebras do código-fonte não contam: só parágrafos, linhas de
+/// tabela e afins separam linhas, e células da mesma linha ficam separadas por espaço.
+pub fn html_para_texto(html: &str) -> String {
+    let html = RE_HTML_IGNORADO.replace_all(html, " ");
+    let html = RE_ESPACOS_HTML.replace_all(&html, " ");
+    let html = RE_BLOCO_HTML.replace_all(&html, "\n");
+    let texto = RE_TAG_HTML.replace_all(&html, " ");
+    decodificar_entidades_html(&texto)
+}
+
+/// Lê bytes de texto em UTF-8 ou, se não forem UTF-8 válido, em Latin-1 (comum em HTML antigo)
+pub fn decodificar_bytes(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_prefix(&[0xef, 0xbb, 0xbf]).unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(texto) => texto.to_string(),
+        Err(_) => bytes.iter().map(|&b| b as char).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalizar_texto() {
+        let texto = "Situação:\u{00a0}Adjudi-\r\ncado  e\tHomologado\u{0007}\n\n\n\u{201c}Marca\u{201d}  \u{2013} Item 1 -\nCaneta\u{000c}Pá-\n gina";
+        assert_eq!(
+            normalizar_texto(texto),
+            "Situação: Adjudicado e Homologado\n\n\"Marca\" - Item 1 -\nCaneta\nPágina"
+        );
+
+        // Traço seguido de maiúscula ou número não é hifenização
+        assert_eq!(normalizar_texto("FULANO-\nEMPRESA"), "FULANO-\nEMPRESA");
+        assert_eq!(normalizar_texto("12.345.678/0001-\n90"), "12.345.678/0001-\n90");
+    }
+
+    #[test]
+    fn test_normalizar_texto_recupera_acentos_e_ligaduras() {
+        // Fonte simbólica de impressora virtual e ligaduras
+        assert_eq!(normalizar_texto("PREG\u{f0c3}O \u{f001}xo \u{f002}uxo e\u{fb01}ciente"), "PREGÃO fixo fluxo eficiente");
+        // Acentos soltos do LaTeX e letras decompostas
+        assert_eq!(normalizar_texto("Situac\u{b8}\u{2dc}ao Munic\u{b4}\u{131}pio Respons\u{b4}avel"), "Situação Município Responsável");
+        assert_eq!(normalizar_texto("adjudicac\u{327}a\u{303}o"), "adjudicação");
+        // NFKC preserva ordinais e expoentes, mas desfaz a largura total
+        assert_eq!(normalizar_texto("Nº 1ª via 10 m² \u{ff21}"), "Nº 1ª via 10 m² A");
+        assert_eq!(normalizar_texto("c\u{b8}x"), "çx");
+    }
+    #[test]
+    fn test_html_para_texto() {
+        let html = "<html><head><title>Ata</title><style>td { color: red }</style></head><body>\n\
+            <h1>PREG&Atilde;O 90008/2024</h1><!-- cabeçalho -->\n\
+            <table><tr><td>Item 1 -</td>\n   <td>Caneta&nbsp;esferogr&aacute;fica</td></tr>\n\
+            <tr><td>Situa&#231;&#xE3;o:</td><td>Adjudicado &amp; Homologado</td></tr></table>\n\
+            <p>Pre&ccedil;o &lt; R$ 2,00 &desconhecida;<br>Fim</p></body></html>";
+        assert_eq!(
+            normalizar_texto(&html_para_texto(html)),
+            "\nPREGÃO 90008/2024\n\nItem 1 - Caneta esferográfica\n\nSituação: Adjudicado & Homologado\n\nPreço < R$ 2,00 &desconhecida;\nFim\n"
+        );
+    }
+
+    #[test]
+    fn test_decodificar_bytes_utf8_ou_latin1() {
+        assert_eq!(decodificar_bytes("Pregão".as_bytes()), "Pregão");
+        assert_eq!(decodificar_bytes(b"\xef\xbb\xbfAta"), "Ata");
+        assert_eq!(decodificar_bytes(b"Preg\xe3o"), "Pregão");
+    }
+}
@@ -1,5 +1,9 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use crate::i18n::{self, Idioma, MsgKey};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ProcessingArgs {
@@ -18,6 +22,155 @@ pub struct ProcessingStatus {
     pub total_files: usize,
     pub errors: Vec<String>,
     pub progress_percentage: f64,
+    #[serde(default)]
+    pub cancelled: bool,
+    /// Sinal de cancelamento compartilhado com o loop de processamento
+    #[serde(skip)]
+    pub cancel_flag: Arc<AtomicBool>,
+    /// Propostas extraídas na sessão, usadas pela prévia da consolidação
+    #[serde(skip)]
+    pub propostas: Arc<Vec<PropostaConsolidada>>,
+    /// Início da sessão (RFC 3339)
+    #[serde(default)]
+    pub started_at: String,
+    /// Fim da sessão (RFC 3339), None enquanto estiver em andamento
+    #[serde(default)]
+    pub finished_at: Option<String>,
+    /// Resultado final de uma sessão de diretório, consultado por `get_processing_result`
+    #[serde(skip)]
+    pub resultado: Option<Arc<Result<ProcessingResult, TauriError>>>,
+}
+
+/// Arquivo concluído numa sessão, identificado pelo caminho e pela data de modificação
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CompletedFile {
+    pub path: String,
+    /// Milissegundos desde a época Unix (None se o sistema não informar)
+    pub modified_ms: Option<u64>,
+}
+
+/// Sessão espelhada em Database/Config/processing_sessions.json para sobreviver a reinícios
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PersistedSession {
+    pub session_id: String,
+    pub input_dir: String,
+    pub output_dir: String,
+    pub verbose: bool,
+    pub total_files: usize,
+    pub started_at: String,
+    /// None enquanto a sessão não terminar (ou se o app fechou no meio)
+    pub finished_at: Option<String>,
+    #[serde(default)]
+    pub cancelled: bool,
+    #[serde(default)]
+    pub completed_files: Vec<CompletedFile>,
+    #[serde(default)]
+    pub errors: Vec<String>,
+    /// Critério usado para listar os arquivos, repetido na retomada
+    #[serde(default)]
+    pub varredura: OpcoesVarredura,
+}
+
+/// Critério de varredura recursiva de uma pasta de atas
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct OpcoesVarredura {
+    /// Profundidade máxima (1 = só a própria pasta, 0 = sem limite)
+    #[serde(default)]
+    pub max_depth: usize,
+    /// Padrões glob, relativos à pasta de entrada, de arquivos e pastas ignorados
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+}
+
+/// Sessão de processamento com seu identificador, retornada por `list_processing_sessions`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProcessingSession {
+    pub session_id: String,
+    #[serde(flatten)]
+    pub status: ProcessingStatus,
+}
+
+/// Situações de item reconhecidas no termo de homologação
+pub const SITUACAO_ADJUDICADO: &str = "Adjudicado e Homologado";
+pub const SITUACAO_DESERTO: &str = "Deserto";
+pub const SITUACAO_FRACASSADO: &str = "Fracassado";
+pub const SITUACAO_CANCELADO: &str = "Cancelado no julgamento";
+
+/// Porte do fornecedor declarado na ata, para a cota reservada da LC 123
+pub const PORTE_ME_EPP: &str = "ME/EPP";
+pub const PORTE_DEMAIS: &str = "Demais";
+
+/// Modalidade do certame, identificada pelo cabeçalho da ata
+pub const MODALIDADE_PREGAO: &str = "pregao";
+pub const MODALIDADE_DISPENSA: &str = "dispensa";
+pub const MODALIDADE_CONCORRENCIA: &str = "concorrencia";
+
+/// JSONs anteriores à modalidade eram sempre de pregão
+fn modalidade_padrao() -> String {
+    MODALIDADE_PREGAO.to_string()
+}
+
+/// Valor monetário em reais, gravado como número no JSON
+///
+/// Na leitura aceita também o texto do PDF ("R$ 1.234,56"), como nos JSONs antigos.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default, Serialize)]
+#[serde(transparent)]
+pub struct Valor(pub f64);
+
+impl Valor {
+    /// Converte o texto extraído do PDF; None para "N/A" e valores ilegíveis
+    pub fn de_texto(texto: &str) -> Option<Self> {
+        crate::pdf_processor::converter_valor_para_float(texto).ok().map(Valor)
+    }
+}
+
+/// Formato brasileiro com duas casas: "1.234,56"
+impl fmt::Display for Valor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&crate::exportacao::formatar_numero_br(self.0))
+    }
+}
+
+/// Valor como lido do JSON: número (esquema atual) ou texto (esquemas antigos)
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ValorGravado {
+    Numero(f64),
+    Texto(String),
+}
+
+impl<'de> Deserialize<'de> for Valor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match ValorGravado::deserialize(deserializer)? {
+            ValorGravado::Numero(numero) => Ok(Valor(numero)),
+            ValorGravado::Texto(texto) => Valor::de_texto(&texto)
+                .ok_or_else(|| serde::de::Error::custom(format!("valor monetário inválido: {}", texto))),
+        }
+    }
+}
+
+/// Lê um valor opcional: null, "N/A" e textos sem número viram None
+pub fn valor_opcional<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Valor>, D::Error> {
+    Ok(match Option::<ValorGravado>::deserialize(deserializer)? {
+        Some(ValorGravado::Numero(numero)) => Some(Valor(numero)),
+        Some(ValorGravado::Texto(texto)) => Valor::de_texto(&texto),
+        None => None,
+    })
+}
+
+/// Texto de um valor opcional para relatórios ("N/A" quando ausente)
+pub fn formatar_valor(valor: Option<Valor>) -> String {
+    valor.map_or_else(|| "N/A".to_string(), |valor| valor.to_string())
+}
+
+/// JSONs gerados antes do campo `situacao` só continham itens adjudicados
+fn situacao_padrao() -> String {
+    SITUACAO_ADJUDICADO.to_string()
+}
+
+/// Dados gravados antes da validação de CNPJ não são marcados como inválidos
+fn cnpj_valido_padrao() -> bool {
+    true
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -26,54 +179,276 @@ pub struct PropostaAdjudicada {
     pub grupo: Option<String>,
     pub descricao: String,
     pub quantidade: String,
-    pub valor_estimado: String,
-    pub valor_adjudicado: String,
+    #[serde(default, deserialize_with = "valor_opcional")]
+    pub valor_estimado: Option<Valor>,
+    #[serde(default, deserialize_with = "valor_opcional")]
+    pub valor_adjudicado: Option<Valor>,
+    /// Valor adjudicado como aparece no PDF, para auditoria
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub valor_adjudicado_raw: String,
     pub fornecedor: String,
     pub cnpj: String,
-    pub melhor_lance: String,
+    #[serde(default, deserialize_with = "valor_opcional")]
+    pub melhor_lance: Option<Valor>,
     pub responsavel: String,
     pub cpf_responsavel: String,
     pub marca_fabricante: String,
     pub modelo_versao: String,
-    pub tipo_formato: String, // "individual" ou "grupo"
+    pub tipo_formato: String, // nome do extrator do layout: "individual", "grupo", "termo_adjudicacao"...
+    #[serde(default = "situacao_padrao")]
+    pub situacao: String,
+    /// O CNPJ extraído confere com os dígitos verificadores
+    #[serde(default = "cnpj_valido_padrao")]
+    pub cnpj_valido: bool,
+    /// A descrição foi completada com as linhas seguintes do bloco do item
+    #[serde(default)]
+    pub descricao_reparada: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub descricao_original: Option<String>,
+    /// Justificativa da adjudicação e notas do pregoeiro no bloco do item
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub observacoes: Option<String>,
+    /// `PORTE_ME_EPP` ou `PORTE_DEMAIS`; None quando a ata não informa
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub porte: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PropostaConsolidada {
     pub uasg: String,
+    /// Número do certame (pregão, dispensa ou concorrência)
     pub pregao: String,
+    #[serde(default = "modalidade_padrao")]
+    pub modalidade: String,
     pub processo: String,
     pub item: String,
     pub grupo: Option<String>,
     pub quantidade: String,
     pub descricao: String,
-    pub valor_estimado: String,
-    pub valor_adjudicado: String,
+    #[serde(default, deserialize_with = "valor_opcional")]
+    pub valor_estimado: Option<Valor>,
+    #[serde(default, deserialize_with = "valor_opcional")]
+    pub valor_adjudicado: Option<Valor>,
+    /// Valor adjudicado como aparece no PDF, para auditoria
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub valor_adjudicado_raw: String,
     pub fornecedor: String,
     pub cnpj: String,
     pub marca_fabricante: String,
     pub modelo_versao: String,
     pub responsavel: String,
-    pub melhor_lance: String,
+    #[serde(default, deserialize_with = "valor_opcional")]
+    pub melhor_lance: Option<Valor>,
+    /// Valores unitários, preenchidos apenas nas atas com "Valor unitário estimado"
+    #[serde(default, deserialize_with = "valor_opcional")]
+    pub valor_unitario_estimado: Option<Valor>,
+    #[serde(default, deserialize_with = "valor_opcional")]
+    pub valor_unitario_adjudicado: Option<Valor>,
+    /// Total do item, somado nos totais da licitação
+    #[serde(default, deserialize_with = "valor_opcional")]
+    pub valor_total_item: Option<Valor>,
+    /// O total do item foi calculado (quantidade × valor unitário), e não lido da ata
+    #[serde(default)]
+    pub valor_calculado: bool,
     pub tipo_formato: String,
+    /// Situação do item; itens desertos, fracassados ou cancelados não têm fornecedor
+    #[serde(default = "situacao_padrao")]
+    pub situacao: String,
+    #[serde(default = "cnpj_valido_padrao")]
+    pub cnpj_valido: bool,
+    #[serde(default)]
+    pub descricao_reparada: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub descricao_original: Option<String>,
+    /// Justificativa da adjudicação e notas do pregoeiro, em um só parágrafo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub observacoes: Option<String>,
+    /// `PORTE_ME_EPP` ou `PORTE_DEMAIS`; None quando a ata não informa
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub porte: Option<String>,
+    /// Histórico de lances do item, em ordem cronológica
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lances: Vec<LanceItem>,
+    /// Todos os fornecedores que apresentaram proposta para o item
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub participantes: Vec<ParticipanteItem>,
+    /// Data de homologação da ata de origem
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub data_homologacao: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_homologacao_iso: Option<String>,
+    /// Quem assinou a homologação da ata (o `responsavel` da proposta é o da adjudicação)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub responsavel_homologacao: Option<ResponsavelHomologacao>,
+    /// Nome do arquivo (PDF, HTML ou TXT) de onde a proposta foi extraída; ausente nos JSONs antigos
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arquivo_origem: Option<String>,
+    /// Origem dos campos que não vieram do cabeçalho (ex.: "pregao" -> "nome_do_arquivo")
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub origem: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub avisos: Vec<String>,
+    /// Valor estimado menos o adjudicado, no total do item (None sem um dos dois)
+    #[serde(default, deserialize_with = "valor_opcional")]
+    pub economia_absoluta: Option<Valor>,
+    /// Economia em % do valor estimado
+    #[serde(default)]
+    pub economia_percentual: Option<f64>,
+}
+
+impl PropostaConsolidada {
+    /// Total do item; propostas gravadas antes de `valor_total_item` usam o valor adjudicado
+    pub fn total_do_item(&self) -> Option<Valor> {
+        self.valor_total_item.or(self.valor_adjudicado)
+    }
+
+    /// Valor estimado do item inteiro, na mesma base de `total_do_item`
+    ///
+    /// Nas atas com valores unitários o estimado também é unitário e é multiplicado pela quantidade.
+    pub fn estimado_do_item(&self) -> Option<Valor> {
+        if !self.valor_calculado {
+            return self.valor_estimado;
+        }
+        let quantidade = Valor::de_texto(&self.quantidade)?;
+        self.valor_estimado.map(|valor| Valor(valor.0 * quantidade.0))
+    }
+
+    /// Preenche `economia_absoluta` e `economia_percentual` a partir dos valores do item
+    pub fn calcular_economia(&mut self) {
+        (self.economia_absoluta, self.economia_percentual) = economia(self.estimado_do_item(), self.total_do_item());
+    }
+}
+
+/// Economia de um item: estimado menos adjudicado, em reais e em % do estimado
+///
+/// Sem um dos valores não há economia; com estimado zero, só o percentual fica vazio.
+pub fn economia(valor_estimado: Option<Valor>, valor_adjudicado: Option<Valor>) -> (Option<Valor>, Option<f64>) {
+    let Some((estimado, adjudicado)) = valor_estimado.zip(valor_adjudicado) else {
+        return (None, None);
+    };
+    let absoluta = estimado.0 - adjudicado.0;
+    let percentual = (estimado.0 > 0.0).then(|| absoluta / estimado.0 * 100.0);
+    (Some(Valor(absoluta)), percentual)
+}
+
+/// Totais de economia de um conjunto de itens adjudicados
+///
+/// Os totais somam só os itens com valor estimado e adjudicado, para que itens sem estimado
+/// não entrem no denominador do percentual; esses itens são contados à parte.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ResumoEconomia {
+    pub valor_estimado_total: f64,
+    pub valor_adjudicado_total: f64,
+    pub economia_total: f64,
+    pub economia_percentual: Option<f64>,
+    pub itens_sem_valor_estimado: usize,
+}
+
+impl ResumoEconomia {
+    /// Soma os pares (estimado, adjudicado) dos itens; itens sem valor adjudicado são ignorados
+    pub fn somar(valores: impl IntoIterator<Item = (Option<Valor>, Option<Valor>)>) -> Self {
+        let mut resumo = Self::default();
+        for (estimado, adjudicado) in valores {
+            match (estimado, adjudicado) {
+                (Some(estimado), Some(adjudicado)) => {
+                    resumo.valor_estimado_total += estimado.0;
+                    resumo.valor_adjudicado_total += adjudicado.0;
+                }
+                (None, Some(_)) => resumo.itens_sem_valor_estimado += 1,
+                _ => {}
+            }
+        }
+        resumo.economia_total = resumo.valor_estimado_total - resumo.valor_adjudicado_total;
+        resumo.economia_percentual = (resumo.valor_estimado_total > 0.0)
+            .then(|| resumo.economia_total / resumo.valor_estimado_total * 100.0);
+        resumo
+    }
+
+    /// Totais das propostas adjudicadas
+    pub fn das_propostas<'a>(propostas: impl IntoIterator<Item = &'a PropostaConsolidada>) -> Self {
+        Self::somar(propostas.into_iter()
+            .filter(|p| p.situacao == SITUACAO_ADJUDICADO)
+            .map(|p| (p.estimado_do_item(), p.total_do_item())))
+    }
+}
+
+/// Valor adjudicado por porte do fornecedor (ME/EPP e demais)
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ResumoPorte {
+    pub valor_me_epp: f64,
+    pub itens_me_epp: usize,
+    pub valor_demais: f64,
+    pub itens_demais: usize,
+    /// Itens adjudicados cuja ata não informa o porte
+    pub itens_sem_porte: usize,
+    /// Parcela do valor classificado que foi para ME/EPP, em %
+    pub percentual_me_epp: Option<f64>,
+}
+
+impl ResumoPorte {
+    /// Soma os pares (porte, valor) dos itens adjudicados
+    pub fn somar<'a>(itens: impl IntoIterator<Item = (Option<&'a str>, Option<Valor>)>) -> Self {
+        let mut resumo = Self::default();
+        for (porte, valor) in itens {
+            let valor = valor.map_or(0.0, |v| v.0);
+            match porte {
+                Some(PORTE_ME_EPP) => {
+                    resumo.valor_me_epp += valor;
+                    resumo.itens_me_epp += 1;
+                }
+                Some(PORTE_DEMAIS) => {
+                    resumo.valor_demais += valor;
+                    resumo.itens_demais += 1;
+                }
+                _ => resumo.itens_sem_porte += 1,
+            }
+        }
+        let classificado = resumo.valor_me_epp + resumo.valor_demais;
+        resumo.percentual_me_epp = (classificado > 0.0).then(|| resumo.valor_me_epp / classificado * 100.0);
+        resumo
+    }
+
+    /// Totais das propostas adjudicadas
+    pub fn das_propostas<'a>(propostas: impl IntoIterator<Item = &'a PropostaConsolidada>) -> Self {
+        Self::somar(propostas.into_iter()
+            .filter(|p| p.situacao == SITUACAO_ADJUDICADO)
+            .map(|p| (p.porte.as_deref(), p.total_do_item())))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LicitacaoConsolidada {
     pub uasg: String,
     pub pregao: String,
+    #[serde(default = "modalidade_padrao")]
+    pub modalidade: String,
     pub processo: String,
     pub total_propostas: usize,
     pub valor_total: f64,
     pub propostas: Vec<PropostaConsolidada>,
+    #[serde(default)]
+    pub itens_adjudicados: usize,
+    #[serde(default)]
+    pub itens_desertos: usize,
+    #[serde(default)]
+    pub itens_fracassados: usize,
+    #[serde(default)]
+    pub itens_cancelados: usize,
 }
 
+/// Conteúdo do consolidado.json: todas as licitações de uma gravação, pela chave de consolidação
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ConsolidadoJson {
+    #[serde(default)]
+    pub schema_version: u32,
     pub data_geracao: String,
     pub total_licitacoes: usize,
     pub total_propostas: usize,
     pub valor_total_geral: f64,
+    #[serde(default)]
+    pub processamento_interrompido: bool,
     pub licitacoes: HashMap<String, LicitacaoConsolidada>,
 }
 
@@ -81,14 +456,64 @@ pub struct ConsolidadoJson {
 pub struct RelatorioLicitacao {
     pub uasg: String,
     pub pregao: String,
+    #[serde(default = "modalidade_padrao")]
+    pub modalidade: String,
     pub processo: String,
     pub data_homologacao: String,
+    /// Data de homologação em RFC 3339 (horário de Brasília), quando o texto pôde ser interpretado
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_homologacao_iso: Option<String>,
+    /// Assinatura da homologação: nome, cargo e matrícula separados por vírgula
     pub responsavel: String,
+    #[serde(default)]
+    pub responsavel_nome: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub responsavel_cargo: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub responsavel_matricula: Option<String>,
     pub valor_total: f64,
     pub propostas: Vec<PropostaAdjudicada>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub origem: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub avisos: Vec<String>,
+    /// O PDF tinha pouco texto extraível (provavelmente escaneado)
+    #[serde(default)]
+    pub provavelmente_escaneado: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Assinatura da homologação ("FULANO DE TAL, Ordenador de Despesas, matrícula 123456")
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct ResponsavelHomologacao {
+    pub nome: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cargo: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matricula: Option<String>,
+}
+
+impl ResponsavelHomologacao {
+    /// Texto concatenado, no formato do antigo campo `responsavel`
+    pub fn texto(&self) -> String {
+        let mut partes = vec![self.nome.clone()];
+        partes.extend(self.cargo.clone());
+        partes.extend(self.matricula.as_ref().map(|m| format!("matrícula {}", m)));
+        partes.join(", ")
+    }
+}
+
+/// Proposta de um participante na tabela "Propostas do Item"
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ParticipanteItem {
+    pub fornecedor: String,
+    pub cnpj: String,
+    pub valor_proposta: String,
+    pub porte_me_epp: bool,
+    /// Situação da proposta na ata (ex.: "Aceita", "Desclassificada")
+    pub classificacao: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct LanceItem {
     pub data_hora: String,
     pub participante: String,
@@ -101,23 +526,282 @@ pub struct ProcessingResult {
     pub message: String,
     pub propostas: Vec<PropostaConsolidada>,
     pub total_processed: usize,
+    /// Primeiro JSON gravado (o único, quando o PDF é de uma só licitação)
     pub json_file_path: Option<String>,
+    /// Todos os JSONs de licitação gravados pelo processamento
+    #[serde(default)]
+    pub json_file_paths: Vec<String>,
     pub session_id: Option<String>,
+    /// PDFs pulados por já constarem no índice de processados
+    #[serde(default)]
+    pub skipped_files: Vec<String>,
+    /// Arquivos processados com sucesso por formato ("pdf", "html", "txt")
+    #[serde(default)]
+    pub arquivos_por_tipo: BTreeMap<String, usize>,
+    /// Prévia do que seria processado, quando chamado com `dry_run`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<DryRunReport>,
+    /// Tempos e propostas de cada arquivo processado nesta execução
+    #[serde(default)]
+    pub metrics: Vec<FileProcessingMetrics>,
+}
+
+/// Desempenho da extração de um arquivo, para identificar PDFs problemáticos
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct FileProcessingMetrics {
+    pub file: String,
+    pub bytes: u64,
+    /// Extração do texto, incluindo o OCR quando necessário
+    pub extract_ms: u64,
+    /// Regex dos extratores e gravação dos relatórios do arquivo
+    pub parse_ms: u64,
+    pub propostas: usize,
+    /// Layouts detectados ("individual", "grupo"...), separados por vírgula; vazio se nenhum
+    pub formato: String,
+}
+
+impl FileProcessingMetrics {
+    pub fn total_ms(&self) -> u64 {
+        self.extract_ms + self.parse_ms
+    }
+}
+
+/// Desempenho do processamento gravado no resumo_geral.json
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ResumoDesempenho {
+    pub arquivos: usize,
+    /// Soma dos tempos dos arquivos (com workers em paralelo, maior que o tempo de relógio)
+    pub tempo_total_ms: u64,
+    pub tempo_medio_ms: u64,
+    pub arquivo_mais_lento: String,
+    pub tempo_mais_lento_ms: u64,
+}
+
+impl ResumoDesempenho {
+    /// Totais das métricas por arquivo; None se nenhum arquivo foi medido
+    pub fn das_metricas(metricas: &[FileProcessingMetrics]) -> Option<Self> {
+        let mais_lento = metricas.iter().max_by_key(|m| m.total_ms())?;
+        let tempo_total_ms: u64 = metricas.iter().map(FileProcessingMetrics::total_ms).sum();
+        Some(Self {
+            arquivos: metricas.len(),
+            tempo_total_ms,
+            tempo_medio_ms: tempo_total_ms / metricas.len() as u64,
+            arquivo_mais_lento: mais_lento.file.clone(),
+            tempo_mais_lento_ms: mais_lento.total_ms(),
+        })
+    }
+}
+
+/// Arquivo encontrado por um `dry_run` de `process_pdf_directory`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DryRunArquivo {
+    pub caminho: String,
+    pub tamanho: u64,
+    /// O conteúdo já consta no índice de PDFs processados
+    pub ja_processado: bool,
+}
+
+/// O que `process_pdf_directory` faria, sem extrair texto nem gravar resultados
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DryRunReport {
+    pub input_dir: String,
+    pub arquivos: Vec<DryRunArquivo>,
+    pub total_arquivos: usize,
+    pub ja_processados: usize,
+    pub total_bytes: u64,
+    /// Bytes dos arquivos que seriam processados (sem os já processados, se forem pulados)
+    pub bytes_a_processar: u64,
+    /// Média das execuções anteriores (None antes da primeira execução medida)
+    pub bytes_por_segundo: Option<f64>,
+    pub tempo_estimado_segundos: Option<f64>,
+}
+
+/// Payload do evento `processing://progress`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProgressEvent {
+    pub session_id: String,
+    pub processed: usize,
+    pub total: usize,
+    pub current_file: Option<String>,
+    pub percentage: f64,
+    /// Tempo desde o início da operação, para o frontend estimar o restante
+    #[serde(default)]
+    pub elapsed_ms: u64,
+}
+
+/// Payload do evento `watcher://processed`: um PDF processado pelo observador da pasta
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WatcherEvent {
+    pub file_path: String,
+    pub success: bool,
+    pub message: String,
+    pub total_propostas: usize,
+    #[serde(default)]
+    pub avisos: Vec<String>,
+}
+
+/// Resultado de `validate_pdf_file`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PdfValidation {
+    Valid,
+    /// Exige senha para abrir
+    Encrypted,
+    /// Não existe, não tem extensão .pdf ou não começa com o cabeçalho %PDF-
+    NotAPdf,
+    Corrupted,
+}
+
+/// Categorias de erro retornadas ao frontend (serializadas pelo nome, ex.: "FileSystemError")
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    FileSystemError,
+    NotFound,
+    ValidationError,
+    ParseError,
+    ProcessingError,
+    SerializationError,
+    SystemError,
+    NetworkError,
+    Unauthorized,
+    /// PDF que não abre sem senha
+    PdfEncrypted,
+    /// Caminho fora das pastas que o app pode acessar
+    PermissionDenied,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TauriError {
-    pub error_type: String,
+    pub error_type: ErrorKind,
     pub message: String,
     pub details: Option<String>,
+    /// Chave da mensagem, para o frontend traduzi-la de novo (ausente nas mensagens ainda não catalogadas)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_key: Option<MsgKey>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub message_args: BTreeMap<String, String>,
+}
+
+impl TauriError {
+    pub fn new(error_type: ErrorKind, message: impl Into<String>, details: impl Into<Option<String>>) -> Self {
+        Self {
+            error_type,
+            message: message.into(),
+            details: details.into(),
+            message_key: None,
+            message_args: BTreeMap::new(),
+        }
+    }
+
+    /// Erro com a mensagem do catálogo no idioma configurado, levando a chave e os argumentos
+    pub fn traduzido(error_type: ErrorKind, chave: MsgKey, args: &i18n::Args, details: impl Into<Option<String>>) -> Self {
+        Self {
+            message_key: Some(chave),
+            message_args: i18n::args_texto(args),
+            ..Self::new(error_type, i18n::t(chave, args), details)
+        }
+    }
+
+    pub fn filesystem(message: impl Into<String>, details: impl Into<Option<String>>) -> Self {
+        Self::new(ErrorKind::FileSystemError, message, details)
+    }
+
+    pub fn not_found(message: impl Into<String>, details: impl Into<Option<String>>) -> Self {
+        Self::new(ErrorKind::NotFound, message, details)
+    }
+
+    pub fn validation(message: impl Into<String>, details: impl Into<Option<String>>) -> Self {
+        Self::new(ErrorKind::ValidationError, message, details)
+    }
+
+    pub fn parse(message: impl Into<String>, details: impl Into<Option<String>>) -> Self {
+        Self::new(ErrorKind::ParseError, message, details)
+    }
+
+    pub fn processing(message: impl Into<String>, details: impl Into<Option<String>>) -> Self {
+        Self::new(ErrorKind::ProcessingError, message, details)
+    }
+
+    pub fn serialization(message: impl Into<String>, details: impl Into<Option<String>>) -> Self {
+        Self::new(ErrorKind::SerializationError, message, details)
+    }
+
+    pub fn system(message: impl Into<String>, details: impl Into<Option<String>>) -> Self {
+        Self::new(ErrorKind::SystemError, message, details)
+    }
+
+    pub fn network(message: impl Into<String>, details: impl Into<Option<String>>) -> Self {
+        Self::new(ErrorKind::NetworkError, message, details)
+    }
+
+    pub fn pdf_encrypted(message: impl Into<String>, details: impl Into<Option<String>>) -> Self {
+        Self::new(ErrorKind::PdfEncrypted, message, details)
+    }
+
+    pub fn permission_denied(message: impl Into<String>, details: impl Into<Option<String>>) -> Self {
+        Self::new(ErrorKind::PermissionDenied, message, details)
+    }
+}
+
+impl std::fmt::Display for TauriError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}: {}", self.error_type, self.message)?;
+        if let Some(details) = &self.details {
+            write!(f, " ({})", details)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TauriError {}
+
+/// Erros dos processadores (anyhow) chegam ao frontend com a cadeia de contexto completa
+impl From<anyhow::Error> for TauriError {
+    fn from(e: anyhow::Error) -> Self {
+        if crate::metadados_pdf::eh_pdf_protegido(&e) {
+            return Self::pdf_encrypted(format!("{:#}", e), None);
+        }
+        Self::processing(format!("{:#}", e), None)
+    }
+}
+
+impl From<std::io::Error> for TauriError {
+    fn from(e: std::io::Error) -> Self {
+        match e.kind() {
+            std::io::ErrorKind::NotFound => Self::not_found(e.to_string(), None),
+            _ => Self::filesystem(e.to_string(), None),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ProcessingLog {
     pub timestamp: String,
     pub message: String,
-    pub log_type: String, // 'info', 'success', 'error', 'progress'
+    pub log_type: String, // 'info', 'success', 'warning', 'error', 'progress', 'progress_summary'
     pub session_id: Option<String>,
+    /// Posição do arquivo no lote (apenas entradas de progresso por arquivo)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_index: Option<usize>,
+    /// Intervalo de arquivos coberto por uma entrada compactada ('progress_summary')
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resumo: Option<ResumoProgresso>,
+}
+
+/// Resumo de várias entradas de progresso compactadas em uma só
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ResumoProgresso {
+    pub primeiro_arquivo: usize,
+    pub ultimo_arquivo: usize,
+    pub entradas_compactadas: usize,
+}
+
+/// Logs de processamento separados entre entradas comuns e resumos compactados
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProcessingLogsView {
+    pub logs: Vec<ProcessingLog>,
+    pub progress_summaries: Vec<ProcessingLog>,
+    pub total_errors: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -129,6 +813,134 @@ pub struct AppConfig {
     pub max_logs: usize,
     pub created_at: String,
     pub updated_at: String,
+    #[serde(default)]
+    pub routing_rules: Vec<RoutingRule>,
+    /// Número de PDFs processados em paralelo (0 = automático, um por núcleo)
+    #[serde(default)]
+    pub max_parallel_jobs: usize,
+    /// Endereço de escuta da API HTTP somente leitura (None = apenas 127.0.0.1)
+    #[serde(default)]
+    pub http_api_bind_address: Option<String>,
+    /// Tentar OCR (tesseract) em PDFs escaneados
+    #[serde(default)]
+    pub ocr_enabled: bool,
+    /// Minutos que uma sessão finalizada permanece disponível antes de ser descartada
+    #[serde(default = "retencao_sessoes_padrao")]
+    pub session_retention_minutes: u64,
+    /// Mover os PDFs processados com sucesso para a subpasta Processados
+    #[serde(default)]
+    pub archive_processed: bool,
+    /// Pasta de dados escolhida pelo usuário (None = pasta padrão)
+    #[serde(default)]
+    pub database_root: Option<String>,
+    /// Tempo máximo para extrair o texto de cada PDF, em segundos (0 = sem limite)
+    #[serde(default = "timeout_extracao_padrao")]
+    pub pdf_extraction_timeout_secs: u64,
+    /// Tamanho máximo de um PDF importado para a pasta de PDFs, em MB (0 = sem limite)
+    #[serde(default = "tamanho_importacao_padrao")]
+    pub max_import_size_mb: u64,
+    /// Similaridade mínima (0 a 1) para aceitar um registro SICAF pela razão social quando o CNPJ não bate
+    #[serde(default = "limiar_razao_social_padrao")]
+    pub sicaf_name_match_threshold: f64,
+    /// Política de retenção aplicada ao final de cada processamento de diretório (None = desligada)
+    #[serde(default)]
+    pub retention_policy: Option<RetentionPolicy>,
+    /// Idioma das mensagens de erro e de resultado
+    #[serde(default)]
+    pub language: Idioma,
+    /// Profundidade máxima da varredura de pastas de atas (1 = só a própria pasta, 0 = sem limite)
+    #[serde(default = "profundidade_varredura_padrao")]
+    pub scan_max_depth: usize,
+    /// Padrões glob ignorados na varredura de pastas de atas
+    #[serde(default = "padroes_exclusao_padrao")]
+    pub scan_exclude_patterns: Vec<String>,
+    /// Máximo de propostas copiadas de uma vez por `get_propostas_tsv` (0 = sem limite)
+    #[serde(default = "limite_linhas_tsv_padrao")]
+    pub clipboard_max_rows: usize,
+    /// Bytes de PDF processados por execuções completas de diretório (para a estimativa do dry-run)
+    #[serde(default)]
+    pub processed_bytes_total: u64,
+    /// Tempo gasto nessas execuções, em segundos
+    #[serde(default)]
+    pub processing_seconds_total: f64,
+    /// Pastas fora da Database que os comandos de arquivo podem ler (ex.: PDFs de um compartilhamento)
+    #[serde(default)]
+    pub allowed_external_dirs: Vec<String>,
+    /// Dias em que uma consulta de CNPJ na BrasilAPI continua valendo no cache
+    #[serde(default = "validade_cache_cnpj_padrao")]
+    pub cnpj_cache_days: u64,
+}
+
+impl AppConfig {
+    /// Vazão média das execuções anteriores, em bytes por segundo
+    pub fn bytes_por_segundo_medio(&self) -> Option<f64> {
+        (self.processed_bytes_total > 0 && self.processing_seconds_total > 0.0)
+            .then(|| self.processed_bytes_total as f64 / self.processing_seconds_total)
+    }
+}
+
+/// Profundidade padrão da varredura: a pasta escolhida e até três níveis de subpastas
+pub const PROFUNDIDADE_VARREDURA_PADRAO: usize = 4;
+
+fn profundidade_varredura_padrao() -> usize {
+    PROFUNDIDADE_VARREDURA_PADRAO
+}
+
+/// Padrões ignorados por padrão: pastas e arquivos ocultos e dependências de projetos
+pub const PADROES_EXCLUSAO_PADRAO: [&str; 2] = ["**/.*", "**/node_modules"];
+
+pub fn padroes_exclusao_padrao() -> Vec<String> {
+    PADROES_EXCLUSAO_PADRAO.iter().map(|p| p.to_string()).collect()
+}
+
+/// Limite padrão de linhas copiadas para a área de transferência (o webview guarda tudo em memória)
+pub const LIMITE_LINHAS_TSV_PADRAO: usize = 5000;
+
+fn limite_linhas_tsv_padrao() -> usize {
+    LIMITE_LINHAS_TSV_PADRAO
+}
+
+/// Retenção padrão das sessões de processamento finalizadas, em minutos
+pub const RETENCAO_SESSOES_PADRAO: u64 = 30;
+
+fn retencao_sessoes_padrao() -> u64 {
+    RETENCAO_SESSOES_PADRAO
+}
+
+/// Tempo máximo padrão para extrair o texto de um PDF, em segundos
+pub const TIMEOUT_EXTRACAO_PADRAO_SECS: u64 = 60;
+
+fn timeout_extracao_padrao() -> u64 {
+    TIMEOUT_EXTRACAO_PADRAO_SECS
+}
+
+/// Tamanho máximo padrão de um PDF importado, em MB
+pub const TAMANHO_IMPORTACAO_PADRAO_MB: u64 = 200;
+
+fn tamanho_importacao_padrao() -> u64 {
+    TAMANHO_IMPORTACAO_PADRAO_MB
+}
+
+/// Limiar padrão de similaridade (Jaro-Winkler) entre razões sociais normalizadas
+pub const LIMIAR_RAZAO_SOCIAL_PADRAO: f64 = 0.92;
+
+fn limiar_razao_social_padrao() -> f64 {
+    LIMIAR_RAZAO_SOCIAL_PADRAO
+}
+
+/// Validade padrão das consultas de CNPJ em cache, em dias
+pub const VALIDADE_CACHE_CNPJ_PADRAO_DIAS: u64 = 30;
+
+fn validade_cache_cnpj_padrao() -> u64 {
+    VALIDADE_CACHE_CNPJ_PADRAO_DIAS
+}
+
+/// Regra de roteamento da saída por licitação (a primeira regra que casar vence)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RoutingRule {
+    pub uasg_prefix: Option<String>,
+    pub pregao_year: Option<String>,
+    pub target_directory: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -138,6 +950,33 @@ pub struct ConfigResult {
     pub config: Option<AppConfig>,
 }
 
+/// Origem de `DadosCnpjOnline`: consulta feita agora na BrasilAPI
+pub const FONTE_CNPJ_BRASIL_API: &str = "brasilapi";
+/// Consulta anterior, ainda válida, lida do cnpj_cache.json
+pub const FONTE_CNPJ_CACHE: &str = "cache";
+/// Sem rede ou a API falhou: só o CNPJ é conhecido
+pub const FONTE_CNPJ_OFFLINE: &str = "offline";
+
+/// Dados públicos de um CNPJ (Receita Federal, via BrasilAPI) para fornecedores fora do SICAF
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct DadosCnpjOnline {
+    /// Só os dígitos
+    pub cnpj: String,
+    pub razao_social: Option<String>,
+    pub nome_fantasia: Option<String>,
+    /// "ATIVA", "BAIXADA", "INAPTA"...
+    pub situacao_cadastral: Option<String>,
+    pub municipio: Option<String>,
+    pub uf: Option<String>,
+    /// `FONTE_CNPJ_BRASIL_API`, `FONTE_CNPJ_CACHE` ou `FONTE_CNPJ_OFFLINE`
+    pub fonte: String,
+    /// Momento da consulta na API (RFC 3339); None quando offline
+    pub consultado_em: Option<String>,
+    /// Motivo da falha quando offline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub erro: Option<String>,
+}
+
 /// Estrutura para dados do SICAF
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SicafData {
@@ -151,10 +990,63 @@ pub struct SicafData {
     pub endereco: Option<String>,
     pub municipio: Option<String>,
     pub uf: Option<String>,
+    /// Só os dígitos, com DDD ("6133334444")
     pub telefone: Option<String>,
+    /// Primeiro e-mail válido do contato
     pub email: Option<String>,
+    /// Todos os e-mails válidos do contato
+    #[serde(default)]
+    pub emails: Vec<String>,
+    /// Telefone e e-mail como vieram no PDF, para auditoria
+    #[serde(default)]
+    pub contato_raw: Option<String>,
     pub cpf_responsavel: Option<String>,
     pub nome_responsavel: Option<String>,
+    #[serde(default = "cnpj_valido_padrao")]
+    pub cnpj_valido: bool,
+    /// Cadastro vencido na data do processamento (None = data de vencimento ausente ou inválida)
+    #[serde(default)]
+    pub cadastro_vencido: Option<bool>,
+    /// Níveis cadastrados ("I - Credenciamento", "II - Habilitação Jurídica"...)
+    #[serde(default)]
+    pub niveis: Vec<String>,
+    /// Ocorrências registradas (impedimentos, suspensões, sanções)
+    #[serde(default)]
+    pub ocorrencias: Vec<OcorrenciaSicaf>,
+    /// Data de emissão do relatório ("Emitido em: dd/mm/aaaa hh:mm")
+    #[serde(default)]
+    pub data_emissao: Option<String>,
+    /// Data de modificação do PDF de origem (RFC 3339), usada quando não há data de emissão
+    #[serde(default)]
+    pub arquivo_modificado_em: Option<String>,
+}
+
+/// Ocorrência da seção "Ocorrências" do relatório SICAF
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct OcorrenciaSicaf {
+    pub descricao: String,
+    pub orgao: Option<String>,
+    /// Datas no formato dd/mm/aaaa, como no relatório
+    pub data_inicio: Option<String>,
+    pub data_fim: Option<String>,
+}
+
+/// Resultado de `verify_cnpj_sicaf_detailed`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct VerificacaoSicaf {
+    pub encontrado: bool,
+    pub vencido: Option<bool>,
+    /// Dias até o vencimento do cadastro; negativo quando já venceu
+    pub dias_para_vencer: Option<i64>,
+}
+
+/// Resultado de `get_sicaf_stats`
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct EstatisticasSicaf {
+    pub total: usize,
+    pub vencidos: usize,
+    /// UFs presentes no cadastro, em ordem alfabética
+    pub ufs: Vec<String>,
 }
 
 /// Estrutura para resultado do processamento SICAF
@@ -165,4 +1057,690 @@ pub struct ProcessingSicafResult {
     pub processed_count: usize,
     pub sicaf_data: Vec<SicafData>,
     pub session_id: Option<String>,
-}
\ No newline at end of file
+    /// CNPJs novos no sicaf_dados.json
+    #[serde(default)]
+    pub adicionados: usize,
+    /// Registros existentes substituídos por um relatório mais recente
+    #[serde(default)]
+    pub atualizados: usize,
+    /// Registros existentes que permaneceram como estavam
+    #[serde(default)]
+    pub mantidos: usize,
+}
+/// Entrada do catálogo de preços unitários, agrupada por descrição normalizada
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EntradaCatalogoPreco {
+    pub chave: String,
+    pub descricao_exemplo: String,
+    pub total_ocorrencias: usize,
+    pub mediana: f64,
+    pub p25: f64,
+    pub p75: f64,
+    pub ultima_ocorrencia: String,
+    pub licitacoes: Vec<String>,
+}
+
+/// Sugestão de preço de referência para uma descrição
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SugestaoPreco {
+    pub pontuacao: f64,
+    pub entrada: EntradaCatalogoPreco,
+}
+
+/// PDFs com o mesmo conteúdo (mesmo SHA-256), possivelmente com nomes diferentes
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GrupoPdfsDuplicados {
+    pub sha256: String,
+    pub tamanho: u64,
+    /// Caminhos em ordem alfabética
+    pub arquivos: Vec<String>,
+}
+
+/// Arquivo de resultado cujo conteúdo não confere com o checksum registrado
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ArquivoDivergente {
+    pub arquivo: String,
+    pub esperado: String,
+    pub encontrado: String,
+    pub origem_pdf: Option<String>,
+    /// PDFs de origem de um JSON de licitação
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub origens_pdf: Vec<String>,
+    /// Ações disponíveis: "regenerar" (quando os PDFs de origem existem) e "lixeira"
+    pub acoes: Vec<String>,
+}
+
+/// Arquivo do manifesto que existe mas não pôde ser lido na verificação
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ArquivoIlegivel {
+    pub arquivo: String,
+    pub erro: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RelatorioIntegridade {
+    pub verificados: usize,
+    pub divergentes: Vec<ArquivoDivergente>,
+    pub ausentes: Vec<String>,
+    pub nao_rastreados: Vec<String>,
+    #[serde(default)]
+    pub ilegiveis: Vec<ArquivoIlegivel>,
+}
+
+/// Licitação como será gravada pela consolidação
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PreviaLicitacao {
+    pub chave: String,
+    pub uasg: String,
+    pub pregao: String,
+    pub processo: String,
+    pub total_propostas: usize,
+    pub valor_total: f64,
+    pub arquivo: String,
+}
+
+/// Prévia da consolidação, calculada sem escrever arquivos
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PreviaConsolidacao {
+    pub licitacoes: Vec<PreviaLicitacao>,
+    pub total_propostas: usize,
+    pub valor_total_geral: f64,
+    pub propostas_duplicadas_ignoradas: usize,
+    pub conflitos: Vec<String>,
+    pub itens_duplicados: Vec<String>,
+    /// Valores adjudicados que não puderam ser interpretados
+    pub valores_invalidos: Vec<String>,
+    /// Nomes dos arquivos que seriam gravados
+    pub arquivos: Vec<String>,
+}
+
+/// Proposta encontrada na busca, com o JSON de licitação de onde veio
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PropostaEncontrada {
+    #[serde(flatten)]
+    pub proposta: PropostaConsolidada,
+    pub arquivo_origem: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResultadoBuscaPropostas {
+    pub propostas: Vec<PropostaEncontrada>,
+    /// Havia mais resultados do que o limite pedido
+    pub truncated: bool,
+}
+
+/// Página de propostas de um JSON de resultado (`read_json_propostas_paginated`)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PropostasPaginadas {
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    pub propostas: Vec<serde_json::Value>,
+}
+
+/// Filtros de `query_propostas_sql_like` (todos opcionais e combinados com E)
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FiltroPropostasSql {
+    /// Parte do nome do fornecedor, sem diferenciar acentos e caixa
+    pub fornecedor: Option<String>,
+    /// Parte dos dígitos do CNPJ
+    pub cnpj: Option<String>,
+    pub uasg: Option<String>,
+    pub pregao: Option<String>,
+    pub descricao: Option<String>,
+    pub valor_min: Option<f64>,
+    pub valor_max: Option<f64>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResultadoConsultaPropostas {
+    /// Total de propostas que atendem aos filtros (sem limite)
+    pub total: usize,
+    pub propostas: Vec<PropostaConsolidada>,
+}
+
+/// Resultado de `rebuild_database_from_json`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ReconstrucaoBanco {
+    pub licitacoes: usize,
+    pub propostas: usize,
+    pub registros_sicaf: usize,
+    pub arquivos_com_erro: Vec<String>,
+}
+
+/// PDF copiado para a pasta de PDFs
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PdfImportado {
+    pub origem: String,
+    pub destino: String,
+}
+
+/// PDF que não foi copiado, com o motivo
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PdfNaoImportado {
+    pub arquivo: String,
+    pub motivo: String,
+}
+
+/// Resultado de `import_pdf_files`, arquivo por arquivo
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ImportacaoPdfs {
+    pub importados: Vec<PdfImportado>,
+    pub ignorados: Vec<PdfNaoImportado>,
+    pub erros: Vec<PdfNaoImportado>,
+}
+
+/// Fornecedor no ranking por valor adjudicado
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FornecedorResumo {
+    pub fornecedor: String,
+    pub cnpj: String,
+    pub valor_adjudicado: f64,
+    pub itens: usize,
+}
+
+/// Item adjudicado a um fornecedor em uma licitação
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ItemFornecedor {
+    pub item: String,
+    pub descricao: String,
+    pub quantidade: String,
+    /// Valor como extraído do PDF
+    pub valor_adjudicado: String,
+    /// Valor convertido (0 quando o texto não é um número)
+    pub valor: f64,
+}
+
+/// Licitação em que o fornecedor teve itens adjudicados
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ParticipacaoFornecedor {
+    pub uasg: String,
+    pub pregao: String,
+    pub processo: String,
+    pub arquivo: String,
+    pub itens: Vec<ItemFornecedor>,
+    pub valor_adjudicado: f64,
+}
+
+/// Ficha do fornecedor: participações adjudicadas de um CNPJ em todos os JSONs de licitação
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FornecedorHistory {
+    pub cnpj: String,
+    pub fornecedor: String,
+    pub total_itens: usize,
+    pub valor_total_adjudicado: f64,
+    pub licitacoes: Vec<ParticipacaoFornecedor>,
+    /// Registro do CNPJ no sicaf_dados.json, se houver
+    pub dados_sicaf: Option<SicafData>,
+    pub arquivos_com_erro: Vec<String>,
+}
+
+/// Totais das licitações de uma UASG
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UasgResumo {
+    pub uasg: String,
+    pub licitacoes: usize,
+    pub propostas: usize,
+    pub valor_total: f64,
+}
+
+/// Filtros de `list_licitacao_files` (todos opcionais e combinados com E)
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FiltroArquivosLicitacao {
+    pub uasg: Option<String>,
+    /// Parte do número do pregão
+    pub pregao: Option<String>,
+    /// "pregao", "dispensa" ou "concorrencia"
+    #[serde(default)]
+    pub modalidade: Option<String>,
+    /// Data inicial (AAAA-MM-DD, inclusiva) da geração do arquivo
+    pub date_from: Option<String>,
+    /// Data final (AAAA-MM-DD, inclusiva) da geração do arquivo
+    pub date_to: Option<String>,
+    /// Data inicial (AAAA-MM-DD, inclusiva) da homologação
+    #[serde(default)]
+    pub homologation_from: Option<String>,
+    /// Data final (AAAA-MM-DD, inclusiva) da homologação
+    #[serde(default)]
+    pub homologation_to: Option<String>,
+    /// Incluir os arquivos `exemplo*.json` criados na verificação da pasta
+    #[serde(default)]
+    pub include_examples: bool,
+}
+
+/// Cabeçalho de um JSON de licitação listado por `list_licitacao_files`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LicitacaoFileInfo {
+    pub nome: String,
+    pub caminho: String,
+    pub uasg: Option<String>,
+    pub pregao: Option<String>,
+    pub modalidade: String,
+    pub total_propostas: usize,
+    pub valor_total: f64,
+    pub data_geracao: Option<String>,
+    /// Data de homologação (RFC 3339)
+    pub data_homologacao_iso: Option<String>,
+    /// Data de modificação do arquivo (RFC 3339)
+    pub modificado_em: Option<String>,
+}
+
+/// Agregados das licitações da pasta Resultados para o dashboard
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct LicitacoesSummary {
+    pub total_licitacoes: usize,
+    pub total_propostas: usize,
+    pub valor_total: f64,
+    /// Os 10 fornecedores com maior valor adjudicado
+    pub top_fornecedores: Vec<FornecedorResumo>,
+    /// Ordenada pela UASG
+    pub por_uasg: Vec<UasgResumo>,
+    /// JSONs que não puderam ser lidos
+    pub arquivos_com_erro: Vec<String>,
+}
+
+/// Resultado de `normalize_uasg_results`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct NormalizacaoUasg {
+    /// JSONs que tinham a UASG sem o zero à esquerda
+    pub arquivos_normalizados: Vec<String>,
+    /// Reconsolidação da pasta, quando havia arquivos a corrigir
+    pub mesclagem: Option<MergeResult>,
+}
+
+/// Resultado de `migrate_all_results`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MigracaoResultados {
+    /// Arquivos atualizados para o esquema atual
+    pub migrados: Vec<String>,
+    pub ja_atualizados: usize,
+    pub erros: Vec<String>,
+    /// Pasta com as cópias dos arquivos antes da migração
+    pub pasta_backup: Option<String>,
+}
+
+/// Gravidade de um problema encontrado na validação de um JSON de licitação
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severidade {
+    Erro,
+    Aviso,
+}
+
+/// Problema de um JSON de licitação, com o caminho do campo (ex.: "propostas[2].cnpj")
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ProblemaValidacao {
+    pub caminho: String,
+    pub severidade: Severidade,
+    pub mensagem: String,
+}
+
+/// Resultado de `validate_licitacao_json`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ValidacaoLicitacao {
+    pub arquivo: String,
+    /// Sem problemas de severidade "erro"
+    pub valido: bool,
+    pub problemas: Vec<ProblemaValidacao>,
+}
+
+/// Conteúdo do relatorio_validacao.json gravado por `validate_all_results`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RelatorioValidacao {
+    pub data_geracao: String,
+    pub total_arquivos: usize,
+    pub arquivos_validos: usize,
+    pub arquivos_com_erro: usize,
+    pub arquivos: Vec<ValidacaoLicitacao>,
+}
+
+/// Resultado de `get_app_version_and_migrations`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VersaoAplicativo {
+    pub versao_app: String,
+    pub versao_esquema: u32,
+    /// JSONs de licitação gravados com um esquema anterior ao atual
+    pub arquivos_desatualizados: usize,
+}
+
+/// Resultado de `merge_licitacao_jsons`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MergeResult {
+    pub arquivos_lidos: usize,
+    pub total_licitacoes: usize,
+    pub total_propostas: usize,
+    pub valor_total_geral: f64,
+    /// Propostas repetidas (mesmo item + CNPJ) descartadas
+    pub duplicatas_removidas: usize,
+    /// Mesmo item + CNPJ com valores diferentes entre execuções (vale o arquivo mais recente)
+    pub conflitos: Vec<String>,
+    pub arquivos_gerados: Vec<String>,
+    /// Arquivos lidos que não correspondem mais a nenhuma licitação e foram para a lixeira
+    pub arquivos_substituidos: Vec<String>,
+    pub arquivos_com_erro: Vec<String>,
+}
+
+/// Arquivo com a data da última modificação (RFC 3339)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ArquivoDatado {
+    pub caminho: String,
+    pub modificado_em: String,
+}
+
+/// Uso de disco de uma pasta da estrutura Database
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct UsoPasta {
+    /// Nome da subpasta (PDFs, Resultados, SICAF ou Config)
+    pub nome: String,
+    pub caminho: String,
+    pub tamanho_bytes: u64,
+    pub total_arquivos: usize,
+    /// Extensão em minúsculas → quantidade de arquivos
+    pub arquivos_por_extensao: BTreeMap<String, usize>,
+    pub arquivo_mais_antigo: Option<ArquivoDatado>,
+    pub arquivo_mais_recente: Option<ArquivoDatado>,
+    /// Entradas que não puderam ser lidas (ex.: sem permissão)
+    pub inacessiveis: usize,
+}
+
+/// Resultado de `get_database_usage`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DatabaseUsage {
+    pub raiz: String,
+    pub tamanho_total_bytes: u64,
+    pub total_arquivos: usize,
+    pub inacessiveis: usize,
+    pub pastas: Vec<UsoPasta>,
+}
+
+/// Política de retenção da pasta Resultados (`cleanup_old_results`)
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct RetentionPolicy {
+    /// Arquivar resultados modificados há mais dias que isso
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+    /// Manter sempre os N resultados mais recentes de cada tipo (JSON e Markdown)
+    #[serde(default)]
+    pub keep_last_n: Option<usize>,
+    /// Só listar o que seria movido
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Arquivo movido (ou que seria movido) pela política de retenção
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ArquivoMovido {
+    pub origem: String,
+    pub destino: String,
+}
+
+/// Resultado de `cleanup_old_results`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct LimpezaResultados {
+    pub dry_run: bool,
+    pub arquivos: Vec<ArquivoMovido>,
+    pub erros: Vec<String>,
+}
+
+/// Conteúdo do resumo_geral.json gravado junto com os JSONs de licitação
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ResumoGeral {
+    pub data_geracao: String,
+    pub total_licitacoes: usize,
+    pub total_propostas: usize,
+    pub valor_total_geral: f64,
+    pub processamento_interrompido: bool,
+    pub propostas_duplicadas_ignoradas: usize,
+    #[serde(flatten)]
+    pub economia: ResumoEconomia,
+    /// Valor adjudicado a ME/EPP e aos demais fornecedores
+    pub porte: ResumoPorte,
+    /// Nomes dos arquivos na pasta de resultados, ou caminhos completos dos roteados para fora dela
+    pub arquivos_gerados: Vec<String>,
+    /// Tempos da extração, quando o resumo vem de um processamento de PDFs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desempenho: Option<ResumoDesempenho>,
+}
+
+/// Campo que mudou entre duas versões de uma licitação
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CampoAlterado {
+    pub campo: String,
+    /// Null quando o campo não existe na versão
+    pub antes: serde_json::Value,
+    pub depois: serde_json::Value,
+}
+
+/// Proposta presente nas duas versões (mesmo grupo, item e CNPJ) com campos diferentes
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PropostaAlterada {
+    pub grupo: Option<String>,
+    pub item: String,
+    pub cnpj: String,
+    pub fornecedor: String,
+    pub campos: Vec<CampoAlterado>,
+}
+
+/// Resultado de `compare_licitacao_jsons`: o que mudou de `arquivo_a` para `arquivo_b`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ComparacaoLicitacoes {
+    pub arquivo_a: String,
+    pub arquivo_b: String,
+    /// UASG, pregão, processo e totais que mudaram
+    pub licitacao: Vec<CampoAlterado>,
+    pub adicionadas: Vec<PropostaConsolidada>,
+    pub removidas: Vec<PropostaConsolidada>,
+    pub alteradas: Vec<PropostaAlterada>,
+    pub inalteradas: usize,
+}
+
+/// Resultado de `reprocess_pdf`: as propostas de um arquivo trocadas nos resultados existentes
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ReprocessamentoPdf {
+    pub arquivo: String,
+    /// JSONs de licitação regravados
+    pub arquivos_alterados: Vec<String>,
+    pub propostas_inseridas: usize,
+    pub propostas_removidas: usize,
+    /// Propostas novas descartadas por colidirem com as de outro arquivo do mesmo pregão
+    pub propostas_ignoradas: usize,
+    pub avisos: Vec<String>,
+}
+
+/// Resultado de `get_pdf_text_preview`: o texto bruto extraído de uma ata, para diagnóstico
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct PreviaTextoPdf {
+    pub arquivo: String,
+    /// Página mostrada (a partir de 1), ou None para o documento inteiro
+    pub pagina: Option<usize>,
+    /// Conhecido só quando uma página é pedida
+    pub total_paginas: Option<usize>,
+    /// Caracteres do texto extraído (da página ou do documento), antes do corte
+    pub total_caracteres: usize,
+    /// Início do texto, como saiu do extrator
+    pub texto: String,
+    pub truncado: bool,
+    /// Ocorrências dos rótulos que as regex procuram ("Adjudicado", "CNPJ", "UASG"), no texto normalizado
+    pub ocorrencias: BTreeMap<String, usize>,
+}
+
+/// Trecho do texto normalizado capturado por um padrão (posições em bytes)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SpanCaptura {
+    pub inicio: usize,
+    pub fim: usize,
+    pub texto: String,
+}
+
+/// Um match de um padrão, com cada grupo (pelo nome, ou pelo número se não tiver nome)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OcorrenciaPadrao {
+    pub span: SpanCaptura,
+    /// Grupos que não participaram do match ficam como None
+    pub grupos: BTreeMap<String, Option<SpanCaptura>>,
+}
+
+/// O que um padrão de extração encontrou no texto
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ResultadoPadrao {
+    /// Extração a que o padrão pertence ("uasg", "certame", "individual", "sicaf"...)
+    pub campo: String,
+    pub nome: String,
+    pub padrao: String,
+    /// Vazio quando o padrão não encontrou nada
+    pub ocorrencias: Vec<OcorrenciaPadrao>,
+}
+
+/// O que um extrator de propostas registrado faz com o texto
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResultadoExtrator {
+    pub nome: String,
+    pub detecta: bool,
+    /// Extraídas mesmo quando o extrator não detecta o layout, para comparar
+    pub propostas: Vec<PropostaAdjudicada>,
+}
+
+/// Resultado de `try_extraction_patterns`: cada extração e cada padrão aplicados a um texto colado
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RelatorioPadroes {
+    /// Texto depois da normalização; os spans se referem a ele
+    pub texto_normalizado: String,
+    pub uasg: String,
+    pub modalidade: String,
+    pub pregao: String,
+    pub processo: String,
+    pub data_homologacao: String,
+    /// Extrator que o processamento usaria, ou None se nenhum reconhece o layout
+    pub extrator_selecionado: Option<String>,
+    pub extratores: Vec<ResultadoExtrator>,
+    pub sicaf: Option<SicafData>,
+    pub padroes: Vec<ResultadoPadrao>,
+}
+
+/// Resultado de `backup_database` e `restore_database`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ResultadoBackup {
+    /// ZIP gerado (backup) ou pasta Database restaurada
+    pub caminho: String,
+    pub arquivos: usize,
+    /// Tamanho do ZIP no backup; soma dos arquivos extraídos na restauração
+    pub tamanho_bytes: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resumo_desempenho_das_metricas() {
+        let metrica = |file: &str, extract_ms, parse_ms| FileProcessingMetrics {
+            file: file.to_string(),
+            extract_ms,
+            parse_ms,
+            ..Default::default()
+        };
+        assert_eq!(ResumoDesempenho::das_metricas(&[]), None);
+        assert_eq!(
+            ResumoDesempenho::das_metricas(&[metrica("a.pdf", 100, 20), metrica("b.pdf", 900, 50), metrica("c.pdf", 10, 5)]),
+            Some(ResumoDesempenho {
+                arquivos: 3,
+                tempo_total_ms: 1085,
+                tempo_medio_ms: 361,
+                arquivo_mais_lento: "b.pdf".to_string(),
+                tempo_mais_lento_ms: 950,
+            }),
+        );
+    }
+
+    #[test]
+    fn test_tauri_error_serializa_no_formato_do_frontend() {
+        let erro = TauriError::filesystem("Diretório não encontrado: /dados", "/dados".to_string());
+        assert_eq!(serde_json::to_value(&erro).unwrap(), serde_json::json!({
+            "error_type": "FileSystemError",
+            "message": "Diretório não encontrado: /dados",
+            "details": "/dados",
+        }));
+
+        let erro = TauriError::validation("CNPJ inválido", None);
+        assert_eq!(serde_json::to_value(&erro).unwrap()["error_type"], "ValidationError");
+        assert!(serde_json::to_value(&erro).unwrap()["details"].is_null());
+
+        let kinds = [
+            (ErrorKind::FileSystemError, "FileSystemError"), (ErrorKind::NotFound, "NotFound"),
+            (ErrorKind::ValidationError, "ValidationError"), (ErrorKind::ParseError, "ParseError"),
+            (ErrorKind::ProcessingError, "ProcessingError"), (ErrorKind::SerializationError, "SerializationError"),
+            (ErrorKind::SystemError, "SystemError"), (ErrorKind::NetworkError, "NetworkError"),
+            (ErrorKind::Unauthorized, "Unauthorized"), (ErrorKind::PdfEncrypted, "PdfEncrypted"),
+            (ErrorKind::PermissionDenied, "PermissionDenied"),
+        ];
+        for (kind, nome) in kinds {
+            assert_eq!(serde_json::to_value(kind).unwrap(), nome);
+            assert_eq!(serde_json::from_value::<ErrorKind>(nome.into()).unwrap(), kind);
+        }
+    }
+
+    #[test]
+    fn test_erro_traduzido_leva_chave_e_argumentos() {
+        let erro = TauriError::traduzido(ErrorKind::NotFound, MsgKey::SessionNotFound, &[("sessao", &"abc")], "abc".to_string());
+        let valor = serde_json::to_value(&erro).unwrap();
+        assert_eq!(valor["error_type"], "NotFound");
+        assert_eq!(valor["message_key"], "session_not_found");
+        assert_eq!(valor["message_args"], serde_json::json!({ "sessao": "abc" }));
+        assert!(erro.message.contains("abc"));
+    }
+
+    #[test]
+    fn test_economia_ignora_itens_sem_valor_estimado() {
+        let proposta = |item: &str, estimado: serde_json::Value, adjudicado: &str, situacao: &str| -> PropostaConsolidada {
+            let mut proposta: PropostaConsolidada = serde_json::from_value(serde_json::json!({
+                "uasg": "787000", "pregao": "90008/2024", "processo": "123", "item": item, "grupo": null,
+                "quantidade": "1", "descricao": "Item", "valor_estimado": estimado, "valor_adjudicado": adjudicado,
+                "fornecedor": "EMPRESA", "cnpj": "12.345.678/0001-90", "marca_fabricante": "N/A", "modelo_versao": "N/A",
+                "responsavel": "N/A", "melhor_lance": adjudicado, "tipo_formato": "individual", "situacao": situacao
+            })).unwrap();
+            proposta.calcular_economia();
+            proposta
+        };
+        let propostas = vec![
+            proposta("1", serde_json::json!("100,00"), "80,00", SITUACAO_ADJUDICADO),
+            proposta("2", serde_json::json!(300.0), "330,00", SITUACAO_ADJUDICADO),
+            proposta("3", serde_json::json!("N/A"), "50,00", SITUACAO_ADJUDICADO),
+            proposta("4", serde_json::json!(null), "1.000,00", SITUACAO_ADJUDICADO),
+            proposta("5", serde_json::json!("10,00"), "N/A", "Deserto"),
+        ];
+
+        assert_eq!((propostas[0].economia_absoluta, propostas[0].economia_percentual), (Some(Valor(20.0)), Some(20.0)));
+        assert_eq!((propostas[1].economia_absoluta, propostas[1].economia_percentual), (Some(Valor(-30.0)), Some(-10.0)));
+        assert_eq!((propostas[2].economia_absoluta, propostas[2].economia_percentual), (None, None));
+        assert_eq!(economia(Some(Valor(0.0)), Some(Valor(5.0))), (Some(Valor(-5.0)), None));
+
+        // Só os itens 1 e 2 entram na comparação: 400 estimados contra 410 adjudicados
+        let resumo = ResumoEconomia::das_propostas(&propostas);
+        assert_eq!(resumo, ResumoEconomia {
+            valor_estimado_total: 400.0,
+            valor_adjudicado_total: 410.0,
+            economia_total: -10.0,
+            economia_percentual: Some(-2.5),
+            itens_sem_valor_estimado: 2,
+        });
+    }
+
+    #[test]
+    fn test_conversao_de_erros() {
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "sem arquivo");
+        assert_eq!(TauriError::from(io).error_type, ErrorKind::NotFound);
+        let io = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "negado");
+        assert_eq!(TauriError::from(io).error_type, ErrorKind::FileSystemError);
+
+        let erro: TauriError = anyhow::anyhow!("PDF ilegível").context("Erro ao processar ata.pdf").into();
+        assert_eq!(erro.error_type, ErrorKind::ProcessingError);
+        assert_eq!(erro.message, "Erro ao processar ata.pdf: PDF ilegível");
+
+        let protegido = anyhow::Error::new(crate::metadados_pdf::PdfEncrypted { arquivo: "ata.pdf".to_string() })
+            .context("Erro ao processar ata.pdf");
+        assert_eq!(TauriError::from(protegido).error_type, ErrorKind::PdfEncrypted);
+    }
+}
@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+use crate::types::{ArquivoDatado, UsoPasta};
+
+/// Rótulo das extensões de arquivos sem extensão
+pub const SEM_EXTENSAO: &str = "(sem extensão)";
+
+/// Mede uma pasta só com os metadados das entradas, sem abrir os arquivos
+///
+/// Entradas que não puderem ser lidas (permissão, arquivo removido durante a varredura)
+/// são contadas em `inacessiveis` em vez de interromper a medição.
+pub fn medir_pasta(nome: &str, pasta: &Path) -> UsoPasta {
+    let mut uso = UsoPasta {
+        nome: nome.to_string(),
+        caminho: pasta.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    if !pasta.exists() {
+        return uso;
+    }
+
+    let mut mais_antigo: Option<(SystemTime, PathBuf)> = None;
+    let mut mais_recente: Option<(SystemTime, PathBuf)> = None;
+    for entry in WalkDir::new(pasta) {
+        let Ok(entry) = entry else {
+            uso.inacessiveis += 1;
+            continue;
+        };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            uso.inacessiveis += 1;
+            continue;
+        };
+
+        uso.tamanho_bytes += metadata.len();
+        uso.total_arquivos += 1;
+        let extensao = entry.path().extension()
+            .map_or_else(|| SEM_EXTENSAO.to_string(), |ext| ext.to_string_lossy().to_lowercase());
+        *uso.arquivos_por_extensao.entry(extensao).or_default() += 1;
+
+        if let Ok(modificado) = metadata.modified() {
+            if mais_antigo.as_ref().is_none_or(|(data, _)| modificado < *data) {
+                mais_antigo = Some((modificado, entry.path().to_path_buf()));
+            }
+            if mais_recente.as_ref().is_none_or(|(data, _)| modificado > *data) {
+                mais_recente = Some((modificado, entry.path().to_path_buf()));
+            }
+        }
+    }
+    uso.arquivo_mais_antigo = mais_antigo.map(arquivo_datado);
+    uso.arquivo_mais_recente = mais_recente.map(arquivo_datado);
+    uso
+}
+
+fn arquivo_datado((modificado, path): (SystemTime, PathBuf)) -> ArquivoDatado {
+    ArquivoDatado {
+        caminho: path.to_string_lossy().to_string(),
+        modificado_em: DateTime::<Utc>::from(modificado).to_rfc3339(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_medir_pasta_por_extensao_e_datas() {
+        let dir = std::env::temp_dir().join(format!("licitacao360_uso_disco_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("Processados")).unwrap();
+        fs::write(dir.join("ata.pdf"), vec![0u8; 100]).unwrap();
+        fs::write(dir.join("Processados").join("antiga.PDF"), vec![0u8; 50]).unwrap();
+        fs::write(dir.join("notas.txt"), b"abc").unwrap();
+        fs::write(dir.join("LEIAME"), b"x").unwrap();
+
+        let antigo = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+        fs::File::options().write(true).open(dir.join("Processados").join("antiga.PDF")).unwrap().set_modified(antigo).unwrap();
+
+        let uso = medir_pasta("PDFs", &dir);
+        assert_eq!(uso.tamanho_bytes, 154);
+        assert_eq!(uso.total_arquivos, 4);
+        assert_eq!(uso.inacessiveis, 0);
+        assert_eq!(uso.arquivos_por_extensao.get("pdf"), Some(&2));
+        assert_eq!(uso.arquivos_por_extensao.get("txt"), Some(&1));
+        assert_eq!(uso.arquivos_por_extensao.get(SEM_EXTENSAO), Some(&1));
+        let mais_antigo = uso.arquivo_mais_antigo.unwrap();
+        assert!(mais_antigo.caminho.ends_with("antiga.PDF"));
+        assert_eq!(mais_antigo.modificado_em, "2023-11-14T22:13:20+00:00");
+        assert!(uso.arquivo_mais_recente.is_some());
+
+        let vazia = medir_pasta("SICAF", &dir.join("inexistente"));
+        assert_eq!((vazia.total_arquivos, vazia.arquivo_mais_antigo), (0, None));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,242 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::{integridade, migracoes, validators};
+use crate::types::{LicitacaoConsolidada, ProblemaValidacao, RelatorioValidacao, Severidade, ValidacaoLicitacao, SITUACAO_ADJUDICADO};
+
+/// Relatório gravado por `validate_all_results` na pasta de resultados
+pub const ARQUIVO_RELATORIO_VALIDACAO: &str = "relatorio_validacao.json";
+
+/// Campos do arquivo que não fazem parte de `LicitacaoConsolidada` (metadados e totais derivados)
+pub const CAMPOS_DO_ARQUIVO: [&str; 9] = [
+    "schema_version",
+    "data_geracao",
+    "data_homologacao_iso",
+    "responsavel_homologacao",
+    "valor_estimado_total",
+    "valor_adjudicado_total",
+    "economia_total",
+    "economia_percentual",
+    "processamento_interrompido",
+];
+
+/// Diferença aceita entre o valor_total gravado e a soma dos itens (arredondamento)
+pub const TOLERANCIA_VALOR_TOTAL: f64 = 0.01;
+
+fn problema(caminho: impl Into<String>, severidade: Severidade, mensagem: impl Into<String>) -> ProblemaValidacao {
+    ProblemaValidacao { caminho: caminho.into(), severidade, mensagem: mensagem.into() }
+}
+
+/// Caminho de um campo ignorado no formato de `serde_path_to_error` ("propostas[2].campo")
+fn caminho_ignorado(path: &serde_ignored::Path) -> String {
+    use serde_ignored::Path;
+    match path {
+        Path::Root => String::new(),
+        Path::Seq { parent, index } => format!("{}[{}]", caminho_ignorado(parent), index),
+        Path::Map { parent, key } => match caminho_ignorado(parent) {
+            pai if pai.is_empty() => key.clone(),
+            pai => format!("{}.{}", pai, key),
+        },
+        Path::Some { parent } | Path::NewtypeStruct { parent } | Path::NewtypeVariant { parent } => caminho_ignorado(parent),
+    }
+}
+
+/// Valida o conteúdo de um JSON de licitação
+///
+/// A leitura é estrita: campos desconhecidos e tipos errados são erros, com o caminho do campo.
+/// Depois são conferidos os invariantes: total_propostas, valor_total e os dígitos dos CNPJs.
+/// CNPJs já marcados como inválidos na extração (`cnpj_valido: false`) viram só avisos.
+pub fn validar_conteudo(conteudo: &str) -> Vec<ProblemaValidacao> {
+    let mut json: serde_json::Value = match serde_json::from_str(conteudo) {
+        Ok(json) => json,
+        Err(e) => return vec![problema("", Severidade::Erro, format!("JSON inválido: {}", e))],
+    };
+    if migracoes::precisa_migrar(&json) {
+        json = match migracoes::migrar_licitacao_json(json) {
+            Ok(json) => json,
+            Err(e) => return vec![problema("schema_version", Severidade::Erro, format!("Erro ao migrar: {:#}", e))],
+        };
+    }
+
+    let mut problemas = Vec::new();
+    let mut ignorados = Vec::new();
+    let mut registrar = |path: serde_ignored::Path| ignorados.push(caminho_ignorado(&path));
+    let deserializer = serde_ignored::Deserializer::new(json, &mut registrar);
+    let licitacao = match serde_path_to_error::deserialize::<_, LicitacaoConsolidada>(deserializer) {
+        Ok(licitacao) => Some(licitacao),
+        Err(e) => {
+            let caminho = e.path().to_string();
+            let caminho = if caminho == "." { String::new() } else { caminho };
+            problemas.push(problema(caminho, Severidade::Erro, e.into_inner().to_string()));
+            None
+        }
+    };
+    for caminho in ignorados {
+        if !CAMPOS_DO_ARQUIVO.contains(&caminho.as_str()) {
+            problemas.push(problema(caminho, Severidade::Erro, "Campo desconhecido"));
+        }
+    }
+
+    if let Some(licitacao) = licitacao {
+        problemas.extend(verificar_invariantes(&licitacao));
+    }
+    problemas
+}
+
+/// Invariantes de uma licitação já lida
+fn verificar_invariantes(licitacao: &LicitacaoConsolidada) -> Vec<ProblemaValidacao> {
+    let mut problemas = Vec::new();
+
+    if licitacao.total_propostas != licitacao.propostas.len() {
+        problemas.push(problema("total_propostas", Severidade::Erro, format!(
+            "total_propostas é {}, mas há {} propostas", licitacao.total_propostas, licitacao.propostas.len(),
+        )));
+    }
+
+    let soma: f64 = licitacao.propostas.iter().filter_map(|p| p.total_do_item()).map(|v| v.0).sum();
+    if (licitacao.valor_total - soma).abs() > TOLERANCIA_VALOR_TOTAL {
+        problemas.push(problema("valor_total", Severidade::Erro, format!(
+            "valor_total é {:.2}, mas a soma dos itens é {:.2}", licitacao.valor_total, soma,
+        )));
+    }
+
+    // Itens desertos, fracassados ou cancelados não têm fornecedor (CNPJ "N/A")
+    for (index, proposta) in licitacao.propostas.iter().enumerate().filter(|(_, p)| p.situacao == SITUACAO_ADJUDICADO) {
+        if proposta.cnpj.trim().is_empty() || validators::validar_cnpj(&proposta.cnpj) {
+            continue;
+        }
+        let severidade = if proposta.cnpj_valido { Severidade::Erro } else { Severidade::Aviso };
+        problemas.push(problema(
+            format!("propostas[{}].cnpj", index),
+            severidade,
+            format!("CNPJ com dígitos verificadores inválidos: {}", proposta.cnpj),
+        ));
+    }
+
+    problemas
+}
+
+/// Valida um arquivo JSON de licitação
+pub fn validar_arquivo(path: &Path) -> ValidacaoLicitacao {
+    let problemas = match fs::read_to_string(path) {
+        Ok(conteudo) => validar_conteudo(&conteudo),
+        Err(e) => vec![problema("", Severidade::Erro, format!("Erro ao ler arquivo: {}", e))],
+    };
+    ValidacaoLicitacao {
+        arquivo: path.to_string_lossy().to_string(),
+        valido: !problemas.iter().any(|p| p.severidade == Severidade::Erro),
+        problemas,
+    }
+}
+
+/// Valida os arquivos em lote
+pub fn validar_arquivos(arquivos: &[PathBuf]) -> RelatorioValidacao {
+    let arquivos: Vec<ValidacaoLicitacao> = arquivos.iter().map(|path| validar_arquivo(path)).collect();
+    let arquivos_validos = arquivos.iter().filter(|a| a.valido).count();
+    RelatorioValidacao {
+        data_geracao: Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        total_arquivos: arquivos.len(),
+        arquivos_validos,
+        arquivos_com_erro: arquivos.len() - arquivos_validos,
+        arquivos,
+    }
+}
+
+/// Valida os arquivos e grava o relatorio_validacao.json em `output_dir`
+pub fn validar_e_gravar(output_dir: &Path, arquivos: &[PathBuf]) -> Result<RelatorioValidacao> {
+    let relatorio = validar_arquivos(arquivos);
+    let content = serde_json::to_string_pretty(&relatorio)
+        .context("Erro ao serializar relatório de validação")?;
+    integridade::gravar_artefato(output_dir, &output_dir.join(ARQUIVO_RELATORIO_VALIDACAO), content.as_bytes(), None)
+        .context("Erro ao salvar relatório de validação")?;
+    Ok(relatorio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn licitacao_json() -> serde_json::Value {
+        serde_json::json!({
+            "schema_version": migracoes::VERSAO_ESQUEMA,
+            "data_geracao": "2024-03-01 10:00:00 UTC",
+            "uasg": "160001",
+            "pregao": "00012/2024",
+            "processo": "123",
+            "total_propostas": 1,
+            "valor_total": 150.0,
+            "valor_estimado_total": 200.0,
+            "processamento_interrompido": false,
+            "propostas": [{
+                "uasg": "160001",
+                "pregao": "00012/2024",
+                "processo": "123",
+                "item": "1",
+                "grupo": null,
+                "quantidade": "10",
+                "descricao": "Caneta",
+                "valor_estimado": "200,00",
+                "valor_adjudicado": "150,00",
+                "fornecedor": "EMPRESA LTDA",
+                "cnpj": "11.222.333/0001-81",
+                "marca_fabricante": "",
+                "modelo_versao": "",
+                "responsavel": "",
+                "melhor_lance": null,
+                "tipo_formato": "individual",
+            }]
+        })
+    }
+
+    #[test]
+    fn test_json_correto_nao_tem_problemas() {
+        assert_eq!(validar_conteudo(&licitacao_json().to_string()), Vec::new());
+    }
+
+    #[test]
+    fn test_item_deserto_nao_tem_cnpj_invalido() {
+        let mut json = licitacao_json();
+        let mut deserto = json["propostas"][0].clone();
+        deserto["item"] = "2".into();
+        deserto["valor_adjudicado"] = serde_json::Value::Null;
+        deserto["fornecedor"] = "N/A".into();
+        deserto["cnpj"] = "N/A".into();
+        deserto["situacao"] = crate::types::SITUACAO_DESERTO.into();
+        json["propostas"].as_array_mut().unwrap().push(deserto);
+        json["total_propostas"] = 2.into();
+        assert_eq!(validar_conteudo(&json.to_string()), Vec::new());
+    }
+
+    #[test]
+    fn test_problemas_trazem_caminho_e_severidade() {
+        let mut json = licitacao_json();
+        json["total_propostas"] = 2.into();
+        json["valor_total"] = 100.0.into();
+        json["propostas"][0]["cnpj"] = "11.222.333/0001-82".into();
+        json["propostas"][0]["campo_extra"] = true.into();
+        let problemas = validar_conteudo(&json.to_string());
+        let caminhos: Vec<(&str, Severidade)> = problemas.iter().map(|p| (p.caminho.as_str(), p.severidade)).collect();
+        assert_eq!(caminhos, [
+            ("propostas[0].campo_extra", Severidade::Erro),
+            ("total_propostas", Severidade::Erro),
+            ("valor_total", Severidade::Erro),
+            ("propostas[0].cnpj", Severidade::Erro),
+        ]);
+
+        // CNPJ já marcado como inválido na extração é só um aviso
+        let mut json = licitacao_json();
+        json["propostas"][0]["cnpj"] = "11.222.333/0001-82".into();
+        json["propostas"][0]["cnpj_valido"] = false.into();
+        let problemas = validar_conteudo(&json.to_string());
+        assert_eq!(problemas.len(), 1);
+        assert_eq!(problemas[0].severidade, Severidade::Aviso);
+
+        // Tipo errado: a leitura para no campo
+        let mut json = licitacao_json();
+        json["propostas"][0]["quantidade"] = serde_json::json!([1]);
+        let problemas = validar_conteudo(&json.to_string());
+        assert_eq!(problemas.len(), 1);
+        assert_eq!(problemas[0].caminho, "propostas[0].quantidade");
+    }
+}
@@ -0,0 +1,174 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Pesos do primeiro e do segundo dígito verificador do CNPJ
+const PESOS_DV1: [u32; 12] = [5, 4, 3, 2, 9, 8, 7, 6, 5, 4, 3, 2];
+const PESOS_DV2: [u32; 13] = [6, 5, 4, 3, 2, 9, 8, 7, 6, 5, 4, 3, 2];
+
+fn digito_verificador(digitos: &[u32], pesos: &[u32]) -> u32 {
+    let soma: u32 = digitos.iter().zip(pesos).map(|(d, p)| d * p).sum();
+    match soma % 11 {
+        0 | 1 => 0,
+        resto => 11 - resto,
+    }
+}
+
+/// Valida um CNPJ pelos dígitos verificadores, com ou sem máscara
+///
+/// Aceita apenas dígitos e os separadores ". / -"; exige 14 dígitos e rejeita
+/// sequências repetidas como "00.000.000/0000-00".
+pub fn validar_cnpj(cnpj: &str) -> bool {
+    let cnpj = cnpj.trim();
+    if cnpj.chars().any(|c| !c.is_ascii_digit() && !matches!(c, '.' | '/' | '-')) {
+        return false;
+    }
+
+    let digitos: Vec<u32> = cnpj.chars().filter_map(|c| c.to_digit(10)).collect();
+    if digitos.len() != 14 || digitos.iter().all(|&d| d == digitos[0]) {
+        return false;
+    }
+
+    digito_verificador(&digitos[..12], &PESOS_DV1) == digitos[12]
+        && digito_verificador(&digitos[..13], &PESOS_DV2) == digitos[13]
+}
+
+/// Número de dígitos de uma UASG
+pub const DIGITOS_UASG: usize = 6;
+
+/// Normaliza uma UASG para 6 dígitos, completando com zeros à esquerda ("70011" vira "070011")
+///
+/// Retorna None se houver algo além de dígitos ou mais de 6 dígitos significativos.
+pub fn normalizar_uasg(uasg: &str) -> Option<String> {
+    let uasg = uasg.trim();
+    if uasg.is_empty() || !uasg.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let significativos = uasg.trim_start_matches('0');
+    (significativos.len() <= DIGITOS_UASG).then(|| format!("{:0>width$}", significativos, width = DIGITOS_UASG))
+}
+
+static RE_EMAIL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}$").unwrap()
+});
+
+/// Telefone com DDD opcionalmente precedido de +55 ou do zero de operadora: "(61) 3333-4444", "61 9 9999-8888"
+static RE_TELEFONE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:\+?55\s*)?\(?\s*0?(?P<ddd>[1-9]{2})\s*\)?[\s.-]*(?P<prefixo>9?\s?\d{4})[\s.-]?(?P<sufixo>\d{4})\b").unwrap()
+});
+
+/// Valida o formato de um e-mail (usuario@dominio.tld)
+pub fn validar_email(email: &str) -> bool {
+    RE_EMAIL.is_match(email.trim())
+}
+
+/// Primeiro telefone do texto, só com dígitos e com o DDD ("(61) 3333-4444 Fax:" vira "6133334444")
+pub fn normalizar_telefone(texto: &str) -> Option<String> {
+    let caps = RE_TELEFONE.captures(texto)?;
+    let digitos: String = [&caps["ddd"], &caps["prefixo"], &caps["sufixo"]].concat()
+        .chars()
+        .filter(|c| c.is_ascii_digit())
+        .collect();
+    matches!(digitos.len(), 10 | 11).then_some(digitos)
+}
+
+/// E-mails válidos do texto, em minúsculas e sem repetição
+///
+/// Os e-mails podem vir separados por ";" ou ",". Dentro de cada parte, pedaços separados
+/// por quebras de linha ou espaços são juntados quando completam um e-mail (quebra de linha do PDF).
+pub fn extrair_emails(texto: &str) -> Vec<String> {
+    let mut emails: Vec<String> = Vec::new();
+    let mut adicionar = |candidato: &str| {
+        let candidato = candidato.trim_matches(|c: char| matches!(c, '.' | ':' | '<' | '>')).to_lowercase();
+        if validar_email(&candidato) && !emails.contains(&candidato) {
+            emails.push(candidato);
+        }
+    };
+
+    for parte in texto.split([';', ',']) {
+        let mut atual = String::new();
+        for pedaco in parte.split_whitespace() {
+            let junto = format!("{}{}", atual, pedaco);
+            // Um e-mail já válido só continua com ".br" e afins; um incompleto junta até ficar válido
+            let juntar = !atual.is_empty() && (!atual.contains('@') || !pedaco.contains('@')) && if validar_email(&atual) {
+                pedaco.starts_with('.') && validar_email(&junto)
+            } else {
+                validar_email(&junto) || !validar_email(pedaco)
+            };
+            if juntar {
+                atual.push_str(pedaco);
+            } else {
+                adicionar(&atual);
+                atual = pedaco.to_string();
+            }
+        }
+        adicionar(&atual);
+    }
+    emails
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalizar_uasg() {
+        assert_eq!(normalizar_uasg("70011").as_deref(), Some("070011"));
+        assert_eq!(normalizar_uasg(" 070011 ").as_deref(), Some("070011"));
+        assert_eq!(normalizar_uasg("0070011").as_deref(), Some("070011"));
+        assert_eq!(normalizar_uasg("787000").as_deref(), Some("787000"));
+        assert_eq!(normalizar_uasg("0").as_deref(), Some("000000"));
+        assert_eq!(normalizar_uasg("1787000"), None);
+        assert_eq!(normalizar_uasg("78.700"), None);
+        assert_eq!(normalizar_uasg("N/A"), None);
+        assert_eq!(normalizar_uasg(""), None);
+    }
+
+    #[test]
+    fn test_validar_cnpj() {
+        for valido in ["11.222.333/0001-81", "11222333000181", " 11.222.333/0001-81 ", "00.000.000/0001-91"] {
+            assert!(validar_cnpj(valido), "{}", valido);
+        }
+
+        for invalido in [
+            "11.222.333/0001-80",  // dígito verificador errado
+            "11.222.333/0001-8",   // truncado
+            "11.222.333/00001-81", // dígito a mais
+            "11.111.111/1111-11",  // sequência repetida
+            "00000000000000",
+            "11.222.333/0001-8I",  // letra no lugar de dígito (OCR)
+            "N/A",
+            "",
+        ] {
+            assert!(!validar_cnpj(invalido), "{}", invalido);
+        }
+    }
+
+    #[test]
+    fn test_normalizar_telefone() {
+        assert_eq!(normalizar_telefone("(61) 3333-4444 Fax:").as_deref(), Some("6133334444"));
+        assert_eq!(normalizar_telefone("61 9 9999-8888").as_deref(), Some("61999998888"));
+        assert_eq!(normalizar_telefone("+55 (11) 98765.4321 / (11) 3333-4444").as_deref(), Some("11987654321"));
+        assert_eq!(normalizar_telefone("(061) 3333 4444").as_deref(), Some("6133334444"));
+        assert_eq!(normalizar_telefone("3333-4444"), None);
+        assert_eq!(normalizar_telefone("N/A"), None);
+    }
+
+    #[test]
+    fn test_extrair_emails() {
+        assert_eq!(extrair_emails("Contato@Empresa.com.br"), ["contato@empresa.com.br"]);
+        // Quebras de linha do PDF no meio do e-mail
+        assert_eq!(extrair_emails("licitacoes@empresa.com\n.br"), ["licitacoes@empresa.com.br"]);
+        assert_eq!(extrair_emails("joao.\nsilva@empresa.com.br"), ["joao.silva@empresa.com.br"]);
+        assert_eq!(extrair_emails("comercial@empre\nsa.com.br"), ["comercial@empresa.com.br"]);
+        // Vários e-mails, repetidos e lixo
+        assert_eq!(
+            extrair_emails("a@x.com.br; b@y.com , A@X.COM.BR;\nsem-arroba; quebrado@"),
+            ["a@x.com.br", "b@y.com"],
+        );
+        assert_eq!(extrair_emails("a@x.com b@y.com"), ["a@x.com", "b@y.com"]);
+        assert_eq!(extrair_emails("Fax: a@x.com\nFax"), ["a@x.com"]);
+        assert!(extrair_emails("N/A").is_empty());
+        assert!(!validar_email("fulano@localhost"));
+    }
+}
@@ -0,0 +1,227 @@
+use anyhow::{Context, Result};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
+use tracing::warn;
+use crate::paths;
+
+/// Tempo sem o arquivo crescer para considerá-lo completamente copiado
+pub const ESTABILIDADE_PADRAO: Duration = Duration::from_secs(3);
+
+/// Intervalo entre as verificações de arquivos pendentes
+const INTERVALO_VERIFICACAO: Duration = Duration::from_millis(500);
+
+/// Sufixos de arquivos parciais de navegadores e programas de cópia
+const SUFIXOS_TEMPORARIOS: [&str; 4] = [".crdownload", ".part", ".tmp", ".download"];
+
+/// Arquivos temporários do Office (`~$`), ocultos ou downloads incompletos
+pub fn eh_arquivo_temporario(path: &Path) -> bool {
+    let nome = path.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+    nome.starts_with("~$")
+        || nome.starts_with('.')
+        || SUFIXOS_TEMPORARIOS.iter().any(|sufixo| nome.ends_with(sufixo))
+}
+
+/// PDFs novos na pasta observada, fora da subpasta Processados
+pub fn deve_processar(path: &Path, pasta: &Path) -> bool {
+    let em_processados = path.strip_prefix(pasta)
+        .map(|relativo| relativo.components().any(|c| paths::eh_pasta_processados(c.as_os_str())))
+        .unwrap_or(true);
+
+    paths::is_pdf_file(path) && !eh_arquivo_temporario(path) && !em_processados
+}
+
+/// Arquivos aguardando parar de crescer antes do processamento
+#[derive(Debug, Default)]
+pub struct Pendentes {
+    /// path -> (último tamanho visto, quando o tamanho mudou pela última vez)
+    arquivos: HashMap<PathBuf, (u64, Instant)>,
+}
+
+impl Pendentes {
+    /// Passa a acompanhar o arquivo (novos eventos do mesmo arquivo reiniciam a espera)
+    pub fn observar(&mut self, path: PathBuf, agora: Instant) {
+        let tamanho = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        self.arquivos.insert(path, (tamanho, agora));
+    }
+
+    /// Retira e retorna os arquivos que não cresceram durante `estabilidade`
+    pub fn prontos(&mut self, agora: Instant, estabilidade: Duration) -> Vec<PathBuf> {
+        let mut prontos = Vec::new();
+
+        self.arquivos.retain(|path, (tamanho, desde)| {
+            // Arquivo removido ou renomeado antes de estabilizar
+            let Ok(metadata) = fs::metadata(path) else {
+                return false;
+            };
+
+            if metadata.len() != *tamanho {
+                *tamanho = metadata.len();
+                *desde = agora;
+                return true;
+            }
+
+            if *tamanho > 0 && agora.duration_since(*desde) >= estabilidade {
+                prontos.push(path.clone());
+                return false;
+            }
+            true
+        });
+
+        prontos.sort();
+        prontos
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.arquivos.is_empty()
+    }
+}
+
+/// Observador ativo da pasta de PDFs
+pub struct ObservadorPdf {
+    pub pasta: PathBuf,
+    parar: Arc<AtomicBool>,
+    tarefa: JoinHandle<()>,
+}
+
+/// Estado gerenciado pelo Tauri com o observador ativo (se houver)
+pub type PdfWatcherState = Arc<Mutex<Option<ObservadorPdf>>>;
+
+/// Observa `pasta` e chama `processar` para cada PDF novo que ficar estável
+///
+/// Os arquivos são processados um de cada vez, na thread do observador; arquivos que
+/// chegam em rajada aguardam na fila de pendentes. Um PDF só é processado de novo se
+/// a data de modificação mudar.
+pub fn iniciar<F>(pasta: &Path, estabilidade: Duration, mut processar: F) -> Result<ObservadorPdf>
+where
+    F: FnMut(&Path) + Send + 'static,
+{
+    let pasta = pasta.to_path_buf();
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)
+        .context("Erro ao criar observador de arquivos")?;
+    watcher.watch(&pasta, RecursiveMode::Recursive)
+        .context(format!("Erro ao observar a pasta {}", pasta.display()))?;
+
+    let parar = Arc::new(AtomicBool::new(false));
+    let parar_tarefa = parar.clone();
+    let pasta_tarefa = pasta.clone();
+
+    let tarefa = std::thread::spawn(move || {
+        // O watcher vive enquanto a thread estiver ativa
+        let _watcher = watcher;
+        let mut pendentes = Pendentes::default();
+        let mut processados: HashMap<PathBuf, Option<SystemTime>> = HashMap::new();
+
+        while !parar_tarefa.load(Ordering::SeqCst) {
+            match rx.recv_timeout(INTERVALO_VERIFICACAO) {
+                Ok(Ok(evento)) => {
+                    if matches!(evento.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                        for path in evento.paths.into_iter().filter(|p| deve_processar(p, &pasta_tarefa)) {
+                            pendentes.observar(path, Instant::now());
+                        }
+                    }
+                }
+                Ok(Err(e)) => warn!(erro = %e, "Erro do observador de PDFs"),
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+
+            if pendentes.is_empty() {
+                continue;
+            }
+
+            for path in pendentes.prontos(Instant::now(), estabilidade) {
+                let modificado = fs::metadata(&path).and_then(|m| m.modified()).ok();
+                if processados.get(&path) == Some(&modificado) {
+                    continue;
+                }
+                if parar_tarefa.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                processar(&path);
+                processados.insert(path, modificado);
+            }
+        }
+    });
+
+    Ok(ObservadorPdf { pasta, parar, tarefa })
+}
+
+impl ObservadorPdf {
+    /// Encerra o observador, aguardando o arquivo em processamento terminar
+    pub fn parar(self) {
+        self.parar.store(true, Ordering::SeqCst);
+        let _ = self.tarefa.join();
+    }
+}
+
+/// Encerra o observador ativo, se houver (usado ao fechar o aplicativo)
+pub fn encerrar(estado: &PdfWatcherState) {
+    let observador = estado.lock().unwrap_or_else(|e| e.into_inner()).take();
+    if let Some(observador) = observador {
+        observador.parar();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ignora_temporarios_e_processados() {
+        let pasta = Path::new("/dados/PDFs");
+        assert!(deve_processar(&pasta.join("ata.pdf"), pasta));
+        assert!(deve_processar(&pasta.join("2024/ATA.PDF"), pasta));
+        assert!(!deve_processar(&pasta.join("~$ata.pdf"), pasta));
+        assert!(!deve_processar(&pasta.join("ata.pdf.crdownload"), pasta));
+        assert!(!deve_processar(&pasta.join(".ata.pdf"), pasta));
+        assert!(!deve_processar(&pasta.join("ata.txt"), pasta));
+        assert!(!deve_processar(&pasta.join(paths::PASTA_PROCESSADOS).join("ata.pdf"), pasta));
+        assert!(!deve_processar(Path::new("/outra/ata.pdf"), pasta));
+    }
+
+    #[test]
+    fn test_pendente_so_fica_pronto_quando_para_de_crescer() {
+        let dir = std::env::temp_dir().join(format!("licitacao360_watcher_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let estabilidade = Duration::from_secs(3);
+        let inicio = Instant::now();
+
+        let copiando = dir.join("copiando.pdf");
+        let completo = dir.join("completo.pdf");
+        fs::write(&copiando, b"%PDF-1.4").unwrap();
+        fs::write(&completo, b"%PDF-1.4 completo").unwrap();
+
+        let mut pendentes = Pendentes::default();
+        pendentes.observar(copiando.clone(), inicio);
+        pendentes.observar(completo.clone(), inicio);
+        assert!(pendentes.prontos(inicio + Duration::from_secs(1), estabilidade).is_empty());
+
+        // O arquivo ainda sendo copiado cresce e reinicia a espera
+        fs::write(&copiando, b"%PDF-1.4 mais dados").unwrap();
+        let prontos = pendentes.prontos(inicio + Duration::from_secs(3), estabilidade);
+        assert_eq!(prontos, vec![completo]);
+
+        assert!(pendentes.prontos(inicio + Duration::from_secs(5), estabilidade).is_empty());
+        assert_eq!(pendentes.prontos(inicio + Duration::from_secs(6), estabilidade), vec![copiando.clone()]);
+        assert!(pendentes.is_empty());
+
+        // Arquivo removido antes de estabilizar sai da fila
+        fs::write(&copiando, b"%PDF").unwrap();
+        pendentes.observar(copiando.clone(), inicio);
+        fs::remove_file(&copiando).unwrap();
+        assert!(pendentes.prontos(inicio + Duration::from_secs(10), estabilidade).is_empty());
+        assert!(pendentes.is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}